@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS channel_activity_index;
+ALTER TABLE channels DROP COLUMN IF EXISTS last_activity_at;
\ No newline at end of file
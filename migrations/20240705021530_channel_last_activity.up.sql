@@ -0,0 +1,3 @@
+ALTER TABLE channels
+    ADD COLUMN IF NOT EXISTS last_activity_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP;
+CREATE INDEX IF NOT EXISTS channel_activity_index ON channels(guild_id, last_activity_at);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS message_content_search_index;
\ No newline at end of file
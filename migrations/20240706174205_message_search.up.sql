@@ -0,0 +1,2 @@
+CREATE INDEX IF NOT EXISTS message_content_search_index
+    ON messages USING GIN (to_tsvector('english', COALESCE(content, '')));
\ No newline at end of file
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS threads (
+    channel_id BIGINT NOT NULL PRIMARY KEY REFERENCES channels(id) ON DELETE CASCADE,
+    creator_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    parent_message_id BIGINT REFERENCES messages(id) ON DELETE SET NULL,
+    auto_archive_duration INTEGER NOT NULL DEFAULT 1440,
+    archived_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE TABLE IF NOT EXISTS thread_members (
+    thread_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    joined_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (thread_id, user_id)
+);
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS thread_applied_tags_index;
+ALTER TABLE threads DROP COLUMN IF EXISTS applied_tags;
+DROP TABLE IF EXISTS forum_tags;
+ALTER TABLE channels DROP COLUMN IF EXISTS forum_sort_order;
\ No newline at end of file
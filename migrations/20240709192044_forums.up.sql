@@ -0,0 +1,14 @@
+ALTER TABLE channels ADD COLUMN IF NOT EXISTS forum_sort_order TEXT;
+
+CREATE TABLE IF NOT EXISTS forum_tags (
+    id BIGINT NOT NULL PRIMARY KEY,
+    channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    emoji_id BIGINT,
+    emoji_name TEXT,
+    moderated BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+ALTER TABLE threads ADD COLUMN IF NOT EXISTS applied_tags BIGINT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS thread_applied_tags_index ON threads USING GIN (applied_tags);
\ No newline at end of file
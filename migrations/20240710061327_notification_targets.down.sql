@@ -0,0 +1,3 @@
+ALTER TABLE notification_settings DROP CONSTRAINT IF EXISTS notification_settings_pkey;
+ALTER TABLE notification_settings DROP COLUMN IF EXISTS target_type;
+ALTER TABLE notification_settings ADD PRIMARY KEY (user_id, target_id);
\ No newline at end of file
@@ -0,0 +1,16 @@
+ALTER TABLE notification_settings ADD COLUMN IF NOT EXISTS target_type TEXT NOT NULL DEFAULT 'channel';
+
+UPDATE notification_settings SET target_type = 'guild'
+WHERE target_id IN (SELECT id FROM guilds);
+
+UPDATE notification_settings SET target_type = 'category'
+WHERE target_id IN (SELECT id FROM channels WHERE type = 'category');
+
+UPDATE notification_settings SET target_type = 'user'
+WHERE target_id IN (SELECT id FROM users);
+
+ALTER TABLE notification_settings ALTER COLUMN target_type DROP DEFAULT;
+
+ALTER TABLE notification_settings
+    DROP CONSTRAINT IF EXISTS notification_settings_pkey,
+    ADD PRIMARY KEY (user_id, target_type, target_id);
\ No newline at end of file
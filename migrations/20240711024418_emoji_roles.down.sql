@@ -0,0 +1 @@
+DROP TABLE IF EXISTS emoji_roles;
\ No newline at end of file
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS emoji_roles (
+    emoji_id BIGINT NOT NULL REFERENCES emojis(id) ON DELETE CASCADE,
+    role_id BIGINT NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    PRIMARY KEY (emoji_id, role_id)
+);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS emoji_usage;
\ No newline at end of file
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS emoji_usage (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    emoji_id BIGINT NOT NULL REFERENCES emojis(id) ON DELETE CASCADE,
+    uses INTEGER NOT NULL DEFAULT 0,
+    last_used_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, emoji_id)
+);
+
+CREATE INDEX IF NOT EXISTS emoji_usage_emoji_id_idx ON emoji_usage (emoji_id);
\ No newline at end of file
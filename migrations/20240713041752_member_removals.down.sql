@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS member_removals;
+DROP TABLE IF EXISTS bans;
\ No newline at end of file
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS bans (
+    guild_id BIGINT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    moderator_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    reason TEXT,
+    banned_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS member_removals (
+    guild_id BIGINT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    moderator_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    kind TEXT NOT NULL,
+    reason TEXT,
+    removed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS member_removals_guild_id_idx ON member_removals (guild_id, removed_at);
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE messages DROP COLUMN IF EXISTS sticker_ids;
+DROP TABLE IF EXISTS stickers;
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS stickers (
+    id BIGINT NOT NULL PRIMARY KEY,
+    guild_id BIGINT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    name VARCHAR(256) NOT NULL,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    format TEXT NOT NULL,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS stickers_guild_id_idx ON stickers (guild_id);
+
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS sticker_ids BIGINT[] NOT NULL DEFAULT '{}';
\ No newline at end of file
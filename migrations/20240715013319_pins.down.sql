@@ -0,0 +1,2 @@
+ALTER TABLE channels DROP COLUMN IF EXISTS pin_limit;
+DROP TABLE IF EXISTS pins;
\ No newline at end of file
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS pins (
+    channel_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    message_id BIGINT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    pinned_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    pinned_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    position SMALLINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (channel_id, message_id)
+);
+
+INSERT INTO pins (channel_id, message_id, position)
+SELECT
+    channel_id,
+    id,
+    (ROW_NUMBER() OVER (PARTITION BY channel_id ORDER BY id DESC) - 1)::SMALLINT
+FROM messages
+WHERE flags & 1 = 1
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE channels ADD COLUMN IF NOT EXISTS pin_limit SMALLINT;
\ No newline at end of file
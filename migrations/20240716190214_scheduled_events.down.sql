@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS scheduled_event_users;
+DROP TABLE IF EXISTS scheduled_events;
\ No newline at end of file
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS scheduled_events (
+    id BIGINT NOT NULL PRIMARY KEY,
+    guild_id BIGINT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    creator_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    channel_id BIGINT REFERENCES channels(id) ON DELETE CASCADE,
+    external_location TEXT,
+    start_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    end_time TIMESTAMP WITH TIME ZONE,
+    status TEXT NOT NULL DEFAULT 'scheduled',
+    CHECK (channel_id IS NOT NULL OR external_location IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS scheduled_events_guild_id_idx ON scheduled_events (guild_id, start_time);
+
+CREATE TABLE IF NOT EXISTS scheduled_event_users (
+    event_id BIGINT NOT NULL REFERENCES scheduled_events(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (event_id, user_id)
+);
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS starboard_entries;
+DROP TABLE IF EXISTS starboards;
+ALTER TABLE messages DROP COLUMN IF EXISTS stars;
+DROP TABLE IF EXISTS stars;
\ No newline at end of file
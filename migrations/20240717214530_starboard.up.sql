@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS stars (
+    message_id BIGINT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    guild_id BIGINT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (message_id, user_id)
+);
+
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS stars INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS starboards (
+    guild_id BIGINT NOT NULL PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    channel_id BIGINT REFERENCES channels(id) ON DELETE SET NULL,
+    threshold SMALLINT NOT NULL DEFAULT 3
+);
+
+CREATE TABLE IF NOT EXISTS starboard_entries (
+    message_id BIGINT NOT NULL PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    guild_id BIGINT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    starboard_message_id BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE members DROP COLUMN IF EXISTS rules_accepted_at;
\ No newline at end of file
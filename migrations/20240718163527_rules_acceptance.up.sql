@@ -0,0 +1 @@
+ALTER TABLE members ADD COLUMN IF NOT EXISTS rules_accepted_at TIMESTAMP WITH TIME ZONE;
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS message_revisions;
\ No newline at end of file
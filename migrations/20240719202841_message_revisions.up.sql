@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS message_revisions (
+    message_id BIGINT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    revision_id INTEGER NOT NULL,
+    content TEXT,
+    embeds JSONB NOT NULL DEFAULT '[]'::JSONB,
+    edited_at TIMESTAMP WITH TIME ZONE,
+    replaced_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (message_id, revision_id)
+);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS guild_widgets;
\ No newline at end of file
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS guild_widgets (
+    guild_id BIGINT NOT NULL PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    channel_id BIGINT REFERENCES channels(id) ON DELETE SET NULL
+);
\ No newline at end of file
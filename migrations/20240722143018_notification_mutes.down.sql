@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS notification_settings_mute_until_idx;
+ALTER TABLE notification_settings DROP COLUMN IF EXISTS mute_until;
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE notification_settings ADD COLUMN IF NOT EXISTS mute_until TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS notification_settings_mute_until_idx
+    ON notification_settings (mute_until) WHERE mute_until IS NOT NULL;
\ No newline at end of file
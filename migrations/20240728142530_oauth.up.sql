@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS oauth_applications (
+    bot_id BIGINT NOT NULL PRIMARY KEY REFERENCES bots(user_id) ON DELETE CASCADE,
+    client_secret_hash TEXT NOT NULL,
+    redirect_uris TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[],
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS oauth_authorization_codes (
+    code_hash TEXT NOT NULL PRIMARY KEY,
+    application_id BIGINT NOT NULL REFERENCES oauth_applications(bot_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    redirect_uri TEXT NOT NULL,
+    scopes BIGINT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS oauth_tokens (
+    access_token_hash TEXT NOT NULL PRIMARY KEY,
+    refresh_token_hash TEXT UNIQUE,
+    application_id BIGINT NOT NULL REFERENCES oauth_applications(bot_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    scopes BIGINT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+CREATE INDEX IF NOT EXISTS oauth_tokens_user_id_idx ON oauth_tokens (user_id);
\ No newline at end of file
@@ -0,0 +1,24 @@
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS created_at TIMESTAMP WITH TIME ZONE;
+
+-- The second section of every token is the base64url-encoded creation timestamp in milliseconds
+-- since the Adapt epoch (2022-12-25T00:00:00Z)
+UPDATE tokens SET created_at = to_timestamp(
+    (
+        convert_from(
+            decode(
+                rpad(
+                    translate(split_part(token, '.', 2), '-_', '+/'),
+                    (length(split_part(token, '.', 2)) + 3) / 4 * 4,
+                    '='
+                ),
+                'base64'
+            ),
+            'UTF8'
+        )::BIGINT + 1671926400000
+    ) / 1000.0
+)
+WHERE created_at IS NULL;
+
+ALTER TABLE tokens
+    ALTER COLUMN created_at SET DEFAULT NOW(),
+    ALTER COLUMN created_at SET NOT NULL;
\ No newline at end of file
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS guilds_vanity_url_idx;
\ No newline at end of file
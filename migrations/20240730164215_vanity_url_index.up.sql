@@ -0,0 +1 @@
+CREATE UNIQUE INDEX IF NOT EXISTS guilds_vanity_url_idx ON guilds (LOWER(vanity_url)) WHERE vanity_url IS NOT NULL;
\ No newline at end of file
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS discovery_upvotes;
+DROP TABLE IF EXISTS discovery_entries;
\ No newline at end of file
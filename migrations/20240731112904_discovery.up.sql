@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS discovery_entries (
+    guild_id BIGINT NOT NULL PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL DEFAULT 1,
+    description TEXT NOT NULL,
+    category TEXT NOT NULL,
+    keywords TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[],
+    upvotes INTEGER NOT NULL DEFAULT 0,
+    uses INTEGER NOT NULL DEFAULT 0,
+    published_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS discovery_entries_category_idx ON discovery_entries (LOWER(category));
+CREATE INDEX IF NOT EXISTS discovery_entries_popularity_idx ON discovery_entries (upvotes DESC, uses DESC);
+
+CREATE TABLE IF NOT EXISTS discovery_upvotes (
+    guild_id BIGINT NOT NULL REFERENCES discovery_entries(guild_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (guild_id, user_id)
+);
\ No newline at end of file
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS marketplace_installs;
+DROP TABLE IF EXISTS marketplace_releases;
+DROP TABLE IF EXISTS marketplace_entries;
\ No newline at end of file
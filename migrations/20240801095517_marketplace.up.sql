@@ -0,0 +1,30 @@
+CREATE TABLE IF NOT EXISTS marketplace_entries (
+    id BIGINT NOT NULL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    author_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    latest_version TEXT,
+    installs INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS marketplace_entries_author_idx ON marketplace_entries (author_id);
+
+CREATE TABLE IF NOT EXISTS marketplace_releases (
+    entry_id BIGINT NOT NULL REFERENCES marketplace_entries(id) ON DELETE CASCADE,
+    version TEXT NOT NULL,
+    changelog TEXT,
+    content TEXT NOT NULL,
+    published_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (entry_id, version)
+);
+
+CREATE TABLE IF NOT EXISTS marketplace_installs (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    entry_id BIGINT NOT NULL REFERENCES marketplace_entries(id) ON DELETE CASCADE,
+    version TEXT,
+    installed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, entry_id),
+    FOREIGN KEY (entry_id, version) REFERENCES marketplace_releases(entry_id, version)
+);
\ No newline at end of file
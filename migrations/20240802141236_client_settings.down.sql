@@ -0,0 +1 @@
+DROP TABLE IF EXISTS client_settings;
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS client_settings (
+    user_id BIGINT NOT NULL PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    dm_channel_order BIGINT[] NOT NULL DEFAULT ARRAY[]::BIGINT[],
+    theme BIGINT REFERENCES marketplace_entries(id) ON DELETE SET NULL,
+    plugins BIGINT[] NOT NULL DEFAULT ARRAY[]::BIGINT[],
+    locale TEXT
+);
\ No newline at end of file
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS guild_folder_entries;
+DROP TABLE IF EXISTS guild_folders;
\ No newline at end of file
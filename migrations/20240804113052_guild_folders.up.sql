@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS guild_folders (
+    id BIGINT NOT NULL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    parent_id BIGINT REFERENCES guild_folders(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    color INTEGER NOT NULL,
+    position SMALLINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS guild_folders_user_id_idx ON guild_folders (user_id);
+
+CREATE TABLE IF NOT EXISTS guild_folder_entries (
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    folder_id BIGINT REFERENCES guild_folders(id) ON DELETE SET NULL,
+    position SMALLINT NOT NULL,
+    PRIMARY KEY (user_id, guild_id),
+    FOREIGN KEY (user_id, guild_id) REFERENCES members(id, guild_id) ON DELETE CASCADE
+);
\ No newline at end of file
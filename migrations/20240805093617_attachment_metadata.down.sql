@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS attachments_hash_idx;
+
+ALTER TABLE attachments
+    DROP COLUMN IF EXISTS content_type,
+    DROP COLUMN IF EXISTS width,
+    DROP COLUMN IF EXISTS height,
+    DROP COLUMN IF EXISTS hash;
\ No newline at end of file
@@ -0,0 +1,7 @@
+ALTER TABLE attachments
+    ADD COLUMN IF NOT EXISTS content_type TEXT,
+    ADD COLUMN IF NOT EXISTS width INTEGER,
+    ADD COLUMN IF NOT EXISTS height INTEGER,
+    ADD COLUMN IF NOT EXISTS hash TEXT;
+
+CREATE INDEX IF NOT EXISTS attachments_hash_idx ON attachments (hash) WHERE hash IS NOT NULL;
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS reactions_message_emoji_idx;
+
+ALTER TABLE reactions
+    DROP COLUMN IF EXISTS burst;
\ No newline at end of file
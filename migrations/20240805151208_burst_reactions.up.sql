@@ -0,0 +1,4 @@
+ALTER TABLE reactions
+    ADD COLUMN IF NOT EXISTS burst BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS reactions_message_emoji_idx ON reactions (message_id, emoji_name, user_id);
\ No newline at end of file
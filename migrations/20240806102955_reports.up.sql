@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS reports (
+    id BIGINT NOT NULL PRIMARY KEY,
+    reporter_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    guild_id BIGINT REFERENCES guilds(id) ON DELETE CASCADE,
+    channel_id BIGINT,
+    message_id BIGINT,
+    target_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    details TEXT,
+    status TEXT NOT NULL DEFAULT 'open',
+    resolved_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    resolution_note TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS reports_guild_idx ON reports (guild_id, status, id DESC);
+CREATE INDEX IF NOT EXISTS reports_status_idx ON reports (status, id DESC);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS automod_rules;
\ No newline at end of file
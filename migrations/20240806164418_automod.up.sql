@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS automod_rules (
+    id BIGINT NOT NULL PRIMARY KEY,
+    guild_id BIGINT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    trigger JSONB NOT NULL,
+    actions JSONB NOT NULL,
+    exempt_channels BIGINT[] NOT NULL DEFAULT '{}',
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS automod_rules_guild_idx ON automod_rules (guild_id);
\ No newline at end of file
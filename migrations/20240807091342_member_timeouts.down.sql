@@ -0,0 +1,2 @@
+ALTER TABLE members
+    DROP COLUMN IF EXISTS communication_disabled_until;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE members
+    ADD COLUMN IF NOT EXISTS communication_disabled_until TIMESTAMP WITH TIME ZONE;
\ No newline at end of file
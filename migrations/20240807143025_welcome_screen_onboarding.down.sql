@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS guild_onboarding;
+DROP TABLE IF EXISTS guild_welcome_screens;
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS guild_welcome_screens (
+    guild_id BIGINT NOT NULL PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    description TEXT,
+    channels JSONB NOT NULL DEFAULT '[]'
+);
+
+CREATE TABLE IF NOT EXISTS guild_onboarding (
+    guild_id BIGINT NOT NULL PRIMARY KEY REFERENCES guilds(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    prompts JSONB NOT NULL DEFAULT '[]'
+);
\ No newline at end of file
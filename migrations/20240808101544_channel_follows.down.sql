@@ -0,0 +1 @@
+DROP TABLE IF EXISTS channel_follows;
\ No newline at end of file
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS channel_follows (
+    source_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    target_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    guild_id BIGINT NOT NULL REFERENCES guilds(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source_id, target_id)
+);
+
+CREATE INDEX IF NOT EXISTS channel_follows_target_idx ON channel_follows (target_id);
\ No newline at end of file
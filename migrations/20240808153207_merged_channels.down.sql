@@ -0,0 +1 @@
+DROP TABLE IF EXISTS merged_channel_sources;
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS merged_channel_sources (
+    merged_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    source_id BIGINT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    PRIMARY KEY (merged_id, source_id)
+);
+
+CREATE INDEX IF NOT EXISTS merged_channel_sources_source_idx ON merged_channel_sources (source_id);
\ No newline at end of file
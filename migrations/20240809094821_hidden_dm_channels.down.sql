@@ -0,0 +1 @@
+ALTER TABLE channel_recipients DROP COLUMN IF EXISTS hidden;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE channel_recipients ADD COLUMN IF NOT EXISTS hidden BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
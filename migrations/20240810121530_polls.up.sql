@@ -0,0 +1,26 @@
+CREATE TABLE IF NOT EXISTS polls (
+    message_id BIGINT NOT NULL PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    question TEXT NOT NULL,
+    multi_select BOOLEAN NOT NULL DEFAULT FALSE,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    finalized BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE IF NOT EXISTS poll_answers (
+    message_id BIGINT NOT NULL REFERENCES polls(message_id) ON DELETE CASCADE,
+    answer_id SMALLINT NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (message_id, answer_id)
+);
+
+CREATE TABLE IF NOT EXISTS poll_votes (
+    message_id BIGINT NOT NULL,
+    answer_id SMALLINT NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (message_id, answer_id, user_id),
+    FOREIGN KEY (message_id, answer_id) REFERENCES poll_answers(message_id, answer_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS poll_votes_user_idx ON poll_votes (message_id, user_id);
+CREATE INDEX IF NOT EXISTS polls_expiry_idx ON polls (expires_at) WHERE NOT finalized AND expires_at IS NOT NULL;
\ No newline at end of file
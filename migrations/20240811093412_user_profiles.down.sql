@@ -0,0 +1,3 @@
+ALTER TABLE users
+    DROP COLUMN IF EXISTS pronouns,
+    DROP COLUMN IF EXISTS accent_color;
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS pronouns TEXT,
+    ADD COLUMN IF NOT EXISTS accent_color INTEGER;
\ No newline at end of file
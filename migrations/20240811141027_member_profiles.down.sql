@@ -0,0 +1,4 @@
+ALTER TABLE members
+    DROP COLUMN IF EXISTS guild_avatar,
+    DROP COLUMN IF EXISTS guild_banner,
+    DROP COLUMN IF EXISTS guild_bio;
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE members
+    ADD COLUMN IF NOT EXISTS guild_avatar TEXT,
+    ADD COLUMN IF NOT EXISTS guild_banner TEXT,
+    ADD COLUMN IF NOT EXISTS guild_bio TEXT;
\ No newline at end of file
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS notes (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    target_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    note TEXT NOT NULL,
+    PRIMARY KEY (user_id, target_id)
+);
\ No newline at end of file
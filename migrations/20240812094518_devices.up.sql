@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS devices (
+    session_id BIGINT NOT NULL PRIMARY KEY REFERENCES sessions(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    device TEXT NOT NULL,
+    last_seen_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS devices_user_id_idx ON devices (user_id);
\ No newline at end of file
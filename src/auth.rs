@@ -12,6 +12,9 @@ use base64::{
 use std::sync::OnceLock;
 use std::time::{Duration, UNIX_EPOCH};
 
+#[cfg(feature = "auth")]
+pub mod oauth;
+
 #[cfg(feature = "auth")]
 pub use argon2_async::{hash as hash_password, verify as verify_password};
 #[cfg(feature = "auth")]
@@ -77,18 +80,223 @@ pub fn generate_token(user_id: u64) -> String {
     token
 }
 
+/// Generates a new token for the given user ID that is associated with the session with the given
+/// ID.
+///
+/// # Token Format
+/// ```text
+/// MzkxMTM0MzUxMjc4MDg.MTg0NjAzMTg2.NDgzOTEyNzM2NDA5NjAwMA.khHChSMQuhJ8hqj3QVp1HZjqjVlBRbXuxdsh7ri7FHU
+/// ^ User ID           ^ Timestamp  ^ Session ID            ^ Random bytes
+/// ```
+///
+/// This is the same as the format described in [`generate_token`], with an additional section
+/// containing the session ID, cast as a string, and then encoded using base64. Tokens without a
+/// session section are still valid and are treated as legacy tokens without a session.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn generate_session_token(user_id: u64, session_id: u64) -> String {
+    let token = generate_token(user_id);
+    let (prefix, random) = token.rsplit_once('.').expect("token has a random section");
+
+    format!(
+        "{prefix}.{}.{random}",
+        ENGINE.encode(session_id.to_string().as_bytes())
+    )
+}
+
+/// The number of digits in a TOTP code.
+pub const TOTP_DIGITS: u32 = 6;
+/// The number of seconds each TOTP code is valid for.
+pub const TOTP_PERIOD: u64 = 30;
+/// The number of periods before and after the current one that are also accepted, to account for
+/// clock drift between the server and the authenticator.
+pub const TOTP_SKEW: u64 = 1;
+/// The number of recovery codes generated when enrolling in two-factor authentication.
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+#[cfg(feature = "auth")]
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encodes the given bytes using unpadded RFC 4648 base32, as expected by authenticator apps.
+#[must_use]
+#[cfg(feature = "auth")]
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut buffer, mut bits) = (0_u32, 0_u32);
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    out
+}
+
+/// Decodes unpadded RFC 4648 base32, ignoring case, whitespace, and padding. Returns ``None`` if
+/// the input contains invalid characters.
+#[must_use]
+#[cfg(feature = "auth")]
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0_u32, 0_u32);
+
+    for c in encoded
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a == c.to_ascii_uppercase())?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// Generates a new random TOTP secret, encoded in base32.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn generate_totp_secret() -> String {
+    let dest = &mut [0_u8; 20];
+    get_system_rng().fill(dest).expect("could not fill bytes");
+
+    base32_encode(dest)
+}
+
+/// Returns the ``otpauth://`` URI for the given base32-encoded secret, which can be encoded as a
+/// QR code to be scanned by authenticator apps.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn totp_uri(secret: &str, username: &str) -> String {
+    format!(
+        "otpauth://totp/Adapt:{username}?secret={secret}&issuer=Adapt&digits={TOTP_DIGITS}&period={TOTP_PERIOD}"
+    )
+}
+
+/// Generates the TOTP code for the given raw secret and time step, as described in RFC 6238.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn totp_code(secret: &[u8], step: u64) -> u32 {
+    use ring::hmac;
+
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let digest = hmac::sign(&key, &step.to_be_bytes());
+    let digest = digest.as_ref();
+
+    let offset = (digest[digest.len() - 1] & 0xf) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10_u32.pow(TOTP_DIGITS)
+}
+
+/// Verifies the given TOTP code against the base32-encoded secret at the given Unix timestamp in
+/// seconds, accepting codes up to [`TOTP_SKEW`] periods away.
+///
+/// Returns the time step the code is valid for, which should be stored to prevent the same code
+/// from being used twice, or ``None`` if the code is invalid.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn verify_totp_code(secret: &str, code: &str, unix_secs: u64) -> Option<u64> {
+    let secret = base32_decode(secret)?;
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+
+    let current = unix_secs / TOTP_PERIOD;
+    (current.saturating_sub(TOTP_SKEW)..=current + TOTP_SKEW)
+        .find(|&step| totp_code(&secret, step) == code)
+}
+
+/// Normalizes a recovery code by removing separators and lowercasing it, so that codes are
+/// accepted regardless of how they are typed.
+#[must_use]
+#[cfg(feature = "auth")]
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Generates [`RECOVERY_CODE_COUNT`] new single-use recovery codes, in the form ``xxxxx-xxxxx``.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let dest = &mut [0_u8; 7];
+            get_system_rng().fill(dest).expect("could not fill bytes");
+
+            let code = base32_encode(dest).to_ascii_lowercase();
+            format!("{}-{}", &code[..5], &code[5..10])
+        })
+        .collect()
+}
+
+/// Hashes a recovery code for storage. Recovery codes are random and high-entropy, so a fast
+/// hash is sufficient.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn hash_recovery_code(code: &str) -> String {
+    let digest = ring::digest::digest(
+        &ring::digest::SHA256,
+        normalize_recovery_code(code).as_bytes(),
+    );
+    ENGINE.encode(digest.as_ref())
+}
+
+/// Generates a new random single-use token, such as an email verification or password reset
+/// token, sent to the user in a link.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn generate_single_use_token() -> String {
+    let dest = &mut [0_u8; 32];
+    get_system_rng().fill(dest).expect("could not fill bytes");
+
+    ENGINE.encode(dest)
+}
+
+/// Hashes a single-use token for storage, so that leaked database rows cannot be used in place
+/// of the token.
+#[must_use]
+#[cfg(feature = "auth")]
+pub fn hash_single_use_token(token: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, token.trim().as_bytes());
+    ENGINE.encode(digest.as_ref())
+}
+
 /// Reads information from a token.
 #[derive(Copy, Clone)]
-pub struct TokenReader<'a>(&'a str, &'a str);
+pub struct TokenReader<'a>(&'a str, &'a str, Option<&'a str>);
 
 impl<'a> TokenReader<'a> {
     /// Creates a new token reader. Returns ``None`` if the token is invalid.
     #[inline]
     #[must_use]
     pub fn new(token: &'a str) -> Option<Self> {
-        let mut split = token.splitn(3, '.');
+        let mut split = token.splitn(4, '.');
+        let (user_id, timestamp, third) = (split.next()?, split.next()?, split.next()?);
+        // The random section never contains a period, so a fourth section means the third one is
+        // the session ID
+        let session_id = split.next().map(|_| third);
 
-        Some(Self(split.next()?, split.next()?))
+        Some(Self(user_id, timestamp, session_id))
     }
 
     /// Returns the user ID from the token. Returns ``None`` if the token is invalid.
@@ -102,6 +310,18 @@ impl<'a> TokenReader<'a> {
             .and_then(|s| s.parse().ok())
     }
 
+    /// Returns the session ID from the token. Returns ``None`` if the token is invalid or if it
+    /// is a legacy token that is not associated with a session.
+    #[inline]
+    #[must_use]
+    pub fn session_id(&self) -> Option<u64> {
+        ENGINE
+            .decode(self.2?)
+            .ok()
+            .and_then(|b| String::from_utf8(b).ok())
+            .and_then(|s| s.parse().ok())
+    }
+
     /// Returns the timestamp from the token as a Unix timestamp in milliseconds.
     #[inline]
     #[must_use]
@@ -149,5 +369,51 @@ mod tests {
 
         assert_eq!(reader.user_id(), Some(39_113_435_127_808));
         assert_eq!(reader.timestamp_millis(), Some(184_603_186));
+        assert_eq!(reader.session_id(), None);
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        let secret = b"12345678901234567890";
+
+        assert_eq!(totp_code(secret, 59 / TOTP_PERIOD), 287_082);
+        assert_eq!(totp_code(secret, 1_111_111_109 / TOTP_PERIOD), 81_804);
+        assert_eq!(totp_code(secret, 1_234_567_890 / TOTP_PERIOD), 5_924);
+    }
+
+    #[test]
+    fn test_verify_totp_code() {
+        let secret = base32_encode(b"12345678901234567890");
+        assert_eq!(secret, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+
+        // Codes from the previous and next periods are accepted
+        assert_eq!(
+            verify_totp_code(&secret, "081804", 1_111_111_109),
+            Some(37_037_036)
+        );
+        assert!(verify_totp_code(&secret, "081804", 1_111_111_109 + TOTP_PERIOD).is_some());
+        assert!(verify_totp_code(&secret, "081804", 1_111_111_109 + TOTP_PERIOD * 3).is_none());
+        assert!(verify_totp_code(&secret, "81804", 1_111_111_109).is_none());
+    }
+
+    #[test]
+    fn test_recovery_codes() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert_eq!(
+            hash_recovery_code(&codes[0]),
+            hash_recovery_code(&codes[0].replace('-', "").to_uppercase())
+        );
+    }
+
+    #[test]
+    fn test_session_token() {
+        let token = generate_session_token(39_113_435_127_808, 4_839_127_364_096_000);
+        let reader = TokenReader::new(&token).unwrap();
+
+        assert_eq!(token.split('.').count(), 4);
+        assert_eq!(reader.user_id(), Some(39_113_435_127_808));
+        assert_eq!(reader.session_id(), Some(4_839_127_364_096_000));
+        assert!(reader.timestamp_millis().is_some());
     }
 }
@@ -0,0 +1,41 @@
+//! OAuth2 authorization for bots.
+//!
+//! Applications are connected to bots and identified by the ID of their bot. Two grants are
+//! supported:
+//!
+//! * The authorization code grant, where a user authorizes the application to act on their
+//!   behalf with a set of [`OAuthScopes`](crate::models::OAuthScopes). The application exchanges
+//!   the short-lived code for an access token and a refresh token.
+//! * The client credentials grant, where the application requests an access token on behalf of
+//!   the owner of its bot, i.e. for testing. No refresh token is issued.
+//!
+//! Client secrets, authorization codes, and tokens are all random and only stored hashed, see
+//! [`hash_single_use_token`](super::hash_single_use_token).
+
+use super::generate_single_use_token;
+
+/// The number of seconds an authorization code is valid for before it must be exchanged.
+pub const AUTHORIZATION_CODE_TTL_SECS: u32 = 600;
+/// The number of seconds an access token is valid for before it must be refreshed.
+pub const ACCESS_TOKEN_TTL_SECS: u32 = 604_800;
+
+/// Generates a new client secret for an application.
+#[inline]
+#[must_use]
+pub fn generate_client_secret() -> String {
+    generate_single_use_token()
+}
+
+/// Generates a new authorization code.
+#[inline]
+#[must_use]
+pub fn generate_authorization_code() -> String {
+    generate_single_use_token()
+}
+
+/// Generates a new pair of access and refresh tokens, returned as ``(access, refresh)``.
+#[inline]
+#[must_use]
+pub fn generate_token_pair() -> (String, String) {
+    (generate_single_use_token(), generate_single_use_token())
+}
@@ -0,0 +1,33 @@
+use super::get_con;
+use crate::{
+    bincode_impl::BincodeType,
+    error::{ErrIntoExt, Result},
+};
+use deadpool_redis::redis::AsyncCommands;
+
+const KEY: &str = "essence-blocked";
+
+/// Fetches the cached IDs of the users blocked by the given user, or `None` if they are not
+/// cached.
+pub async fn blocked_ids(user_id: u64) -> Result<Option<Vec<u64>>> {
+    Ok(get_con()
+        .await?
+        .hget::<_, _, Option<BincodeType<Vec<u64>>>>(KEY, user_id)
+        .await?
+        .map(|ids| ids.0))
+}
+
+/// Caches the IDs of the users blocked by the given user.
+pub async fn cache_blocked_ids(user_id: u64, blocked_ids: Vec<u64>) -> Result<()> {
+    get_con()
+        .await?
+        .hset(KEY, user_id, BincodeType(blocked_ids))
+        .await
+        .err_into()
+}
+
+/// Invalidates the cached IDs of the users blocked by the given users. This should be called
+/// whenever a relationship involving these users changes.
+pub async fn invalidate_blocked_ids(user_ids: &[u64]) -> Result<()> {
+    get_con().await?.hdel(KEY, user_ids).await.err_into()
+}
@@ -0,0 +1,75 @@
+use super::get_con;
+use crate::{bincode_impl::BincodeType, error::Result, models::Embed};
+use deadpool_redis::redis::{self, AsyncCommands};
+
+/// How long each resolved embed is cached for, in seconds.
+pub const EMBED_TTL_SECS: u32 = 3600;
+
+/// The number of keys scanned per ``SCAN`` call while invalidating a domain.
+const SCAN_COUNT: u32 = 500;
+
+/// The key of the cached embed of a URL. Hosts cannot contain ``/``, so the domain prefix of a key
+/// is unambiguous, which lets [`invalidate_domain`] match all keys of a domain.
+#[inline]
+fn key(domain: &str, url: &str) -> String {
+    format!("essence-embeds-{domain}/{url}")
+}
+
+/// Fetches the cached embed resolved from the given URL.
+///
+/// Returns `None` if the URL is not cached, or `Some(None)` if the URL was resolved but did not
+/// produce an embed.
+pub async fn fetch_embed(domain: &str, url: &str) -> Result<Option<Option<Embed>>> {
+    let embed = get_con()
+        .await?
+        .get::<_, Option<BincodeType<Option<Embed>>>>(key(domain, url))
+        .await?;
+
+    Ok(embed.map(|embed| embed.0))
+}
+
+/// Caches the embed resolved from the given URL, or `None` if the URL did not produce an embed.
+/// The embed expires after [`EMBED_TTL_SECS`] seconds.
+pub async fn cache_embed(domain: &str, url: &str, embed: Option<&Embed>) -> Result<()> {
+    get_con()
+        .await?
+        .set_ex::<_, _, ()>(key(domain, url), BincodeType(embed), EMBED_TTL_SECS as _)
+        .await?;
+
+    Ok(())
+}
+
+/// Removes all cached embeds of the given domain.
+pub async fn invalidate_domain(domain: &str) -> Result<()> {
+    // hosts may contain glob characters, e.g. the brackets of IPv6 addresses
+    let mut pattern = String::with_capacity(domain.len() + 18);
+    pattern.push_str("essence-embeds-");
+    for c in domain.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push_str("/*");
+
+    // SCAN instead of KEYS, so that the server is not blocked while the keyspace is searched
+    let mut con = get_con().await?;
+    let mut cursor = 0_u64;
+    loop {
+        let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(&pattern)
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query_async(&mut con)
+            .await?;
+        if !keys.is_empty() {
+            con.del::<_, ()>(keys).await?;
+        }
+        if next == 0 {
+            return Ok(());
+        }
+        cursor = next;
+    }
+}
@@ -0,0 +1,118 @@
+use super::{get_con, presence::online_count_cmd, AsRefThreadSafe};
+use crate::{
+    error::{ErrIntoExt, Result},
+    models::GuildMemberCount,
+};
+use deadpool_redis::redis;
+
+/// Adjusts the total count by ``ARGV[1]``, only if the counts of the guild are cached.
+const ADJUST_TOTAL_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return 0
+end
+redis.call('INCRBY', KEYS[1], ARGV[1])
+return 1
+";
+
+#[inline]
+fn total_key(guild_id: u64) -> String {
+    format!("essence-{guild_id}-member-count")
+}
+
+/// Caches the member counts of a guild from the IDs of all of its members, replacing any counts
+/// and members that were cached before. Returns the resulting counts.
+///
+/// Online counts are not stored separately: they are derived from the member cache of the guild
+/// and the presence cache, see [`online_count`](super::presence::online_count).
+pub async fn seed_guild_counts(
+    guild_id: u64,
+    member_ids: impl AsRefThreadSafe<[u64]>,
+) -> Result<GuildMemberCount> {
+    let member_ids = member_ids.as_ref();
+    let members_key = format!("essence-{guild_id}-members");
+
+    // the member set is cleared first, so that members who left are not counted as online
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .set(total_key(guild_id), member_ids.len())
+        .ignore()
+        .del(&members_key)
+        .ignore();
+    if !member_ids.is_empty() {
+        pipe.sadd(&members_key, member_ids).ignore();
+    }
+    pipe.add_command(online_count_cmd(guild_id));
+    let (online,): (u32,) = pipe.query_async(&mut get_con().await?).await?;
+
+    Ok(GuildMemberCount {
+        total: member_ids.len() as u32,
+        online: Some(online),
+    })
+}
+
+/// Fetches the cached member counts of a guild, or `None` if they are not cached.
+pub async fn guild_counts(guild_id: u64) -> Result<Option<GuildMemberCount>> {
+    let (total, online): (Option<u32>, u32) = redis::pipe()
+        .get(total_key(guild_id))
+        .add_command(online_count_cmd(guild_id))
+        .query_async(&mut get_con().await?)
+        .await?;
+
+    Ok(total.map(|total| GuildMemberCount {
+        total,
+        online: Some(online),
+    }))
+}
+
+/// Fetches the cached member counts of multiple guilds at once, in the same order as the given
+/// guild IDs. Guilds whose counts are not cached are `None`.
+pub async fn guild_counts_bulk(
+    guild_ids: impl AsRefThreadSafe<[u64]>,
+) -> Result<Vec<Option<GuildMemberCount>>> {
+    let guild_ids = guild_ids.as_ref();
+    if guild_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut pipe = redis::pipe();
+    for &guild_id in guild_ids {
+        pipe.get(total_key(guild_id))
+            .add_command(online_count_cmd(guild_id));
+    }
+    let counts: Vec<(Option<u32>, u32)> = pipe.query_async(&mut get_con().await?).await?;
+
+    Ok(counts
+        .into_iter()
+        .map(|(total, online)| {
+            total.map(|total| GuildMemberCount {
+                total,
+                online: Some(online),
+            })
+        })
+        .collect())
+}
+
+/// Updates the cached total count of a guild after a member joined it. This does nothing if the
+/// counts of the guild are not cached.
+///
+/// This should be called after the change is committed.
+pub async fn member_joined(guild_id: u64) -> Result<()> {
+    adjust_total(guild_id, 1).await
+}
+
+/// Updates the cached total count of a guild after a member left it. This does nothing if the
+/// counts of the guild are not cached.
+///
+/// This should be called after the change is committed.
+pub async fn member_left(guild_id: u64) -> Result<()> {
+    adjust_total(guild_id, -1).await
+}
+
+async fn adjust_total(guild_id: u64, delta: i32) -> Result<()> {
+    redis::Script::new(ADJUST_TOTAL_SCRIPT)
+        .key(total_key(guild_id))
+        .arg(delta)
+        .invoke_async::<_, ()>(&mut get_con().await?)
+        .await
+        .err_into()
+}
@@ -3,9 +3,23 @@ use crate::{
     error::{ErrIntoExt, Result},
     models::{ChannelType, Permissions, User, UserFlags},
 };
-use deadpool_redis::{redis::AsyncCommands, Config, Connection, Pool, Runtime};
+use deadpool_redis::{
+    redis::{self, AsyncCommands},
+    Config, Connection, Pool, Runtime,
+};
 use std::sync::OnceLock;
 
+pub mod blocked;
+pub mod embeds;
+pub mod guild_counts;
+pub mod nonce;
+pub mod observable;
+pub mod presence;
+pub mod slowmode;
+pub mod typing;
+pub mod verification;
+pub mod voice;
+
 static POOL: OnceLock<Pool> = OnceLock::new();
 
 pub trait AsRefThreadSafe<T: ?Sized> = AsRef<T> + Send + Sync;
@@ -288,6 +302,26 @@ pub async fn delete_permissions_for_user_in_channel(
         .err_into()
 }
 
+pub async fn delete_permissions_for_users_in_channel(
+    guild_id: u64,
+    user_ids: impl AsRefThreadSafe<[u64]>,
+    channel_id: u64,
+) -> Result<()> {
+    let user_ids = user_ids.as_ref();
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+
+    let mut pipe = redis::pipe();
+    for user_id in user_ids {
+        pipe.hdel(format!("essence-{guild_id}-{user_id}-perm"), channel_id)
+            .ignore();
+    }
+
+    let mut con = get_con().await?;
+    pipe.query_async(&mut con).await.err_into()
+}
+
 pub async fn clear_member_permissions(guild_id: u64) -> Result<()> {
     let mut con = get_con().await?;
     let keys = con
@@ -296,3 +330,141 @@ pub async fn clear_member_permissions(guild_id: u64) -> Result<()> {
 
     con.del(keys).await.err_into()
 }
+
+/// Adds the member to the cached member set of each of the given roles, skipping roles whose
+/// member set is not cached.
+const ADD_ROLE_MEMBER_SCRIPT: &str = r"
+for _, key in ipairs(KEYS) do
+    if redis.call('EXISTS', key) == 1 then
+        redis.call('SADD', key, ARGV[1])
+    end
+end
+";
+
+/// Every cached member set of a role contains this placeholder, since Redis removes empty sets
+/// and the set of a role without members would otherwise look as if it was not cached. User IDs
+/// are never ``0``.
+const ROLE_MEMBERS_SENTINEL: u64 = 0;
+
+#[inline]
+fn role_members_key(guild_id: u64, role_id: u64) -> String {
+    format!("essence-{guild_id}-role-{role_id}-members")
+}
+
+/// Caches the IDs of the members that have the given role, replacing any that were cached before.
+pub async fn cache_role_members(
+    guild_id: u64,
+    role_id: u64,
+    user_ids: impl AsRefThreadSafe<[u64]>,
+) -> Result<()> {
+    let user_ids = user_ids.as_ref();
+    let key = role_members_key(guild_id, role_id);
+
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .del(&key)
+        .ignore()
+        .sadd(&key, ROLE_MEMBERS_SENTINEL)
+        .ignore();
+    if !user_ids.is_empty() {
+        pipe.sadd(&key, user_ids).ignore();
+    }
+    pipe.query_async(&mut get_con().await?).await.err_into()
+}
+
+/// Records that the member was given the given roles. Roles whose members are not cached are
+/// skipped, since they are resolved from the database when needed.
+pub async fn add_member_to_roles(
+    guild_id: u64,
+    user_id: u64,
+    role_ids: impl AsRefThreadSafe<[u64]>,
+) -> Result<()> {
+    let role_ids = role_ids.as_ref();
+    if role_ids.is_empty() {
+        return Ok(());
+    }
+
+    let script = redis::Script::new(ADD_ROLE_MEMBER_SCRIPT);
+    let mut invocation = script.prepare_invoke();
+    for &role_id in role_ids {
+        invocation.key(role_members_key(guild_id, role_id));
+    }
+    invocation
+        .arg(user_id)
+        .invoke_async(&mut get_con().await?)
+        .await
+        .err_into()
+}
+
+/// Records that the given roles were taken from the member.
+pub async fn remove_member_from_roles(
+    guild_id: u64,
+    user_id: u64,
+    role_ids: impl AsRefThreadSafe<[u64]>,
+) -> Result<()> {
+    let role_ids = role_ids.as_ref();
+    if role_ids.is_empty() {
+        return Ok(());
+    }
+
+    let mut pipe = redis::pipe();
+    for &role_id in role_ids {
+        pipe.srem(role_members_key(guild_id, role_id), user_id)
+            .ignore();
+    }
+    pipe.query_async(&mut get_con().await?).await.err_into()
+}
+
+/// Removes the member from the cached member sets of all roles in the guild.
+pub async fn remove_member_from_all_roles(guild_id: u64, user_id: u64) -> Result<()> {
+    let mut con = get_con().await?;
+    let keys = con
+        .keys::<_, Vec<String>>(format!("essence-{guild_id}-role-*-members"))
+        .await?;
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.srem(key, user_id).ignore();
+    }
+    pipe.query_async(&mut con).await.err_into()
+}
+
+/// Removes the cached member set of the given role, e.g. after it was deleted.
+pub async fn remove_role_members(guild_id: u64, role_id: u64) -> Result<()> {
+    get_con()
+        .await?
+        .del(role_members_key(guild_id, role_id))
+        .await
+        .err_into()
+}
+
+/// Clears the cached permissions of every member that has the given role, in every channel.
+///
+/// Returns `false` without clearing anything if the members of the role are not cached, in which
+/// case they should be cached with [`cache_role_members`] and this should be called again. A role
+/// that is cached without any members returns `true`.
+pub async fn invalidate_role_members(guild_id: u64, role_id: u64) -> Result<bool> {
+    let mut con = get_con().await?;
+    let mut user_ids = con
+        .smembers::<_, Vec<u64>>(role_members_key(guild_id, role_id))
+        .await?;
+    // a cached set always contains the sentinel, even if the role has no members
+    if user_ids.is_empty() {
+        return Ok(false);
+    }
+    user_ids.retain(|&user_id| user_id != ROLE_MEMBERS_SENTINEL);
+    if user_ids.is_empty() {
+        return Ok(true);
+    }
+
+    let mut pipe = redis::pipe();
+    for user_id in user_ids {
+        pipe.del(format!("essence-{guild_id}-{user_id}-perm"))
+            .ignore();
+    }
+    pipe.query_async::<_, ()>(&mut con).await?;
+    Ok(true)
+}
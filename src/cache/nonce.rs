@@ -0,0 +1,81 @@
+use super::get_con;
+use crate::error::{Error, Result};
+use deadpool_redis::redis::{self, AsyncCommands};
+
+/// How long a claimed nonce is remembered, in seconds.
+pub const NONCE_TTL_SECS: u32 = 300;
+
+/// The maximum length of a nonce, in bytes.
+pub const MAX_NONCE_LENGTH: usize = 64;
+
+/// The value stored for a nonce that was claimed but not yet bound to an ID.
+const UNBOUND: u64 = 0;
+
+#[inline]
+fn key(user_id: u64, nonce: &str) -> String {
+    format!("essence-{user_id}-nonce-{nonce}")
+}
+
+fn validate_nonce(nonce: &str) -> Result<()> {
+    if nonce.is_empty() || nonce.len() > MAX_NONCE_LENGTH {
+        return Err(Error::InvalidField {
+            field: "nonce".to_string(),
+            message: format!("Nonce must be between 1 and {MAX_NONCE_LENGTH} bytes long"),
+        });
+    }
+    Ok(())
+}
+
+/// Atomically claims the nonce for the user for [`NONCE_TTL_SECS`] seconds.
+///
+/// Returns `true` if the nonce was claimed, or `false` if it was already claimed by an earlier
+/// request, in which case the request should be treated as a retry. Once the model is created,
+/// its ID (or an [`Internal`](crate::models::ModelType::Internal) snowflake identifying the
+/// request) should be bound to the nonce with [`bind_nonce`], so that retries can be answered
+/// with [`fetch_nonce`] instead of creating a duplicate.
+///
+/// # Errors
+/// * If the nonce is empty or longer than [`MAX_NONCE_LENGTH`] bytes.
+pub async fn claim_nonce(user_id: u64, nonce: &str) -> Result<bool> {
+    validate_nonce(nonce)?;
+
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(key(user_id, nonce))
+        .arg(UNBOUND)
+        .arg("NX")
+        .arg("EX")
+        .arg(NONCE_TTL_SECS)
+        .query_async(&mut get_con().await?)
+        .await?;
+
+    Ok(claimed.is_some())
+}
+
+/// Binds the ID of the model created for the request to a claimed nonce, keeping its expiry.
+/// This does nothing if the nonce is not claimed or has expired.
+pub async fn bind_nonce(user_id: u64, nonce: &str, id: u64) -> Result<()> {
+    redis::cmd("SET")
+        .arg(key(user_id, nonce))
+        .arg(id)
+        .arg("XX")
+        .arg("KEEPTTL")
+        .query_async::<_, Option<String>>(&mut get_con().await?)
+        .await?;
+
+    Ok(())
+}
+
+/// Fetches the ID bound to the nonce with [`bind_nonce`]. Returns `None` if the nonce is not
+/// claimed or the request that claimed it has not finished yet.
+pub async fn fetch_nonce(user_id: u64, nonce: &str) -> Result<Option<u64>> {
+    let id: Option<u64> = get_con().await?.get(key(user_id, nonce)).await?;
+
+    Ok(id.filter(|&id| id != UNBOUND))
+}
+
+/// Releases a claimed nonce, e.g. when the request that claimed it failed, so that it can be
+/// retried.
+pub async fn release_nonce(user_id: u64, nonce: &str) -> Result<()> {
+    get_con().await?.del(key(user_id, nonce)).await?;
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use super::get_con;
+use crate::error::{ErrIntoExt, Result};
+use deadpool_redis::redis::{self, AsyncCommands};
+
+#[inline]
+fn key(user_id: u64) -> String {
+    format!("essence-{user_id}-observable")
+}
+
+/// Caches the full set of users observable by the given user, replacing any existing set.
+pub async fn cache_observable_users(user_id: u64, user_ids: &[u64]) -> Result<()> {
+    let key = key(user_id);
+    let mut pipe = redis::pipe();
+    pipe.atomic().del(&key).ignore();
+    if !user_ids.is_empty() {
+        pipe.sadd(&key, user_ids).ignore();
+    }
+
+    pipe.query_async(&mut get_con().await?).await.err_into()
+}
+
+/// Fetches the cached set of users observable by the given user. Returns `None` if the set has
+/// not been cached.
+pub async fn fetch_observable_users(user_id: u64) -> Result<Option<Vec<u64>>> {
+    let mut con = get_con().await?;
+    let key = key(user_id);
+
+    let (exists, user_ids): (bool, Vec<u64>) = redis::pipe()
+        .atomic()
+        .exists(&key)
+        .smembers(&key)
+        .query_async(&mut con)
+        .await?;
+
+    Ok(exists.then_some(user_ids))
+}
+
+/// Returns whether the observable set of the given user has been cached.
+pub async fn is_observable_cached(user_id: u64) -> Result<bool> {
+    get_con().await?.exists(key(user_id)).await.err_into()
+}
+
+/// Checks which of the given users are cached as observable by the given user, in the same order
+/// as ``user_ids``.
+async fn observable_flags(
+    con: &mut deadpool_redis::Connection,
+    key: &str,
+    user_ids: &[u64],
+) -> Result<Vec<bool>> {
+    redis::cmd("SMISMEMBER")
+        .arg(key)
+        .arg(user_ids)
+        .query_async(con)
+        .await
+        .err_into()
+}
+
+/// Adds the given users to the cached observable set of the given user, returning the users that
+/// were not observable before.
+///
+/// Returns `None` if the observable set of the user has not been cached, in which case nothing is
+/// added and the set must be seeded with [`cache_observable_users`] first.
+pub async fn add_observable_users(user_id: u64, user_ids: &[u64]) -> Result<Option<Vec<u64>>> {
+    let mut con = get_con().await?;
+    let key = key(user_id);
+
+    if !con.exists::<_, bool>(&key).await? {
+        return Ok(None);
+    }
+    if user_ids.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+
+    let flags = observable_flags(&mut con, &key, user_ids).await?;
+    con.sadd::<_, _, ()>(&key, user_ids).await?;
+
+    Ok(Some(
+        user_ids
+            .iter()
+            .zip(flags)
+            .filter_map(|(&id, observed)| (!observed).then_some(id))
+            .collect(),
+    ))
+}
+
+/// Removes the given users from the cached observable set of the given user, returning the users
+/// that were observable before.
+///
+/// Returns `None` if the observable set of the user has not been cached.
+pub async fn remove_observable_users(user_id: u64, user_ids: &[u64]) -> Result<Option<Vec<u64>>> {
+    let mut con = get_con().await?;
+    let key = key(user_id);
+
+    if !con.exists::<_, bool>(&key).await? {
+        return Ok(None);
+    }
+    if user_ids.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+
+    let flags = observable_flags(&mut con, &key, user_ids).await?;
+    con.srem::<_, _, ()>(&key, user_ids).await?;
+
+    Ok(Some(
+        user_ids
+            .iter()
+            .zip(flags)
+            .filter_map(|(&id, observed)| observed.then_some(id))
+            .collect(),
+    ))
+}
+
+/// Invalidates the cached observable set of the given user, i.e. when they disconnect.
+pub async fn invalidate_observable_users(user_id: u64) -> Result<()> {
+    get_con().await?.del(key(user_id)).await.err_into()
+}
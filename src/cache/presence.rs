@@ -0,0 +1,252 @@
+use super::{get_con, AsRefThreadSafe};
+use crate::{
+    bincode_impl::BincodeType,
+    error::{ErrIntoExt, Result},
+    models::{Device, Devices, Presence, PresenceStatus},
+};
+use chrono::{DateTime, Utc};
+use deadpool_redis::redis::{self, AsyncCommands};
+
+const CUSTOM_STATUS_EXPIRY_KEY: &str = "essence-custom-status-expiry";
+
+/// Updates the presence of a user. If the status is [`PresenceStatus::Offline`], the user is
+/// no longer considered online. If the presence has a custom status with a
+/// [clear time](Presence::custom_status_clear_at), it is scheduled to be cleared by
+/// [`clear_expired_custom_statuses`].
+pub async fn update_presence(presence: Presence) -> Result<()> {
+    let mut con = get_con().await?;
+    let user_id = presence.user_id;
+
+    if presence.status == PresenceStatus::Offline {
+        con.srem("essence-online", user_id).await?;
+    } else {
+        con.sadd("essence-online", user_id).await?;
+    }
+
+    match presence.custom_status_clear_at {
+        Some(clear_at) if presence.custom_status.is_some() => {
+            con.zadd(
+                CUSTOM_STATUS_EXPIRY_KEY,
+                user_id,
+                clear_at.timestamp_millis(),
+            )
+            .await?;
+        }
+        _ => con.zrem(CUSTOM_STATUS_EXPIRY_KEY, user_id).await?,
+    }
+
+    con.hset("essence-presences", user_id, BincodeType(presence))
+        .await
+        .err_into()
+}
+
+/// Updates the status of a user on a single device, and merges it with the status of the user on
+/// all other devices they are present on. The status with the highest
+/// [precedence](PresenceStatus::precedence) is used. Setting the status to
+/// [`PresenceStatus::Offline`] removes the device.
+///
+/// Returns the resulting merged presence.
+pub async fn update_device_presence(
+    user_id: u64,
+    device: Device,
+    status: PresenceStatus,
+) -> Result<Presence> {
+    let mut con = get_con().await?;
+    let key = format!("essence-{user_id}-devices");
+    let field = Devices::from(device).bits();
+
+    if status == PresenceStatus::Offline {
+        con.hdel(&key, field).await?;
+    } else {
+        con.hset(&key, field, BincodeType(status)).await?;
+    }
+
+    let (status, devices) = con
+        .hgetall::<_, Vec<(u32, BincodeType<PresenceStatus>)>>(&key)
+        .await?
+        .into_iter()
+        .fold(
+            (PresenceStatus::Offline, Devices::empty()),
+            |(status, devices), (device, other)| {
+                (
+                    status.merge(other.0),
+                    devices | Devices::from_bits_truncate(device),
+                )
+            },
+        );
+
+    let old = fetch_presence(user_id).await?;
+    let presence = Presence {
+        user_id,
+        status,
+        custom_status: old.as_ref().and_then(|p| p.custom_status.clone()),
+        custom_status_clear_at: old.as_ref().and_then(|p| p.custom_status_clear_at),
+        devices,
+        online_since: (status != PresenceStatus::Offline).then(|| {
+            old.and_then(|p| p.online_since)
+                .unwrap_or_else(chrono::Utc::now)
+        }),
+    };
+
+    update_presence(presence.clone()).await?;
+    Ok(presence)
+}
+
+/// Sets the custom status of a user, optionally clearing it automatically at the given time.
+/// Passing `None` as the custom status clears it immediately.
+///
+/// Returns the resulting presence, or `None` if the user has no presence stored.
+pub async fn update_custom_status(
+    user_id: u64,
+    custom_status: Option<String>,
+    clear_at: Option<DateTime<Utc>>,
+) -> Result<Option<Presence>> {
+    let Some(mut presence) = fetch_presence(user_id).await? else {
+        return Ok(None);
+    };
+
+    presence.custom_status_clear_at = custom_status.as_ref().and(clear_at);
+    presence.custom_status = custom_status;
+
+    update_presence(presence.clone()).await?;
+    Ok(Some(presence))
+}
+
+/// Clears all custom statuses whose clear time has passed. This should be called periodically.
+///
+/// Returns the updated presences of the affected users so that they can be dispatched.
+pub async fn clear_expired_custom_statuses() -> Result<Vec<Presence>> {
+    let mut con = get_con().await?;
+    let now = Utc::now().timestamp_millis();
+
+    let user_ids: Vec<u64> = con
+        .zrangebyscore(CUSTOM_STATUS_EXPIRY_KEY, "-inf", now)
+        .await?;
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    con.zrembyscore(CUSTOM_STATUS_EXPIRY_KEY, "-inf", now)
+        .await?;
+
+    let mut presences = Vec::with_capacity(user_ids.len());
+    for user_id in user_ids {
+        let Some(BincodeType(mut presence)) = con
+            .hget::<_, _, Option<BincodeType<Presence>>>("essence-presences", user_id)
+            .await?
+        else {
+            continue;
+        };
+
+        if presence.clear_expired_custom_status() {
+            con.hset("essence-presences", user_id, BincodeType(presence.clone()))
+                .await?;
+            presences.push(presence);
+        }
+    }
+
+    Ok(presences)
+}
+
+/// Fetches the presence of a user, or `None` if the user has no presence stored. Custom statuses
+/// that have expired but have not been cleared yet are omitted.
+pub async fn fetch_presence(user_id: u64) -> Result<Option<Presence>> {
+    Ok(get_con()
+        .await?
+        .hget::<_, _, Option<BincodeType<Presence>>>("essence-presences", user_id)
+        .await?
+        .map(|BincodeType(mut p)| {
+            p.clear_expired_custom_status();
+            p
+        }))
+}
+
+/// Fetches the presences of multiple users at once. Users without a stored presence are omitted.
+pub async fn fetch_presences_bulk(user_ids: impl AsRefThreadSafe<[u64]>) -> Result<Vec<Presence>> {
+    let user_ids = user_ids.as_ref();
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut con = get_con().await?;
+    let presences: Vec<Option<BincodeType<Presence>>> = redis::cmd("HMGET")
+        .arg("essence-presences")
+        .arg(user_ids)
+        .query_async(&mut con)
+        .await?;
+
+    Ok(presences
+        .into_iter()
+        .flatten()
+        .map(|BincodeType(mut p)| {
+            p.clear_expired_custom_status();
+            p
+        })
+        .collect())
+}
+
+/// Removes all presence information of a user, marking them as offline.
+pub async fn remove_presence(user_id: u64) -> Result<()> {
+    let mut con = get_con().await?;
+
+    con.del(format!("essence-{user_id}-devices")).await?;
+    con.srem("essence-online", user_id).await?;
+    con.zrem(CUSTOM_STATUS_EXPIRY_KEY, user_id).await?;
+    con.hdel("essence-presences", user_id).await.err_into()
+}
+
+/// Returns whether the user is currently online, idle, or on do not disturb.
+pub async fn is_online(user_id: u64) -> Result<bool> {
+    get_con()
+        .await?
+        .sismember("essence-online", user_id)
+        .await
+        .err_into()
+}
+
+/// Fetches the IDs of up to ``limit`` members in the given guild that are currently online. This
+/// relies on the member cache of the guild being built.
+pub async fn sample_online_members(guild_id: u64, limit: usize) -> Result<Vec<u64>> {
+    let mut user_ids = get_con()
+        .await?
+        .sinter::<_, Vec<u64>>(&[
+            format!("essence-{guild_id}-members"),
+            "essence-online".to_string(),
+        ])
+        .await?;
+
+    user_ids.truncate(limit);
+    Ok(user_ids)
+}
+
+#[inline]
+pub(super) fn online_count_cmd(guild_id: u64) -> redis::Cmd {
+    let mut cmd = redis::cmd("SINTERCARD");
+    cmd.arg(2)
+        .arg(format!("essence-{guild_id}-members"))
+        .arg("essence-online");
+    cmd
+}
+
+/// Counts the number of members in the given guild that are currently online. This relies on the
+/// member cache of the guild being built.
+pub async fn online_count(guild_id: u64) -> Result<u32> {
+    online_count_cmd(guild_id)
+        .query_async(&mut get_con().await?)
+        .await
+        .err_into()
+}
+
+/// Counts the number of members that are currently online in each of the given guilds, in the
+/// same order as the given guild IDs. This relies on the member caches of the guilds being built.
+pub async fn online_counts(guild_ids: impl AsRefThreadSafe<[u64]>) -> Result<Vec<u32>> {
+    let guild_ids = guild_ids.as_ref();
+    if guild_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut pipe = redis::pipe();
+    for &guild_id in guild_ids {
+        pipe.add_command(online_count_cmd(guild_id));
+    }
+    pipe.query_async(&mut get_con().await?).await.err_into()
+}
@@ -0,0 +1,44 @@
+use super::get_con;
+use crate::error::Result;
+use deadpool_redis::redis::{self, AsyncCommands};
+
+#[inline]
+fn key(channel_id: u64, user_id: u64) -> String {
+    format!("essence-{channel_id}-{user_id}-slowmode")
+}
+
+/// Atomically starts a slowmode cooldown of ``slowmode`` milliseconds for the user in the given
+/// channel, unless one is already active.
+///
+/// Returns `None` if the cooldown was started, otherwise the number of milliseconds remaining on
+/// the active cooldown.
+pub async fn try_start_slowmode(
+    channel_id: u64,
+    user_id: u64,
+    slowmode: u32,
+) -> Result<Option<u64>> {
+    let mut con = get_con().await?;
+    let key = key(channel_id, user_id);
+
+    let started: Option<String> = redis::cmd("SET")
+        .arg(&key)
+        .arg(1)
+        .arg("NX")
+        .arg("PX")
+        .arg(slowmode)
+        .query_async(&mut con)
+        .await?;
+    if started.is_some() {
+        return Ok(None);
+    }
+
+    let remaining: i64 = con.pttl(&key).await?;
+    // The key may have expired between the two commands, in which case there is no cooldown left
+    Ok(Some(remaining.max(0) as u64))
+}
+
+/// Clears the slowmode cooldown of the user in the given channel.
+pub async fn clear_slowmode(channel_id: u64, user_id: u64) -> Result<()> {
+    get_con().await?.del(key(channel_id, user_id)).await?;
+    Ok(())
+}
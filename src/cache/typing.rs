@@ -0,0 +1,43 @@
+use super::get_con;
+use crate::error::{ErrIntoExt, Result};
+use deadpool_redis::redis::AsyncCommands;
+
+/// The number of seconds a typing indicator lasts before it automatically expires. Clients are
+/// expected to resend their typing state before this elapses if they are still typing.
+pub const TYPING_TIMEOUT: i64 = 10;
+
+#[inline]
+fn key(channel_id: u64) -> String {
+    format!("essence-{channel_id}-typing")
+}
+
+/// Marks the user as typing in the given channel. The typing state automatically expires after
+/// [`TYPING_TIMEOUT`] seconds unless it is refreshed.
+pub async fn set_typing(channel_id: u64, user_id: u64) -> Result<()> {
+    let mut con = get_con().await?;
+    let key = key(channel_id);
+    let expires_at = chrono::Utc::now().timestamp_millis() + TYPING_TIMEOUT * 1000;
+
+    con.zadd(&key, user_id, expires_at).await?;
+    con.expire(&key, TYPING_TIMEOUT).await.err_into()
+}
+
+/// Clears the typing state of the user in the given channel, i.e. when they send a message.
+pub async fn clear_typing(channel_id: u64, user_id: u64) -> Result<()> {
+    get_con()
+        .await?
+        .zrem(key(channel_id), user_id)
+        .await
+        .err_into()
+}
+
+/// Fetches the IDs of all users currently typing in the given channel. Expired entries are
+/// pruned before fetching.
+pub async fn fetch_typing_users(channel_id: u64) -> Result<Vec<u64>> {
+    let mut con = get_con().await?;
+    let key = key(channel_id);
+    let now = chrono::Utc::now().timestamp_millis();
+
+    con.zrembyscore(&key, "-inf", now).await?;
+    con.zrangebyscore(&key, now, "+inf").await.err_into()
+}
@@ -0,0 +1,39 @@
+use super::get_con;
+use crate::error::Result;
+use deadpool_redis::redis::{self, AsyncCommands};
+
+#[inline]
+fn key(user_id: u64) -> String {
+    format!("essence-{user_id}-email-verification")
+}
+
+/// Atomically starts a cooldown of ``cooldown`` seconds before the user can request another
+/// verification email, unless one is already active.
+///
+/// Returns `None` if the cooldown was started, otherwise the number of milliseconds remaining on
+/// the active cooldown.
+pub async fn try_start_verification_cooldown(user_id: u64, cooldown: u32) -> Result<Option<u64>> {
+    let mut con = get_con().await?;
+    let key = key(user_id);
+
+    let started: Option<String> = redis::cmd("SET")
+        .arg(&key)
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg(cooldown)
+        .query_async(&mut con)
+        .await?;
+    if started.is_some() {
+        return Ok(None);
+    }
+
+    let remaining: i64 = con.pttl(&key).await?;
+    Ok(Some(remaining.max(0) as u64))
+}
+
+/// Clears the verification email cooldown of the user, i.e. after the email was verified.
+pub async fn clear_verification_cooldown(user_id: u64) -> Result<()> {
+    get_con().await?.del(key(user_id)).await?;
+    Ok(())
+}
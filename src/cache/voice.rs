@@ -0,0 +1,85 @@
+use super::get_con;
+use crate::{
+    bincode_impl::BincodeType,
+    error::{ErrIntoExt, Result},
+    models::VoiceState,
+};
+use deadpool_redis::redis::AsyncCommands;
+
+#[inline]
+fn key(channel_id: u64) -> String {
+    format!("essence-{channel_id}-voice")
+}
+
+/// Updates the voice state of a user. Since a user can only be connected to one voice channel at
+/// a time, this removes them from the voice channel they were previously connected to, if any.
+///
+/// Returns the previous voice state of the user, if any.
+pub async fn update_voice_state(state: VoiceState) -> Result<Option<VoiceState>> {
+    let old = fetch_voice_state(state.user_id).await?;
+    let mut con = get_con().await?;
+
+    if let Some(ref old) = old
+        && old.channel_id != state.channel_id
+    {
+        con.hdel(key(old.channel_id), state.user_id).await?;
+    }
+
+    con.hset("essence-voice-channels", state.user_id, state.channel_id)
+        .await?;
+    con.hset(key(state.channel_id), state.user_id, BincodeType(state))
+        .await?;
+
+    Ok(old)
+}
+
+/// Removes the voice state of a user, i.e. when they disconnect from voice. Returns the removed
+/// voice state, if any.
+pub async fn remove_voice_state(user_id: u64) -> Result<Option<VoiceState>> {
+    let old = fetch_voice_state(user_id).await?;
+    if let Some(ref old) = old {
+        let mut con = get_con().await?;
+
+        con.hdel(key(old.channel_id), user_id).await?;
+        con.hdel("essence-voice-channels", user_id).await?;
+    }
+    Ok(old)
+}
+
+/// Fetches the voice state of a user, or `None` if they are not connected to a voice channel.
+pub async fn fetch_voice_state(user_id: u64) -> Result<Option<VoiceState>> {
+    let mut con = get_con().await?;
+    let Some(channel_id) = con
+        .hget::<_, _, Option<u64>>("essence-voice-channels", user_id)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    Ok(con
+        .hget::<_, _, Option<BincodeType<VoiceState>>>(key(channel_id), user_id)
+        .await?
+        .map(|s| s.0))
+}
+
+/// Fetches the voice states of all users connected to the given voice channel.
+pub async fn fetch_voice_states(channel_id: u64) -> Result<Vec<VoiceState>> {
+    Ok(get_con()
+        .await?
+        .hvals::<_, Vec<BincodeType<VoiceState>>>(key(channel_id))
+        .await?
+        .into_iter()
+        .map(|s| s.0)
+        .collect())
+}
+
+/// Removes all voice states in the given voice channel, i.e. when the channel is deleted.
+pub async fn clear_voice_states(channel_id: u64) -> Result<()> {
+    let mut con = get_con().await?;
+    let user_ids = con.hkeys::<_, Vec<u64>>(key(channel_id)).await?;
+
+    if !user_ids.is_empty() {
+        con.hdel("essence-voice-channels", user_ids).await?;
+    }
+    con.del(key(channel_id)).await.err_into()
+}
@@ -1,6 +1,33 @@
 use crate::cache;
 use crate::db::DbExt;
-use crate::models::UserFlags;
+use crate::models::{Device, Devices, OAuthApplication, Session, SessionDevice, UserFlags};
+#[cfg(feature = "auth")]
+use crate::{
+    auth::oauth,
+    db::get_pool,
+    http::oauth::{
+        AuthorizePayload, CreateOAuthApplicationPayload, OAuthGrant, OAuthTokenRequest,
+        OAuthTokenResponse,
+    },
+    http::validate::Validate,
+    models::{OAuthScopes, OAuthTokenInfo},
+};
+use crate::{Error, NotFoundExt};
+use chrono::{DateTime, Utc};
+
+macro_rules! construct_session {
+    ($data:ident) => {{
+        Session {
+            id: $data.id as u64,
+            user_id: $data.user_id as u64,
+            device_name: $data.device_name,
+            platform: $data.platform,
+            ip: $data.ip,
+            created_at: $data.created_at,
+            last_used_at: $data.last_used_at,
+        }
+    }};
+}
 
 #[async_trait::async_trait]
 pub trait AuthDbExt<'t>: DbExt<'t> {
@@ -92,31 +119,1224 @@ pub trait AuthDbExt<'t>: DbExt<'t> {
         .map(|_| ())
     }
 
-    /// Deletes all stale push notification registration keys using a SQL function.
+    /// Creates a new session for the given user and registers its token. The token should be
+    /// generated with [`generate_session_token`](crate::auth::generate_session_token) so that
+    /// the session ID can be read back from it.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
-    /// * If an error occurs with deleting the keys.
-    async fn delete_stale_push_keys(&mut self) -> sqlx::Result<()> {
-        sqlx::query!("SELECT delete_stale_keys()::TEXT")
-            .fetch_one(self.transaction())
-            .await
-            .map(|_| ())
+    /// * If the device name is longer than 64 characters.
+    /// * If an error occurs with creating the session or registering the token.
+    async fn create_session(
+        &mut self,
+        session_id: u64,
+        user_id: u64,
+        token: impl AsRef<str> + Send,
+        device_name: Option<String>,
+        platform: Option<String>,
+        ip: Option<String>,
+    ) -> crate::Result<Session> {
+        if let Some(ref device_name) = device_name
+            && device_name.chars().count() > 64
+        {
+            return Err(Error::InvalidField {
+                field: "device_name".to_string(),
+                message: "Device name must be at most 64 characters long".to_string(),
+            });
+        }
+
+        let session = sqlx::query!(
+            "INSERT INTO sessions (id, user_id, device_name, platform, ip)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *",
+            session_id as i64,
+            user_id as i64,
+            device_name,
+            platform,
+            ip,
+        )
+        .fetch_one(self.transaction())
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO tokens (user_id, token, session_id) VALUES ($1, $2, $3)",
+            user_id as i64,
+            token.as_ref(),
+            session_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(construct_session!(session))
+    }
+
+    /// Fetches all sessions of the given user, most recently used first.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the sessions.
+    async fn list_sessions(&self, user_id: u64) -> crate::Result<Vec<Session>> {
+        let sessions = sqlx::query!(
+            "SELECT * FROM sessions WHERE user_id = $1 ORDER BY last_used_at DESC",
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|s| construct_session!(s))
+            .collect())
     }
 
-    /// Deletes all tokens associated with the given user ID.
+    /// Records that the session with the given ID was just used to authenticate.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
-    /// * If an error occurs with deleting the tokens.
-    async fn delete_all_tokens(&mut self, user_id: u64) -> crate::Result<()> {
-        sqlx::query!("DELETE FROM tokens WHERE user_id = $1", user_id as i64)
+    /// * If an error occurs with updating the session.
+    async fn touch_session(&mut self, session_id: u64) -> crate::Result<()> {
+        sqlx::query!(
+            "UPDATE sessions SET last_used_at = NOW() WHERE id = $1",
+            session_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records that the session with the given ID was just seen on the given device, e.g. when it
+    /// connects to harmony or sends a heartbeat.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the session is not found.
+    /// * If an error occurs with updating the device.
+    async fn touch_device(&mut self, session_id: u64, device: Device) -> crate::Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO devices (session_id, user_id, device)
+            SELECT id, user_id, $2 FROM sessions WHERE id = $1
+            ON CONFLICT (session_id)
+            DO UPDATE SET device = EXCLUDED.device, last_seen_at = NOW()
+            RETURNING session_id"#,
+            session_id as i64,
+            device.name(),
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("session", "session not found")?;
+
+        Ok(())
+    }
+
+    /// Fetches the devices of all sessions of the given user, most recently seen first.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the devices.
+    async fn fetch_user_devices(&self, user_id: u64) -> crate::Result<Vec<SessionDevice>> {
+        sqlx::query!(
+            "SELECT * FROM devices WHERE user_id = $1 ORDER BY last_seen_at DESC",
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|d| {
+            Ok(SessionDevice {
+                session_id: d.session_id as u64,
+                user_id: d.user_id as u64,
+                device: Device::from_db(&d.device)?,
+                last_seen_at: d.last_seen_at,
+            })
+        })
+        .collect()
+    }
+
+    /// Fetches the devices the given user was seen on since the given time.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the devices.
+    async fn fetch_active_devices(
+        &self,
+        user_id: u64,
+        since: DateTime<Utc>,
+    ) -> crate::Result<Devices> {
+        sqlx::query!(
+            "SELECT DISTINCT device FROM devices WHERE user_id = $1 AND last_seen_at >= $2",
+            user_id as i64,
+            since,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|d| Device::from_db(&d.device))
+        .collect()
+    }
+
+    /// Revokes the session with the given ID, invalidating its token.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the session is not found or does not belong to the user.
+    /// * If an error occurs with revoking the session.
+    async fn revoke_session(&mut self, user_id: u64, session_id: u64) -> crate::Result<()> {
+        let tokens = sqlx::query!(
+            "DELETE FROM tokens WHERE user_id = $1 AND session_id = $2 RETURNING token",
+            user_id as i64,
+            session_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM sessions WHERE id = $1 AND user_id = $2 RETURNING id",
+            session_id as i64,
+            user_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("session", format!("Session with ID {session_id} not found"))?;
+
+        for token in tokens {
+            cache::invalidate_token(token.token).await?;
+        }
+        Ok(())
+    }
+
+    /// Revokes all sessions of the given user except the session with the given ID, i.e. to log
+    /// out of all other devices. Legacy tokens without a session are also revoked. Returns the
+    /// IDs of the revoked sessions.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with revoking the sessions.
+    async fn revoke_all_other_sessions(
+        &mut self,
+        user_id: u64,
+        current_session_id: u64,
+    ) -> crate::Result<Vec<u64>> {
+        let tokens = sqlx::query!(
+            "DELETE FROM tokens
+            WHERE user_id = $1 AND session_id IS DISTINCT FROM $2
+            RETURNING token",
+            user_id as i64,
+            current_session_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?;
+
+        let revoked = sqlx::query!(
+            "DELETE FROM sessions WHERE user_id = $1 AND id != $2 RETURNING id",
+            user_id as i64,
+            current_session_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.id as u64)
+        .collect();
+
+        for token in tokens {
+            cache::invalidate_token(token.token).await?;
+        }
+        Ok(revoked)
+    }
+
+    /// Returns whether the given user has two-factor authentication enabled.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the two-factor authentication state.
+    async fn is_mfa_enabled(&self, user_id: u64) -> crate::Result<bool> {
+        Ok(sqlx::query!(
+            "SELECT enabled FROM user_mfa WHERE user_id = $1",
+            user_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .is_some_and(|r| r.enabled))
+    }
+
+    /// Begins enrolling the given user in TOTP two-factor authentication, replacing any pending
+    /// enrollment. Returns the new base32-encoded secret. Two-factor authentication is not
+    /// enabled until [`Self::confirm_totp_enrollment`] is called with a valid code.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user already has two-factor authentication enabled.
+    /// * If an error occurs with storing the secret.
+    #[cfg(feature = "auth")]
+    async fn begin_totp_enrollment(&mut self, user_id: u64) -> crate::Result<String> {
+        if get_pool().is_mfa_enabled(user_id).await? {
+            return Err(Error::AlreadyExists {
+                what: "mfa".to_string(),
+                message: "Two-factor authentication is already enabled".to_string(),
+            });
+        }
+
+        let secret = crate::auth::generate_totp_secret();
+        sqlx::query!(
+            "INSERT INTO user_mfa (user_id, totp_secret) VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET totp_secret = $2, created_at = NOW()",
+            user_id as i64,
+            secret,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(secret)
+    }
+
+    /// Confirms a pending TOTP enrollment with a code from the authenticator app, enabling
+    /// two-factor authentication for the user. Returns the newly generated recovery codes, which
+    /// are only stored hashed and must be shown to the user.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user has no pending enrollment.
+    /// * If the user already has two-factor authentication enabled.
+    /// * If the code is invalid.
+    /// * If an error occurs with enabling two-factor authentication.
+    #[cfg(feature = "auth")]
+    async fn confirm_totp_enrollment(
+        &mut self,
+        user_id: u64,
+        code: &str,
+    ) -> crate::Result<Vec<String>> {
+        let mfa = sqlx::query!(
+            "SELECT totp_secret, enabled FROM user_mfa WHERE user_id = $1",
+            user_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found("mfa", "No pending two-factor authentication enrollment")?;
+
+        if mfa.enabled {
+            return Err(Error::AlreadyExists {
+                what: "mfa".to_string(),
+                message: "Two-factor authentication is already enabled".to_string(),
+            });
+        }
+        let step = crate::auth::verify_totp_code(
+            &mfa.totp_secret,
+            code,
+            chrono::Utc::now().timestamp() as u64,
+        )
+        .ok_or_else(|| Error::InvalidMfaCode {
+            message: "Invalid two-factor authentication code".to_string(),
+        })?;
+
+        sqlx::query!(
+            "UPDATE user_mfa SET enabled = TRUE, last_used_step = $2 WHERE user_id = $1",
+            user_id as i64,
+            step as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        self.regenerate_recovery_codes(user_id).await
+    }
+
+    /// Verifies a TOTP code or a recovery code for the given user. TOTP codes are accepted within
+    /// a window of [`TOTP_SKEW`](crate::auth::TOTP_SKEW) periods to account for clock drift, but
+    /// each code can only be used once. Recovery codes are consumed when used.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user does not have two-factor authentication enabled.
+    /// * If the code is invalid or was already used.
+    /// * If an error occurs with verifying the code.
+    #[cfg(feature = "auth")]
+    async fn verify_totp(&mut self, user_id: u64, code: &str) -> crate::Result<()> {
+        let mfa = sqlx::query!(
+            "SELECT totp_secret, last_used_step FROM user_mfa WHERE user_id = $1 AND enabled",
+            user_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found("mfa", "Two-factor authentication is not enabled")?;
+
+        let step = crate::auth::verify_totp_code(
+            &mfa.totp_secret,
+            code,
+            chrono::Utc::now().timestamp() as u64,
+        );
+        if let Some(step) = step {
+            // Reject codes at or before the last accepted step to prevent replay attacks
+            if mfa.last_used_step.is_some_and(|last| step as i64 <= last) {
+                return Err(Error::InvalidMfaCode {
+                    message: "This two-factor authentication code was already used".to_string(),
+                });
+            }
+
+            sqlx::query!(
+                "UPDATE user_mfa SET last_used_step = $2 WHERE user_id = $1",
+                user_id as i64,
+                step as i64,
+            )
+            .execute(self.transaction())
+            .await?;
+
+            return Ok(());
+        }
+
+        sqlx::query!(
+            "DELETE FROM mfa_recovery_codes WHERE user_id = $1 AND code_hash = $2 RETURNING user_id",
+            user_id as i64,
+            crate::auth::hash_recovery_code(code),
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_else(|| Error::InvalidMfaCode {
+            message: "Invalid two-factor authentication code".to_string(),
+        })?;
+
+        Ok(())
+    }
+
+    /// Replaces all recovery codes of the given user with newly generated ones. Returns the new
+    /// recovery codes.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with replacing the recovery codes.
+    #[cfg(feature = "auth")]
+    async fn regenerate_recovery_codes(&mut self, user_id: u64) -> crate::Result<Vec<String>> {
+        let codes = crate::auth::generate_recovery_codes();
+        let hashes = codes
+            .iter()
+            .map(|code| crate::auth::hash_recovery_code(code))
+            .collect::<Vec<_>>();
+
+        sqlx::query!(
+            "DELETE FROM mfa_recovery_codes WHERE user_id = $1",
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+        sqlx::query!(
+            "INSERT INTO mfa_recovery_codes (user_id, code_hash) SELECT $1, * FROM UNNEST($2::TEXT[])",
+            user_id as i64,
+            &hashes,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(codes)
+    }
+
+    /// Disables two-factor authentication for the given user, deleting their secret and recovery
+    /// codes. The caller should verify a code with [`Self::verify_totp`] first.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with disabling two-factor authentication.
+    async fn disable_totp(&mut self, user_id: u64) -> crate::Result<()> {
+        sqlx::query!("DELETE FROM user_mfa WHERE user_id = $1", user_id as i64)
+            .execute(self.transaction())
+            .await?;
+        sqlx::query!(
+            "DELETE FROM mfa_recovery_codes WHERE user_id = $1",
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Creates an email verification for the current email of the given user, replacing any
+    /// pending verification. Returns the token to send to the user in a verification link.
+    ///
+    /// Users must wait [`Limits::email_verification_cooldown_secs`](crate::Limits) seconds
+    /// between requests.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes. The cooldown is
+    /// started before the verification is created and cleared if creating it fails; if the
+    /// transaction fails to commit, the caller should clear it with
+    /// [`clear_verification_cooldown`](cache::verification::clear_verification_cooldown).
+    ///
+    /// # Errors
+    /// * If the user is not found or has no email, i.e. a bot account.
+    /// * If the email of the user is already verified.
+    /// * If a verification email was requested too recently.
+    /// * If an error occurs with creating the verification.
+    #[cfg(feature = "auth")]
+    async fn create_email_verification(&mut self, user_id: u64) -> crate::Result<String> {
+        let user = sqlx::query!(
+            r#"SELECT email AS "email!", flags FROM users WHERE id = $1 AND email IS NOT NULL"#,
+            user_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found("user", format!("User with ID {user_id} not found"))?;
+
+        if UserFlags::from_bits_truncate(user.flags as u32).contains(UserFlags::VERIFIED) {
+            return Err(Error::AlreadyExists {
+                what: "verification".to_string(),
+                message: "Your email is already verified".to_string(),
+            });
+        }
+        let cooldown = crate::limits().email_verification_cooldown_secs;
+        if let Some(remaining) =
+            cache::verification::try_start_verification_cooldown(user_id, cooldown).await?
+        {
+            return Err(Error::VerificationCooldown {
+                retry_after: remaining as f32 / 1000.0,
+                message: "You are requesting verification emails too quickly".to_string(),
+            });
+        }
+
+        let token = crate::auth::generate_single_use_token();
+        let result: crate::Result<()> = async {
+            sqlx::query!(
+                "DELETE FROM email_verifications WHERE user_id = $1",
+                user_id as i64,
+            )
+            .execute(self.transaction())
+            .await?;
+            sqlx::query!(
+                "INSERT INTO email_verifications (token_hash, user_id, email, expires_at)
+                VALUES ($1, $2, $3, NOW() + make_interval(hours => $4))",
+                crate::auth::hash_single_use_token(&token),
+                user_id as i64,
+                user.email,
+                crate::limits().email_verification_ttl_hours as i32,
+            )
+            .execute(self.transaction())
+            .await?;
+            Ok(())
+        }
+        .await;
+
+        // the cooldown must not outlive a verification that was never created
+        if let Err(err) = result {
+            cache::verification::clear_verification_cooldown(user_id).await?;
+            return Err(err);
+        }
+        Ok(token)
+    }
+
+    /// Consumes the given email verification token, marking the email of its user as verified.
+    /// Returns the ID of the verified user.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the token is invalid or was already used.
+    /// * If the token has expired.
+    /// * If the email of the user changed since the token was created.
+    /// * If an error occurs with verifying the user.
+    #[cfg(feature = "auth")]
+    async fn consume_email_verification(&mut self, token: &str) -> crate::Result<u64> {
+        let verification = sqlx::query!(
+            r#"DELETE FROM email_verifications WHERE token_hash = $1
+            RETURNING user_id, email, expires_at < NOW() AS "expired!""#,
+            crate::auth::hash_single_use_token(token),
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("verification", "Invalid or already used verification token")?;
+
+        if verification.expired {
+            return Err(Error::ExpiredToken {
+                message: "This verification link has expired".to_string(),
+            });
+        }
+
+        sqlx::query!(
+            "UPDATE users SET flags = flags | $1 WHERE id = $2 AND email = $3 RETURNING id",
+            UserFlags::VERIFIED.bits() as i32,
+            verification.user_id,
+            verification.email,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_else(|| Error::InvalidField {
+            field: "token".to_string(),
+            message: "The email address changed since this verification link was sent".to_string(),
+        })?;
+
+        let user_id = verification.user_id as u64;
+        // Cached token info includes user flags
+        cache::invalidate_tokens_for(user_id).await?;
+        cache::verification::clear_verification_cooldown(user_id).await?;
+
+        Ok(user_id)
+    }
+
+    /// Deletes all expired email verifications, password resets, OAuth2 authorization codes, and
+    /// OAuth2 access tokens that cannot be refreshed. This should be called periodically.
+    ///
+    /// Returns the number of rows deleted.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with deleting the verifications.
+    async fn purge_expired_single_use_tokens(&mut self) -> crate::Result<u64> {
+        let verifications =
+            sqlx::query!("DELETE FROM email_verifications WHERE expires_at < NOW()")
+                .execute(self.transaction())
+                .await?
+                .rows_affected();
+        let resets = sqlx::query!("DELETE FROM password_resets WHERE expires_at < NOW()")
+            .execute(self.transaction())
+            .await?
+            .rows_affected();
+
+        let codes = sqlx::query!("DELETE FROM oauth_authorization_codes WHERE expires_at < NOW()")
+            .execute(self.transaction())
+            .await?
+            .rows_affected();
+        let tokens = sqlx::query!(
+            "DELETE FROM oauth_tokens WHERE expires_at < NOW() AND refresh_token_hash IS NULL"
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected();
+
+        Ok(verifications + resets + codes + tokens)
+    }
+
+    /// Creates a password reset for the user with the given email, replacing any pending reset.
+    /// Returns the ID of the user and the token to send to them in a reset link, or `None` if no
+    /// user has the given email. Callers should respond identically in both cases so that
+    /// registered emails cannot be discovered.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with creating the reset.
+    #[cfg(feature = "auth")]
+    async fn create_password_reset(
+        &mut self,
+        email: impl AsRef<str> + Send,
+    ) -> crate::Result<Option<(u64, String)>> {
+        let Some(user_id) = sqlx::query!(
+            "SELECT id FROM users WHERE email = $1",
+            email.as_ref().trim(),
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| r.id as u64) else {
+            return Ok(None);
+        };
+
+        let token = crate::auth::generate_single_use_token();
+        sqlx::query!(
+            "DELETE FROM password_resets WHERE user_id = $1",
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+        sqlx::query!(
+            "INSERT INTO password_resets (token_hash, user_id, expires_at)
+            VALUES ($1, $2, NOW() + make_interval(mins => $3))",
+            crate::auth::hash_single_use_token(&token),
+            user_id as i64,
+            crate::limits().password_reset_ttl_minutes as i32,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(Some((user_id, token)))
+    }
+
+    /// Consumes the given password reset token, changing the password of its user to the given
+    /// one and revoking all of their sessions and tokens. Returns the ID of the user. No
+    /// validation is done on the new password, it must be done before calling this method.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the token is invalid or was already used.
+    /// * If the token has expired.
+    /// * If an error occurs with changing the password.
+    #[cfg(feature = "auth")]
+    async fn consume_password_reset(
+        &mut self,
+        token: &str,
+        new_password: impl AsRef<str> + Send,
+    ) -> crate::Result<u64> {
+        let reset = sqlx::query!(
+            r#"DELETE FROM password_resets WHERE token_hash = $1
+            RETURNING user_id, expires_at < NOW() AS "expired!""#,
+            crate::auth::hash_single_use_token(token),
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found(
+            "password_reset",
+            "Invalid or already used password reset token",
+        )?;
+
+        if reset.expired {
+            return Err(Error::ExpiredToken {
+                message: "This password reset link has expired".to_string(),
+            });
+        }
+
+        let user_id = reset.user_id as u64;
+        let hashed = crate::auth::hash_password(new_password.as_ref()).await?;
+        sqlx::query!(
+            "UPDATE users SET password = $1 WHERE id = $2",
+            hashed,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        self.delete_all_tokens(user_id).await?;
+        Ok(user_id)
+    }
+
+    /// Fetches the OAuth2 application with the given client ID.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the application. If the application is not found,
+    /// `Ok(None)` is returned.
+    async fn fetch_oauth_application(
+        &self,
+        client_id: u64,
+    ) -> crate::Result<Option<OAuthApplication>> {
+        Ok(sqlx::query!(
+            "SELECT a.bot_id, b.owner_id, a.redirect_uris, a.created_at
+            FROM oauth_applications a
+            INNER JOIN bots b ON b.user_id = a.bot_id
+            WHERE a.bot_id = $1",
+            client_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| OAuthApplication {
+            client_id: r.bot_id as u64,
+            owner_id: r.owner_id as u64,
+            redirect_uris: r.redirect_uris,
+            created_at: r.created_at,
+        }))
+    }
+
+    /// Creates an OAuth2 application for the given bot. Returns the application and its client
+    /// secret, which is only stored hashed and must be shown to the owner.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the redirect URIs are invalid.
+    /// * If the bot is not found or already has an application.
+    /// * If an error occurs with creating the application.
+    #[cfg(feature = "auth")]
+    async fn create_oauth_application(
+        &mut self,
+        bot_id: u64,
+        payload: CreateOAuthApplicationPayload,
+    ) -> crate::Result<(OAuthApplication, String)> {
+        payload.validate()?;
+        if get_pool().fetch_oauth_application(bot_id).await?.is_some() {
+            return Err(Error::AlreadyExists {
+                what: "oauth_application".to_string(),
+                message: "This bot already has an OAuth2 application".to_string(),
+            });
+        }
+
+        let secret = oauth::generate_client_secret();
+        let row = sqlx::query!(
+            r#"INSERT INTO oauth_applications (bot_id, client_secret_hash, redirect_uris)
+            VALUES ($1, $2, $3)
+            RETURNING created_at, (SELECT owner_id FROM bots WHERE user_id = $1) AS "owner_id!""#,
+            bot_id as i64,
+            crate::auth::hash_single_use_token(&secret),
+            &payload.redirect_uris,
+        )
+        .fetch_one(self.transaction())
+        .await?;
+
+        Ok((
+            OAuthApplication {
+                client_id: bot_id,
+                owner_id: row.owner_id as u64,
+                redirect_uris: payload.redirect_uris,
+                created_at: row.created_at,
+            },
+            secret,
+        ))
+    }
+
+    /// Regenerates the client secret of the given OAuth2 application. Returns the new secret.
+    /// Existing access tokens remain valid.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the application is not found.
+    /// * If an error occurs with regenerating the secret.
+    #[cfg(feature = "auth")]
+    async fn regenerate_oauth_client_secret(&mut self, client_id: u64) -> crate::Result<String> {
+        let secret = oauth::generate_client_secret();
+        sqlx::query!(
+            "UPDATE oauth_applications SET client_secret_hash = $1 WHERE bot_id = $2 RETURNING bot_id",
+            crate::auth::hash_single_use_token(&secret),
+            client_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found(
+            "oauth_application",
+            format!("OAuth2 application with client ID {client_id} not found"),
+        )?;
+
+        Ok(secret)
+    }
+
+    /// Verifies the given client credentials, returning the application if they are valid.
+    ///
+    /// # Errors
+    /// * If the client ID or secret is invalid.
+    /// * If an error occurs with fetching the application.
+    #[cfg(feature = "auth")]
+    async fn authenticate_oauth_client(
+        &self,
+        client_id: u64,
+        client_secret: &str,
+    ) -> crate::Result<OAuthApplication> {
+        let valid = sqlx::query!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM oauth_applications WHERE bot_id = $1 AND client_secret_hash = $2
+            ) AS "valid!""#,
+            client_id as i64,
+            crate::auth::hash_single_use_token(client_secret),
+        )
+        .fetch_one(self.executor())
+        .await?
+        .valid;
+
+        let application = if valid {
+            self.fetch_oauth_application(client_id).await?
+        } else {
+            None
+        };
+        application.ok_or_else(|| Error::InvalidCredentials {
+            what: "client_secret".to_string(),
+            message: "Invalid client ID or client secret".to_string(),
+        })
+    }
+
+    /// Authorizes the given application to act on behalf of the user with the requested scopes.
+    /// Returns an authorization code to redirect the user back to the application with, which is
+    /// valid for [`AUTHORIZATION_CODE_TTL_SECS`](oauth::AUTHORIZATION_CODE_TTL_SECS) seconds.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the application is not found.
+    /// * If the redirect URI is not registered for the application.
+    /// * If the scopes are empty or invalid.
+    /// * If an error occurs with creating the authorization code.
+    #[cfg(feature = "auth")]
+    async fn create_authorization_code(
+        &mut self,
+        user_id: u64,
+        payload: AuthorizePayload,
+    ) -> crate::Result<String> {
+        let application = get_pool()
+            .fetch_oauth_application(payload.client_id)
+            .await?
+            .ok_or_not_found(
+                "oauth_application",
+                format!(
+                    "OAuth2 application with client ID {} not found",
+                    payload.client_id
+                ),
+            )?;
+
+        if !application.redirect_uris.contains(&payload.redirect_uri) {
+            return Err(Error::InvalidField {
+                field: "redirect_uri".to_string(),
+                message: "Redirect URI is not registered for this application".to_string(),
+            });
+        }
+        let scopes = OAuthScopes::parse(&payload.scope)
+            .filter(|scopes| !scopes.is_empty())
+            .ok_or_else(|| Error::InvalidField {
+                field: "scope".to_string(),
+                message: "Invalid or empty scope".to_string(),
+            })?;
+
+        let code = oauth::generate_authorization_code();
+        sqlx::query!(
+            "INSERT INTO oauth_authorization_codes
+                (code_hash, application_id, user_id, redirect_uri, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(secs => $6))",
+            crate::auth::hash_single_use_token(&code),
+            payload.client_id as i64,
+            user_id as i64,
+            payload.redirect_uri,
+            scopes.bits(),
+            oauth::AUTHORIZATION_CODE_TTL_SECS as f64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(code)
+    }
+
+    /// Issues a new access token for the given application and user. A refresh token is only
+    /// issued if ``refreshable`` is true.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with issuing the token.
+    #[cfg(feature = "auth")]
+    async fn issue_oauth_token(
+        &mut self,
+        client_id: u64,
+        user_id: u64,
+        scopes: OAuthScopes,
+        refreshable: bool,
+    ) -> crate::Result<OAuthTokenResponse> {
+        let (access_token, refresh_token) = oauth::generate_token_pair();
+        let refresh_token = refreshable.then_some(refresh_token);
+
+        sqlx::query!(
+            "INSERT INTO oauth_tokens
+                (access_token_hash, refresh_token_hash, application_id, user_id, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(secs => $6))",
+            crate::auth::hash_single_use_token(&access_token),
+            refresh_token
+                .as_deref()
+                .map(crate::auth::hash_single_use_token),
+            client_id as i64,
+            user_id as i64,
+            scopes.bits(),
+            oauth::ACCESS_TOKEN_TTL_SECS as f64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(OAuthTokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: oauth::ACCESS_TOKEN_TTL_SECS,
+            refresh_token,
+            scope: scopes.to_scope_string(),
+        })
+    }
+
+    /// Handles a request to the token endpoint, authenticating the client and exchanging the
+    /// grant for an access token. Authorization codes and refresh tokens are single-use, and
+    /// refreshing an access token revokes the previous one.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the client credentials are invalid.
+    /// * If the authorization code or refresh token is invalid, expired, or already used.
+    /// * If the redirect URI does not match the one the code was issued for.
+    /// * If the requested scopes are invalid.
+    /// * If an error occurs with issuing the token.
+    #[cfg(feature = "auth")]
+    async fn exchange_oauth_grant(
+        &mut self,
+        request: OAuthTokenRequest,
+    ) -> crate::Result<OAuthTokenResponse> {
+        let application = get_pool()
+            .authenticate_oauth_client(request.client_id, &request.client_secret)
+            .await?;
+        let client_id = application.client_id;
+
+        match request.grant {
+            OAuthGrant::AuthorizationCode { code, redirect_uri } => {
+                let code = sqlx::query!(
+                    r#"DELETE FROM oauth_authorization_codes
+                    WHERE code_hash = $1 AND application_id = $2
+                    RETURNING user_id, redirect_uri, scopes, expires_at < NOW() AS "expired!""#,
+                    crate::auth::hash_single_use_token(&code),
+                    client_id as i64,
+                )
+                .fetch_optional(self.transaction())
+                .await?
+                .ok_or_not_found(
+                    "authorization_code",
+                    "Invalid or already used authorization code",
+                )?;
+
+                if code.expired {
+                    return Err(Error::ExpiredToken {
+                        message: "This authorization code has expired".to_string(),
+                    });
+                }
+                if code.redirect_uri != redirect_uri {
+                    return Err(Error::InvalidField {
+                        field: "redirect_uri".to_string(),
+                        message: "Redirect URI does not match the authorization request"
+                            .to_string(),
+                    });
+                }
+
+                let scopes = OAuthScopes::from_bits_truncate(code.scopes);
+                self.issue_oauth_token(client_id, code.user_id as u64, scopes, true)
+                    .await
+            }
+            OAuthGrant::RefreshToken { refresh_token } => {
+                let token = sqlx::query!(
+                    "DELETE FROM oauth_tokens
+                    WHERE refresh_token_hash = $1 AND application_id = $2
+                    RETURNING user_id, scopes",
+                    crate::auth::hash_single_use_token(&refresh_token),
+                    client_id as i64,
+                )
+                .fetch_optional(self.transaction())
+                .await?
+                .ok_or_not_found("refresh_token", "Invalid or already used refresh token")?;
+
+                let scopes = OAuthScopes::from_bits_truncate(token.scopes);
+                self.issue_oauth_token(client_id, token.user_id as u64, scopes, true)
+                    .await
+            }
+            OAuthGrant::ClientCredentials { scope } => {
+                let scopes = OAuthScopes::parse(&scope)
+                    .filter(|scopes| !scopes.is_empty() && !scopes.contains(OAuthScopes::BOT))
+                    .ok_or_else(|| Error::InvalidField {
+                        field: "scope".to_string(),
+                        message: "Invalid or empty scope".to_string(),
+                    })?;
+
+                self.issue_oauth_token(client_id, application.owner_id, scopes, false)
+                    .await
+            }
+        }
+    }
+
+    /// Resolves information about the given OAuth2 access token. Returns `None` if the token is
+    /// invalid or has expired.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the token.
+    #[cfg(feature = "auth")]
+    async fn fetch_oauth_token_info(
+        &self,
+        access_token: &str,
+    ) -> crate::Result<Option<OAuthTokenInfo>> {
+        Ok(sqlx::query!(
+            "SELECT application_id, user_id, scopes, expires_at FROM oauth_tokens
+            WHERE access_token_hash = $1 AND expires_at > NOW()",
+            crate::auth::hash_single_use_token(access_token),
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| OAuthTokenInfo {
+            client_id: r.application_id as u64,
+            user_id: r.user_id as u64,
+            scopes: OAuthScopes::from_bits_truncate(r.scopes),
+            expires_at: r.expires_at,
+        }))
+    }
+
+    /// Revokes the given access or refresh token of an application, along with its counterpart.
+    /// Returns whether a token was revoked. As per RFC 7009, revoking an invalid token is not an
+    /// error.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the client credentials are invalid.
+    /// * If an error occurs with revoking the token.
+    #[cfg(feature = "auth")]
+    async fn revoke_oauth_token(
+        &mut self,
+        client_id: u64,
+        client_secret: &str,
+        token: &str,
+    ) -> crate::Result<bool> {
+        get_pool()
+            .authenticate_oauth_client(client_id, client_secret)
+            .await?;
+
+        let hash = crate::auth::hash_single_use_token(token);
+        let revoked = sqlx::query!(
+            "DELETE FROM oauth_tokens
+            WHERE application_id = $1 AND (access_token_hash = $2 OR refresh_token_hash = $2)",
+            client_id as i64,
+            hash,
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected();
+
+        Ok(revoked > 0)
+    }
+
+    /// Revokes all tokens the given user has granted to an application, i.e. when the user
+    /// deauthorizes the application.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with revoking the tokens.
+    async fn revoke_oauth_authorization(
+        &mut self,
+        user_id: u64,
+        client_id: u64,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "DELETE FROM oauth_tokens WHERE user_id = $1 AND application_id = $2",
+            user_id as i64,
+            client_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+        sqlx::query!(
+            "DELETE FROM oauth_authorization_codes WHERE user_id = $1 AND application_id = $2",
+            user_id as i64,
+            client_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Regenerates the token of the given bot, invalidating all of its previous tokens. Returns the
+    /// new token, which is not retrievable afterwards through any other means than
+    /// [`Self::fetch_token`].
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the bot is not found.
+    /// * If an error occurs with regenerating the token.
+    #[cfg(feature = "auth")]
+    async fn regenerate_bot_token(&mut self, bot_id: u64) -> crate::Result<String> {
+        sqlx::query!("SELECT user_id FROM bots WHERE user_id = $1", bot_id as i64)
+            .fetch_optional(self.executor())
+            .await?
+            .ok_or_not_found("bot", format!("Bot with ID {bot_id} not found"))?;
+
+        let old_tokens = sqlx::query!(
+            "DELETE FROM tokens WHERE user_id = $1 RETURNING token",
+            bot_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?;
+
+        let token = crate::auth::generate_token(bot_id);
+        sqlx::query!(
+            "INSERT INTO tokens (user_id, token) VALUES ($1, $2)",
+            bot_id as i64,
+            token,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        for old in old_tokens {
+            cache::invalidate_token(old.token).await?;
+        }
+        Ok(token)
+    }
+
+    /// Fetches when the current token of the given bot was created, for display without
+    /// revealing the token itself.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the token. If the bot has no token, `Ok(None)` is
+    /// returned.
+    async fn fetch_bot_token_created_at(
+        &self,
+        bot_id: u64,
+    ) -> crate::Result<Option<DateTime<Utc>>> {
+        Ok(sqlx::query!(
+            "SELECT created_at FROM tokens WHERE user_id = $1 ORDER BY created_at DESC LIMIT 1",
+            bot_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| r.created_at))
+    }
+
+    /// Deletes all stale push notification registration keys using a SQL function.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with deleting the keys.
+    async fn delete_stale_push_keys(&mut self) -> sqlx::Result<()> {
+        sqlx::query!("SELECT delete_stale_keys()::TEXT")
+            .fetch_one(self.transaction())
+            .await
+            .map(|_| ())
+    }
+
+    /// Deletes all tokens and sessions associated with the given user ID.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with deleting the tokens.
+    async fn delete_all_tokens(&mut self, user_id: u64) -> crate::Result<()> {
+        sqlx::query!("DELETE FROM tokens WHERE user_id = $1", user_id as i64)
+            .execute(self.transaction())
+            .await?;
+        sqlx::query!("DELETE FROM sessions WHERE user_id = $1", user_id as i64)
             .execute(self.transaction())
             .await?;
 
@@ -0,0 +1,400 @@
+use crate::{
+    db::{get_pool, ChannelDbExt, DbExt},
+    http::{
+        automod::{CreateAutoModRulePayload, EditAutoModRulePayload},
+        validate::Validate,
+    },
+    models::{AutoModAction, AutoModRule, AutoModTrigger, AUTOMOD_REGEX_SIZE_LIMIT},
+    Error, NotFoundExt,
+};
+
+macro_rules! construct_automod_rule {
+    ($data:ident) => {{
+        AutoModRule {
+            id: $data.id as _,
+            guild_id: $data.guild_id as _,
+            name: $data.name,
+            enabled: $data.enabled,
+            trigger: $data.trigger.0,
+            actions: $data.actions.0,
+            exempt_channels: $data
+                .exempt_channels
+                .into_iter()
+                .map(|id| id as _)
+                .collect(),
+            created_by: $data.created_by.map(|id| id as _),
+        }
+    }};
+}
+
+/// The maximum number of keywords, patterns or domains in a single automod trigger.
+const MAX_TRIGGER_ENTRIES: usize = 1000;
+/// The maximum number of regular expressions in a single automod trigger.
+const MAX_TRIGGER_PATTERNS: usize = 10;
+/// The maximum length of a single keyword, pattern or domain in an automod trigger.
+const MAX_TRIGGER_ENTRY_LENGTH: usize = 260;
+/// The maximum duration of an automod timeout, in seconds. This is 28 days.
+const MAX_TIMEOUT_DURATION: u32 = 28 * 24 * 60 * 60;
+
+fn invalid(field: &str, message: impl ToString) -> Error {
+    Error::InvalidField {
+        field: field.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn to_json(value: &impl serde::Serialize) -> crate::Result<serde_json::Value> {
+    serde_json::to_value(value).map_err(|err| Error::InternalError {
+        what: Some("automod serialization".to_string()),
+        message: err.to_string(),
+        debug: Some(format!("{err:?}")),
+    })
+}
+
+fn validate_entries(entries: &[String], max: usize) -> crate::Result<()> {
+    if entries.len() > max {
+        return Err(invalid(
+            "trigger",
+            format!("Trigger can have at most {max} entries"),
+        ));
+    }
+    if entries
+        .iter()
+        .any(|entry| entry.is_empty() || entry.chars().count() > MAX_TRIGGER_ENTRY_LENGTH)
+    {
+        return Err(invalid(
+            "trigger",
+            format!(
+                "Trigger entries must be between 1 and {MAX_TRIGGER_ENTRY_LENGTH} characters long"
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_trigger(trigger: &AutoModTrigger) -> crate::Result<()> {
+    match trigger {
+        AutoModTrigger::Keyword {
+            keywords,
+            allow_list,
+        } => {
+            if keywords.is_empty() {
+                return Err(invalid("trigger", "At least one keyword must be given"));
+            }
+            validate_entries(keywords, MAX_TRIGGER_ENTRIES)?;
+            validate_entries(allow_list, MAX_TRIGGER_ENTRIES)
+        }
+        AutoModTrigger::Regex { patterns } => {
+            if patterns.is_empty() {
+                return Err(invalid("trigger", "At least one pattern must be given"));
+            }
+            validate_entries(patterns, MAX_TRIGGER_PATTERNS)?;
+            for pattern in patterns {
+                regex::RegexBuilder::new(pattern)
+                    .size_limit(AUTOMOD_REGEX_SIZE_LIMIT)
+                    .build()
+                    .map_err(|err| invalid("trigger", format!("Invalid pattern: {err}")))?;
+            }
+            Ok(())
+        }
+        AutoModTrigger::MentionSpam { max_mentions } => {
+            if *max_mentions == 0 {
+                return Err(invalid("trigger", "Maximum mentions must be at least 1"));
+            }
+            Ok(())
+        }
+        AutoModTrigger::LinkFilter { allowed_domains } => {
+            validate_entries(allowed_domains, MAX_TRIGGER_ENTRIES)
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait AutoModDbExt<'t>: DbExt<'t> {
+    /// Validates the actions of an automod rule. Alert channels must belong to the guild.
+    ///
+    /// # Errors
+    /// * If no actions are given, or an action is given more than once.
+    /// * If a timeout duration or block message is out of range.
+    /// * If an alert channel does not exist in the guild.
+    async fn validate_automod_actions(
+        &self,
+        guild_id: u64,
+        actions: &[AutoModAction],
+    ) -> crate::Result<()> {
+        if actions.is_empty() {
+            return Err(invalid("actions", "At least one action must be given"));
+        }
+        for (i, action) in actions.iter().enumerate() {
+            if actions[..i]
+                .iter()
+                .any(|other| std::mem::discriminant(other) == std::mem::discriminant(action))
+            {
+                return Err(invalid(
+                    "actions",
+                    "Each type of action may only be given once",
+                ));
+            }
+
+            match action {
+                AutoModAction::Block { message } => {
+                    if let Some(message) = message
+                        && message.chars().count() > 150
+                    {
+                        return Err(invalid(
+                            "actions",
+                            "Block message must be at most 150 characters long",
+                        ));
+                    }
+                }
+                AutoModAction::Alert { channel_id } => {
+                    self.assert_channel_in_guild(guild_id, *channel_id).await?;
+                }
+                AutoModAction::Timeout { duration } => {
+                    if !(1..=MAX_TIMEOUT_DURATION).contains(duration) {
+                        return Err(invalid(
+                            "actions",
+                            format!("Timeout duration must be between 1 and {MAX_TIMEOUT_DURATION} seconds"),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches an automod rule of a guild.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the rule. If the rule is not found, `Ok(None)` is
+    /// returned.
+    async fn fetch_automod_rule(
+        &self,
+        guild_id: u64,
+        rule_id: u64,
+    ) -> crate::Result<Option<AutoModRule>> {
+        let rule = sqlx::query!(
+            r#"SELECT
+                id, guild_id, name, enabled, exempt_channels, created_by,
+                trigger AS "trigger: sqlx::types::Json<AutoModTrigger>",
+                actions AS "actions: sqlx::types::Json<Vec<AutoModAction>>"
+            FROM automod_rules
+            WHERE guild_id = $1 AND id = $2"#,
+            guild_id as i64,
+            rule_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| construct_automod_rule!(r));
+
+        Ok(rule)
+    }
+
+    /// Fetches all automod rules of a guild, ordered by ID. This is the order rules are evaluated
+    /// in.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the rules.
+    async fn fetch_automod_rules(&self, guild_id: u64) -> crate::Result<Vec<AutoModRule>> {
+        let rules = sqlx::query!(
+            r#"SELECT
+                id, guild_id, name, enabled, exempt_channels, created_by,
+                trigger AS "trigger: sqlx::types::Json<AutoModTrigger>",
+                actions AS "actions: sqlx::types::Json<Vec<AutoModAction>>"
+            FROM automod_rules
+            WHERE guild_id = $1
+            ORDER BY id"#,
+            guild_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| construct_automod_rule!(r))
+        .collect();
+
+        Ok(rules)
+    }
+
+    /// Creates a new automod rule in a guild.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the guild already has the maximum number of automod rules.
+    /// * If the name, trigger or actions are invalid.
+    /// * If an exempt channel does not exist in the guild.
+    /// * If an error occurs with creating the rule.
+    async fn create_automod_rule(
+        &mut self,
+        rule_id: u64,
+        guild_id: u64,
+        user_id: u64,
+        payload: CreateAutoModRulePayload,
+    ) -> crate::Result<AutoModRule> {
+        payload.validate()?;
+        validate_trigger(&payload.trigger)?;
+        get_pool()
+            .validate_automod_actions(guild_id, &payload.actions)
+            .await?;
+        for &channel_id in &payload.exempt_channels {
+            get_pool()
+                .assert_channel_in_guild(guild_id, channel_id)
+                .await?;
+        }
+
+        let count = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM automod_rules WHERE guild_id = $1"#,
+            guild_id as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .count;
+        let max = crate::limits().max_automod_rules;
+        if count as usize >= max {
+            return Err(invalid(
+                "guild_id",
+                format!("Guilds can have at most {max} automod rules"),
+            ));
+        }
+
+        sqlx::query!(
+            "INSERT INTO automod_rules
+                (id, guild_id, name, enabled, trigger, actions, exempt_channels, created_by)
+            VALUES ($1, $2, $3, $4, $5::JSONB, $6::JSONB, $7, $8)",
+            rule_id as i64,
+            guild_id as i64,
+            payload.name,
+            payload.enabled,
+            to_json(&payload.trigger)?,
+            to_json(&payload.actions)?,
+            &payload
+                .exempt_channels
+                .iter()
+                .map(|&id| id as i64)
+                .collect::<Vec<_>>(),
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(AutoModRule {
+            id: rule_id,
+            guild_id,
+            name: payload.name,
+            enabled: payload.enabled,
+            trigger: payload.trigger,
+            actions: payload.actions,
+            exempt_channels: payload.exempt_channels,
+            created_by: Some(user_id),
+        })
+    }
+
+    /// Edits an automod rule of a guild. Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the rule does not exist.
+    /// * If the name, trigger or actions are invalid.
+    /// * If an exempt channel does not exist in the guild.
+    /// * If an error occurs with editing the rule.
+    async fn edit_automod_rule(
+        &mut self,
+        guild_id: u64,
+        rule_id: u64,
+        payload: EditAutoModRulePayload,
+    ) -> crate::Result<(AutoModRule, AutoModRule)> {
+        payload.validate()?;
+
+        let old = get_pool()
+            .fetch_automod_rule(guild_id, rule_id)
+            .await?
+            .ok_or_not_found(
+                "automod_rule",
+                format!("Automod rule with ID {rule_id} not found"),
+            )?;
+        let mut rule = old.clone();
+
+        if let Some(name) = payload.name {
+            rule.name = name;
+        }
+        if let Some(enabled) = payload.enabled {
+            rule.enabled = enabled;
+        }
+        if let Some(trigger) = payload.trigger {
+            validate_trigger(&trigger)?;
+            rule.trigger = trigger;
+        }
+        if let Some(actions) = payload.actions {
+            get_pool()
+                .validate_automod_actions(guild_id, &actions)
+                .await?;
+            rule.actions = actions;
+        }
+        if let Some(exempt_channels) = payload.exempt_channels {
+            for &channel_id in &exempt_channels {
+                get_pool()
+                    .assert_channel_in_guild(guild_id, channel_id)
+                    .await?;
+            }
+            rule.exempt_channels = exempt_channels;
+        }
+
+        sqlx::query!(
+            "UPDATE automod_rules
+            SET
+                name = $1, enabled = $2, trigger = $3::JSONB, actions = $4::JSONB,
+                exempt_channels = $5
+            WHERE guild_id = $6 AND id = $7",
+            rule.name,
+            rule.enabled,
+            to_json(&rule.trigger)?,
+            to_json(&rule.actions)?,
+            &rule
+                .exempt_channels
+                .iter()
+                .map(|&id| id as i64)
+                .collect::<Vec<_>>(),
+            guild_id as i64,
+            rule_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((old, rule))
+    }
+
+    /// Deletes an automod rule of a guild.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the rule does not exist.
+    /// * If an error occurs with deleting the rule.
+    async fn delete_automod_rule(&mut self, guild_id: u64, rule_id: u64) -> crate::Result<()> {
+        let deleted = sqlx::query!(
+            "DELETE FROM automod_rules WHERE guild_id = $1 AND id = $2",
+            guild_id as i64,
+            rule_id as i64,
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected();
+
+        if deleted == 0 {
+            return Err(Error::NotFound {
+                entity: "automod_rule".to_string(),
+                message: format!("Automod rule with ID {rule_id} not found"),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<'t, T> AutoModDbExt<'t> for T where T: DbExt<'t> {}
@@ -2,23 +2,30 @@
 use crate::models::Embed;
 use crate::{
     cache::{self, ChannelInspection},
-    db::{get_pool, message::construct_message, DbExt, GuildDbExt, MessageDbExt},
+    db::{get_pool, message::construct_message, DbExt, GuildDbExt, MessageDbExt, UserDbExt},
+    error::UserInteractionType,
     http::channel::{
         CreateDmChannelPayload, CreateGuildChannelInfo, CreateGuildChannelPayload,
         EditChannelPayload,
     },
+    http::validate::Validate,
     models::{
-        Channel, ChannelType, DbGradient, DmChannel, DmChannelInfo, ExtendedColor, Guild,
-        GuildChannel, GuildChannelInfo, Message, PermissionOverwrite, PermissionPair, Permissions,
-        TextBasedGuildChannelInfo,
+        Asset, Channel, ChannelType, DbGradient, DmChannel, DmChannelInfo, ExtendedColor,
+        ForumChannelInfo, ForumSortOrder, ForumTag, Guild, GuildChannel, GuildChannelInfo,
+        MergedChannelInfo, Message, MessageInfo, ModelType, PermissionOverwrite, PermissionPair,
+        Permissions, TextBasedGuildChannelInfo, ThreadInfo,
     },
-    ws::UnackedChannel,
-    Error, Maybe, NotFoundExt,
+    snowflake::{generate_snowflake, with_model_type, SnowflakeReader},
+    ws::{AckedChannel, ChannelAckState, UnackedChannel, UnreadCount},
+    Error, NotFoundExt,
 };
-use futures_util::future::TryJoinAll;
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
 use std::{collections::HashMap, str::FromStr};
 
+/// The maximum number of source channels a merged channel may have.
+pub const MAX_MERGED_CHANNEL_SOURCES: usize = 10;
+
 macro_rules! query_channels {
     ($where:literal $(, $($args:expr),*)?) => {{
         sqlx::query_as!(
@@ -38,9 +45,36 @@ macro_rules! query_channels {
                 nsfw,
                 locked,
                 user_limit,
-                owner_id
+                owner_id,
+                forum_sort_order,
+                CASE WHEN c.type = 'forum' THEN (
+                    SELECT
+                        COALESCE(jsonb_agg(jsonb_build_object(
+                            'id', ft.id,
+                            'name', ft.name,
+                            'emoji', CASE WHEN ft.emoji_name IS NULL THEN NULL ELSE
+                                jsonb_build_object('id', ft.emoji_id, 'name', ft.emoji_name)
+                            END,
+                            'moderated', ft.moderated
+                        ) ORDER BY ft.id), '[]')
+                    FROM forum_tags ft WHERE ft.channel_id = c.id
+                ) END AS "forum_tags?: sqlx::types::Json<Vec<crate::models::ForumTag>>",
+                t.creator_id AS "thread_creator_id?",
+                t.parent_message_id AS "thread_parent_message_id?",
+                t.auto_archive_duration AS "thread_auto_archive_duration?",
+                t.archived_at AS "thread_archived_at?",
+                t.applied_tags AS "thread_applied_tags?",
+                CASE WHEN c.type = 'thread' THEN (
+                    SELECT COUNT(*) FROM thread_members tm WHERE tm.thread_id = c.id
+                ) END AS "thread_member_count?",
+                CASE WHEN c.type = 'merged' THEN ARRAY(
+                    SELECT ms.source_id FROM merged_channel_sources ms
+                    WHERE ms.merged_id = c.id ORDER BY ms.source_id
+                ) END AS "merged_source_ids?"
             FROM
                 channels c
+            LEFT JOIN
+                threads t ON t.channel_id = c.id
             WHERE
             "# + $where,
             $($($args),*)?
@@ -83,6 +117,15 @@ pub(crate) struct ChannelRecord {
     pub locked: Option<bool>,
     pub user_limit: Option<i16>,
     pub owner_id: Option<i64>,
+    pub forum_sort_order: Option<String>,
+    pub forum_tags: Option<sqlx::types::Json<Vec<ForumTag>>>,
+    pub thread_creator_id: Option<i64>,
+    pub thread_parent_message_id: Option<i64>,
+    pub thread_auto_archive_duration: Option<i32>,
+    pub thread_archived_at: Option<DateTime<Utc>>,
+    pub thread_applied_tags: Option<Vec<i64>>,
+    pub thread_member_count: Option<i64>,
+    pub merged_source_ids: Option<Vec<i64>>,
 }
 
 impl ChannelRecord {
@@ -117,8 +160,51 @@ impl ChannelRecord {
                 user_limit: self.user_limit.unwrap_or_default() as u16,
             },
             ChannelType::Category => GuildChannelInfo::Category,
-            _ if kind.is_dm() => unreachable!("This method should not be called for DM channels"),
-            _ => unimplemented!(),
+            ChannelType::Merged => GuildChannelInfo::Merged(MergedChannelInfo {
+                info: TextBasedGuildChannelInfo {
+                    topic: self.topic.take(),
+                    nsfw: self.nsfw.unwrap_or_default(),
+                    locked: self.locked.unwrap_or_default(),
+                    slowmode: self.slowmode.unwrap_or_default() as u32,
+                    last_message,
+                },
+                source_ids: self
+                    .merged_source_ids
+                    .take()
+                    .map(|ids| ids.into_iter().map(|id| id as u64).collect())
+                    .unwrap_or_default(),
+            }),
+            ChannelType::Thread => GuildChannelInfo::Thread(ThreadInfo {
+                owner_id: self.thread_creator_id.unwrap_or_default() as u64,
+                parent_message_id: self.thread_parent_message_id.map(|id| id as u64),
+                auto_archive_duration: self.thread_auto_archive_duration.unwrap_or_default() as u32,
+                archived_at: self.thread_archived_at,
+                locked: self.locked.unwrap_or_default(),
+                slowmode: self.slowmode.unwrap_or_default() as u32,
+                member_count: self.thread_member_count.unwrap_or_default() as u32,
+                applied_tags: self
+                    .thread_applied_tags
+                    .take()
+                    .map(|tags| tags.into_iter().map(|id| id as u64).collect())
+                    .unwrap_or_default(),
+                last_message,
+            }),
+            ChannelType::Forum => GuildChannelInfo::Forum(ForumChannelInfo {
+                topic: self.topic.take(),
+                nsfw: self.nsfw.unwrap_or_default(),
+                tags: self
+                    .forum_tags
+                    .take()
+                    .map(|tags| tags.0)
+                    .unwrap_or_default(),
+                default_sort_order: self
+                    .forum_sort_order
+                    .as_deref()
+                    .map(ForumSortOrder::from_str)
+                    .transpose()?
+                    .unwrap_or_default(),
+            }),
+            _ => unreachable!("This method should not be called for DM channels"),
         };
 
         let guild_id = self.guild_id.ok_or_else(|| Error::InternalError {
@@ -131,7 +217,7 @@ impl ChannelRecord {
             id: channel_id,
             guild_id,
             color: self.extended_color(),
-            icon: self.icon.clone(),
+            icon: self.icon.map(Asset::from_raw),
             position: self.position.unwrap_or_default() as u16,
             parent_id: self.parent_id.map(|id| id as u64),
             name: self.name.unwrap_or_default(),
@@ -162,7 +248,7 @@ impl ChannelRecord {
             }
             ChannelType::Group => DmChannelInfo::Group {
                 name: self.name.clone().unwrap_or_default(),
-                icon: self.icon,
+                icon: self.icon.map(Asset::from_raw),
                 topic: self.topic,
                 owner_id: self.owner_id.unwrap_or_default() as u64,
                 recipient_ids: recipients,
@@ -181,6 +267,42 @@ impl ChannelRecord {
     }
 }
 
+fn guild_channel_info_from_payload(info: CreateGuildChannelInfo) -> GuildChannelInfo {
+    match info {
+        CreateGuildChannelInfo::Text { topic } => {
+            GuildChannelInfo::Text(TextBasedGuildChannelInfo {
+                topic,
+                ..Default::default()
+            })
+        }
+        CreateGuildChannelInfo::Announcement { topic } => {
+            GuildChannelInfo::Announcement(TextBasedGuildChannelInfo {
+                topic,
+                ..Default::default()
+            })
+        }
+        CreateGuildChannelInfo::Voice { user_limit } => GuildChannelInfo::Voice { user_limit },
+        CreateGuildChannelInfo::Category => GuildChannelInfo::Category,
+        CreateGuildChannelInfo::Forum {
+            topic,
+            default_sort_order,
+        } => GuildChannelInfo::Forum(ForumChannelInfo {
+            topic,
+            default_sort_order,
+            ..ForumChannelInfo::default()
+        }),
+        CreateGuildChannelInfo::Merged { topic, source_ids } => {
+            GuildChannelInfo::Merged(MergedChannelInfo {
+                info: TextBasedGuildChannelInfo {
+                    topic,
+                    ..Default::default()
+                },
+                source_ids: source_ids.into_iter().sorted_unstable().collect(),
+            })
+        }
+    }
+}
+
 #[async_trait::async_trait]
 pub trait ChannelDbExt<'t>: DbExt<'t> {
     /// Asserts the given channel ID exists in the given guild.
@@ -283,6 +405,46 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         Ok(())
     }
 
+    /// Checks whether the user is still on slowmode cooldown in the given channel, and if not,
+    /// starts a new cooldown based on the slowmode of the channel. This should be called before a
+    /// message is sent. Users exempt from slowmode (i.e. those with permission to manage the
+    /// channel or its messages) should not be checked.
+    ///
+    /// # Errors
+    /// * If the channel is not found.
+    /// * If the user is still on cooldown.
+    /// * If an error occurs with fetching the slowmode of the channel.
+    async fn check_and_record_slowmode(&self, channel_id: u64, user_id: u64) -> crate::Result<()> {
+        let slowmode = sqlx::query!(
+            "SELECT slowmode FROM channels WHERE id = $1",
+            channel_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?
+        .slowmode
+        .unwrap_or_default();
+
+        if slowmode <= 0 {
+            return Ok(());
+        }
+
+        if let Some(remaining) =
+            cache::slowmode::try_start_slowmode(channel_id, user_id, slowmode as u32).await?
+        {
+            return Err(Error::Slowmode {
+                channel_id,
+                retry_after: remaining as f32 / 1000.0,
+                message: format!(
+                    "This channel has slowmode enabled. Try again in {:.1} seconds.",
+                    remaining as f32 / 1000.0,
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
     /// Inspects basic information about a channel.
     ///
     /// # Errors
@@ -345,6 +507,8 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
                 messages
             WHERE
                 channel_id = $1
+            AND
+                deleted_at IS NULL
             ORDER BY id DESC
             LIMIT 1
             "#,
@@ -352,7 +516,8 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         )
         .fetch_optional(self.executor())
         .await?
-        .map(|m| construct_message!(m));
+        .map(|m| construct_message!(m))
+        .transpose()?;
 
         if let Some(message) = message.as_mut() {
             message.attachments = self.fetch_message_attachments(message.id).await?;
@@ -385,7 +550,7 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         }
 
         let guild_id = inspection.guild_id.unwrap_or(0); // silent-ish fail
-        let user_ids = sqlx::query!(
+        let member_ids = sqlx::query!(
             "SELECT id FROM members WHERE guild_id = $1",
             guild_id as i64,
         )
@@ -393,16 +558,14 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         .await?
         .into_iter()
         .map(|r| r.id as u64)
-        .map(|u| async move {
-            self.fetch_member_permissions(guild_id, u, Some(channel_id))
-                .await
-                .map(|p| (u, p))
-        })
-        .collect::<TryJoinAll<_>>()
-        .await?
-        .into_iter()
-        .filter_map(|(u, p): (_, Permissions)| p.contains(Permissions::VIEW_CHANNEL).then_some(u))
-        .collect();
+        .collect::<Vec<_>>();
+
+        let user_ids = self
+            .fetch_permissions_for_members(guild_id, &member_ids, Some(channel_id))
+            .await?
+            .into_iter()
+            .filter_map(|(u, p)| p.contains(Permissions::VIEW_CHANNEL).then_some(u))
+            .collect();
 
         Ok(user_ids)
     }
@@ -530,11 +693,9 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         channel_ids: &[i64],
     ) -> crate::Result<HashMap<u64, Message>> {
         let message_ids: Vec<u64> = sqlx::query!(
-            r#"SELECT id FROM messages
-            WHERE channel_id = ANY($1::BIGINT[])
-            AND id IN (
-                SELECT MAX(id) FROM messages GROUP BY channel_id
-            )"#,
+            r#"SELECT DISTINCT ON (channel_id) id FROM messages
+            WHERE channel_id = ANY($1::BIGINT[]) AND deleted_at IS NULL
+            ORDER BY channel_id, id DESC"#,
             channel_ids,
         )
         .fetch_all(self.executor())
@@ -552,9 +713,10 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
     /// # Errors
     /// * If an error occurs with fetching the channels.
     async fn fetch_all_channels_in_guild(&self, guild_id: u64) -> crate::Result<Vec<GuildChannel>> {
-        let channels: Vec<ChannelRecord> = query_channels!("guild_id = $1", guild_id as i64)
-            .fetch_all(self.executor())
-            .await?;
+        let channels: Vec<ChannelRecord> =
+            query_channels!("guild_id = $1 AND type <> 'thread'", guild_id as i64)
+                .fetch_all(self.executor())
+                .await?;
 
         let mut overwrites = self
             .fetch_channel_overwrites_where("guild_id = $1", guild_id)
@@ -581,17 +743,153 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         Ok(channels)
     }
 
-    /// Fetches all DM and group channels for a user.
+    /// Fetches all channels in a guild that have not seen any activity (e.g. messages sent or
+    /// voice joins) since the given timestamp, ordered by least recently active first.
     ///
     /// # Errors
     /// * If an error occurs with fetching the channels.
-    async fn fetch_all_dm_channels_for_user(&self, user_id: u64) -> crate::Result<Vec<DmChannel>> {
+    async fn fetch_inactive_channels(
+        &self,
+        guild_id: u64,
+        since: DateTime<Utc>,
+    ) -> crate::Result<Vec<GuildChannel>> {
+        let channels: Vec<ChannelRecord> = query_channels!(
+            "guild_id = $1 AND type NOT IN ('category', 'thread') AND last_activity_at < $2
+            ORDER BY last_activity_at ASC",
+            guild_id as i64,
+            since
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        let mut overwrites = self
+            .fetch_channel_overwrites_where("guild_id = $1", guild_id)
+            .await?;
+
+        let channel_ids: Vec<_> = channels.iter().map(|c| c.id).collect();
+        let mut last_messages = self.fetch_last_message_map(&channel_ids).await?;
+
+        channels
+            .into_iter()
+            .map(|c| {
+                let id = c.id as u64;
+                c.into_guild_channel(
+                    overwrites
+                        .get_mut(&id)
+                        .unwrap_or(&mut None)
+                        .take()
+                        .unwrap_or_default(),
+                    last_messages.remove(&id),
+                )
+            })
+            .collect()
+    }
+
+    /// Marks the channel as active as of now. This is called automatically when a message is
+    /// created, and should be called when a user joins a voice channel. If the channel is an
+    /// archived thread, it is unarchived. If the channel is a DM channel hidden by any of its
+    /// recipients, it is unhidden for them.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with updating the channel.
+    async fn touch_channel(&mut self, channel_id: u64) -> crate::Result<()> {
+        sqlx::query!(
+            "UPDATE channels SET last_activity_at = CURRENT_TIMESTAMP WHERE id = $1",
+            channel_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+        sqlx::query!(
+            "UPDATE threads SET archived_at = NULL WHERE channel_id = $1 AND archived_at IS NOT NULL",
+            channel_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+        sqlx::query!(
+            "UPDATE channel_recipients SET hidden = FALSE WHERE channel_id = $1 AND hidden",
+            channel_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Hides (closes) the given DM channel for the given user, so that it is excluded from
+    /// [`Self::fetch_all_dm_channels_for_user`] unless hidden channels are requested. The channel
+    /// is unhidden automatically when a new message is sent in it.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user is not a recipient of the channel.
+    /// * If an error occurs with hiding the channel.
+    async fn hide_dm_channel(&mut self, channel_id: u64, user_id: u64) -> crate::Result<()> {
+        self.set_dm_channel_hidden(channel_id, user_id, true).await
+    }
+
+    /// Unhides the given DM channel for the given user. See [`Self::hide_dm_channel`].
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user is not a recipient of the channel.
+    /// * If an error occurs with unhiding the channel.
+    async fn unhide_dm_channel(&mut self, channel_id: u64, user_id: u64) -> crate::Result<()> {
+        self.set_dm_channel_hidden(channel_id, user_id, false).await
+    }
+
+    /// Internally used, see [`Self::hide_dm_channel`] and [`Self::unhide_dm_channel`] instead.
+    async fn set_dm_channel_hidden(
+        &mut self,
+        channel_id: u64,
+        user_id: u64,
+        hidden: bool,
+    ) -> crate::Result<()> {
+        let result = sqlx::query!(
+            "UPDATE channel_recipients SET hidden = $1 WHERE channel_id = $2 AND user_id = $3",
+            hidden,
+            channel_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                entity: "channel".to_string(),
+                message: format!("You are not a recipient of any DM channels with ID {channel_id}"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fetches all DM and group channels for a user. Channels the user has hidden (see
+    /// [`Self::hide_dm_channel`]) are only included if ``include_hidden`` is `true`.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the channels.
+    async fn fetch_all_dm_channels_for_user(
+        &self,
+        user_id: u64,
+        include_hidden: bool,
+    ) -> crate::Result<Vec<DmChannel>> {
         let channels = query_channels!(
             "(c.type = 'dm' OR c.type = 'group')
             AND c.id IN (
-                SELECT channel_id FROM channel_recipients WHERE user_id = $1
+                SELECT channel_id FROM channel_recipients
+                WHERE user_id = $1 AND ($2 OR NOT hidden)
             )",
-            user_id as i64
+            user_id as i64,
+            include_hidden
         )
         .fetch_all(self.executor())
         .await?;
@@ -627,12 +925,30 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         Ok(resolved)
     }
 
+    /// Replaces the permission overwrites of the given channel with the given overwrites. Only
+    /// overwrites that were added, removed, or changed are written. Returns the IDs of the targets
+    /// whose overwrites were affected.
     async fn bulk_register_overwrites(
         &mut self,
         guild_id: u64,
         channel_id: u64,
         overwrites: &[PermissionOverwrite],
-    ) -> crate::Result<()> {
+    ) -> crate::Result<Vec<u64>> {
+        let existing = sqlx::query!(
+            "SELECT target_id, allow, deny FROM channel_overwrites WHERE channel_id = $1",
+            channel_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| (r.target_id as u64, (r.allow, r.deny)))
+        .collect::<HashMap<_, _>>();
+
+        let removed = existing
+            .keys()
+            .filter(|id| !overwrites.iter().any(|o| o.id == **id))
+            .map(|id| *id as i64)
+            .collect_vec();
         let (targets, (allow, deny)) = overwrites
             .iter()
             .map(|o| {
@@ -641,43 +957,170 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
                     (o.permissions.allow.bits(), o.permissions.deny.bits()),
                 )
             })
+            .filter(|(id, pair)| existing.get(&(*id as u64)) != Some(pair))
             .unzip::<_, _, Vec<_>, (Vec<_>, Vec<_>)>();
 
-        sqlx::query!(
-            "DELETE FROM channel_overwrites WHERE channel_id = $1",
-            channel_id as i64
+        if !removed.is_empty() {
+            sqlx::query!(
+                "DELETE FROM channel_overwrites WHERE channel_id = $1 AND target_id = ANY($2)",
+                channel_id as i64,
+                &removed,
+            )
+            .execute(self.transaction())
+            .await?;
+        }
+        if !targets.is_empty() {
+            sqlx::query(
+                r"INSERT INTO
+                    channel_overwrites (channel_id, guild_id, target_id, allow, deny)
+                SELECT
+                    $1, $2, out.*
+                FROM
+                    UNNEST($3, $4, $5)
+                AS
+                    out(target_id, allow, deny)
+                ON CONFLICT (guild_id, channel_id, target_id)
+                DO UPDATE SET allow = EXCLUDED.allow, deny = EXCLUDED.deny",
+            )
+            .bind(channel_id as i64)
+            .bind(guild_id as i64)
+            .bind(&targets)
+            .bind(allow)
+            .bind(deny)
+            .execute(self.transaction())
+            .await?;
+        }
+
+        Ok(removed
+            .into_iter()
+            .chain(targets)
+            .map(|id| id as u64)
+            .collect())
+    }
+
+    /// Invalidates the cached permissions in the given channel of all members affected by
+    /// changes to the overwrites of the given targets.
+    async fn invalidate_overwrite_targets(
+        &mut self,
+        guild_id: u64,
+        channel_id: u64,
+        targets: Vec<u64>,
+    ) -> crate::Result<()> {
+        if targets.is_empty() {
+            return Ok(());
+        }
+        // Permissions in merged channels are resolved from their sources
+        let channel_ids = sqlx::query!(
+            "SELECT merged_id FROM merged_channel_sources WHERE source_id = $1",
+            channel_id as i64,
         )
-        .execute(self.transaction())
-        .await?;
-        sqlx::query(
-            r"INSERT INTO
-                channel_overwrites (channel_id, guild_id, target_id, allow, deny)
-            SELECT
-                $1, $2, out.*
-            FROM
-                UNNEST($3, $4, $5)
-            AS
-                out(target_id, allow, deny)",
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.merged_id as u64)
+        .chain(std::iter::once(channel_id))
+        .collect_vec();
+
+        // The default role applies to every member
+        if targets.contains(&with_model_type(guild_id, ModelType::Role)) {
+            for channel_id in channel_ids {
+                cache::delete_permissions_for_channel(guild_id, channel_id).await?;
+            }
+            return Ok(());
+        }
+
+        let (roles, mut users): (Vec<_>, Vec<_>) = targets
+            .into_iter()
+            .partition(|id| SnowflakeReader::new(*id).model_type() == ModelType::Role);
+
+        if !roles.is_empty() {
+            users.extend(
+                sqlx::query!(
+                    "SELECT DISTINCT user_id FROM role_data
+                    WHERE guild_id = $1 AND role_id = ANY($2)",
+                    guild_id as i64,
+                    &roles.into_iter().map(|id| id as i64).collect_vec(),
+                )
+                .fetch_all(self.transaction())
+                .await?
+                .into_iter()
+                .map(|r| r.user_id as u64),
+            );
+        }
+
+        for channel_id in channel_ids {
+            cache::delete_permissions_for_users_in_channel(guild_id, &users, channel_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Registers the source channels of the given merged channel. Sources must be unique text or
+    /// announcement channels in the same guild, and there must be between 2 and
+    /// [`MAX_MERGED_CHANNEL_SOURCES`] of them.
+    ///
+    /// # Errors
+    /// * If the number of sources is invalid or there are duplicate sources.
+    /// * If any source is not a text or announcement channel in the guild.
+    /// * If an error occurs with registering the sources.
+    async fn register_merged_channel_sources(
+        &mut self,
+        guild_id: u64,
+        channel_id: u64,
+        source_ids: &[u64],
+    ) -> crate::Result<()> {
+        if !(2..=MAX_MERGED_CHANNEL_SOURCES).contains(&source_ids.len()) {
+            return Err(Error::InvalidField {
+                field: "source_ids".to_string(),
+                message: format!(
+                    "Merged channels must have between 2 and {MAX_MERGED_CHANNEL_SOURCES} sources"
+                ),
+            });
+        }
+        if !source_ids.iter().all_unique() {
+            return Err(Error::InvalidField {
+                field: "source_ids".to_string(),
+                message: "Source channels must be unique".to_string(),
+            });
+        }
+
+        let source_ids = source_ids.iter().map(|&id| id as i64).collect_vec();
+        let valid = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM channels
+            WHERE id = ANY($1) AND guild_id = $2 AND type IN ('text', 'announcement')"#,
+            &source_ids,
+            guild_id as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .count;
+
+        if valid as usize != source_ids.len() {
+            return Err(Error::InvalidField {
+                field: "source_ids".to_string(),
+                message: "Sources must be text or announcement channels in this guild".to_string(),
+            });
+        }
+
+        sqlx::query!(
+            "INSERT INTO merged_channel_sources (merged_id, source_id)
+            SELECT $1, source_id FROM UNNEST($2::BIGINT[]) AS source_id",
+            channel_id as i64,
+            &source_ids,
         )
-        .bind(channel_id as i64)
-        .bind(guild_id as i64)
-        .bind(targets)
-        .bind(allow)
-        .bind(deny)
         .execute(self.transaction())
         .await?;
 
         Ok(())
     }
 
-    /// Creates a new channel in a guild from a payload. Payload must be validated prior to creating
-    /// the channel.
+    /// Creates a new channel in a guild from a payload.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
     /// * If an error occurs with creating the channel.
     #[allow(clippy::too_many_lines)]
     async fn create_guild_channel(
@@ -686,11 +1129,18 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         channel_id: u64,
         payload: CreateGuildChannelPayload,
     ) -> crate::Result<GuildChannel> {
-        let (topic, user_limit) = match &payload.info {
+        payload.validate()?;
+
+        let (topic, user_limit, sort_order) = match &payload.info {
             CreateGuildChannelInfo::Text { topic }
-            | CreateGuildChannelInfo::Announcement { topic } => (topic.as_ref(), None),
-            CreateGuildChannelInfo::Voice { user_limit } => (None, Some(user_limit)),
-            CreateGuildChannelInfo::Category => (None, None),
+            | CreateGuildChannelInfo::Announcement { topic }
+            | CreateGuildChannelInfo::Merged { topic, .. } => (topic.as_ref(), None, None),
+            CreateGuildChannelInfo::Voice { user_limit } => (None, Some(user_limit), None),
+            CreateGuildChannelInfo::Category => (None, None, None),
+            CreateGuildChannelInfo::Forum {
+                topic,
+                default_sort_order,
+            } => (topic.as_ref(), None, Some(default_sort_order.name())),
         };
 
         let kind = payload.info.channel_type();
@@ -720,17 +1170,14 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
             }
         };
 
-        if let Some(ref color) = payload.color {
-            color.validate()?;
-        }
         let (color, gradient) = payload.color.as_ref().map(ExtendedColor::to_db).unzip();
         sqlx::query!(
             "INSERT INTO channels (
                 id, guild_id, type, name, position, parent_id, topic,
-                icon, color, gradient, user_limit
+                icon, color, gradient, user_limit, forum_sort_order
             )
             VALUES
-                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::gradient_type, $11)
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::gradient_type, $11, $12)
             ",
             channel_id as i64,
             guild_id as i64,
@@ -739,10 +1186,11 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
             position as i16,
             postgres_parent_id,
             topic,
-            payload.icon,
+            payload.icon.as_deref(),
             color.flatten(),
             gradient.flatten() as _,
             user_limit.map(|&limit| limit as i16),
+            sort_order,
         )
         .execute(self.transaction())
         .await?;
@@ -751,31 +1199,15 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
             self.bulk_register_overwrites(guild_id, channel_id, overwrites)
                 .await?;
         }
-
-        let info = match payload.info {
-            CreateGuildChannelInfo::Text { topic, .. }
-            | CreateGuildChannelInfo::Announcement { topic, .. } => {
-                let info = TextBasedGuildChannelInfo {
-                    topic,
-                    ..Default::default()
-                };
-
-                match kind {
-                    ChannelType::Text => GuildChannelInfo::Text(info),
-                    ChannelType::Announcement => GuildChannelInfo::Announcement(info),
-                    _ => unreachable!(),
-                }
-            }
-            CreateGuildChannelInfo::Voice { user_limit, .. } => {
-                GuildChannelInfo::Voice { user_limit }
-            }
-            CreateGuildChannelInfo::Category => GuildChannelInfo::Category,
-        };
+        if let CreateGuildChannelInfo::Merged { ref source_ids, .. } = payload.info {
+            self.register_merged_channel_sources(guild_id, channel_id, source_ids)
+                .await?;
+        }
 
         Ok(GuildChannel {
             id: channel_id,
             guild_id,
-            info,
+            info: guild_channel_info_from_payload(payload.info),
             name: payload.name,
             color: payload.color,
             icon: payload.icon,
@@ -785,14 +1217,203 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         })
     }
 
-    /// Creates a new DM-type channel from a payload. Payload must be validated prior to creating
-    /// the channel.
+    /// Creates many channels in a guild at once, e.g. when setting up a guild from a template.
+    /// Each payload is paired with the ID of the channel to create. Every payload is validated
+    /// before any of the channels are created.
+    ///
+    /// Positions are resolved once for the whole batch, in the order the payloads are given.
+    /// Payloads may specify the ID of a category created in the same batch as their `parent_id`.
+    /// The created channels are returned in the same order as the payloads.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If any of the payloads are invalid.
+    /// * If an error occurs with creating the channels.
+    #[allow(clippy::too_many_lines)]
+    async fn bulk_create_guild_channels(
+        &mut self,
+        guild_id: u64,
+        payloads: Vec<(u64, CreateGuildChannelPayload)>,
+    ) -> crate::Result<Vec<GuildChannel>> {
+        if payloads.is_empty() {
+            return Ok(Vec::new());
+        }
+        for (_, payload) in &payloads {
+            payload.validate()?;
+        }
+
+        let mut next_positions: HashMap<(Option<u64>, bool), u16> = sqlx::query!(
+            r#"SELECT
+                parent_id,
+                type = 'category' AS "is_category!",
+                MAX(position) AS "position!"
+            FROM
+                channels
+            WHERE
+                guild_id = $1
+            GROUP BY
+                parent_id, type = 'category'
+            "#,
+            guild_id as i64,
+        )
+        .fetch_all(get_pool())
+        .await?
+        .into_iter()
+        .map(|r| {
+            (
+                (r.parent_id.map(|id| id as u64), r.is_category),
+                r.position as u16 + 1,
+            )
+        })
+        .collect();
+
+        let len = payloads.len();
+        let mut ids = Vec::with_capacity(len);
+        let mut kinds = Vec::with_capacity(len);
+        let mut names = Vec::with_capacity(len);
+        let mut positions = Vec::with_capacity(len);
+        let mut parent_ids = Vec::with_capacity(len);
+        let mut topics = Vec::with_capacity(len);
+        let mut icons = Vec::with_capacity(len);
+        let mut colors = Vec::with_capacity(len);
+        let mut gradients = Vec::with_capacity(len);
+        let mut user_limits = Vec::with_capacity(len);
+        let mut sort_orders = Vec::with_capacity(len);
+        let (mut overwrite_channels, mut overwrite_targets) = (Vec::new(), Vec::new());
+        let (mut overwrite_allow, mut overwrite_deny) = (Vec::new(), Vec::new());
+        let mut merged_sources = Vec::new();
+
+        let mut channels = Vec::with_capacity(len);
+        for (channel_id, payload) in payloads {
+            let kind = payload.info.channel_type();
+            let position = next_positions
+                .entry((payload.parent_id, kind == ChannelType::Category))
+                .or_insert(0);
+            let (topic, user_limit, sort_order) = match &payload.info {
+                CreateGuildChannelInfo::Text { topic }
+                | CreateGuildChannelInfo::Announcement { topic } => (topic.clone(), None, None),
+                CreateGuildChannelInfo::Merged { topic, source_ids } => {
+                    merged_sources.push((channel_id, source_ids.clone()));
+                    (topic.clone(), None, None)
+                }
+                CreateGuildChannelInfo::Voice { user_limit } => {
+                    (None, Some(*user_limit as i16), None)
+                }
+                CreateGuildChannelInfo::Category => (None, None, None),
+                CreateGuildChannelInfo::Forum {
+                    topic,
+                    default_sort_order,
+                } => (topic.clone(), None, Some(default_sort_order.name())),
+            };
+            let (color, gradient) = payload.color.as_ref().map(ExtendedColor::to_db).unzip();
+
+            ids.push(channel_id as i64);
+            kinds.push(kind.name());
+            names.push(payload.name.trim().to_string());
+            positions.push(*position as i16);
+            parent_ids.push(payload.parent_id.map(|id| id as i64));
+            topics.push(topic);
+            icons.push(payload.icon.clone().map(String::from));
+            colors.push(color.flatten());
+            gradients.push(gradient.flatten());
+            user_limits.push(user_limit);
+            sort_orders.push(sort_order);
+
+            let overwrites = payload.overwrites.unwrap_or_default();
+            for overwrite in &overwrites {
+                overwrite_channels.push(channel_id as i64);
+                overwrite_targets.push(overwrite.id as i64);
+                overwrite_allow.push(overwrite.permissions.allow.bits());
+                overwrite_deny.push(overwrite.permissions.deny.bits());
+            }
+
+            channels.push(GuildChannel {
+                id: channel_id,
+                guild_id,
+                info: guild_channel_info_from_payload(payload.info),
+                name: payload.name,
+                color: payload.color,
+                icon: payload.icon,
+                position: *position,
+                parent_id: payload.parent_id,
+                overwrites,
+            });
+            *position += 1;
+        }
+
+        sqlx::query(
+            r"INSERT INTO channels (
+                id, guild_id, type, name, position, parent_id, topic,
+                icon, color, gradient, user_limit, forum_sort_order
+            )
+            SELECT
+                out.id, $1, out.type, out.name, out.position, out.parent_id, out.topic,
+                out.icon, out.color, out.gradient, out.user_limit, out.forum_sort_order
+            FROM
+                UNNEST($2, $3, $4, $5, $6, $7, $8, $9, $10::gradient_type[], $11, $12)
+            AS
+                out(
+                    id, type, name, position, parent_id, topic, icon, color, gradient, user_limit,
+                    forum_sort_order
+                )",
+        )
+        .bind(guild_id as i64)
+        .bind(ids)
+        .bind(kinds)
+        .bind(names)
+        .bind(positions)
+        .bind(parent_ids)
+        .bind(topics)
+        .bind(icons)
+        .bind(colors)
+        .bind(gradients)
+        .bind(user_limits)
+        .bind(sort_orders)
+        .execute(self.transaction())
+        .await?;
+
+        if !overwrite_channels.is_empty() {
+            sqlx::query(
+                r"INSERT INTO
+                    channel_overwrites (channel_id, guild_id, target_id, allow, deny)
+                SELECT
+                    out.channel_id, $1, out.target_id, out.allow, out.deny
+                FROM
+                    UNNEST($2, $3, $4, $5)
+                AS
+                    out(channel_id, target_id, allow, deny)",
+            )
+            .bind(guild_id as i64)
+            .bind(overwrite_channels)
+            .bind(overwrite_targets)
+            .bind(overwrite_allow)
+            .bind(overwrite_deny)
+            .execute(self.transaction())
+            .await?;
+        }
+
+        // Sources are registered last so that they may be channels created in the same batch
+        for (channel_id, source_ids) in merged_sources {
+            self.register_merged_channel_sources(guild_id, channel_id, &source_ids)
+                .await?;
+        }
+
+        Ok(channels)
+    }
+
+    /// Creates a new DM-type channel from a payload.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
+    /// * If any recipient of a group DM has blocked the user or has privacy settings that
+    /// disallow being added to the group.
     /// * If an error occurs with creating the channel.
     async fn create_dm_channel(
         &mut self,
@@ -800,6 +1421,8 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         channel_id: u64,
         payload: CreateDmChannelPayload,
     ) -> crate::Result<DmChannel> {
+        payload.validate()?;
+
         let kind = payload.channel_type();
         let (name, owner_id, recipient_ids) = match payload.clone() {
             CreateDmChannelPayload::Dm { recipient_id } => {
@@ -841,9 +1464,16 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
                 name,
                 mut recipient_ids,
             } => {
-                if !recipient_ids.contains(&user_id) {
-                    recipient_ids.push(user_id);
-                }
+                recipient_ids.retain(|&id| id != user_id);
+                get_pool()
+                    .assert_user_can_interact_with_all(
+                        user_id,
+                        &recipient_ids,
+                        UserInteractionType::GroupDm,
+                    )
+                    .await?;
+
+                recipient_ids.push(user_id);
                 (Some(name), Some(user_id), recipient_ids)
             }
         };
@@ -890,14 +1520,227 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         })
     }
 
-    /// Edits a channel from a payload. Payload must be validated prior to updating the channel.
-    /// Returns a tuple ``(old_channel, new_channel)``.
+    /// Asserts the given channel is a group DM channel.
+    ///
+    /// # Errors
+    /// * If the channel is not found or is not a group DM channel.
+    async fn assert_channel_is_group(&self, channel_id: u64) -> crate::Result<()> {
+        let channel = self
+            .inspect_channel(channel_id)
+            .await?
+            .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?;
+
+        if channel.channel_type != ChannelType::Group {
+            return Err(Error::InvalidField {
+                field: "channel_id".to_string(),
+                message: "Channel is not a group DM channel".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds the given users to a group DM channel on behalf of ``user_id``, who must be a
+    /// recipient of the channel. Users that are already recipients are ignored. A system join
+    /// message is sent for every added user, with IDs generated on the given node; the messages
+    /// are returned in the order the users were given.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the channel is not a group DM channel or the user is not a recipient of it.
+    /// * If adding the users would exceed the maximum number of recipients in a group DM.
+    /// * If any of the users has blocked the user or has privacy settings that disallow being
+    /// added to the group.
+    /// * If an error occurs with adding the recipients.
+    async fn add_group_recipients(
+        &mut self,
+        channel_id: u64,
+        user_id: u64,
+        recipient_ids: &[u64],
+        node_id: u8,
+    ) -> crate::Result<Vec<Message>> {
+        get_pool().assert_channel_is_group(channel_id).await?;
+        get_pool()
+            .assert_user_is_recipient(channel_id, user_id)
+            .await?;
+
+        let existing = get_pool().fetch_channel_recipients(channel_id).await?;
+        let recipient_ids = recipient_ids
+            .iter()
+            .copied()
+            .unique()
+            .filter(|id| !existing.contains(id))
+            .collect_vec();
+        if recipient_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let max = crate::limits().max_group_dm_recipients;
+        if existing.len() + recipient_ids.len() > max {
+            return Err(Error::InvalidField {
+                field: "recipient_ids".to_string(),
+                message: format!("Group DMs can have at most {max} recipients"),
+            });
+        }
+        for &recipient_id in &recipient_ids {
+            get_pool()
+                .assert_user_can_interact_with(user_id, recipient_id, UserInteractionType::GroupDm)
+                .await?;
+        }
+
+        sqlx::query!(
+            "INSERT INTO channel_recipients (channel_id, user_id)
+            SELECT $1, user_id FROM UNNEST($2::BIGINT[]) AS user_id",
+            channel_id as i64,
+            &recipient_ids.iter().map(|&id| id as i64).collect_vec(),
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let mut messages = Vec::with_capacity(recipient_ids.len());
+        for user_id in recipient_ids {
+            let message_id = generate_snowflake(ModelType::Message, node_id);
+            messages.push(
+                self.send_system_message(channel_id, message_id, MessageInfo::Join { user_id })
+                    .await?,
+            );
+        }
+        Ok(messages)
+    }
+
+    /// Removes ``recipient_id`` from a group DM channel on behalf of ``user_id``. Users may always
+    /// remove themselves (leave), but only the owner of the group may remove other recipients.
+    ///
+    /// If the owner leaves, ownership is transferred to the remaining recipient with the lowest
+    /// ID. A system leave message is sent with an ID generated on the given node and returned,
+    /// unless the last recipient left, in which case the channel is deleted and `None` is
+    /// returned.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the channel is not a group DM channel or either user is not a recipient of it.
+    /// * If the user is removing someone else and is not the owner of the group.
+    /// * If an error occurs with removing the recipient.
+    async fn remove_group_recipient(
+        &mut self,
+        channel_id: u64,
+        user_id: u64,
+        recipient_id: u64,
+        node_id: u8,
+    ) -> crate::Result<Option<Message>> {
+        get_pool().assert_channel_is_group(channel_id).await?;
+        get_pool()
+            .assert_user_is_recipient(channel_id, user_id)
+            .await?;
+        if user_id != recipient_id {
+            get_pool()
+                .assert_user_is_group_owner(channel_id, user_id)
+                .await?;
+            get_pool()
+                .assert_user_is_recipient(channel_id, recipient_id)
+                .await?;
+        }
+
+        sqlx::query!(
+            "DELETE FROM channel_recipients WHERE channel_id = $1 AND user_id = $2",
+            channel_id as i64,
+            recipient_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let next_owner = sqlx::query!(
+            "SELECT MIN(user_id) AS user_id FROM channel_recipients WHERE channel_id = $1",
+            channel_id as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .user_id;
+
+        let Some(next_owner) = next_owner else {
+            self.delete_channel(channel_id).await?;
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "UPDATE channels SET owner_id = $1 WHERE id = $2 AND owner_id = $3",
+            next_owner,
+            channel_id as i64,
+            recipient_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+        cache::remove_channel(channel_id).await?;
+
+        let message_id = generate_snowflake(ModelType::Message, node_id);
+        let message = self
+            .send_system_message(
+                channel_id,
+                message_id,
+                MessageInfo::Leave {
+                    user_id: recipient_id,
+                },
+            )
+            .await?;
+        Ok(Some(message))
+    }
+
+    /// Transfers ownership of a group DM channel from ``owner_id`` to ``new_owner_id``, who must
+    /// be a recipient of the channel.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the channel is not a group DM channel.
+    /// * If ``owner_id`` is not the owner of the group.
+    /// * If ``new_owner_id`` is the current owner or is not a recipient of the group.
+    /// * If an error occurs with transferring ownership.
+    async fn transfer_group_ownership(
+        &mut self,
+        channel_id: u64,
+        owner_id: u64,
+        new_owner_id: u64,
+    ) -> crate::Result<()> {
+        get_pool().assert_channel_is_group(channel_id).await?;
+        get_pool()
+            .assert_user_is_group_owner(channel_id, owner_id)
+            .await?;
+        if owner_id == new_owner_id {
+            return Err(Error::CannotActOnSelf {
+                message: "You already own this group DM channel".to_string(),
+            });
+        }
+        get_pool()
+            .assert_user_is_recipient(channel_id, new_owner_id)
+            .await?;
+
+        sqlx::query!(
+            "UPDATE channels SET owner_id = $1 WHERE id = $2",
+            new_owner_id as i64,
+            channel_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        cache::remove_channel(channel_id).await?;
+        Ok(())
+    }
+
+    /// Edits a channel from a payload. Returns a tuple ``(old_channel, new_channel)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid.
     /// * If an error occurs with updating the channel.
     /// * If the channel is not found.
     async fn edit_channel(
@@ -905,6 +1748,8 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         channel_id: u64,
         payload: EditChannelPayload,
     ) -> crate::Result<(Channel, Channel)> {
+        payload.validate()?;
+
         let mut channel = get_pool()
             .fetch_channel(channel_id)
             .await?
@@ -926,9 +1771,22 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         channel.set_icon(
             payload
                 .icon
-                .into_option_or_if_absent_then(|| channel.icon().map(ToOwned::to_owned)),
+                .into_option_or_if_absent_then(|| channel.icon().cloned()),
         );
 
+        let sort_order = payload.default_sort_order.and_then(|sort_order| {
+            if let Channel::Guild(GuildChannel {
+                info: GuildChannelInfo::Forum(ref mut info),
+                ..
+            }) = channel
+            {
+                info.default_sort_order = sort_order;
+                Some(sort_order.name())
+            } else {
+                None
+            }
+        });
+
         let limit = payload.user_limit.and_then(|limit| {
             if let Channel::Guild(GuildChannel {
                 info:
@@ -949,9 +1807,11 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
             let guild_id = channel.guild_id;
 
             if let Some(ref overwrites) = payload.overwrites {
-                self.bulk_register_overwrites(guild_id, channel_id, overwrites)
+                let targets = self
+                    .bulk_register_overwrites(guild_id, channel_id, overwrites)
+                    .await?;
+                self.invalidate_overwrite_targets(guild_id, channel_id, targets)
                     .await?;
-                cache::delete_permissions_for_channel(guild_id, channel_id).await?;
                 channel.overwrites.clone_from(overwrites);
             }
 
@@ -961,9 +1821,6 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
                 .into_option_or_if_absent(channel.color.clone());
         }
 
-        if let Maybe::Value(ref color) = payload.color {
-            color.validate()?;
-        }
         let (color, gradient) = payload
             .color
             .into_option()
@@ -975,14 +1832,16 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
             r"UPDATE channels
             SET
                 name = $1, topic = $2, icon = $3, user_limit = $4,
-                color = $5, gradient = $6::gradient_type
-            WHERE id = $7",
+                color = $5, gradient = $6::gradient_type,
+                forum_sort_order = COALESCE($7, forum_sort_order)
+            WHERE id = $8",
             channel.name().map(str::trim),
             channel.topic(),
-            channel.icon(),
+            channel.icon().map(Asset::as_str),
             limit,
             color.flatten(),
             gradient.flatten() as _,
+            sort_order,
             channel_id as i64,
         )
         .execute(self.transaction())
@@ -1047,6 +1906,9 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
     }
 
     /// Marks a channel as read up to the given message ID for the user in the given channel.
+    /// Returns the mentions that were cleared by this acknowledgement and the number of unread
+    /// messages and mentions that remain after it, so that they can be dispatched to the user's
+    /// other sessions.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
@@ -1054,7 +1916,59 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
     ///
     /// # Errors
     /// * If an error occurs with marking the channel as read.
-    async fn ack(&mut self, user_id: u64, channel_id: u64, message_id: u64) -> crate::Result<()> {
+    async fn ack(
+        &mut self,
+        user_id: u64,
+        channel_id: u64,
+        message_id: u64,
+    ) -> crate::Result<ChannelAckState> {
+        let previous = sqlx::query!(
+            "SELECT last_message_id FROM channel_acks WHERE channel_id = $1 AND user_id = $2",
+            channel_id as i64,
+            user_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .and_then(|r| r.last_message_id);
+
+        let state = sqlx::query!(
+            r#"SELECT
+                COALESCE(
+                    array_agg(m.id ORDER BY m.id) FILTER (WHERE m.id <= $3 AND m.mentioned),
+                    '{}'
+                ) AS "cleared_mentions!",
+                COUNT(*) FILTER (WHERE m.id > $3) AS "unread_count!",
+                COUNT(*) FILTER (WHERE m.id > $3 AND m.mentioned) AS "mention_count!"
+            FROM (
+                SELECT
+                    m.id,
+                    (
+                        $1 = ANY(m.mentions)
+                        OR c.guild_id = ANY(m.mentions)
+                        OR m.mentions && (
+                            SELECT array_agg(role_id) FROM role_data
+                            WHERE guild_id = c.guild_id AND user_id = $1
+                        )
+                    ) AS mentioned
+                FROM messages m
+                INNER JOIN channels c ON m.channel_id = c.id
+                WHERE
+                    m.channel_id = $2
+                AND
+                    m.deleted_at IS NULL
+                AND
+                    m.author_id IS DISTINCT FROM $1
+                AND
+                    ($4::BIGINT IS NULL OR m.id > $4)
+            ) m"#,
+            user_id as i64,
+            channel_id as i64,
+            message_id as i64,
+            previous,
+        )
+        .fetch_one(self.transaction())
+        .await?;
+
         sqlx::query!(
             r"INSERT INTO channel_acks (
                 channel_id, user_id, last_message_id
@@ -1069,7 +1983,52 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         .execute(self.transaction())
         .await?;
 
-        Ok(())
+        Ok(ChannelAckState {
+            cleared_mentions: state
+                .cleared_mentions
+                .into_iter()
+                .map(|id| id as u64)
+                .collect(),
+            unread_count: state.unread_count as u32,
+            mention_count: state.mention_count as u32,
+        })
+    }
+
+    /// Marks every channel in the given guild as read up to its latest message for the user.
+    /// Returns the channels that were acknowledged, excluding those that were already read up to
+    /// their latest message.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with marking the channels as read.
+    async fn ack_guild(&mut self, user_id: u64, guild_id: u64) -> crate::Result<Vec<AckedChannel>> {
+        Ok(sqlx::query!(
+            r#"INSERT INTO channel_acks (channel_id, user_id, last_message_id)
+            SELECT m.channel_id, $1, MAX(m.id)
+            FROM messages m
+            INNER JOIN channels c ON c.id = m.channel_id
+            WHERE c.guild_id = $2 AND m.deleted_at IS NULL
+            GROUP BY m.channel_id
+            ON CONFLICT (channel_id, user_id)
+            DO UPDATE SET last_message_id = EXCLUDED.last_message_id
+            WHERE
+                channel_acks.last_message_id IS NULL
+                OR channel_acks.last_message_id < EXCLUDED.last_message_id
+            RETURNING channel_id, last_message_id AS "last_message_id!""#,
+            user_id as i64,
+            guild_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| AckedChannel {
+            channel_id: r.channel_id as u64,
+            last_message_id: r.last_message_id as u64,
+        })
+        .collect())
     }
 
     /// Fetches a mapping of channel IDs to the last message ID that the user has read up to.
@@ -1136,6 +2095,69 @@ pub trait ChannelDbExt<'t>: DbExt<'t> {
         }
         Ok(unacked.into_values().collect())
     }
+
+    /// Fetches the number of unread messages and mentions in every guild channel observable by
+    /// the user and every DM channel the user is a recipient of. Channels without any unread
+    /// messages are omitted. Messages sent by the user themselves are never counted as unread.
+    ///
+    /// # Errors
+    /// * If an error occurs while counting unread messages.
+    async fn fetch_unread_counts(
+        &self,
+        user_id: u64,
+        guilds: &[Guild],
+    ) -> crate::Result<Vec<UnreadCount>> {
+        let channel_ids = self
+            .fetch_observable_channel_ids(user_id, guilds)
+            .await?
+            .into_iter()
+            .map(|id| id as i64)
+            .collect_vec();
+
+        Ok(sqlx::query!(
+            r#"SELECT
+                m.channel_id,
+                COUNT(*) AS "unread_count!",
+                COUNT(*) FILTER (WHERE
+                    $1 = ANY(m.mentions)
+                    OR c.guild_id = ANY(m.mentions)
+                    OR m.mentions && (
+                        SELECT array_agg(role_id) FROM role_data
+                        WHERE guild_id = c.guild_id AND user_id = $1
+                    )
+                ) AS "mention_count!"
+            FROM messages m
+            INNER JOIN channels c ON m.channel_id = c.id
+            LEFT JOIN channel_acks a ON m.channel_id = a.channel_id AND a.user_id = $1
+            WHERE
+                (
+                    m.channel_id = ANY($2::BIGINT[])
+                    OR m.channel_id IN (
+                        SELECT channel_id FROM channel_recipients WHERE user_id = $1
+                    )
+                )
+            AND
+                m.deleted_at IS NULL
+            AND
+                m.author_id IS DISTINCT FROM $1
+            AND (
+                a.last_message_id IS NULL
+                OR m.id > a.last_message_id
+            )
+            GROUP BY m.channel_id"#,
+            user_id as i64,
+            &channel_ids,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| UnreadCount {
+            channel_id: r.channel_id as u64,
+            unread_count: r.unread_count as u32,
+            mention_count: r.mention_count as u32,
+        })
+        .collect())
+    }
 }
 
 impl<'t, T> ChannelDbExt<'t> for T where T: DbExt<'t> {}
@@ -0,0 +1,78 @@
+//! Hooks for transforming message content before it is stored in the database.
+//!
+//! By default message content and attachment alt text are stored as-is. Deployments that want to
+//! encrypt this data at rest may install their own [`ContentCodec`] once at startup through
+//! [`set_content_codec`], before any messages are created or fetched.
+
+use std::sync::OnceLock;
+
+static CODEC: OnceLock<Box<dyn ContentCodec>> = OnceLock::new();
+
+/// Encodes user-provided content before it is written to the database, and decodes it after it
+/// is read back. This applies to message content and attachment alt text.
+pub trait ContentCodec: Send + Sync + 'static {
+    /// Encodes (e.g. encrypts) the given plaintext into the form it is stored in.
+    ///
+    /// # Errors
+    /// * If the content could not be encoded.
+    fn encode(&self, plaintext: &str) -> crate::Result<String>;
+
+    /// Decodes (e.g. decrypts) stored content back into plaintext.
+    ///
+    /// # Errors
+    /// * If the content could not be decoded.
+    fn decode(&self, stored: &str) -> crate::Result<String>;
+
+    /// Whether stored content is plaintext. Full-text search over message content is only
+    /// possible when this is `true`.
+    fn is_passthrough(&self) -> bool {
+        false
+    }
+}
+
+/// The default codec, which stores content as-is.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PassthroughCodec;
+
+impl ContentCodec for PassthroughCodec {
+    #[inline]
+    fn encode(&self, plaintext: &str) -> crate::Result<String> {
+        Ok(plaintext.to_string())
+    }
+
+    #[inline]
+    fn decode(&self, stored: &str) -> crate::Result<String> {
+        Ok(stored.to_string())
+    }
+
+    #[inline]
+    fn is_passthrough(&self) -> bool {
+        true
+    }
+}
+
+/// Installs the codec used for message content. This may only be called once, and must be
+/// called before any call to [`content_codec`].
+///
+/// # Errors
+/// * If a codec was already installed or read, the given codec is returned back.
+pub fn set_content_codec(codec: impl ContentCodec) -> Result<(), Box<dyn ContentCodec>> {
+    CODEC.set(Box::new(codec))
+}
+
+/// Returns the codec currently in use. If no codec was installed through [`set_content_codec`],
+/// this is [`PassthroughCodec`].
+#[must_use]
+pub fn content_codec() -> &'static dyn ContentCodec {
+    CODEC.get_or_init(|| Box::new(PassthroughCodec)).as_ref()
+}
+
+/// Encodes optional content with the current codec.
+pub(crate) fn encode_content(content: Option<&str>) -> crate::Result<Option<String>> {
+    content.map(|c| content_codec().encode(c)).transpose()
+}
+
+/// Decodes optional stored content with the current codec.
+pub(crate) fn decode_content(content: Option<String>) -> crate::Result<Option<String>> {
+    content.map(|c| content_codec().decode(&c)).transpose()
+}
@@ -0,0 +1,432 @@
+use crate::{
+    db::DbExt,
+    http::{
+        guild::{DiscoverySearchQuery, DiscoverySort, PublishDiscoveryEntryPayload},
+        validate::Validate,
+    },
+    models::{
+        Asset, DiscoveryEntry, DiscoverySearchResult, GuildFlags, GuildMemberCount, PartialGuild,
+    },
+    Error, NotFoundExt,
+};
+
+macro_rules! construct_discovery_entry {
+    ($data:ident) => {{
+        DiscoveryEntry {
+            guild: PartialGuild {
+                id: $data.guild_id as _,
+                name: $data.name,
+                description: $data.guild_description,
+                icon: $data.icon.map(Asset::from_raw),
+                banner: $data.banner.map(Asset::from_raw),
+                owner_id: $data.owner_id as _,
+                flags: GuildFlags::from_bits_truncate($data.flags as _),
+                member_count: Some(GuildMemberCount {
+                    total: $data.member_count as _,
+                    online: None,
+                }),
+                vanity_url: $data.vanity_url,
+            },
+            revision: $data.revision as _,
+            description: $data.description,
+            category: $data.category,
+            keywords: $data.keywords,
+            upvotes: $data.upvotes as _,
+            uses: $data.uses as _,
+            published_at: $data.published_at,
+        }
+    }};
+}
+
+/// Escapes the wildcard characters of a ``LIKE`` pattern.
+pub(crate) fn escape_like(pattern: &str) -> String {
+    pattern
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[async_trait::async_trait]
+pub trait DiscoveryDbExt<'t>: DbExt<'t> {
+    /// Fetches the discovery listing of the given guild.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the listing. If the guild is not listed, `Ok(None)` is
+    /// returned.
+    async fn fetch_discovery_entry(&self, guild_id: u64) -> crate::Result<Option<DiscoveryEntry>> {
+        Ok(sqlx::query!(
+            r#"SELECT
+                d.*,
+                g.name,
+                g.description AS guild_description,
+                g.icon,
+                g.banner,
+                g.owner_id,
+                g.flags,
+                g.vanity_url,
+                (SELECT COUNT(*) FROM members WHERE guild_id = d.guild_id) AS "member_count!"
+            FROM
+                discovery_entries d
+            INNER JOIN
+                guilds g ON g.id = d.guild_id
+            WHERE
+                d.guild_id = $1"#,
+            guild_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| construct_discovery_entry!(r)))
+    }
+
+    /// Searches guild discovery with the given query. Only guilds that are still public are
+    /// returned.
+    ///
+    /// # Errors
+    /// * If the limit is greater than ``100``.
+    /// * If an error occurs with searching guild discovery.
+    async fn search_discovery(
+        &self,
+        query: DiscoverySearchQuery,
+    ) -> crate::Result<DiscoverySearchResult> {
+        if query.limit > 100 {
+            return Err(Error::InvalidField {
+                field: "limit".to_string(),
+                message: "Limit must be between 0 and 100".to_string(),
+            });
+        }
+
+        let keyword = query
+            .keyword
+            .as_deref()
+            .map(str::trim)
+            .filter(|keyword| !keyword.is_empty());
+        let pattern = keyword.map(|keyword| format!("%{}%", escape_like(keyword)));
+
+        let limit = i64::from(query.limit);
+        let offset = i64::from(query.offset);
+
+        // The page of results and the total are counted over the same filters. Parameters from
+        // `$5` onwards are given by the caller.
+        macro_rules! search_discovery {
+            ($select:literal $(, $tail:literal)*; $($arg:expr),*) => {
+                sqlx::query!(
+                    "SELECT " + $select + r#"
+                    FROM
+                        discovery_entries d
+                    INNER JOIN
+                        guilds g ON g.id = d.guild_id
+                    WHERE
+                        g.flags & $1 = $1
+                    AND (
+                        $2::TEXT IS NULL
+                        OR EXISTS(SELECT 1 FROM unnest(d.keywords) k WHERE k = LOWER($2))
+                        OR g.name ILIKE $3
+                        OR d.description ILIKE $3
+                    )
+                    AND
+                        ($4::TEXT IS NULL OR LOWER(d.category) = LOWER($4))"# $(+ $tail)*,
+                    GuildFlags::PUBLIC.bits() as i32,
+                    keyword,
+                    pattern,
+                    query.category,
+                    $($arg),*
+                )
+            };
+        }
+
+        macro_rules! search_page {
+            ($order:literal) => {{
+                let records = search_discovery!(
+                    r#"d.*,
+                    g.name,
+                    g.description AS guild_description,
+                    g.icon,
+                    g.banner,
+                    g.owner_id,
+                    g.flags,
+                    g.vanity_url,
+                    (SELECT COUNT(*) FROM members WHERE guild_id = d.guild_id) AS "member_count!",
+                    COUNT(*) OVER () AS "total!""#,
+                    " ORDER BY ",
+                    $order,
+                    " LIMIT $5 OFFSET $6";
+                    limit,
+                    offset
+                )
+                .fetch_all(self.executor())
+                .await?;
+
+                DiscoverySearchResult {
+                    total: records.first().map_or(0, |r| r.total as u64),
+                    entries: records
+                        .into_iter()
+                        .map(|r| construct_discovery_entry!(r))
+                        .collect(),
+                }
+            }};
+        }
+
+        let mut result = match query.sort {
+            DiscoverySort::Popularity => search_page!("d.upvotes DESC, d.uses DESC, d.guild_id"),
+            DiscoverySort::Recency => search_page!("d.published_at DESC, d.guild_id"),
+        };
+        // The total is counted alongside each row, so a page past the last result cannot report
+        // it. Count the matching entries separately instead.
+        if result.entries.is_empty() && offset > 0 {
+            result.total = search_discovery!(r#"COUNT(*) AS "count!""#;)
+                .fetch_one(self.executor())
+                .await?
+                .count as u64;
+        }
+        Ok(result)
+    }
+
+    /// Publishes the guild to guild discovery. If the guild is already listed, a new revision of
+    /// its listing is published instead, keeping its upvotes and uses.
+    ///
+    /// Returns the revision of the published listing.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the guild does not exist or is not public.
+    /// * If the description, category, or keywords are invalid.
+    /// * If an error occurs with publishing the listing.
+    async fn publish_discovery_entry(
+        &mut self,
+        guild_id: u64,
+        payload: PublishDiscoveryEntryPayload,
+    ) -> crate::Result<u32> {
+        payload.validate()?;
+
+        let flags = sqlx::query!("SELECT flags FROM guilds WHERE id = $1", guild_id as i64)
+            .fetch_optional(self.executor())
+            .await?
+            .ok_or_not_found("guild", format!("Guild with ID {guild_id} does not exist"))?
+            .flags;
+
+        if !GuildFlags::from_bits_truncate(flags as _).contains(GuildFlags::PUBLIC) {
+            return Err(Error::InvalidField {
+                field: "guild_id".to_string(),
+                message: "Only public guilds can be published to discovery".to_string(),
+            });
+        }
+
+        let description = payload.description.trim();
+        let category = payload.category.trim().to_lowercase();
+        let keywords = payload.normalized_keywords();
+
+        let revision = sqlx::query!(
+            r#"INSERT INTO
+                discovery_entries (guild_id, description, category, keywords)
+            VALUES
+                ($1, $2, $3, $4)
+            ON CONFLICT
+                (guild_id)
+            DO UPDATE SET
+                revision = discovery_entries.revision + 1,
+                description = $2,
+                category = $3,
+                keywords = $4,
+                published_at = NOW()
+            RETURNING revision"#,
+            guild_id as i64,
+            description,
+            category,
+            &keywords,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .revision;
+
+        Ok(revision as u32)
+    }
+
+    /// Removes the guild from guild discovery, along with all of its upvotes.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the guild is not listed.
+    /// * If an error occurs with removing the listing.
+    async fn unpublish_discovery_entry(&mut self, guild_id: u64) -> crate::Result<()> {
+        sqlx::query!(
+            "DELETE FROM discovery_entries WHERE guild_id = $1 RETURNING guild_id",
+            guild_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("discovery_entry", "Guild is not listed in discovery")?;
+
+        Ok(())
+    }
+
+    /// Upvotes the discovery listing of the given guild on behalf of the user. Returns whether the
+    /// upvote was added, or `false` if the user had already upvoted the listing.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the guild is not listed.
+    /// * If an error occurs with upvoting the listing.
+    async fn upvote_discovery_entry(&mut self, guild_id: u64, user_id: u64) -> crate::Result<bool> {
+        sqlx::query!(
+            "SELECT guild_id FROM discovery_entries WHERE guild_id = $1",
+            guild_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found("discovery_entry", "Guild is not listed in discovery")?;
+
+        let added = sqlx::query!(
+            "INSERT INTO discovery_upvotes (guild_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected()
+            > 0;
+
+        if added {
+            sqlx::query!(
+                "UPDATE discovery_entries SET upvotes = upvotes + 1 WHERE guild_id = $1",
+                guild_id as i64,
+            )
+            .execute(self.transaction())
+            .await?;
+        }
+        Ok(added)
+    }
+
+    /// Removes the upvote of the user from the discovery listing of the given guild. Returns
+    /// whether an upvote was removed.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with removing the upvote.
+    async fn remove_discovery_upvote(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+    ) -> crate::Result<bool> {
+        let removed = sqlx::query!(
+            "DELETE FROM discovery_upvotes WHERE guild_id = $1 AND user_id = $2",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected()
+            > 0;
+
+        if removed {
+            sqlx::query!(
+                "UPDATE discovery_entries SET upvotes = upvotes - 1 WHERE guild_id = $1",
+                guild_id as i64,
+            )
+            .execute(self.transaction())
+            .await?;
+        }
+        Ok(removed)
+    }
+
+    /// Increments the number of times users joined the given guild through discovery. This
+    /// should be called when a user joins a guild from its discovery listing.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the guild is not listed.
+    /// * If an error occurs with incrementing the use count.
+    async fn increment_discovery_uses(&mut self, guild_id: u64) -> crate::Result<()> {
+        sqlx::query!(
+            "UPDATE discovery_entries SET uses = uses + 1 WHERE guild_id = $1 RETURNING guild_id",
+            guild_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("discovery_entry", "Guild is not listed in discovery")?;
+
+        Ok(())
+    }
+}
+
+impl<'t, T> DiscoveryDbExt<'t> for T where T: DbExt<'t> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Pool, Postgres};
+
+    #[sqlx::test(fixtures("discovery"))]
+    async fn test_search_discovery(pool: Pool<Postgres>) -> crate::Result<()> {
+        // `DbExt` is only implemented for a `'static` pool
+        let pool: &'static Pool<Postgres> = Box::leak(Box::new(pool));
+        let search = |query| async move {
+            let result = pool.search_discovery(query).await?;
+            let ids = result
+                .entries
+                .iter()
+                .map(|e| e.guild.id)
+                .collect::<Vec<_>>();
+            crate::Result::Ok((result.total, ids))
+        };
+        let all = DiscoverySearchQuery {
+            limit: 25,
+            ..Default::default()
+        };
+
+        assert_eq!(search(all.clone()).await?, (2, vec![10, 11]));
+        assert_eq!(
+            search(DiscoverySearchQuery {
+                keyword: Some("games".to_string()),
+                ..all.clone()
+            })
+            .await?,
+            (1, vec![10]),
+        );
+        assert_eq!(
+            search(DiscoverySearchQuery {
+                category: Some("Art".to_string()),
+                ..all
+            })
+            .await?,
+            (1, vec![11]),
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("discovery"))]
+    async fn test_search_discovery_pagination(pool: Pool<Postgres>) -> crate::Result<()> {
+        let pool: &'static Pool<Postgres> = Box::leak(Box::new(pool));
+        let query = |offset| DiscoverySearchQuery {
+            limit: 1,
+            offset,
+            ..Default::default()
+        };
+
+        let result = pool.search_discovery(query(1)).await?;
+        assert_eq!(result.total, 2);
+        assert_eq!(result.entries.len(), 1);
+
+        let result = pool.search_discovery(query(5)).await?;
+        assert_eq!(
+            result.total, 2,
+            "the total should still be counted past the last page"
+        );
+        assert!(result.entries.is_empty());
+        Ok(())
+    }
+}
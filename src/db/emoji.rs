@@ -1,5 +1,13 @@
 use super::{get_pool, DbExt};
-use crate::models::{CustomEmoji, PartialEmoji, Reaction};
+use crate::{
+    http::emoji::ReactionUsersQuery,
+    models::{
+        CustomEmoji, EmojiUsage, ModelType, PartialEmoji, Permissions, Reaction, ReactionUser,
+    },
+    snowflake::with_model_type,
+    Error,
+};
+use std::collections::HashSet;
 
 macro_rules! construct_emoji {
     ($data:expr) => {
@@ -8,6 +16,7 @@ macro_rules! construct_emoji {
             guild_id: $data.guild_id as u64,
             name: $data.name,
             created_by: $data.created_by.map(|id| id as u64),
+            roles: $data.roles.into_iter().map(|id| id as u64).collect(),
         }
     };
 }
@@ -23,6 +32,9 @@ macro_rules! construct_reaction {
             user_ids: $data
                 .user_ids
                 .map_or_else(Vec::new, |u| u.into_iter().map(|id| id as u64).collect()),
+            burst_user_ids: $data
+                .burst_user_ids
+                .map_or_else(Vec::new, |u| u.into_iter().map(|id| id as u64).collect()),
             created_at: $data.created_at,
         }
     };
@@ -37,26 +49,36 @@ pub(crate) use {construct_emoji, construct_reaction};
 pub trait EmojiDbExt<'t>: DbExt<'t> {
     /// Fetch all custom emojis that belongs to `guild_id`
     async fn fetch_all_emojis_in_guild(&self, guild_id: u64) -> crate::Result<Vec<CustomEmoji>> {
-        Ok(
-            sqlx::query!("SELECT * FROM emojis WHERE guild_id = $1", guild_id as i64)
-                .fetch_all(self.executor())
-                .await?
-                .into_iter()
-                .map(|r| construct_emoji!(r))
-                .collect::<Vec<CustomEmoji>>(),
+        Ok(sqlx::query!(
+            r#"SELECT
+                    *,
+                    ARRAY(SELECT role_id FROM emoji_roles er WHERE er.emoji_id = emojis.id)
+                        AS "roles!"
+                FROM emojis WHERE guild_id = $1"#,
+            guild_id as i64
         )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| construct_emoji!(r))
+        .collect::<Vec<CustomEmoji>>())
     }
 
     /// Fetch emoji with id.
     ///
     /// Returns `None` if not found.
     async fn fetch_emoji(&self, id: u64) -> crate::Result<Option<CustomEmoji>> {
-        Ok(
-            sqlx::query!("SELECT * FROM emojis WHERE id = $1", id as i64)
-                .fetch_optional(self.executor())
-                .await?
-                .map(|r| construct_emoji!(r)),
+        Ok(sqlx::query!(
+            r#"SELECT
+                    *,
+                    ARRAY(SELECT role_id FROM emoji_roles er WHERE er.emoji_id = emojis.id)
+                        AS "roles!"
+                FROM emojis WHERE id = $1"#,
+            id as i64
         )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| construct_emoji!(r)))
     }
 
     /// Create a new emoji with the given parameters.
@@ -84,6 +106,7 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
             guild_id,
             name: name.as_ref().to_string(),
             created_by: Some(created_by),
+            roles: Vec::new(),
         })
     }
 
@@ -96,7 +119,11 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
         name: impl AsRef<str> + Send,
     ) -> crate::Result<CustomEmoji> {
         let r = sqlx::query!(
-            "UPDATE emojis SET name = $1 WHERE id = $2 RETURNING *",
+            r#"UPDATE emojis SET name = $1 WHERE id = $2
+            RETURNING
+                *,
+                ARRAY(SELECT role_id FROM emoji_roles er WHERE er.emoji_id = emojis.id)
+                    AS "roles!""#,
             name.as_ref(),
             id as i64
         )
@@ -115,6 +142,272 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
         Ok(())
     }
 
+    /// Sets the roles that are allowed to use the emoji with the given ID, replacing any existing
+    /// restrictions. An empty list allows everyone to use the emoji. Roles that are not in the
+    /// same guild as the emoji are ignored.
+    ///
+    /// Returns the IDs of the roles that are now allowed to use the emoji.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with updating the emoji roles.
+    async fn set_emoji_roles(&mut self, id: u64, role_ids: &[u64]) -> crate::Result<Vec<u64>> {
+        sqlx::query!("DELETE FROM emoji_roles WHERE emoji_id = $1", id as i64)
+            .execute(self.transaction())
+            .await?;
+
+        Ok(sqlx::query!(
+            "INSERT INTO emoji_roles (emoji_id, role_id)
+            SELECT $1, r.id FROM roles r
+            WHERE
+                r.id = ANY($2::BIGINT[])
+                AND r.guild_id = (SELECT guild_id FROM emojis WHERE id = $1)
+            RETURNING role_id",
+            id as i64,
+            &role_ids.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.role_id as u64)
+        .collect())
+    }
+
+    /// Asserts that the user can use all of the given custom emojis in the given guild, or in a
+    /// DM if `guild_id` is `None`. ``permissions`` should be the permissions of the user in the
+    /// channel the emojis are used in. Emoji IDs that do not exist are ignored, since they are
+    /// rendered as plain text.
+    ///
+    /// This should be used to validate message content, e.g. with
+    /// [`extract_custom_emojis`](crate::snowflake::extract_custom_emojis).
+    ///
+    /// An emoji can be used if:
+    /// * It is from `guild_id`, and it is either unrestricted or the user has one of its roles.
+    /// * It is from another guild the user is a member of, it is unrestricted, and the user has
+    ///   the `USE_EXTERNAL_EMOJIS` permission (which is not required in DMs).
+    ///
+    /// # Errors
+    /// * If the user is missing the `USE_EXTERNAL_EMOJIS` permission for an external emoji.
+    /// * If the user is not allowed to use one of the emojis.
+    /// * If an error occurs with fetching the emojis.
+    async fn assert_can_use_emojis(
+        &self,
+        guild_id: Option<u64>,
+        user_id: u64,
+        permissions: Permissions,
+        emoji_ids: &[u64],
+    ) -> crate::Result<()> {
+        if emoji_ids.is_empty() {
+            return Ok(());
+        }
+
+        let emojis = sqlx::query!(
+            r#"SELECT
+                id,
+                guild_id,
+                ARRAY(SELECT role_id FROM emoji_roles er WHERE er.emoji_id = emojis.id) AS "roles!"
+            FROM emojis
+            WHERE id = ANY($1::BIGINT[])"#,
+            &emoji_ids.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        let emoji_guild_ids = emojis.iter().map(|e| e.guild_id).collect::<Vec<_>>();
+        let member_of: HashSet<u64> = sqlx::query!(
+            "SELECT guild_id FROM members WHERE id = $1 AND guild_id = ANY($2::BIGINT[])",
+            user_id as i64,
+            &emoji_guild_ids,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.guild_id as u64)
+        .collect();
+
+        let mut role_ids = None;
+        for emoji in emojis {
+            let emoji_guild_id = emoji.guild_id as u64;
+            let restricted = !emoji.roles.is_empty();
+            let disallowed = || Error::InvalidField {
+                field: "emoji".to_string(),
+                message: format!("You are not allowed to use the emoji with ID {}", emoji.id),
+            };
+
+            if Some(emoji_guild_id) == guild_id {
+                if !restricted {
+                    continue;
+                }
+                if role_ids.is_none() {
+                    let mut roles: HashSet<i64> = sqlx::query!(
+                        "SELECT role_id FROM role_data WHERE guild_id = $1 AND user_id = $2",
+                        emoji_guild_id as i64,
+                        user_id as i64,
+                    )
+                    .fetch_all(self.executor())
+                    .await?
+                    .into_iter()
+                    .map(|r| r.role_id)
+                    .collect();
+                    roles.insert(with_model_type(emoji_guild_id, ModelType::Role) as i64);
+                    role_ids = Some(roles);
+                }
+                if let Some(ref roles) = role_ids
+                    && !emoji.roles.iter().any(|id| roles.contains(id))
+                {
+                    return Err(disallowed());
+                }
+                continue;
+            }
+
+            if restricted || !member_of.contains(&emoji_guild_id) {
+                return Err(disallowed());
+            }
+            if let Some(guild_id) = guild_id
+                && !permissions.contains(Permissions::USE_EXTERNAL_EMOJIS)
+            {
+                return Err(Error::MissingPermissions {
+                    guild_id,
+                    permissions: Permissions::USE_EXTERNAL_EMOJIS,
+                    message: "You need the USE_EXTERNAL_EMOJIS permission to use emojis from \
+                        other servers"
+                        .to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Asserts that the user can react with the given emoji in the given guild, or in a DM if
+    /// `guild_id` is `None`. Unicode emojis can always be used. Unlike
+    /// [`assert_can_use_emojis`](Self::assert_can_use_emojis), custom emojis that do not exist
+    /// cannot be used.
+    ///
+    /// # Errors
+    /// * If the custom emoji does not exist.
+    /// * If the user is not allowed to use the emoji. See
+    ///   [`assert_can_use_emojis`](Self::assert_can_use_emojis).
+    async fn assert_can_react_with(
+        &self,
+        guild_id: Option<u64>,
+        user_id: u64,
+        permissions: Permissions,
+        emoji: &PartialEmoji,
+    ) -> crate::Result<()> {
+        let Some(emoji_id) = emoji.id else {
+            return Ok(());
+        };
+        if self.fetch_emoji(emoji_id).await?.is_none() {
+            return Err(Error::NotFound {
+                entity: "emoji".to_string(),
+                message: format!("Emoji with ID {emoji_id} not found"),
+            });
+        }
+
+        self.assert_can_use_emojis(guild_id, user_id, permissions, &[emoji_id])
+            .await
+    }
+
+    /// Records that the user used each of the given custom emojis once, e.g. in a message or as a
+    /// reaction. Emoji IDs that do not exist are ignored.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with recording the usage.
+    async fn record_emoji_use(&mut self, user_id: u64, emoji_ids: &[u64]) -> crate::Result<()> {
+        if emoji_ids.is_empty() {
+            return Ok(());
+        }
+
+        sqlx::query!(
+            "INSERT INTO emoji_usage (user_id, emoji_id, uses)
+            SELECT $1, e.id, 1 FROM emojis e WHERE e.id = ANY($2::BIGINT[])
+            ON CONFLICT (user_id, emoji_id) DO UPDATE SET
+                uses = emoji_usage.uses + 1,
+                last_used_at = CURRENT_TIMESTAMP",
+            user_id as i64,
+            &emoji_ids.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fetches the most used custom emojis of the guild with the given ID, aggregated across all
+    /// users. Emojis are sorted by the number of uses, then by when they were last used.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the emoji usage.
+    async fn fetch_top_emojis(&self, guild_id: u64, limit: u16) -> crate::Result<Vec<EmojiUsage>> {
+        Ok(sqlx::query!(
+            r#"SELECT
+                e.*,
+                ARRAY(SELECT role_id FROM emoji_roles er WHERE er.emoji_id = e.id) AS "roles!",
+                SUM(u.uses)::BIGINT AS "uses!",
+                MAX(u.last_used_at) AS "last_used_at!"
+            FROM emoji_usage u
+            INNER JOIN emojis e ON e.id = u.emoji_id
+            WHERE e.guild_id = $1
+            GROUP BY e.id
+            ORDER BY "uses!" DESC, "last_used_at!" DESC
+            LIMIT $2"#,
+            guild_id as i64,
+            limit as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| EmojiUsage {
+            uses: r.uses as u64,
+            last_used_at: r.last_used_at,
+            emoji: construct_emoji!(r),
+        })
+        .collect())
+    }
+
+    /// Fetches the custom emojis most used by the user with the given ID, across all guilds.
+    /// Emojis are sorted by the number of uses, then by when they were last used.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the emoji usage.
+    async fn fetch_emoji_usage_for_user(
+        &self,
+        user_id: u64,
+        limit: u16,
+    ) -> crate::Result<Vec<EmojiUsage>> {
+        Ok(sqlx::query!(
+            r#"SELECT
+                e.*,
+                ARRAY(SELECT role_id FROM emoji_roles er WHERE er.emoji_id = e.id) AS "roles!",
+                u.uses,
+                u.last_used_at
+            FROM emoji_usage u
+            INNER JOIN emojis e ON e.id = u.emoji_id
+            WHERE u.user_id = $1
+            ORDER BY u.uses DESC, u.last_used_at DESC
+            LIMIT $2"#,
+            user_id as i64,
+            limit as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| EmojiUsage {
+            uses: r.uses as u64,
+            last_used_at: r.last_used_at,
+            emoji: construct_emoji!(r),
+        })
+        .collect())
+    }
+
     /// Returns whether the given emoji is already an existing reaction on the given message.
     async fn reaction_exists(
         &self,
@@ -144,17 +437,20 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
         Ok(exists)
     }
 
-    /// Fetches all reactions from the message with the given ID.
+    /// Fetches all reactions from the message with the given ID. Reactions are ordered by when
+    /// the emoji was first reacted with, and users within a reaction by when they reacted.
     async fn fetch_reactions(&self, message_id: u64) -> crate::Result<Vec<Reaction>> {
         let reactions = sqlx::query!(
             r"SELECT
                 emoji_id,
                 emoji_name,
-                array_agg(user_id) AS user_ids,
-                array_agg(created_at) AS created_at
+                array_agg(user_id ORDER BY created_at) AS user_ids,
+                array_agg(user_id ORDER BY created_at) FILTER (WHERE burst) AS burst_user_ids,
+                array_agg(created_at ORDER BY created_at) AS created_at
             FROM reactions
             WHERE message_id = $1
-            GROUP BY (emoji_id, emoji_name)",
+            GROUP BY (emoji_id, emoji_name)
+            ORDER BY MIN(created_at)",
             message_id as i64
         )
         .fetch_all(self.executor())
@@ -166,8 +462,57 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
         Ok(reactions)
     }
 
+    /// Fetches the users that reacted to the message with the given ID with the given emoji,
+    /// ordered by user ID.
+    ///
+    /// # Errors
+    /// * If the limit is not between ``1`` and ``100``.
+    /// * If an error occurs with fetching the users.
+    async fn fetch_reaction_users(
+        &self,
+        message_id: u64,
+        emoji: &PartialEmoji,
+        query: ReactionUsersQuery,
+    ) -> crate::Result<Vec<ReactionUser>> {
+        if !(1..=100).contains(&query.limit) {
+            return Err(Error::InvalidField {
+                field: "limit".to_string(),
+                message: "Limit must be between 1 and 100".to_string(),
+            });
+        }
+
+        let users = sqlx::query!(
+            "SELECT user_id, burst, created_at FROM reactions
+            WHERE
+                message_id = $1
+                AND emoji_id IS NOT DISTINCT FROM $2
+                AND emoji_name = $3
+                AND ($4::BIGINT IS NULL OR user_id > $4)
+                AND (NOT $5 OR burst)
+            ORDER BY user_id
+            LIMIT $6",
+            message_id as i64,
+            emoji.id.map(|id| id as i64),
+            emoji.name,
+            query.after.map(|id| id as i64),
+            query.burst_only,
+            query.limit as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| ReactionUser {
+            user_id: r.user_id as u64,
+            burst: r.burst,
+            created_at: r.created_at,
+        })
+        .collect();
+
+        Ok(users)
+    }
+
     /// Adds a reaction to the message with the given ID. Returns whether the reaction was newly
-    /// added.
+    /// added. If ``burst`` is ``true``, the reaction is recorded as a burst (super) reaction.
     ///
     /// # Errors
     /// * If an error occurs with adding the reaction.
@@ -177,6 +522,7 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
         message_id: u64,
         user_id: u64,
         emoji: &PartialEmoji,
+        burst: bool,
     ) -> crate::Result<bool> {
         if get_pool()
             .reaction_exists(message_id, Some(user_id), emoji)
@@ -185,12 +531,13 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
             return Ok(false);
         }
         sqlx::query!(
-            "INSERT INTO reactions (message_id, user_id, emoji_id, emoji_name)
-            VALUES ($1, $2, $3, $4)",
+            "INSERT INTO reactions (message_id, user_id, emoji_id, emoji_name, burst)
+            VALUES ($1, $2, $3, $4, $5)",
             message_id as i64,
             user_id as i64,
             emoji.id.map(|id| id as i64),
             emoji.name,
+            burst,
         )
         .execute(self.transaction())
         .await?;
@@ -228,6 +575,40 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
         Ok(deleted > 0)
     }
 
+    /// Removes every reaction with the given emoji from the message with the given ID, e.g. when
+    /// a moderator clears an emoji. Returns the IDs of the users whose reactions were removed.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with removing the reactions.
+    async fn remove_all_reactions_for_emoji(
+        &mut self,
+        message_id: u64,
+        emoji: &PartialEmoji,
+    ) -> crate::Result<Vec<u64>> {
+        let user_ids = sqlx::query!(
+            "DELETE FROM reactions
+            WHERE
+                message_id = $1
+                AND emoji_id IS NOT DISTINCT FROM $2
+                AND emoji_name = $3
+            RETURNING user_id",
+            message_id as i64,
+            emoji.id.map(|id| id as i64),
+            emoji.name,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.user_id as u64)
+        .collect();
+
+        Ok(user_ids)
+    }
+
     /// Removes all reactions from the message with the given ID, optionally filtering by emoji.
     async fn bulk_remove_reactions(
         &mut self,
@@ -236,19 +617,8 @@ pub trait EmojiDbExt<'t>: DbExt<'t> {
     ) -> crate::Result<()> {
         match emoji {
             Some(emoji) => {
-                sqlx::query!(
-                    "DELETE FROM reactions
-                    WHERE
-                        message_id = $1
-                        AND emoji_id IS NOT DISTINCT FROM $2
-                        AND emoji_name = $3
-                    ",
-                    message_id as i64,
-                    emoji.id.map(|id| id as i64),
-                    emoji.name,
-                )
-                .execute(self.transaction())
-                .await?;
+                self.remove_all_reactions_for_emoji(message_id, emoji)
+                    .await?;
             }
             None => {
                 sqlx::query!(
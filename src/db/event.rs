@@ -0,0 +1,407 @@
+use crate::{
+    db::{get_pool, ChannelDbExt, DbExt},
+    http::{
+        event::{CreateScheduledEventPayload, EditScheduledEventPayload, ScheduledEventUsersQuery},
+        validate::Validate,
+    },
+    models::{ChannelType, ScheduledEvent, ScheduledEventLocation, ScheduledEventStatus},
+    Error, NotFoundExt,
+};
+use chrono::{DateTime, Utc};
+use std::str::FromStr;
+
+macro_rules! query_scheduled_events {
+    ($where:literal, $($arg:expr),* $(,)?) => {{
+        sqlx::query!(
+            r#"SELECT
+                e.*,
+                (SELECT COUNT(*) FROM scheduled_event_users u WHERE u.event_id = e.id)
+                    AS "interested_count!"
+            FROM
+                scheduled_events e
+            WHERE
+            "# + $where,
+            $($arg),*
+        )
+    }};
+}
+
+macro_rules! construct_scheduled_event {
+    ($data:ident) => {{
+        ScheduledEventStatus::from_str(&$data.status).map(|status| ScheduledEvent {
+            id: $data.id as _,
+            guild_id: $data.guild_id as _,
+            creator_id: $data.creator_id.map(|id| id as _),
+            name: $data.name,
+            description: $data.description,
+            location: match ($data.channel_id, $data.external_location) {
+                (Some(channel_id), _) => ScheduledEventLocation::Voice {
+                    channel_id: channel_id as _,
+                },
+                (None, location) => ScheduledEventLocation::External {
+                    location: location.unwrap_or_default(),
+                },
+            },
+            start_time: $data.start_time,
+            end_time: $data.end_time,
+            status,
+            interested_count: $data.interested_count as _,
+        })
+    }};
+}
+
+#[async_trait::async_trait]
+pub trait ScheduledEventDbExt<'t>: DbExt<'t> {
+    /// Asserts that the given location is valid for an event in the given guild, i.e. that voice
+    /// locations refer to a voice channel in the guild.
+    ///
+    /// # Errors
+    /// * If the location is a channel that is not a voice channel in the guild.
+    async fn assert_valid_event_location(
+        &self,
+        guild_id: u64,
+        location: &ScheduledEventLocation,
+    ) -> crate::Result<()> {
+        if let ScheduledEventLocation::Voice { channel_id } = *location {
+            let channel = self
+                .inspect_channel(channel_id)
+                .await?
+                .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?;
+
+            if channel.guild_id != Some(guild_id) || channel.channel_type != ChannelType::Voice {
+                return Err(Error::InvalidField {
+                    field: "location".to_string(),
+                    message: "Events can only take place in voice channels of the guild"
+                        .to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches the scheduled event with the given ID in the given guild.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the event. If the event is not found, `Ok(None)` is
+    /// returned.
+    async fn fetch_scheduled_event(
+        &self,
+        guild_id: u64,
+        event_id: u64,
+    ) -> crate::Result<Option<ScheduledEvent>> {
+        query_scheduled_events!(
+            "e.guild_id = $1 AND e.id = $2",
+            guild_id as i64,
+            event_id as i64
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| construct_scheduled_event!(r))
+        .transpose()
+    }
+
+    /// Fetches all scheduled events in the given guild, ordered by their start time. Finished
+    /// events are only included if `include_finished` is `true`.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the events.
+    async fn fetch_scheduled_events(
+        &self,
+        guild_id: u64,
+        include_finished: bool,
+    ) -> crate::Result<Vec<ScheduledEvent>> {
+        query_scheduled_events!(
+            "e.guild_id = $1 AND ($2 OR e.status NOT IN ('completed', 'cancelled'))
+            ORDER BY e.start_time",
+            guild_id as i64,
+            include_finished
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| construct_scheduled_event!(r))
+        .collect()
+    }
+
+    /// Creates a new scheduled event in the given guild.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid, e.g. the end time is not after the start time.
+    /// * If the location is invalid.
+    /// * If an error occurs with creating the event.
+    async fn create_scheduled_event(
+        &mut self,
+        guild_id: u64,
+        event_id: u64,
+        creator_id: u64,
+        payload: CreateScheduledEventPayload,
+    ) -> crate::Result<ScheduledEvent> {
+        payload.validate()?;
+        get_pool()
+            .assert_valid_event_location(guild_id, &payload.location)
+            .await?;
+
+        let (channel_id, external_location) = location_to_db(&payload.location);
+        sqlx::query!(
+            "INSERT INTO scheduled_events (
+                id, guild_id, creator_id, name, description, channel_id, external_location,
+                start_time, end_time
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            event_id as i64,
+            guild_id as i64,
+            creator_id as i64,
+            payload.name.trim(),
+            payload.description,
+            channel_id,
+            external_location,
+            payload.start_time,
+            payload.end_time,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(ScheduledEvent {
+            id: event_id,
+            guild_id,
+            creator_id: Some(creator_id),
+            name: payload.name,
+            description: payload.description,
+            location: payload.location,
+            start_time: payload.start_time,
+            end_time: payload.end_time,
+            status: ScheduledEventStatus::Scheduled,
+            interested_count: 0,
+        })
+    }
+
+    /// Edits the scheduled event with the given ID. Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid.
+    /// * If the event is not found.
+    /// * If the location is invalid.
+    /// * If the end time is not after the start time.
+    /// * If the status of a finished event is changed.
+    /// * If an error occurs with editing the event.
+    async fn edit_scheduled_event(
+        &mut self,
+        guild_id: u64,
+        event_id: u64,
+        payload: EditScheduledEventPayload,
+    ) -> crate::Result<(ScheduledEvent, ScheduledEvent)> {
+        payload.validate()?;
+
+        let before = get_pool()
+            .fetch_scheduled_event(guild_id, event_id)
+            .await?
+            .ok_or_not_found(
+                "scheduled_event",
+                format!("Scheduled event with ID {event_id} not found"),
+            )?;
+
+        let mut after = before.clone();
+        if let Some(name) = payload.name {
+            after.name = name;
+        }
+        after.description = payload
+            .description
+            .into_option_or_if_absent(after.description);
+        if let Some(location) = payload.location {
+            get_pool()
+                .assert_valid_event_location(guild_id, &location)
+                .await?;
+            after.location = location;
+        }
+        if let Some(start_time) = payload.start_time {
+            after.start_time = start_time;
+        }
+        after.end_time = payload.end_time.into_option_or_if_absent(after.end_time);
+        if let Some(status) = payload.status
+            && status != before.status
+        {
+            if before.status.is_finished() {
+                return Err(Error::InvalidField {
+                    field: "status".to_string(),
+                    message: "The status of a finished event cannot be changed".to_string(),
+                });
+            }
+            after.status = status;
+        }
+        assert_valid_event_times(after.start_time, after.end_time)?;
+
+        let (channel_id, external_location) = location_to_db(&after.location);
+        sqlx::query!(
+            "UPDATE scheduled_events
+            SET
+                name = $1, description = $2, channel_id = $3, external_location = $4,
+                start_time = $5, end_time = $6, status = $7
+            WHERE id = $8",
+            after.name.trim(),
+            after.description,
+            channel_id,
+            external_location,
+            after.start_time,
+            after.end_time,
+            after.status.name(),
+            event_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((before, after))
+    }
+
+    /// Deletes the scheduled event with the given ID.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the event is not found.
+    /// * If an error occurs with deleting the event.
+    async fn delete_scheduled_event(&mut self, guild_id: u64, event_id: u64) -> crate::Result<()> {
+        let result = sqlx::query!(
+            "DELETE FROM scheduled_events WHERE guild_id = $1 AND id = $2",
+            guild_id as i64,
+            event_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                entity: "scheduled_event".to_string(),
+                message: format!("Scheduled event with ID {event_id} not found"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Marks the given user as interested in the scheduled event with the given ID. Returns
+    /// whether the user was newly added.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the event is not found or has already finished.
+    /// * If an error occurs with adding the user.
+    async fn add_scheduled_event_user(
+        &mut self,
+        guild_id: u64,
+        event_id: u64,
+        user_id: u64,
+    ) -> crate::Result<bool> {
+        let event = get_pool()
+            .fetch_scheduled_event(guild_id, event_id)
+            .await?
+            .ok_or_not_found(
+                "scheduled_event",
+                format!("Scheduled event with ID {event_id} not found"),
+            )?;
+
+        if event.status.is_finished() {
+            return Err(Error::InvalidField {
+                field: "event_id".to_string(),
+                message: "This event has already finished".to_string(),
+            });
+        }
+
+        let result = sqlx::query!(
+            "INSERT INTO scheduled_event_users (event_id, user_id) VALUES ($1, $2)
+            ON CONFLICT DO NOTHING",
+            event_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Removes the given user from the users interested in the scheduled event with the given
+    /// ID. Returns whether the user was interested in the event.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with removing the user.
+    async fn remove_scheduled_event_user(
+        &mut self,
+        event_id: u64,
+        user_id: u64,
+    ) -> crate::Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM scheduled_event_users WHERE event_id = $1 AND user_id = $2",
+            event_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Fetches the IDs of the users interested in the scheduled event with the given ID, ordered
+    /// by user ID.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the users.
+    async fn fetch_scheduled_event_users(
+        &self,
+        event_id: u64,
+        query: ScheduledEventUsersQuery,
+    ) -> crate::Result<Vec<u64>> {
+        Ok(sqlx::query!(
+            "SELECT user_id FROM scheduled_event_users
+            WHERE event_id = $1 AND ($2::BIGINT IS NULL OR user_id > $2)
+            ORDER BY user_id
+            LIMIT $3",
+            event_id as i64,
+            query.after.map(|id| id as i64),
+            query.limit as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.user_id as u64)
+        .collect())
+    }
+}
+
+impl<'t, T> ScheduledEventDbExt<'t> for T where T: DbExt<'t> {}
+
+fn location_to_db(location: &ScheduledEventLocation) -> (Option<i64>, Option<&str>) {
+    match location {
+        ScheduledEventLocation::Voice { channel_id } => (Some(*channel_id as i64), None),
+        ScheduledEventLocation::External { location } => (None, Some(location.as_str())),
+    }
+}
+
+fn assert_valid_event_times(
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+) -> crate::Result<()> {
+    if let Some(end_time) = end_time
+        && end_time <= start_time
+    {
+        return Err(Error::InvalidField {
+            field: "end_time".to_string(),
+            message: "The end time of an event must be after its start time".to_string(),
+        });
+    }
+    Ok(())
+}
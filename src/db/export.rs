@@ -0,0 +1,265 @@
+#[allow(unused_imports)]
+use crate::models::Embed;
+use crate::{
+    db::{
+        message::construct_message, ChannelDbExt, ClientSettingsDbExt, DbExt, MessageDbExt,
+        UserDbExt,
+    },
+    http::message::MessageHistoryQuery,
+    models::{ChannelExportHeader, ExportFormat, Message, UserDataExport, EXPORT_SCHEMA_VERSION},
+    Error, NotFoundExt,
+};
+use chrono::Utc;
+use futures_util::{Stream, TryStreamExt};
+use serde::Serialize;
+use std::pin::pin;
+
+/// The number of messages fetched and populated at once while exporting.
+const EXPORT_CHUNK_SIZE: u8 = 200;
+
+fn write_json(buffer: &mut Vec<u8>, value: &impl Serialize) -> crate::Result<()> {
+    serde_json::to_writer(&mut *buffer, value).map_err(|err| Error::InternalError {
+        what: Some("export serialization".to_string()),
+        message: err.to_string(),
+        debug: Some(format!("{err:?}")),
+    })
+}
+
+/// Serializes a channel export, writing each message as it is yielded by the stream.
+async fn serialize_channel_export(
+    header: ChannelExportHeader,
+    format: ExportFormat,
+    mut messages: impl Stream<Item = crate::Result<Message>> + Unpin + Send,
+) -> crate::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    match format {
+        ExportFormat::Json => {
+            // Written by hand to match a serialized `ChannelExport`. The header is flattened into
+            // the export, so its closing brace is replaced by the messages array.
+            write_json(&mut buffer, &header)?;
+            buffer.pop();
+            buffer.extend_from_slice(br#","messages":["#);
+            let mut first = true;
+            while let Some(message) = messages.try_next().await? {
+                if !first {
+                    buffer.push(b',');
+                }
+                first = false;
+                write_json(&mut buffer, &message)?;
+            }
+            buffer.extend_from_slice(b"]}");
+        }
+        ExportFormat::NdJson => {
+            write_json(&mut buffer, &header)?;
+            while let Some(message) = messages.try_next().await? {
+                buffer.push(b'\n');
+                write_json(&mut buffer, &message)?;
+            }
+        }
+    }
+    Ok(buffer)
+}
+
+#[async_trait::async_trait]
+pub trait ExportDbExt<'t>: DbExt<'t> {
+    /// Exports the entire message history of a channel in the given format. Messages are sorted
+    /// from oldest to newest and are fetched in chunks, so only the serialized export is held in
+    /// memory. See [`ChannelExport`](crate::models::ChannelExport) for the schema of the export.
+    ///
+    /// Permission checks on the channel must be done prior to calling this method.
+    ///
+    /// # Errors
+    /// * If the channel does not exist.
+    /// * If an error occurs with fetching the messages or serializing the export.
+    async fn export_channel_messages(
+        &self,
+        channel_id: u64,
+        format: ExportFormat,
+    ) -> crate::Result<Vec<u8>> {
+        self.inspect_channel(channel_id)
+            .await?
+            .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?;
+
+        let header = ChannelExportHeader {
+            version: EXPORT_SCHEMA_VERSION,
+            channel_id,
+            exported_at: Utc::now(),
+        };
+        let messages = pin!(self.fetch_message_history_stream(
+            channel_id,
+            MessageHistoryQuery {
+                before: None,
+                after: None,
+                limit: EXPORT_CHUNK_SIZE,
+                user_id: None,
+                oldest_first: true,
+            },
+        ));
+
+        serialize_channel_export(header, format, messages).await
+    }
+
+    /// Exports all data associated with a user: their account, client settings, relationships,
+    /// favorites, guild memberships and every message they have sent that has not been deleted.
+    /// Messages are fetched and populated in chunks, but the returned export holds all of them
+    /// in memory. See [`UserDataExport`] for the schema of the export.
+    ///
+    /// # Errors
+    /// * If the user does not exist.
+    /// * If an error occurs with fetching any of the data.
+    async fn export_user_data(&self, user_id: u64) -> crate::Result<UserDataExport> {
+        let user = self
+            .fetch_client_user_by_id(user_id)
+            .await?
+            .ok_or_not_found("user", format!("User with ID {user_id} not found"))?;
+
+        let guild_ids = sqlx::query!(
+            "SELECT guild_id FROM members WHERE id = $1 ORDER BY joined_at",
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.guild_id as u64)
+        .collect();
+
+        let mut messages = Vec::new();
+        let mut after = 0;
+        loop {
+            let mut chunk = sqlx::query!(
+                r#"SELECT
+                    m.*,
+                    embeds AS "embeds_ser: sqlx::types::Json<Vec<Embed>>"
+                FROM
+                    messages m
+                WHERE
+                    m.author_id = $1
+                AND
+                    m.id > $2
+                AND
+                    m.deleted_at IS NULL
+                ORDER BY id ASC
+                LIMIT $3"#,
+                user_id as i64,
+                after,
+                i64::from(EXPORT_CHUNK_SIZE),
+            )
+            .fetch_all(self.executor())
+            .await?
+            .into_iter()
+            .map(|m| construct_message!(m))
+            .collect::<crate::Result<Vec<Message>>>()?;
+
+            self.populate_messages(&mut chunk).await?;
+            let Some(last) = chunk.last() else {
+                break;
+            };
+            after = last.id as i64;
+
+            let done = chunk.len() < EXPORT_CHUNK_SIZE as usize;
+            messages.append(&mut chunk);
+            if done {
+                break;
+            }
+        }
+
+        Ok(UserDataExport {
+            version: EXPORT_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            user,
+            settings: self.fetch_client_settings(user_id).await?,
+            relationships: self.fetch_relationships(user_id).await?,
+            favorites: self.fetch_favorites(user_id).await?,
+            guild_ids,
+            messages,
+        })
+    }
+}
+
+impl<'t, T> ExportDbExt<'t> for T where T: DbExt<'t> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChannelExport, MessageFlags, MessageInfo};
+    use futures_util::{stream, FutureExt};
+    use sqlx::{Pool, Postgres};
+
+    fn message(id: u64) -> Message {
+        Message {
+            id,
+            channel_id: 1,
+            author_id: Some(2),
+            author: None,
+            kind: MessageInfo::Default,
+            content: Some(format!("message {id}")),
+            embeds: Vec::new(),
+            attachments: Vec::new(),
+            flags: MessageFlags::empty(),
+            reactions: Vec::new(),
+            stars: 0,
+            mentions: Vec::new(),
+            edited_at: None,
+            references: Vec::new(),
+            sticker_ids: Vec::new(),
+            poll: None,
+        }
+    }
+
+    fn serialize(format: ExportFormat, messages: Vec<Message>) -> (ChannelExportHeader, Vec<u8>) {
+        let header = ChannelExportHeader {
+            version: EXPORT_SCHEMA_VERSION,
+            channel_id: 1,
+            exported_at: Utc::now(),
+        };
+        let export = serialize_channel_export(
+            header.clone(),
+            format,
+            stream::iter(messages.into_iter().map(Ok)),
+        )
+        .now_or_never()
+        .expect("serializing should not be pending")
+        .unwrap();
+        (header, export)
+    }
+
+    #[test]
+    fn test_serialize_channel_export_json() {
+        for count in [0, 1, 3] {
+            let messages = (1..=count).map(message).collect::<Vec<_>>();
+            let (header, export) = serialize(ExportFormat::Json, messages.clone());
+            let expected = serde_json::to_vec(&ChannelExport { header, messages }).unwrap();
+            assert_eq!(
+                String::from_utf8(export).unwrap(),
+                String::from_utf8(expected).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialize_channel_export_ndjson() {
+        let (header, export) = serialize(ExportFormat::NdJson, vec![message(1), message(2)]);
+        let expected = [
+            serde_json::to_string(&header).unwrap(),
+            serde_json::to_string(&message(1)).unwrap(),
+            serde_json::to_string(&message(2)).unwrap(),
+        ]
+        .join("\n");
+        assert_eq!(String::from_utf8(export).unwrap(), expected);
+    }
+
+    #[sqlx::test(fixtures("search"))]
+    async fn test_export_user_data(pool: Pool<Postgres>) -> crate::Result<()> {
+        // `DbExt` is only implemented for a `'static` pool
+        let pool: &'static Pool<Postgres> = Box::leak(Box::new(pool));
+
+        let export = pool.export_user_data(2).await?;
+        assert_eq!(export.guild_ids, vec![10]);
+        assert_eq!(
+            export.messages.iter().map(|m| m.id).collect::<Vec<_>>(),
+            vec![101],
+            "deleted messages should not be exported",
+        );
+        Ok(())
+    }
+}
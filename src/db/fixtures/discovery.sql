@@ -0,0 +1,11 @@
+-- Guilds listed in guild discovery. Guild 12 is no longer public, so it should not be returned.
+INSERT INTO users (id, username) VALUES (1, 'alice');
+INSERT INTO guilds (id, owner_id, name, flags) VALUES
+    (10, 1, 'Gaming Hub', 1),
+    (11, 1, 'Art Corner', 1),
+    (12, 1, 'Private Games', 0);
+
+INSERT INTO discovery_entries (guild_id, description, category, keywords, upvotes) VALUES
+    (10, 'A place to play games', 'gaming', ARRAY['games'], 5),
+    (11, 'Share your art', 'art', ARRAY['drawing'], 2),
+    (12, 'Games among friends', 'gaming', ARRAY['games'], 9);
@@ -0,0 +1,18 @@
+-- Messages in a single guild text channel for testing message search filters. User 2 has role 30,
+-- so they are mentioned by message 103.
+INSERT INTO users (id, username) VALUES (1, 'alice'), (2, 'bob');
+INSERT INTO guilds (id, owner_id, name) VALUES (10, 1, 'Guild');
+INSERT INTO channels (id, guild_id, type, name) VALUES (20, 10, 'text', 'general');
+INSERT INTO roles (id, guild_id, name) VALUES (30, 10, 'Role');
+INSERT INTO members (id, guild_id) VALUES (1, 10), (2, 10);
+INSERT INTO role_data (role_id, user_id, guild_id) VALUES (30, 2, 10);
+
+INSERT INTO messages (id, channel_id, author_id, content, mentions, embeds, deleted_at) VALUES
+    (100, 20, 1, 'hello world', '{}', '[]', NULL),
+    (101, 20, 2, 'hello there', '{2}', '[]', NULL),
+    (102, 20, 1, 'goodbye world', '{}', '[{"type": "rich", "title": "Embed"}]', NULL),
+    (103, 20, 1, 'hello roles', '{30}', '[]', NULL),
+    (104, 20, 2, 'hello deleted', '{}', '[]', NOW());
+
+INSERT INTO attachments (id, message_id, filename, size)
+VALUES ('00000000-0000-0000-0000-000000000001', 100, 'file.txt', 1);
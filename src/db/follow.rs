@@ -0,0 +1,221 @@
+use crate::{
+    cache::ChannelInspection,
+    db::{get_pool, ChannelDbExt, DbExt},
+    models::{ChannelFollow, ChannelType, MessageFlags},
+    Error, NotFoundExt,
+};
+
+macro_rules! construct_follow {
+    ($data:ident) => {{
+        ChannelFollow {
+            source_id: $data.source_id as _,
+            target_id: $data.target_id as _,
+            guild_id: $data.guild_id as _,
+            created_at: $data.created_at,
+        }
+    }};
+}
+
+#[async_trait::async_trait]
+pub trait ChannelFollowDbExt<'t>: DbExt<'t> {
+    /// Asserts that the given channel is an announcement channel.
+    ///
+    /// # Errors
+    /// * If the channel is not found or is not an announcement channel.
+    async fn assert_channel_is_announcement(&self, channel_id: u64) -> crate::Result<()> {
+        let channel = self
+            .inspect_channel(channel_id)
+            .await?
+            .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?;
+
+        if channel.channel_type != ChannelType::Announcement {
+            return Err(Error::InvalidField {
+                field: "channel_id".to_string(),
+                message: "Channel is not an announcement channel".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fetches all channels following the given announcement channel, oldest follow first.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the followers.
+    async fn fetch_followers(&self, source_id: u64) -> crate::Result<Vec<ChannelFollow>> {
+        let follows = sqlx::query!(
+            "SELECT * FROM channel_follows WHERE source_id = $1 ORDER BY created_at",
+            source_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| construct_follow!(r))
+        .collect();
+
+        Ok(follows)
+    }
+
+    /// Follows the announcement channel ``source_id`` from the text or announcement channel
+    /// ``target_id``, so that messages published in the source channel are crossposted to the
+    /// target channel. Permission checks must be done prior to calling this method.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If either channel is not found.
+    /// * If the source channel is not an announcement channel.
+    /// * If the target channel is not a text or announcement channel, or is the source channel.
+    /// * If the target channel already follows the source channel.
+    async fn follow_announcement_channel(
+        &mut self,
+        source_id: u64,
+        target_id: u64,
+    ) -> crate::Result<ChannelFollow> {
+        get_pool().assert_channel_is_announcement(source_id).await?;
+
+        let target = get_pool()
+            .inspect_channel(target_id)
+            .await?
+            .ok_or_not_found("channel", format!("Channel with ID {target_id} not found"))?;
+
+        if source_id == target_id {
+            return Err(Error::InvalidField {
+                field: "target_id".to_string(),
+                message: "An announcement channel cannot follow itself".to_string(),
+            });
+        }
+        let guild_id = match target {
+            ChannelInspection {
+                guild_id: Some(guild_id),
+                channel_type: ChannelType::Text | ChannelType::Announcement,
+                ..
+            } => guild_id,
+            _ => {
+                return Err(Error::InvalidField {
+                    field: "target_id".to_string(),
+                    message: "Target must be a text or announcement channel in a guild".to_string(),
+                })
+            }
+        };
+
+        let created_at = sqlx::query!(
+            "INSERT INTO channel_follows (source_id, target_id, guild_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            RETURNING created_at",
+            source_id as i64,
+            target_id as i64,
+            guild_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_else(|| Error::AlreadyExists {
+            what: "channel_follow".to_string(),
+            message: format!("Channel {target_id} already follows channel {source_id}"),
+        })?
+        .created_at;
+
+        Ok(ChannelFollow {
+            source_id,
+            target_id,
+            guild_id,
+            created_at,
+        })
+    }
+
+    /// Stops the channel ``target_id`` from following the announcement channel ``source_id``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the target channel does not follow the source channel.
+    async fn unfollow_announcement_channel(
+        &mut self,
+        source_id: u64,
+        target_id: u64,
+    ) -> crate::Result<()> {
+        let result = sqlx::query!(
+            "DELETE FROM channel_follows WHERE source_id = $1 AND target_id = $2",
+            source_id as i64,
+            target_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                entity: "channel_follow".to_string(),
+                message: format!("Channel {target_id} does not follow channel {source_id}"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Publishes a message in an announcement channel by marking it as
+    /// [`MessageFlags::PUBLISHED`]. Returns the IDs of the channels following the announcement
+    /// channel, which the message should be crossposted to.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the channel is not found or is not an announcement channel.
+    /// * If the message is not found in the channel.
+    /// * If the message has already been published.
+    async fn publish_message(
+        &mut self,
+        channel_id: u64,
+        message_id: u64,
+    ) -> crate::Result<Vec<u64>> {
+        get_pool()
+            .assert_channel_is_announcement(channel_id)
+            .await?;
+
+        let flags = sqlx::query!(
+            "SELECT flags FROM messages
+            WHERE id = $1 AND channel_id = $2 AND deleted_at IS NULL
+            FOR UPDATE",
+            message_id as i64,
+            channel_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("message", format!("Message with ID {message_id} not found"))?
+        .flags;
+
+        if MessageFlags::from_bits_truncate(flags as _).contains(MessageFlags::PUBLISHED) {
+            return Err(Error::AlreadyExists {
+                what: "publication".to_string(),
+                message: format!("Message with ID {message_id} has already been published"),
+            });
+        }
+
+        sqlx::query!(
+            "UPDATE messages SET flags = flags | $1 WHERE id = $2 AND deleted_at IS NULL",
+            MessageFlags::PUBLISHED.bits() as i32,
+            message_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let targets = sqlx::query!(
+            "SELECT target_id FROM channel_follows WHERE source_id = $1",
+            channel_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.target_id as u64)
+        .collect();
+
+        Ok(targets)
+    }
+}
+
+impl<'t, T> ChannelFollowDbExt<'t> for T where T: DbExt<'t> {}
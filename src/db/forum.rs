@@ -0,0 +1,308 @@
+use crate::{
+    db::{channel::query_channels, get_pool, ChannelDbExt, DbExt, MessageDbExt},
+    http::{
+        channel::{CreateForumPostPayload, CreateForumTagPayload},
+        validate::Validate,
+    },
+    models::{
+        ChannelType, ForumSortOrder, ForumTag, GuildChannel, GuildChannelInfo, Message, ThreadInfo,
+    },
+    Error, NotFoundExt,
+};
+use std::str::FromStr;
+
+#[async_trait::async_trait]
+pub trait ForumDbExt<'t>: DbExt<'t> {
+    /// Asserts that the given channel is a forum channel.
+    ///
+    /// # Errors
+    /// * If the channel is not found or is not a forum channel.
+    async fn assert_channel_is_forum(&self, channel_id: u64) -> crate::Result<()> {
+        let channel = self
+            .inspect_channel(channel_id)
+            .await?
+            .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?;
+
+        if channel.channel_type != ChannelType::Forum {
+            return Err(Error::InvalidField {
+                field: "channel_id".to_string(),
+                message: "Channel is not a forum channel".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fetches the posts in a forum channel, sorted by the default sort order of the forum.
+    ///
+    /// If `tags` is non-empty, only posts with at least one of the given tags applied are
+    /// returned. Archived posts are only included if `include_archived` is `true`.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the posts.
+    async fn fetch_forum_posts(
+        &self,
+        forum_id: u64,
+        tags: &[u64],
+        include_archived: bool,
+    ) -> crate::Result<Vec<GuildChannel>> {
+        let sort_order = sqlx::query!(
+            "SELECT forum_sort_order FROM channels WHERE id = $1",
+            forum_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .and_then(|r| r.forum_sort_order)
+        .map(|s| ForumSortOrder::from_str(&s))
+        .transpose()?
+        .unwrap_or_default();
+
+        let tags = tags.iter().map(|&id| id as i64).collect::<Vec<_>>();
+        let posts = match sort_order {
+            ForumSortOrder::LatestActivity => {
+                query_channels!(
+                    "c.parent_id = $1
+                AND c.type = 'thread'
+                AND (cardinality($2::BIGINT[]) = 0 OR t.applied_tags && $2::BIGINT[])
+                AND ($3 OR t.archived_at IS NULL)
+                ORDER BY c.last_activity_at DESC",
+                    forum_id as i64,
+                    &tags,
+                    include_archived
+                )
+                .fetch_all(self.executor())
+                .await?
+            }
+            ForumSortOrder::CreationDate => {
+                query_channels!(
+                    "c.parent_id = $1
+                AND c.type = 'thread'
+                AND (cardinality($2::BIGINT[]) = 0 OR t.applied_tags && $2::BIGINT[])
+                AND ($3 OR t.archived_at IS NULL)
+                ORDER BY c.id DESC",
+                    forum_id as i64,
+                    &tags,
+                    include_archived
+                )
+                .fetch_all(self.executor())
+                .await?
+            }
+        };
+
+        let post_ids = posts.iter().map(|p| p.id).collect::<Vec<_>>();
+        let mut last_messages = self.fetch_last_message_map(&post_ids).await?;
+
+        posts
+            .into_iter()
+            .map(|p| {
+                let last_message = last_messages.remove(&(p.id as u64));
+                p.into_guild_channel(Vec::new(), last_message)
+            })
+            .collect()
+    }
+
+    /// Creates a new tag in a forum channel.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid.
+    /// * If the channel is not found or is not a forum channel.
+    /// * If an error occurs with creating the tag.
+    async fn create_forum_tag(
+        &mut self,
+        forum_id: u64,
+        tag_id: u64,
+        payload: CreateForumTagPayload,
+    ) -> crate::Result<ForumTag> {
+        payload.validate()?;
+        get_pool().assert_channel_is_forum(forum_id).await?;
+
+        let (emoji_id, emoji_name) = payload
+            .emoji
+            .as_ref()
+            .map(|emoji| (emoji.id.map(|id| id as i64), emoji.name.as_str()))
+            .unzip();
+
+        sqlx::query!(
+            "INSERT INTO forum_tags (id, channel_id, name, emoji_id, emoji_name, moderated)
+            VALUES ($1, $2, $3, $4, $5, $6)",
+            tag_id as i64,
+            forum_id as i64,
+            payload.name.trim(),
+            emoji_id.flatten(),
+            emoji_name,
+            payload.moderated,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(ForumTag {
+            id: tag_id,
+            name: payload.name,
+            emoji: payload.emoji,
+            moderated: payload.moderated,
+        })
+    }
+
+    /// Deletes a tag from a forum channel, removing it from all posts it was applied to.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the tag is not found.
+    /// * If an error occurs with deleting the tag.
+    async fn delete_forum_tag(&mut self, forum_id: u64, tag_id: u64) -> crate::Result<()> {
+        let result = sqlx::query!(
+            "DELETE FROM forum_tags WHERE id = $1 AND channel_id = $2",
+            tag_id as i64,
+            forum_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                entity: "forum_tag".to_string(),
+                message: format!("Tag with ID {tag_id} not found"),
+            });
+        }
+
+        sqlx::query!(
+            "UPDATE threads SET applied_tags = array_remove(applied_tags, $1)
+            WHERE channel_id IN (SELECT id FROM channels WHERE parent_id = $2)",
+            tag_id as i64,
+            forum_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Creates a new post in a forum channel along with its initial message. The post is backed
+    /// by a thread whose `parent_id` is the forum channel, and the owner of the post
+    /// automatically joins it.
+    ///
+    /// Returns a tuple ``(post, message)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid, including its initial message.
+    /// * If the forum channel is not found, is not a forum channel, or is not in the guild.
+    /// * If any of the applied tags do not belong to the forum.
+    /// * If an error occurs with creating the post or its initial message.
+    async fn create_forum_post(
+        &mut self,
+        guild_id: u64,
+        forum_id: u64,
+        post_id: u64,
+        message_id: u64,
+        owner_id: u64,
+        mut payload: CreateForumPostPayload,
+    ) -> crate::Result<(GuildChannel, Message)> {
+        payload.validate()?;
+
+        let forum = get_pool()
+            .inspect_channel(forum_id)
+            .await?
+            .ok_or_not_found("channel", format!("Channel with ID {forum_id} not found"))?;
+
+        if forum.guild_id != Some(guild_id) || forum.channel_type != ChannelType::Forum {
+            return Err(Error::InvalidField {
+                field: "channel_id".to_string(),
+                message: "Posts can only be created in forum channels of this guild".to_string(),
+            });
+        }
+
+        payload.applied_tags.sort_unstable();
+        payload.applied_tags.dedup();
+
+        let applied_tags = payload
+            .applied_tags
+            .iter()
+            .map(|&id| id as i64)
+            .collect::<Vec<_>>();
+
+        if !applied_tags.is_empty() {
+            let found = sqlx::query!(
+                r#"SELECT COUNT(*) AS "count!" FROM forum_tags
+                WHERE channel_id = $1 AND id = ANY($2::BIGINT[])"#,
+                forum_id as i64,
+                &applied_tags,
+            )
+            .fetch_one(self.executor())
+            .await?
+            .count;
+
+            if found as usize != applied_tags.len() {
+                return Err(Error::InvalidField {
+                    field: "applied_tags".to_string(),
+                    message: "One or more tags do not belong to this forum".to_string(),
+                });
+            }
+        }
+
+        sqlx::query!(
+            "INSERT INTO channels (id, guild_id, type, name, position, parent_id)
+            VALUES ($1, $2, 'thread', $3, 0, $4)",
+            post_id as i64,
+            guild_id as i64,
+            payload.name.trim(),
+            forum_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO threads (channel_id, creator_id, auto_archive_duration, applied_tags)
+            VALUES ($1, $2, $3, $4)",
+            post_id as i64,
+            owner_id as i64,
+            payload.auto_archive_duration as i32,
+            &applied_tags,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO thread_members (thread_id, user_id) VALUES ($1, $2)",
+            post_id as i64,
+            owner_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let message = self
+            .create_message(post_id, message_id, owner_id, payload.message)
+            .await?;
+
+        let post = GuildChannel {
+            id: post_id,
+            guild_id,
+            info: GuildChannelInfo::Thread(ThreadInfo {
+                owner_id,
+                auto_archive_duration: payload.auto_archive_duration,
+                member_count: 1,
+                applied_tags: payload.applied_tags,
+                last_message: Some(message.clone()),
+                ..ThreadInfo::default()
+            }),
+            name: payload.name,
+            color: None,
+            icon: None,
+            position: 0,
+            parent_id: Some(forum_id),
+            overwrites: Vec::new(),
+        };
+        Ok((post, message))
+    }
+}
+
+impl<'t, T> ForumDbExt<'t> for T where T: DbExt<'t> {}
@@ -8,30 +8,90 @@ use crate::{
         channel::query_channels, get_pool, member::construct_member, ChannelDbExt, DbExt,
         MemberDbExt, RoleDbExt,
     },
-    http::guild::{CreateGuildPayload, EditGuildPayload, GetGuildQuery},
+    http::{
+        channel::{CreateGuildChannelInfo, CreateGuildChannelPayload},
+        guild::{
+            CloneGuildOptions, CreateGuildPayload, EditGuildPayload, EditOnboardingPayload,
+            EditWelcomeScreenPayload, GetGuildQuery, OnboardingAnswer,
+        },
+        validate::Validate,
+    },
     models::{
-        Guild, GuildChannel, GuildFlags, GuildMemberCount, MaybePartialUser, Member, PartialGuild,
-        PermissionPair, Permissions, Role, RoleFlags,
+        Asset, ChannelType, ExtendedColor, Guild, GuildChannel, GuildChannelInfo, GuildFlags,
+        GuildMemberCount, GuildOnboarding, GuildTemplate, GuildWelcomeScreen, MaybePartialUser,
+        Member, ModelType, OnboardingPrompt, PartialGuild, PermissionOverwrite, PermissionPair,
+        Permissions, Role, RoleFlags, TemplateChannel, TemplateChannelInfo, TemplateOverwrite,
+        TemplateRole, UserFlags, WelcomeScreenChannel, GUILD_TEMPLATE_VERSION,
     },
+    snowflake::{generate_snowflake, with_model_type},
     Error, NotFoundExt,
 };
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
+/// Vanity URL codes that may not be claimed by any guild, since they collide with routes of the
+/// web client or could be used for impersonation. Codes are compared case-insensitively.
+pub const RESERVED_VANITY_URLS: &[&str] = &[
+    "adapt", "admin", "api", "app", "assets", "channels", "discover", "download", "help", "invite",
+    "invites", "login", "oauth", "official", "register", "settings", "staff", "status", "support",
+    "system",
+];
+
+/// The minimum number of non-bot members a guild must have to set a vanity URL, unless it is
+/// verified.
+pub const VANITY_URL_MIN_MEMBERS: u32 = 100;
+
+/// Calculates the permissions in a channel from the overwrites of each of its permission sources
+/// (see [`GuildDbExt::fetch_permission_sources`]), taking the union of the permissions in each.
+fn union_source_permissions(
+    sources: &[u64],
+    overwrites: &HashMap<u64, Vec<PermissionOverwrite>>,
+    calculate: impl Fn(&[PermissionOverwrite]) -> Permissions,
+) -> Permissions {
+    sources.iter().fold(Permissions::empty(), |acc, id| {
+        acc | calculate(overwrites.get(id).map_or(&[], Vec::as_slice))
+    })
+}
+
+fn validate_vanity_url(slug: &str) -> crate::Result<()> {
+    if !(3..=32).contains(&slug.len()) {
+        return Err(Error::InvalidField {
+            field: "vanity_url".to_string(),
+            message: "Vanity URL must be between 3 and 32 characters long".to_string(),
+        });
+    }
+    if !slug.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        || slug.starts_with('-')
+        || slug.ends_with('-')
+    {
+        return Err(Error::InvalidField {
+            field: "vanity_url".to_string(),
+            message: "Vanity URL may only contain letters, numbers, and inner hyphens".to_string(),
+        });
+    }
+    if RESERVED_VANITY_URLS
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(slug))
+    {
+        return Err(Error::InvalidField {
+            field: "vanity_url".to_string(),
+            message: "This vanity URL is reserved".to_string(),
+        });
+    }
+    Ok(())
+}
+
 macro_rules! construct_partial_guild {
     ($data:ident) => {{
         PartialGuild {
             id: $data.id as _,
             name: $data.name,
             description: $data.description,
-            icon: $data.icon,
-            banner: $data.banner,
+            icon: $data.icon.map($crate::models::Asset::from_raw),
+            banner: $data.banner.map($crate::models::Asset::from_raw),
             owner_id: $data.owner_id as _,
             flags: GuildFlags::from_bits_truncate($data.flags as _),
-            member_count: Some(GuildMemberCount {
-                total: $data.member_count as _,
-                online: None, // TODO
-            }),
+            member_count: None,
             vanity_url: $data.vanity_url,
         }
     }};
@@ -201,24 +261,46 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
             return Ok(Permissions::all());
         }
 
-        let base = sqlx::query!(
-            "SELECT permissions FROM members WHERE id = $1",
-            user_id as i64
+        let member = sqlx::query!(
+            "SELECT m.permissions, m.rules_accepted_at, g.flags AS guild_flags
+            FROM members m INNER JOIN guilds g ON g.id = m.guild_id
+            WHERE m.guild_id = $1 AND m.id = $2",
+            guild_id as i64,
+            user_id as i64,
         )
         .fetch_one(self.executor())
-        .await?
-        .permissions;
+        .await?;
+        let pending = member.rules_accepted_at.is_none()
+            && GuildFlags::from_bits_truncate(member.guild_flags as _)
+                .contains(GuildFlags::REQUIRES_RULES_ACCEPTANCE);
         let mut roles = self.fetch_all_roles_for_member(guild_id, user_id).await?;
-        let overwrites = match channel_id {
-            Some(channel_id) => Some(self.fetch_channel_overwrites(channel_id).await?),
-            None => None,
+        let base = Permissions::from_bits_truncate(member.permissions);
+        let Some(channel_id) = channel_id else {
+            return Ok(crate::calculate_permissions(
+                user_id, base, &mut roles, None, pending,
+            ));
         };
 
-        Ok(crate::calculate_permissions(
-            user_id,
-            Permissions::from_bits_truncate(base),
-            &mut roles,
-            overwrites.as_ref().map(AsRef::as_ref),
+        roles.sort_unstable_by_key(|r| r.position);
+        let sources = self
+            .fetch_permission_sources(&[channel_id])
+            .await?
+            .remove(&channel_id)
+            .unwrap_or_else(|| vec![channel_id]);
+        let overwrites = self.fetch_effective_overwrites(&sources).await?;
+
+        Ok(union_source_permissions(
+            &sources,
+            &overwrites,
+            |overwrites| {
+                crate::calculate_permissions_sorted(
+                    user_id,
+                    base,
+                    &roles,
+                    Some(overwrites),
+                    pending,
+                )
+            },
         ))
     }
 
@@ -249,6 +331,245 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
         }
     }
 
+    /// Resolves the channels whose permission overwrites determine the permissions in each of the
+    /// given channels. Threads resolve to their parent channel, and merged channels resolve to
+    /// their sources, since the permissions in a merged channel are the union of the permissions
+    /// in each of its sources. All other channels resolve to themselves.
+    async fn fetch_permission_sources(
+        &self,
+        channel_ids: &[u64],
+    ) -> crate::Result<HashMap<u64, Vec<u64>>> {
+        let mut sources = sqlx::query!(
+            r#"SELECT
+                c.id,
+                COALESCE(ms.source_id, p.id, c.id) AS "source_id!"
+            FROM
+                channels c
+            CROSS JOIN LATERAL
+                (SELECT CASE WHEN c.type = 'thread' THEN c.parent_id ELSE c.id END AS id) p
+            LEFT JOIN
+                merged_channel_sources ms ON ms.merged_id = p.id
+            WHERE
+                c.id = ANY($1::BIGINT[])"#,
+            &channel_ids.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| (r.id as u64, r.source_id as u64))
+        .into_group_map();
+
+        for &channel_id in channel_ids {
+            sources
+                .entry(channel_id)
+                .or_insert_with(|| vec![channel_id]);
+        }
+        Ok(sources)
+    }
+
+    /// Fetches the effective permission overwrites of each of the given channels in a single
+    /// query. Threads resolve to the overwrites of their parent channel.
+    async fn fetch_effective_overwrites(
+        &self,
+        channel_ids: &[u64],
+    ) -> crate::Result<HashMap<u64, Vec<PermissionOverwrite>>> {
+        let mut overwrites = channel_ids
+            .iter()
+            .map(|&id| (id, Vec::new()))
+            .collect::<HashMap<_, _>>();
+
+        for o in sqlx::query!(
+            r#"SELECT
+                c.id AS channel_id,
+                o.target_id,
+                o.allow,
+                o.deny
+            FROM
+                channels c
+            INNER JOIN
+                channel_overwrites o
+                ON o.channel_id = CASE WHEN c.type = 'thread' THEN c.parent_id ELSE c.id END
+            WHERE
+                c.id = ANY($1::BIGINT[])"#,
+            &channel_ids.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .fetch_all(self.executor())
+        .await?
+        {
+            overwrites
+                .entry(o.channel_id as u64)
+                .or_default()
+                .push(PermissionOverwrite {
+                    id: o.target_id as u64,
+                    permissions: PermissionPair {
+                        allow: Permissions::from_bits_truncate(o.allow),
+                        deny: Permissions::from_bits_truncate(o.deny),
+                    },
+                });
+        }
+        Ok(overwrites)
+    }
+
+    /// Calculates the permissions of each of the given members in the given guild, loading the
+    /// roles of the guild and the overwrites of the channel only once. A channel ID may be provided
+    /// to calculate the permissions for a specific channel, otherwise the permissions for the guild
+    /// will be calculated.
+    ///
+    /// Users that are not members of the guild are omitted from the result.
+    ///
+    /// # Errors
+    /// * If the guild does not exist.
+    /// * If an error occurs with the database.
+    async fn fetch_permissions_for_members(
+        &self,
+        guild_id: u64,
+        user_ids: &[u64],
+        channel_id: Option<u64>,
+    ) -> crate::Result<HashMap<u64, Permissions>> {
+        let guild = sqlx::query!(
+            "SELECT owner_id, flags FROM guilds WHERE id = $1",
+            guild_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found("guild", format!("Guild with ID {guild_id} does not exist"))?;
+        let requires_rules = GuildFlags::from_bits_truncate(guild.flags as _)
+            .contains(GuildFlags::REQUIRES_RULES_ACCEPTANCE);
+        let user_ids = user_ids.iter().map(|&id| id as i64).collect::<Vec<_>>();
+
+        let default_role_id = with_model_type(guild_id, ModelType::Role);
+        let roles = self.fetch_all_roles_in_guild(guild_id).await?;
+        let mut assigned = sqlx::query!(
+            "SELECT user_id, role_id FROM role_data WHERE guild_id = $1 AND user_id = ANY($2)",
+            guild_id as i64,
+            &user_ids,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| (r.user_id as u64, r.role_id as u64))
+        .into_group_map();
+        let (sources, overwrites) = match channel_id {
+            Some(channel_id) => {
+                let sources = self
+                    .fetch_permission_sources(&[channel_id])
+                    .await?
+                    .remove(&channel_id)
+                    .unwrap_or_else(|| vec![channel_id]);
+                let overwrites = self.fetch_effective_overwrites(&sources).await?;
+                (Some(sources), overwrites)
+            }
+            None => (None, HashMap::new()),
+        };
+
+        let members = sqlx::query!(
+            "SELECT id, permissions, rules_accepted_at FROM members
+            WHERE guild_id = $1 AND id = ANY($2)",
+            guild_id as i64,
+            &user_ids,
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        Ok(members
+            .into_iter()
+            .map(|m| {
+                let user_id = m.id as u64;
+                if m.id == guild.owner_id {
+                    return (user_id, Permissions::all());
+                }
+
+                let role_ids = assigned.remove(&user_id).unwrap_or_default();
+                let member_roles = roles
+                    .iter()
+                    .filter(|r| r.id == default_role_id || role_ids.contains(&r.id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let base = Permissions::from_bits_truncate(m.permissions);
+                let pending = requires_rules && m.rules_accepted_at.is_none();
+                let permissions = match sources {
+                    Some(ref sources) => {
+                        union_source_permissions(sources, &overwrites, |overwrites| {
+                            crate::calculate_permissions_sorted(
+                                user_id,
+                                base,
+                                &member_roles,
+                                Some(overwrites),
+                                pending,
+                            )
+                        })
+                    }
+                    None => crate::calculate_permissions_sorted(
+                        user_id,
+                        base,
+                        member_roles,
+                        None,
+                        pending,
+                    ),
+                };
+                (user_id, permissions)
+            })
+            .collect())
+    }
+
+    /// Calculates the permissions of the given member in each of the given channels of the guild,
+    /// fetching the roles of the member and the overwrites of all channels only once.
+    ///
+    /// # Errors
+    /// * If the user is not a member of the guild.
+    /// * If an error occurs with the database.
+    async fn fetch_member_permissions_in_channels(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        channel_ids: &[u64],
+    ) -> crate::Result<HashMap<u64, Permissions>> {
+        self.assert_invoker_in_guild(guild_id, user_id).await?;
+        if self.is_guild_owner(guild_id, user_id).await? {
+            return Ok(channel_ids
+                .iter()
+                .map(|&id| (id, Permissions::all()))
+                .collect());
+        }
+
+        let member = sqlx::query!(
+            "SELECT m.permissions, m.rules_accepted_at, g.flags AS guild_flags
+            FROM members m INNER JOIN guilds g ON g.id = m.guild_id
+            WHERE m.guild_id = $1 AND m.id = $2",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .fetch_one(self.executor())
+        .await?;
+        let pending = member.rules_accepted_at.is_none()
+            && GuildFlags::from_bits_truncate(member.guild_flags as _)
+                .contains(GuildFlags::REQUIRES_RULES_ACCEPTANCE);
+        let roles = self.fetch_all_roles_for_member(guild_id, user_id).await?;
+        let base = Permissions::from_bits_truncate(member.permissions);
+
+        let sources = self.fetch_permission_sources(channel_ids).await?;
+        let overwrites = self
+            .fetch_effective_overwrites(&sources.values().flatten().copied().unique().collect_vec())
+            .await?;
+
+        Ok(sources
+            .into_iter()
+            .map(|(channel_id, sources)| {
+                let permissions = union_source_permissions(&sources, &overwrites, |overwrites| {
+                    crate::calculate_permissions_sorted(
+                        user_id,
+                        base,
+                        &roles,
+                        Some(overwrites),
+                        pending,
+                    )
+                });
+                (channel_id, permissions)
+            })
+            .collect())
+    }
+
     /// Internally used, see [`Self::assert_member_has_permissions`] instead.
     fn assert_member_has_permissions_with(
         &self,
@@ -293,8 +614,8 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
     /// # Errors
     /// * If an error occurs with fetching the guild. If the guild is not found, `Ok(None)` is
     /// returned.
-    async fn fetch_partial_guild(&self, guild_id: u64) -> sqlx::Result<Option<PartialGuild>> {
-        let guild = sqlx::query!(
+    async fn fetch_partial_guild(&self, guild_id: u64) -> crate::Result<Option<PartialGuild>> {
+        let Some(mut guild) = sqlx::query!(
             r#"SELECT
                 id,
                 name,
@@ -303,8 +624,7 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
                 banner,
                 owner_id,
                 flags,
-                vanity_url,
-                (SELECT COUNT(*) FROM members WHERE guild_id = $1) AS "member_count!"
+                vanity_url
             FROM
                 guilds
             WHERE
@@ -313,9 +633,81 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
         )
         .fetch_optional(self.executor())
         .await?
-        .map(|r| construct_partial_guild!(r));
+        .map(|r| construct_partial_guild!(r)) else {
+            return Ok(None);
+        };
+
+        guild.member_count = Some(self.fetch_member_counts(guild_id).await?);
+        Ok(Some(guild))
+    }
+
+    /// Fetches the total and online member counts of the given guild. Counts are read from the
+    /// cache, and are seeded from the database if they are not cached yet.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching or seeding the counts.
+    async fn fetch_member_counts(&self, guild_id: u64) -> crate::Result<GuildMemberCount> {
+        if let Some(counts) = cache::guild_counts::guild_counts(guild_id).await? {
+            return Ok(counts);
+        }
+
+        let member_ids = sqlx::query!(
+            "SELECT id FROM members WHERE guild_id = $1",
+            guild_id as i64
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.id as u64)
+        .collect::<Vec<_>>();
+
+        cache::guild_counts::seed_guild_counts(guild_id, member_ids).await
+    }
+
+    /// Fetches the total and online member counts of multiple guilds at once. Counts that are
+    /// not cached yet are seeded from the database in a single query.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching or seeding the counts.
+    async fn fetch_member_counts_bulk(
+        &self,
+        guild_ids: &[u64],
+    ) -> crate::Result<HashMap<u64, GuildMemberCount>> {
+        let cached = cache::guild_counts::guild_counts_bulk(guild_ids).await?;
+        let mut counts = HashMap::with_capacity(guild_ids.len());
+        let mut missing = Vec::new();
+
+        for (&guild_id, count) in guild_ids.iter().zip(cached) {
+            match count {
+                Some(count) => {
+                    counts.insert(guild_id, count);
+                }
+                None => missing.push(guild_id as i64),
+            }
+        }
+        if missing.is_empty() {
+            return Ok(counts);
+        }
+
+        let mut member_ids = sqlx::query!(
+            "SELECT guild_id, id FROM members WHERE guild_id = ANY($1)",
+            &missing,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| (r.guild_id as u64, r.id as u64))
+        .into_group_map();
 
-        Ok(guild)
+        for guild_id in missing {
+            let guild_id = guild_id as u64;
+            let ids = member_ids.remove(&guild_id).unwrap_or_default();
+            counts.insert(
+                guild_id,
+                cache::guild_counts::seed_guild_counts(guild_id, ids).await?,
+            );
+        }
+        Ok(counts)
     }
 
     /// Fetches a guild from the database with the given ID and query.
@@ -405,9 +797,8 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
         query: GetGuildQuery,
     ) -> crate::Result<Vec<Guild>> {
         let mut guilds: HashMap<u64, Guild> = sqlx::query!(
-            r#"SELECT 
-                guilds.*,
-                (SELECT COUNT(*) FROM members WHERE guild_id = $1) AS "member_count!"
+            r#"SELECT
+                guilds.*
             FROM
                 guilds 
             WHERE 
@@ -436,9 +827,12 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
             )
             .await?;
 
-            let out = query_channels!("guild_id = ANY($1::BIGINT[])", &guild_ids)
-                .fetch_all(self.executor())
-                .await?;
+            let out = query_channels!(
+                "guild_id = ANY($1::BIGINT[]) AND type <> 'thread'",
+                &guild_ids
+            )
+            .fetch_all(self.executor())
+            .await?;
 
             let channel_ids: Vec<_> = out.iter().map(|r| r.id).collect();
             let mut last_messages = self.fetch_last_message_map(&channel_ids).await?;
@@ -504,15 +898,22 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
                     users.avatar,
                     users.banner,
                     users.bio,
-                    users.flags
+                    users.pronouns,
+                    users.accent_color,
+                    users.flags,
+                    guilds.flags AS guild_flags
                 FROM
                     members
                 INNER JOIN
                     users
                 ON
                     members.id = users.id
+                INNER JOIN
+                    guilds
+                ON
+                    members.guild_id = guilds.id
                 WHERE
-                    guild_id = ANY($1::BIGINT[])
+                    members.guild_id = ANY($1::BIGINT[])
                 "#,
                 &guild_ids,
             )
@@ -537,7 +938,11 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
 
         if query.emojis {
             let emojis: HashMap<u64, Vec<CustomEmoji>> = sqlx::query!(
-                "SELECT * FROM emojis WHERE guild_id = ANY($1::BIGINT[])",
+                r#"SELECT
+                    *,
+                    ARRAY(SELECT role_id FROM emoji_roles er WHERE er.emoji_id = emojis.id)
+                        AS "roles!"
+                FROM emojis WHERE guild_id = ANY($1::BIGINT[])"#,
                 &guild_ids,
             )
             .fetch_all(self.executor())
@@ -553,11 +958,17 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
             }
         }
 
+        let mut counts = self
+            .fetch_member_counts_bulk(&guilds.keys().copied().collect::<Vec<_>>())
+            .await?;
+        for (guild_id, guild) in &mut guilds {
+            guild.partial.member_count = counts.remove(guild_id);
+        }
+
         Ok(guilds.into_values().collect())
     }
 
-    /// Creates a guild in the database with the given ID, owner ID and payload. Validation should
-    /// be done before calling this function.
+    /// Creates a guild in the database with the given ID, owner ID and payload.
     ///
     /// * `channel_id` is the ID of the default `general` channel all guilds come with.
     /// * `role_id` is the ID of the default role (the `@everyone` role).
@@ -567,6 +978,7 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
     /// * If an error occurs with creating the guild.
     #[allow(clippy::too_many_lines)]
     async fn create_guild(
@@ -577,6 +989,8 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
         owner_id: u64,
         payload: CreateGuildPayload,
     ) -> crate::Result<Guild> {
+        payload.validate()?;
+
         let flags = payload
             .public
             .then_some(GuildFlags::PUBLIC)
@@ -591,8 +1005,8 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
             guild_id as i64,
             payload.name.trim(),
             payload.description,
-            payload.icon,
-            payload.banner,
+            payload.icon.as_deref(),
+            payload.banner.as_deref(),
             owner_id as i64,
             flags.bits() as i32,
         )
@@ -656,7 +1070,7 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
             flags,
             member_count: Some(GuildMemberCount {
                 total: 1,
-                online: None, // TODO
+                online: Some(u32::from(cache::presence::is_online(owner_id).await?)),
             }),
             vanity_url: None,
         };
@@ -680,9 +1094,15 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
             user: MaybePartialUser::Partial { id: owner_id },
             guild_id,
             nick: None,
+            guild_avatar: None,
+            guild_banner: None,
+            guild_bio: None,
             roles: Some(vec![role_id]),
             joined_at,
             permissions: Permissions::empty(),
+            rules_accepted_at: None,
+            pending: false,
+            communication_disabled_until: None,
         };
 
         cache::insert_guild(guild_id).await?;
@@ -697,81 +1117,1289 @@ pub trait GuildDbExt<'t>: DbExt<'t> {
         })
     }
 
-    /// Edits the guild with the given ID with the given payload. Validation should be done before
-    /// calling this function. Returns a tuple of two [`PartialGuild`]s on success: the first
-    /// element is the original guild and the second element is the guild with updated fields.
+    /// Clones the structure of the guild with the given ID into a new guild owned by
+    /// ``owner_id``.
+    ///
+    /// Roles, channels, categories, permission overwrites, channel settings, forum tags and
+    /// optionally custom emojis are copied with fresh snowflakes generated on the given node.
+    /// Members, messages, threads, invites, managed roles, verification and the vanity URL are
+    /// not copied. Permission overwrites that target members or managed roles are dropped.
+    ///
+    /// Returns a tuple ``(guild, emoji_ids)`` where ``emoji_ids`` is a list of
+    /// ``(source_emoji_id, cloned_emoji_id)`` pairs, so that the emoji images can be copied over.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
-    /// * If an error occurs with editing the guild.
-    /// * If the guild does not exist.
-    async fn edit_guild(
+    /// * If the options are invalid.
+    /// * If the source guild does not exist.
+    /// * If an error occurs with creating the guild.
+    #[allow(clippy::too_many_lines)]
+    async fn clone_guild(
         &mut self,
-        guild_id: u64,
-        payload: EditGuildPayload,
-    ) -> crate::Result<(PartialGuild, PartialGuild)> {
-        let old = get_pool()
-            .fetch_partial_guild(guild_id)
+        source_guild_id: u64,
+        owner_id: u64,
+        options: CloneGuildOptions,
+        node_id: u8,
+    ) -> crate::Result<(Guild, Vec<(u64, u64)>)> {
+        options.validate()?;
+
+        let source = get_pool()
+            .fetch_partial_guild(source_guild_id)
             .await?
-            .ok_or_not_found("guild", format!("Guild with ID {guild_id} does not exist"))?;
-        let mut guild = old.clone();
+            .ok_or_not_found(
+                "guild",
+                format!("Guild with ID {source_guild_id} does not exist"),
+            )?;
 
-        if let Some(name) = payload.name {
-            guild.name = name;
+        let guild_id = generate_snowflake(ModelType::Guild, node_id);
+        let name = options.name.unwrap_or(source.name).trim().to_string();
+        // Verification and the vanity URL belong to the source guild only
+        let flags = source.flags - (GuildFlags::VERIFIED | GuildFlags::VANITY_URL);
+
+        sqlx::query!(
+            r#"INSERT INTO
+                guilds (id, name, description, icon, banner, owner_id, flags)
+            VALUES
+                ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            guild_id as i64,
+            name,
+            source.description,
+            source.icon.as_ref().map(Asset::as_str),
+            source.banner.as_ref().map(Asset::as_str),
+            owner_id as i64,
+            flags.bits() as i32,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let joined_at = sqlx::query!(
+            "INSERT INTO members (id, guild_id) VALUES ($1, $2) RETURNING joined_at",
+            owner_id as i64,
+            guild_id as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .joined_at;
+
+        // Roles
+        let source_default_role_id = with_model_type(source_guild_id, ModelType::Role);
+        let mut roles = get_pool().fetch_all_roles_in_guild(source_guild_id).await?;
+        roles.retain(|role| !role.flags.contains(RoleFlags::MANAGED));
+        let role_ids: HashMap<u64, u64> = roles
+            .iter()
+            .map(|role| {
+                let new_id = if role.id == source_default_role_id {
+                    with_model_type(guild_id, ModelType::Role)
+                } else {
+                    generate_snowflake(ModelType::Role, node_id)
+                };
+                (role.id, new_id)
+            })
+            .collect();
+
+        let (old_role_ids, new_role_ids): (Vec<_>, Vec<_>) = role_ids
+            .iter()
+            .map(|(&old, &new)| (old as i64, new as i64))
+            .unzip();
+
+        sqlx::query!(
+            "INSERT INTO roles (
+                id, guild_id, name, color, gradient, icon,
+                allowed_permissions, denied_permissions, position, flags
+            )
+            SELECT
+                m.new_id, $1, r.name, r.color, r.gradient, r.icon,
+                r.allowed_permissions, r.denied_permissions, r.position, r.flags
+            FROM
+                roles r
+            INNER JOIN
+                UNNEST($2::BIGINT[], $3::BIGINT[]) AS m(old_id, new_id) ON r.id = m.old_id",
+            guild_id as i64,
+            &old_role_ids,
+            &new_role_ids,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        for role in &mut roles {
+            role.id = role_ids[&role.id];
+            role.guild_id = guild_id;
         }
 
-        guild.description = payload
-            .description
-            .into_option_or_if_absent(guild.description);
-        guild.icon = payload.icon.into_option_or_if_absent(guild.icon);
-        guild.banner = payload.banner.into_option_or_if_absent(guild.banner);
+        // Emojis
+        let mut emoji_ids = Vec::new();
+        let mut emojis = Vec::new();
+        if options.emojis {
+            emojis = get_pool()
+                .fetch_all_emojis_in_guild(source_guild_id)
+                .await?;
+            for emoji in &mut emojis {
+                let new_id = generate_snowflake(ModelType::Emoji, node_id);
+                emoji_ids.push((emoji.id, new_id));
+                emoji.id = new_id;
+                emoji.guild_id = guild_id;
+                emoji.created_by = Some(owner_id);
+                emoji.roles = emoji
+                    .roles
+                    .iter()
+                    .filter_map(|id| role_ids.get(id).copied())
+                    .collect();
+            }
 
-        match payload.public {
-            Some(true) => guild.flags.insert(GuildFlags::PUBLIC),
-            Some(false) => guild.flags.remove(GuildFlags::PUBLIC),
-            None => (),
+            let (old_emoji_ids, new_emoji_ids): (Vec<_>, Vec<_>) = emoji_ids
+                .iter()
+                .map(|&(old, new)| (old as i64, new as i64))
+                .unzip();
+
+            sqlx::query!(
+                "INSERT INTO emojis (id, guild_id, name, created_by)
+                SELECT m.new_id, $1, e.name, $2
+                FROM emojis e
+                INNER JOIN
+                    UNNEST($3::BIGINT[], $4::BIGINT[]) AS m(old_id, new_id) ON e.id = m.old_id",
+                guild_id as i64,
+                owner_id as i64,
+                &old_emoji_ids,
+                &new_emoji_ids,
+            )
+            .execute(self.transaction())
+            .await?;
+
+            sqlx::query!(
+                "INSERT INTO emoji_roles (emoji_id, role_id)
+                SELECT e.new_id, r.new_id
+                FROM emoji_roles er
+                INNER JOIN
+                    UNNEST($1::BIGINT[], $2::BIGINT[]) AS e(old_id, new_id)
+                    ON er.emoji_id = e.old_id
+                INNER JOIN
+                    UNNEST($3::BIGINT[], $4::BIGINT[]) AS r(old_id, new_id)
+                    ON er.role_id = r.old_id",
+                &old_emoji_ids,
+                &new_emoji_ids,
+                &old_role_ids,
+                &new_role_ids,
+            )
+            .execute(self.transaction())
+            .await?;
         }
+        let emoji_id_map: HashMap<u64, u64> = emoji_ids.iter().copied().collect();
+
+        // Channels, categories first so that their children follow the same order
+        let mut source_channels = get_pool()
+            .fetch_all_channels_in_guild(source_guild_id)
+            .await?;
+        source_channels.retain(|c| c.info.channel_type() != ChannelType::Merged);
+        source_channels
+            .sort_by_key(|c| (c.info.channel_type() != ChannelType::Category, c.position));
+
+        let channel_ids: HashMap<u64, u64> = source_channels
+            .iter()
+            .map(|c| (c.id, generate_snowflake(ModelType::Channel, node_id)))
+            .collect();
+
+        let mut forum_tags = Vec::new();
+        let payloads = source_channels
+            .iter()
+            .map(|channel| {
+                let new_id = channel_ids[&channel.id];
+                let info = match &channel.info {
+                    GuildChannelInfo::Text(info) => CreateGuildChannelInfo::Text {
+                        topic: info.topic.clone(),
+                    },
+                    GuildChannelInfo::Announcement(info) => CreateGuildChannelInfo::Announcement {
+                        topic: info.topic.clone(),
+                    },
+                    GuildChannelInfo::Voice { user_limit } => CreateGuildChannelInfo::Voice {
+                        user_limit: *user_limit,
+                    },
+                    GuildChannelInfo::Forum(info) => {
+                        forum_tags.extend(info.tags.iter().map(|tag| (new_id, tag.clone())));
+                        CreateGuildChannelInfo::Forum {
+                            topic: info.topic.clone(),
+                            default_sort_order: info.default_sort_order,
+                        }
+                    }
+                    _ => CreateGuildChannelInfo::Category,
+                };
+                let overwrites = channel
+                    .overwrites
+                    .iter()
+                    .filter_map(|overwrite| {
+                        Some(PermissionOverwrite {
+                            id: *role_ids.get(&overwrite.id)?,
+                            permissions: overwrite.permissions,
+                        })
+                    })
+                    .collect();
+
+                (
+                    new_id,
+                    CreateGuildChannelPayload {
+                        name: channel.name.clone(),
+                        info,
+                        color: channel.color.clone(),
+                        icon: channel.icon.clone(),
+                        parent_id: channel
+                            .parent_id
+                            .and_then(|id| channel_ids.get(&id).copied()),
+                        overwrites: Some(overwrites),
+                        nonce: None,
+                    },
+                )
+            })
+            .collect();
+
+        let mut channels = self.bulk_create_guild_channels(guild_id, payloads).await?;
+
+        let (old_channel_ids, new_channel_ids): (Vec<_>, Vec<_>) = channel_ids
+            .iter()
+            .map(|(&old, &new)| (old as i64, new as i64))
+            .unzip();
 
         sqlx::query!(
-            r#"UPDATE
-                guilds
-            SET
-                name = $1, description = $2, icon = $3, banner = $4, flags = $5
+            "UPDATE channels c
+            SET nsfw = s.nsfw, locked = s.locked, slowmode = s.slowmode
+            FROM
+                channels s
+            INNER JOIN
+                UNNEST($1::BIGINT[], $2::BIGINT[]) AS m(old_id, new_id) ON s.id = m.old_id
             WHERE
-                id = $6
-            "#,
-            guild.name,
-            guild.description,
-            guild.icon,
-            guild.banner,
-            guild.flags.bits() as i32,
-            guild_id as i64,
+                c.id = m.new_id",
+            &old_channel_ids,
+            &new_channel_ids,
         )
         .execute(self.transaction())
         .await?;
 
-        Ok((old, guild))
-    }
+        let source_settings: HashMap<u64, (bool, bool, u32)> = source_channels
+            .iter()
+            .filter_map(|channel| match &channel.info {
+                GuildChannelInfo::Text(info) | GuildChannelInfo::Announcement(info) => Some((
+                    channel_ids[&channel.id],
+                    (info.nsfw, info.locked, info.slowmode),
+                )),
+                GuildChannelInfo::Forum(info) => {
+                    Some((channel_ids[&channel.id], (info.nsfw, false, 0)))
+                }
+                _ => None,
+            })
+            .collect();
 
-    /// Deletes a guild from the database with the given ID.
-    ///
-    /// # Note
-    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
-    /// rolled back, and the transaction must be committed to save the changes.
-    ///
-    /// # Errors
-    /// * If an error occurs with deleting the guild.
-    /// * If the guild does not exist.
-    async fn delete_guild(&mut self, guild_id: u64) -> crate::Result<()> {
-        sqlx::query!("DELETE FROM guilds WHERE id = $1", guild_id as i64)
+        // Forum tags
+        for (forum_id, tag) in &mut forum_tags {
+            tag.id = generate_snowflake(ModelType::Internal, node_id);
+            if let Some(ref mut emoji) = tag.emoji
+                && let Some(id) = emoji.id
+                && let Some(&new_id) = emoji_id_map.get(&id)
+            {
+                emoji.id = Some(new_id);
+            }
+
+            sqlx::query!(
+                "INSERT INTO forum_tags (id, channel_id, name, emoji_id, emoji_name, moderated)
+                VALUES ($1, $2, $3, $4, $5, $6)",
+                tag.id as i64,
+                *forum_id as i64,
+                tag.name,
+                tag.emoji.as_ref().and_then(|e| e.id).map(|id| id as i64),
+                tag.emoji.as_ref().map(|e| e.name.as_str()),
+                tag.moderated,
+            )
             .execute(self.transaction())
             .await?;
+        }
 
-        cache::remove_guild(guild_id).await?;
-        Ok(())
+        for channel in &mut channels {
+            match &mut channel.info {
+                GuildChannelInfo::Text(info) | GuildChannelInfo::Announcement(info) => {
+                    if let Some(&(nsfw, locked, slowmode)) = source_settings.get(&channel.id) {
+                        info.nsfw = nsfw;
+                        info.locked = locked;
+                        info.slowmode = slowmode;
+                    }
+                }
+                GuildChannelInfo::Forum(info) => {
+                    if let Some(&(nsfw, ..)) = source_settings.get(&channel.id) {
+                        info.nsfw = nsfw;
+                    }
+                    info.tags = forum_tags
+                        .iter()
+                        .filter(|(forum_id, _)| *forum_id == channel.id)
+                        .map(|(_, tag)| tag.clone())
+                        .collect();
+                }
+                _ => (),
+            }
+        }
+
+        let partial = PartialGuild {
+            id: guild_id,
+            name,
+            description: source.description,
+            icon: source.icon,
+            banner: source.banner,
+            owner_id,
+            flags,
+            member_count: Some(GuildMemberCount {
+                total: 1,
+                online: Some(u32::from(cache::presence::is_online(owner_id).await?)),
+            }),
+            vanity_url: None,
+        };
+
+        let member = Member {
+            user: MaybePartialUser::Partial { id: owner_id },
+            guild_id,
+            nick: None,
+            guild_avatar: None,
+            guild_banner: None,
+            guild_bio: None,
+            roles: Some(vec![with_model_type(guild_id, ModelType::Role)]),
+            joined_at,
+            permissions: Permissions::empty(),
+            rules_accepted_at: None,
+            pending: false,
+            communication_disabled_until: None,
+        };
+
+        cache::insert_guild(guild_id).await?;
+        cache::update_owner_of_guild(guild_id, owner_id).await?;
+
+        Ok((
+            Guild {
+                partial,
+                members: Some(vec![member]),
+                roles: Some(roles),
+                channels: Some(channels),
+                emojis: Some(emojis),
+            },
+            emoji_ids,
+        ))
+    }
+
+    /// Creates a [`GuildTemplate`] from the current structure of the guild with the given ID.
+    ///
+    /// Roles, channels, categories, role permission overwrites and settings are captured, and
+    /// snowflakes are replaced with template-local IDs. Managed roles, member overwrites, threads,
+    /// emojis, forum tags, assets and messages are not included.
+    ///
+    /// # Errors
+    /// * If the guild does not exist.
+    /// * If an error occurs with fetching the guild structure.
+    async fn create_guild_template(&self, guild_id: u64) -> crate::Result<GuildTemplate> {
+        let guild = self
+            .fetch_partial_guild(guild_id)
+            .await?
+            .ok_or_not_found("guild", format!("Guild with ID {guild_id} does not exist"))?;
+
+        let default_role_id = with_model_type(guild_id, ModelType::Role);
+        let mut roles = self.fetch_all_roles_in_guild(guild_id).await?;
+        roles.retain(|role| !role.flags.contains(RoleFlags::MANAGED));
+        roles.sort_by_key(|role| (role.id != default_role_id, role.position));
+
+        // The default role is sorted first, giving it an ID of 0
+        let role_ids: HashMap<u64, u32> = roles
+            .iter()
+            .zip(0..)
+            .map(|(role, id)| (role.id, id))
+            .collect();
+
+        let mut channels = self.fetch_all_channels_in_guild(guild_id).await?;
+        channels.retain(|c| {
+            !matches!(
+                c.info.channel_type(),
+                ChannelType::Merged | ChannelType::Thread
+            )
+        });
+        channels.sort_by_key(|c| (c.info.channel_type() != ChannelType::Category, c.position));
+
+        let channel_ids: HashMap<u64, u32> = channels
+            .iter()
+            .zip(0..)
+            .map(|(channel, id)| (channel.id, id))
+            .collect();
+
+        Ok(GuildTemplate {
+            version: GUILD_TEMPLATE_VERSION,
+            name: guild.name,
+            description: guild.description,
+            flags: guild.flags & GuildFlags::REQUIRES_RULES_ACCEPTANCE,
+            roles: roles
+                .into_iter()
+                .map(|role| TemplateRole {
+                    id: role_ids[&role.id],
+                    name: role.name,
+                    color: role.color,
+                    permissions: role.permissions,
+                    position: role.position,
+                    flags: role.flags,
+                })
+                .collect(),
+            channels: channels
+                .into_iter()
+                .map(|channel| TemplateChannel {
+                    id: channel_ids[&channel.id],
+                    parent_id: channel
+                        .parent_id
+                        .and_then(|id| channel_ids.get(&id).copied()),
+                    info: match channel.info {
+                        GuildChannelInfo::Text(info) => TemplateChannelInfo::Text {
+                            topic: info.topic,
+                            nsfw: info.nsfw,
+                            locked: info.locked,
+                            slowmode: info.slowmode,
+                        },
+                        GuildChannelInfo::Announcement(info) => TemplateChannelInfo::Announcement {
+                            topic: info.topic,
+                            nsfw: info.nsfw,
+                            locked: info.locked,
+                            slowmode: info.slowmode,
+                        },
+                        GuildChannelInfo::Voice { user_limit } => {
+                            TemplateChannelInfo::Voice { user_limit }
+                        }
+                        GuildChannelInfo::Forum(info) => TemplateChannelInfo::Forum {
+                            topic: info.topic,
+                            nsfw: info.nsfw,
+                            default_sort_order: info.default_sort_order,
+                        },
+                        _ => TemplateChannelInfo::Category,
+                    },
+                    name: channel.name,
+                    color: channel.color,
+                    position: channel.position,
+                    overwrites: channel
+                        .overwrites
+                        .into_iter()
+                        .filter_map(|overwrite| {
+                            Some(TemplateOverwrite {
+                                role_id: *role_ids.get(&overwrite.id)?,
+                                permissions: overwrite.permissions,
+                            })
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Creates a new guild owned by ``owner_id`` from the given [`GuildTemplate`], generating
+    /// fresh snowflakes on the given node. The whole structure is created in the current
+    /// transaction, so a template that fails validation part-way leaves nothing behind once the
+    /// transaction is rolled back.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the template version is not supported.
+    /// * If the name or description of the guild, or the name of any role, is invalid.
+    /// * If the template has no default role, or has duplicate role or channel IDs.
+    /// * If a channel references a parent or role that is not in the template.
+    /// * If any of the colors in the template are invalid.
+    /// * If an error occurs with creating the guild.
+    #[allow(clippy::too_many_lines)]
+    async fn create_guild_from_template(
+        &mut self,
+        template: GuildTemplate,
+        owner_id: u64,
+        node_id: u8,
+    ) -> crate::Result<Guild> {
+        if template.version != GUILD_TEMPLATE_VERSION {
+            return Err(Error::InvalidField {
+                field: "version".to_string(),
+                message: format!(
+                    "Unsupported template version {}, expected {GUILD_TEMPLATE_VERSION}",
+                    template.version,
+                ),
+            });
+        }
+        template.validate()?;
+        if !template.roles.iter().any(|role| role.id == 0) {
+            return Err(Error::InvalidField {
+                field: "roles".to_string(),
+                message: "Template must include a default role with ID 0".to_string(),
+            });
+        }
+
+        let guild_id = generate_snowflake(ModelType::Guild, node_id);
+        let flags = template.flags & GuildFlags::REQUIRES_RULES_ACCEPTANCE;
+
+        sqlx::query!(
+            r#"INSERT INTO
+                guilds (id, name, description, owner_id, flags)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            "#,
+            guild_id as i64,
+            template.name.trim(),
+            template.description,
+            owner_id as i64,
+            flags.bits() as i32,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let joined_at = sqlx::query!(
+            "INSERT INTO members (id, guild_id) VALUES ($1, $2) RETURNING joined_at",
+            owner_id as i64,
+            guild_id as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .joined_at;
+
+        // Roles
+        let mut role_ids = HashMap::with_capacity(template.roles.len());
+        let mut roles = Vec::with_capacity(template.roles.len());
+        for role in template.roles {
+            let is_default = role.id == 0;
+            let new_id = if is_default {
+                with_model_type(guild_id, ModelType::Role)
+            } else {
+                generate_snowflake(ModelType::Role, node_id)
+            };
+            if role_ids.insert(role.id, new_id).is_some() {
+                return Err(Error::InvalidField {
+                    field: "roles".to_string(),
+                    message: format!("Duplicate role ID {} in template", role.id),
+                });
+            }
+            if let Some(ref color) = role.color {
+                color.validate()?;
+            }
+
+            let mut flags = role.flags - RoleFlags::MANAGED - RoleFlags::DEFAULT;
+            flags.set(RoleFlags::DEFAULT, is_default);
+            let position = if is_default { 0 } else { role.position };
+            let (color, gradient) = role.color.as_ref().map(ExtendedColor::to_db).unzip();
+
+            sqlx::query!(
+                r#"INSERT INTO roles (
+                    id, guild_id, name, color, gradient,
+                    allowed_permissions, denied_permissions, position, flags
+                )
+                VALUES
+                    ($1, $2, $3, $4, $5::gradient_type, $6, $7, $8, $9)
+                "#,
+                new_id as i64,
+                guild_id as i64,
+                role.name,
+                color.flatten(),
+                gradient.flatten() as _,
+                role.permissions.allow.bits(),
+                role.permissions.deny.bits(),
+                position as i16,
+                flags.bits() as i32,
+            )
+            .execute(self.transaction())
+            .await?;
+
+            roles.push(Role {
+                id: new_id,
+                guild_id,
+                name: role.name,
+                color: role.color,
+                icon: None,
+                permissions: role.permissions,
+                position,
+                flags,
+            });
+        }
+
+        // Channels, categories first so that their children can reference them
+        let mut template_channels = template.channels;
+        template_channels
+            .sort_by_key(|c| (!matches!(c.info, TemplateChannelInfo::Category), c.position));
+
+        let mut channel_ids = HashMap::with_capacity(template_channels.len());
+        for channel in &template_channels {
+            if channel_ids
+                .insert(channel.id, generate_snowflake(ModelType::Channel, node_id))
+                .is_some()
+            {
+                return Err(Error::InvalidField {
+                    field: "channels".to_string(),
+                    message: format!("Duplicate channel ID {} in template", channel.id),
+                });
+            }
+        }
+
+        let mut settings = HashMap::new();
+        let mut payloads = Vec::with_capacity(template_channels.len());
+        for channel in template_channels {
+            let new_id = channel_ids[&channel.id];
+            let parent_id = channel
+                .parent_id
+                .map(|id| {
+                    channel_ids
+                        .get(&id)
+                        .copied()
+                        .ok_or_else(|| Error::InvalidField {
+                            field: "channels".to_string(),
+                            message: format!(
+                                "Channel {} references unknown parent channel {id}",
+                                channel.id,
+                            ),
+                        })
+                })
+                .transpose()?;
+            let overwrites = channel
+                .overwrites
+                .into_iter()
+                .map(|overwrite| {
+                    Ok(PermissionOverwrite {
+                        id: *role_ids.get(&overwrite.role_id).ok_or_else(|| {
+                            Error::InvalidField {
+                                field: "channels".to_string(),
+                                message: format!(
+                                    "Channel {} has an overwrite for unknown role {}",
+                                    channel.id, overwrite.role_id,
+                                ),
+                            }
+                        })?,
+                        permissions: overwrite.permissions,
+                    })
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+
+            let info = match channel.info {
+                TemplateChannelInfo::Text {
+                    topic,
+                    nsfw,
+                    locked,
+                    slowmode,
+                } => {
+                    settings.insert(new_id, (nsfw, locked, slowmode));
+                    CreateGuildChannelInfo::Text { topic }
+                }
+                TemplateChannelInfo::Announcement {
+                    topic,
+                    nsfw,
+                    locked,
+                    slowmode,
+                } => {
+                    settings.insert(new_id, (nsfw, locked, slowmode));
+                    CreateGuildChannelInfo::Announcement { topic }
+                }
+                TemplateChannelInfo::Voice { user_limit } => {
+                    CreateGuildChannelInfo::Voice { user_limit }
+                }
+                TemplateChannelInfo::Category => CreateGuildChannelInfo::Category,
+                TemplateChannelInfo::Forum {
+                    topic,
+                    nsfw,
+                    default_sort_order,
+                } => {
+                    settings.insert(new_id, (nsfw, false, 0));
+                    CreateGuildChannelInfo::Forum {
+                        topic,
+                        default_sort_order,
+                    }
+                }
+            };
+
+            payloads.push((
+                new_id,
+                CreateGuildChannelPayload {
+                    name: channel.name,
+                    info,
+                    color: channel.color,
+                    icon: None,
+                    parent_id,
+                    overwrites: Some(overwrites),
+                    nonce: None,
+                },
+            ));
+        }
+
+        let mut channels = self.bulk_create_guild_channels(guild_id, payloads).await?;
+
+        let mut ids = Vec::with_capacity(settings.len());
+        let mut nsfw = Vec::with_capacity(settings.len());
+        let mut locked = Vec::with_capacity(settings.len());
+        let mut slowmode = Vec::with_capacity(settings.len());
+        for (&id, &(n, l, s)) in &settings {
+            ids.push(id as i64);
+            nsfw.push(n);
+            locked.push(l);
+            slowmode.push(s as i32);
+        }
+
+        sqlx::query!(
+            "UPDATE channels c
+            SET nsfw = s.nsfw, locked = s.locked, slowmode = s.slowmode
+            FROM
+                UNNEST($1::BIGINT[], $2::BOOLEAN[], $3::BOOLEAN[], $4::INTEGER[])
+                AS s(id, nsfw, locked, slowmode)
+            WHERE
+                c.id = s.id",
+            &ids,
+            &nsfw,
+            &locked,
+            &slowmode,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        for channel in &mut channels {
+            let Some(&(nsfw, locked, slowmode)) = settings.get(&channel.id) else {
+                continue;
+            };
+            match &mut channel.info {
+                GuildChannelInfo::Text(info) | GuildChannelInfo::Announcement(info) => {
+                    info.nsfw = nsfw;
+                    info.locked = locked;
+                    info.slowmode = slowmode;
+                }
+                GuildChannelInfo::Forum(info) => info.nsfw = nsfw,
+                _ => (),
+            }
+        }
+
+        let partial = PartialGuild {
+            id: guild_id,
+            name: template.name,
+            description: template.description,
+            icon: None,
+            banner: None,
+            owner_id,
+            flags,
+            member_count: Some(GuildMemberCount {
+                total: 1,
+                online: Some(u32::from(cache::presence::is_online(owner_id).await?)),
+            }),
+            vanity_url: None,
+        };
+
+        let member = Member {
+            user: MaybePartialUser::Partial { id: owner_id },
+            guild_id,
+            nick: None,
+            guild_avatar: None,
+            guild_banner: None,
+            guild_bio: None,
+            roles: Some(vec![with_model_type(guild_id, ModelType::Role)]),
+            joined_at,
+            permissions: Permissions::empty(),
+            rules_accepted_at: None,
+            pending: false,
+            communication_disabled_until: None,
+        };
+
+        cache::insert_guild(guild_id).await?;
+        cache::update_owner_of_guild(guild_id, owner_id).await?;
+
+        Ok(Guild {
+            partial,
+            members: Some(vec![member]),
+            roles: Some(roles),
+            channels: Some(channels),
+            emojis: Some(Vec::new()),
+        })
+    }
+
+    /// Edits the guild with the given ID with the given payload. Returns a tuple of two
+    /// [`PartialGuild`]s on success: the first element is the original guild and the second
+    /// element is the guild with updated fields.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid.
+    /// * If an error occurs with editing the guild.
+    /// * If the guild does not exist.
+    async fn edit_guild(
+        &mut self,
+        guild_id: u64,
+        payload: EditGuildPayload,
+    ) -> crate::Result<(PartialGuild, PartialGuild)> {
+        payload.validate()?;
+
+        let old = get_pool()
+            .fetch_partial_guild(guild_id)
+            .await?
+            .ok_or_not_found("guild", format!("Guild with ID {guild_id} does not exist"))?;
+        let mut guild = old.clone();
+
+        if let Some(name) = payload.name {
+            guild.name = name;
+        }
+
+        guild.description = payload
+            .description
+            .into_option_or_if_absent(guild.description);
+        guild.icon = payload.icon.into_option_or_if_absent(guild.icon);
+        guild.banner = payload.banner.into_option_or_if_absent(guild.banner);
+
+        match payload.public {
+            Some(true) => guild.flags.insert(GuildFlags::PUBLIC),
+            Some(false) => guild.flags.remove(GuildFlags::PUBLIC),
+            None => (),
+        }
+        if let Some(requires_rules_acceptance) = payload.requires_rules_acceptance {
+            guild.flags.set(
+                GuildFlags::REQUIRES_RULES_ACCEPTANCE,
+                requires_rules_acceptance,
+            );
+        }
+
+        sqlx::query!(
+            r#"UPDATE
+                guilds
+            SET
+                name = $1, description = $2, icon = $3, banner = $4, flags = $5
+            WHERE
+                id = $6
+            "#,
+            guild.name,
+            guild.description,
+            guild.icon.as_deref(),
+            guild.banner.as_deref(),
+            guild.flags.bits() as i32,
+            guild_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if old.flags.contains(GuildFlags::REQUIRES_RULES_ACCEPTANCE)
+            != guild.flags.contains(GuildFlags::REQUIRES_RULES_ACCEPTANCE)
+        {
+            cache::clear_member_permissions(guild_id).await?;
+        }
+
+        Ok((old, guild))
+    }
+
+    /// Sets or clears the vanity URL code of the guild. Codes are unique regardless of case, but
+    /// are stored with the case they were set with.
+    ///
+    /// Only public guilds with at least [`VANITY_URL_MIN_MEMBERS`] non-bot members may set a
+    /// vanity URL, unless they are verified. Clearing the vanity URL is always allowed.
+    ///
+    /// Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the guild does not exist.
+    /// * If the guild is not eligible for a vanity URL.
+    /// * If the code is invalid or reserved.
+    /// * If the code is used by another guild.
+    /// * If an error occurs with setting the vanity URL.
+    async fn set_vanity_url(
+        &mut self,
+        guild_id: u64,
+        slug: Option<&str>,
+    ) -> crate::Result<(PartialGuild, PartialGuild)> {
+        let old = get_pool()
+            .fetch_partial_guild(guild_id)
+            .await?
+            .ok_or_not_found("guild", format!("Guild with ID {guild_id} does not exist"))?;
+        let mut guild = old.clone();
+
+        if let Some(slug) = slug {
+            if !guild.flags.contains(GuildFlags::VERIFIED) {
+                let human_count = sqlx::query!(
+                    r#"SELECT COUNT(*) AS "count!" FROM members m
+                    INNER JOIN users u ON u.id = m.id
+                    WHERE m.guild_id = $1 AND u.flags & $2 = 0"#,
+                    guild_id as i64,
+                    UserFlags::BOT.bits() as i32,
+                )
+                .fetch_one(self.executor())
+                .await?
+                .count;
+
+                if !guild.flags.contains(GuildFlags::PUBLIC)
+                    || human_count < VANITY_URL_MIN_MEMBERS as i64
+                {
+                    return Err(Error::InvalidField {
+                        field: "vanity_url".to_string(),
+                        message: format!(
+                            "Only public guilds with at least {VANITY_URL_MIN_MEMBERS} members \
+                            can set a vanity URL"
+                        ),
+                    });
+                }
+            }
+
+            validate_vanity_url(slug)?;
+            let taken = sqlx::query!(
+                r#"SELECT EXISTS(
+                    SELECT 1 FROM guilds WHERE LOWER(vanity_url) = LOWER($1) AND id != $2
+                ) AS "taken!""#,
+                slug,
+                guild_id as i64,
+            )
+            .fetch_one(self.executor())
+            .await?
+            .taken;
+
+            if taken {
+                return Err(Error::VanityTaken {
+                    vanity_url: slug.to_string(),
+                    message: "This vanity URL is already taken".to_string(),
+                });
+            }
+        }
+
+        guild.vanity_url = slug.map(ToString::to_string);
+        guild.flags.set(GuildFlags::VANITY_URL, slug.is_some());
+        sqlx::query!(
+            "UPDATE guilds SET vanity_url = $1, flags = $2 WHERE id = $3",
+            guild.vanity_url,
+            guild.flags.bits() as i32,
+            guild_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((old, guild))
+    }
+
+    /// Resolves the ID of the guild with the given vanity URL code, ignoring case.
+    ///
+    /// # Errors
+    /// * If an error occurs with resolving the vanity URL. If no guild has the vanity URL,
+    /// `Ok(None)` is returned.
+    async fn resolve_vanity_url(&self, slug: &str) -> crate::Result<Option<u64>> {
+        Ok(sqlx::query!(
+            "SELECT id FROM guilds WHERE LOWER(vanity_url) = LOWER($1)",
+            slug
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| r.id as u64))
+    }
+
+    /// Deletes a guild from the database with the given ID.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with deleting the guild.
+    /// * If the guild does not exist.
+    async fn delete_guild(&mut self, guild_id: u64) -> crate::Result<()> {
+        sqlx::query!("DELETE FROM guilds WHERE id = $1", guild_id as i64)
+            .execute(self.transaction())
+            .await?;
+
+        cache::remove_guild(guild_id).await?;
+        Ok(())
+    }
+
+    /// Asserts that all of the given channels exist in the given guild.
+    ///
+    /// # Errors
+    /// * If any of the channels does not exist in the guild.
+    /// * If an error occurs with fetching the channels.
+    async fn assert_channels_in_guild(
+        &self,
+        guild_id: u64,
+        channel_ids: &[u64],
+    ) -> crate::Result<()> {
+        let channel_ids = channel_ids
+            .iter()
+            .map(|&id| id as i64)
+            .unique()
+            .collect_vec();
+        let found = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM channels WHERE guild_id = $1 AND id = ANY($2)"#,
+            guild_id as i64,
+            &channel_ids,
+        )
+        .fetch_one(self.executor())
+        .await?
+        .count;
+
+        if found as usize != channel_ids.len() {
+            return Err(Error::NotFound {
+                entity: "channel".to_string(),
+                message: format!("Some of the given channels do not exist in guild {guild_id}"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fetches the welcome screen of a guild. Guilds that never configured a welcome screen have
+    /// a disabled, empty welcome screen.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the welcome screen.
+    async fn fetch_welcome_screen(&self, guild_id: u64) -> crate::Result<GuildWelcomeScreen> {
+        let screen = sqlx::query!(
+            r#"SELECT
+                enabled,
+                description,
+                channels AS "channels: sqlx::types::Json<Vec<WelcomeScreenChannel>>"
+            FROM guild_welcome_screens
+            WHERE guild_id = $1"#,
+            guild_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map_or_else(GuildWelcomeScreen::default, |r| GuildWelcomeScreen {
+            enabled: r.enabled,
+            description: r.description,
+            channels: r.channels.0,
+        });
+
+        Ok(screen)
+    }
+
+    /// Edits the welcome screen of a guild. Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the description or a channel description is too long.
+    /// * If too many channels are highlighted, or a channel is highlighted more than once.
+    /// * If a highlighted channel does not exist in the guild.
+    /// * If an error occurs with editing the welcome screen.
+    async fn edit_welcome_screen(
+        &mut self,
+        guild_id: u64,
+        payload: EditWelcomeScreenPayload,
+    ) -> crate::Result<(GuildWelcomeScreen, GuildWelcomeScreen)> {
+        payload.validate()?;
+
+        let old = get_pool().fetch_welcome_screen(guild_id).await?;
+        let mut screen = old.clone();
+
+        if let Some(enabled) = payload.enabled {
+            screen.enabled = enabled;
+        }
+        screen.description = payload
+            .description
+            .into_option_or_if_absent(screen.description);
+
+        if let Some(channels) = payload.channels {
+            get_pool()
+                .assert_channels_in_guild(
+                    guild_id,
+                    &channels.iter().map(|c| c.channel_id).collect_vec(),
+                )
+                .await?;
+            screen.channels = channels;
+        }
+
+        let channels =
+            serde_json::to_value(&screen.channels).map_err(|err| Error::InternalError {
+                what: Some("welcome screen serialization".to_string()),
+                message: err.to_string(),
+                debug: Some(format!("{err:?}")),
+            })?;
+        sqlx::query!(
+            "INSERT INTO guild_welcome_screens (guild_id, enabled, description, channels)
+            VALUES ($1, $2, $3, $4::JSONB)
+            ON CONFLICT (guild_id) DO UPDATE
+            SET enabled = $2, description = $3, channels = $4::JSONB",
+            guild_id as i64,
+            screen.enabled,
+            screen.description,
+            channels,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((old, screen))
+    }
+
+    /// Fetches the onboarding configuration of a guild. Guilds that never configured onboarding
+    /// have onboarding disabled and no prompts.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the configuration.
+    async fn fetch_onboarding(&self, guild_id: u64) -> crate::Result<GuildOnboarding> {
+        let onboarding = sqlx::query!(
+            r#"SELECT
+                enabled,
+                prompts AS "prompts: sqlx::types::Json<Vec<OnboardingPrompt>>"
+            FROM guild_onboarding
+            WHERE guild_id = $1"#,
+            guild_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map_or_else(GuildOnboarding::default, |r| GuildOnboarding {
+            enabled: r.enabled,
+            prompts: r.prompts.0,
+        });
+
+        Ok(onboarding)
+    }
+
+    /// Edits the onboarding configuration of a guild. Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If there are too many prompts or options, or a title is out of range.
+    /// * If a prompt has no options.
+    /// * If an option assigns a role that does not exist in the guild, is managed, or is the
+    /// default role.
+    /// * If an error occurs with editing the configuration.
+    async fn edit_onboarding(
+        &mut self,
+        guild_id: u64,
+        payload: EditOnboardingPayload,
+    ) -> crate::Result<(GuildOnboarding, GuildOnboarding)> {
+        payload.validate()?;
+
+        let old = get_pool().fetch_onboarding(guild_id).await?;
+        let mut onboarding = old.clone();
+
+        if let Some(enabled) = payload.enabled {
+            onboarding.enabled = enabled;
+        }
+        if let Some(prompts) = payload.prompts {
+            let invalid = |message: &str| Error::InvalidField {
+                field: "prompts".to_string(),
+                message: message.to_string(),
+            };
+            let role_ids = prompts
+                .iter()
+                .flat_map(|p| &p.options)
+                .flat_map(|o| o.role_ids.iter().map(|&id| id as i64))
+                .unique()
+                .collect_vec();
+            let assignable = sqlx::query!(
+                r#"SELECT COUNT(*) AS "count!" FROM roles
+                WHERE guild_id = $1 AND id = ANY($2) AND id != $3 AND flags & $4 = 0"#,
+                guild_id as i64,
+                &role_ids,
+                with_model_type(guild_id, ModelType::Role) as i64,
+                RoleFlags::MANAGED.bits() as i32,
+            )
+            .fetch_one(self.executor())
+            .await?
+            .count;
+            if assignable as usize != role_ids.len() {
+                return Err(invalid(
+                    "Options can only assign roles of the guild that are not managed and are not \
+                    the default role",
+                ));
+            }
+            onboarding.prompts = prompts;
+        }
+
+        let prompts =
+            serde_json::to_value(&onboarding.prompts).map_err(|err| Error::InternalError {
+                what: Some("onboarding serialization".to_string()),
+                message: err.to_string(),
+                debug: Some(format!("{err:?}")),
+            })?;
+        sqlx::query!(
+            "INSERT INTO guild_onboarding (guild_id, enabled, prompts)
+            VALUES ($1, $2, $3::JSONB)
+            ON CONFLICT (guild_id) DO UPDATE
+            SET enabled = $2, prompts = $3::JSONB",
+            guild_id as i64,
+            onboarding.enabled,
+            prompts,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((old, onboarding))
+    }
+
+    /// Applies the onboarding answers of a member, assigning the roles of every picked option in
+    /// addition to the roles the member already has. Roles are assigned in a single statement, so
+    /// either all of them or none are assigned. Roles of options that were deleted since the
+    /// onboarding was configured are ignored.
+    ///
+    /// Returns the IDs of the roles that were assigned and the IDs of the roles the member has
+    /// after the answers were applied.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    /// The cached role members should be updated with the assigned roles using
+    /// [`add_member_to_roles`](crate::cache::add_member_to_roles) after the change is committed.
+    ///
+    /// # Errors
+    /// * If onboarding is not enabled in the guild.
+    /// * If an answer references a prompt or option that does not exist, or a prompt is answered
+    /// more than once.
+    /// * If more than one option is picked for a single-select prompt.
+    /// * If a required prompt is not answered.
+    /// * If an error occurs with assigning the roles.
+    async fn apply_onboarding_answers(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+        answers: Vec<OnboardingAnswer>,
+    ) -> crate::Result<(Vec<u64>, Vec<u64>)> {
+        let onboarding = get_pool().fetch_onboarding(guild_id).await?;
+        if !onboarding.enabled {
+            return Err(Error::InvalidField {
+                field: "answers".to_string(),
+                message: "Onboarding is not enabled in this guild".to_string(),
+            });
+        }
+
+        let invalid = |message: String| Error::InvalidField {
+            field: "answers".to_string(),
+            message,
+        };
+        if !answers.iter().map(|a| a.prompt).all_unique() {
+            return Err(invalid("Each prompt can only be answered once".to_string()));
+        }
+
+        let mut role_ids = Vec::new();
+        for answer in &answers {
+            let prompt = onboarding
+                .prompts
+                .get(answer.prompt)
+                .ok_or_else(|| invalid(format!("Prompt {} does not exist", answer.prompt)))?;
+            if prompt.single_select && answer.options.len() > 1 {
+                return Err(invalid(format!(
+                    "Only one option can be picked for prompt {}",
+                    answer.prompt,
+                )));
+            }
+            for &index in answer.options.iter().unique() {
+                let option = prompt.options.get(index).ok_or_else(|| {
+                    invalid(format!(
+                        "Option {index} of prompt {} does not exist",
+                        answer.prompt,
+                    ))
+                })?;
+                role_ids.extend_from_slice(&option.role_ids);
+            }
+        }
+
+        let answered = answers
+            .iter()
+            .filter(|a| !a.options.is_empty())
+            .map(|a| a.prompt)
+            .collect::<HashSet<_>>();
+        if let Some(index) = (0..onboarding.prompts.len())
+            .find(|i| onboarding.prompts[*i].required && !answered.contains(i))
+        {
+            return Err(invalid(format!("Prompt {index} must be answered")));
+        }
+
+        let role_ids = role_ids.into_iter().unique().collect_vec();
+        self.add_roles_to_member(guild_id, user_id, &role_ids).await
     }
 }
 
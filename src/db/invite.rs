@@ -1,6 +1,6 @@
 use crate::{
-    db::{DbExt, GuildDbExt, MemberDbExt},
-    http::invite::CreateInvitePayload,
+    db::{get_pool, DbExt, GuildDbExt, MemberDbExt},
+    http::{invite::CreateInvitePayload, validate::Validate},
     models::{invite::Invite, Member},
     Error, NotFoundExt,
 };
@@ -28,7 +28,7 @@ pub trait InviteDbExt<'t>: DbExt<'t> {
     /// # Errors
     /// * If an error occurs with fetching the invite.
     /// * If an error occurs with fetching the guild.
-    async fn fetch_invite(&self, code: impl AsRef<str> + Send) -> sqlx::Result<Option<Invite>> {
+    async fn fetch_invite(&self, code: impl AsRef<str> + Send) -> crate::Result<Option<Invite>> {
         let Some(i) = sqlx::query!(
             r#"SELECT * FROM invites
             WHERE
@@ -77,9 +77,12 @@ pub trait InviteDbExt<'t>: DbExt<'t> {
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
+    /// If a member was created, the cached member counts should be updated with
+    /// [`member_joined`](crate::cache::guild_counts::member_joined) after the change is committed.
     ///
     /// # Errors
     /// * If no invite is found with the given invite code.
+    /// * If the user is banned from the guild.
     /// * If an error occurs with creating the invite.
     #[allow(clippy::default_trait_access)]
     async fn use_invite(
@@ -103,6 +106,10 @@ pub trait InviteDbExt<'t>: DbExt<'t> {
         .ok_or_not_found("invite", format!("No invite with code {code} found"))?;
 
         let invite = construct_invite!(invite, None);
+        get_pool()
+            .assert_not_banned(invite.guild_id, user_id)
+            .await?;
+
         if invite.max_uses != 0 && invite.uses >= invite.max_uses {
             self.delete_invite(code).await?;
         }
@@ -122,6 +129,7 @@ pub trait InviteDbExt<'t>: DbExt<'t> {
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
     /// * If the guild is not found.
     /// * If an error occurs with creating the invite.
     async fn create_invite(
@@ -131,6 +139,8 @@ pub trait InviteDbExt<'t>: DbExt<'t> {
         code: String,
         payload: CreateInvitePayload,
     ) -> crate::Result<Invite> {
+        payload.validate()?;
+
         let created_at = sqlx::query!(
             r#"INSERT INTO invites
                 (code, inviter_id, guild_id, max_uses, max_age)
@@ -0,0 +1,367 @@
+use crate::{
+    db::{get_pool, DbExt},
+    http::{
+        marketplace::{CreateMarketplaceEntryPayload, PublishMarketplaceVersionPayload},
+        validate::Validate,
+    },
+    models::{InstalledMarketplaceEntry, MarketplaceEntry, MarketplaceKind, MarketplaceRelease},
+    Error, NotFoundExt,
+};
+
+pub use crate::http::marketplace::MAX_MARKETPLACE_CONTENT_SIZE;
+
+macro_rules! construct_marketplace_entry {
+    ($data:ident) => {{
+        MarketplaceEntry {
+            id: $data.id as _,
+            kind: MarketplaceKind::from_db(&$data.kind)?,
+            author_id: $data.author_id as _,
+            name: $data.name,
+            description: $data.description,
+            latest_version: $data.latest_version,
+            installs: $data.installs as _,
+            created_at: $data.created_at,
+        }
+    }};
+}
+
+macro_rules! construct_marketplace_release {
+    ($data:ident) => {{
+        MarketplaceRelease {
+            entry_id: $data.entry_id as _,
+            version: $data.version,
+            changelog: $data.changelog,
+            content: $data.content,
+            published_at: $data.published_at,
+        }
+    }};
+}
+
+#[async_trait::async_trait]
+pub trait MarketplaceDbExt<'t>: DbExt<'t> {
+    /// Fetches a marketplace entry from the database with the given ID.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the entry. If the entry is not found, `Ok(None)` is
+    /// returned.
+    async fn fetch_marketplace_entry(
+        &self,
+        entry_id: u64,
+    ) -> crate::Result<Option<MarketplaceEntry>> {
+        sqlx::query!(
+            "SELECT * FROM marketplace_entries WHERE id = $1",
+            entry_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| Ok(construct_marketplace_entry!(r)))
+        .transpose()
+    }
+
+    /// Creates a new marketplace entry without any published versions.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the name or description is invalid.
+    /// * If an error occurs with creating the entry.
+    async fn create_marketplace_entry(
+        &mut self,
+        entry_id: u64,
+        author_id: u64,
+        payload: CreateMarketplaceEntryPayload,
+    ) -> crate::Result<MarketplaceEntry> {
+        payload.validate()?;
+        let name = payload.name.trim();
+
+        let entry = sqlx::query!(
+            r#"INSERT INTO
+                marketplace_entries (id, kind, author_id, name, description)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            RETURNING *"#,
+            entry_id as i64,
+            payload.kind.name(),
+            author_id as i64,
+            name,
+            payload.description,
+        )
+        .fetch_one(self.transaction())
+        .await?;
+
+        Ok(construct_marketplace_entry!(entry))
+    }
+
+    /// Publishes a new version of the given marketplace entry, making it the latest version.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the entry does not exist or was not published by the given user.
+    /// * If the version string, changelog, or content is invalid.
+    /// * If the version was already published.
+    /// * If an error occurs with publishing the version.
+    async fn publish_marketplace_version(
+        &mut self,
+        entry_id: u64,
+        author_id: u64,
+        payload: PublishMarketplaceVersionPayload,
+    ) -> crate::Result<MarketplaceRelease> {
+        payload.validate()?;
+
+        let entry = sqlx::query!(
+            "SELECT author_id FROM marketplace_entries WHERE id = $1",
+            entry_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found(
+            "marketplace_entry",
+            format!("Marketplace entry with ID {entry_id} not found"),
+        )?;
+
+        if entry.author_id as u64 != author_id {
+            return Err(Error::NotFound {
+                entity: "marketplace_entry".to_string(),
+                message: format!("Marketplace entry with ID {entry_id} not found"),
+            });
+        }
+
+        let release = sqlx::query!(
+            r#"INSERT INTO
+                marketplace_releases (entry_id, version, changelog, content)
+            VALUES
+                ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING
+            RETURNING *"#,
+            entry_id as i64,
+            payload.version,
+            payload.changelog,
+            payload.content,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_else(|| Error::AlreadyExists {
+            what: "version".to_string(),
+            message: format!("Version {} was already published", payload.version),
+        })?;
+
+        sqlx::query!(
+            "UPDATE marketplace_entries SET latest_version = $1 WHERE id = $2",
+            release.version,
+            entry_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(construct_marketplace_release!(release))
+    }
+
+    /// Fetches a specific version of the given marketplace entry.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the version. If the version is not found, `Ok(None)` is
+    /// returned.
+    async fn fetch_marketplace_version(
+        &self,
+        entry_id: u64,
+        version: &str,
+    ) -> crate::Result<Option<MarketplaceRelease>> {
+        Ok(sqlx::query!(
+            "SELECT * FROM marketplace_releases WHERE entry_id = $1 AND version = $2",
+            entry_id as i64,
+            version,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| construct_marketplace_release!(r)))
+    }
+
+    /// Resolves the snapshot a client should download for the given marketplace entry. If no
+    /// version is given, the latest version is resolved.
+    ///
+    /// # Errors
+    /// * If the entry or version does not exist, or the entry has no published versions.
+    /// * If an error occurs with resolving the snapshot.
+    async fn download_marketplace_snapshot(
+        &self,
+        entry_id: u64,
+        version: Option<&str>,
+    ) -> crate::Result<MarketplaceRelease> {
+        let release = sqlx::query!(
+            r#"SELECT
+                r.*
+            FROM
+                marketplace_releases r
+            INNER JOIN
+                marketplace_entries e ON e.id = r.entry_id
+            WHERE
+                r.entry_id = $1
+            AND
+                r.version = COALESCE($2, e.latest_version)"#,
+            entry_id as i64,
+            version,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found(
+            "marketplace_release",
+            format!("No matching version of marketplace entry {entry_id} found"),
+        )?;
+
+        Ok(construct_marketplace_release!(release))
+    }
+
+    /// Installs the given marketplace entry for the user, optionally pinned to a version. If the
+    /// entry is already installed, its pinned version is replaced.
+    ///
+    /// Returns whether the entry was newly installed.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the entry or version does not exist.
+    /// * If an error occurs with installing the entry.
+    async fn install_marketplace_entry(
+        &mut self,
+        user_id: u64,
+        entry_id: u64,
+        version: Option<&str>,
+    ) -> crate::Result<bool> {
+        match version {
+            Some(version) => {
+                get_pool()
+                    .fetch_marketplace_version(entry_id, version)
+                    .await?
+                    .ok_or_not_found(
+                        "marketplace_release",
+                        format!("Version {version} of marketplace entry {entry_id} not found"),
+                    )?;
+            }
+            None => {
+                get_pool()
+                    .fetch_marketplace_entry(entry_id)
+                    .await?
+                    .ok_or_not_found(
+                        "marketplace_entry",
+                        format!("Marketplace entry with ID {entry_id} not found"),
+                    )?;
+            }
+        }
+
+        let installed = sqlx::query!(
+            r#"INSERT INTO
+                marketplace_installs (user_id, entry_id, version)
+            VALUES
+                ($1, $2, $3)
+            ON CONFLICT
+                (user_id, entry_id)
+            DO UPDATE SET
+                version = $3
+            RETURNING (xmax = 0) AS "inserted!""#,
+            user_id as i64,
+            entry_id as i64,
+            version,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .inserted;
+
+        if installed {
+            sqlx::query!(
+                "UPDATE marketplace_entries SET installs = installs + 1 WHERE id = $1",
+                entry_id as i64,
+            )
+            .execute(self.transaction())
+            .await?;
+        }
+        Ok(installed)
+    }
+
+    /// Uninstalls the given marketplace entry for the user.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user does not have the entry installed.
+    /// * If an error occurs with uninstalling the entry.
+    async fn uninstall_marketplace_entry(
+        &mut self,
+        user_id: u64,
+        entry_id: u64,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "DELETE FROM marketplace_installs WHERE user_id = $1 AND entry_id = $2 RETURNING entry_id",
+            user_id as i64,
+            entry_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found(
+            "marketplace_install",
+            format!("Marketplace entry with ID {entry_id} is not installed"),
+        )?;
+
+        sqlx::query!(
+            "UPDATE marketplace_entries SET installs = installs - 1 WHERE id = $1",
+            entry_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fetches the marketplace entries installed by the given user, optionally only those of the
+    /// given kind, from the most to least recently installed.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the installed entries.
+    async fn fetch_installed_marketplace_entries(
+        &self,
+        user_id: u64,
+        kind: Option<MarketplaceKind>,
+    ) -> crate::Result<Vec<InstalledMarketplaceEntry>> {
+        sqlx::query!(
+            r#"SELECT
+                e.*,
+                i.version AS pinned_version,
+                i.installed_at
+            FROM
+                marketplace_installs i
+            INNER JOIN
+                marketplace_entries e ON e.id = i.entry_id
+            WHERE
+                i.user_id = $1
+            AND
+                ($2::TEXT IS NULL OR e.kind = $2)
+            ORDER BY
+                i.installed_at DESC"#,
+            user_id as i64,
+            kind.map(MarketplaceKind::name),
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| {
+            let version = r.pinned_version;
+            let installed_at = r.installed_at;
+            Ok(InstalledMarketplaceEntry {
+                entry: construct_marketplace_entry!(r),
+                version,
+                installed_at,
+            })
+        })
+        .collect()
+    }
+}
+
+impl<'t, T> MarketplaceDbExt<'t> for T where T: DbExt<'t> {}
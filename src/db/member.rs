@@ -1,4 +1,5 @@
 use crate::{cache, db::DbExt, models::Member, snowflake::with_model_type, NotFoundExt};
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
 
 macro_rules! query_member {
@@ -8,18 +9,28 @@ macro_rules! query_member {
                 m.id,
                 m.guild_id,
                 m.nick AS nick,
+                m.guild_avatar AS guild_avatar,
+                m.guild_banner AS guild_banner,
+                m.guild_bio AS guild_bio,
                 m.joined_at AS joined_at,
                 m.permissions AS permissions,
+                m.rules_accepted_at AS rules_accepted_at,
+                m.communication_disabled_until AS communication_disabled_until,
+                g.flags AS guild_flags,
                 u.username AS username,
                 u.display_name AS display_name,
                 u.avatar AS avatar,
                 u.banner AS banner,
                 u.bio AS bio,
+                u.pronouns AS pronouns,
+                u.accent_color AS accent_color,
                 u.flags AS flags
             FROM
                 members AS m
             INNER JOIN
                 users AS u ON u.id = m.id
+            INNER JOIN
+                guilds AS g ON g.id = m.guild_id
             "# + $where,
             $($arg),*
         )
@@ -28,32 +39,65 @@ macro_rules! query_member {
 
 macro_rules! construct_member {
     ($data:ident, $roles:expr) => {{
-        use $crate::models::{MaybePartialUser, User, UserFlags};
+        use $crate::models::{Asset, GuildFlags, MaybePartialUser, User, UserFlags};
+
+        let avatar = $data
+            .guild_avatar
+            .or_else(|| $data.avatar.clone())
+            .map(Asset::from_raw);
+        let banner = $data
+            .guild_banner
+            .or_else(|| $data.banner.clone())
+            .map(Asset::from_raw);
+        let bio = $data.guild_bio.or_else(|| $data.bio.clone());
 
         Member {
             user: MaybePartialUser::Full(User {
                 id: $data.id as _,
                 username: $data.username,
                 display_name: $data.display_name as _,
-                avatar: $data.avatar,
-                banner: $data.banner,
+                avatar: $data.avatar.map(Asset::from_raw),
+                banner: $data.banner.map(Asset::from_raw),
                 bio: $data.bio,
+                pronouns: $data.pronouns,
+                accent_color: $data.accent_color.map(|color| color as u32),
                 flags: UserFlags::from_bits_truncate($data.flags as _),
             }),
             guild_id: $data.guild_id as _,
             nick: $data.nick,
+            guild_avatar: avatar,
+            guild_banner: banner,
+            guild_bio: bio,
             roles: $roles,
             joined_at: $data.joined_at,
             permissions: Permissions::from_bits_truncate($data.permissions),
+            pending: $data.rules_accepted_at.is_none()
+                && GuildFlags::from_bits_truncate($data.guild_flags as _)
+                    .contains(GuildFlags::REQUIRES_RULES_ACCEPTANCE),
+            rules_accepted_at: $data.rules_accepted_at,
+            communication_disabled_until: $data
+                .communication_disabled_until
+                .filter(|until| *until > chrono::Utc::now()),
         }
     }};
 }
 
-use crate::db::{get_pool, UserDbExt};
-use crate::http::member::{EditClientMemberPayload, EditMemberPayload};
-use crate::models::{MaybePartialUser, ModelType, Permissions};
+use crate::db::{discovery::escape_like, get_pool, GuildDbExt, MessageDbExt, RoleDbExt, UserDbExt};
+use crate::http::member::{
+    EditClientMemberPayload, EditMemberPayload, GuildBansQuery, MemberQuery,
+};
+use crate::http::validate::Validate;
+use crate::models::{
+    Asset, GuildBan, GuildFlags, MaybePartialUser, Message, MessageInfo, ModelType, Permissions,
+    User, UserFlags,
+};
+use crate::ws::{MemberChunks, MemberRemoveInfo};
+use crate::Error;
 pub(crate) use construct_member;
 
+/// The maximum duration of a timeout, in days.
+const MAX_TIMEOUT_DAYS: i64 = 28;
+
 #[async_trait::async_trait]
 pub trait MemberDbExt<'t>: DbExt<'t> {
     /// Fetches a member from the database with the given guild and user ID.
@@ -122,10 +166,98 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
         Ok(members)
     }
 
-    /// Edits a member in the database with the given guild, user ID, and payload. The payload
-    /// should be validated prior to calling this method.
+    /// Fetches all members of the given guild, split into chunks of at most ``chunk_size``
+    /// members that are ready to be dispatched. To answer requests with a query, fetch the
+    /// members with [`Self::fetch_members_page`] and split them with [`MemberChunks::new`]
+    /// instead.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the members.
+    async fn fetch_member_chunks(
+        &self,
+        guild_id: u64,
+        chunk_size: usize,
+    ) -> crate::Result<MemberChunks> {
+        let members = self.fetch_all_members_in_guild(guild_id).await?;
+        Ok(MemberChunks::new(guild_id, members, chunk_size))
+    }
+
+    /// Fetches a page of the members of the given guild with the given query, ordered by user ID.
+    /// Use the ID of the last returned member as the ``after`` cursor to fetch the next page.
+    ///
+    /// # Errors
+    /// * If the limit is not between ``1`` and ``1000``.
+    /// * If an error occurs with fetching the members or their roles.
+    async fn fetch_members_page(
+        &self,
+        guild_id: u64,
+        query: MemberQuery,
+    ) -> crate::Result<Vec<Member>> {
+        if !(1..=1000).contains(&query.limit) {
+            return Err(Error::InvalidField {
+                field: "limit".to_string(),
+                message: "Limit must be between 1 and 1000".to_string(),
+            });
+        }
+
+        let pattern = query
+            .username_prefix
+            .as_deref()
+            .map(|prefix| format!("{}%", escape_like(&prefix.to_lowercase())));
+        // Every member has the default role, so filtering by it is a no-op
+        let role_id = query
+            .role_id
+            .filter(|&id| id != with_model_type(guild_id, ModelType::Role));
+
+        let records = query_member!(
+            "WHERE
+                m.guild_id = $1
+                AND ($2::BIGINT IS NULL OR m.id > $2)
+                AND ($3::TEXT IS NULL OR LOWER(u.username) LIKE $3)
+                AND ($4::BIGINT IS NULL OR EXISTS(
+                    SELECT 1 FROM role_data r
+                    WHERE r.guild_id = m.guild_id AND r.user_id = m.id AND r.role_id = $4
+                ))
+            ORDER BY m.id
+            LIMIT $5",
+            guild_id as i64,
+            query.after.map(|id| id as i64),
+            pattern,
+            role_id.map(|id| id as i64),
+            query.limit as i64,
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        let user_ids = records.iter().map(|m| m.id).collect_vec();
+        let roles = sqlx::query!(
+            "SELECT user_id, role_id FROM role_data WHERE guild_id = $1 AND user_id = ANY($2)",
+            guild_id as i64,
+            &user_ids,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .into_group_map_by(|r| r.user_id as u64);
+
+        let members = records
+            .into_iter()
+            .map(|m| {
+                construct_member!(
+                    m,
+                    roles
+                        .get(&(m.id as u64))
+                        .map(|r| r.iter().map(|r| r.role_id as u64).collect::<Vec<_>>())
+                )
+            })
+            .collect();
+
+        Ok(members)
+    }
+
+    /// Edits a member in the database with the given guild, user ID, and payload.
     ///
-    /// **This includes roles; roles must be validated prior to calling this method and roles
+    /// **Roles must be validated prior to calling this method and roles
     /// that are managed or do not meet required permissions should be removed from the payload.**
     ///
     /// # Note
@@ -133,6 +265,7 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
     /// * If an error occurs with editing the member.
     async fn edit_member(
         &mut self,
@@ -140,6 +273,8 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
         user_id: u64,
         payload: EditMemberPayload,
     ) -> crate::Result<(Member, Member)> {
+        payload.validate()?;
+
         let mut member = get_pool()
             .fetch_member_by_id(guild_id, user_id)
             .await?
@@ -149,16 +284,48 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
         member.nick = payload.nick.into_option_or_if_absent(member.nick);
         member.permissions = payload.permissions.unwrap_or(member.permissions);
 
+        let overrides = sqlx::query!(
+            "SELECT guild_avatar, guild_banner, guild_bio FROM members
+            WHERE guild_id = $1 AND id = $2",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?;
+        let guild_avatar = payload
+            .avatar
+            .map(String::from)
+            .into_option_or_if_absent(overrides.guild_avatar);
+        let guild_banner = payload
+            .banner
+            .map(String::from)
+            .into_option_or_if_absent(overrides.guild_banner);
+        let guild_bio = payload.bio.into_option_or_if_absent(overrides.guild_bio);
+
         sqlx::query!(
-            "UPDATE members SET nick = $1, permissions = $2 WHERE guild_id = $3 AND id = $4",
+            "UPDATE members
+            SET nick = $1, permissions = $2, guild_avatar = $3, guild_banner = $4, guild_bio = $5
+            WHERE guild_id = $6 AND id = $7",
             member.nick,
             member.permissions.bits(),
+            guild_avatar,
+            guild_banner,
+            guild_bio,
             guild_id as i64,
             user_id as i64,
         )
         .execute(self.transaction())
         .await?;
 
+        let user = member.user.as_full();
+        member.guild_avatar = guild_avatar
+            .map(Asset::from_raw)
+            .or_else(|| user.and_then(|user| user.avatar.clone()));
+        member.guild_banner = guild_banner
+            .map(Asset::from_raw)
+            .or_else(|| user.and_then(|user| user.banner.clone()));
+        member.guild_bio = guild_bio.or_else(|| user.and_then(|user| user.bio.clone()));
+
         if payload.permissions.is_some() {
             cache::delete_permissions_for_user(guild_id, user_id).await?;
         }
@@ -190,10 +357,14 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
             )
             .bind(user_id as i64)
             .bind(guild_id as i64)
-            .bind(roles.into_iter().map(|r| r as i64).collect::<Vec<_>>())
+            .bind(roles.iter().map(|&r| r as i64).collect::<Vec<_>>())
             .fetch_all(self.transaction())
             .await?;
 
+            cache::delete_permissions_for_user(guild_id, user_id).await?;
+            cache::remove_member_from_all_roles(guild_id, user_id).await?;
+            cache::add_member_to_roles(guild_id, user_id, &roles).await?;
+
             member.roles = Some(
                 sqlx::query!(
                     "SELECT role_id FROM role_data WHERE guild_id = $1 AND user_id = $2",
@@ -211,6 +382,130 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
         Ok((old, member))
     }
 
+    /// Assigns the given roles to a member in addition to the roles they already have. Roles
+    /// that do not belong to the guild or that the member already has are ignored. Unlike
+    /// [`edit_member`](Self::edit_member), this does not replace the member's roles, so
+    /// concurrent role changes do not overwrite each other.
+    ///
+    /// **Roles must be validated prior to calling this method and roles that are managed or do
+    /// not meet required permissions should be removed.**
+    ///
+    /// Returns the IDs of the roles that were assigned and the IDs of the roles the member has
+    /// after the change.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    /// The cached role members should be updated with the assigned roles using
+    /// [`add_member_to_roles`](cache::add_member_to_roles) after the change is committed.
+    ///
+    /// # Errors
+    /// * If an error occurs with assigning the roles.
+    async fn add_roles_to_member(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+        role_ids: &[u64],
+    ) -> crate::Result<(Vec<u64>, Vec<u64>)> {
+        let added = sqlx::query!(
+            "INSERT INTO
+                role_data
+            SELECT
+                out.*, $1, $2
+            FROM
+                UNNEST($3::BIGINT[])
+            AS
+                out(role_id)
+            WHERE
+                role_id IN (SELECT id FROM roles WHERE guild_id = $2)
+            ON CONFLICT DO NOTHING
+            RETURNING role_id",
+            user_id as i64,
+            guild_id as i64,
+            &role_ids.iter().map(|&r| r as i64).collect::<Vec<_>>(),
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.role_id as u64)
+        .collect();
+
+        cache::delete_permissions_for_user(guild_id, user_id).await?;
+        let role_ids = self
+            .fetch_member_role_ids_in_transaction(guild_id, user_id)
+            .await?;
+        Ok((added, role_ids))
+    }
+
+    /// Removes the given roles from a member, leaving all other roles they have untouched. The
+    /// default role of the guild cannot be removed and is ignored.
+    ///
+    /// **Roles must be validated prior to calling this method and roles that are managed or do
+    /// not meet required permissions should be removed.**
+    ///
+    /// Returns the IDs of the roles that were removed and the IDs of the roles the member has
+    /// after the change.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    /// The cached role members should be updated with the removed roles using
+    /// [`remove_member_from_roles`](cache::remove_member_from_roles) after the change is
+    /// committed.
+    ///
+    /// # Errors
+    /// * If an error occurs with removing the roles.
+    async fn remove_roles_from_member(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+        role_ids: &[u64],
+    ) -> crate::Result<(Vec<u64>, Vec<u64>)> {
+        let default_role_id = with_model_type(guild_id, ModelType::Role);
+        let removed = sqlx::query!(
+            "DELETE FROM role_data
+            WHERE guild_id = $1 AND user_id = $2 AND role_id = ANY($3::BIGINT[]) AND role_id != $4
+            RETURNING role_id",
+            guild_id as i64,
+            user_id as i64,
+            &role_ids.iter().map(|&r| r as i64).collect::<Vec<_>>(),
+            default_role_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.role_id as u64)
+        .collect();
+
+        cache::delete_permissions_for_user(guild_id, user_id).await?;
+        let role_ids = self
+            .fetch_member_role_ids_in_transaction(guild_id, user_id)
+            .await?;
+        Ok((removed, role_ids))
+    }
+
+    /// Fetches the IDs of the roles a member has within the current transaction, so that
+    /// uncommitted role changes are visible.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the roles.
+    async fn fetch_member_role_ids_in_transaction(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+    ) -> crate::Result<Vec<u64>> {
+        Ok(sqlx::query!(
+            "SELECT role_id FROM role_data WHERE guild_id = $1 AND user_id = $2",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.role_id as u64)
+        .collect())
+    }
+
     /// Edits a member in the database with the given guild, user ID, and a
     /// [`EditClientMemberPayload`].
     ///
@@ -219,6 +514,7 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
     /// * If an error occurs with editing the member.
     async fn edit_client_member(
         &mut self,
@@ -231,6 +527,9 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
             user_id,
             EditMemberPayload {
                 nick: payload.nick,
+                avatar: payload.avatar,
+                banner: payload.banner,
+                bio: payload.bio,
                 roles: None,
                 permissions: None,
             },
@@ -245,6 +544,8 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
+    /// If a member was created, the cached member counts should be updated with
+    /// [`member_joined`](cache::guild_counts::member_joined) after the change is committed.
     ///
     /// # Errors
     /// * If an error occurs with creating the member.
@@ -259,8 +560,9 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
             MaybePartialUser::Full,
         );
         let member = sqlx::query!(
-            "INSERT INTO members (guild_id, id, permissions) VALUES ($1, $2, $3)
-            ON CONFLICT (guild_id, id) DO NOTHING RETURNING joined_at",
+            r#"INSERT INTO members (guild_id, id, permissions) VALUES ($1, $2, $3)
+            ON CONFLICT (guild_id, id) DO NOTHING
+            RETURNING joined_at, (SELECT flags FROM guilds WHERE id = $1) AS "guild_flags!""#,
             guild_id as i64,
             user_id as i64,
             permissions.bits(),
@@ -269,38 +571,622 @@ pub trait MemberDbExt<'t>: DbExt<'t> {
         .await?
         .map(|m| Member {
             guild_id,
+            guild_avatar: user.as_full().and_then(|user| user.avatar.clone()),
+            guild_banner: user.as_full().and_then(|user| user.banner.clone()),
+            guild_bio: user.as_full().and_then(|user| user.bio.clone()),
             user,
             nick: None,
             joined_at: m.joined_at,
             roles: None,
             permissions,
+            rules_accepted_at: None,
+            pending: GuildFlags::from_bits_truncate(m.guild_flags as _)
+                .contains(GuildFlags::REQUIRES_RULES_ACCEPTANCE),
+            communication_disabled_until: None,
         });
 
         cache::update_member_of_guild(guild_id, user_id).await?;
-
         Ok(member)
     }
 
-    /// Deletes a member from the database with the given guild and user ID.
+    /// Deletes a member from the database with the given guild and user ID. Returns whether the
+    /// user was a member of the guild.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
+    /// If the member was deleted, the cached member counts should be updated with
+    /// [`member_left`](cache::guild_counts::member_left) after the change is committed.
     ///
     /// # Errors
     /// * If an error occurs with deleting the member.
-    async fn delete_member(&mut self, guild_id: u64, user_id: u64) -> crate::Result<()> {
-        sqlx::query!(
+    async fn delete_member(&mut self, guild_id: u64, user_id: u64) -> crate::Result<bool> {
+        let deleted = sqlx::query!(
             "DELETE FROM members WHERE guild_id = $1 AND id = $2",
             guild_id as i64,
             user_id as i64,
         )
         .execute(self.transaction())
-        .await?;
+        .await?
+        .rows_affected()
+            > 0;
 
         cache::remove_member_from_guild(guild_id, user_id).await?;
+        Ok(deleted)
+    }
+
+    /// Records that the given member accepted the rules of the guild. If the member already
+    /// accepted the rules, the original time of acceptance is kept. Returns the time of acceptance.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the member is not found.
+    /// * If an error occurs with recording the acceptance.
+    async fn record_rules_acceptance(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+    ) -> crate::Result<DateTime<Utc>> {
+        let accepted_at = sqlx::query!(
+            r#"UPDATE members SET rules_accepted_at = COALESCE(rules_accepted_at, NOW())
+            WHERE guild_id = $1 AND id = $2
+            RETURNING rules_accepted_at AS "rules_accepted_at!""#,
+            guild_id as i64,
+            user_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("member", format!("Member with ID {user_id} not found"))?
+        .rules_accepted_at;
+
+        cache::delete_permissions_for_user(guild_id, user_id).await?;
+        Ok(accepted_at)
+    }
+
+    /// Times out the given member until the given time. If the member is already timed out, the
+    /// timeout is replaced. Hierarchy and permission checks must be done prior to calling this
+    /// method. Returns a tuple ``(before, after)``, which is the data of the ``member_update``
+    /// event.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the timeout is not in the future or is longer than 28 days.
+    /// * If the member is not found.
+    /// * If the member is the owner of the guild.
+    /// * If an error occurs with timing out the member.
+    async fn timeout_member(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+        until: DateTime<Utc>,
+    ) -> crate::Result<(Member, Member)> {
+        let now = Utc::now();
+        if until <= now || until - now > chrono::Duration::days(MAX_TIMEOUT_DAYS) {
+            return Err(Error::InvalidField {
+                field: "communication_disabled_until".to_string(),
+                message: format!(
+                    "Timeout must be in the future and at most {MAX_TIMEOUT_DAYS} days long",
+                ),
+            });
+        }
+
+        let old = get_pool()
+            .fetch_member_by_id(guild_id, user_id)
+            .await?
+            .ok_or_not_found("member", format!("Member with ID {user_id} not found"))?;
+        if get_pool().is_guild_owner(guild_id, user_id).await? {
+            return Err(Error::InvalidField {
+                field: "user_id".to_string(),
+                message: "The owner of the guild cannot be timed out".to_string(),
+            });
+        }
+
+        sqlx::query!(
+            "UPDATE members SET communication_disabled_until = $1 WHERE guild_id = $2 AND id = $3",
+            until,
+            guild_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let mut member = old.clone();
+        member.communication_disabled_until = Some(until);
+        Ok((old, member))
+    }
+
+    /// Removes the timeout of the given member. Returns a tuple ``(before, after)``, which is the
+    /// data of the ``member_update`` event. If the member was not timed out, both are equal.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the member is not found.
+    /// * If an error occurs with removing the timeout.
+    async fn clear_timeout(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+    ) -> crate::Result<(Member, Member)> {
+        let old = get_pool()
+            .fetch_member_by_id(guild_id, user_id)
+            .await?
+            .ok_or_not_found("member", format!("Member with ID {user_id} not found"))?;
+
+        sqlx::query!(
+            "UPDATE members SET communication_disabled_until = NULL WHERE guild_id = $1 AND id = $2",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let mut member = old.clone();
+        member.communication_disabled_until = None;
+        Ok((old, member))
+    }
+
+    /// Asserts that the given member is not timed out. This should be checked before a member
+    /// sends a message, adds a reaction, or connects to a voice channel.
+    ///
+    /// # Errors
+    /// * If the member is timed out.
+    /// * If an error occurs with fetching the timeout.
+    async fn assert_member_not_timed_out(&self, guild_id: u64, user_id: u64) -> crate::Result<()> {
+        let until = sqlx::query!(
+            "SELECT communication_disabled_until FROM members WHERE guild_id = $1 AND id = $2",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .and_then(|r| r.communication_disabled_until);
+
+        if let Some(until) = until
+            && until > Utc::now()
+        {
+            return Err(Error::TimedOut {
+                guild_id,
+                until,
+                message: format!("You are timed out in this guild until {until}"),
+            });
+        }
         Ok(())
     }
+
+    /// Returns whether the given user is banned from the given guild.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the ban.
+    async fn is_banned(&self, guild_id: u64, user_id: u64) -> crate::Result<bool> {
+        Ok(sqlx::query!(
+            "SELECT EXISTS(SELECT 1 FROM bans WHERE guild_id = $1 AND user_id = $2) AS exists",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .fetch_one(self.executor())
+        .await?
+        .exists
+        .unwrap_or(false))
+    }
+
+    /// Fetches the bans of the given guild with the given query, ordered by the ID of the banned
+    /// user. Use the ID of the last returned user as the ``after`` cursor to fetch the next page.
+    ///
+    /// # Errors
+    /// * If the limit is not between ``1`` and ``1000``.
+    /// * If an error occurs with fetching the bans.
+    async fn fetch_bans(
+        &self,
+        guild_id: u64,
+        query: GuildBansQuery,
+    ) -> crate::Result<Vec<GuildBan>> {
+        if !(1..=1000).contains(&query.limit) {
+            return Err(Error::InvalidField {
+                field: "limit".to_string(),
+                message: "Limit must be between 1 and 1000".to_string(),
+            });
+        }
+
+        let pattern = query
+            .query
+            .as_deref()
+            .map(|prefix| format!("{}%", escape_like(&prefix.to_lowercase())));
+        let bans = sqlx::query!(
+            r"SELECT
+                u.id,
+                u.username,
+                u.display_name,
+                u.avatar,
+                u.banner,
+                u.bio,
+                u.pronouns,
+                u.accent_color,
+                u.flags,
+                b.moderator_id,
+                b.reason,
+                b.banned_at
+            FROM bans AS b
+            INNER JOIN users AS u ON u.id = b.user_id
+            WHERE
+                b.guild_id = $1
+                AND ($2::BIGINT IS NULL OR b.user_id > $2)
+                AND ($3::TEXT IS NULL OR LOWER(u.username) LIKE $3)
+            ORDER BY b.user_id
+            LIMIT $4",
+            guild_id as i64,
+            query.after.map(|id| id as i64),
+            pattern,
+            query.limit as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| GuildBan {
+            user: User {
+                id: r.id as _,
+                username: r.username,
+                display_name: r.display_name,
+                avatar: r.avatar.map(Asset::from_raw),
+                banner: r.banner.map(Asset::from_raw),
+                bio: r.bio,
+                pronouns: r.pronouns,
+                accent_color: r.accent_color.map(|color| color as u32),
+                flags: UserFlags::from_bits_truncate(r.flags as _),
+            },
+            moderator_id: r.moderator_id.map(|id| id as _),
+            reason: r.reason,
+            banned_at: r.banned_at,
+        })
+        .collect();
+
+        Ok(bans)
+    }
+
+    /// Asserts the given user is not banned from the given guild.
+    ///
+    /// # Errors
+    /// * If the user is banned from the guild.
+    /// * If an error occurs with fetching the ban.
+    async fn assert_not_banned(&self, guild_id: u64, user_id: u64) -> crate::Result<()> {
+        if self.is_banned(guild_id, user_id).await? {
+            return Err(Error::Banned {
+                guild_id,
+                message: "You are banned from this guild.".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds the given user to the given guild, sending a join system message if
+    /// ``system_message`` is provided as a tuple ``(channel_id, message_id)``.
+    ///
+    /// Returns `None` if the user is already in the guild, in which case no system message is sent.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    /// If a member was created, the cached member counts should be updated with
+    /// [`member_joined`](cache::guild_counts::member_joined) after the change is committed.
+    ///
+    /// # Errors
+    /// * If the user is banned from the guild.
+    /// * If an error occurs with creating the member or sending the system message.
+    async fn join_guild(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+        system_message: Option<(u64, u64)>,
+    ) -> crate::Result<Option<(Member, Option<Message>)>> {
+        get_pool().assert_not_banned(guild_id, user_id).await?;
+
+        let Some(member) = self
+            .create_member(guild_id, user_id, Permissions::empty())
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let message = match system_message {
+            Some((channel_id, message_id)) => Some(
+                self.send_system_message(channel_id, message_id, MessageInfo::Join { user_id })
+                    .await?,
+            ),
+            None => None,
+        };
+        Ok(Some((member, message)))
+    }
+
+    /// Internally used, see [`Self::leave_guild`], [`Self::kick_member`] and
+    /// [`Self::ban_member`] instead.
+    ///
+    /// Removes the member, records the removal, and sends a leave system message if
+    /// ``system_message`` is provided as a tuple ``(channel_id, message_id)``.
+    async fn remove_member_with(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+        info: &MemberRemoveInfo,
+        reason: Option<&str>,
+        system_message: Option<(u64, u64)>,
+    ) -> crate::Result<Option<Message>> {
+        let (kind, moderator_id) = match *info {
+            MemberRemoveInfo::Delete => {
+                return Err(Error::custom_for(
+                    "member removal",
+                    "Cannot remove a single member by deleting the guild",
+                ));
+            }
+            MemberRemoveInfo::Leave => ("leave", None),
+            MemberRemoveInfo::Kick { moderator_id } => ("kick", Some(moderator_id as i64)),
+            MemberRemoveInfo::Ban { moderator_id } => ("ban", Some(moderator_id as i64)),
+        };
+
+        self.delete_member(guild_id, user_id).await?;
+        sqlx::query!(
+            "INSERT INTO member_removals (guild_id, user_id, moderator_id, kind, reason)
+            VALUES ($1, $2, $3, $4, $5)",
+            guild_id as i64,
+            user_id as i64,
+            moderator_id,
+            kind,
+            reason,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(match system_message {
+            Some((channel_id, message_id)) => Some(
+                self.send_system_message(channel_id, message_id, MessageInfo::Leave { user_id })
+                    .await?,
+            ),
+            None => None,
+        })
+    }
+
+    /// Asserts the moderator can remove the target from the given guild, i.e. that neither is
+    /// acting on themselves, the target is not the owner, the moderator has the given
+    /// permissions, and the moderator's top role is higher than the target's.
+    ///
+    /// # Errors
+    /// * If any of the above conditions are not met.
+    /// * If an error occurs with fetching permissions or roles.
+    async fn assert_can_moderate_member(
+        &self,
+        guild_id: u64,
+        moderator_id: u64,
+        target_id: u64,
+        permissions: Permissions,
+    ) -> crate::Result<()> {
+        if moderator_id == target_id {
+            return Err(Error::CannotActOnSelf {
+                message: "You cannot act on yourself.".to_string(),
+            });
+        }
+
+        self.assert_member_has_permissions(guild_id, moderator_id, None, permissions)
+            .await?;
+        if self.is_guild_owner(guild_id, target_id).await? {
+            // The owner is above every role in the hierarchy
+            let (top_role_id, top_role_position) =
+                self.fetch_top_role(guild_id, moderator_id).await?;
+            return Err(Error::RoleTooLow {
+                guild_id,
+                top_role_id,
+                top_role_position,
+                desired_position: u16::MAX,
+                message: "You cannot perform the requested action on the owner of the guild."
+                    .to_string(),
+            });
+        }
+        self.assert_top_role_higher_than_target(guild_id, moderator_id, target_id)
+            .await
+    }
+
+    /// Removes the given user from the given guild on their own accord, sending a leave system
+    /// message if ``system_message`` is provided as a tuple ``(channel_id, message_id)``.
+    ///
+    /// Returns a tuple ``(info, system_message)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    /// The cached member counts should be updated with
+    /// [`member_left`](cache::guild_counts::member_left) after the change is committed.
+    ///
+    /// # Errors
+    /// * If the user is the owner of the guild.
+    /// * If the user is not a member of the guild.
+    /// * If an error occurs with removing the member or sending the system message.
+    async fn leave_guild(
+        &mut self,
+        guild_id: u64,
+        user_id: u64,
+        system_message: Option<(u64, u64)>,
+    ) -> crate::Result<(MemberRemoveInfo, Option<Message>)> {
+        get_pool()
+            .assert_invoker_in_guild(guild_id, user_id)
+            .await?;
+        if get_pool().is_guild_owner(guild_id, user_id).await? {
+            return Err(Error::CannotLeaveAsOwner {
+                id: guild_id,
+                message: "You cannot leave a guild you own. Transfer ownership first.".to_string(),
+            });
+        }
+
+        let info = MemberRemoveInfo::Leave;
+        let message = self
+            .remove_member_with(guild_id, user_id, &info, None, system_message)
+            .await?;
+        Ok((info, message))
+    }
+
+    /// Kicks the target from the given guild on behalf of the moderator, sending a leave system
+    /// message if ``system_message`` is provided as a tuple ``(channel_id, message_id)``.
+    ///
+    /// Returns a tuple ``(info, system_message)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    /// The cached member counts should be updated with
+    /// [`member_left`](cache::guild_counts::member_left) after the change is committed.
+    ///
+    /// # Errors
+    /// * If the moderator cannot moderate the target, see [`Self::assert_can_moderate_member`].
+    /// * If the target is not a member of the guild.
+    /// * If an error occurs with removing the member or sending the system message.
+    async fn kick_member(
+        &mut self,
+        guild_id: u64,
+        moderator_id: u64,
+        target_id: u64,
+        reason: Option<String>,
+        system_message: Option<(u64, u64)>,
+    ) -> crate::Result<(MemberRemoveInfo, Option<Message>)> {
+        get_pool()
+            .assert_member_in_guild(guild_id, target_id)
+            .await?;
+        get_pool()
+            .assert_can_moderate_member(
+                guild_id,
+                moderator_id,
+                target_id,
+                Permissions::KICK_MEMBERS,
+            )
+            .await?;
+
+        let info = MemberRemoveInfo::Kick { moderator_id };
+        let message = self
+            .remove_member_with(
+                guild_id,
+                target_id,
+                &info,
+                reason.as_deref(),
+                system_message,
+            )
+            .await?;
+        Ok((info, message))
+    }
+
+    /// Bans the target from the given guild on behalf of the moderator. The target does not have
+    /// to be a member of the guild. If they are, they are removed from the guild and a leave
+    /// system message is sent if ``system_message`` is provided as a tuple
+    /// ``(channel_id, message_id)``.
+    ///
+    /// Returns a tuple ``(info, system_message)``. ``system_message`` is always `None` if the
+    /// target was not a member of the guild.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    /// If the target was a member of the guild, the cached member counts should be updated with
+    /// [`member_left`](cache::guild_counts::member_left) after the change is committed.
+    ///
+    /// # Errors
+    /// * If the moderator cannot moderate the target, see [`Self::assert_can_moderate_member`].
+    /// * If the target is already banned.
+    /// * If an error occurs with banning the member or sending the system message.
+    async fn ban_member(
+        &mut self,
+        guild_id: u64,
+        moderator_id: u64,
+        target_id: u64,
+        reason: Option<String>,
+        system_message: Option<(u64, u64)>,
+    ) -> crate::Result<(MemberRemoveInfo, Option<Message>)> {
+        let is_member = get_pool()
+            .fetch_member_by_id(guild_id, target_id)
+            .await?
+            .is_some();
+        if is_member {
+            get_pool()
+                .assert_can_moderate_member(
+                    guild_id,
+                    moderator_id,
+                    target_id,
+                    Permissions::BAN_MEMBERS,
+                )
+                .await?;
+        } else {
+            if moderator_id == target_id {
+                return Err(Error::CannotActOnSelf {
+                    message: "You cannot act on yourself.".to_string(),
+                });
+            }
+            get_pool()
+                .assert_member_has_permissions(
+                    guild_id,
+                    moderator_id,
+                    None,
+                    Permissions::BAN_MEMBERS,
+                )
+                .await?;
+        }
+
+        let banned = sqlx::query!(
+            "INSERT INTO bans (guild_id, user_id, moderator_id, reason) VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING",
+            guild_id as i64,
+            target_id as i64,
+            moderator_id as i64,
+            reason.as_deref(),
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected();
+
+        if banned == 0 {
+            return Err(Error::AlreadyExists {
+                what: "ban".to_string(),
+                message: format!("User with ID {target_id} is already banned from this guild"),
+            });
+        }
+
+        let info = MemberRemoveInfo::Ban { moderator_id };
+        let message = if is_member {
+            self.remove_member_with(
+                guild_id,
+                target_id,
+                &info,
+                reason.as_deref(),
+                system_message,
+            )
+            .await?
+        } else {
+            None
+        };
+        Ok((info, message))
+    }
+
+    /// Removes the ban of the given user from the given guild. Returns whether the user was
+    /// banned.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with removing the ban.
+    async fn unban_member(&mut self, guild_id: u64, user_id: u64) -> crate::Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM bans WHERE guild_id = $1 AND user_id = $2",
+            guild_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
 }
 
 impl<'t, T> MemberDbExt<'t> for T where T: DbExt<'t> {}
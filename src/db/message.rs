@@ -1,22 +1,37 @@
 #[allow(unused_imports)]
 use crate::models::Embed;
 use crate::{
-    db::{get_pool, DbExt, EmojiDbExt, GuildDbExt},
-    http::message::{CreateMessagePayload, EditMessagePayload, MessageHistoryQuery},
+    db::{
+        content_codec, decode_content, encode_content, get_pool, ChannelDbExt, DbExt, EmojiDbExt,
+        GuildDbExt, PollDbExt,
+    },
+    http::{
+        message::{
+            CreateMessagePayload, EditMessagePayload, MessageHistoryQuery, MessageSearchOrder,
+            MessageSearchQuery,
+        },
+        validate::Validate,
+    },
     models::{
-        Attachment, Guild, Message, MessageFlags, MessageInfo, MessageReference, Permissions,
+        Attachment, Guild, Message, MessageFlags, MessageInfo, MessageReference, MessageRevision,
+        MessageSearchResult, Permissions,
     },
     snowflake::extract_mentions,
     Error, NotFoundExt,
 };
+use chrono::{DateTime, Utc};
+use futures_util::{stream, Stream, TryStreamExt};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
 
+/// Constructs a [`Message`] from a database record, decoding its content with the current
+/// [`ContentCodec`](crate::db::ContentCodec). This evaluates to a `crate::Result<Message>`.
 macro_rules! construct_message {
     ($data:ident) => {{
         use $crate::models::{Message, MessageFlags, MessageInfo};
 
-        Message {
+        $crate::db::decode_content($data.content).map(|content| Message {
             id: $data.id as _,
             channel_id: $data.channel_id as _,
             author_id: $data.author_id.map(|id| id as _),
@@ -34,15 +49,35 @@ macro_rules! construct_message {
                 },
                 _ => MessageInfo::Default,
             },
-            content: $data.content,
+            content,
             embeds: $data.embeds_ser.0,
             attachments: Vec::with_capacity(10),
             flags: MessageFlags::from_bits_truncate($data.flags as _),
             reactions: Vec::new(),
+            stars: $data.stars as _,
             mentions: $data.mentions.into_iter().map(|id| id as _).collect(),
             edited_at: $data.edited_at,
             references: Vec::new(),
-        }
+            sticker_ids: $data.sticker_ids.into_iter().map(|id| id as _).collect(),
+            poll: None,
+        })
+    }};
+}
+
+/// Constructs an [`Attachment`] from a database record, decoding its alt text with the current
+/// [`ContentCodec`](crate::db::ContentCodec). This evaluates to a `crate::Result<Attachment>`.
+macro_rules! construct_attachment {
+    ($data:ident) => {{
+        $crate::db::decode_content($data.alt).map(|alt| $crate::models::Attachment {
+            id: $data.id as _,
+            alt,
+            filename: $data.filename,
+            size: $data.size as _,
+            content_type: $data.content_type,
+            width: $data.width.map(|w| w as _),
+            height: $data.height.map(|h| h as _),
+            hash: $data.hash,
+        })
     }};
 }
 
@@ -50,6 +85,42 @@ use crate::db::emoji::construct_reaction;
 use crate::models::{PartialEmoji, Reaction};
 pub(crate) use construct_message;
 
+/// The maximum number of messages returned by a single message search.
+const MAX_SEARCH_LIMIT: u8 = 100;
+
+/// Pages through message history by calling ``fetch`` with the query advanced past the last
+/// message of the previous page, until a page comes back with fewer than ``query.limit``
+/// messages. See [`MessageDbExt::fetch_message_history_stream`].
+fn paginate_message_history<F, Fut>(
+    query: MessageHistoryQuery,
+    fetch: F,
+) -> impl Stream<Item = crate::Result<Message>> + Send
+where
+    F: FnMut(MessageHistoryQuery) -> Fut + Send,
+    Fut: Future<Output = crate::Result<Vec<Message>>> + Send,
+{
+    stream::try_unfold(Some((query, fetch)), |state| async move {
+        let Some((mut query, mut fetch)) = state else {
+            return Ok::<_, Error>(None);
+        };
+        let messages = fetch(query.clone()).await?;
+        let Some(last_id) = messages.last().map(|message| message.id) else {
+            return Ok(None);
+        };
+
+        let next = (messages.len() >= query.limit as usize).then(|| {
+            if query.oldest_first {
+                query.after = Some(last_id);
+            } else {
+                query.before = Some(last_id);
+            }
+            (query, fetch)
+        });
+        Ok(Some((stream::iter(messages.into_iter().map(Ok)), next)))
+    })
+    .try_flatten()
+}
+
 #[async_trait::async_trait]
 pub trait MessageDbExt<'t>: DbExt<'t> {
     /// Fetches quick metadata about a message. Returns `author_id`.
@@ -58,7 +129,7 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
     /// * If an error occurs inspecting the message
     async fn inspect_message(&self, message_id: u64) -> crate::Result<Option<Option<u64>>> {
         let data = sqlx::query!(
-            "SELECT author_id FROM messages WHERE id = $1",
+            "SELECT author_id FROM messages WHERE id = $1 AND deleted_at IS NULL",
             message_id as i64,
         )
         .fetch_optional(self.executor())
@@ -75,20 +146,34 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
     /// * If an error occurs fetching the message.
     /// * If the message is not found.
     async fn fetch_message_attachments(&self, message_id: u64) -> crate::Result<Vec<Attachment>> {
-        Ok(sqlx::query!(
+        sqlx::query!(
             r"SELECT * FROM attachments WHERE message_id = $1",
             message_id as i64
         )
         .fetch_all(self.executor())
         .await?
         .into_iter()
-        .map(|attachment| Attachment {
-            id: attachment.id as _,
-            alt: attachment.alt,
-            filename: attachment.filename,
-            size: attachment.size as _,
-        })
-        .collect())
+        .map(|attachment| construct_attachment!(attachment))
+        .collect()
+    }
+
+    /// Finds an attachment with the given content hash, so that identical files can be
+    /// deduplicated. If several attachments have the same hash, the oldest one is returned.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the attachment.
+    async fn find_attachment_by_hash(&self, hash: &str) -> crate::Result<Option<Attachment>> {
+        sqlx::query!(
+            r"SELECT a.* FROM attachments a
+            WHERE a.hash = $1
+            ORDER BY a.message_id
+            LIMIT 1",
+            hash.to_ascii_lowercase(),
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|attachment| construct_attachment!(attachment))
+        .transpose()
     }
 
     /// Fetches what this message references.
@@ -139,18 +224,22 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
                 channel_id = $1
             AND
                 id = $2
+            AND
+                deleted_at IS NULL
             "#,
             channel_id as i64,
             message_id as i64,
         )
         .fetch_optional(self.executor())
         .await?
-        .map(|m| construct_message!(m));
+        .map(|m| construct_message!(m))
+        .transpose()?;
 
         if let Some(message) = message.as_mut() {
             message.attachments = self.fetch_message_attachments(message_id).await?;
             message.references = self.fetch_message_references(message_id).await?;
             message.reactions = self.fetch_reactions(message_id).await?;
+            message.poll = self.fetch_poll(message_id).await?;
         }
         Ok(message)
     }
@@ -166,16 +255,11 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         .await?
         .into_iter()
         .map(|attachment| {
-            (
-                attachment.message_id as u64,
-                Attachment {
-                    id: attachment.id as _,
-                    alt: attachment.alt,
-                    filename: attachment.filename,
-                    size: attachment.size as _,
-                },
-            )
+            let message_id = attachment.message_id as u64;
+            construct_attachment!(attachment).map(|attachment| (message_id, attachment))
         })
+        .collect::<crate::Result<Vec<_>>>()?
+        .into_iter()
         .into_group_map();
 
         let mut references = sqlx::query!(
@@ -203,12 +287,14 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
                 message_id,
                 emoji_id,
                 emoji_name,
-                array_agg(user_id) AS user_ids,
-                array_agg(created_at) AS created_at
+                array_agg(user_id ORDER BY created_at) AS user_ids,
+                array_agg(user_id ORDER BY created_at) FILTER (WHERE burst) AS burst_user_ids,
+                array_agg(created_at ORDER BY created_at) AS created_at
             FROM reactions
             WHERE
                 message_id = ANY($1::BIGINT[])
-            GROUP BY (message_id, emoji_id, emoji_name)"#,
+            GROUP BY (message_id, emoji_id, emoji_name)
+            ORDER BY MIN(created_at)"#,
             &ids,
         )
         .fetch_all(self.executor())
@@ -217,6 +303,10 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         .map(|r| construct_reaction!(r))
         .into_group_map_by(|r| r.message_id);
 
+        let mut polls = self
+            .fetch_polls(&messages.iter().map(|m| m.id).collect_vec())
+            .await?;
+
         for message in messages {
             if let Some(attachments) = attachments.remove(&message.id) {
                 message.attachments = attachments;
@@ -227,6 +317,7 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
             if let Some(reactions) = reactions.remove(&message.id) {
                 message.reactions = reactions;
             }
+            message.poll = polls.remove(&message.id);
         }
         Ok(())
     }
@@ -256,6 +347,8 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
                         ($3::BIGINT IS NULL OR m.id > $3)
                     AND
                         ($4::BIGINT IS NULL OR m.author_id = $4)
+                    AND
+                        m.deleted_at IS NULL
                     ORDER BY id "#
                         + $direction
                         + " LIMIT $5",
@@ -269,7 +362,7 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
                 .await?
                 .into_iter()
                 .map(|m| construct_message!(m))
-                .collect_vec()
+                .collect::<crate::Result<Vec<_>>>()?
             }};
         }
 
@@ -282,6 +375,157 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         Ok(messages)
     }
 
+    /// Streams the entire message history of a channel matching the given query, fetching and
+    /// populating messages in chunks of ``query.limit`` messages at a time. Unlike
+    /// [`fetch_message_history`](Self::fetch_message_history), this keeps paging until no more
+    /// messages match, so that large histories can be consumed without buffering them in memory.
+    ///
+    /// The ``before`` and ``after`` bounds of the query are respected; the stream ends once the
+    /// bound in the direction of travel is exhausted.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching a chunk of messages. The stream yields the error and
+    /// then ends.
+    fn fetch_message_history_stream(
+        &self,
+        channel_id: u64,
+        query: MessageHistoryQuery,
+    ) -> impl Stream<Item = crate::Result<Message>> + Send + '_
+    where
+        Self: Sync,
+    {
+        paginate_message_history(query, move |query| {
+            self.fetch_message_history(channel_id, query)
+        })
+    }
+
+    /// Searches for messages in the given channels with the given query. Permission checks on the
+    /// channels must be done prior to calling this method.
+    ///
+    /// # Errors
+    /// * If content is searched for while a non-passthrough [`ContentCodec`] is in use, since
+    /// stored content cannot be searched in that case.
+    /// * If an error occurs with searching the messages.
+    ///
+    /// [`ContentCodec`]: crate::db::ContentCodec
+    async fn search_messages(
+        &self,
+        channel_ids: &[u64],
+        query: MessageSearchQuery,
+    ) -> crate::Result<MessageSearchResult> {
+        let channel_ids = channel_ids.iter().map(|&id| id as i64).collect_vec();
+        let content = query
+            .content
+            .as_deref()
+            .map(str::trim)
+            .filter(|content| !content.is_empty());
+        if content.is_some() && !content_codec().is_passthrough() {
+            return Err(Error::InvalidField {
+                field: "content".to_string(),
+                message: "Searching message content is not supported on this instance".to_string(),
+            });
+        }
+
+        let limit = i64::from(query.limit.min(MAX_SEARCH_LIMIT));
+        let offset = i64::from(query.offset);
+
+        // The page of results and the total are counted over the same filters. Parameters from
+        // `$9` onwards are given by the caller.
+        macro_rules! search_messages {
+            ($select:literal $(, $tail:literal)*; $($arg:expr),*) => {
+                sqlx::query!(
+                    "SELECT " + $select + r#"
+                    FROM
+                        messages m
+                    WHERE
+                        m.channel_id = ANY($1::BIGINT[])
+                    AND (
+                        $2::TEXT IS NULL
+                        OR to_tsvector('english', COALESCE(m.content, ''))
+                            @@ websearch_to_tsquery('english', $2)
+                    )
+                    AND
+                        ($3::BIGINT IS NULL OR m.author_id = $3)
+                    AND (
+                        $4::BIGINT IS NULL
+                        OR $4 = ANY(m.mentions)
+                        OR m.mentions && (
+                            SELECT array_agg(r.role_id) FROM role_data r
+                            WHERE
+                                r.guild_id = (SELECT guild_id FROM channels WHERE id = m.channel_id)
+                            AND
+                                r.user_id = $4
+                        )
+                    )
+                    AND (
+                        $5::BOOLEAN IS NULL
+                        OR EXISTS(SELECT 1 FROM attachments a WHERE a.message_id = m.id) = $5
+                    )
+                    AND
+                        ($6::BOOLEAN IS NULL OR (jsonb_array_length(m.embeds) > 0) = $6)
+                    AND
+                        ($7::BIGINT IS NULL OR m.id < $7)
+                    AND
+                        ($8::BIGINT IS NULL OR m.id > $8)
+                    AND
+                        m.deleted_at IS NULL"# $(+ $tail)*,
+                    &channel_ids,
+                    content,
+                    query.author_id.map(|id| id as i64),
+                    query.mentions.map(|id| id as i64),
+                    query.has_attachment,
+                    query.has_embed,
+                    query.before.map(|id| id as i64),
+                    query.after.map(|id| id as i64),
+                    $($arg),*
+                )
+            };
+        }
+
+        macro_rules! search_page {
+            ($order:literal) => {{
+                let records = search_messages!(
+                    r#"m.*,
+                    embeds AS "embeds_ser: sqlx::types::Json<Vec<Embed>>",
+                    COUNT(*) OVER () AS "total!""#,
+                    " ORDER BY ",
+                    $order,
+                    " LIMIT $9 OFFSET $10";
+                    limit,
+                    offset
+                )
+                .fetch_all(self.executor())
+                .await?;
+
+                (
+                    records.first().map_or(0, |r| r.total as u64),
+                    records
+                        .into_iter()
+                        .map(|m| construct_message!(m))
+                        .collect::<crate::Result<Vec<_>>>()?,
+                )
+            }};
+        }
+
+        let (mut total, mut messages) = match query.order {
+            MessageSearchOrder::Relevance if content.is_some() => search_page!(
+                "ts_rank(to_tsvector('english', COALESCE(m.content, '')), \
+                websearch_to_tsquery('english', $2)) DESC, m.id DESC"
+            ),
+            _ => search_page!("m.id DESC"),
+        };
+        // The total is counted alongside each row, so a page past the last result cannot report
+        // it. Count the matching messages separately instead.
+        if messages.is_empty() && offset > 0 {
+            total = search_messages!(r#"COUNT(*) AS "count!""#;)
+                .fetch_one(self.executor())
+                .await?
+                .count as u64;
+        }
+        self.populate_messages(&mut messages).await?;
+        Ok(MessageSearchResult { total, messages })
+    }
+
     /// Fetches a list of messages by ID from the database in bulk.
     ///
     /// # Note
@@ -306,6 +550,8 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
                 id = ANY($1::BIGINT[])
             AND
                 ($2::BIGINT[] IS NULL OR channel_id = ANY($2::BIGINT[]))
+            AND
+                deleted_at IS NULL
             ORDER BY id DESC
             LIMIT $3"#,
             &message_ids,
@@ -316,12 +562,42 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         .await?
         .into_iter()
         .map(|m| construct_message!(m))
-        .collect_vec();
+        .collect::<crate::Result<Vec<_>>>()?;
 
         self.populate_messages(&mut messages).await?;
         Ok(messages)
     }
 
+    /// Resolves the IDs to store in the ``mentions`` of a message with the given content sent in
+    /// the given channel: the IDs of mentioned users and roles, and the ID of the guild if
+    /// everyone is mentioned in a guild channel. Whether the author may mention roles or everyone
+    /// should be checked prior to sending the message.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the guild of the channel.
+    async fn resolve_stored_mentions(
+        &self,
+        channel_id: u64,
+        content: Option<&str>,
+    ) -> crate::Result<Vec<u64>> {
+        let Some(content) = content else {
+            return Ok(Vec::new());
+        };
+        let parsed = extract_mentions(content);
+
+        let mut mentions = parsed.users;
+        mentions.extend(parsed.roles);
+        if parsed.everyone
+            && let Some(guild_id) = self
+                .inspect_channel(channel_id)
+                .await?
+                .and_then(|channel| channel.guild_id)
+        {
+            mentions.push(guild_id);
+        }
+        Ok(mentions)
+    }
+
     /// Sends a message in the given channel.
     ///
     /// # Note
@@ -329,6 +605,7 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid, including its embeds and poll.
     /// * If an error occurs registering the message in the database.
     async fn create_message(
         &mut self,
@@ -337,12 +614,8 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         user_id: u64,
         mut payload: CreateMessagePayload,
     ) -> crate::Result<Message> {
-        let embeds =
-            serde_json::to_value(payload.embeds.clone()).map_err(|err| Error::InternalError {
-                what: Some("embed serialization".to_string()),
-                message: err.to_string(),
-                debug: Some(format!("{err:?}")),
-            })?;
+        payload.validate()?;
+        let embeds = serde_json::to_value(payload.embeds.clone())?;
 
         let mention_author = payload
             .references
@@ -355,7 +628,8 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
             .map(|r| r.message_id as i64)
             .collect_vec();
         let valid_references: HashMap<u64, u64> = sqlx::query!(
-            "SELECT id, author_id FROM messages WHERE id = ANY($1::BIGINT[])",
+            "SELECT id, author_id FROM messages
+            WHERE id = ANY($1::BIGINT[]) AND deleted_at IS NULL",
             &reference_ids,
         )
         .fetch_all(self.transaction())
@@ -367,11 +641,9 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         payload
             .references
             .retain(|r| valid_references.contains_key(&r.message_id));
-        let mut mentions = payload
-            .content
-            .as_deref()
-            .map(extract_mentions)
-            .unwrap_or_default();
+        let mut mentions = get_pool()
+            .resolve_stored_mentions(channel_id, payload.content.as_deref())
+            .await?;
         mentions.extend(
             valid_references
                 .iter()
@@ -382,19 +654,51 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
 
         let mentions_i64 = mentions.iter().map(|m| *m as i64).collect_vec();
 
+        let mut seen = HashSet::new();
+        payload.sticker_ids.retain(|id| seen.insert(*id));
+        let sticker_ids = if payload.sticker_ids.is_empty() {
+            Vec::new()
+        } else {
+            let requested = payload
+                .sticker_ids
+                .iter()
+                .map(|&id| id as i64)
+                .collect_vec();
+            let found = sqlx::query!(
+                "SELECT id FROM stickers WHERE id = ANY($1::BIGINT[])",
+                &requested,
+            )
+            .fetch_all(self.executor())
+            .await?
+            .into_iter()
+            .map(|r| r.id)
+            .collect::<HashSet<_>>();
+
+            requested
+                .into_iter()
+                .filter(|id| found.contains(id))
+                .collect_vec()
+        };
+
         sqlx::query!(
-            "INSERT INTO messages (id, channel_id, author_id, content, embeds, mentions)
-             VALUES ($1, $2, $3, $4, $5::JSONB, $6::BIGINT[])",
+            "INSERT INTO messages (id, channel_id, author_id, content, embeds, mentions, sticker_ids)
+             VALUES ($1, $2, $3, $4, $5::JSONB, $6::BIGINT[], $7::BIGINT[])",
             message_id as i64,
             channel_id as i64,
             user_id as i64,
-            payload.content,
+            encode_content(payload.content.as_deref())?,
             embeds,
             &mentions_i64,
+            &sticker_ids,
         )
         .execute(self.transaction())
         .await?;
 
+        let poll = match &payload.poll {
+            Some(poll) => Some(self.create_poll(message_id, poll).await?),
+            None => None,
+        };
+
         for reference in &payload.references {
             sqlx::query!(
                 "INSERT INTO message_references VALUES ($1, $2, $3, $4, $5)",
@@ -408,6 +712,7 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
             .await?;
         }
 
+        self.touch_channel(channel_id).await?;
         Ok(Message {
             id: message_id,
             channel_id,
@@ -419,28 +724,56 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
             attachments: Vec::new(),
             flags: MessageFlags::empty(),
             reactions: Vec::new(),
+            stars: 0,
             mentions,
             edited_at: None,
             references: payload.references,
+            sticker_ids: sticker_ids.into_iter().map(|id| id as u64).collect(),
+            poll,
         })
     }
 
-    /// Create a new attachment.
+    /// Create a new attachment. The content hash is normalized to lowercase.
     ///
     /// # Note
     /// This method uses transactions to ensure consistency with [`create_message`]
+    ///
+    /// # Errors
+    /// * If the content hash is not a hex-encoded SHA-256 digest.
+    /// * If only one of the width and height is given.
+    /// * If an error occurs with creating the attachment.
     async fn create_attachment(
         &mut self,
         message_id: u64,
         attachment: Attachment,
     ) -> crate::Result<()> {
+        if let Some(hash) = &attachment.hash
+            && (hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()))
+        {
+            return Err(Error::InvalidField {
+                field: "hash".to_string(),
+                message: "Hash must be a hex-encoded SHA-256 digest".to_string(),
+            });
+        }
+        if attachment.width.is_some() != attachment.height.is_some() {
+            return Err(Error::InvalidField {
+                field: "width".to_string(),
+                message: "Width and height must be given together".to_string(),
+            });
+        }
+
         sqlx::query!(
-            "INSERT INTO attachments VALUES ($1, $2, $3, $4, $5)",
+            "INSERT INTO attachments (id, message_id, filename, size, alt, content_type, width, height, hash)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
             attachment.id,
             message_id as i64,
             attachment.filename,
             attachment.size as i64,
-            attachment.alt,
+            encode_content(attachment.alt.as_deref())?,
+            attachment.content_type,
+            attachment.width.map(|w| w as i32),
+            attachment.height.map(|h| h as i32),
+            attachment.hash.map(|hash| hash.to_ascii_lowercase()),
         )
         .execute(self.transaction())
         .await?;
@@ -512,9 +845,12 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
             attachments: Vec::new(),
             flags: MessageFlags::empty(),
             reactions: Vec::new(),
+            stars: 0,
             mentions: Vec::new(),
             edited_at: None,
             references: Vec::new(),
+            sticker_ids: Vec::new(),
+            poll: None,
         })
     }
 
@@ -535,7 +871,7 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
     ) -> crate::Result<MessageFlags> {
         let message = sqlx::query!(
             r"UPDATE messages SET flags = flags | $1::INT & ~$2::INT
-                WHERE id = $3 AND channel_id = $4
+                WHERE id = $3 AND channel_id = $4 AND deleted_at IS NULL
                 RETURNING flags",
             enable.bits() as i32,
             disable.bits() as i32,
@@ -549,8 +885,9 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         Ok(MessageFlags::from_bits_truncate(message.flags as _))
     }
 
-    /// Edits a message in the given channel. This turns the current message into a revision of the
-    /// message, and creates a new message with the new data.
+    /// Edits a message in the given channel. The current content and embeds of the message are
+    /// stored as a revision of the message before it is updated with the new data. See
+    /// [`Self::fetch_message_revisions`].
     ///
     /// If provided, ``user_id``` will be checked against the author of the message and throw a
     /// forbidden error if they do not match.
@@ -560,6 +897,7 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid, including any new embeds.
     /// * If an error occurs with fetching the message.
     async fn edit_message(
         &mut self,
@@ -568,6 +906,8 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         user_id: Option<u64>,
         payload: EditMessagePayload,
     ) -> crate::Result<(Message, Message)> {
+        payload.validate()?;
+
         let old = get_pool()
             .fetch_message(channel_id, message_id)
             .await?
@@ -589,13 +929,30 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
             .embeds
             .into_option_or_if_absent_then(|| Some(old.embeds.clone()))
             .unwrap_or_default();
-        let embeds = serde_json::to_value(embeds).map_err(|err| Error::InternalError {
-            what: Some("embed serialization".to_string()),
-            message: err.to_string(),
-            debug: Some(format!("{err:?}")),
-        })?;
+        let embeds = serde_json::to_value(embeds)?;
 
-        let mentions = content.as_deref().map(extract_mentions).unwrap_or_default();
+        sqlx::query!(
+            "INSERT INTO message_revisions (message_id, revision_id, content, embeds, edited_at)
+            SELECT
+                id,
+                COALESCE(
+                    (SELECT MAX(revision_id) FROM message_revisions WHERE message_id = $1),
+                    0
+                ) + 1,
+                content,
+                embeds,
+                edited_at
+            FROM messages
+            WHERE id = $1 AND channel_id = $2 AND deleted_at IS NULL",
+            message_id as i64,
+            channel_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let mentions = get_pool()
+            .resolve_stored_mentions(channel_id, content.as_deref())
+            .await?;
         let mentions_i64 = mentions.iter().map(|m| *m as i64).collect_vec();
         let new = sqlx::query!(
             r#"UPDATE messages
@@ -605,9 +962,9 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
                 edited_at = NOW(), 
                 mentions = $3::BIGINT[]
             WHERE 
-                id = $4 AND channel_id = $5
+                id = $4 AND channel_id = $5 AND deleted_at IS NULL
             RETURNING *, embeds AS "embeds_ser: sqlx::types::Json<Vec<Embed>>""#,
-            content,
+            encode_content(content.as_deref())?,
             embeds,
             &mentions_i64,
             message_id as i64,
@@ -616,27 +973,178 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
         .fetch_one(self.transaction())
         .await?;
 
-        Ok((old, construct_message!(new)))
+        Ok((old, construct_message!(new)?))
+    }
+
+    /// Fetches all previous revisions of the given message, oldest first.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the revisions.
+    async fn fetch_message_revisions(
+        &self,
+        message_id: u64,
+    ) -> crate::Result<Vec<MessageRevision>> {
+        sqlx::query!(
+            r#"SELECT
+                message_id,
+                revision_id,
+                content,
+                embeds AS "embeds: sqlx::types::Json<Vec<Embed>>",
+                edited_at,
+                replaced_at
+            FROM message_revisions
+            WHERE message_id = $1
+            ORDER BY revision_id ASC"#,
+            message_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| {
+            Ok(MessageRevision {
+                message_id: r.message_id as u64,
+                revision_id: r.revision_id as u32,
+                content: decode_content(r.content)?,
+                embeds: r.embeds.0,
+                edited_at: r.edited_at,
+                replaced_at: r.replaced_at,
+            })
+        })
+        .collect()
+    }
+
+    /// Fetches a single revision of the given message.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the revision. If the revision is not found, `Ok(None)`
+    /// is returned.
+    async fn fetch_revision(
+        &self,
+        message_id: u64,
+        revision_id: u32,
+    ) -> crate::Result<Option<MessageRevision>> {
+        sqlx::query!(
+            r#"SELECT
+                message_id,
+                revision_id,
+                content,
+                embeds AS "embeds: sqlx::types::Json<Vec<Embed>>",
+                edited_at,
+                replaced_at
+            FROM message_revisions
+            WHERE message_id = $1 AND revision_id = $2"#,
+            message_id as i64,
+            revision_id as i32,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| {
+            Ok(MessageRevision {
+                message_id: r.message_id as u64,
+                revision_id: r.revision_id as u32,
+                content: decode_content(r.content)?,
+                embeds: r.embeds.0,
+                edited_at: r.edited_at,
+                replaced_at: r.replaced_at,
+            })
+        })
+        .transpose()
     }
 
     /// Deletes a message with the given channel and message ID.
     ///
+    /// If ``soft`` is `true`, the message is only flagged as deleted and is hidden from all reads
+    /// until it is either [restored](Self::restore_message) or
+    /// [purged](Self::purge_soft_deleted). Otherwise, the message is removed permanently.
+    ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
     /// * If an error occurs with deleting the message.
-    async fn delete_message(&self, channel_id: u64, message_id: u64) -> crate::Result<()> {
-        sqlx::query!(
-            "DELETE FROM messages WHERE id = $1 AND channel_id = $2",
+    async fn delete_message(
+        &mut self,
+        channel_id: u64,
+        message_id: u64,
+        soft: bool,
+    ) -> crate::Result<()> {
+        if soft {
+            sqlx::query!(
+                "UPDATE messages SET deleted_at = NOW()
+                WHERE id = $1 AND channel_id = $2 AND deleted_at IS NULL",
+                message_id as i64,
+                channel_id as i64,
+            )
+            .execute(self.transaction())
+            .await?;
+        } else {
+            sqlx::query!(
+                "DELETE FROM messages WHERE id = $1 AND channel_id = $2",
+                message_id as i64,
+                channel_id as i64,
+            )
+            .execute(self.transaction())
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Restores a soft-deleted message with the given channel and message ID. Returns the restored
+    /// message.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If no soft-deleted message with the given ID exists in the channel.
+    /// * If an error occurs with restoring the message.
+    async fn restore_message(
+        &mut self,
+        channel_id: u64,
+        message_id: u64,
+    ) -> crate::Result<Message> {
+        let message = sqlx::query!(
+            r#"UPDATE messages SET deleted_at = NULL
+            WHERE id = $1 AND channel_id = $2 AND deleted_at IS NOT NULL
+            RETURNING *, embeds AS "embeds_ser: sqlx::types::Json<Vec<Embed>>""#,
             message_id as i64,
             channel_id as i64,
         )
-        .execute(self.executor())
-        .await?;
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found(
+            "message",
+            format!("Deleted message with ID {message_id} not found"),
+        )?;
 
-        Ok(())
+        let mut message = construct_message!(message)?;
+        message.attachments = get_pool().fetch_message_attachments(message_id).await?;
+        message.references = get_pool().fetch_message_references(message_id).await?;
+        message.reactions = get_pool().fetch_reactions(message_id).await?;
+        message.poll = get_pool().fetch_poll(message_id).await?;
+        Ok(message)
+    }
+
+    /// Permanently removes all messages that were soft-deleted before the given time. Returns the
+    /// number of messages purged.
+    ///
+    /// This is a maintenance method that should be run periodically, usually with a cutoff of
+    /// [`soft_deleted_message_retention_days`](crate::Limits::soft_deleted_message_retention_days)
+    /// days ago.
+    ///
+    /// # Errors
+    /// * If an error occurs with purging the messages.
+    async fn purge_soft_deleted(&self, before: DateTime<Utc>) -> crate::Result<u64> {
+        Ok(sqlx::query!(
+            "DELETE FROM messages WHERE deleted_at IS NOT NULL AND deleted_at < $1",
+            before,
+        )
+        .execute(self.executor())
+        .await?
+        .rows_affected())
     }
 
     /// Bulk deletes messages with the given message IDs, optionally limiting to a channel.
@@ -680,15 +1188,16 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
             .iter()
             .filter_map(|g| g.channels.as_ref().map(|c| (g, c)))
         {
-            for channel in channels {
-                if self
-                    .fetch_member_permissions(guild.partial.id, user_id, Some(channel.id))
+            let ids = channels.iter().map(|c| c.id).collect::<Vec<_>>();
+            channel_ids.extend(
+                self.fetch_member_permissions_in_channels(guild.partial.id, user_id, &ids)
                     .await?
-                    .contains(Permissions::VIEW_CHANNEL | Permissions::VIEW_MESSAGE_HISTORY)
-                {
-                    channel_ids.push(channel.id);
-                }
-            }
+                    .into_iter()
+                    .filter_map(|(id, p)| {
+                        p.contains(Permissions::VIEW_CHANNEL | Permissions::VIEW_MESSAGE_HISTORY)
+                            .then_some(id)
+                    }),
+            );
         }
         Ok(channel_ids)
     }
@@ -715,6 +1224,8 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
             LEFT JOIN channel_acks a ON m.channel_id = a.channel_id AND a.user_id = $1
             WHERE
                 m.channel_id = ANY($2::BIGINT[])
+            AND
+                m.deleted_at IS NULL
             AND (
                 a.last_message_id IS NULL
                 OR m.id > a.last_message_id
@@ -741,3 +1252,222 @@ pub trait MessageDbExt<'t>: DbExt<'t> {
 }
 
 impl<'t, T> MessageDbExt<'t> for T where T: DbExt<'t> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{FutureExt, StreamExt};
+    use sqlx::{Pool, Postgres};
+    use std::future::ready;
+
+    fn message(id: u64) -> Message {
+        Message {
+            id,
+            channel_id: 1,
+            author_id: None,
+            author: None,
+            kind: MessageInfo::Default,
+            content: None,
+            embeds: Vec::new(),
+            attachments: Vec::new(),
+            flags: MessageFlags::empty(),
+            reactions: Vec::new(),
+            stars: 0,
+            mentions: Vec::new(),
+            edited_at: None,
+            references: Vec::new(),
+            sticker_ids: Vec::new(),
+            poll: None,
+        }
+    }
+
+    fn query(limit: u8, oldest_first: bool) -> MessageHistoryQuery {
+        MessageHistoryQuery {
+            before: None,
+            after: None,
+            limit,
+            user_id: None,
+            oldest_first,
+        }
+    }
+
+    /// Serves pages of the message IDs ``1..=count`` like `fetch_message_history` would.
+    fn fetch_page(count: u64, query: &MessageHistoryQuery) -> Vec<Message> {
+        let ids = (1..=count).filter(|&id| {
+            query.before.is_none_or(|before| id < before)
+                && query.after.is_none_or(|after| id > after)
+        });
+        let ids: Vec<u64> = if query.oldest_first {
+            ids.collect()
+        } else {
+            ids.rev().collect()
+        };
+        ids.into_iter()
+            .take(query.limit as usize)
+            .map(message)
+            .collect()
+    }
+
+    fn collect_ids(stream: impl Stream<Item = crate::Result<Message>>) -> crate::Result<Vec<u64>> {
+        stream
+            .map_ok(|message| message.id)
+            .try_collect()
+            .now_or_never()
+            .expect("fetching should not be pending")
+    }
+
+    #[test]
+    fn test_paginate_message_history() {
+        let mut queries = Vec::new();
+        let stream = paginate_message_history(query(2, false), |query| {
+            queries.push((query.before, query.after));
+            ready(Ok(fetch_page(5, &query)))
+        });
+
+        assert_eq!(collect_ids(stream).unwrap(), vec![5, 4, 3, 2, 1]);
+        assert_eq!(
+            queries,
+            vec![(None, None), (Some(4), None), (Some(2), None)],
+        );
+    }
+
+    #[test]
+    fn test_paginate_message_history_oldest_first() {
+        let mut calls = 0;
+        let stream = paginate_message_history(query(2, true), |query| {
+            calls += 1;
+            ready(Ok(fetch_page(4, &query)))
+        });
+
+        assert_eq!(collect_ids(stream).unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(
+            calls, 3,
+            "an exact multiple of the limit needs an empty final page"
+        );
+    }
+
+    #[test]
+    fn test_paginate_message_history_error() {
+        let mut calls = 0;
+        let stream = paginate_message_history(query(2, false), |query| {
+            calls += 1;
+            ready(if calls == 1 {
+                Ok(fetch_page(5, &query))
+            } else {
+                Err(Error::InternalError {
+                    what: None,
+                    message: "failed".to_string(),
+                    debug: None,
+                })
+            })
+        });
+        let results = stream
+            .map_ok(|message| message.id)
+            .collect::<Vec<_>>()
+            .now_or_never()
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[..2], [Ok(5), Ok(4)]));
+        assert!(results[2].is_err());
+    }
+
+    async fn search(
+        pool: &'static Pool<Postgres>,
+        query: MessageSearchQuery,
+    ) -> crate::Result<(u64, Vec<u64>)> {
+        let result = pool.search_messages(&[20], query).await?;
+        Ok((
+            result.total,
+            result.messages.into_iter().map(|m| m.id).collect(),
+        ))
+    }
+
+    #[sqlx::test(fixtures("search"))]
+    async fn test_search_messages(pool: Pool<Postgres>) -> crate::Result<()> {
+        // `DbExt` is only implemented for a `'static` pool
+        let pool: &'static Pool<Postgres> = Box::leak(Box::new(pool));
+
+        let all = MessageSearchQuery {
+            limit: 25,
+            ..Default::default()
+        };
+        assert_eq!(
+            search(pool, all.clone()).await?,
+            (4, vec![103, 102, 101, 100])
+        );
+
+        let cases = [
+            (
+                MessageSearchQuery {
+                    content: Some("hello".to_string()),
+                    ..all.clone()
+                },
+                vec![103, 101, 100],
+            ),
+            (
+                MessageSearchQuery {
+                    author_id: Some(2),
+                    ..all.clone()
+                },
+                vec![101],
+            ),
+            (
+                MessageSearchQuery {
+                    mentions: Some(2),
+                    ..all.clone()
+                },
+                vec![103, 101],
+            ),
+            (
+                MessageSearchQuery {
+                    has_attachment: Some(true),
+                    ..all.clone()
+                },
+                vec![100],
+            ),
+            (
+                MessageSearchQuery {
+                    has_embed: Some(true),
+                    ..all.clone()
+                },
+                vec![102],
+            ),
+            (
+                MessageSearchQuery {
+                    has_embed: Some(false),
+                    before: Some(103),
+                    after: Some(100),
+                    ..all.clone()
+                },
+                vec![101],
+            ),
+        ];
+        for (query, expected) in cases {
+            let (total, ids) = search(pool, query.clone()).await?;
+            assert_eq!(ids, expected, "{query:?}");
+            assert_eq!(total, expected.len() as u64, "{query:?}");
+        }
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("search"))]
+    async fn test_search_messages_pagination(pool: Pool<Postgres>) -> crate::Result<()> {
+        let pool: &'static Pool<Postgres> = Box::leak(Box::new(pool));
+        let query = |offset| MessageSearchQuery {
+            content: Some("hello".to_string()),
+            order: MessageSearchOrder::Relevance,
+            limit: 2,
+            offset,
+            ..Default::default()
+        };
+
+        assert_eq!(search(pool, query(2)).await?, (3, vec![100]));
+        assert_eq!(
+            search(pool, query(5)).await?,
+            (3, vec![]),
+            "the total should still be counted past the last page",
+        );
+        Ok(())
+    }
+}
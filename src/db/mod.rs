@@ -1,25 +1,62 @@
 #![allow(clippy::redundant_pub_crate)]
 
 mod auth;
+mod automod;
 mod channel;
+mod codec;
+mod discovery;
 mod emoji;
+mod event;
+mod export;
+mod follow;
+mod forum;
 mod guild;
 mod invite;
+mod marketplace;
 mod member;
 mod message;
+mod pin;
+mod poll;
+mod push;
+mod quota;
+mod report;
 mod role;
+mod settings;
+mod starboard;
+mod sticker;
+mod thread;
 mod user;
+mod widget;
 
 pub use auth::AuthDbExt;
-pub use channel::ChannelDbExt;
+pub use automod::AutoModDbExt;
+pub use channel::{ChannelDbExt, MAX_MERGED_CHANNEL_SOURCES};
+pub use codec::{content_codec, set_content_codec, ContentCodec, PassthroughCodec};
+pub(crate) use codec::{decode_content, encode_content};
+pub use discovery::DiscoveryDbExt;
 pub use emoji::EmojiDbExt;
-pub use guild::GuildDbExt;
+pub use event::ScheduledEventDbExt;
+pub use export::ExportDbExt;
+pub use follow::ChannelFollowDbExt;
+pub use forum::ForumDbExt;
+pub use guild::{GuildDbExt, RESERVED_VANITY_URLS, VANITY_URL_MIN_MEMBERS};
 pub use invite::InviteDbExt;
+pub use marketplace::{MarketplaceDbExt, MAX_MARKETPLACE_CONTENT_SIZE};
 pub use member::MemberDbExt;
 pub use message::MessageDbExt;
+pub use pin::PinDbExt;
+pub use poll::PollDbExt;
+pub use push::PushDbExt;
+pub use quota::UploadQuotaDbExt;
+pub use report::ReportDbExt;
 pub use role::RoleDbExt;
+pub use settings::{ClientSettingsDbExt, MAX_ENABLED_PLUGINS};
+pub use starboard::{StarboardDbExt, DEFAULT_STARBOARD_THRESHOLD};
+pub use sticker::StickerDbExt;
+pub use thread::ThreadDbExt;
 pub use user::UserDbExt;
 pub(crate) use user::{DbRelationship, DbRelationshipType};
+pub use widget::{GuildWidgetDbExt, WIDGET_MEMBER_SAMPLE_SIZE};
 
 pub use sqlx;
 use sqlx::{
@@ -0,0 +1,312 @@
+use crate::{
+    db::{get_pool, DbExt, MessageDbExt, UserDbExt},
+    models::{Message, MessageFlags, MessageInfo, Pin, User},
+    Error, NotFoundExt,
+};
+use std::collections::{hash_map::Entry, HashMap};
+
+#[async_trait::async_trait]
+pub trait PinDbExt<'t>: DbExt<'t> {
+    /// Fetches the maximum number of pinned messages allowed in the given channel. This is the
+    /// pin limit of the channel if it has one, otherwise the default from
+    /// [`Limits::max_pins_per_channel`](crate::Limits::max_pins_per_channel).
+    ///
+    /// # Errors
+    /// * If the channel is not found.
+    /// * If an error occurs with fetching the pin limit.
+    async fn fetch_pin_limit(&self, channel_id: u64) -> crate::Result<u16> {
+        let limit = sqlx::query!(
+            "SELECT pin_limit FROM channels WHERE id = $1",
+            channel_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?
+        .pin_limit;
+
+        Ok(limit.map_or(crate::limits().max_pins_per_channel as u16, |l| l as u16))
+    }
+
+    /// Sets the maximum number of pinned messages allowed in the given channel. `None` resets the
+    /// limit to the default. Existing pins over the new limit are kept.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the channel is not found.
+    /// * If an error occurs with updating the pin limit.
+    async fn set_pin_limit(&mut self, channel_id: u64, limit: Option<u16>) -> crate::Result<()> {
+        let result = sqlx::query!(
+            "UPDATE channels SET pin_limit = $1 WHERE id = $2",
+            limit.map(|l| l as i16),
+            channel_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                entity: "channel".to_string(),
+                message: format!("Channel with ID {channel_id} not found"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fetches all pinned messages in the given channel, in the order they should be displayed.
+    /// Pins are ordered by their position, then by when they were pinned, most recent first.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the pins.
+    async fn fetch_pins(&self, channel_id: u64) -> crate::Result<Vec<Pin>> {
+        let pins = sqlx::query!(
+            "SELECT message_id, pinned_by, pinned_at, position FROM pins
+            WHERE channel_id = $1
+            ORDER BY position, pinned_at DESC",
+            channel_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        let message_ids = pins.iter().map(|p| p.message_id as u64).collect::<Vec<_>>();
+        let mut messages: HashMap<u64, Message> = self
+            .bulk_fetch_messages(Some(&[channel_id as i64]), &message_ids, None)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect();
+
+        let mut users: HashMap<u64, Option<User>> = HashMap::new();
+        let mut out = Vec::with_capacity(pins.len());
+        for pin in pins {
+            let Some(message) = messages.remove(&(pin.message_id as u64)) else {
+                continue;
+            };
+            let pinned_by = match pin.pinned_by {
+                Some(user_id) => {
+                    let user_id = user_id as u64;
+                    if let Entry::Vacant(entry) = users.entry(user_id) {
+                        entry.insert(self.fetch_user_by_id(user_id).await?);
+                    }
+                    users[&user_id].clone()
+                }
+                None => None,
+            };
+
+            out.push(Pin {
+                message,
+                pinned_by,
+                pinned_at: pin.pinned_at,
+                position: pin.position as u16,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Pins the given message to the top of the pins panel of the channel, sending a pin system
+    /// message with the given ID if ``system_message_id`` is provided.
+    ///
+    /// Returns a tuple ``(pin, system_message)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the message is not found.
+    /// * If the message is already pinned.
+    /// * If the channel has reached its pin limit.
+    /// * If an error occurs with pinning the message or sending the system message.
+    async fn pin_message(
+        &mut self,
+        channel_id: u64,
+        message_id: u64,
+        pinned_by: u64,
+        system_message_id: Option<u64>,
+    ) -> crate::Result<(Pin, Option<Message>)> {
+        let mut message = get_pool()
+            .fetch_message(channel_id, message_id)
+            .await?
+            .ok_or_not_found("message", format!("Message with ID {message_id} not found"))?;
+
+        if message.flags.contains(MessageFlags::PINNED) {
+            return Err(Error::AlreadyExists {
+                what: "pin".to_string(),
+                message: "This message is already pinned".to_string(),
+            });
+        }
+
+        let limit = get_pool().fetch_pin_limit(channel_id).await?;
+        let count = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!" FROM pins WHERE channel_id = $1"#,
+            channel_id as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .count;
+
+        if count >= limit as i64 {
+            return Err(Error::InvalidField {
+                field: "message_id".to_string(),
+                message: format!("This channel has reached its limit of {limit} pinned messages"),
+            });
+        }
+
+        sqlx::query!(
+            "UPDATE pins SET position = position + 1 WHERE channel_id = $1",
+            channel_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let pinned_at = sqlx::query!(
+            "INSERT INTO pins (channel_id, message_id, pinned_by, position)
+            VALUES ($1, $2, $3, 0)
+            RETURNING pinned_at",
+            channel_id as i64,
+            message_id as i64,
+            pinned_by as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .pinned_at;
+
+        message.flags = self
+            .edit_message_flags(
+                channel_id,
+                message_id,
+                MessageFlags::PINNED,
+                MessageFlags::empty(),
+            )
+            .await?;
+
+        let system_message = match system_message_id {
+            Some(id) => Some(
+                self.send_system_message(
+                    channel_id,
+                    id,
+                    MessageInfo::Pin {
+                        pinned_message_id: message_id,
+                        pinned_by,
+                    },
+                )
+                .await?,
+            ),
+            None => None,
+        };
+
+        let pin = Pin {
+            message,
+            pinned_by: get_pool().fetch_user_by_id(pinned_by).await?,
+            pinned_at,
+            position: 0,
+        };
+        Ok((pin, system_message))
+    }
+
+    /// Unpins the given message. Returns whether the message was pinned.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with unpinning the message.
+    async fn unpin_message(&mut self, channel_id: u64, message_id: u64) -> crate::Result<bool> {
+        let Some(position) = sqlx::query!(
+            "DELETE FROM pins WHERE channel_id = $1 AND message_id = $2 RETURNING position",
+            channel_id as i64,
+            message_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .map(|r| r.position) else {
+            return Ok(false);
+        };
+
+        sqlx::query!(
+            "UPDATE pins SET position = position - 1 WHERE channel_id = $1 AND position > $2",
+            channel_id as i64,
+            position,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        self.edit_message_flags(
+            channel_id,
+            message_id,
+            MessageFlags::empty(),
+            MessageFlags::PINNED,
+        )
+        .await?;
+
+        Ok(true)
+    }
+
+    /// Reorders the pins of the given channel. ``message_ids`` must contain the IDs of all pinned
+    /// messages in the channel, in the order they should be displayed.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If ``message_ids`` does not contain exactly the pinned messages of the channel.
+    /// * If an error occurs with reordering the pins.
+    async fn edit_pin_positions(
+        &mut self,
+        channel_id: u64,
+        message_ids: &[u64],
+    ) -> crate::Result<()> {
+        let mut pinned = sqlx::query!(
+            "SELECT message_id FROM pins WHERE channel_id = $1",
+            channel_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.message_id as u64)
+        .collect::<Vec<_>>();
+
+        let mut requested = message_ids.to_vec();
+        pinned.sort_unstable();
+        requested.sort_unstable();
+        requested.dedup();
+
+        if pinned != requested || requested.len() != message_ids.len() {
+            return Err(Error::InvalidField {
+                field: "message_ids".to_string(),
+                message: format!(
+                    "Expected to reorder {} pins, but the given message IDs do not match them",
+                    pinned.len(),
+                ),
+            });
+        }
+
+        let ids = message_ids.iter().map(|&id| id as i64).collect::<Vec<_>>();
+        let positions = (0..ids.len() as i16).collect::<Vec<_>>();
+
+        sqlx::query(
+            r"UPDATE
+                pins
+            SET
+                position = p.position
+            FROM
+                (SELECT UNNEST($1::BIGINT[]) AS id, UNNEST($2::SMALLINT[]) AS position) AS p
+            WHERE
+                pins.channel_id = $3 AND pins.message_id = p.id
+            ",
+        )
+        .bind(&ids)
+        .bind(&positions)
+        .bind(channel_id as i64)
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl<'t, T> PinDbExt<'t> for T where T: DbExt<'t> {}
@@ -0,0 +1,335 @@
+use crate::{
+    db::DbExt,
+    http::{message::CreatePollPayload, validate::Validate},
+    models::{Poll, PollAnswer},
+    Error, NotFoundExt,
+};
+use chrono::{Duration, Utc};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+#[async_trait::async_trait]
+pub trait PollDbExt<'t>: DbExt<'t> {
+    /// Fetches the polls attached to the given messages, along with their results. Messages
+    /// without a poll are not included in the returned map.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the polls.
+    async fn fetch_polls(&self, message_ids: &[u64]) -> crate::Result<HashMap<u64, Poll>> {
+        let ids = message_ids.iter().map(|&id| id as i64).collect_vec();
+
+        let mut answers = sqlx::query!(
+            r#"SELECT
+                a.message_id,
+                a.answer_id,
+                a.text,
+                COUNT(v.user_id) AS "votes!"
+            FROM poll_answers a
+            LEFT JOIN poll_votes v
+                ON v.message_id = a.message_id AND v.answer_id = a.answer_id
+            WHERE a.message_id = ANY($1::BIGINT[])
+            GROUP BY (a.message_id, a.answer_id)
+            ORDER BY a.answer_id"#,
+            &ids,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| {
+            (
+                r.message_id as u64,
+                PollAnswer {
+                    id: r.answer_id as _,
+                    text: r.text,
+                    votes: r.votes as _,
+                },
+            )
+        })
+        .into_group_map();
+
+        let polls = sqlx::query!(
+            "SELECT * FROM polls WHERE message_id = ANY($1::BIGINT[])",
+            &ids,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| {
+            let message_id = r.message_id as u64;
+            let poll = Poll {
+                question: r.question,
+                answers: answers.remove(&message_id).unwrap_or_default(),
+                multi_select: r.multi_select,
+                expires_at: r.expires_at,
+                finalized: r.finalized,
+            };
+            (message_id, poll)
+        })
+        .collect();
+
+        Ok(polls)
+    }
+
+    /// Fetches the poll attached to the given message, along with its results.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the poll.
+    async fn fetch_poll(&self, message_id: u64) -> crate::Result<Option<Poll>> {
+        Ok(self.fetch_polls(&[message_id]).await?.remove(&message_id))
+    }
+
+    /// Fetches the IDs of the answers the given user voted for in the poll attached to the given
+    /// message, in ascending order.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the votes.
+    async fn fetch_poll_votes(&self, message_id: u64, user_id: u64) -> crate::Result<Vec<u16>> {
+        let answer_ids = sqlx::query!(
+            "SELECT answer_id FROM poll_votes
+            WHERE message_id = $1 AND user_id = $2
+            ORDER BY answer_id",
+            message_id as i64,
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.answer_id as u16)
+        .collect();
+
+        Ok(answer_ids)
+    }
+
+    /// Attaches a poll to the given message. This should be called when the message is created.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the poll is invalid.
+    /// * If an error occurs with creating the poll.
+    async fn create_poll(
+        &mut self,
+        message_id: u64,
+        payload: &CreatePollPayload,
+    ) -> crate::Result<Poll> {
+        payload.validate()?;
+
+        let question = payload.question.trim().to_string();
+        let answers = payload
+            .answers
+            .iter()
+            .map(|answer| answer.trim().to_string())
+            .collect_vec();
+        let expires_at = payload
+            .duration_hours
+            .map(|hours| Utc::now() + Duration::hours(hours as _));
+
+        sqlx::query!(
+            "INSERT INTO polls (message_id, question, multi_select, expires_at)
+            VALUES ($1, $2, $3, $4)",
+            message_id as i64,
+            question,
+            payload.multi_select,
+            expires_at,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO poll_answers (message_id, answer_id, text)
+            SELECT $1, * FROM UNNEST($2::SMALLINT[], $3::TEXT[])",
+            message_id as i64,
+            &(0..answers.len() as i16).collect_vec(),
+            &answers,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(Poll {
+            question,
+            answers: answers
+                .into_iter()
+                .zip(0..)
+                .map(|(text, id)| PollAnswer { id, text, votes: 0 })
+                .collect(),
+            multi_select: payload.multi_select,
+            expires_at,
+            finalized: false,
+        })
+    }
+
+    /// Locks the poll attached to the given message for the rest of the transaction, so that
+    /// votes in the poll are serialized. Returns whether the poll is multi-select.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the message is not found or has no poll.
+    /// * If the poll has been finalized or has expired.
+    async fn lock_open_poll(&mut self, message_id: u64) -> crate::Result<bool> {
+        let poll = sqlx::query!(
+            "SELECT p.multi_select, p.finalized, p.expires_at
+            FROM polls p
+            INNER JOIN messages m ON m.id = p.message_id
+            WHERE p.message_id = $1 AND m.deleted_at IS NULL
+            FOR UPDATE OF p",
+            message_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("poll", format!("Message with ID {message_id} has no poll"))?;
+
+        if poll.finalized || poll.expires_at.is_some_and(|at| at <= Utc::now()) {
+            return Err(Error::PollEnded {
+                message_id,
+                message: "This poll has ended".to_string(),
+            });
+        }
+        Ok(poll.multi_select)
+    }
+
+    /// Votes for an answer in the poll attached to the given message. In single-select polls, any
+    /// previous vote of the user is replaced, in which case the ID of the answer the user
+    /// previously voted for is returned.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the message is not found or has no poll.
+    /// * If the poll has ended.
+    /// * If the answer is not found in the poll.
+    /// * If the user already voted for the answer.
+    async fn add_poll_vote(
+        &mut self,
+        message_id: u64,
+        user_id: u64,
+        answer_id: u16,
+    ) -> crate::Result<Option<u16>> {
+        let multi_select = self.lock_open_poll(message_id).await?;
+
+        let answer_exists = sqlx::query!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM poll_answers WHERE message_id = $1 AND answer_id = $2
+            ) AS "exists!""#,
+            message_id as i64,
+            answer_id as i16,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .exists;
+
+        if !answer_exists {
+            return Err(Error::NotFound {
+                entity: "poll_answer".to_string(),
+                message: format!("Poll answer with ID {answer_id} not found"),
+            });
+        }
+
+        let replaced = if multi_select {
+            None
+        } else {
+            sqlx::query!(
+                "DELETE FROM poll_votes
+                WHERE message_id = $1 AND user_id = $2 AND answer_id <> $3
+                RETURNING answer_id",
+                message_id as i64,
+                user_id as i64,
+                answer_id as i16,
+            )
+            .fetch_optional(self.transaction())
+            .await?
+            .map(|r| r.answer_id as u16)
+        };
+
+        let inserted = sqlx::query!(
+            "INSERT INTO poll_votes (message_id, answer_id, user_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING",
+            message_id as i64,
+            answer_id as i16,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(Error::AlreadyExists {
+                what: "poll_vote".to_string(),
+                message: format!("You already voted for answer {answer_id}"),
+            });
+        }
+        Ok(replaced)
+    }
+
+    /// Removes the user's vote for an answer in the poll attached to the given message.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the message is not found or has no poll.
+    /// * If the poll has ended.
+    /// * If the user did not vote for the answer.
+    async fn remove_poll_vote(
+        &mut self,
+        message_id: u64,
+        user_id: u64,
+        answer_id: u16,
+    ) -> crate::Result<()> {
+        self.lock_open_poll(message_id).await?;
+
+        let result = sqlx::query!(
+            "DELETE FROM poll_votes WHERE message_id = $1 AND answer_id = $2 AND user_id = $3",
+            message_id as i64,
+            answer_id as i16,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                entity: "poll_vote".to_string(),
+                message: format!("You did not vote for answer {answer_id}"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Finalizes all polls that have expired. Returns the channel and message IDs of the messages
+    /// whose polls were finalized, so that updates can be dispatched for them.
+    ///
+    /// This is a maintenance method that should be run periodically, e.g. every minute.
+    ///
+    /// # Errors
+    /// * If an error occurs with finalizing the polls.
+    async fn finalize_expired_polls(&self) -> crate::Result<Vec<(u64, u64)>> {
+        let finalized = sqlx::query!(
+            "UPDATE polls p SET finalized = TRUE
+            FROM messages m
+            WHERE
+                m.id = p.message_id
+                AND m.deleted_at IS NULL
+                AND NOT p.finalized
+                AND p.expires_at <= NOW()
+            RETURNING m.channel_id, p.message_id",
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| (r.channel_id as u64, r.message_id as u64))
+        .collect();
+
+        Ok(finalized)
+    }
+}
+
+impl<'t, T> PollDbExt<'t> for T where T: DbExt<'t> {}
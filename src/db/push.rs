@@ -0,0 +1,140 @@
+use crate::{
+    db::DbExt,
+    http::{user::RegisterPushSubscriptionPayload, validate::Validate},
+    models::{PushProvider, PushSubscription, WebPushKeys},
+    NotFoundExt,
+};
+
+macro_rules! construct_push_subscription {
+    ($data:ident) => {{
+        PushSubscription {
+            user_id: $data.user_id as u64,
+            provider: PushProvider::from_db(&$data.provider)?,
+            endpoint: $data.endpoint,
+            keys: $data
+                .p256dh
+                .zip($data.auth)
+                .map(|(p256dh, auth)| WebPushKeys { p256dh, auth }),
+            created_at: $data.created_at,
+        }
+    }};
+}
+
+#[async_trait::async_trait]
+pub trait PushDbExt<'t>: DbExt<'t> {
+    /// Subscribes a device to receive push notifications for the given user. If the endpoint is
+    /// already registered, it is transferred to the user and its keys are replaced, since a
+    /// device may be signed into a different account.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the endpoint is empty or too long.
+    /// * If keys are missing for a Web Push subscription, or provided for any other provider.
+    /// * If an error occurs with registering the subscription.
+    async fn register_push_subscription(
+        &mut self,
+        user_id: u64,
+        payload: RegisterPushSubscriptionPayload,
+    ) -> crate::Result<PushSubscription> {
+        payload.validate()?;
+
+        let (p256dh, auth) = payload.keys.map(|keys| (keys.p256dh, keys.auth)).unzip();
+        let subscription = sqlx::query!(
+            r#"INSERT INTO
+                push_subscriptions (endpoint, user_id, provider, p256dh, auth)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            ON CONFLICT
+                (endpoint)
+            DO UPDATE SET
+                user_id = $2,
+                provider = $3,
+                p256dh = $4,
+                auth = $5,
+                created_at = NOW()
+            RETURNING *"#,
+            payload.endpoint,
+            user_id as i64,
+            payload.provider.name(),
+            p256dh,
+            auth,
+        )
+        .fetch_one(self.transaction())
+        .await?;
+
+        Ok(construct_push_subscription!(subscription))
+    }
+
+    /// Unsubscribes the device with the given endpoint from the push notifications of the user.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user has no subscription with the given endpoint.
+    /// * If an error occurs with removing the subscription.
+    async fn remove_push_subscription(
+        &mut self,
+        user_id: u64,
+        endpoint: &str,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "DELETE FROM push_subscriptions WHERE user_id = $1 AND endpoint = $2 RETURNING endpoint",
+            user_id as i64,
+            endpoint,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found(
+            "push_subscription",
+            "No push subscription with the given endpoint found",
+        )?;
+
+        Ok(())
+    }
+
+    /// Removes the subscriptions with the given endpoints regardless of the user they belong to.
+    /// This should be called when a provider reports that an endpoint is no longer valid.
+    ///
+    /// Returns the number of subscriptions removed.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with removing the subscriptions.
+    async fn prune_push_subscriptions(&mut self, endpoints: &[String]) -> crate::Result<u64> {
+        Ok(sqlx::query!(
+            "DELETE FROM push_subscriptions WHERE endpoint = ANY($1)",
+            endpoints,
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected())
+    }
+
+    /// Fetches all devices subscribed to the push notifications of the given user. Whether a
+    /// notification should be delivered at all is decided separately by
+    /// [`UserDbExt::can_push`](crate::db::UserDbExt::can_push).
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the subscriptions.
+    async fn fetch_push_targets(&self, user_id: u64) -> crate::Result<Vec<PushSubscription>> {
+        sqlx::query!(
+            "SELECT * FROM push_subscriptions WHERE user_id = $1 ORDER BY created_at",
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| Ok(construct_push_subscription!(r)))
+        .collect()
+    }
+}
+
+impl<'t, T> PushDbExt<'t> for T where T: DbExt<'t> {}
@@ -0,0 +1,95 @@
+use crate::{db::DbExt, error::QuotaType, Error};
+
+#[async_trait::async_trait]
+pub trait UploadQuotaDbExt<'t>: DbExt<'t> {
+    /// Fetches the total size of all attachments uploaded by the given user, in bytes.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the usage.
+    async fn fetch_user_upload_usage(&self, user_id: u64) -> crate::Result<u64> {
+        let usage = sqlx::query!(
+            r#"SELECT COALESCE(SUM(a.size), 0)::BIGINT AS "usage!"
+            FROM attachments a
+            INNER JOIN messages m ON m.id = a.message_id
+            WHERE m.author_id = $1 AND m.deleted_at IS NULL"#,
+            user_id as i64,
+        )
+        .fetch_one(self.executor())
+        .await?
+        .usage;
+
+        Ok(usage as u64)
+    }
+
+    /// Fetches the total size of all attachments uploaded within the given guild, in bytes.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the usage.
+    async fn fetch_guild_upload_usage(&self, guild_id: u64) -> crate::Result<u64> {
+        let usage = sqlx::query!(
+            r#"SELECT COALESCE(SUM(a.size), 0)::BIGINT AS "usage!"
+            FROM attachments a
+            INNER JOIN messages m ON m.id = a.message_id
+            INNER JOIN channels c ON c.id = m.channel_id
+            WHERE c.guild_id = $1 AND m.deleted_at IS NULL"#,
+            guild_id as i64,
+        )
+        .fetch_one(self.executor())
+        .await?
+        .usage;
+
+        Ok(usage as u64)
+    }
+
+    /// Asserts that the user can upload a file of the given size, in bytes. If the file is
+    /// uploaded within a guild, the quota of the guild is checked as well. DM uploads only count
+    /// towards the quota of the user.
+    ///
+    /// Returns the number of bytes that can still be uploaded after this file, which is the
+    /// smaller of the remaining quotas.
+    ///
+    /// # Errors
+    /// * If the upload would exceed the quota of the user or the guild.
+    /// * If an error occurs with fetching the usage.
+    async fn check_upload_allowed(
+        &self,
+        user_id: u64,
+        guild_id: Option<u64>,
+        size: u64,
+    ) -> crate::Result<u64> {
+        let limits = crate::limits();
+        let mut remaining = limits
+            .user_upload_quota
+            .saturating_sub(self.fetch_user_upload_usage(user_id).await?);
+        if size > remaining {
+            return Err(Error::QuotaExceeded {
+                quota_type: QuotaType::User,
+                remaining,
+                message: format!(
+                    "Uploading this file would exceed your storage quota, only {remaining} bytes remain",
+                ),
+            });
+        }
+
+        if let Some(guild_id) = guild_id {
+            let guild_remaining = limits
+                .guild_upload_quota
+                .saturating_sub(self.fetch_guild_upload_usage(guild_id).await?);
+            if size > guild_remaining {
+                return Err(Error::QuotaExceeded {
+                    quota_type: QuotaType::Guild,
+                    remaining: guild_remaining,
+                    message: format!(
+                        "Uploading this file would exceed the storage quota of this guild, only \
+                        {guild_remaining} bytes remain",
+                    ),
+                });
+            }
+            remaining = remaining.min(guild_remaining);
+        }
+
+        Ok(remaining - size)
+    }
+}
+
+impl<'t, T> UploadQuotaDbExt<'t> for T where T: DbExt<'t> {}
@@ -0,0 +1,306 @@
+use crate::{
+    db::{get_pool, DbExt, GuildDbExt},
+    http::{
+        report::{CreateReportPayload, ReportQuery, ResolveReportPayload},
+        validate::Validate,
+    },
+    models::{Permissions, Report, ReportReason, ReportStatus, UserFlags},
+    Error, NotFoundExt,
+};
+use itertools::Itertools;
+
+macro_rules! construct_report {
+    ($data:ident) => {{
+        Report {
+            id: $data.id as _,
+            reporter_id: $data.reporter_id as _,
+            guild_id: $data.guild_id.map(|id| id as _),
+            channel_id: $data.channel_id.map(|id| id as _),
+            message_id: $data.message_id.map(|id| id as _),
+            target_id: $data.target_id as _,
+            reason: ReportReason::from_db(&$data.reason)?,
+            details: $data.details,
+            status: ReportStatus::from_db(&$data.status)?,
+            resolved_by: $data.resolved_by.map(|id| id as _),
+            resolution_note: $data.resolution_note,
+            created_at: $data.created_at,
+            resolved_at: $data.resolved_at,
+        }
+    }};
+}
+
+#[async_trait::async_trait]
+pub trait ReportDbExt<'t>: DbExt<'t> {
+    /// Fetches a report from the database with the given ID.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the report. If the report is not found, `Ok(None)` is
+    /// returned.
+    async fn fetch_report(&self, report_id: u64) -> crate::Result<Option<Report>> {
+        sqlx::query!("SELECT * FROM reports WHERE id = $1", report_id as i64)
+            .fetch_optional(self.executor())
+            .await?
+            .map(|r| Ok(construct_report!(r)))
+            .transpose()
+    }
+
+    /// Fetches reports in the moderation queue, newest first. If ``guild_id`` is given, only
+    /// reports filed in that guild are returned; this is the queue moderators of the guild see.
+    /// Otherwise, all reports are returned; this is the global queue for platform staff.
+    ///
+    /// Permission checks must be done prior to calling this method.
+    ///
+    /// # Errors
+    /// * If the limit is not between ``1`` and ``100``.
+    /// * If an error occurs with fetching the reports.
+    async fn fetch_reports(
+        &self,
+        guild_id: Option<u64>,
+        query: ReportQuery,
+    ) -> crate::Result<Vec<Report>> {
+        if !(1..=100).contains(&query.limit) {
+            return Err(Error::InvalidField {
+                field: "limit".to_string(),
+                message: "Limit must be between 1 and 100".to_string(),
+            });
+        }
+
+        sqlx::query!(
+            "SELECT * FROM reports
+            WHERE
+                ($1::BIGINT IS NULL OR guild_id = $1)
+                AND ($2::TEXT IS NULL OR status = $2)
+                AND ($3::BIGINT IS NULL OR id < $3)
+            ORDER BY id DESC
+            LIMIT $4",
+            guild_id.map(|id| id as i64),
+            query.status.map(ReportStatus::name),
+            query.before.map(|id| id as i64),
+            query.limit as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| Ok(construct_report!(r)))
+        .collect()
+    }
+
+    /// Files a new report of a message or user. When a message is reported, its author is the
+    /// reported user and the report is filed in the guild of the message, if any.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If not exactly one of a message and a user is reported.
+    /// * If the details are too long.
+    /// * If the message, user or guild does not exist, or the reported user is not a member of
+    /// the guild.
+    /// * If the message is a system message.
+    /// * If the user is reporting themselves.
+    /// * If the user already has an open report of the same message or user.
+    /// * If an error occurs with filing the report.
+    async fn create_report(
+        &mut self,
+        report_id: u64,
+        reporter_id: u64,
+        payload: CreateReportPayload,
+    ) -> crate::Result<Report> {
+        payload.validate()?;
+
+        let (guild_id, channel_id, target_id) = match (payload.message_id, payload.user_id) {
+            (Some(message_id), None) => {
+                let message = sqlx::query!(
+                    "SELECT m.author_id, m.channel_id, c.guild_id
+                    FROM messages m
+                    INNER JOIN channels c ON c.id = m.channel_id
+                    WHERE m.id = $1 AND m.deleted_at IS NULL",
+                    message_id as i64,
+                )
+                .fetch_optional(self.executor())
+                .await?
+                .ok_or_not_found("message", format!("Message with ID {message_id} not found"))?;
+
+                let author_id = message.author_id.ok_or_else(|| Error::InvalidField {
+                    field: "message_id".to_string(),
+                    message: "System messages cannot be reported".to_string(),
+                })?;
+                (
+                    message.guild_id.map(|id| id as u64),
+                    Some(message.channel_id as u64),
+                    author_id as u64,
+                )
+            }
+            (None, Some(user_id)) => {
+                let exists = sqlx::query!(
+                    "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS \"exists!\"",
+                    user_id as i64,
+                )
+                .fetch_one(self.executor())
+                .await?
+                .exists;
+                if !exists {
+                    return Err(Error::NotFound {
+                        entity: "user".to_string(),
+                        message: format!("User with ID {user_id} not found"),
+                    });
+                }
+                if let Some(guild_id) = payload.guild_id {
+                    get_pool().assert_member_in_guild(guild_id, user_id).await?;
+                }
+                (payload.guild_id, None, user_id)
+            }
+            _ => {
+                return Err(Error::InvalidField {
+                    field: "message_id".to_string(),
+                    message: "Exactly one of message_id and user_id must be provided".to_string(),
+                })
+            }
+        };
+
+        if target_id == reporter_id {
+            return Err(Error::CannotActOnSelf {
+                message: "You cannot report yourself".to_string(),
+            });
+        }
+
+        let duplicate = sqlx::query!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM reports
+                WHERE
+                    reporter_id = $1
+                    AND target_id = $2
+                    AND message_id IS NOT DISTINCT FROM $3
+                    AND status = 'open'
+            ) AS "exists!""#,
+            reporter_id as i64,
+            target_id as i64,
+            payload.message_id.map(|id| id as i64),
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .exists;
+        if duplicate {
+            return Err(Error::AlreadyExists {
+                what: "report".to_string(),
+                message: "You already have an open report of this".to_string(),
+            });
+        }
+
+        let report = sqlx::query!(
+            "INSERT INTO reports
+                (id, reporter_id, guild_id, channel_id, message_id, target_id, reason, details)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *",
+            report_id as i64,
+            reporter_id as i64,
+            guild_id.map(|id| id as i64),
+            channel_id.map(|id| id as i64),
+            payload.message_id.map(|id| id as i64),
+            target_id as i64,
+            payload.reason.name(),
+            payload.details,
+        )
+        .fetch_one(self.transaction())
+        .await?;
+
+        Ok(construct_report!(report))
+    }
+
+    /// Resolves or dismisses an open report. Permission checks must be done prior to calling
+    /// this method.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the new status is ``open``.
+    /// * If the note is too long.
+    /// * If the report does not exist or was already resolved or dismissed.
+    /// * If an error occurs with resolving the report.
+    async fn resolve_report(
+        &mut self,
+        report_id: u64,
+        moderator_id: u64,
+        payload: ResolveReportPayload,
+    ) -> crate::Result<Report> {
+        payload.validate()?;
+
+        let report = sqlx::query!(
+            "UPDATE reports
+            SET status = $1, resolved_by = $2, resolution_note = $3, resolved_at = NOW()
+            WHERE id = $4 AND status = 'open'
+            RETURNING *",
+            payload.status.name(),
+            moderator_id as i64,
+            payload.note,
+            report_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?;
+
+        match report {
+            Some(report) => Ok(construct_report!(report)),
+            None => {
+                get_pool()
+                    .fetch_report(report_id)
+                    .await?
+                    .ok_or_not_found("report", format!("Report with ID {report_id} not found"))?;
+                Err(Error::InvalidField {
+                    field: "status".to_string(),
+                    message: "This report was already resolved or dismissed".to_string(),
+                })
+            }
+        }
+    }
+
+    /// Fetches the IDs of the users that should be notified about the given report: platform
+    /// staff, and if the report was filed in a guild, the members of the guild with the
+    /// `VIEW_REPORTS` permission. The reporter is never included.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the users or their permissions.
+    async fn fetch_report_recipients(&self, report: &Report) -> crate::Result<Vec<u64>> {
+        let mut recipients = sqlx::query!(
+            "SELECT id FROM users WHERE flags & $1 != 0",
+            UserFlags::PRIVILEGED.bits() as i32,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.id as u64)
+        .collect_vec();
+
+        if let Some(guild_id) = report.guild_id {
+            let member_ids = sqlx::query!(
+                "SELECT id FROM members WHERE guild_id = $1",
+                guild_id as i64,
+            )
+            .fetch_all(self.executor())
+            .await?
+            .into_iter()
+            .map(|r| r.id as u64)
+            .collect_vec();
+
+            let permissions = self
+                .fetch_permissions_for_members(guild_id, &member_ids, None)
+                .await?;
+            recipients.extend(
+                permissions
+                    .into_iter()
+                    .filter(|(_, perms)| perms.contains(Permissions::VIEW_REPORTS))
+                    .map(|(user_id, _)| user_id),
+            );
+        }
+
+        Ok(recipients
+            .into_iter()
+            .filter(|&id| id != report.reporter_id)
+            .unique()
+            .collect())
+    }
+}
+
+impl<'t, T> ReportDbExt<'t> for T where T: DbExt<'t> {}
@@ -1,11 +1,19 @@
 use crate::{
     cache,
     db::{get_pool, DbExt, GuildDbExt},
-    http::role::{CreateRolePayload, EditRolePayload},
-    models::{DbGradient, ExtendedColor, ModelType, PermissionPair, Permissions, Role, RoleFlags},
+    http::{
+        role::{CreateRolePayload, EditRolePayload, RoleMembersQuery},
+        validate::Validate,
+    },
+    models::{
+        Asset, DbGradient, ExtendedColor, ModelType, PermissionPair, Permissions, Role, RoleFlags,
+        RoleMembers,
+    },
     snowflake::with_model_type,
-    Error, Maybe,
+    Error,
 };
+use itertools::Itertools;
+use std::collections::HashMap;
 
 macro_rules! query_roles {
     ($where:literal $(, $($args:expr),*)?) => {{
@@ -51,7 +59,7 @@ impl RoleRecord {
             id: self.id as _,
             guild_id: self.guild_id as _,
             name: self.name,
-            icon: self.icon,
+            icon: self.icon.map(Asset::from_raw),
             color: ExtendedColor::from_db(self.color, self.gradient.as_ref()),
             position: self.position as _,
             permissions: PermissionPair {
@@ -297,14 +305,104 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         Ok(roles)
     }
 
-    /// Creates a new role in the given guild ID with the given query. Payload must be validated
-    /// before using this method.
+    /// Fetches a page of the IDs of members that have the given role, in ascending order of user
+    /// ID, along with the total number of members that have the role. If the role is the default
+    /// role of the guild, all members of the guild are considered to have it.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the members.
+    async fn fetch_members_with_role(
+        &self,
+        guild_id: u64,
+        role_id: u64,
+        query: RoleMembersQuery,
+    ) -> crate::Result<RoleMembers> {
+        let after = query.after.map(|id| id as i64);
+        let limit = query.limit as i64;
+
+        let (total, user_ids) = if role_id == with_model_type(guild_id, ModelType::Role) {
+            let total = sqlx::query!(
+                r#"SELECT COUNT(*) AS "count!" FROM members WHERE guild_id = $1"#,
+                guild_id as i64,
+            )
+            .fetch_one(self.executor())
+            .await?
+            .count;
+
+            let user_ids = sqlx::query!(
+                "SELECT id FROM members
+                WHERE guild_id = $1 AND ($2::BIGINT IS NULL OR id > $2)
+                ORDER BY id ASC LIMIT $3",
+                guild_id as i64,
+                after,
+                limit,
+            )
+            .fetch_all(self.executor())
+            .await?
+            .into_iter()
+            .map(|r| r.id as u64)
+            .collect();
+
+            (total, user_ids)
+        } else {
+            let total = sqlx::query!(
+                r#"SELECT COUNT(*) AS "count!" FROM role_data WHERE guild_id = $1 AND role_id = $2"#,
+                guild_id as i64,
+                role_id as i64,
+            )
+            .fetch_one(self.executor())
+            .await?
+            .count;
+
+            let user_ids = sqlx::query!(
+                "SELECT user_id FROM role_data
+                WHERE guild_id = $1 AND role_id = $2 AND ($3::BIGINT IS NULL OR user_id > $3)
+                ORDER BY user_id ASC LIMIT $4",
+                guild_id as i64,
+                role_id as i64,
+                after,
+                limit,
+            )
+            .fetch_all(self.executor())
+            .await?
+            .into_iter()
+            .map(|r| r.user_id as u64)
+            .collect();
+
+            (total, user_ids)
+        };
+
+        Ok(RoleMembers {
+            total: total as u64,
+            user_ids,
+        })
+    }
+
+    /// Fetches a mapping of role IDs to the IDs of members that have each role, for all roles in
+    /// the given guild. Roles without any members and the default role are omitted.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the role data.
+    async fn fetch_role_members_map(&self, guild_id: u64) -> crate::Result<HashMap<u64, Vec<u64>>> {
+        Ok(sqlx::query!(
+            "SELECT role_id, user_id FROM role_data WHERE guild_id = $1 ORDER BY user_id ASC",
+            guild_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| (r.role_id as u64, r.user_id as u64))
+        .into_group_map())
+    }
+
+    /// Creates a new role in the given guild ID with the given payload.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
     /// * If an error occurs with creatimg the role.
     async fn create_role(
         &mut self,
@@ -312,6 +410,8 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         role_id: u64,
         payload: CreateRolePayload,
     ) -> crate::Result<Role> {
+        payload.validate()?;
+
         let mut flags = RoleFlags::default();
         if payload.hoisted {
             flags.insert(RoleFlags::HOISTED);
@@ -328,9 +428,6 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         .execute(self.transaction())
         .await?;
 
-        if let Some(ref color) = payload.color {
-            color.validate()?;
-        }
         let (color, gradient) = payload.color.map(|c| c.to_db()).unzip();
         sqlx::query!(
             r#"INSERT INTO roles (
@@ -345,7 +442,7 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
             payload.name,
             color.flatten(),
             gradient.clone().flatten() as _,
-            payload.icon,
+            payload.icon.as_deref(),
             payload.permissions.allow.bits(),
             payload.permissions.deny.bits(),
             payload.position as i16,
@@ -366,14 +463,14 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         })
     }
 
-    /// Edits the role with the given ID in the given guild. Payload must be validated before using
-    /// this method.
+    /// Edits the role with the given ID in the given guild.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
     /// * If an error occurs with editing the role.
     /// * If the role does not exist.
     async fn edit_role(
@@ -382,6 +479,8 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         mut role: Role,
         payload: EditRolePayload,
     ) -> crate::Result<(Role, Role)> {
+        payload.validate()?;
+
         let old = role.clone();
         let role_id = role.id;
 
@@ -397,9 +496,6 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         if let Some(hoisted) = payload.hoisted {
             role.flags.set(RoleFlags::HOISTED, hoisted);
         }
-        if let Maybe::Value(ref color) = payload.color {
-            color.validate()?;
-        }
         role.color = payload.color.into_option_or_if_absent(role.color);
         role.icon = payload.icon.into_option_or_if_absent(role.icon);
 
@@ -421,7 +517,7 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
                 id = $9
             "#,
             role.name,
-            role.icon,
+            role.icon.as_deref(),
             color.flatten(),
             gradient.flatten() as _,
             role.permissions.allow.bits(),
@@ -433,7 +529,11 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         .execute(self.transaction())
         .await?;
 
-        cache::clear_member_permissions(guild_id).await?;
+        if old.permissions != role.permissions {
+            get_pool()
+                .invalidate_role_permissions(guild_id, role_id)
+                .await?;
+        }
         Ok((old, role))
     }
 
@@ -533,7 +633,11 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         .execute(self.transaction())
         .await?;
 
-        cache::clear_member_permissions(guild_id).await?;
+        for role_id in ids {
+            get_pool()
+                .invalidate_role_permissions(guild_id, role_id as u64)
+                .await?;
+        }
         Ok(())
     }
 
@@ -564,7 +668,40 @@ pub trait RoleDbExt<'t>: DbExt<'t> {
         .execute(self.transaction())
         .await?;
 
-        cache::clear_member_permissions(guild_id).await?;
+        get_pool()
+            .invalidate_role_permissions(guild_id, role_id)
+            .await?;
+        cache::remove_role_members(guild_id, role_id).await?;
+        Ok(())
+    }
+
+    /// Clears the cached permissions of every member that has the given role. Members of the role
+    /// are resolved from the database if they are not cached yet. Since every member has the
+    /// default role, invalidating it clears the permissions of the whole guild.
+    ///
+    /// # Errors
+    /// * If an error occurs with resolving the members or clearing their permissions.
+    async fn invalidate_role_permissions(&self, guild_id: u64, role_id: u64) -> crate::Result<()> {
+        if role_id == with_model_type(guild_id, ModelType::Role) {
+            return cache::clear_member_permissions(guild_id).await;
+        }
+        if cache::invalidate_role_members(guild_id, role_id).await? {
+            return Ok(());
+        }
+
+        let user_ids = sqlx::query!(
+            "SELECT user_id FROM role_data WHERE guild_id = $1 AND role_id = $2",
+            guild_id as i64,
+            role_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.user_id as u64)
+        .collect::<Vec<_>>();
+
+        cache::cache_role_members(guild_id, role_id, &user_ids).await?;
+        cache::invalidate_role_members(guild_id, role_id).await?;
         Ok(())
     }
 }
@@ -0,0 +1,152 @@
+use crate::{
+    db::{get_pool, DbExt, UserDbExt},
+    http::{user::EditClientSettingsPayload, validate::Validate},
+    models::{ClientSettings, MarketplaceKind},
+    Error,
+};
+use itertools::Itertools;
+
+/// The maximum number of marketplace plugins a user may enable at once.
+pub const MAX_ENABLED_PLUGINS: usize = 100;
+
+#[async_trait::async_trait]
+pub trait ClientSettingsDbExt<'t>: DbExt<'t> {
+    /// Fetches the client settings of the given user. Users that have never changed their
+    /// settings have the default settings. The guild order is resolved from the guild folders of
+    /// the user.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the settings.
+    async fn fetch_client_settings(&self, user_id: u64) -> crate::Result<ClientSettings> {
+        let guild_order = self.fetch_guild_folders(user_id).await?;
+
+        let settings = sqlx::query!(
+            "SELECT dm_channel_order, theme, plugins, locale FROM client_settings WHERE user_id = $1",
+            user_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?;
+
+        Ok(match settings {
+            Some(r) => ClientSettings {
+                guild_order,
+                dm_channel_order: r.dm_channel_order.into_iter().map(|id| id as u64).collect(),
+                theme: r.theme.map(|id| id as u64),
+                plugins: r.plugins.into_iter().map(|id| id as u64).collect(),
+                locale: r.locale,
+            },
+            None => ClientSettings {
+                guild_order,
+                ..ClientSettings::default()
+            },
+        })
+    }
+
+    /// Edits the client settings of the given user. Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the theme is not a marketplace theme.
+    /// * If too many plugins are enabled, or any of them is not a marketplace plugin.
+    /// * If the locale is invalid.
+    /// * If an error occurs with editing the settings.
+    async fn update_client_settings(
+        &mut self,
+        user_id: u64,
+        payload: EditClientSettingsPayload,
+    ) -> crate::Result<(ClientSettings, ClientSettings)> {
+        payload.validate()?;
+
+        let before = get_pool().fetch_client_settings(user_id).await?;
+        let mut after = before.clone();
+
+        if let Some(dm_channel_order) = payload.dm_channel_order {
+            after.dm_channel_order = dm_channel_order.into_iter().unique().collect();
+        }
+
+        after.theme = payload.theme.into_option_or_if_absent(after.theme);
+        if let Some(theme) = after.theme
+            && after.theme != before.theme
+        {
+            let valid = sqlx::query!(
+                r#"SELECT EXISTS(
+                    SELECT 1 FROM marketplace_entries WHERE id = $1 AND kind = $2
+                ) AS "valid!""#,
+                theme as i64,
+                MarketplaceKind::Theme.name(),
+            )
+            .fetch_one(self.executor())
+            .await?
+            .valid;
+
+            if !valid {
+                return Err(Error::InvalidField {
+                    field: "theme".to_string(),
+                    message: "Theme must be the ID of a marketplace theme".to_string(),
+                });
+            }
+        }
+
+        if let Some(plugins) = payload.plugins {
+            let plugins = plugins.into_iter().unique().collect_vec();
+            if plugins.len() > MAX_ENABLED_PLUGINS {
+                return Err(Error::InvalidField {
+                    field: "plugins".to_string(),
+                    message: format!("At most {MAX_ENABLED_PLUGINS} plugins may be enabled"),
+                });
+            }
+
+            let found = sqlx::query!(
+                r#"SELECT COUNT(*) AS "count!" FROM marketplace_entries
+                WHERE id = ANY($1) AND kind = $2"#,
+                &plugins.iter().map(|&id| id as i64).collect_vec(),
+                MarketplaceKind::Plugin.name(),
+            )
+            .fetch_one(self.executor())
+            .await?
+            .count;
+
+            if found as usize != plugins.len() {
+                return Err(Error::InvalidField {
+                    field: "plugins".to_string(),
+                    message: "Plugins must be IDs of marketplace plugins".to_string(),
+                });
+            }
+            after.plugins = plugins;
+        }
+
+        after.locale = payload.locale.into_option_or_if_absent(after.locale);
+
+        sqlx::query!(
+            r#"INSERT INTO
+                client_settings (user_id, dm_channel_order, theme, plugins, locale)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            ON CONFLICT
+                (user_id)
+            DO UPDATE SET
+                dm_channel_order = $2,
+                theme = $3,
+                plugins = $4,
+                locale = $5"#,
+            user_id as i64,
+            &after
+                .dm_channel_order
+                .iter()
+                .map(|&id| id as i64)
+                .collect_vec(),
+            after.theme.map(|id| id as i64),
+            &after.plugins.iter().map(|&id| id as i64).collect_vec(),
+            after.locale,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((before, after))
+    }
+}
+
+impl<'t, T> ClientSettingsDbExt<'t> for T where T: DbExt<'t> {}
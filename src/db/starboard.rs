@@ -0,0 +1,305 @@
+use crate::{
+    db::{get_pool, ChannelDbExt, DbExt},
+    http::{guild::EditStarboardPayload, validate::Validate},
+    models::{StarboardConfig, StarboardEntry},
+    Error, NotFoundExt,
+};
+
+/// The number of stars a message needs to be posted in the starboard if the guild has not
+/// configured a threshold.
+pub const DEFAULT_STARBOARD_THRESHOLD: u16 = 3;
+
+#[async_trait::async_trait]
+pub trait StarboardDbExt<'t>: DbExt<'t> {
+    /// Fetches the starboard configuration of the given guild. Guilds that have not configured
+    /// their starboard have it disabled with the default threshold.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the configuration.
+    async fn fetch_starboard_config(&self, guild_id: u64) -> crate::Result<StarboardConfig> {
+        Ok(sqlx::query!(
+            "SELECT channel_id, threshold FROM starboards WHERE guild_id = $1",
+            guild_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map_or(
+            StarboardConfig {
+                guild_id,
+                channel_id: None,
+                threshold: DEFAULT_STARBOARD_THRESHOLD,
+            },
+            |r| StarboardConfig {
+                guild_id,
+                channel_id: r.channel_id.map(|id| id as u64),
+                threshold: r.threshold as u16,
+            },
+        ))
+    }
+
+    /// Edits the starboard configuration of the given guild. Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the channel is not a text channel in the guild.
+    /// * If the threshold is zero.
+    /// * If an error occurs with editing the configuration.
+    async fn edit_starboard_config(
+        &mut self,
+        guild_id: u64,
+        payload: EditStarboardPayload,
+    ) -> crate::Result<(StarboardConfig, StarboardConfig)> {
+        payload.validate()?;
+
+        let before = get_pool().fetch_starboard_config(guild_id).await?;
+        let mut after = before.clone();
+
+        after.channel_id = payload
+            .channel_id
+            .into_option_or_if_absent(after.channel_id);
+        if let Some(channel_id) = after.channel_id {
+            let channel = get_pool()
+                .inspect_channel(channel_id)
+                .await?
+                .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?;
+
+            if channel.guild_id != Some(guild_id) || !channel.channel_type.is_guild_text_based() {
+                return Err(Error::InvalidField {
+                    field: "channel_id".to_string(),
+                    message: "The starboard must be a text-based channel in the guild".to_string(),
+                });
+            }
+        }
+        if let Some(threshold) = payload.threshold {
+            after.threshold = threshold;
+        }
+
+        sqlx::query!(
+            "INSERT INTO starboards (guild_id, channel_id, threshold) VALUES ($1, $2, $3)
+            ON CONFLICT (guild_id) DO UPDATE SET channel_id = $2, threshold = $3",
+            guild_id as i64,
+            after.channel_id.map(|id| id as i64),
+            after.threshold as i16,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((before, after))
+    }
+
+    /// Fetches the number of stars on the given message.
+    ///
+    /// # Errors
+    /// * If the message is not found.
+    /// * If an error occurs with fetching the star count.
+    async fn fetch_star_count(&self, message_id: u64) -> crate::Result<u32> {
+        Ok(sqlx::query!(
+            "SELECT stars FROM messages WHERE id = $1 AND deleted_at IS NULL",
+            message_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found("message", format!("Message with ID {message_id} not found"))?
+        .stars as u32)
+    }
+
+    /// Stars the given message on behalf of the given user. Returns the new number of stars on
+    /// the message, or `None` if the user already starred the message.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the message is not found in the guild.
+    /// * If an error occurs with starring the message.
+    async fn star_message(
+        &mut self,
+        guild_id: u64,
+        message_id: u64,
+        user_id: u64,
+    ) -> crate::Result<Option<u32>> {
+        sqlx::query!(
+            "SELECT 1 AS exists FROM messages m
+            INNER JOIN channels c ON c.id = m.channel_id
+            WHERE m.id = $1 AND c.guild_id = $2 AND m.deleted_at IS NULL",
+            message_id as i64,
+            guild_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found(
+            "message",
+            format!("Message with ID {message_id} not found in guild {guild_id}"),
+        )?;
+
+        let result = sqlx::query!(
+            "INSERT INTO stars (message_id, user_id, guild_id) VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING",
+            message_id as i64,
+            user_id as i64,
+            guild_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let stars = sqlx::query!(
+            "UPDATE messages SET stars = stars + 1
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING stars",
+            message_id as i64,
+        )
+        .fetch_one(self.transaction())
+        .await?
+        .stars;
+
+        Ok(Some(stars as u32))
+    }
+
+    /// Removes the star of the given user from the given message. Returns the new number of
+    /// stars on the message, or `None` if the user had not starred the message.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the message is not found.
+    /// * If an error occurs with unstarring the message.
+    async fn unstar_message(
+        &mut self,
+        message_id: u64,
+        user_id: u64,
+    ) -> crate::Result<Option<u32>> {
+        let result = sqlx::query!(
+            "DELETE FROM stars WHERE message_id = $1 AND user_id = $2",
+            message_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let stars = sqlx::query!(
+            "UPDATE messages SET stars = GREATEST(stars - 1, 0)
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING stars",
+            message_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("message", format!("Message with ID {message_id} not found"))?
+        .stars;
+
+        Ok(Some(stars as u32))
+    }
+
+    /// Fetches the messages in the given guild that have reached the starboard threshold but have
+    /// not been posted in the starboard yet, oldest first. Messages in the starboard channel
+    /// itself are never included. If the starboard is disabled, this returns an empty list.
+    ///
+    /// Once posted, entries should be recorded with [`Self::record_starboard_entry`].
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the messages.
+    async fn fetch_pending_starboard_entries(
+        &self,
+        guild_id: u64,
+    ) -> crate::Result<Vec<StarboardEntry>> {
+        let config = self.fetch_starboard_config(guild_id).await?;
+        let Some(starboard_id) = config.channel_id else {
+            return Ok(Vec::new());
+        };
+
+        Ok(sqlx::query!(
+            "SELECT m.id, m.channel_id, m.stars
+            FROM messages m
+            INNER JOIN channels c ON c.id = m.channel_id
+            WHERE
+                c.guild_id = $1
+                AND m.channel_id <> $2
+                AND m.stars >= $3
+                AND m.deleted_at IS NULL
+                AND NOT EXISTS (SELECT 1 FROM starboard_entries e WHERE e.message_id = m.id)
+            ORDER BY m.id",
+            guild_id as i64,
+            starboard_id as i64,
+            config.threshold as i32,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| StarboardEntry {
+            message_id: r.id as u64,
+            channel_id: r.channel_id as u64,
+            stars: r.stars as u32,
+            starboard_message_id: None,
+        })
+        .collect())
+    }
+
+    /// Fetches the starboard entry of the given message, if it has been posted in the starboard.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the entry.
+    async fn fetch_starboard_entry(
+        &self,
+        message_id: u64,
+    ) -> crate::Result<Option<StarboardEntry>> {
+        Ok(sqlx::query!(
+            "SELECT m.id, m.channel_id, m.stars, e.starboard_message_id
+            FROM starboard_entries e
+            INNER JOIN messages m ON m.id = e.message_id
+            WHERE e.message_id = $1 AND m.deleted_at IS NULL",
+            message_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map(|r| StarboardEntry {
+            message_id: r.id as u64,
+            channel_id: r.channel_id as u64,
+            stars: r.stars as u32,
+            starboard_message_id: Some(r.starboard_message_id as u64),
+        }))
+    }
+
+    /// Records that the given message was posted in the starboard as ``starboard_message_id``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with recording the entry.
+    async fn record_starboard_entry(
+        &mut self,
+        guild_id: u64,
+        message_id: u64,
+        starboard_message_id: u64,
+    ) -> crate::Result<()> {
+        sqlx::query!(
+            "INSERT INTO starboard_entries (message_id, guild_id, starboard_message_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (message_id) DO UPDATE SET starboard_message_id = $3",
+            message_id as i64,
+            guild_id as i64,
+            starboard_message_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl<'t, T> StarboardDbExt<'t> for T where T: DbExt<'t> {}
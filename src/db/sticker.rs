@@ -0,0 +1,229 @@
+use super::{get_pool, DbExt};
+use crate::{
+    http::{
+        sticker::{CreateStickerPayload, EditStickerPayload},
+        validate::Validate,
+    },
+    models::{Permissions, Sticker, StickerFormat},
+    Error, NotFoundExt,
+};
+use std::str::FromStr;
+
+macro_rules! construct_sticker {
+    ($data:expr) => {{
+        let data = $data;
+        StickerFormat::from_str(&data.format).map(|format| Sticker {
+            id: data.id as u64,
+            guild_id: data.guild_id as u64,
+            name: data.name,
+            tags: data.tags,
+            format,
+            created_by: data.created_by.map(|id| id as u64),
+        })
+    }};
+}
+
+#[async_trait::async_trait]
+pub trait StickerDbExt<'t>: DbExt<'t> {
+    /// Fetches all stickers in the guild with the given ID.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the stickers.
+    async fn fetch_all_stickers_in_guild(&self, guild_id: u64) -> crate::Result<Vec<Sticker>> {
+        sqlx::query!(
+            "SELECT * FROM stickers WHERE guild_id = $1 ORDER BY id",
+            guild_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| construct_sticker!(r))
+        .collect()
+    }
+
+    /// Fetches the sticker with the given ID. Returns `None` if not found.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the sticker.
+    async fn fetch_sticker(&self, id: u64) -> crate::Result<Option<Sticker>> {
+        sqlx::query!("SELECT * FROM stickers WHERE id = $1", id as i64)
+            .fetch_optional(self.executor())
+            .await?
+            .map(|r| construct_sticker!(r))
+            .transpose()
+    }
+
+    /// Creates a new sticker in the given guild. The sticker file should be uploaded separately.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid.
+    /// * If an error occurs with creating the sticker.
+    async fn create_sticker(
+        &mut self,
+        id: u64,
+        guild_id: u64,
+        created_by: u64,
+        payload: CreateStickerPayload,
+    ) -> crate::Result<Sticker> {
+        payload.validate()?;
+
+        sqlx::query!(
+            "INSERT INTO stickers (id, guild_id, name, tags, format, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)",
+            id as i64,
+            guild_id as i64,
+            payload.name.trim(),
+            &payload.tags,
+            payload.format.name(),
+            created_by as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(Sticker {
+            id,
+            guild_id,
+            name: payload.name,
+            tags: payload.tags,
+            format: payload.format,
+            created_by: Some(created_by),
+        })
+    }
+
+    /// Edits the sticker with the given ID. Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid.
+    /// * If the sticker is not found.
+    /// * If an error occurs with editing the sticker.
+    async fn edit_sticker(
+        &mut self,
+        id: u64,
+        payload: EditStickerPayload,
+    ) -> crate::Result<(Sticker, Sticker)> {
+        payload.validate()?;
+
+        let before = get_pool()
+            .fetch_sticker(id)
+            .await?
+            .ok_or_not_found("sticker", format!("Sticker with ID {id} not found"))?;
+
+        let mut after = before.clone();
+        if let Some(name) = payload.name {
+            after.name = name;
+        }
+        if let Some(tags) = payload.tags {
+            after.tags = tags;
+        }
+
+        sqlx::query!(
+            "UPDATE stickers SET name = $1, tags = $2 WHERE id = $3",
+            after.name.trim(),
+            &after.tags,
+            id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((before, after))
+    }
+
+    /// Deletes the sticker with the given ID.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the sticker is not found.
+    /// * If an error occurs with deleting the sticker.
+    async fn delete_sticker(&mut self, id: u64) -> crate::Result<()> {
+        let result = sqlx::query!("DELETE FROM stickers WHERE id = $1", id as i64)
+            .execute(self.transaction())
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                entity: "sticker".to_string(),
+                message: format!("Sticker with ID {id} not found"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Asserts that the user can send all of the given stickers in the given guild, or in a DM if
+    /// `guild_id` is `None`. ``permissions`` should be the permissions of the user in the channel
+    /// the stickers are sent in. Sticker IDs that do not exist are ignored.
+    ///
+    /// Stickers from `guild_id` can always be used. Stickers from other guilds can only be used
+    /// if the user is a member of that guild and has the `USE_EXTERNAL_STICKERS` permission
+    /// (which is not required in DMs).
+    ///
+    /// # Errors
+    /// * If the user is missing the `USE_EXTERNAL_STICKERS` permission for an external sticker.
+    /// * If the user is not a member of the guild of an external sticker.
+    /// * If an error occurs with fetching the stickers.
+    async fn assert_can_use_stickers(
+        &self,
+        guild_id: Option<u64>,
+        user_id: u64,
+        permissions: Permissions,
+        sticker_ids: &[u64],
+    ) -> crate::Result<()> {
+        if sticker_ids.is_empty() {
+            return Ok(());
+        }
+
+        let external = sqlx::query!(
+            r#"SELECT
+                s.id,
+                EXISTS(
+                    SELECT 1 FROM members m WHERE m.guild_id = s.guild_id AND m.id = $2
+                ) AS "is_member!"
+            FROM stickers s
+            WHERE s.id = ANY($1::BIGINT[]) AND s.guild_id IS DISTINCT FROM $3"#,
+            &sticker_ids.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+            user_id as i64,
+            guild_id.map(|id| id as i64),
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        if external.is_empty() {
+            return Ok(());
+        }
+        if let Some(guild_id) = guild_id
+            && !permissions.contains(Permissions::USE_EXTERNAL_STICKERS)
+        {
+            return Err(Error::MissingPermissions {
+                guild_id,
+                permissions: Permissions::USE_EXTERNAL_STICKERS,
+                message: "You need the USE_EXTERNAL_STICKERS permission to use stickers from \
+                    other servers"
+                    .to_string(),
+            });
+        }
+
+        if let Some(sticker) = external.iter().find(|s| !s.is_member) {
+            return Err(Error::InvalidField {
+                field: "sticker_ids".to_string(),
+                message: format!(
+                    "You are not allowed to use the sticker with ID {}",
+                    sticker.id
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<'t, T> StickerDbExt<'t> for T where T: DbExt<'t> {}
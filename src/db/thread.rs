@@ -0,0 +1,260 @@
+use crate::{
+    db::{channel::query_channels, get_pool, ChannelDbExt, DbExt},
+    http::{channel::CreateThreadPayload, validate::Validate},
+    models::{GuildChannel, GuildChannelInfo, ThreadInfo, ThreadMember},
+    Error, NotFoundExt,
+};
+use chrono::Utc;
+
+#[async_trait::async_trait]
+pub trait ThreadDbExt<'t>: DbExt<'t> {
+    /// Fetches all active (non-archived) threads in a guild.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the threads.
+    async fn fetch_active_threads(&self, guild_id: u64) -> crate::Result<Vec<GuildChannel>> {
+        let threads = query_channels!(
+            "c.guild_id = $1 AND c.type = 'thread' AND t.archived_at IS NULL",
+            guild_id as i64
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        let thread_ids = threads.iter().map(|t| t.id).collect::<Vec<_>>();
+        let mut last_messages = self.fetch_last_message_map(&thread_ids).await?;
+
+        threads
+            .into_iter()
+            .map(|t| {
+                let last_message = last_messages.remove(&(t.id as u64));
+                t.into_guild_channel(Vec::new(), last_message)
+            })
+            .collect()
+    }
+
+    /// Fetches all members of a thread.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the thread members.
+    async fn fetch_thread_members(&self, thread_id: u64) -> crate::Result<Vec<ThreadMember>> {
+        Ok(sqlx::query!(
+            "SELECT user_id, joined_at FROM thread_members WHERE thread_id = $1",
+            thread_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| ThreadMember {
+            thread_id,
+            user_id: r.user_id as u64,
+            joined_at: r.joined_at,
+        })
+        .collect())
+    }
+
+    /// Creates a new thread within the text-based guild channel `parent_id`. The owner of the
+    /// thread automatically joins it.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid.
+    /// * If the parent channel is not found or is not a text-based channel in the guild.
+    /// * If the parent message is not found in the parent channel.
+    /// * If an error occurs with creating the thread.
+    async fn create_thread(
+        &mut self,
+        guild_id: u64,
+        parent_id: u64,
+        thread_id: u64,
+        owner_id: u64,
+        payload: CreateThreadPayload,
+    ) -> crate::Result<GuildChannel> {
+        payload.validate()?;
+
+        let parent = get_pool()
+            .inspect_channel(parent_id)
+            .await?
+            .ok_or_not_found("channel", format!("Channel with ID {parent_id} not found"))?;
+
+        if parent.guild_id != Some(guild_id) || !parent.channel_type.is_guild_text_based() {
+            return Err(Error::InvalidField {
+                field: "parent_id".to_string(),
+                message: "Threads can only be created in text-based guild channels".to_string(),
+            });
+        }
+
+        if let Some(message_id) = payload.parent_message_id {
+            sqlx::query!(
+                "SELECT 1 AS exists FROM messages
+                WHERE id = $1 AND channel_id = $2 AND deleted_at IS NULL",
+                message_id as i64,
+                parent_id as i64,
+            )
+            .fetch_optional(self.executor())
+            .await?
+            .ok_or_not_found(
+                "message",
+                format!("Message with ID {message_id} not found in channel {parent_id}"),
+            )?;
+        }
+
+        sqlx::query!(
+            "INSERT INTO channels (id, guild_id, type, name, position, parent_id)
+            VALUES ($1, $2, 'thread', $3, 0, $4)",
+            thread_id as i64,
+            guild_id as i64,
+            payload.name.trim(),
+            parent_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO threads (channel_id, creator_id, parent_message_id, auto_archive_duration)
+            VALUES ($1, $2, $3, $4)",
+            thread_id as i64,
+            owner_id as i64,
+            payload.parent_message_id.map(|id| id as i64),
+            payload.auto_archive_duration as i32,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO thread_members (thread_id, user_id) VALUES ($1, $2)",
+            thread_id as i64,
+            owner_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(GuildChannel {
+            id: thread_id,
+            guild_id,
+            info: GuildChannelInfo::Thread(ThreadInfo {
+                owner_id,
+                parent_message_id: payload.parent_message_id,
+                auto_archive_duration: payload.auto_archive_duration,
+                member_count: 1,
+                ..ThreadInfo::default()
+            }),
+            name: payload.name,
+            color: None,
+            icon: None,
+            position: 0,
+            parent_id: Some(parent_id),
+            overwrites: Vec::new(),
+        })
+    }
+
+    /// Adds a user to a thread. Returns `None` if the user is already a member of the thread.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with joining the thread.
+    async fn join_thread(
+        &mut self,
+        thread_id: u64,
+        user_id: u64,
+    ) -> crate::Result<Option<ThreadMember>> {
+        Ok(sqlx::query!(
+            "INSERT INTO thread_members (thread_id, user_id) VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            RETURNING joined_at",
+            thread_id as i64,
+            user_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .map(|r| ThreadMember {
+            thread_id,
+            user_id,
+            joined_at: r.joined_at,
+        }))
+    }
+
+    /// Removes a user from a thread. Returns whether the user was a member of the thread.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with leaving the thread.
+    async fn leave_thread(&mut self, thread_id: u64, user_id: u64) -> crate::Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM thread_members WHERE thread_id = $1 AND user_id = $2",
+            thread_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Archives or unarchives a thread.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the thread is not found.
+    /// * If an error occurs with archiving the thread.
+    async fn archive_thread(&mut self, thread_id: u64, archived: bool) -> crate::Result<()> {
+        let archived_at = archived.then(Utc::now);
+        let result = sqlx::query!(
+            "UPDATE threads SET archived_at = $1 WHERE channel_id = $2",
+            archived_at,
+            thread_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound {
+                entity: "thread".to_string(),
+                message: format!("Thread with ID {thread_id} not found"),
+            });
+        }
+        Ok(())
+    }
+
+    /// Archives all active threads in a guild that have been inactive for longer than their
+    /// auto-archive duration. Returns the IDs of the threads that were archived.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with archiving the threads.
+    async fn archive_inactive_threads(&mut self, guild_id: u64) -> crate::Result<Vec<u64>> {
+        Ok(sqlx::query!(
+            "UPDATE threads t SET archived_at = CURRENT_TIMESTAMP
+            FROM channels c
+            WHERE
+                c.id = t.channel_id
+                AND c.guild_id = $1
+                AND t.archived_at IS NULL
+                AND c.last_activity_at
+                    < CURRENT_TIMESTAMP - make_interval(mins => t.auto_archive_duration)
+            RETURNING t.channel_id",
+            guild_id as i64,
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| r.channel_id as u64)
+        .collect())
+    }
+}
+
+impl<'t, T> ThreadDbExt<'t> for T where T: DbExt<'t> {}
@@ -1,17 +1,25 @@
-use std::collections::HashMap;
-
-use super::DbExt;
+use super::{ChannelDbExt, DbExt, GuildDbExt};
 use crate::http::user::EditBotPayload;
+use crate::http::validate::Validate;
 use crate::{
+    cache,
     db::get_pool,
     error::UserInteractionType,
-    http::user::EditUserPayload,
+    http::user::{
+        CreateGuildFolderPayload, EditGuildFolderPayload, EditPrivacyPayload, EditUserPayload,
+        MoveGuildPayload,
+    },
     models::{
-        Bot, BotFlags, ClientUser, NotificationFlags, Permissions, PrivacyConfiguration,
-        Relationship, RelationshipType, Settings, User, UserFlags, UserOnboardingFlags,
+        Asset, Bot, BotFlags, ClientUser, GuildFlags, GuildFolder, GuildFolderInfo, MentionType,
+        ModelType, NotificationFlags, NotificationOverride, NotificationTarget, PartialGuild,
+        Permissions, PrivacyConfiguration, PrivacySettings, Relationship, RelationshipType,
+        Settings, User, UserFlags, UserNote, UserOnboardingFlags,
     },
+    snowflake::SnowflakeReader,
     Error, NotFoundExt,
 };
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
 macro_rules! construct_user {
     ($data:ident) => {{
@@ -19,9 +27,11 @@ macro_rules! construct_user {
             id: $data.id as _,
             username: $data.username,
             display_name: $data.display_name as _,
-            avatar: $data.avatar,
-            banner: $data.banner,
+            avatar: $data.avatar.map($crate::models::Asset::from_raw),
+            banner: $data.banner.map($crate::models::Asset::from_raw),
             bio: $data.bio,
+            pronouns: $data.pronouns,
+            accent_color: $data.accent_color.map(|color| color as u32),
             flags: UserFlags::from_bits_truncate($data.flags as _),
         }
     }};
@@ -54,20 +64,11 @@ macro_rules! fetch_client_user {
                 ),
                 onboarding_flags: UserOnboardingFlags::from_bits_truncate(r.onboarding_flags),
                 settings: Settings::from_bits_truncate(r.settings),
-                notification_override: HashMap::new(),
+                notification_override: Vec::new(),
             });
 
         if let Some(client) = result.as_mut() {
-            client.notification_override =
-                sqlx::query!(
-                    "SELECT target_id, notif_flags FROM notification_settings WHERE user_id = $1",
-                    client.id as i64,
-                )
-                .fetch_all($self.executor())
-                .await?
-                .into_iter()
-                .map(|r| (r.target_id as u64, NotificationFlags::from_bits_truncate(r.notif_flags)))
-                .collect();
+            client.notification_override = $self.fetch_notification_settings(client.id).await?;
         }
 
         Ok(result)
@@ -86,6 +87,8 @@ macro_rules! query_relationships {
                 u.avatar AS avatar,
                 u.banner AS banner,
                 u.bio AS bio,
+                u.pronouns AS pronouns,
+                u.accent_color AS accent_color,
                 u.flags AS flags,
                 r.type AS "kind: _"
             FROM
@@ -136,6 +139,8 @@ macro_rules! query_bots {
                 u.avatar AS avatar,
                 u.banner AS banner,
                 u.bio AS bio,
+                u.pronouns AS pronouns,
+                u.accent_color AS accent_color,
                 u.flags AS flags,
                 b.owner_id AS owner_id,
                 b.default_permissions AS default_permissions,
@@ -157,9 +162,11 @@ macro_rules! construct_bot {
                 id: $data.id as _,
                 username: $data.username,
                 display_name: $data.display_name,
-                avatar: $data.avatar,
-                banner: $data.banner,
+                avatar: $data.avatar.map($crate::models::Asset::from_raw),
+                banner: $data.banner.map($crate::models::Asset::from_raw),
                 bio: $data.bio,
+                pronouns: $data.pronouns,
+                accent_color: $data.accent_color.map(|color| color as u32),
                 flags: UserFlags::from_bits_truncate($data.flags as _),
             },
             owner_id: $data.owner_id as _,
@@ -169,7 +176,7 @@ macro_rules! construct_bot {
     }};
 }
 
-#[derive(Copy, Clone, sqlx::Type)]
+#[derive(Copy, Clone, Debug, sqlx::Type)]
 #[sqlx(type_name = "relationship_type")] // only for PostgreSQL to match a type definition
 #[sqlx(rename_all = "snake_case")]
 pub enum DbRelationshipType {
@@ -186,6 +193,8 @@ pub struct DbRelationship {
     pub avatar: Option<String>,
     pub banner: Option<String>,
     pub bio: Option<String>,
+    pub pronouns: Option<String>,
+    pub accent_color: Option<i32>,
     pub flags: i32,
     pub kind: DbRelationshipType,
 }
@@ -218,7 +227,7 @@ pub trait UserDbExt<'t>: DbExt<'t> {
     ///
     /// # Errors
     /// * If an error occurs with fetching the client user.
-    async fn fetch_client_user_by_id(&self, id: u64) -> sqlx::Result<Option<ClientUser>> {
+    async fn fetch_client_user_by_id(&self, id: u64) -> crate::Result<Option<ClientUser>> {
         fetch_client_user!(self, "id = $1", id as i64)
     }
 
@@ -263,7 +272,7 @@ pub trait UserDbExt<'t>: DbExt<'t> {
     async fn fetch_client_user_by_email(
         &self,
         email: impl AsRef<str> + Send,
-    ) -> sqlx::Result<Option<ClientUser>> {
+    ) -> crate::Result<Option<ClientUser>> {
         fetch_client_user!(self, "email = $1", email.as_ref())
     }
 
@@ -358,14 +367,14 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         Ok(())
     }
 
-    /// Edits a user in the database with the given payload. No validation is done, they must
-    /// be done before calling this method. Returns `(old_user, new_user)`.
+    /// Edits a user in the database with the given payload. Returns `(old_user, new_user)`.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
     ///
     /// # Errors
+    /// * If the payload is invalid.
     /// * If an error occurs with editing the user.
     /// * If the user is not found.
     /// * If the user is trying to change their username to one that is already taken.
@@ -374,6 +383,8 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         id: u64,
         payload: EditUserPayload,
     ) -> crate::Result<(User, User)> {
+        payload.validate()?;
+
         let mut user = get_pool()
             .fetch_user_by_id(id)
             .await?
@@ -387,25 +398,33 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         user.avatar = payload.avatar.into_option_or_if_absent(user.avatar);
         user.banner = payload.banner.into_option_or_if_absent(user.banner);
         user.bio = payload.bio.into_option_or_if_absent(user.bio);
+        user.pronouns = payload.pronouns.into_option_or_if_absent(user.pronouns);
+        user.accent_color = payload
+            .accent_color
+            .into_option_or_if_absent(user.accent_color);
 
         sqlx::query!(
             r#"UPDATE users
             SET
                 username = $1, display_name = $2,
-                avatar = $3, banner = $4, bio = $5
+                avatar = $3, banner = $4, bio = $5,
+                pronouns = $6, accent_color = $7
             WHERE
-                id = $6
+                id = $8
             "#,
             user.username,
             user.display_name,
-            user.avatar,
-            user.banner,
+            user.avatar.as_deref(),
+            user.banner.as_deref(),
             user.bio,
+            user.pronouns,
+            user.accent_color.map(|color| color as i32),
             id as i64,
         )
         .execute(self.transaction())
         .await?;
 
+        cache::remove_user(id).await?;
         Ok((old, user))
     }
 
@@ -455,6 +474,104 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         Ok(user_ids)
     }
 
+    /// Fetches which of the given users are observable by the user with the given ID, i.e. they
+    /// share a guild or have a relationship with the user. This is a cheaper alternative to
+    /// [`Self::fetch_observable_user_ids_for_user`] when only a few users need to be checked.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the observable users.
+    async fn fetch_observable_user_ids_among(
+        &self,
+        user_id: u64,
+        candidates: &[u64],
+    ) -> crate::Result<Vec<u64>> {
+        let user_ids = sqlx::query!(
+            r#"SELECT DISTINCT
+                id AS "id!"
+            FROM members
+            WHERE
+                id = ANY($2)
+                AND guild_id IN (SELECT guild_id FROM members WHERE id = $1)
+            UNION SELECT
+                target_id AS "id!"
+            FROM
+                relationships
+            WHERE
+                user_id = $1 AND target_id = ANY($2)
+            UNION SELECT $1 AS "id!" WHERE $1 = ANY($2)
+            "#,
+            user_id as i64,
+            &candidates.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.id as u64)
+        .collect();
+
+        Ok(user_ids)
+    }
+
+    /// Seeds the cached observable set of the user with the given ID using the full observable
+    /// users query. This should be called once when the user connects, after which the set is
+    /// kept up to date by [`Self::observe_users`] and [`Self::unobserve_users`].
+    ///
+    /// Returns the IDs of all observable users.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the observable users or caching them.
+    async fn seed_observable_users(&self, user_id: u64) -> crate::Result<Vec<u64>> {
+        let user_ids = self.fetch_observable_user_ids_for_user(user_id).await?;
+        cache::observable::cache_observable_users(user_id, &user_ids).await?;
+
+        Ok(user_ids)
+    }
+
+    /// Records that the given users may have become observable by the user with the given ID,
+    /// i.e. after joining a guild or adding a friend. This should be called after the change is
+    /// committed.
+    ///
+    /// Returns the IDs of the users that were not observable before, for which a
+    /// ``UserAvailable`` event should be dispatched. If the observable set of the user is not
+    /// cached, no events are needed since the user is not connected.
+    ///
+    /// # Errors
+    /// * If an error occurs with updating the cache.
+    async fn observe_users(&self, user_id: u64, candidates: &[u64]) -> crate::Result<Vec<u64>> {
+        Ok(cache::observable::add_observable_users(user_id, candidates)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Records that the given users may no longer be observable by the user with the given ID,
+    /// i.e. after leaving a guild or removing a friend. Since the users may still be observable
+    /// through another guild or relationship, only those that are no longer observable at all
+    /// are removed. This should be called after the change is committed.
+    ///
+    /// Returns the IDs of the users that are no longer observable, for which a
+    /// ``UserUnavailable`` event should be dispatched.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the observable users or updating the cache.
+    async fn unobserve_users(&self, user_id: u64, candidates: &[u64]) -> crate::Result<Vec<u64>> {
+        if !cache::observable::is_observable_cached(user_id).await? {
+            return Ok(Vec::new());
+        }
+
+        let still_observable = self
+            .fetch_observable_user_ids_among(user_id, candidates)
+            .await?;
+        let lost = candidates
+            .iter()
+            .copied()
+            .filter(|id| !still_observable.contains(id))
+            .collect::<Vec<_>>();
+
+        Ok(cache::observable::remove_observable_users(user_id, &lost)
+            .await?
+            .unwrap_or_default())
+    }
+
     /// Asserts that the user with the given ID has not blocked the user with the given ID. This
     /// returns an error if the is blocked by the user.
     ///
@@ -578,6 +695,110 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         }
     }
 
+    /// Asserts that the user with the given ID can interact with all of the given target users at
+    /// once, e.g. when adding them to a group DM. Unlike [`Self::assert_user_can_interact_with`],
+    /// this checks every target in a single query and reports all failures together.
+    ///
+    /// # Errors
+    /// * If any of the target users is the user itself.
+    /// * If any of the target users are not found.
+    /// * If any of the target users have blocked the user, or have privacy settings that
+    /// disallow the interaction. The error lists the IDs of every such user.
+    async fn assert_user_can_interact_with_all(
+        &self,
+        user_id: u64,
+        target_ids: &[u64],
+        interaction: UserInteractionType,
+    ) -> crate::Result<()> {
+        if target_ids.contains(&user_id) {
+            return Err(Error::CannotActOnSelf {
+                message: "You cannot act on yourself.".to_string(),
+            });
+        }
+
+        let records = sqlx::query!(
+            r#"SELECT
+                u.id,
+                u.dm_privacy,
+                u.group_dm_privacy,
+                u.friend_request_privacy,
+                r.type AS "relationship?: DbRelationshipType",
+                EXISTS(
+                    SELECT 1 FROM relationships a
+                    INNER JOIN relationships b ON a.target_id = b.target_id
+                    WHERE
+                        a.user_id = u.id AND a.type = 'friend'
+                        AND b.user_id = $1 AND b.type = 'friend'
+                ) AS "mutual_friends!",
+                EXISTS(
+                    SELECT 1 FROM members a
+                    INNER JOIN members b ON a.guild_id = b.guild_id
+                    WHERE a.id = u.id AND b.id = $1
+                ) AS "mutual_guilds!"
+            FROM
+                users u
+            LEFT JOIN
+                relationships r ON r.user_id = u.id AND r.target_id = $1
+            WHERE
+                u.id = ANY($2::BIGINT[])
+            "#,
+            user_id as i64,
+            &target_ids.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        if let Some(missing) = target_ids
+            .iter()
+            .find(|&&id| !records.iter().any(|r| r.id as u64 == id))
+        {
+            return Err(Error::NotFound {
+                entity: "user".to_string(),
+                message: format!("User with ID {missing} not found"),
+            });
+        }
+
+        let mut blocked_by = Vec::new();
+        let mut disallowed = Vec::new();
+        for r in records {
+            let relationship = r.relationship.map(RelationshipType::from);
+            if relationship == Some(RelationshipType::Blocked) {
+                blocked_by.push(r.id as u64);
+                continue;
+            }
+
+            let privacy = PrivacyConfiguration::from_bits_truncate(match interaction {
+                UserInteractionType::Dm => r.dm_privacy,
+                UserInteractionType::GroupDm => r.group_dm_privacy,
+                UserInteractionType::FriendRequest => r.friend_request_privacy,
+            });
+            let allowed = privacy.contains(PrivacyConfiguration::EVERYONE)
+                || interaction != UserInteractionType::FriendRequest
+                    && privacy.contains(PrivacyConfiguration::FRIENDS)
+                    && relationship == Some(RelationshipType::Friend)
+                || privacy.contains(PrivacyConfiguration::MUTUAL_FRIENDS) && r.mutual_friends
+                || privacy.contains(PrivacyConfiguration::GUILD_MEMBERS) && r.mutual_guilds;
+
+            if !allowed {
+                disallowed.push(r.id as u64);
+            }
+        }
+
+        if blocked_by.is_empty() && disallowed.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UserInteractionsDisallowed {
+                interaction_type: interaction,
+                blocked_by,
+                disallowed,
+                message: format!(
+                    "Some of the users you are trying to {} have blocked you or have privacy settings that prevent you from doing so.",
+                    interaction.as_verb(),
+                ),
+            })
+        }
+    }
+
     /// Fetches the relationship between two users.
     ///
     /// # Errors
@@ -641,6 +862,80 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         Ok(relationships)
     }
 
+    /// Fetches the users that are friends with both of the given users, ordered by ID.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the mutual friends.
+    async fn fetch_mutual_friends(&self, user_id: u64, target_id: u64) -> crate::Result<Vec<User>> {
+        let users = sqlx::query!(
+            r#"SELECT * FROM users
+            WHERE id IN (
+                SELECT target_id FROM relationships WHERE user_id = $1 AND type = 'friend'
+                INTERSECT
+                SELECT target_id FROM relationships WHERE user_id = $2 AND type = 'friend'
+            )
+            ORDER BY id"#,
+            user_id as i64,
+            target_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| construct_user!(r))
+        .collect();
+
+        Ok(users)
+    }
+
+    /// Fetches the guilds that both of the given users are members of, ordered by ID.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the mutual guilds.
+    async fn fetch_mutual_guilds(
+        &self,
+        user_id: u64,
+        target_id: u64,
+    ) -> crate::Result<Vec<PartialGuild>> {
+        let guilds = sqlx::query!(
+            r#"SELECT
+                id,
+                name,
+                description,
+                icon,
+                banner,
+                owner_id,
+                flags,
+                vanity_url
+            FROM
+                guilds
+            WHERE id IN (
+                SELECT guild_id FROM members WHERE id = $1
+                INTERSECT
+                SELECT guild_id FROM members WHERE id = $2
+            )
+            ORDER BY id"#,
+            user_id as i64,
+            target_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| PartialGuild {
+            id: r.id as _,
+            name: r.name,
+            description: r.description,
+            icon: r.icon.map(Asset::from_raw),
+            banner: r.banner.map(Asset::from_raw),
+            owner_id: r.owner_id as _,
+            flags: GuildFlags::from_bits_truncate(r.flags as _),
+            member_count: None,
+            vanity_url: r.vanity_url,
+        })
+        .collect();
+
+        Ok(guilds)
+    }
+
     /// Registers a one-way relationship between two users. This is used internally.
     async fn register_one_way_relationship(
         &mut self,
@@ -677,6 +972,8 @@ pub trait UserDbExt<'t>: DbExt<'t> {
                 u.avatar AS avatar,
                 u.banner AS banner,
                 u.bio AS bio,
+                u.pronouns AS pronouns,
+                u.accent_color AS accent_color,
                 u.flags AS flags,
                 updated.type AS "kind: _"
             FROM
@@ -734,6 +1031,7 @@ pub trait UserDbExt<'t>: DbExt<'t> {
             .register_one_way_relationship(target_id, user_id, target_kind)
             .await?;
 
+        cache::blocked::invalidate_blocked_ids(&[user_id, target_id]).await?;
         Ok((relationship, external_relationship))
     }
 
@@ -747,7 +1045,7 @@ pub trait UserDbExt<'t>: DbExt<'t> {
     /// * If an error occurs with deleting the relationship.
     /// * If the relationship doesn't exist.
     async fn delete_relationship(&mut self, user_id: u64, target_id: u64) -> crate::Result<u64> {
-        Ok(sqlx::query!(
+        let deleted = sqlx::query!(
             r#"DELETE FROM
                 relationships
             WHERE
@@ -760,7 +1058,35 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         )
         .execute(self.transaction())
         .await?
-        .rows_affected())
+        .rows_affected();
+
+        cache::blocked::invalidate_blocked_ids(&[user_id, target_id]).await?;
+        Ok(deleted)
+    }
+
+    /// Fetches the IDs of the users blocked by the given user, in ascending order. This is cached.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the blocked users.
+    async fn fetch_blocked_ids(&self, user_id: u64) -> crate::Result<Vec<u64>> {
+        if let Some(blocked_ids) = cache::blocked::blocked_ids(user_id).await? {
+            return Ok(blocked_ids);
+        }
+
+        let blocked_ids = sqlx::query!(
+            "SELECT target_id FROM relationships
+            WHERE user_id = $1 AND type = 'blocked'
+            ORDER BY target_id",
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.target_id as u64)
+        .collect::<Vec<_>>();
+
+        cache::blocked::cache_blocked_ids(user_id, blocked_ids.clone()).await?;
+        Ok(blocked_ids)
     }
 
     async fn fetch_user_settings(&self, user_id: u64) -> crate::Result<Settings> {
@@ -788,60 +1114,293 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         Ok(())
     }
 
+    /// Updates the privacy configurations of the given user. Configurations set to `null`, or
+    /// stored with unknown bits, are reset to their defaults. Returns the updated privacy
+    /// configurations.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user does not exist.
+    /// * If ``friend_request_privacy`` includes ``FRIENDS``.
+    /// * If an error occurs with updating the privacy configurations.
+    async fn update_privacy_configuration(
+        &mut self,
+        user_id: u64,
+        payload: EditPrivacyPayload,
+    ) -> crate::Result<PrivacySettings> {
+        payload.validate()?;
+
+        let current = sqlx::query!(
+            "SELECT dm_privacy, group_dm_privacy, friend_request_privacy FROM users WHERE id = $1",
+            user_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("user", "user not found")?;
+
+        let privacy = PrivacySettings {
+            dm_privacy: payload
+                .dm_privacy
+                .into_option_or_if_absent(PrivacyConfiguration::from_bits(current.dm_privacy))
+                .unwrap_or(PrivacyConfiguration::DEFAULT_DM_PRIVACY),
+            group_dm_privacy: payload
+                .group_dm_privacy
+                .into_option_or_if_absent(PrivacyConfiguration::from_bits(current.group_dm_privacy))
+                .unwrap_or(PrivacyConfiguration::DEFAULT_GROUP_DM_PRIVACY),
+            friend_request_privacy: payload
+                .friend_request_privacy
+                .into_option_or_if_absent(PrivacyConfiguration::from_bits(
+                    current.friend_request_privacy,
+                ))
+                .unwrap_or(PrivacyConfiguration::DEFAULT_FRIEND_REQUEST_PRIVACY),
+        };
+
+        sqlx::query!(
+            r#"UPDATE users
+            SET dm_privacy = $1, group_dm_privacy = $2, friend_request_privacy = $3
+            WHERE id = $4"#,
+            privacy.dm_privacy.bits(),
+            privacy.group_dm_privacy.bits(),
+            privacy.friend_request_privacy.bits(),
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(privacy)
+    }
+
+    /// Fetches all private notes the given user keeps on other users.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the notes.
+    async fn fetch_user_notes(&self, user_id: u64) -> crate::Result<Vec<UserNote>> {
+        let notes = sqlx::query!(
+            "SELECT target_id, note FROM notes WHERE user_id = $1 ORDER BY target_id",
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| UserNote {
+            user_id: r.target_id as u64,
+            note: r.note,
+        })
+        .collect();
+
+        Ok(notes)
+    }
+
+    /// Sets the private note the given user keeps on the target user. If the note is `None` or
+    /// empty, the note is removed. Returns the note after the change.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the note is too long.
+    /// * If the target user is not found.
+    /// * If an error occurs with setting the note.
+    async fn set_user_note(
+        &mut self,
+        user_id: u64,
+        target_id: u64,
+        note: Option<&str>,
+    ) -> crate::Result<Option<String>> {
+        let note = note.map(str::trim).filter(|note| !note.is_empty());
+        let Some(note) = note else {
+            sqlx::query!(
+                "DELETE FROM notes WHERE user_id = $1 AND target_id = $2",
+                user_id as i64,
+                target_id as i64,
+            )
+            .execute(self.transaction())
+            .await?;
+
+            return Ok(None);
+        };
+
+        let max_length = crate::limits().max_user_note_length;
+        if note.chars().count() > max_length {
+            return Err(Error::InvalidField {
+                field: "note".to_string(),
+                message: format!("Note must be at most {max_length} characters long"),
+            });
+        }
+
+        let inserted = sqlx::query!(
+            "INSERT INTO notes (user_id, target_id, note)
+            SELECT $1, id, $3 FROM users WHERE id = $2
+            ON CONFLICT (user_id, target_id) DO UPDATE SET note = EXCLUDED.note",
+            user_id as i64,
+            target_id as i64,
+            note,
+        )
+        .execute(self.transaction())
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(Error::NotFound {
+                entity: "user".to_string(),
+                message: format!("User with ID {target_id} not found"),
+            });
+        }
+        Ok(Some(note.to_string()))
+    }
+
     async fn fetch_notification_settings(
         &self,
         user_id: u64,
-    ) -> crate::Result<HashMap<u64, NotificationFlags>> {
-        Ok(sqlx::query!(
-            "SELECT target_id, notif_flags FROM notification_settings WHERE user_id = $1",
+    ) -> crate::Result<Vec<NotificationOverride>> {
+        sqlx::query!(
+            "SELECT target_type, target_id, notif_flags, mute_until
+            FROM notification_settings WHERE user_id = $1",
             user_id as i64
         )
         .fetch_all(self.executor())
         .await?
         .into_iter()
         .map(|r| {
-            (
-                r.target_id as u64,
-                NotificationFlags::from_bits_truncate(r.notif_flags),
-            )
+            Ok(NotificationOverride {
+                target: NotificationTarget::from_db(&r.target_type, r.target_id)?,
+                flags: NotificationFlags::from_bits_truncate(r.notif_flags),
+                mute_until: r.mute_until,
+            })
         })
-        .collect())
+        .collect()
     }
 
     async fn fetch_notification_settings_in_target(
         &self,
         user_id: u64,
-        target_id: u64,
+        target: NotificationTarget,
     ) -> crate::Result<Option<NotificationFlags>> {
         Ok(sqlx::query!(
-            "SELECT notif_flags FROM notification_settings WHERE user_id = $1 AND target_id = $2",
+            "SELECT notif_flags FROM notification_settings
+            WHERE user_id = $1 AND target_id = $2 AND target_type = $3",
             user_id as i64,
-            target_id as i64
+            target.id() as i64,
+            target.name(),
         )
         .fetch_optional(self.executor())
         .await?
         .map(|r| NotificationFlags::from_bits_truncate(r.notif_flags)))
     }
 
+    /// Fetches the notification targets that apply to the given channel, ordered from most to
+    /// least specific: the channel itself, then for threads the parent channel, then the category
+    /// of the channel, and finally its guild.
+    ///
+    /// Returns an empty list if the channel is not found.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the channel.
+    async fn fetch_notification_targets(
+        &self,
+        channel_id: u64,
+    ) -> crate::Result<Vec<NotificationTarget>> {
+        let Some(channel) = sqlx::query!(
+            r#"SELECT
+                c.guild_id,
+                c.type AS kind,
+                c.parent_id,
+                p.parent_id AS "grandparent_id?"
+            FROM
+                channels c
+            LEFT JOIN
+                channels p ON p.id = c.parent_id
+            WHERE
+                c.id = $1
+            "#,
+            channel_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        else {
+            return Ok(Vec::new());
+        };
+
+        let parent_id = channel.parent_id.map(|id| id as u64);
+        let mut targets = vec![NotificationTarget::Channel(channel_id)];
+        if channel.kind == "thread" {
+            targets.extend(parent_id.map(NotificationTarget::Channel));
+            targets.extend(
+                channel
+                    .grandparent_id
+                    .map(|id| NotificationTarget::Category(id as u64)),
+            );
+        } else {
+            targets.extend(parent_id.map(NotificationTarget::Category));
+        }
+        targets.extend(
+            channel
+                .guild_id
+                .map(|id| NotificationTarget::Guild(id as u64)),
+        );
+
+        Ok(targets)
+    }
+
+    /// Resolves the notification flags the user has set for the given channel. Overrides cascade
+    /// from the guild to the category of the channel and then the channel itself, with the most
+    /// specific override taking precedence. Threads additionally inherit the overrides of their
+    /// parent channel.
+    ///
+    /// Returns `None` if no override applies to the channel.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the channel or the overrides.
+    async fn resolve_notification_settings(
+        &self,
+        user_id: u64,
+        channel_id: u64,
+    ) -> crate::Result<Option<NotificationFlags>> {
+        let targets = self.fetch_notification_targets(channel_id).await?;
+        if targets.is_empty() {
+            return Ok(None);
+        }
+
+        let overrides = self.fetch_notification_settings(user_id).await?;
+        Ok(NotificationOverride::resolve(&overrides, &targets))
+    }
+
+    /// Sets the notification override of the user for the given target. If ``mute_until`` is
+    /// provided, the target is muted until that time.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with updating the override.
     async fn update_notification_settings(
         &mut self,
         user_id: u64,
-        target_id: u64,
+        target: NotificationTarget,
         flags: NotificationFlags,
+        mute_until: Option<DateTime<Utc>>,
     ) -> crate::Result<()> {
         sqlx::query!(
-            r#"INSERT INTO 
-                notification_settings 
-            VALUES 
-                ($1, $2, $3) 
-            ON CONFLICT 
-                (user_id, target_id) 
-            DO UPDATE SET 
-                notif_flags = $3
+            r#"INSERT INTO
+                notification_settings (user_id, target_id, target_type, notif_flags, mute_until)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            ON CONFLICT
+                (user_id, target_type, target_id)
+            DO UPDATE SET
+                notif_flags = $4,
+                mute_until = $5
             "#,
             user_id as i64,
-            target_id as i64,
-            flags.bits()
+            target.id() as i64,
+            target.name(),
+            flags.bits(),
+            mute_until,
         )
         .execute(self.transaction())
         .await?;
@@ -849,15 +1408,49 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         Ok(())
     }
 
+    /// Fetches and clears all notification mutes that have expired. Returns the IDs of the users
+    /// along with their updated overrides, so that clients can be notified that the mutes were
+    /// lifted. This should be called periodically.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If an error occurs with clearing the mutes.
+    async fn fetch_expired_mutes(&mut self) -> crate::Result<Vec<(u64, NotificationOverride)>> {
+        sqlx::query!(
+            "UPDATE notification_settings SET mute_until = NULL
+            WHERE mute_until IS NOT NULL AND mute_until <= NOW()
+            RETURNING user_id, target_type, target_id, notif_flags",
+        )
+        .fetch_all(self.transaction())
+        .await?
+        .into_iter()
+        .map(|r| {
+            Ok((
+                r.user_id as u64,
+                NotificationOverride {
+                    target: NotificationTarget::from_db(&r.target_type, r.target_id)?,
+                    flags: NotificationFlags::from_bits_truncate(r.notif_flags),
+                    mute_until: None,
+                },
+            ))
+        })
+        .collect()
+    }
+
     async fn remove_notification_settings(
         &mut self,
         user_id: u64,
-        target_id: u64,
+        target: NotificationTarget,
     ) -> crate::Result<()> {
         sqlx::query!(
-            "DELETE FROM notification_settings WHERE user_id = $1 AND target_id = $2",
+            "DELETE FROM notification_settings
+            WHERE user_id = $1 AND target_id = $2 AND target_type = $3",
             user_id as i64,
-            target_id as i64
+            target.id() as i64,
+            target.name(),
         )
         .execute(self.transaction())
         .await?;
@@ -865,14 +1458,46 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         Ok(())
     }
 
-    async fn can_push(&self, user_id: u64, _target_id: Option<u64>) -> crate::Result<bool> {
+    /// Returns whether a push notification should be sent to the user for a message in the
+    /// channel with the given ID, if any, that mentions the user as described by ``mention``.
+    ///
+    /// Notifications are never pushed if they are disabled globally, or if the channel, its
+    /// category, or its guild is currently muted. Otherwise, the most specific override for the
+    /// channel decides, falling back to the override of its category and then its guild. If no
+    /// override applies, [`NotificationFlags::default_for`] is used.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the settings of the user.
+    async fn can_push(
+        &self,
+        user_id: u64,
+        target_id: Option<u64>,
+        mention: MentionType,
+    ) -> crate::Result<bool> {
         let enabled = self
             .fetch_user_settings(user_id)
             .await?
             .contains(Settings::NOTIFICATIONS);
-        // TODO: Check override and target.
+        if !enabled {
+            return Ok(false);
+        }
+
+        let Some(channel_id) = target_id else {
+            return Ok(true);
+        };
+        let targets = self.fetch_notification_targets(channel_id).await?;
+        let overrides = self.fetch_notification_settings(user_id).await?;
+        if NotificationOverride::resolve_muted(&overrides, &targets) {
+            return Ok(false);
+        }
 
-        Ok(enabled)
+        let in_guild = targets
+            .iter()
+            .any(|target| matches!(target, NotificationTarget::Guild(_)));
+        let flags = NotificationOverride::resolve(&overrides, &targets)
+            .unwrap_or_else(|| NotificationFlags::default_for(in_guild));
+
+        Ok(flags.allows(mention))
     }
 
     /// Registers a new bot account with the given payload.
@@ -942,13 +1567,18 @@ pub trait UserDbExt<'t>: DbExt<'t> {
         Ok(bots)
     }
 
-    /// Modifies a bot in the database with the given payload. Validation must be done before
-    /// calling this method.
+    /// Modifies a bot in the database with the given payload.
     ///
     /// # Note
     /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
     /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the payload is invalid.
+    /// * If an error occurs with modifying the bot.
     async fn edit_bot(&mut self, user: User, payload: EditBotPayload) -> crate::Result<Bot> {
+        payload.validate()?;
+
         let bot = sqlx::query!("SELECT * FROM bots WHERE user_id = $1", user.id as i64)
             .fetch_one(self.transaction())
             .await?;
@@ -1024,6 +1654,495 @@ pub trait UserDbExt<'t>: DbExt<'t> {
 
         Ok(())
     }
+
+    /// Asserts the given channel or guild is observable by the user: the user must be a member
+    /// of the guild, able to view the guild channel, or a recipient of the DM channel.
+    ///
+    /// # Errors
+    /// * If the ID is not the ID of a channel or guild.
+    /// * If the channel or guild does not exist or is not observable by the user.
+    async fn assert_favorite_observable(&self, user_id: u64, target_id: u64) -> crate::Result<()> {
+        match SnowflakeReader::new(target_id).model_type() {
+            ModelType::Guild => self.assert_member_in_guild(target_id, user_id).await,
+            ModelType::Channel => {
+                let channel = self
+                    .inspect_channel(target_id)
+                    .await?
+                    .ok_or_not_found("channel", format!("Channel with ID {target_id} not found"))?;
+
+                match channel.guild_id {
+                    Some(guild_id) => {
+                        self.assert_member_has_permissions(
+                            guild_id,
+                            user_id,
+                            Some(target_id),
+                            Permissions::VIEW_CHANNEL,
+                        )
+                        .await
+                    }
+                    None => self.assert_user_is_recipient(target_id, user_id).await,
+                }
+            }
+            _ => Err(Error::InvalidField {
+                field: "target_id".to_string(),
+                message: "Only channels and guilds can be favorited".to_string(),
+            }),
+        }
+    }
+
+    /// Fetches the IDs of the channels and guilds favorited by the given user, in order.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the favorites.
+    async fn fetch_favorites(&self, user_id: u64) -> crate::Result<Vec<u64>> {
+        Ok(sqlx::query!(
+            "SELECT target_id FROM favorites WHERE user_id = $1 ORDER BY position",
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.target_id as u64)
+        .collect())
+    }
+
+    /// Adds the given channel or guild to the end of the favorites of the user. Returns the
+    /// updated favorites.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the channel or guild is not observable by the user.
+    /// * If the channel or guild is already favorited.
+    /// * If the user has reached the maximum number of favorites.
+    /// * If an error occurs with adding the favorite.
+    async fn add_favorite(&mut self, user_id: u64, target_id: u64) -> crate::Result<Vec<u64>> {
+        get_pool()
+            .assert_favorite_observable(user_id, target_id)
+            .await?;
+
+        let mut favorites = get_pool().fetch_favorites(user_id).await?;
+        if favorites.contains(&target_id) {
+            return Err(Error::AlreadyExists {
+                what: "favorite".to_string(),
+                message: "This channel or guild is already favorited".to_string(),
+            });
+        }
+        let max = crate::limits().max_favorites;
+        if favorites.len() >= max {
+            return Err(Error::InvalidField {
+                field: "target_id".to_string(),
+                message: format!("You can have at most {max} favorites"),
+            });
+        }
+
+        sqlx::query!(
+            "INSERT INTO favorites (user_id, target_id, position) VALUES ($1, $2, $3)",
+            user_id as i64,
+            target_id as i64,
+            favorites.len() as i16,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        favorites.push(target_id);
+        Ok(favorites)
+    }
+
+    /// Removes the given channel or guild from the favorites of the user. Returns the updated
+    /// favorites.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the channel or guild is not favorited.
+    /// * If an error occurs with removing the favorite.
+    async fn remove_favorite(&mut self, user_id: u64, target_id: u64) -> crate::Result<Vec<u64>> {
+        let position = sqlx::query!(
+            "DELETE FROM favorites WHERE user_id = $1 AND target_id = $2 RETURNING position",
+            user_id as i64,
+            target_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("favorite", "This channel or guild is not favorited")?
+        .position;
+
+        sqlx::query!(
+            "UPDATE favorites SET position = position - 1 WHERE user_id = $1 AND position > $2",
+            user_id as i64,
+            position,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        let mut favorites = get_pool().fetch_favorites(user_id).await?;
+        favorites.retain(|&id| id != target_id);
+        Ok(favorites)
+    }
+
+    /// Reorders the favorites of the user. The given IDs must be exactly the current favorites
+    /// of the user, in the new order.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the given IDs are not exactly the current favorites of the user.
+    /// * If an error occurs with reordering the favorites.
+    async fn reorder_favorites(&mut self, user_id: u64, target_ids: Vec<u64>) -> crate::Result<()> {
+        let mut current = get_pool().fetch_favorites(user_id).await?;
+        let mut sorted = target_ids.clone();
+        current.sort_unstable();
+        sorted.sort_unstable();
+
+        if current != sorted {
+            return Err(Error::InvalidField {
+                field: "target_ids".to_string(),
+                message: "Target IDs must contain every favorite exactly once".to_string(),
+            });
+        }
+
+        sqlx::query!(
+            r#"UPDATE favorites f SET position = o.position - 1
+            FROM UNNEST($2::BIGINT[]) WITH ORDINALITY AS o(target_id, position)
+            WHERE f.user_id = $1 AND f.target_id = o.target_id"#,
+            user_id as i64,
+            &target_ids.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fetches the guild folders of the given user as a flattened tree. The root folder is
+    /// always first, followed by every folder in depth-first order. Guilds the user has not
+    /// placed in any folder are included at the bottom of the root folder, in the order they
+    /// were joined.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the folders.
+    async fn fetch_guild_folders(&self, user_id: u64) -> crate::Result<Vec<GuildFolder>> {
+        let folders = sqlx::query!(
+            "SELECT id, parent_id, name, color FROM guild_folders WHERE user_id = $1 ORDER BY position",
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        let entries = sqlx::query!(
+            r#"SELECT
+                m.guild_id,
+                e.folder_id AS "folder_id?"
+            FROM
+                members m
+            LEFT JOIN
+                guild_folder_entries e ON e.user_id = m.id AND e.guild_id = m.guild_id
+            WHERE
+                m.id = $1
+            ORDER BY
+                e.position NULLS LAST, m.joined_at"#,
+            user_id as i64,
+        )
+        .fetch_all(self.executor())
+        .await?;
+
+        let mut guilds = HashMap::<Option<u64>, Vec<u64>>::new();
+        for entry in entries {
+            guilds
+                .entry(entry.folder_id.map(|id| id as u64))
+                .or_default()
+                .push(entry.guild_id as u64);
+        }
+
+        let mut children = HashMap::<Option<u64>, Vec<(u64, GuildFolderInfo)>>::new();
+        for folder in folders {
+            children
+                .entry(folder.parent_id.map(|id| id as u64))
+                .or_default()
+                .push((
+                    folder.id as u64,
+                    GuildFolderInfo {
+                        name: folder.name,
+                        color: folder.color as u32,
+                    },
+                ));
+        }
+
+        let mut tree = vec![GuildFolder {
+            id: None,
+            path: None,
+            guilds: guilds.remove(&None).unwrap_or_default(),
+        }];
+        let mut stack = children
+            .remove(&None)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .map(|(id, info)| (id, vec![info]))
+            .collect::<Vec<_>>();
+
+        while let Some((id, path)) = stack.pop() {
+            if let Some(nested) = children.remove(&Some(id)) {
+                stack.extend(nested.into_iter().rev().map(|(child_id, info)| {
+                    let mut child_path = path.clone();
+                    child_path.push(info);
+                    (child_id, child_path)
+                }));
+            }
+            tree.push(GuildFolder {
+                id: Some(id),
+                path: Some(path),
+                guilds: guilds.remove(&Some(id)).unwrap_or_default(),
+            });
+        }
+
+        Ok(tree)
+    }
+
+    /// Creates a guild folder for the user at the bottom of its parent folder.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the name or color is invalid.
+    /// * If the parent folder does not exist or does not belong to the user.
+    /// * If the user has reached the maximum number of folders.
+    /// * If an error occurs with creating the folder.
+    async fn create_guild_folder(
+        &mut self,
+        folder_id: u64,
+        user_id: u64,
+        payload: CreateGuildFolderPayload,
+    ) -> crate::Result<GuildFolder> {
+        payload.validate()?;
+        let name = payload.name.trim();
+
+        let folders = get_pool().fetch_guild_folders(user_id).await?;
+        let max = crate::limits().max_guild_folders;
+        if folders.len() > max {
+            return Err(Error::InvalidField {
+                field: "parent_id".to_string(),
+                message: format!("You can have at most {max} guild folders"),
+            });
+        }
+
+        let mut path = match payload.parent_id {
+            Some(parent_id) => folders
+                .into_iter()
+                .find(|folder| folder.id == Some(parent_id))
+                .and_then(|folder| folder.path)
+                .ok_or_not_found("folder", format!("Folder with ID {parent_id} not found"))?,
+            None => Vec::new(),
+        };
+
+        sqlx::query!(
+            r#"INSERT INTO
+                guild_folders (id, user_id, parent_id, name, color, position)
+            VALUES
+                ($1, $2, $3, $4, $5, (
+                    SELECT COALESCE(MAX(position) + 1, 0) FROM guild_folders
+                    WHERE user_id = $2 AND parent_id IS NOT DISTINCT FROM $3
+                ))"#,
+            folder_id as i64,
+            user_id as i64,
+            payload.parent_id.map(|id| id as i64),
+            name,
+            payload.color as i32,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        path.push(GuildFolderInfo {
+            name: name.to_string(),
+            color: payload.color,
+        });
+        Ok(GuildFolder {
+            id: Some(folder_id),
+            path: Some(path),
+            guilds: Vec::new(),
+        })
+    }
+
+    /// Renames or recolors a guild folder of the user.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the name or color is invalid.
+    /// * If the folder does not exist or does not belong to the user.
+    /// * If an error occurs with editing the folder.
+    async fn edit_guild_folder(
+        &mut self,
+        user_id: u64,
+        folder_id: u64,
+        payload: EditGuildFolderPayload,
+    ) -> crate::Result<GuildFolderInfo> {
+        payload.validate()?;
+        let name = payload.name.as_deref().map(str::trim);
+
+        let folder = sqlx::query!(
+            r#"UPDATE guild_folders
+            SET name = COALESCE($3, name), color = COALESCE($4, color)
+            WHERE id = $1 AND user_id = $2
+            RETURNING name, color"#,
+            folder_id as i64,
+            user_id as i64,
+            name,
+            payload.color.map(|color| color as i32),
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("folder", format!("Folder with ID {folder_id} not found"))?;
+
+        Ok(GuildFolderInfo {
+            name: folder.name,
+            color: folder.color as u32,
+        })
+    }
+
+    /// Deletes a guild folder of the user along with all folders nested in it. Guilds in the
+    /// deleted folders are moved to the bottom of the root folder.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the folder does not exist or does not belong to the user.
+    /// * If an error occurs with deleting the folder.
+    async fn delete_guild_folder(&mut self, user_id: u64, folder_id: u64) -> crate::Result<()> {
+        sqlx::query!(
+            r#"WITH RECURSIVE nested AS (
+                SELECT id FROM guild_folders WHERE id = $1 AND user_id = $2
+                UNION
+                SELECT f.id FROM guild_folders f INNER JOIN nested n ON f.parent_id = n.id
+            ),
+            moved AS (
+                SELECT
+                    guild_id,
+                    ROW_NUMBER() OVER (ORDER BY position) AS rank
+                FROM
+                    guild_folder_entries
+                WHERE
+                    user_id = $2 AND folder_id IN (SELECT id FROM nested)
+            )
+            UPDATE guild_folder_entries e
+            SET
+                folder_id = NULL,
+                position = (
+                    SELECT COALESCE(MAX(position), -1) FROM guild_folder_entries
+                    WHERE user_id = $2 AND folder_id IS NULL
+                ) + moved.rank
+            FROM
+                moved
+            WHERE
+                e.user_id = $2 AND e.guild_id = moved.guild_id"#,
+            folder_id as i64,
+            user_id as i64,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM guild_folders WHERE id = $1 AND user_id = $2 RETURNING id",
+            folder_id as i64,
+            user_id as i64,
+        )
+        .fetch_optional(self.transaction())
+        .await?
+        .ok_or_not_found("folder", format!("Folder with ID {folder_id} not found"))?;
+
+        Ok(())
+    }
+
+    /// Moves a guild of the user into the given folder.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the user is not a member of the guild.
+    /// * If the folder does not exist or does not belong to the user.
+    /// * If an error occurs with moving the guild.
+    async fn move_guild_to_folder(
+        &mut self,
+        user_id: u64,
+        guild_id: u64,
+        payload: MoveGuildPayload,
+    ) -> crate::Result<()> {
+        get_pool().assert_member_in_guild(guild_id, user_id).await?;
+        let folder_id = payload.folder_id.map(|id| id as i64);
+
+        if let Some(folder_id) = payload.folder_id {
+            sqlx::query!(
+                "SELECT id FROM guild_folders WHERE id = $1 AND user_id = $2",
+                folder_id as i64,
+                user_id as i64,
+            )
+            .fetch_optional(self.executor())
+            .await?
+            .ok_or_not_found("folder", format!("Folder with ID {folder_id} not found"))?;
+        }
+
+        let mut guild_ids = sqlx::query!(
+            r#"SELECT
+                m.guild_id
+            FROM
+                members m
+            LEFT JOIN
+                guild_folder_entries e ON e.user_id = m.id AND e.guild_id = m.guild_id
+            WHERE
+                m.id = $1 AND m.guild_id != $2 AND e.folder_id IS NOT DISTINCT FROM $3
+            ORDER BY
+                e.position NULLS LAST, m.joined_at"#,
+            user_id as i64,
+            guild_id as i64,
+            folder_id,
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| r.guild_id)
+        .collect::<Vec<_>>();
+
+        let position = payload.position.map_or(guild_ids.len(), |position| {
+            (position as usize).min(guild_ids.len())
+        });
+        guild_ids.insert(position, guild_id as i64);
+
+        sqlx::query!(
+            r#"INSERT INTO
+                guild_folder_entries (user_id, guild_id, folder_id, position)
+            SELECT
+                $1, o.guild_id, $3, o.position - 1
+            FROM
+                UNNEST($2::BIGINT[]) WITH ORDINALITY AS o(guild_id, position)
+            ON CONFLICT
+                (user_id, guild_id)
+            DO UPDATE SET
+                folder_id = EXCLUDED.folder_id,
+                position = EXCLUDED.position"#,
+            user_id as i64,
+            &guild_ids,
+            folder_id,
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok(())
+    }
 }
 
 impl<'t, T> UserDbExt<'t> for T where T: DbExt<'t> {}
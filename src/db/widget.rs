@@ -0,0 +1,170 @@
+use crate::{
+    cache,
+    db::{get_pool, ChannelDbExt, DbExt},
+    http::guild::EditGuildWidgetPayload,
+    models::{Asset, GuildWidget, GuildWidgetSettings, PresenceStatus, WidgetMember},
+    Error, NotFoundExt,
+};
+use std::collections::HashMap;
+
+/// The maximum number of online members included in a guild widget.
+pub const WIDGET_MEMBER_SAMPLE_SIZE: usize = 100;
+
+#[async_trait::async_trait]
+pub trait GuildWidgetDbExt<'t>: DbExt<'t> {
+    /// Fetches the widget settings of the given guild. Guilds that have not configured their
+    /// widget have it disabled.
+    ///
+    /// # Errors
+    /// * If an error occurs with fetching the settings.
+    async fn fetch_guild_widget_settings(
+        &self,
+        guild_id: u64,
+    ) -> crate::Result<GuildWidgetSettings> {
+        Ok(sqlx::query!(
+            "SELECT enabled, channel_id FROM guild_widgets WHERE guild_id = $1",
+            guild_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .map_or(
+            GuildWidgetSettings {
+                guild_id,
+                enabled: false,
+                channel_id: None,
+            },
+            |r| GuildWidgetSettings {
+                guild_id,
+                enabled: r.enabled,
+                channel_id: r.channel_id.map(|id| id as u64),
+            },
+        ))
+    }
+
+    /// Edits the widget settings of the given guild. Returns a tuple ``(before, after)``.
+    ///
+    /// # Note
+    /// This method uses transactions, on the event of an ``Err`` the transaction must be properly
+    /// rolled back, and the transaction must be committed to save the changes.
+    ///
+    /// # Errors
+    /// * If the channel is not a channel in the guild.
+    /// * If an error occurs with editing the settings.
+    async fn edit_guild_widget_settings(
+        &mut self,
+        guild_id: u64,
+        payload: EditGuildWidgetPayload,
+    ) -> crate::Result<(GuildWidgetSettings, GuildWidgetSettings)> {
+        let before = get_pool().fetch_guild_widget_settings(guild_id).await?;
+        let mut after = before.clone();
+
+        after.enabled = payload.enabled.unwrap_or(after.enabled);
+        after.channel_id = payload
+            .channel_id
+            .into_option_or_if_absent(after.channel_id);
+        if let Some(channel_id) = after.channel_id {
+            let channel = get_pool()
+                .inspect_channel(channel_id)
+                .await?
+                .ok_or_not_found("channel", format!("Channel with ID {channel_id} not found"))?;
+
+            if channel.guild_id != Some(guild_id) {
+                return Err(Error::InvalidField {
+                    field: "channel_id".to_string(),
+                    message: "The widget channel must be a channel in the guild".to_string(),
+                });
+            }
+        }
+
+        sqlx::query!(
+            "INSERT INTO guild_widgets (guild_id, enabled, channel_id) VALUES ($1, $2, $3)
+            ON CONFLICT (guild_id) DO UPDATE SET enabled = $2, channel_id = $3",
+            guild_id as i64,
+            after.enabled,
+            after.channel_id.map(|id| id as i64),
+        )
+        .execute(self.transaction())
+        .await?;
+
+        Ok((before, after))
+    }
+
+    /// Fetches the widget of the given guild. Online counts and the sample of online members are
+    /// read from the presence cache.
+    ///
+    /// # Errors
+    /// * If the guild is not found, or its widget is disabled.
+    /// * If an error occurs with fetching the widget.
+    async fn fetch_guild_widget(&self, guild_id: u64) -> crate::Result<GuildWidget> {
+        let guild = sqlx::query!(
+            r#"SELECT
+                g.name,
+                g.icon,
+                w.channel_id
+            FROM
+                guilds g
+            INNER JOIN
+                guild_widgets w ON w.guild_id = g.id
+            WHERE
+                g.id = $1 AND w.enabled
+            "#,
+            guild_id as i64,
+        )
+        .fetch_optional(self.executor())
+        .await?
+        .ok_or_not_found(
+            "widget",
+            format!("Guild with ID {guild_id} not found or has its widget disabled"),
+        )?;
+
+        let online_count = cache::presence::online_count(guild_id).await?;
+        let sample =
+            cache::presence::sample_online_members(guild_id, WIDGET_MEMBER_SAMPLE_SIZE).await?;
+        let statuses = cache::presence::fetch_presences_bulk(&sample)
+            .await?
+            .into_iter()
+            .map(|p| (p.user_id, p.status))
+            .collect::<HashMap<_, _>>();
+
+        let members = sqlx::query!(
+            r#"SELECT
+                m.id,
+                COALESCE(m.nick, u.display_name, u.username) AS "display_name!",
+                u.avatar
+            FROM
+                members m
+            INNER JOIN
+                users u ON u.id = m.id
+            WHERE
+                m.guild_id = $1 AND m.id = ANY($2::BIGINT[])
+            ORDER BY "display_name!"
+            "#,
+            guild_id as i64,
+            &sample.iter().map(|&id| id as i64).collect::<Vec<_>>(),
+        )
+        .fetch_all(self.executor())
+        .await?
+        .into_iter()
+        .map(|r| WidgetMember {
+            id: r.id as u64,
+            display_name: r.display_name,
+            avatar: r.avatar.map(Asset::from_raw),
+            status: statuses
+                .get(&(r.id as u64))
+                .copied()
+                .unwrap_or(PresenceStatus::Online),
+        })
+        .collect();
+
+        Ok(GuildWidget {
+            guild_id,
+            name: guild.name,
+            icon: guild.icon.map(Asset::from_raw),
+            channel_id: guild.channel_id.map(|id| id as u64),
+            online_count,
+            members,
+        })
+    }
+}
+
+impl<'t, T> GuildWidgetDbExt<'t> for T where T: DbExt<'t> {}
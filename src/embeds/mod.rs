@@ -0,0 +1,5 @@
+//! Automatic resolution of embeds for links in message content.
+
+pub mod resolver;
+
+pub use resolver::*;
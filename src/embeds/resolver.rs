@@ -0,0 +1,508 @@
+use crate::{
+    cache,
+    models::{parse_markdown, visit_markdown, Embed, EmbedAuthor, EmbedType, MarkdownNode},
+    Error, Result,
+};
+use futures_util::future::join_all;
+use std::collections::HashMap;
+
+/// The maximum number of links in a message that embeds are resolved for.
+pub const MAX_RESOLVED_EMBEDS: usize = 5;
+
+/// The maximum length of the title of a resolved embed, in characters.
+pub const MAX_RESOLVED_TITLE_LENGTH: usize = 256;
+
+/// The maximum length of the description of a resolved embed, in characters.
+pub const MAX_RESOLVED_DESCRIPTION_LENGTH: usize = 1024;
+
+/// A resource fetched by an [`EmbedHttpClient`].
+#[derive(Clone, Debug)]
+pub struct FetchedResource {
+    /// The final URL of the resource, after following redirects.
+    pub url: String,
+    /// The media type of the resource from its ``Content-Type`` header, without parameters,
+    /// e.g. ``text/html``.
+    pub content_type: Option<String>,
+    /// The body of the resource. This is only read for HTML documents, and only up to the end of
+    /// the ``<head>`` element, so clients may omit or truncate it otherwise.
+    pub body: Option<String>,
+}
+
+/// An HTTP client used by an [`EmbedResolver`] to fetch linked resources.
+#[async_trait::async_trait]
+pub trait EmbedHttpClient: Send + Sync {
+    /// Fetches the resource at the given URL.
+    ///
+    /// Implementations are responsible for following redirects, enforcing timeouts and size
+    /// limits, and refusing to fetch private or internal addresses.
+    async fn fetch(&self, url: &str) -> Result<FetchedResource>;
+}
+
+/// Resolves embeds for links in message content from their OpenGraph metadata. Resolved embeds
+/// are cached per URL in [`cache::embeds`].
+pub struct EmbedResolver<C> {
+    client: C,
+}
+
+impl<C: EmbedHttpClient> EmbedResolver<C> {
+    /// Creates a new resolver which fetches resources with the given client.
+    #[must_use]
+    pub const fn new(client: C) -> Self {
+        Self { client }
+    }
+
+    /// The HTTP client of this resolver.
+    #[must_use]
+    pub const fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Resolves embeds for the links in the given message content, in the order they appear.
+    /// Embeds are resolved best-effort: links that fail to resolve are skipped.
+    pub async fn resolve(&self, content: &str) -> Vec<Embed> {
+        join_all(
+            extract_embed_urls(content)
+                .into_iter()
+                .map(|url| self.resolve_url(url)),
+        )
+        .await
+        .into_iter()
+        .filter_map(|embed| embed.ok().flatten())
+        .collect()
+    }
+
+    /// Resolves the embed for a single URL, using the cached embed if there is one. Returns
+    /// `None` if the resource at the URL does not produce an embed.
+    ///
+    /// # Errors
+    /// * If the URL is not a valid HTTP(S) URL.
+    /// * If the resource could not be fetched.
+    /// * If an error occurs with the cache.
+    pub async fn resolve_url(&self, url: &str) -> Result<Option<Embed>> {
+        let domain = url_domain(url).ok_or_else(|| Error::InvalidField {
+            field: "url".to_string(),
+            message: format!("{url} is not a valid HTTP(S) URL"),
+        })?;
+
+        if let Some(embed) = cache::embeds::fetch_embed(&domain, url).await? {
+            return Ok(embed);
+        }
+
+        let resource = self.client.fetch(url).await?;
+        let embed = embed_from_resource(&resource);
+        cache::embeds::cache_embed(&domain, url, embed.as_ref()).await?;
+
+        Ok(embed)
+    }
+}
+
+/// Extracts the URLs of the links in message content that should be embedded, without
+/// duplicates. Links in code and links wrapped in angle brackets are ignored, and at most
+/// [`MAX_RESOLVED_EMBEDS`] URLs are returned.
+#[must_use]
+pub fn extract_embed_urls(content: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    visit_markdown(&parse_markdown(content), &mut |node| {
+        if let MarkdownNode::Link {
+            url,
+            suppress_embed: false,
+            ..
+        } = node
+            && urls.len() < MAX_RESOLVED_EMBEDS
+            && !urls.contains(url)
+        {
+            urls.push(*url);
+        }
+    });
+    urls
+}
+
+/// Returns the lowercase host of an HTTP(S) URL, or `None` if the URL is not a valid HTTP(S) URL.
+#[must_use]
+pub fn url_domain(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if host.starts_with('[') {
+        &host[..=host.find(']')?]
+    } else {
+        host.split(':').next()?
+    };
+
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// Metadata of an HTML document, resolved from its OpenGraph, Twitter card and standard meta
+/// tags.
+#[derive(Clone, Debug, Default)]
+pub struct OpenGraph {
+    /// The title of the document.
+    pub title: Option<String>,
+    /// The description of the document.
+    pub description: Option<String>,
+    /// The name of the site the document belongs to.
+    pub site_name: Option<String>,
+    /// The URL of the image of the document, as written in the document.
+    pub image: Option<String>,
+    /// The URL of the video of the document, as written in the document.
+    pub video: Option<String>,
+    /// The theme color of the document, as an RGB integer.
+    pub theme_color: Option<u32>,
+}
+
+impl OpenGraph {
+    /// Parses the metadata from the ``<head>`` of an HTML document. This never fails; missing or
+    /// malformed metadata is simply left out.
+    #[must_use]
+    pub fn parse(html: &str) -> Self {
+        // ASCII lowercasing preserves byte offsets, so indices into `lower` are valid in `html`
+        let lower = html.to_ascii_lowercase();
+        let head_end = lower.find("</head>").unwrap_or(lower.len());
+
+        let mut meta = HashMap::new();
+        let mut title = None;
+        let mut i = 0;
+
+        while let Some(start) = lower[i..head_end].find('<') {
+            let start = i + start;
+            let Some(end) = lower[start..head_end].find('>') else {
+                break;
+            };
+            let end = start + end;
+            let tag = &lower[start + 1..end];
+
+            if tag.starts_with("meta") && tag[4..].starts_with(char::is_whitespace) {
+                let attrs = parse_attributes(&html[start + 5..end]);
+                if let Some(key) = attrs.get("property").or_else(|| attrs.get("name"))
+                    && let Some(content) = attrs.get("content")
+                {
+                    meta.entry(key.to_ascii_lowercase())
+                        .or_insert_with(|| content.trim().to_string());
+                }
+            } else if (tag == "title" || tag.starts_with("title "))
+                && let Some(close) = lower[end..head_end].find("</title>")
+            {
+                title = Some(decode_entities(html[end + 1..end + close].trim()));
+            }
+            i = end + 1;
+        }
+
+        let mut get = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| meta.remove(*key))
+                .filter(|value| !value.is_empty())
+        };
+        Self {
+            title: get(&["og:title", "twitter:title"]).or(title),
+            description: get(&["og:description", "twitter:description", "description"]),
+            site_name: get(&["og:site_name"]),
+            image: get(&[
+                "og:image:secure_url",
+                "og:image:url",
+                "og:image",
+                "twitter:image",
+            ]),
+            video: get(&["og:video:secure_url", "og:video:url", "og:video"]),
+            theme_color: get(&["theme-color"]).and_then(|color| {
+                let hex = color.strip_prefix('#')?;
+                (hex.len() == 6).then(|| u32::from_str_radix(hex, 16).ok())?
+            }),
+        }
+    }
+}
+
+/// Parses the attributes of an HTML tag, after its name. Attribute names are lowercased and
+/// values are decoded. The first occurrence of an attribute wins.
+fn parse_attributes(mut rest: &str) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+
+        let name_end = rest
+            .find(|c: char| c == '=' || c == '/' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        if name_end == 0 {
+            rest = &rest[1..];
+            continue;
+        }
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let value = match rest.strip_prefix('=').map(str::trim_start) {
+            Some(after) => {
+                let (value, remaining) = match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let inner = &after[1..];
+                        let end = inner.find(quote).unwrap_or(inner.len());
+                        (&inner[..end], inner.get(end + 1..).unwrap_or_default())
+                    }
+                    _ => after.split_at(after.find(char::is_whitespace).unwrap_or(after.len())),
+                };
+                rest = remaining;
+                decode_entities(value)
+            }
+            None => String::new(),
+        };
+        attrs.entry(name).or_insert(value);
+    }
+    attrs
+}
+
+/// Decodes the common named and all numeric HTML character references in the given text.
+/// Unknown references are kept as-is.
+fn decode_entities(s: &str) -> String {
+    let mut decoded = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(i) = rest.find('&') {
+        decoded.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let entity = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => '\u{a0}',
+                entity => {
+                    let code = entity.strip_prefix('#')?;
+                    let code = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end))
+        });
+
+        if let Some((c, end)) = entity {
+            decoded.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            decoded.push('&');
+            rest = &rest[1..];
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Resolves a URL found in a document against the URL of the document. Returns `None` if the URL
+/// is not an HTTP(S) URL after resolution, e.g. a ``data:`` URL.
+fn absolute_url(base: &str, url: &str) -> Option<String> {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        return Some(url.to_string());
+    }
+    if url
+        .split('/')
+        .next()
+        .is_some_and(|first| first.contains(':'))
+    {
+        return None;
+    }
+
+    let scheme_end = base.find("://")? + 3;
+    if let Some(url) = url.strip_prefix("//") {
+        return Some(format!("{}{url}", &base[..scheme_end]));
+    }
+
+    let origin_end = base[scheme_end..]
+        .find(['/', '?', '#'])
+        .map_or(base.len(), |i| scheme_end + i);
+    if url.starts_with('/') {
+        return Some(format!("{}{url}", &base[..origin_end]));
+    }
+
+    let path_end = base[origin_end..]
+        .find(['?', '#'])
+        .map_or(base.len(), |i| origin_end + i);
+    let dir_end = base[origin_end..path_end]
+        .rfind('/')
+        .map_or(path_end, |i| origin_end + i);
+    Some(format!("{}/{url}", &base[..dir_end]))
+}
+
+/// Truncates text to the given number of characters, ending it with an ellipsis if truncated.
+fn truncate(text: String, max: usize) -> String {
+    match text.char_indices().nth(max - 1) {
+        Some((i, _)) if text[i..].chars().nth(1).is_some() => format!("{}…", &text[..i]),
+        _ => text,
+    }
+}
+
+const fn empty_embed(kind: EmbedType) -> Embed {
+    Embed {
+        kind,
+        title: None,
+        description: None,
+        url: None,
+        timestamp: None,
+        color: None,
+        hue: None,
+        author: None,
+        footer: None,
+        image: None,
+        thumbnail: None,
+        fields: None,
+    }
+}
+
+/// Produces an embed from a fetched resource, or `None` if the resource should not be embedded.
+///
+/// Images and videos are embedded directly. HTML documents are embedded from their metadata, as
+/// a video embed if they specify a video and otherwise as a meta embed.
+#[must_use]
+pub fn embed_from_resource(resource: &FetchedResource) -> Option<Embed> {
+    let content_type = resource.content_type.as_deref().unwrap_or_default();
+
+    if content_type.starts_with("image/") {
+        return Some(Embed {
+            image: Some(resource.url.clone()),
+            ..empty_embed(EmbedType::Image)
+        });
+    }
+    if content_type.starts_with("video/") {
+        return Some(Embed {
+            url: Some(resource.url.clone()),
+            ..empty_embed(EmbedType::Video)
+        });
+    }
+    if content_type != "text/html" && content_type != "application/xhtml+xml" {
+        return None;
+    }
+
+    let og = OpenGraph::parse(resource.body.as_deref()?);
+    let image = og
+        .image
+        .and_then(|image| absolute_url(&resource.url, &image));
+    if og.title.is_none() && og.description.is_none() && image.is_none() {
+        return None;
+    }
+
+    let embed = Embed {
+        title: og
+            .title
+            .map(|title| truncate(title, MAX_RESOLVED_TITLE_LENGTH)),
+        description: og
+            .description
+            .map(|description| truncate(description, MAX_RESOLVED_DESCRIPTION_LENGTH)),
+        color: og.theme_color,
+        author: og.site_name.map(|name| EmbedAuthor {
+            name: truncate(name, MAX_RESOLVED_TITLE_LENGTH),
+            url: None,
+            icon_url: None,
+        }),
+        ..empty_embed(EmbedType::Meta)
+    };
+
+    Some(
+        match og
+            .video
+            .and_then(|video| absolute_url(&resource.url, &video))
+        {
+            Some(video) => Embed {
+                kind: EmbedType::Video,
+                url: Some(video),
+                thumbnail: image,
+                ..embed
+            },
+            None => Embed {
+                url: Some(resource.url.clone()),
+                image,
+                ..embed
+            },
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_open_graph() {
+        let og = OpenGraph::parse(
+            r##"<html><head>
+            <title>Fallback &amp; title</title>
+            <meta property="og:title" content="Title &quot;quoted&quot;">
+            <meta name=description content='A description'>
+            <meta property="og:site_name" content="Site" />
+            <meta property="og:image" content="/image.png">
+            <meta property="og:image:secure_url" content="https://example.com/secure.png">
+            <meta name="theme-color" content="#FF8000">
+            </head><body><meta property="og:video" content="/ignored.mp4"></body></html>"##,
+        );
+
+        assert_eq!(og.title.as_deref(), Some("Title \"quoted\""));
+        assert_eq!(og.description.as_deref(), Some("A description"));
+        assert_eq!(og.site_name.as_deref(), Some("Site"));
+        assert_eq!(og.image.as_deref(), Some("https://example.com/secure.png"));
+        assert_eq!(og.video, None);
+        assert_eq!(og.theme_color, Some(0xFF8000));
+    }
+
+    #[test]
+    fn test_parse_open_graph_fallbacks() {
+        let og = OpenGraph::parse(
+            r#"<HEAD><TITLE>Plain &#x27;title&#39;</TITLE>
+            <META NAME="twitter:description" CONTENT="  Card  ">
+            <meta property="og:title" content="">
+            <meta name="theme-color" content="red"></HEAD>"#,
+        );
+
+        assert_eq!(og.title.as_deref(), Some("Plain 'title'"));
+        assert_eq!(og.description.as_deref(), Some("Card"));
+        assert_eq!(og.theme_color, None);
+        assert!(OpenGraph::parse("<meta content=").title.is_none());
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &amp; b &lt;c&gt;"), "a & b <c>");
+        assert_eq!(decode_entities("&#65;&#x42;&#X43;"), "ABC");
+        assert_eq!(
+            decode_entities("&unknown; & &#xZZ; &amp"),
+            "&unknown; & &#xZZ; &amp"
+        );
+        assert_eq!(decode_entities("&#1114112;"), "&#1114112;");
+    }
+
+    #[test]
+    fn test_absolute_url() {
+        let base = "https://example.com/a/b/page.html?q=1#frag";
+
+        assert_eq!(
+            absolute_url(base, "http://other.com/x").as_deref(),
+            Some("http://other.com/x")
+        );
+        assert_eq!(
+            absolute_url(base, "//cdn.com/x.png").as_deref(),
+            Some("https://cdn.com/x.png")
+        );
+        assert_eq!(
+            absolute_url(base, "/root.png").as_deref(),
+            Some("https://example.com/root.png")
+        );
+        assert_eq!(
+            absolute_url(base, "rel.png").as_deref(),
+            Some("https://example.com/a/b/rel.png")
+        );
+        assert_eq!(
+            absolute_url("https://example.com", "rel.png").as_deref(),
+            Some("https://example.com/rel.png")
+        );
+        assert_eq!(absolute_url(base, "data:image/png;base64,AAAA"), None);
+        assert_eq!(absolute_url(base, "javascript:alert(1)"), None);
+    }
+}
@@ -1,13 +1,263 @@
 use crate::models::Permissions;
+use chrono::{DateTime, Utc};
 #[cfg(feature = "client")]
 use serde::Deserialize;
 use serde::Serialize;
 #[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
 
+/// A stable, machine-readable code identifying the kind of an [`Error`]. Codes are grouped by
+/// category in blocks of 1000 and are never reused, so clients may match on them instead of on
+/// error messages. This is serialized as an integer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum ErrorCode {
+    // Malformed or invalid requests (1xxx)
+    /// The request body is malformed.
+    MalformedBody = 1001,
+    /// The request body is missing.
+    MissingBody = 1002,
+    /// A field in the request body failed validation.
+    InvalidField = 1003,
+    /// A required field is missing from the request body.
+    MissingField = 1004,
+    /// A plausible IP address could not be resolved from the request.
+    MalformedIp = 1005,
+    /// One or more fields in the request body failed validation.
+    ValidationFailed = 1006,
+
+    // Authentication (2xxx)
+    /// The authentication method is not supported for this account.
+    UnsupportedAuthMethod = 2001,
+    /// The authentication token is missing, malformed or invalid.
+    InvalidToken = 2002,
+    /// The single-use token has expired.
+    ExpiredToken = 2003,
+    /// The login credentials are invalid.
+    InvalidCredentials = 2004,
+    /// A TOTP or recovery code is required.
+    MfaRequired = 2005,
+    /// The TOTP or recovery code is invalid.
+    InvalidMfaCode = 2006,
+    /// The CAPTCHA token is invalid.
+    InvalidCaptcha = 2007,
+
+    // Permissions and guild membership (3xxx)
+    /// The action is only allowed in guilds.
+    GuildOnly = 3001,
+    /// You are not a member of the guild.
+    NotMember = 3002,
+    /// You are not the owner of the guild.
+    NotOwner = 3003,
+    /// You are not the owner of the bot.
+    NotBotOwner = 3004,
+    /// You are not the author of the message.
+    NotMessageAuthor = 3005,
+    /// You are too low in the role hierarchy.
+    RoleTooLow = 3006,
+    /// You are missing the required permissions.
+    MissingPermissions = 3007,
+    /// The role is managed and cannot be modified.
+    RoleIsManaged = 3008,
+    /// You cannot leave as the owner.
+    CannotLeaveAsOwner = 3009,
+    /// You are banned from the guild.
+    Banned = 3010,
+    /// You are timed out in the guild.
+    TimedOut = 3011,
+    /// The poll has ended.
+    PollEnded = 3012,
+
+    // User interactions (4xxx)
+    /// You cannot perform the action on yourself.
+    CannotActOnSelf = 4001,
+    /// You cannot add bots as friends.
+    CannotFriendBots = 4002,
+    /// The user's privacy settings prevent the interaction.
+    UserInteractionDisallowed = 4003,
+    /// Some of the users' privacy settings prevent the interaction.
+    UserInteractionsDisallowed = 4004,
+    /// The user has blocked you.
+    BlockedByUser = 4005,
+
+    // Entities (5xxx)
+    /// The entity was not found.
+    NotFound = 5001,
+    /// Something, e.g. a username or email, is already taken.
+    AlreadyTaken = 5002,
+    /// The vanity URL is already taken.
+    VanityTaken = 5003,
+    /// The entity already exists.
+    AlreadyExists = 5004,
+
+    // Rate limits and quotas (6xxx)
+    /// You are being rate limited.
+    Ratelimited = 6001,
+    /// You requested another verification email too soon.
+    VerificationCooldown = 6002,
+    /// A storage quota would be exceeded.
+    QuotaExceeded = 6003,
+    /// You are sending messages too quickly in a slowmode channel.
+    Slowmode = 6004,
+
+    // Internal errors (9xxx)
+    /// An internal server error occurred.
+    InternalError = 9001,
+}
+
+impl ErrorCode {
+    /// Every error code, in ascending order.
+    pub const ALL: [Self; 39] = [
+        Self::MalformedBody,
+        Self::MissingBody,
+        Self::InvalidField,
+        Self::MissingField,
+        Self::MalformedIp,
+        Self::ValidationFailed,
+        Self::UnsupportedAuthMethod,
+        Self::InvalidToken,
+        Self::ExpiredToken,
+        Self::InvalidCredentials,
+        Self::MfaRequired,
+        Self::InvalidMfaCode,
+        Self::InvalidCaptcha,
+        Self::GuildOnly,
+        Self::NotMember,
+        Self::NotOwner,
+        Self::NotBotOwner,
+        Self::NotMessageAuthor,
+        Self::RoleTooLow,
+        Self::MissingPermissions,
+        Self::RoleIsManaged,
+        Self::CannotLeaveAsOwner,
+        Self::Banned,
+        Self::TimedOut,
+        Self::PollEnded,
+        Self::CannotActOnSelf,
+        Self::CannotFriendBots,
+        Self::UserInteractionDisallowed,
+        Self::UserInteractionsDisallowed,
+        Self::BlockedByUser,
+        Self::NotFound,
+        Self::AlreadyTaken,
+        Self::VanityTaken,
+        Self::AlreadyExists,
+        Self::Ratelimited,
+        Self::VerificationCooldown,
+        Self::QuotaExceeded,
+        Self::Slowmode,
+        Self::InternalError,
+    ];
+
+    /// Returns the error code with the given integer value, if it exists.
+    #[must_use]
+    pub const fn from_u16(code: u16) -> Option<Self> {
+        Some(match code {
+            1001 => Self::MalformedBody,
+            1002 => Self::MissingBody,
+            1003 => Self::InvalidField,
+            1004 => Self::MissingField,
+            1005 => Self::MalformedIp,
+            1006 => Self::ValidationFailed,
+            2001 => Self::UnsupportedAuthMethod,
+            2002 => Self::InvalidToken,
+            2003 => Self::ExpiredToken,
+            2004 => Self::InvalidCredentials,
+            2005 => Self::MfaRequired,
+            2006 => Self::InvalidMfaCode,
+            2007 => Self::InvalidCaptcha,
+            3001 => Self::GuildOnly,
+            3002 => Self::NotMember,
+            3003 => Self::NotOwner,
+            3004 => Self::NotBotOwner,
+            3005 => Self::NotMessageAuthor,
+            3006 => Self::RoleTooLow,
+            3007 => Self::MissingPermissions,
+            3008 => Self::RoleIsManaged,
+            3009 => Self::CannotLeaveAsOwner,
+            3010 => Self::Banned,
+            3011 => Self::TimedOut,
+            3012 => Self::PollEnded,
+            4001 => Self::CannotActOnSelf,
+            4002 => Self::CannotFriendBots,
+            4003 => Self::UserInteractionDisallowed,
+            4004 => Self::UserInteractionsDisallowed,
+            4005 => Self::BlockedByUser,
+            5001 => Self::NotFound,
+            5002 => Self::AlreadyTaken,
+            5003 => Self::VanityTaken,
+            5004 => Self::AlreadyExists,
+            6001 => Self::Ratelimited,
+            6002 => Self::VerificationCooldown,
+            6003 => Self::QuotaExceeded,
+            6004 => Self::Slowmode,
+            9001 => Self::InternalError,
+            _ => return None,
+        })
+    }
+}
+
+impl From<ErrorCode> for u16 {
+    fn from(code: ErrorCode) -> Self {
+        code as Self
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u16(*self as u16)
+    }
+}
+
+#[cfg(feature = "client")]
+impl<'de> Deserialize<'de> for ErrorCode {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let code = u16::deserialize(deserializer)?;
+        Self::from_u16(code)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown error code: {code}")))
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl utoipa::ToSchema<'static> for ErrorCode {
+    fn schema() -> (
+        &'static str,
+        utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>,
+    ) {
+        (
+            "ErrorCode",
+            utoipa::openapi::ObjectBuilder::new()
+                .schema_type(utoipa::openapi::SchemaType::Integer)
+                .description(Some(
+                    "A stable, machine-readable code identifying the kind of an error.",
+                ))
+                .enum_values(Some(Self::ALL.map(u16::from)))
+                .build()
+                .into(),
+        )
+    }
+}
+
 /// A type alias for a [`Result`] with the error type [`Error`].
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// A single field of a request body that failed validation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct FieldError {
+    /// The path of the field that failed validation, e.g. ``embeds.0.title``.
+    pub field: String,
+    /// The error message.
+    pub message: String,
+}
+
 /// The categorization of why the body is malformed.
 #[derive(Copy, Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -22,6 +272,10 @@ pub enum MalformedBodyErrorType {
     InvalidJson,
     /// Received invalid multipart body.
     InvalidMultipart,
+    /// Received invalid MsgPack body.
+    InvalidMsgpack,
+    /// Body could not be decompressed.
+    InvalidCompression,
 }
 
 /// The type of user interaction that was disallowed.
@@ -51,11 +305,28 @@ impl UserInteractionType {
     }
 }
 
+/// The scope of a storage quota.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaType {
+    /// The quota of all files uploaded by a user.
+    User,
+    /// The quota of all files uploaded within a guild.
+    Guild,
+}
+
 /// An error that occurs within Adapt.
+///
+/// Errors are serialized with their [`code`](Self::code) alongside their fields, e.g.
+/// ``{"type": "not_found", "code": 5001, ...}``.
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
 #[cfg_attr(feature = "utoipa", derive(ToSchema))]
-#[serde(tag = "type", rename_all = "snake_case")]
+// the derived impls are generated as inherent functions, so that the `Serialize` impl below can
+// add the error code
+#[serde(remote = "Self", tag = "type", rename_all = "snake_case")]
 pub enum Error {
     /// Received a malformed JSON or MsgPack body.
     MalformedBody {
@@ -77,6 +348,14 @@ pub enum Error {
         /// The error message.
         message: String,
     },
+    /// One or more fields in the request body failed validation. Unlike [`Self::InvalidField`],
+    /// this reports every invalid field at once.
+    ValidationFailed {
+        /// The fields that failed validation.
+        errors: Vec<FieldError>,
+        /// The error message.
+        message: String,
+    },
     /// You are missing a required field in the request body.
     MissingField {
         /// The name of the missing field.
@@ -115,6 +394,12 @@ pub enum Error {
         /// The error message.
         message: String,
     },
+    /// The provided single-use token, such as an email verification or password reset token, has
+    /// expired. A new one must be requested.
+    ExpiredToken {
+        /// The error message.
+        message: String,
+    },
     /// Invalid login credentials were provided, i.e. an invalid password.
     InvalidCredentials {
         /// Which credential was invalid.
@@ -122,6 +407,17 @@ pub enum Error {
         /// The error message.
         message: String,
     },
+    /// The account has two-factor authentication enabled, and a TOTP or recovery code must be
+    /// provided to complete the request.
+    MfaRequired {
+        /// The error message.
+        message: String,
+    },
+    /// The provided TOTP or recovery code is invalid, expired, or was already used.
+    InvalidMfaCode {
+        /// The error message.
+        message: String,
+    },
     /// Invalid Turnstile CAPTCHA token was provided. This is a token provided by Cloudflare
     /// when a user completes a CAPTCHA, and is used while registering new users to prevent spam.
     InvalidCaptcha {
@@ -195,6 +491,13 @@ pub enum Error {
         /// The error message.
         message: String,
     },
+    /// You are banned from the guild you are trying to join.
+    Banned {
+        /// The ID of the guild you are banned from.
+        guild_id: u64,
+        /// The error message.
+        message: String,
+    },
     /// You cannot perform the requested action on yourself.
     CannotActOnSelf {
         /// The error message.
@@ -217,6 +520,18 @@ pub enum Error {
         /// The error message.
         message: String,
     },
+    /// Some of the users you are trying to interact with at once (e.g. add to a group DM) have
+    /// blocked you or have privacy settings that prevent you from doing so.
+    UserInteractionsDisallowed {
+        /// The type of interaction that was disallowed.
+        interaction_type: UserInteractionType,
+        /// The IDs of the users that have blocked you.
+        blocked_by: Vec<u64>,
+        /// The IDs of the users whose privacy settings disallow the interaction.
+        disallowed: Vec<u64>,
+        /// The error message.
+        message: String,
+    },
     /// The user has blocked you, so you cannot interact with them.
     BlockedByUser {
         /// The ID of the user that blocked you.
@@ -231,6 +546,13 @@ pub enum Error {
         /// The error message.
         message: String,
     },
+    /// The requested vanity URL is already used by another guild.
+    VanityTaken {
+        /// The vanity URL code that was requested.
+        vanity_url: String,
+        /// The error message.
+        message: String,
+    },
     /// Something already exists, e.g. a relationship.
     AlreadyExists {
         /// What already exists.
@@ -247,6 +569,47 @@ pub enum Error {
         /// The ratelimited message.
         message: String,
     },
+    /// You requested another verification email too soon after the previous one.
+    VerificationCooldown {
+        /// How long you should wait before requesting another email, in seconds.
+        retry_after: f32,
+        /// The error message.
+        message: String,
+    },
+    /// The file you are uploading would exceed a storage quota.
+    QuotaExceeded {
+        /// The quota that would be exceeded.
+        quota_type: QuotaType,
+        /// The number of bytes that can still be uploaded before the quota is reached.
+        remaining: u64,
+        /// The error message.
+        message: String,
+    },
+    /// You are timed out in the guild and cannot communicate in it until the timeout expires.
+    TimedOut {
+        /// The ID of the guild you are timed out in.
+        guild_id: u64,
+        /// When the timeout expires.
+        until: DateTime<Utc>,
+        /// The error message.
+        message: String,
+    },
+    /// You are sending messages too quickly in a channel with slowmode enabled.
+    Slowmode {
+        /// The ID of the channel with slowmode enabled.
+        channel_id: u64,
+        /// How long you should wait before sending another message, in seconds.
+        retry_after: f32,
+        /// The error message.
+        message: String,
+    },
+    /// The poll you are trying to vote in has ended, so its votes can no longer be changed.
+    PollEnded {
+        /// The ID of the message the poll is attached to.
+        message_id: u64,
+        /// The error message.
+        message: String,
+    },
     /// Internal server error occured, this is likely a bug.
     InternalError {
         /// What caused the error. `None` if unknown.
@@ -258,6 +621,48 @@ pub enum Error {
     },
 }
 
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        struct Fields<'a>(&'a Error);
+
+        impl Serialize for Fields<'_> {
+            fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                Error::serialize(self.0, serializer)
+            }
+        }
+
+        #[derive(Serialize)]
+        struct Body<'a> {
+            #[serde(flatten)]
+            fields: Fields<'a>,
+            code: ErrorCode,
+        }
+
+        Body {
+            fields: Fields(self),
+            code: self.code(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "client")]
+impl<'de> Deserialize<'de> for Error {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // calls the derived function; ``code`` is ignored as it is derived from the type
+        Self::deserialize(deserializer)
+    }
+}
+
 impl Error {
     /// The HTTP status code associated with this error. If this error is not sent over HTTP,
     /// this will be `None`.
@@ -267,12 +672,17 @@ impl Error {
             Self::MalformedBody { .. }
             | Self::MissingBody { .. }
             | Self::InvalidField { .. }
+            | Self::ValidationFailed { .. }
             | Self::MissingField { .. }
             | Self::MalformedIp { .. }
             | Self::UnsupportedAuthMethod { .. }
             | Self::CannotActOnSelf { .. }
-            | Self::CannotFriendBots { .. } => 400,
-            Self::InvalidToken { .. } | Self::InvalidCredentials { .. } => 401,
+            | Self::CannotFriendBots { .. }
+            | Self::ExpiredToken { .. } => 400,
+            Self::InvalidToken { .. }
+            | Self::InvalidCredentials { .. }
+            | Self::MfaRequired { .. }
+            | Self::InvalidMfaCode { .. } => 401,
             Self::GuildOnly { .. }
             | Self::NotMember { .. }
             | Self::NotOwner { .. }
@@ -282,16 +692,71 @@ impl Error {
             | Self::RoleTooLow { .. }
             | Self::RoleIsManaged { .. }
             | Self::CannotLeaveAsOwner { .. }
+            | Self::Banned { .. }
             | Self::UserInteractionDisallowed { .. }
+            | Self::UserInteractionsDisallowed { .. }
             | Self::BlockedByUser { .. }
+            | Self::TimedOut { .. }
+            | Self::PollEnded { .. }
             | Self::InvalidCaptcha { .. } => 403,
             Self::NotFound { .. } => 404,
-            Self::AlreadyTaken { .. } | Self::AlreadyExists { .. } => 409,
-            Self::Ratelimited { .. } => 429,
+            Self::AlreadyTaken { .. } | Self::VanityTaken { .. } | Self::AlreadyExists { .. } => {
+                409
+            }
+            Self::QuotaExceeded { .. } => 413,
+            Self::Ratelimited { .. }
+            | Self::VerificationCooldown { .. }
+            | Self::Slowmode { .. } => 429,
             Self::InternalError { .. } => 500,
         })
     }
 
+    /// The stable, machine-readable code of this error.
+    #[must_use]
+    pub const fn code(&self) -> ErrorCode {
+        match self {
+            Self::MalformedBody { .. } => ErrorCode::MalformedBody,
+            Self::MissingBody { .. } => ErrorCode::MissingBody,
+            Self::InvalidField { .. } => ErrorCode::InvalidField,
+            Self::ValidationFailed { .. } => ErrorCode::ValidationFailed,
+            Self::MissingField { .. } => ErrorCode::MissingField,
+            Self::MalformedIp { .. } => ErrorCode::MalformedIp,
+            Self::UnsupportedAuthMethod { .. } => ErrorCode::UnsupportedAuthMethod,
+            Self::InvalidToken { .. } => ErrorCode::InvalidToken,
+            Self::ExpiredToken { .. } => ErrorCode::ExpiredToken,
+            Self::InvalidCredentials { .. } => ErrorCode::InvalidCredentials,
+            Self::MfaRequired { .. } => ErrorCode::MfaRequired,
+            Self::InvalidMfaCode { .. } => ErrorCode::InvalidMfaCode,
+            Self::InvalidCaptcha { .. } => ErrorCode::InvalidCaptcha,
+            Self::GuildOnly { .. } => ErrorCode::GuildOnly,
+            Self::NotMember { .. } => ErrorCode::NotMember,
+            Self::NotOwner { .. } => ErrorCode::NotOwner,
+            Self::NotBotOwner { .. } => ErrorCode::NotBotOwner,
+            Self::NotMessageAuthor { .. } => ErrorCode::NotMessageAuthor,
+            Self::RoleTooLow { .. } => ErrorCode::RoleTooLow,
+            Self::MissingPermissions { .. } => ErrorCode::MissingPermissions,
+            Self::RoleIsManaged { .. } => ErrorCode::RoleIsManaged,
+            Self::CannotLeaveAsOwner { .. } => ErrorCode::CannotLeaveAsOwner,
+            Self::Banned { .. } => ErrorCode::Banned,
+            Self::TimedOut { .. } => ErrorCode::TimedOut,
+            Self::PollEnded { .. } => ErrorCode::PollEnded,
+            Self::CannotActOnSelf { .. } => ErrorCode::CannotActOnSelf,
+            Self::CannotFriendBots { .. } => ErrorCode::CannotFriendBots,
+            Self::UserInteractionDisallowed { .. } => ErrorCode::UserInteractionDisallowed,
+            Self::UserInteractionsDisallowed { .. } => ErrorCode::UserInteractionsDisallowed,
+            Self::BlockedByUser { .. } => ErrorCode::BlockedByUser,
+            Self::NotFound { .. } => ErrorCode::NotFound,
+            Self::AlreadyTaken { .. } => ErrorCode::AlreadyTaken,
+            Self::VanityTaken { .. } => ErrorCode::VanityTaken,
+            Self::AlreadyExists { .. } => ErrorCode::AlreadyExists,
+            Self::Ratelimited { .. } => ErrorCode::Ratelimited,
+            Self::VerificationCooldown { .. } => ErrorCode::VerificationCooldown,
+            Self::QuotaExceeded { .. } => ErrorCode::QuotaExceeded,
+            Self::Slowmode { .. } => ErrorCode::Slowmode,
+            Self::InternalError { .. } => ErrorCode::InternalError,
+        }
+    }
+
     /// Returns an internal error with the given message.
     #[must_use]
     pub fn custom(message: impl AsRef<str>) -> Self {
@@ -313,6 +778,74 @@ impl Error {
     }
 }
 
+impl std::fmt::Display for Error {
+    /// Formats the error message. Internal errors only include what caused them, since their
+    /// messages may contain sensitive details about the server.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MalformedBody { message, .. }
+            | Self::MissingBody { message, .. }
+            | Self::InvalidField { message, .. }
+            | Self::ValidationFailed { message, .. }
+            | Self::MissingField { message, .. }
+            | Self::MalformedIp { message, .. }
+            | Self::NotFound { message, .. }
+            | Self::GuildOnly { message, .. }
+            | Self::UnsupportedAuthMethod { message, .. }
+            | Self::InvalidToken { message, .. }
+            | Self::ExpiredToken { message, .. }
+            | Self::InvalidCredentials { message, .. }
+            | Self::MfaRequired { message, .. }
+            | Self::InvalidMfaCode { message, .. }
+            | Self::InvalidCaptcha { message, .. }
+            | Self::NotMember { message, .. }
+            | Self::NotOwner { message, .. }
+            | Self::NotBotOwner { message, .. }
+            | Self::NotMessageAuthor { message, .. }
+            | Self::RoleTooLow { message, .. }
+            | Self::MissingPermissions { message, .. }
+            | Self::RoleIsManaged { message, .. }
+            | Self::CannotLeaveAsOwner { message, .. }
+            | Self::Banned { message, .. }
+            | Self::CannotActOnSelf { message, .. }
+            | Self::CannotFriendBots { message, .. }
+            | Self::UserInteractionDisallowed { message, .. }
+            | Self::UserInteractionsDisallowed { message, .. }
+            | Self::BlockedByUser { message, .. }
+            | Self::AlreadyTaken { message, .. }
+            | Self::VanityTaken { message, .. }
+            | Self::AlreadyExists { message, .. }
+            | Self::Ratelimited { message, .. }
+            | Self::VerificationCooldown { message, .. }
+            | Self::QuotaExceeded { message, .. }
+            | Self::TimedOut { message, .. }
+            | Self::Slowmode { message, .. }
+            | Self::PollEnded { message, .. } => f.write_str(message),
+            Self::InternalError {
+                what: Some(what), ..
+            } => {
+                write!(f, "An internal server error occurred ({what})")
+            }
+            Self::InternalError { what: None, .. } => {
+                f.write_str("An internal server error occurred")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::InternalError {
+            what: Some("json".to_string()),
+            message: e.to_string(),
+            debug: Some(format!("{e:?}")),
+        }
+    }
+}
+
 #[cfg(feature = "db")]
 impl From<sqlx::Error> for Error {
     fn from(e: sqlx::Error) -> Self {
@@ -414,3 +947,34 @@ impl<T, E, F: From<E>> ErrIntoExt<T, E, F> for std::result::Result<T, E> {
         self.map_err(Into::into)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn not_found() -> Error {
+        Error::NotFound {
+            entity: "user".to_string(),
+            message: "User not found".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_serialize_code() {
+        let value = serde_json::to_value(not_found()).unwrap();
+
+        assert_eq!(value["type"], "not_found");
+        assert_eq!(value["code"], ErrorCode::NotFound as u16);
+        assert_eq!(value["entity"], "user");
+        assert_eq!(value["message"], "User not found");
+    }
+
+    #[test]
+    #[cfg(feature = "client")]
+    fn test_deserialize_ignores_code() {
+        let json = serde_json::to_string(&not_found()).unwrap();
+        let error = serde_json::from_str::<Error>(&json).unwrap();
+
+        assert_eq!(error.code(), ErrorCode::NotFound);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::http::validate::{Validate, Validator};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
@@ -31,6 +32,21 @@ pub struct LoginRequest {
     /// The token retrieval method to use.
     #[serde(default)]
     pub method: TokenRetrievalMethod,
+    /// The name of the device logging in, shown when listing sessions. Must be at most 64
+    /// characters.
+    pub device_name: Option<String>,
+    /// The platform of the device logging in, e.g. ``web`` or ``ios``.
+    pub platform: Option<String>,
+    /// The TOTP or recovery code, required if the user has two-factor authentication enabled.
+    pub mfa_code: Option<String>,
+}
+
+impl Validate for LoginRequest {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(ref device_name) = self.device_name {
+            validator.length("device_name", device_name, 0, 64);
+        }
+    }
 }
 
 /// The response body for POST /login
@@ -42,4 +58,38 @@ pub struct LoginResponse {
     pub user_id: u64,
     /// The authentication token to use for future requests.
     pub token: String,
+    /// The ID of the session associated with the token. This is `None` if an existing token
+    /// created before sessions were introduced was reused.
+    pub session_id: Option<u64>,
+}
+
+/// The response body when beginning two-factor authentication enrollment.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct TotpEnrollmentResponse {
+    /// The base32-encoded TOTP secret, for authenticator apps that cannot scan QR codes.
+    pub secret: String,
+    /// The ``otpauth://`` URI of the secret, to be displayed as a QR code.
+    pub uri: String,
+}
+
+/// The request body to confirm two-factor authentication enrollment or to disable it.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct MfaCodePayload {
+    /// The current TOTP code from the authenticator app, or a recovery code.
+    pub code: String,
+}
+
+impl Validate for MfaCodePayload {}
+
+/// The response body containing newly generated recovery codes. These are only shown once.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct RecoveryCodesResponse {
+    /// The single-use recovery codes.
+    pub recovery_codes: Vec<String>,
 }
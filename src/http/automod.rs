@@ -0,0 +1,64 @@
+use crate::{
+    http::validate::{Validate, Validator},
+    models::{AutoModAction, AutoModTrigger},
+};
+use serde::Deserialize;
+#[cfg(feature = "client")]
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+#[inline]
+const fn default_enabled() -> bool {
+    true
+}
+
+/// The payload sent to create an automod rule.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateAutoModRulePayload {
+    /// The name of the rule. Must be between 1 and 100 characters long.
+    pub name: String,
+    /// Whether the rule is enabled. Defaults to ``true``.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// What causes the rule to trigger.
+    pub trigger: AutoModTrigger,
+    /// What happens when the rule triggers. At least one action must be given.
+    pub actions: Vec<AutoModAction>,
+    /// The IDs of the channels the rule does not apply to.
+    #[serde(default)]
+    pub exempt_channels: Vec<u64>,
+}
+
+impl Validate for CreateAutoModRulePayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.length("name", &self.name, 1, 100);
+    }
+}
+
+/// The payload sent to edit an automod rule. Fields that are not given are left unchanged.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditAutoModRulePayload {
+    /// The new name of the rule. Must be between 1 and 100 characters long.
+    pub name: Option<String>,
+    /// Whether the rule is enabled.
+    pub enabled: Option<bool>,
+    /// What causes the rule to trigger.
+    pub trigger: Option<AutoModTrigger>,
+    /// What happens when the rule triggers. At least one action must be given.
+    pub actions: Option<Vec<AutoModAction>>,
+    /// The IDs of the channels the rule does not apply to.
+    pub exempt_channels: Option<Vec<u64>>,
+}
+
+impl Validate for EditAutoModRulePayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(ref name) = self.name {
+            validator.length("name", name, 1, 100);
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use crate::models::ExtendedColor;
 use crate::{
-    models::{ChannelType, PermissionOverwrite},
+    http::{
+        message::CreateMessagePayload,
+        validate::{Validate, Validator},
+    },
+    models::{Asset, ChannelType, ForumSortOrder, PartialEmoji, PermissionOverwrite},
     Maybe,
 };
 use serde::Deserialize;
@@ -34,6 +38,22 @@ pub enum CreateGuildChannelInfo {
     },
     /// A category channel.
     Category,
+    /// A forum channel.
+    Forum {
+        /// The topic or guidelines of the forum, if any.
+        topic: Option<String>,
+        /// The order in which posts are sorted by default.
+        #[serde(default)]
+        default_sort_order: ForumSortOrder,
+    },
+    /// A merged channel, combining two or more text or announcement channels.
+    Merged {
+        /// The topic of the merged channel, if any.
+        topic: Option<String>,
+        /// The IDs of the text or announcement channels to merge. There must be between 2 and 10
+        /// unique sources, all in the same guild as the merged channel.
+        source_ids: Vec<u64>,
+    },
 }
 
 impl CreateGuildChannelInfo {
@@ -46,6 +66,28 @@ impl CreateGuildChannelInfo {
             Self::Announcement { .. } => ChannelType::Announcement,
             Self::Voice { .. } => ChannelType::Voice,
             Self::Category => ChannelType::Category,
+            Self::Forum { .. } => ChannelType::Forum,
+            Self::Merged { .. } => ChannelType::Merged,
+        }
+    }
+}
+
+impl Validate for CreateGuildChannelInfo {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        match self {
+            Self::Text { topic }
+            | Self::Announcement { topic }
+            | Self::Forum { topic, .. }
+            | Self::Merged { topic, .. } => {
+                if let Some(topic) = topic {
+                    validator.length("topic", topic, 0, limits.max_channel_topic_length);
+                }
+            }
+            Self::Voice { user_limit } => {
+                validator.range("user_limit", *user_limit, 0, limits.max_voice_user_limit);
+            }
+            Self::Category => {}
         }
     }
 }
@@ -62,9 +104,8 @@ pub struct CreateGuildChannelPayload {
     pub info: CreateGuildChannelInfo,
     /// The optional color of the channel.
     pub color: Option<ExtendedColor>,
-    /// The icon of the channel represented as a
-    /// [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme), if any.
-    pub icon: Option<String>,
+    /// The icon of the channel as an [`Asset`] uploaded to the CDN, if any.
+    pub icon: Option<Asset>,
     /// The ID of the category to create the channel in, if any.
     pub parent_id: Option<u64>,
     /// A list of permission overwrites to apply to the channel, if any.
@@ -74,6 +115,111 @@ pub struct CreateGuildChannelPayload {
     pub nonce: Option<String>,
 }
 
+impl Validate for CreateGuildChannelPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let max = crate::limits().max_channel_name_length;
+        validator.length("name", &self.name, 1, max);
+        self.info.validate_fields(validator);
+        if let Some(ref color) = self.color {
+            validator.result("color", color.validate());
+        }
+        if let Some(ref icon) = self.icon {
+            validator.result("icon", icon.validate());
+        }
+    }
+}
+
+const fn default_auto_archive_duration() -> u32 {
+    1440
+}
+
+/// The request body sent to create a new thread within a text-based guild channel.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateThreadPayload {
+    /// The name of the thread.
+    pub name: String,
+    /// The ID of the message to start the thread from, if any. The message must be in the parent
+    /// channel of the thread.
+    pub parent_message_id: Option<u64>,
+    /// The number of minutes of inactivity after which the thread is automatically archived.
+    /// Defaults to 1440 (one day).
+    #[serde(default = "default_auto_archive_duration")]
+    pub auto_archive_duration: u32,
+    /// A nonce to include with the thread. This is not stored and can be used to identify the
+    /// thread later on (it is relayed through the websocket).
+    pub nonce: Option<String>,
+}
+
+impl Validate for CreateThreadPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let max = crate::limits().max_channel_name_length;
+        validator.length("name", &self.name, 1, max);
+        validator.range(
+            "auto_archive_duration",
+            self.auto_archive_duration,
+            1,
+            10_080,
+        );
+    }
+}
+
+/// The request body sent to create a new tag in a forum channel.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateForumTagPayload {
+    /// The name of the tag.
+    pub name: String,
+    /// The emoji to show alongside the tag, if any.
+    pub emoji: Option<PartialEmoji>,
+    /// Whether only people with the `MANAGE_CHANNELS` permission can apply this tag to posts.
+    #[serde(default)]
+    pub moderated: bool,
+}
+
+impl Validate for CreateForumTagPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.length("name", &self.name, 1, 32);
+    }
+}
+
+/// The request body sent to create a new post in a forum channel.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateForumPostPayload {
+    /// The title of the post.
+    pub name: String,
+    /// The initial message of the post.
+    pub message: CreateMessagePayload,
+    /// The IDs of the forum tags to apply to the post.
+    #[serde(default)]
+    pub applied_tags: Vec<u64>,
+    /// The number of minutes of inactivity after which the post is automatically archived.
+    /// Defaults to 1440 (one day).
+    #[serde(default = "default_auto_archive_duration")]
+    pub auto_archive_duration: u32,
+    /// A nonce to include with the post. This is not stored and can be used to identify the
+    /// post later on (it is relayed through the websocket).
+    pub nonce: Option<String>,
+}
+
+impl Validate for CreateForumPostPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let max = crate::limits().max_channel_name_length;
+        validator.length("name", &self.name, 1, max);
+        validator.nested("message", &self.message);
+        validator.range(
+            "auto_archive_duration",
+            self.auto_archive_duration,
+            1,
+            10_080,
+        );
+    }
+}
+
 /// The request body sent to create a new DM or group channel.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -106,6 +252,26 @@ impl CreateDmChannelPayload {
     }
 }
 
+impl Validate for CreateDmChannelPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Self::Group {
+            name,
+            recipient_ids,
+        } = self
+        {
+            let limits = crate::limits();
+            validator.length("name", name, 1, limits.max_channel_name_length);
+
+            let max = limits.max_group_dm_recipients;
+            validator.check(
+                "recipient_ids",
+                recipient_ids.len() <= max,
+                format!("Group DMs can have at most {max} recipients"),
+            );
+        }
+    }
+}
+
 /// The request body sent to modify a channel.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -129,14 +295,17 @@ pub struct EditChannelPayload {
     /// The new icon of the channel. Explicitly setting this to `None` will clear the icon.
     /// Takes effect for all channels except for user DMs.
     ///
-    /// This should be a [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme).
+    /// If provided, this must be an [`Asset`] uploaded to the CDN.
     #[serde(default)]
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
-    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>, format = "byte"))]
-    pub icon: Maybe<String>,
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub icon: Maybe<Asset>,
     /// The new user limit of the voice channel. Explicitly setting this to `0` will remove the
     /// current limit, if there is any. Only takes effect for guild voice channels.
     pub user_limit: Option<u16>,
+    /// The new default sort order of posts in the forum. If left blank, the sort order will not
+    /// be changed. Only takes effect for forum channels.
+    pub default_sort_order: Option<ForumSortOrder>,
     /// The new permission overwrites to apply to the channel. If provided, this will completely
     /// replace the existing overwrites. You may only create overwrites for manageable roles and
     /// users. You may only modify/set permissions that you have. Only takes effect for guild
@@ -144,6 +313,27 @@ pub struct EditChannelPayload {
     pub overwrites: Option<Vec<PermissionOverwrite>>,
 }
 
+impl Validate for EditChannelPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        if let Some(ref name) = self.name {
+            validator.length("name", name, 1, limits.max_channel_name_length);
+        }
+        if let Some(topic) = self.topic.as_value() {
+            validator.length("topic", topic, 0, limits.max_channel_topic_length);
+        }
+        if let Some(color) = self.color.as_value() {
+            validator.result("color", color.validate());
+        }
+        if let Some(icon) = self.icon.as_value() {
+            validator.result("icon", icon.validate());
+        }
+        if let Some(user_limit) = self.user_limit {
+            validator.range("user_limit", user_limit, 0, limits.max_voice_user_limit);
+        }
+    }
+}
+
 /// The payload used per channel to specify its new position data.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -170,3 +360,5 @@ pub struct EditChannelPositionsPayload {
     /// A list of channel positions to modify.
     pub positions: Vec<EditChannelPositionPayload>,
 }
+
+impl Validate for EditChannelPositionsPayload {}
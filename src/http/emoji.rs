@@ -1,8 +1,21 @@
+use crate::http::validate::{Validate, Validator};
 use serde::Deserialize;
 #[cfg(feature = "client")]
 use serde::Serialize;
 #[cfg(feature = "utoipa")]
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+
+/// Validates that an emoji name is not too long and only contains alphanumeric characters and
+/// underscores, so that it can be used in the ``:name:`` syntax.
+fn validate_emoji_name(validator: &mut Validator, name: &str) {
+    let max = crate::limits().max_emoji_name_length;
+    validator.length("name", name, 1, max);
+    validator.check(
+        "name",
+        name.chars().all(|c| c.is_alphanumeric() || c == '_'),
+        "Must only contain alphanumeric characters and underscores",
+    );
+}
 
 /// The payload sent to create a new emoji.
 #[derive(Clone, Debug, Deserialize)]
@@ -15,6 +28,15 @@ pub struct CreateEmojiPayload {
     /// The image should be represented as a
     /// [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme).
     pub image: String,
+    /// The IDs of the roles that are allowed to use the emoji. If empty, everyone can use it.
+    #[serde(default)]
+    pub roles: Vec<u64>,
+}
+
+impl Validate for CreateEmojiPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validate_emoji_name(validator, &self.name);
+    }
 }
 
 /// The payload sent to modify an emoji.
@@ -27,4 +49,36 @@ pub struct CreateEmojiPayload {
 pub struct EditEmojiPayload {
     /// The new name of the emoji.
     pub name: String,
+    /// The new IDs of the roles that are allowed to use the emoji. Set this to an empty list to
+    /// allow everyone to use the emoji, or leave blank to leave the roles unchanged.
+    pub roles: Option<Vec<u64>>,
+}
+
+impl Validate for EditEmojiPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validate_emoji_name(validator, &self.name);
+    }
+}
+
+#[inline]
+const fn default_reaction_users_limit() -> u8 {
+    100
+}
+
+/// Query to fetch the users that reacted to a message with a specific emoji.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(IntoParams))]
+pub struct ReactionUsersQuery {
+    /// If specified, only users with an ID greater than this ID will be returned, used for
+    /// pagination.
+    pub after: Option<u64>,
+    /// The maximum number of users to return. If unspecified, this defaults to ``100``. Must be
+    /// between ``1`` and ``100``.
+    #[serde(default = "default_reaction_users_limit")]
+    pub limit: u8,
+    /// Whether to only return users whose reaction is a burst (super) reaction. Defaults to
+    /// ``false``.
+    #[serde(default)]
+    pub burst_only: bool,
 }
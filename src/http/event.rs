@@ -0,0 +1,126 @@
+use crate::{
+    http::validate::{Validate, Validator},
+    models::{ScheduledEventLocation, ScheduledEventStatus},
+    Maybe,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+#[cfg(feature = "client")]
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::{IntoParams, ToSchema};
+
+/// Validates that the location of an external event is given.
+fn validate_location(validator: &mut Validator, location: &ScheduledEventLocation) {
+    if let ScheduledEventLocation::External { location } = location {
+        validator.length("location.location", location, 1, 100);
+    }
+}
+
+/// The payload sent to create a new scheduled event in a guild.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateScheduledEventPayload {
+    /// The name of the event.
+    pub name: String,
+    /// The description of the event, if any.
+    pub description: Option<String>,
+    /// Where the event takes place. Voice channels must be in the same guild as the event.
+    pub location: ScheduledEventLocation,
+    /// When the event is scheduled to start. Must be in the future.
+    pub start_time: DateTime<Utc>,
+    /// When the event is scheduled to end, if known. Must be after `start_time`.
+    pub end_time: Option<DateTime<Utc>>,
+    /// A nonce to include with the event. This is not stored and can be used to identify the
+    /// event later on (it is relayed through the websocket).
+    pub nonce: Option<String>,
+}
+
+impl Validate for CreateScheduledEventPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        validator.length("name", &self.name, 1, limits.max_event_name_length);
+        if let Some(ref description) = self.description {
+            validator.length(
+                "description",
+                description,
+                0,
+                limits.max_event_description_length,
+            );
+        }
+        validate_location(validator, &self.location);
+        if let Some(end_time) = self.end_time {
+            validator.check(
+                "end_time",
+                end_time > self.start_time,
+                "The end time of an event must be after its start time",
+            );
+        }
+    }
+}
+
+/// The payload sent to edit a scheduled event.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditScheduledEventPayload {
+    /// The new name of the event, if any.
+    pub name: Option<String>,
+    /// The new description of the event. Explicitly setting this to `None` will clear the
+    /// description.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
+    pub description: Maybe<String>,
+    /// The new location of the event, if any.
+    pub location: Option<ScheduledEventLocation>,
+    /// The new start time of the event, if any.
+    pub start_time: Option<DateTime<Utc>>,
+    /// The new end time of the event. Explicitly setting this to `None` will clear the end time.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<DateTime<Utc>>))]
+    pub end_time: Maybe<DateTime<Utc>>,
+    /// The new status of the event, if any. Finished events cannot change their status.
+    pub status: Option<ScheduledEventStatus>,
+}
+
+impl Validate for EditScheduledEventPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        if let Some(ref name) = self.name {
+            validator.length("name", name, 1, limits.max_event_name_length);
+        }
+        if let Some(description) = self.description.as_value() {
+            validator.length(
+                "description",
+                description,
+                0,
+                limits.max_event_description_length,
+            );
+        }
+        if let Some(ref location) = self.location {
+            validate_location(validator, location);
+        }
+    }
+}
+
+#[inline]
+const fn default_event_users_limit() -> u16 {
+    100
+}
+
+/// The query parameters used to list the users interested in a scheduled event.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(IntoParams))]
+pub struct ScheduledEventUsersQuery {
+    /// If specified, only users with an ID greater than this ID will be returned. This is used as
+    /// a cursor for pagination.
+    pub after: Option<u64>,
+    /// The limit of users to return. If unspecified, this defaults to ``100``. Must be between
+    /// ``0`` and ``1000``.
+    #[serde(default = "default_event_users_limit")]
+    pub limit: u16,
+}
@@ -1,3 +1,5 @@
+use crate::http::validate::{Validate, Validator};
+use crate::models::{Asset, GuildTemplate, OnboardingPrompt, WelcomeScreenChannel};
 use crate::Maybe;
 use serde::Deserialize;
 #[cfg(feature = "client")]
@@ -15,11 +17,11 @@ pub struct CreateGuildPayload {
     /// The description of the guild. Must be between 0 and 1000 characters, or `None` for
     /// no description.
     pub description: Option<String>,
-    /// The icon for the guild. Must be a valid URL, or `None` to not set an icon. This should be
-    /// a [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme) if provided.
-    pub icon: Option<String>,
-    /// The banner URL for the guild. Must be a valid URL, or `None` to not set a banner.
-    pub banner: Option<String>,
+    /// The icon for the guild as an [`Asset`] uploaded to the CDN, or `None` to not set an icon.
+    pub icon: Option<Asset>,
+    /// The banner for the guild as an [`Asset`] uploaded to the CDN, or `None` to not set a
+    /// banner.
+    pub banner: Option<Asset>,
     /// Whether the guild should be public or not. Defaults to `false`.
     #[serde(default)]
     pub public: bool,
@@ -28,6 +30,50 @@ pub struct CreateGuildPayload {
     pub nonce: Option<String>,
 }
 
+impl Validate for CreateGuildPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        validator.length("name", &self.name, 2, limits.max_guild_name_length);
+        if let Some(ref description) = self.description {
+            validator.length(
+                "description",
+                description,
+                0,
+                limits.max_guild_description_length,
+            );
+        }
+        if let Some(ref icon) = self.icon {
+            validator.result("icon", icon.validate());
+        }
+        if let Some(ref banner) = self.banner {
+            validator.result("banner", banner.validate());
+        }
+    }
+}
+
+impl Validate for GuildTemplate {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        validator.length("name", &self.name, 2, limits.max_guild_name_length);
+        if let Some(ref description) = self.description {
+            validator.length(
+                "description",
+                description,
+                0,
+                limits.max_guild_description_length,
+            );
+        }
+        for (i, role) in self.roles.iter().enumerate() {
+            validator.length(
+                format_args!("roles.{i}.name"),
+                &role.name,
+                1,
+                limits.max_role_name_length,
+            );
+        }
+    }
+}
+
 /// The payload sent to edit a guild.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -42,22 +88,219 @@ pub struct EditGuildPayload {
     #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
     pub description: Maybe<String>,
     /// The new icon of the guild. Leave empty to keep the current icon, and set to `null` to
-    /// remove the icon. The icon should be represented as a
-    /// [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme).
+    /// remove the icon. If provided, this must be an [`Asset`] uploaded to the CDN.
     #[serde(default)]
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
-    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>, format = "byte"))]
-    pub icon: Maybe<String>,
-    /// The new banner URL of the guild. Leave empty to keep the current banner, and set to `null`
-    /// to remove the banner.
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub icon: Maybe<Asset>,
+    /// The new banner of the guild. Leave empty to keep the current banner, and set to `null` to
+    /// remove the banner. If provided, this must be an [`Asset`] uploaded to the CDN.
     #[serde(default)]
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
-    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
-    pub banner: Maybe<String>,
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub banner: Maybe<Asset>,
     /// Whether the guild should be public or not. Leave empty to keep the current setting.
     pub public: Option<bool>,
+    /// Whether members must accept the rules of the guild before they can send messages. Leave
+    /// empty to keep the current setting.
+    pub requires_rules_acceptance: Option<bool>,
+}
+
+impl Validate for EditGuildPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        if let Some(ref name) = self.name {
+            validator.length("name", name, 2, limits.max_guild_name_length);
+        }
+        if let Some(description) = self.description.as_value() {
+            validator.length(
+                "description",
+                description,
+                0,
+                limits.max_guild_description_length,
+            );
+        }
+        if let Some(icon) = self.icon.as_value() {
+            validator.result("icon", icon.validate());
+        }
+        if let Some(banner) = self.banner.as_value() {
+            validator.result("banner", banner.validate());
+        }
+    }
+}
+
+/// The options used when cloning a guild.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CloneGuildOptions {
+    /// The name of the new guild. Must be between 2 and 100 characters. Defaults to the name of
+    /// the source guild.
+    pub name: Option<String>,
+    /// Whether to also clone the custom emojis of the source guild. Defaults to `false`.
+    #[serde(default)]
+    pub emojis: bool,
+    /// A nonce to include with the guild. This is not stored and can be used to identify the
+    /// guild later on (it is relayed through the websocket).
+    pub nonce: Option<String>,
+}
+
+impl Validate for CloneGuildOptions {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(ref name) = self.name {
+            let max = crate::limits().max_guild_name_length;
+            validator.length("name", name, 2, max);
+        }
+    }
+}
+
+/// The payload sent to edit the starboard configuration of a guild.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditStarboardPayload {
+    /// The ID of the channel to post starboard entries in. Explicitly setting this to `None` will
+    /// disable the starboard.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<u64>))]
+    pub channel_id: Maybe<u64>,
+    /// The number of stars a message needs to be posted in the starboard. Must be at least ``1``.
+    pub threshold: Option<u16>,
+}
+
+impl Validate for EditStarboardPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(threshold) = self.threshold {
+            validator.check(
+                "threshold",
+                threshold > 0,
+                "The starboard threshold must be at least 1",
+            );
+        }
+    }
+}
+
+/// The payload sent to edit the widget settings of a guild.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditGuildWidgetPayload {
+    /// Whether the widget should be enabled. Leave empty to keep the current setting.
+    pub enabled: Option<bool>,
+    /// The ID of the channel that invites generated from the widget should lead to. Explicitly
+    /// setting this to `None` will disable invites from the widget.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<u64>))]
+    pub channel_id: Maybe<u64>,
+}
+
+impl Validate for EditGuildWidgetPayload {}
+
+/// The payload sent to edit the welcome screen of a guild.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditWelcomeScreenPayload {
+    /// Whether the welcome screen should be enabled. Leave empty to keep the current setting.
+    pub enabled: Option<bool>,
+    /// The description shown at the top of the welcome screen. Must be at most 140 characters
+    /// long. Leave empty to keep the current description, and set to `null` to remove it.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
+    pub description: Maybe<String>,
+    /// The channels to highlight, replacing the current ones. At most 5 channels may be
+    /// highlighted. Leave empty to keep the current channels.
+    pub channels: Option<Vec<WelcomeScreenChannel>>,
+}
+
+impl Validate for EditWelcomeScreenPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(description) = self.description.as_value() {
+            validator.length("description", description, 0, 140);
+        }
+        if let Some(ref channels) = self.channels {
+            validator.count("channels", channels.len(), 0, 5);
+            for (i, channel) in channels.iter().enumerate() {
+                validator.check(
+                    format_args!("channels.{i}.channel_id"),
+                    !channels[..i]
+                        .iter()
+                        .any(|c| c.channel_id == channel.channel_id),
+                    "A channel can only be highlighted once",
+                );
+                validator.length(
+                    format_args!("channels.{i}.description"),
+                    &channel.description,
+                    1,
+                    50,
+                );
+            }
+        }
+    }
+}
+
+/// The payload sent to edit the onboarding configuration of a guild.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditOnboardingPayload {
+    /// Whether new members should be asked the onboarding prompts. Leave empty to keep the
+    /// current setting.
+    pub enabled: Option<bool>,
+    /// The prompts to ask, replacing the current ones. Leave empty to keep the current prompts.
+    pub prompts: Option<Vec<OnboardingPrompt>>,
+}
+
+impl Validate for EditOnboardingPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let Some(ref prompts) = self.prompts else {
+            return;
+        };
+        validator.count("prompts", prompts.len(), 0, 15);
+        for (i, prompt) in prompts.iter().enumerate() {
+            validator.length(format_args!("prompts.{i}.title"), &prompt.title, 1, 100);
+            validator.count(
+                format_args!("prompts.{i}.options"),
+                prompt.options.len(),
+                1,
+                50,
+            );
+            for (j, option) in prompt.options.iter().enumerate() {
+                validator.length(
+                    format_args!("prompts.{i}.options.{j}.title"),
+                    &option.title,
+                    1,
+                    50,
+                );
+                if let Some(ref description) = option.description {
+                    validator.length(
+                        format_args!("prompts.{i}.options.{j}.description"),
+                        description,
+                        0,
+                        100,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// An answer to an onboarding prompt.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct OnboardingAnswer {
+    /// The index of the prompt that is answered.
+    pub prompt: usize,
+    /// The indices of the options that were picked.
+    pub options: Vec<usize>,
 }
 
+impl Validate for OnboardingAnswer {}
+
 /// The payload sent to delete a guild.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -68,6 +311,8 @@ pub struct DeleteGuildPayload {
     pub password: String,
 }
 
+impl Validate for DeleteGuildPayload {}
+
 /// The query parameters used to specify what information to return when fetching a guild.
 #[derive(Clone, Debug, Default, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -113,3 +358,89 @@ impl GetGuildQuery {
         }
     }
 }
+
+/// The payload sent to publish a guild to guild discovery, or to publish a new revision of its
+/// listing.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct PublishDiscoveryEntryPayload {
+    /// The description shown in discovery. Must be between 1 and 300 characters.
+    pub description: String,
+    /// The category to list the guild under. Must be between 1 and 32 characters.
+    pub category: String,
+    /// The keywords the guild can be searched by. Keywords are case-insensitive and duplicates
+    /// are ignored. At most 10 distinct keywords may be provided, each between 1 and 32
+    /// characters.
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+impl PublishDiscoveryEntryPayload {
+    /// Returns the keywords trimmed and lowercased, without duplicates, in the order they were
+    /// first given.
+    #[must_use]
+    pub fn normalized_keywords(&self) -> Vec<String> {
+        let mut keywords = Vec::with_capacity(self.keywords.len());
+        for keyword in &self.keywords {
+            let keyword = keyword.trim().to_lowercase();
+            if !keywords.contains(&keyword) {
+                keywords.push(keyword);
+            }
+        }
+        keywords
+    }
+}
+
+impl Validate for PublishDiscoveryEntryPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.length("description", &self.description, 1, 300);
+        validator.length("category", &self.category, 1, 32);
+
+        let keywords = self.normalized_keywords();
+        validator.count("keywords", keywords.len(), 0, 10);
+        for (i, keyword) in keywords.iter().enumerate() {
+            validator.length(format_args!("keywords.{i}"), keyword, 1, 32);
+        }
+    }
+}
+
+/// How to order guild discovery results.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoverySort {
+    /// Order results from the most to least popular, by upvotes and then by uses.
+    #[default]
+    Popularity,
+    /// Order results from the most to least recently published.
+    Recency,
+}
+
+#[inline]
+const fn default_discovery_limit() -> u8 {
+    25
+}
+
+/// Query to search guild discovery.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(IntoParams))]
+pub struct DiscoverySearchQuery {
+    /// The keyword to search for. This matches the keywords, name, and description of listed
+    /// guilds, ignoring case. If unspecified, all listed guilds are returned.
+    pub keyword: Option<String>,
+    /// If specified, only guilds listed under this category will be returned.
+    pub category: Option<String>,
+    /// How to order the results. Defaults to ``popularity``.
+    #[serde(default)]
+    pub sort: DiscoverySort,
+    /// The limit of entries to return. If unspecified, this defaults to ``25``. Must be between
+    /// ``0`` and ``100``.
+    #[serde(default = "default_discovery_limit")]
+    pub limit: u8,
+    /// The number of results to skip, used for pagination. Defaults to ``0``.
+    #[serde(default)]
+    pub offset: u32,
+}
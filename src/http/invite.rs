@@ -1,3 +1,4 @@
+use crate::http::validate::{Validate, Validator};
 use serde::Deserialize;
 #[cfg(feature = "client")]
 use serde::Serialize;
@@ -20,6 +21,13 @@ pub struct CreateInvitePayload {
     pub max_age: u32,
 }
 
+impl Validate for CreateInvitePayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.range("max_uses", self.max_uses, 0, i32::MAX as u32);
+        validator.range("max_age", self.max_age, 0, 604_800);
+    }
+}
+
 /// Query used to provide a guild nonce when using an invite.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
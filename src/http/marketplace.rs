@@ -0,0 +1,82 @@
+use crate::{
+    http::validate::{Validate, Validator},
+    models::MarketplaceKind,
+};
+use serde::Deserialize;
+#[cfg(feature = "client")]
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// The maximum size of the content of a marketplace release, in bytes.
+pub const MAX_MARKETPLACE_CONTENT_SIZE: usize = 1024 * 1024;
+
+/// The payload sent to create a new marketplace entry.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateMarketplaceEntryPayload {
+    /// Whether the entry is a theme or a plugin.
+    pub kind: MarketplaceKind,
+    /// The name of the entry. Must be between 1 and 64 characters.
+    pub name: String,
+    /// The description of the entry. Must be at most 1024 characters.
+    pub description: Option<String>,
+}
+
+impl Validate for CreateMarketplaceEntryPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.length("name", &self.name, 1, 64);
+        if let Some(ref description) = self.description {
+            validator.length("description", description, 0, 1024);
+        }
+    }
+}
+
+/// The payload sent to publish a new version of a marketplace entry.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct PublishMarketplaceVersionPayload {
+    /// The version string. Must be between 1 and 32 characters and consist only of ASCII
+    /// letters, digits, ``.``, ``-``, and ``+``. Versions cannot be republished.
+    pub version: String,
+    /// The notes describing the changes in this version. Must be at most 4096 characters.
+    pub changelog: Option<String>,
+    /// The source of the theme or plugin. Must be at most 1 MiB.
+    pub content: String,
+}
+
+impl Validate for PublishMarketplaceVersionPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.check(
+            "version",
+            (1..=32).contains(&self.version.len())
+                && self
+                    .version
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')),
+            "Version must be between 1 and 32 characters long and may only contain letters, \
+                numbers, '.', '-', and '+'",
+        );
+        if let Some(ref changelog) = self.changelog {
+            validator.length("changelog", changelog, 0, 4096);
+        }
+        validator.check(
+            "content",
+            self.content.len() <= MAX_MARKETPLACE_CONTENT_SIZE,
+            "Content must be at most 1 MiB",
+        );
+    }
+}
+
+/// The payload sent to install a marketplace entry.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct InstallMarketplaceEntryPayload {
+    /// The version to pin the installation to. If unspecified, the latest version is used.
+    pub version: Option<String>,
+}
+
+impl Validate for InstallMarketplaceEntryPayload {}
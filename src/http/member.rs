@@ -1,9 +1,13 @@
-use crate::{models::Permissions, Maybe};
+use crate::{
+    http::validate::{Validate, Validator},
+    models::{Asset, Permissions},
+    Maybe,
+};
 use serde::Deserialize;
 #[cfg(feature = "client")]
 use serde::Serialize;
 #[cfg(feature = "utoipa")]
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 /// The payload send to edit the authenticated user as a member.
 #[derive(Clone, Debug, Deserialize)]
@@ -16,6 +20,44 @@ pub struct EditClientMemberPayload {
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
     #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
     pub nick: Maybe<String>,
+    /// The new guild-specific avatar of the member. Leave empty to keep the current guild avatar,
+    /// and set to `null` to remove it and fall back to the user's global avatar. If provided, this
+    /// must be an [`Asset`] uploaded to the CDN.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub avatar: Maybe<Asset>,
+    /// The new guild-specific banner of the member. Leave empty to keep the current guild banner,
+    /// and set to `null` to remove it and fall back to the user's global banner. If provided, this
+    /// must be an [`Asset`] uploaded to the CDN.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub banner: Maybe<Asset>,
+    /// The new guild-specific bio of the member. Leave empty to keep the current guild bio, and
+    /// set to `null` to remove it and fall back to the user's global bio.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
+    pub bio: Maybe<String>,
+}
+
+impl Validate for EditClientMemberPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        if let Some(nick) = self.nick.as_value() {
+            validator.length("nick", nick, 1, limits.max_display_name_length);
+        }
+        if let Some(avatar) = self.avatar.as_value() {
+            validator.result("avatar", avatar.validate());
+        }
+        if let Some(banner) = self.banner.as_value() {
+            validator.result("banner", banner.validate());
+        }
+        if let Some(bio) = self.bio.as_value() {
+            validator.length("bio", bio, 0, limits.max_bio_length);
+        }
+    }
 }
 
 /// The payload sent to edit a member.
@@ -29,6 +71,26 @@ pub struct EditMemberPayload {
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
     #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
     pub nick: Maybe<String>,
+    /// The new guild-specific avatar of the member. Leave empty to keep the current guild avatar,
+    /// and set to `null` to remove it and fall back to the user's global avatar. If provided, this
+    /// must be an [`Asset`] uploaded to the CDN.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub avatar: Maybe<Asset>,
+    /// The new guild-specific banner of the member. Leave empty to keep the current guild banner,
+    /// and set to `null` to remove it and fall back to the user's global banner. If provided, this
+    /// must be an [`Asset`] uploaded to the CDN.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub banner: Maybe<Asset>,
+    /// The new guild-specific bio of the member. Leave empty to keep the current guild bio, and
+    /// set to `null` to remove it and fall back to the user's global bio.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
+    pub bio: Maybe<String>,
     /// If provided, this is a bulk overwrite of the member's roles. Any roles not in this list
     /// will be removed from the member, and any roles in this list that are managable by the
     /// user will be added to the member (that is, if the role isn't managed and the user's top role
@@ -43,6 +105,24 @@ pub struct EditMemberPayload {
     pub permissions: Option<Permissions>,
 }
 
+impl Validate for EditMemberPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        if let Some(nick) = self.nick.as_value() {
+            validator.length("nick", nick, 1, limits.max_display_name_length);
+        }
+        if let Some(avatar) = self.avatar.as_value() {
+            validator.result("avatar", avatar.validate());
+        }
+        if let Some(banner) = self.banner.as_value() {
+            validator.result("banner", banner.validate());
+        }
+        if let Some(bio) = self.bio.as_value() {
+            validator.length("bio", bio, 0, limits.max_bio_length);
+        }
+    }
+}
+
 /// The payload sent to add a bot to a guild.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -52,3 +132,51 @@ pub struct AddBotPayload {
     /// configured permissions set by the bot owner.
     pub permissions: Option<Permissions>,
 }
+
+impl Validate for AddBotPayload {}
+
+#[inline]
+const fn default_members_limit() -> u16 {
+    100
+}
+
+/// Query to fetch a page of the members of a guild.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(IntoParams))]
+pub struct MemberQuery {
+    /// If specified, only members with a user ID greater than this ID will be returned. This is
+    /// used as a cursor for pagination.
+    pub after: Option<u64>,
+    /// The limit of members to return. If unspecified, this defaults to ``100``. Must be between
+    /// ``1`` and ``1000``.
+    #[serde(default = "default_members_limit")]
+    pub limit: u16,
+    /// If specified, only members whose username starts with this text (case-insensitive) will
+    /// be returned.
+    pub username_prefix: Option<String>,
+    /// If specified, only members with this role will be returned.
+    pub role_id: Option<u64>,
+}
+
+#[inline]
+const fn default_bans_limit() -> u16 {
+    100
+}
+
+/// Query to fetch the bans of a guild.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(IntoParams))]
+pub struct GuildBansQuery {
+    /// If specified, only bans of users with an ID greater than this ID will be returned. This is
+    /// used as a cursor for pagination.
+    pub after: Option<u64>,
+    /// The limit of bans to return. If unspecified, this defaults to ``100``. Must be between
+    /// ``1`` and ``1000``.
+    #[serde(default = "default_bans_limit")]
+    pub limit: u16,
+    /// If specified, only bans of users whose username starts with this text (case-insensitive)
+    /// will be returned.
+    pub query: Option<String>,
+}
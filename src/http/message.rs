@@ -1,3 +1,4 @@
+use crate::http::validate::{Validate, Validator};
 use crate::models::{Embed, MessageReference};
 use crate::Maybe;
 use serde::Deserialize;
@@ -24,6 +25,79 @@ pub struct CreateMessagePayload {
     /// Message(s) that this message replies to.
     #[serde(default)]
     pub references: Vec<MessageReference>,
+    /// The IDs of the stickers to send with the message. Leave empty to send no stickers. Sticker
+    /// IDs that do not exist are ignored.
+    #[serde(default)]
+    pub sticker_ids: Vec<u64>,
+    /// A poll to attach to the message, if any.
+    pub poll: Option<CreatePollPayload>,
+}
+
+impl Validate for CreateMessagePayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        if let Some(ref content) = self.content {
+            validator.length("content", content, 0, limits.max_message_content_length);
+        }
+        validator.result("", Embed::validate_all(&self.embeds));
+        validator.count(
+            "references",
+            self.references.len(),
+            0,
+            limits.max_message_references,
+        );
+        validator.count(
+            "sticker_ids",
+            self.sticker_ids.len(),
+            0,
+            limits.max_message_stickers,
+        );
+        if let Some(ref poll) = self.poll {
+            validator.nested("poll", poll);
+        }
+    }
+}
+
+/// Payload sent to attach a poll to a new message.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreatePollPayload {
+    /// The question of the poll. Must be between 1 and 300 characters long.
+    pub question: String,
+    /// The answers of the poll. There must be between 2 and 10 answers, each between 1 and 55
+    /// characters long.
+    pub answers: Vec<String>,
+    /// Whether users may vote for more than one answer. Defaults to `false`.
+    #[serde(default)]
+    pub multi_select: bool,
+    /// How long the poll lasts, in hours. If unspecified, the poll never expires. Polls may last at
+    /// most 7 days.
+    pub duration_hours: Option<u32>,
+}
+
+impl Validate for CreatePollPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        validator.length(
+            "question",
+            &self.question,
+            1,
+            limits.max_poll_question_length,
+        );
+        validator.count("answers", self.answers.len(), 2, limits.max_poll_answers);
+        for (i, answer) in self.answers.iter().enumerate() {
+            validator.length(
+                format_args!("answers.{i}"),
+                answer,
+                1,
+                limits.max_poll_answer_length,
+            );
+        }
+        if let Some(hours) = self.duration_hours {
+            validator.range("duration_hours", hours, 1, limits.max_poll_duration_hours);
+        }
+    }
 }
 
 /// Payload sent to edit a message.
@@ -46,6 +120,19 @@ pub struct EditMessagePayload {
     pub embeds: Maybe<Vec<Embed>>,
 }
 
+impl Validate for EditMessagePayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(content) = self.content.as_value() {
+            let max = crate::limits().max_message_content_length;
+            validator.length("content", content, 0, max);
+        }
+        // Existing embeds are not revalidated, so that they never block edits to the content
+        if let Some(embeds) = self.embeds.as_value() {
+            validator.result("", Embed::validate_all(embeds));
+        }
+    }
+}
+
 #[inline]
 const fn default_limit() -> u8 {
     100
@@ -76,3 +163,54 @@ pub struct MessageHistoryQuery {
     #[serde(default)]
     pub oldest_first: bool,
 }
+
+#[inline]
+const fn default_search_limit() -> u8 {
+    25
+}
+
+/// How to order message search results.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MessageSearchOrder {
+    /// Order results from the most relevant to least relevant to the search terms.
+    Relevance,
+    /// Order results from the newest to oldest message.
+    #[default]
+    Recency,
+}
+
+/// Query to search messages.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(IntoParams))]
+pub struct MessageSearchQuery {
+    /// The search terms to match message content against. This supports quoted phrases, ``or``,
+    /// and ``-`` to exclude terms. If unspecified, messages are only filtered by the other fields.
+    pub content: Option<String>,
+    /// If specified, only messages sent by the given user will be returned.
+    pub author_id: Option<u64>,
+    /// If specified, only messages that mention the given user or role will be returned. This
+    /// includes messages that mention any role the given user has.
+    pub mentions: Option<u64>,
+    /// If specified, only messages that have (or do not have) attachments will be returned.
+    pub has_attachment: Option<bool>,
+    /// If specified, only messages that have (or do not have) embeds will be returned.
+    pub has_embed: Option<bool>,
+    /// If specified, only messages before this message will be returned.
+    pub before: Option<u64>,
+    /// If specified, only messages after this message will be returned.
+    pub after: Option<u64>,
+    /// How to order the results. Defaults to ``recency``.
+    #[serde(default)]
+    pub order: MessageSearchOrder,
+    /// The limit of messages to return. If unspecified, this defaults to ``25``. Values above
+    /// ``100`` are clamped to ``100``.
+    #[serde(default = "default_search_limit")]
+    pub limit: u8,
+    /// The number of results to skip, used for pagination. Defaults to ``0``.
+    #[serde(default)]
+    pub offset: u32,
+}
@@ -1,9 +1,16 @@
 pub mod auth;
+pub mod automod;
 pub mod channel;
 pub mod emoji;
+pub mod event;
 pub mod guild;
 pub mod invite;
+pub mod marketplace;
 pub mod member;
 pub mod message;
+pub mod oauth;
+pub mod report;
 pub mod role;
+pub mod sticker;
 pub mod user;
+pub mod validate;
@@ -0,0 +1,124 @@
+use crate::http::validate::{Validate, Validator};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// Payload sent to create an OAuth2 application for a bot.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateOAuthApplicationPayload {
+    /// The URIs users may be redirected to after authorizing the application. At most 10 URIs
+    /// may be provided.
+    pub redirect_uris: Vec<String>,
+}
+
+impl Validate for CreateOAuthApplicationPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.count("redirect_uris", self.redirect_uris.len(), 0, 10);
+        for (i, uri) in self.redirect_uris.iter().enumerate() {
+            validator.check(
+                format_args!("redirect_uris.{i}"),
+                (1..=2048).contains(&uri.len()) && uri.contains("://"),
+                "Redirect URIs must be absolute and at most 2048 characters long",
+            );
+        }
+    }
+}
+
+/// Data returned when creating an OAuth2 application or regenerating its secret.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct OAuthClientCredentials {
+    /// The client ID of the application.
+    pub client_id: u64,
+    /// The client secret of the application. This is only shown once.
+    pub client_secret: String,
+}
+
+/// Payload sent when a user authorizes an application, in exchange for an authorization code.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct AuthorizePayload {
+    /// The client ID of the application to authorize.
+    pub client_id: u64,
+    /// The URI to redirect to with the authorization code. This must be one of the redirect URIs
+    /// of the application.
+    pub redirect_uri: String,
+    /// A space-delimited list of scopes to grant.
+    pub scope: String,
+}
+
+impl Validate for AuthorizePayload {}
+
+/// The grant used to request an access token.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[serde(tag = "grant_type", rename_all = "snake_case")]
+pub enum OAuthGrant {
+    /// Exchange an authorization code for an access token.
+    AuthorizationCode {
+        /// The authorization code.
+        code: String,
+        /// The redirect URI the code was issued for.
+        redirect_uri: String,
+    },
+    /// Exchange a refresh token for a new access token.
+    RefreshToken {
+        /// The refresh token.
+        refresh_token: String,
+    },
+    /// Request an access token on behalf of the owner of the bot of the application.
+    ClientCredentials {
+        /// A space-delimited list of scopes to grant.
+        scope: String,
+    },
+}
+
+/// The request body for the token endpoint.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct OAuthTokenRequest {
+    /// The client ID of the application.
+    pub client_id: u64,
+    /// The client secret of the application.
+    pub client_secret: String,
+    /// The grant to use.
+    #[serde(flatten)]
+    pub grant: OAuthGrant,
+}
+
+impl Validate for OAuthTokenRequest {}
+
+/// The response body for the token endpoint.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct OAuthTokenResponse {
+    /// The access token to use for future requests.
+    pub access_token: String,
+    /// The type of the token. This is always ``Bearer``.
+    pub token_type: String,
+    /// The number of seconds until the access token expires.
+    pub expires_in: u32,
+    /// The refresh token used to request a new access token. This is `None` for the client
+    /// credentials grant.
+    pub refresh_token: Option<String>,
+    /// A space-delimited list of the scopes granted to the access token.
+    pub scope: String,
+}
+
+/// Payload sent to revoke an access or refresh token.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct RevokeOAuthTokenPayload {
+    /// The access or refresh token to revoke.
+    pub token: String,
+}
+
+impl Validate for RevokeOAuthTokenPayload {}
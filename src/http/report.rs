@@ -0,0 +1,83 @@
+use crate::{
+    http::validate::{Validate, Validator},
+    models::{ReportReason, ReportStatus},
+};
+use serde::Deserialize;
+#[cfg(feature = "client")]
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::{IntoParams, ToSchema};
+
+/// The maximum length of the details of a report or of a resolution note.
+const MAX_REPORT_TEXT_LENGTH: usize = 1024;
+
+/// The payload sent to report a message or user. Exactly one of ``message_id`` and ``user_id``
+/// must be given.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateReportPayload {
+    /// The ID of the message to report.
+    pub message_id: Option<u64>,
+    /// The ID of the user to report.
+    pub user_id: Option<u64>,
+    /// When reporting a user, the ID of the guild to report them to. If omitted, the report is
+    /// sent to platform staff.
+    pub guild_id: Option<u64>,
+    /// Why the message or user is being reported.
+    pub reason: ReportReason,
+    /// Additional details about the report. Must be at most 1024 characters long.
+    pub details: Option<String>,
+}
+
+impl Validate for CreateReportPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(ref details) = self.details {
+            validator.length("details", details, 0, MAX_REPORT_TEXT_LENGTH);
+        }
+    }
+}
+
+/// The payload sent to resolve or dismiss a report.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct ResolveReportPayload {
+    /// The new status of the report. Must be either ``resolved`` or ``dismissed``.
+    pub status: ReportStatus,
+    /// A note describing what action was taken. Must be at most 1024 characters long.
+    pub note: Option<String>,
+}
+
+impl Validate for ResolveReportPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.check(
+            "status",
+            self.status != ReportStatus::Open,
+            "Reports can only be resolved or dismissed",
+        );
+        if let Some(ref note) = self.note {
+            validator.length("note", note, 0, MAX_REPORT_TEXT_LENGTH);
+        }
+    }
+}
+
+#[inline]
+const fn default_report_limit() -> u8 {
+    50
+}
+
+/// Query to list reports in the moderation queue.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(IntoParams))]
+pub struct ReportQuery {
+    /// If specified, only reports with this status will be returned.
+    pub status: Option<ReportStatus>,
+    /// If specified, only reports filed before this report will be returned.
+    pub before: Option<u64>,
+    /// The maximum number of reports to return. If unspecified, this defaults to ``50``. Must be
+    /// between ``1`` and ``100``.
+    #[serde(default = "default_report_limit")]
+    pub limit: u8,
+}
@@ -1,10 +1,11 @@
-use crate::models::{ExtendedColor, PermissionPair};
+use crate::http::validate::{Validate, Validator};
+use crate::models::{Asset, ExtendedColor, PermissionPair};
 use crate::Maybe;
 use serde::Deserialize;
 #[cfg(feature = "client")]
 use serde::Serialize;
 #[cfg(feature = "utoipa")]
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 #[inline]
 const fn default_position() -> u16 {
@@ -20,9 +21,8 @@ pub struct CreateRolePayload {
     pub name: String,
     /// The color of the role. Leave empty for the default/inherited color.
     pub color: Option<ExtendedColor>,
-    /// The icon of the role represented as a
-    /// [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme), if any.
-    pub icon: Option<String>,
+    /// The icon of the role as an [`Asset`] uploaded to the CDN, if any.
+    pub icon: Option<Asset>,
     /// The permissions users with this role will have.
     #[serde(default = "PermissionPair::empty")]
     pub permissions: PermissionPair,
@@ -38,6 +38,19 @@ pub struct CreateRolePayload {
     pub mentionable: bool,
 }
 
+impl Validate for CreateRolePayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let max = crate::limits().max_role_name_length;
+        validator.length("name", &self.name, 1, max);
+        if let Some(ref color) = self.color {
+            validator.result("color", color.validate());
+        }
+        if let Some(ref icon) = self.icon {
+            validator.result("icon", icon.validate());
+        }
+    }
+}
+
 /// Payload sent to edit a role.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -52,11 +65,11 @@ pub struct EditRolePayload {
     #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<ExtendedColor>))]
     pub color: Maybe<ExtendedColor>,
     /// The new icon of the role. Explicitly setting this to `None` will clear the icon.
-    /// This should be a [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme).
+    /// If provided, this must be an [`Asset`] uploaded to the CDN.
     #[serde(default)]
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
-    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>, format = "byte"))]
-    pub icon: Maybe<String>,
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub icon: Maybe<Asset>,
     /// The permissions users with this role will have. Both `allow` and `deny` should be specified
     /// if this field is specified.
     pub permissions: Option<PermissionPair>,
@@ -65,3 +78,37 @@ pub struct EditRolePayload {
     /// Whether the role should be mentionable by anyone.
     pub mentionable: Option<bool>,
 }
+
+impl Validate for EditRolePayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(ref name) = self.name {
+            let max = crate::limits().max_role_name_length;
+            validator.length("name", name, 1, max);
+        }
+        if let Some(color) = self.color.as_value() {
+            validator.result("color", color.validate());
+        }
+        if let Some(icon) = self.icon.as_value() {
+            validator.result("icon", icon.validate());
+        }
+    }
+}
+
+#[inline]
+const fn default_role_members_limit() -> u16 {
+    100
+}
+
+/// Query to fetch the members that have a role.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(IntoParams))]
+pub struct RoleMembersQuery {
+    /// If specified, only members with a user ID greater than this ID will be returned. This is
+    /// used as a cursor for pagination.
+    pub after: Option<u64>,
+    /// The limit of members to return. If unspecified, this defaults to ``100``. Must be between
+    /// ``0`` and ``1000``.
+    #[serde(default = "default_role_members_limit")]
+    pub limit: u16,
+}
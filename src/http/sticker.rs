@@ -0,0 +1,69 @@
+use crate::{
+    http::validate::{Validate, Validator},
+    models::StickerFormat,
+};
+use serde::Deserialize;
+#[cfg(feature = "client")]
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// Validates the keywords of a sticker.
+fn validate_tags(validator: &mut Validator, tags: &[String]) {
+    validator.count("tags", tags.len(), 0, 10);
+    for (i, tag) in tags.iter().enumerate() {
+        validator.length(format_args!("tags.{i}"), tag, 1, 32);
+    }
+}
+
+/// The payload sent to create a new sticker.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateStickerPayload {
+    /// The name of the sticker.
+    pub name: String,
+    /// A list of keywords used to find the sticker.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The file format of the sticker.
+    pub format: StickerFormat,
+    /// The sticker file.
+    /// The file should be represented as a
+    /// [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme).
+    pub file: String,
+}
+
+impl Validate for CreateStickerPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let max = crate::limits().max_emoji_name_length;
+        validator.length("name", &self.name, 1, max);
+        validate_tags(validator, &self.tags);
+    }
+}
+
+/// The payload sent to modify a sticker.
+///
+/// # Note
+/// The file of a sticker is immutable. To change the file, create a new sticker instead.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditStickerPayload {
+    /// The new name of the sticker, or leave blank to leave it unchanged.
+    pub name: Option<String>,
+    /// The new keywords of the sticker, or leave blank to leave them unchanged.
+    pub tags: Option<Vec<String>>,
+}
+
+impl Validate for EditStickerPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(ref name) = self.name {
+            let max = crate::limits().max_emoji_name_length;
+            validator.length("name", name, 1, max);
+        }
+        if let Some(ref tags) = self.tags {
+            validate_tags(validator, tags);
+        }
+    }
+}
@@ -1,11 +1,40 @@
 use crate::{
-    models::{Bot, Permissions},
+    http::validate::{Validate, Validator},
+    models::{Asset, Bot, Permissions, PrivacyConfiguration, PushProvider, WebPushKeys},
     Maybe,
 };
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
 
+/// Validates that a username is between 2 and 32 characters long and only contains alphanumeric
+/// characters, periods, hyphens and underscores.
+fn validate_username(validator: &mut Validator, username: &str) {
+    let max = crate::limits().max_username_length;
+    let len = username.chars().count();
+    validator.check(
+        "username",
+        (2..=max).contains(&len)
+            && username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')),
+        format!(
+            "Must be between 2 and {max} characters long and only contain alphanumeric \
+            characters, periods, hyphens and underscores"
+        ),
+    );
+}
+
+/// Validates that an email address is plausible. Whether the address exists is only verified by
+/// sending an email to it.
+fn validate_email(validator: &mut Validator, field: &str, email: &str) {
+    let valid = email.len() <= 254
+        && email
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+    validator.check(field, valid, "Must be a valid email address");
+}
+
 /// Payload sent to create a new user.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -26,6 +55,18 @@ pub struct CreateUserPayload {
     pub captcha_token: String,
 }
 
+impl Validate for CreateUserPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validate_username(validator, &self.username);
+        if let Some(ref display_name) = self.display_name {
+            let max = crate::limits().max_display_name_length;
+            validator.length("display_name", display_name, 2, max);
+        }
+        validate_email(validator, "email", &self.email);
+        validator.length("password", &self.password, 8, 32);
+    }
+}
+
 /// Data returned when creating a new user.
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -47,6 +88,8 @@ pub struct DeleteUserPayload {
     pub password: String,
 }
 
+impl Validate for DeleteUserPayload {}
+
 /// Payload sent when changing a user's password.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -58,6 +101,12 @@ pub struct ChangePasswordPayload {
     pub new_password: String,
 }
 
+impl Validate for ChangePasswordPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.length("new_password", &self.new_password, 8, 32);
+    }
+}
+
 /// Payload sent when changing a user's email.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -69,6 +118,12 @@ pub struct ChangeEmailPayload {
     pub new_email: String,
 }
 
+impl Validate for ChangeEmailPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validate_email(validator, "new_email", &self.new_email);
+    }
+}
+
 /// Payload sent when editing a user.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -83,26 +138,201 @@ pub struct EditUserPayload {
     #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
     pub display_name: Maybe<String>,
     /// The new avatar of the user. Leave empty to keep the current avatar, and set to `null` to
-    /// remove the avatar. If provided, the avatar should be represented as a
-    /// [Data URI scheme](https://en.wikipedia.org/wiki/Data_URI_scheme).
+    /// remove the avatar. If provided, this must be an [`Asset`] uploaded to the CDN.
     #[serde(default)]
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
-    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>, format = Byte))]
-    pub avatar: Maybe<String>,
-    /// The new banner URL of the user. Leave empty to keep the current banner, and set to `null` to
-    /// remove the banner.
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub avatar: Maybe<Asset>,
+    /// The new banner of the user. Leave empty to keep the current banner, and set to `null` to
+    /// remove the banner. If provided, this must be an [`Asset`] uploaded to the CDN.
     #[serde(default)]
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
-    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
-    pub banner: Maybe<String>,
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<Asset>))]
+    pub banner: Maybe<Asset>,
     /// The new bio of the user. Leave empty to keep the current bio, and set to `null` to remove
     /// the bio.
     #[serde(default)]
     #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
     #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
     pub bio: Maybe<String>,
+    /// The new pronouns of the user. Leave empty to keep the current pronouns, and set to `null`
+    /// to remove them.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
+    pub pronouns: Maybe<String>,
+    /// The new accent color of the user's profile, as an RGB integer. Leave empty to keep the
+    /// current accent color, and set to `null` to remove it.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<u32>))]
+    pub accent_color: Maybe<u32>,
 }
 
+impl Validate for EditUserPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let limits = crate::limits();
+        if let Some(ref username) = self.username {
+            validate_username(validator, username);
+        }
+        if let Some(display_name) = self.display_name.as_value() {
+            validator.length(
+                "display_name",
+                display_name,
+                2,
+                limits.max_display_name_length,
+            );
+        }
+        if let Some(avatar) = self.avatar.as_value() {
+            validator.result("avatar", avatar.validate());
+        }
+        if let Some(banner) = self.banner.as_value() {
+            validator.result("banner", banner.validate());
+        }
+        if let Some(bio) = self.bio.as_value() {
+            validator.length("bio", bio, 0, limits.max_bio_length);
+        }
+        if let Some(pronouns) = self.pronouns.as_value() {
+            validator.length("pronouns", pronouns, 1, limits.max_pronouns_length);
+        }
+        if let Some(&accent_color) = self.accent_color.as_value() {
+            validator.range("accent_color", accent_color, 0, 0xFF_FFFF);
+        }
+    }
+}
+
+/// Payload sent when editing the client settings of a user. Omitted fields are left unchanged.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditClientSettingsPayload {
+    /// The new IDs of DM channels pinned to the top of the DM list, in order from top to bottom.
+    pub dm_channel_order: Option<Vec<u64>>,
+    /// The ID of the marketplace theme to apply. Leave empty to keep the current theme, and set
+    /// to `null` to use the default theme.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<u64>))]
+    pub theme: Maybe<u64>,
+    /// The new IDs of the marketplace plugins to enable. At most 100 plugins may be enabled.
+    pub plugins: Option<Vec<u64>>,
+    /// The new locale as a BCP 47 language tag. Leave empty to keep the current locale, and set
+    /// to `null` to use the locale of the device.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<String>))]
+    pub locale: Maybe<String>,
+}
+
+impl Validate for EditClientSettingsPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(locale) = self.locale.as_value() {
+            validator.check(
+                "locale",
+                (1..=35).contains(&locale.len())
+                    && locale
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-'),
+                "Locale must be a valid BCP 47 language tag",
+            );
+        }
+    }
+}
+
+/// Payload sent when editing the privacy configurations of the client user.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditPrivacyPayload {
+    /// Controls who can open and/or send direct messages to the user. Leave empty to keep the
+    /// current configuration, and set to `null` to reset it to the default.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<PrivacyConfiguration>))]
+    pub dm_privacy: Maybe<PrivacyConfiguration>,
+    /// Controls who can add the user to group DMs. Leave empty to keep the current
+    /// configuration, and set to `null` to reset it to the default.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<PrivacyConfiguration>))]
+    pub group_dm_privacy: Maybe<PrivacyConfiguration>,
+    /// Controls who can request to add the user as a friend. Leave empty to keep the current
+    /// configuration, and set to `null` to reset it to the default. This may not include
+    /// ``FRIENDS``, since friends cannot send each other friend requests.
+    #[serde(default)]
+    #[cfg_attr(feature = "client", serde(skip_serializing_if = "Maybe::is_absent"))]
+    #[cfg_attr(feature = "utoipa", schema(nullable, value_type = Option<PrivacyConfiguration>))]
+    pub friend_request_privacy: Maybe<PrivacyConfiguration>,
+}
+
+impl Validate for EditPrivacyPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(privacy) = self.friend_request_privacy.as_value() {
+            validator.check(
+                "friend_request_privacy",
+                !privacy.contains(PrivacyConfiguration::FRIENDS),
+                "Friend request privacy cannot include friends",
+            );
+        }
+    }
+}
+
+/// Payload sent to create a guild folder.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct CreateGuildFolderPayload {
+    /// The ID of the folder to nest this folder in, or `None` to create it at the top level.
+    pub parent_id: Option<u64>,
+    /// The name of the folder. Must be between 1 and 32 characters.
+    pub name: String,
+    /// The color of the folder.
+    pub color: u32,
+}
+
+impl Validate for CreateGuildFolderPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.length("name", &self.name, 1, 32);
+        validator.range("color", self.color, 0, 0xFF_FFFF);
+    }
+}
+
+/// Payload sent to rename or recolor a guild folder. Omitted fields are left unchanged.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditGuildFolderPayload {
+    /// The new name of the folder. Must be between 1 and 32 characters.
+    pub name: Option<String>,
+    /// The new color of the folder.
+    pub color: Option<u32>,
+}
+
+impl Validate for EditGuildFolderPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(ref name) = self.name {
+            validator.length("name", name, 1, 32);
+        }
+        if let Some(color) = self.color {
+            validator.range("color", color, 0, 0xFF_FFFF);
+        }
+    }
+}
+
+/// Payload sent to move a guild into a folder.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct MoveGuildPayload {
+    /// The ID of the folder to move the guild into, or `None` for the root folder.
+    pub folder_id: Option<u64>,
+    /// The position of the guild in the folder, where ``0`` is the top. If unspecified, the
+    /// guild is moved to the bottom of the folder.
+    pub position: Option<u16>,
+}
+
+impl Validate for MoveGuildPayload {}
+
 /// Payload sent when requesting to add a user as a friend.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -112,6 +342,55 @@ pub struct SendFriendRequestPayload {
     pub username: String,
 }
 
+impl Validate for SendFriendRequestPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        let max = crate::limits().max_username_length;
+        validator.length("username", &self.username, 1, max);
+    }
+}
+
+/// Payload sent to subscribe a device to push notifications. Registering an endpoint that is
+/// already registered transfers it to the current user.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct RegisterPushSubscriptionPayload {
+    /// The service used to deliver notifications to the device.
+    pub provider: PushProvider,
+    /// The endpoint URL for Web Push, or the device token for APNs and FCM. Must be between 1
+    /// and 2048 characters.
+    pub endpoint: String,
+    /// The encryption keys of the subscription. Required for Web Push, and must be omitted for
+    /// other providers.
+    pub keys: Option<WebPushKeys>,
+}
+
+impl Validate for RegisterPushSubscriptionPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validator.check(
+            "endpoint",
+            (1..=2048).contains(&self.endpoint.len()),
+            "Endpoint must be between 1 and 2048 characters long",
+        );
+        validator.check(
+            "keys",
+            (self.provider == PushProvider::WebPush) == self.keys.is_some(),
+            "Keys must be provided for Web Push subscriptions and only for them",
+        );
+    }
+}
+
+/// Payload sent to unsubscribe a device from push notifications.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct RemovePushSubscriptionPayload {
+    /// The endpoint of the subscription to remove.
+    pub endpoint: String,
+}
+
+impl Validate for RemovePushSubscriptionPayload {}
+
 /// Payload sent when creating a new bot account.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -131,6 +410,16 @@ pub struct CreateBotPayload {
     pub public: bool,
 }
 
+impl Validate for CreateBotPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        validate_username(validator, &self.username);
+        if let Some(ref display_name) = self.display_name {
+            let max = crate::limits().max_display_name_length;
+            validator.length("display_name", display_name, 2, max);
+        }
+    }
+}
+
 /// Data sent when creating a new bot account.
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -164,6 +453,12 @@ pub struct EditBotPayload {
     pub global_enabled: Option<bool>,
 }
 
+impl Validate for EditBotPayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        self.user_payload.validate_fields(validator);
+    }
+}
+
 /// Payload sent when deleting a bot.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -174,6 +469,8 @@ pub struct DeleteBotPayload {
     pub password: String,
 }
 
+impl Validate for DeleteBotPayload {}
+
 /// Payload sent when regenerating a bot's token.
 #[derive(Clone, Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
@@ -183,3 +480,22 @@ pub struct RegenerateBotTokenPayload {
     #[cfg_attr(feature = "utoipa", schema(format = "password"))]
     pub password: String,
 }
+
+impl Validate for RegenerateBotTokenPayload {}
+
+/// Payload sent when setting the private note on another user.
+#[derive(Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EditUserNotePayload {
+    /// The new content of the note. Set to `null` or an empty string to remove the note.
+    pub note: Option<String>,
+}
+
+impl Validate for EditUserNotePayload {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(ref note) = self.note {
+            validator.length("note", note, 0, crate::limits().max_user_note_length);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{error::FieldError, Error};
+use std::fmt::Display;
+
+/// A payload that can be validated before it is used. Implementors only check what can be known
+/// from the payload itself, such as lengths and bounds; checks that need the database are left to
+/// the methods that use the payload.
+pub trait Validate {
+    /// Checks every field of this payload, recording an error in the given [`Validator`] for each
+    /// invalid field. This does nothing by default, for payloads with nothing to check.
+    fn validate_fields(&self, _validator: &mut Validator) {}
+
+    /// Validates this payload, reporting every invalid field at once.
+    ///
+    /// # Errors
+    /// * [`Error::ValidationFailed`] if any field is invalid.
+    fn validate(&self) -> crate::Result<()> {
+        let mut validator = Validator::new();
+        self.validate_fields(&mut validator);
+        validator.finish()
+    }
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate_fields(&self, validator: &mut Validator) {
+        if let Some(payload) = self {
+            payload.validate_fields(validator);
+        }
+    }
+}
+
+/// Collects the errors of the invalid fields of a payload.
+#[derive(Clone, Debug, Default)]
+pub struct Validator {
+    prefix: String,
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    /// Creates a new validator with no errors.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an error for the given field.
+    pub fn error(&mut self, field: impl Display, message: impl ToString) {
+        self.errors.push(FieldError {
+            field: format!("{}{field}", self.prefix),
+            message: message.to_string(),
+        });
+    }
+
+    /// Records an error for the given field if ``valid`` is `false`.
+    pub fn check(&mut self, field: impl Display, valid: bool, message: impl ToString) {
+        if !valid {
+            self.error(field, message);
+        }
+    }
+
+    /// Checks that the given text is between ``min`` and ``max`` characters long. Leading and
+    /// trailing whitespace counts towards ``max``, since some fields are stored untrimmed, but not
+    /// towards ``min``, so that whitespace-only text is not considered long enough.
+    pub fn length(&mut self, field: impl Display, text: &str, min: usize, max: usize) {
+        let len = text.chars().count();
+        if min == 0 {
+            self.check(
+                field,
+                len <= max,
+                format!("Must be at most {max} characters long"),
+            );
+        } else {
+            self.check(
+                field,
+                len <= max && text.trim().chars().count() >= min,
+                format!("Must be between {min} and {max} characters long"),
+            );
+        }
+    }
+
+    /// Checks that the given number of items is between ``min`` and ``max``.
+    pub fn count(&mut self, field: impl Display, count: usize, min: usize, max: usize) {
+        if min == 0 {
+            self.check(
+                field,
+                count <= max,
+                format!("Must have at most {max} items"),
+            );
+        } else {
+            self.check(
+                field,
+                (min..=max).contains(&count),
+                format!("Must have between {min} and {max} items"),
+            );
+        }
+    }
+
+    /// Checks that the given value is between ``min`` and ``max``, inclusive.
+    pub fn range<T: PartialOrd + Display>(
+        &mut self,
+        field: impl Display,
+        value: T,
+        min: T,
+        max: T,
+    ) {
+        let valid = value >= min && value <= max;
+        self.check(field, valid, format!("Must be between {min} and {max}"));
+    }
+
+    /// Records the error of an existing validation function, if any. Fields of
+    /// [`Error::InvalidField`] and [`Error::ValidationFailed`] errors are nested under the given
+    /// field, or kept as is if it is empty; other errors are recorded for the given field.
+    pub fn result<T>(&mut self, field: &str, result: crate::Result<T>) {
+        let nest = |inner: &str| {
+            if field.is_empty() {
+                inner.to_string()
+            } else {
+                format!("{field}.{inner}")
+            }
+        };
+        match result {
+            Ok(_) => (),
+            Err(Error::InvalidField {
+                field: inner,
+                message,
+            }) => self.error(nest(&inner), message),
+            Err(Error::ValidationFailed { errors, .. }) => {
+                for error in errors {
+                    self.error(nest(&error.field), error.message);
+                }
+            }
+            Err(err) => self.error(field, err),
+        }
+    }
+
+    /// Validates a payload nested in this payload under the given field.
+    pub fn nested(&mut self, field: impl Display, payload: &impl Validate) {
+        let prefix = std::mem::take(&mut self.prefix);
+        self.prefix = format!("{prefix}{field}.");
+        payload.validate_fields(self);
+        self.prefix = prefix;
+    }
+
+    /// Whether no errors were recorded.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The errors recorded so far.
+    #[must_use]
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Consumes the validator, returning an error if any errors were recorded.
+    ///
+    /// # Errors
+    /// * [`Error::ValidationFailed`] with every recorded error, if there are any.
+    pub fn finish(self) -> crate::Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        Err(Error::ValidationFailed {
+            message: match self.errors.len() {
+                1 => format!(
+                    "Invalid {}: {}",
+                    self.errors[0].field, self.errors[0].message
+                ),
+                n => format!("{n} fields failed validation"),
+            },
+            errors: self.errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Empty;
+
+    impl Validate for Empty {}
+
+    struct Named {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    impl Validate for Named {
+        fn validate_fields(&self, validator: &mut Validator) {
+            validator.length("name", &self.name, 1, 4);
+            validator.count("tags", self.tags.len(), 0, 1);
+        }
+    }
+
+    fn fields(validator: &Validator) -> Vec<&str> {
+        validator
+            .errors()
+            .iter()
+            .map(|error| error.field.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_default_validate_fields() {
+        assert!(Empty.validate().is_ok());
+        assert!(None::<Named>.validate().is_ok());
+    }
+
+    #[test]
+    fn test_length_and_count() {
+        let mut validator = Validator::new();
+        validator.length("exact", "abcd", 1, 4);
+        validator.length("empty", "   ", 1, 4);
+        validator.length("long", "abcde", 0, 4);
+        validator.length("chars", "éééé", 1, 4);
+        validator.count("count", 3, 1, 2);
+        validator.count("none", 0, 0, 2);
+
+        assert_eq!(fields(&validator), ["empty", "long", "count"]);
+        assert_eq!(
+            validator.errors()[0].message,
+            "Must be between 1 and 4 characters long"
+        );
+        assert_eq!(
+            validator.errors()[1].message,
+            "Must be at most 4 characters long"
+        );
+        assert_eq!(
+            validator.errors()[2].message,
+            "Must have between 1 and 2 items"
+        );
+    }
+
+    #[test]
+    fn test_length_whitespace() {
+        let mut validator = Validator::new();
+        validator.length("padded", " abcd ", 1, 4);
+        validator.length("padded_unbounded", "     abcd     ", 0, 4);
+        validator.length("padded_short", "  a  ", 2, 10);
+        validator.length("padded_fits", "  ab  ", 2, 6);
+
+        assert_eq!(
+            fields(&validator),
+            ["padded", "padded_unbounded", "padded_short"]
+        );
+    }
+
+    #[test]
+    fn test_range_and_check() {
+        let mut validator = Validator::new();
+        validator.range("low", 0, 1, 10);
+        validator.range("min", 1, 1, 10);
+        validator.range("max", 10, 1, 10);
+        validator.range("high", 11, 1, 10);
+        validator.check("checked", true, "unused");
+        validator.check("failed", false, "Invalid");
+
+        assert_eq!(fields(&validator), ["low", "high", "failed"]);
+        assert_eq!(validator.errors()[0].message, "Must be between 1 and 10");
+    }
+
+    #[test]
+    fn test_result() {
+        let mut validator = Validator::new();
+        validator.result("ok", Ok(()));
+        validator.result(
+            "color",
+            Err::<(), _>(Error::InvalidField {
+                field: "hue".to_string(),
+                message: "Invalid hue".to_string(),
+            }),
+        );
+        validator.result(
+            "",
+            Err::<(), _>(Error::InvalidField {
+                field: "top".to_string(),
+                message: "Invalid".to_string(),
+            }),
+        );
+        validator.result(
+            "poll",
+            Named {
+                name: String::new(),
+                tags: vec![String::new(); 2],
+            }
+            .validate(),
+        );
+        validator.result(
+            "other",
+            Err::<(), _>(Error::MissingBody {
+                message: "Missing body".to_string(),
+            }),
+        );
+
+        assert_eq!(
+            fields(&validator),
+            ["color.hue", "top", "poll.name", "poll.tags", "other"]
+        );
+        assert_eq!(validator.errors()[4].message, "Missing body");
+    }
+
+    #[test]
+    fn test_nested() {
+        let invalid = Named {
+            name: "too long".to_string(),
+            tags: Vec::new(),
+        };
+        let mut validator = Validator::new();
+        validator.nested("a", &invalid);
+        validator.nested(format_args!("b.{}", 0), &invalid);
+        validator.error("after", "Invalid");
+
+        assert_eq!(fields(&validator), ["a.name", "b.0.name", "after"]);
+    }
+
+    #[test]
+    fn test_finish() {
+        assert!(Validator::new().finish().is_ok());
+
+        let mut validator = Validator::new();
+        validator.error("name", "Too long");
+        let Err(Error::ValidationFailed { errors, message }) = validator.finish() else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(message, "Invalid name: Too long");
+
+        let result = Named {
+            name: String::new(),
+            tags: vec![String::new(); 2],
+        }
+        .validate();
+        let Err(Error::ValidationFailed { errors, message }) = result else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(errors.len(), 2);
+        assert_eq!(message, "2 fields failed validation");
+    }
+}
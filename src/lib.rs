@@ -13,6 +13,9 @@
     clippy::collection_is_never_read // false positives, but when fixed this ignore can be removed
 )]
 
+#[cfg(all(target_arch = "wasm32", any(feature = "db", feature = "auth")))]
+compile_error!("the `db` and `auth` features are not supported on wasm targets");
+
 #[cfg(any(feature = "auth", feature = "token-parsing"))]
 pub mod auth;
 #[cfg(feature = "db")]
@@ -21,18 +24,26 @@ pub mod bincode_impl;
 pub mod cache;
 #[cfg(feature = "db")]
 pub mod db;
+#[cfg(feature = "embed-resolver")]
+pub mod embeds;
 pub mod error;
 pub mod http;
+pub mod limits;
 mod macros;
 mod maybe;
 pub mod models;
-mod permissions;
+#[cfg(feature = "utoipa")]
+pub mod openapi;
+pub mod permissions;
 #[cfg(feature = "snowflakes")]
 pub mod snowflake;
 pub mod ws;
 
 pub use error::{Error, NotFoundExt, Result};
+pub use limits::{limits, Limits};
 pub use maybe::Maybe;
+#[cfg(feature = "utoipa")]
+pub use openapi::openapi;
 pub use permissions::{calculate_permissions, calculate_permissions_sorted};
 #[cfg(feature = "utoipa")]
 pub use utoipa;
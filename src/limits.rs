@@ -0,0 +1,172 @@
+//! Crate-level limits used when validating payloads.
+//!
+//! The defaults are the limits used by Adapt. Self-hosted instances may override them once at
+//! startup through [`set_limits`], before any validation takes place.
+
+use std::sync::OnceLock;
+
+static LIMITS: OnceLock<Limits> = OnceLock::new();
+
+/// A registry of limits enforced by essence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum length of a username.
+    pub max_username_length: usize,
+    /// The maximum length of a display name or nickname.
+    pub max_display_name_length: usize,
+    /// The maximum length of a user bio.
+    pub max_bio_length: usize,
+    /// The maximum length of a user's pronouns.
+    pub max_pronouns_length: usize,
+    /// The maximum length of a private note on another user.
+    pub max_user_note_length: usize,
+    /// The maximum length of a guild name.
+    pub max_guild_name_length: usize,
+    /// The maximum length of a guild description.
+    pub max_guild_description_length: usize,
+    /// The maximum length of a channel name.
+    pub max_channel_name_length: usize,
+    /// The maximum length of a channel topic.
+    pub max_channel_topic_length: usize,
+    /// The maximum length of a role name.
+    pub max_role_name_length: usize,
+    /// The maximum user limit of a voice channel.
+    pub max_voice_user_limit: u16,
+    /// The maximum length of a custom emoji or sticker name.
+    pub max_emoji_name_length: usize,
+    /// The maximum length of a scheduled event name.
+    pub max_event_name_length: usize,
+    /// The maximum length of a scheduled event description.
+    pub max_event_description_length: usize,
+    /// The maximum number of recipients in a group DM, including the owner.
+    pub max_group_dm_recipients: usize,
+    /// The maximum length of message content.
+    pub max_message_content_length: usize,
+    /// The maximum number of embeds in a message.
+    pub max_embeds: usize,
+    /// The maximum number of fields in a single embed.
+    pub max_embed_fields: usize,
+    /// The maximum length of the title of an embed or the name of an embed author.
+    pub max_embed_title_length: usize,
+    /// The maximum length of the description of an embed.
+    pub max_embed_description_length: usize,
+    /// The maximum length of the name of an embed field.
+    pub max_embed_field_name_length: usize,
+    /// The maximum length of the value of an embed field.
+    pub max_embed_field_value_length: usize,
+    /// The maximum length of the footer text of an embed.
+    pub max_embed_footer_length: usize,
+    /// The maximum total length of all text in all embeds of a message.
+    pub max_embed_characters: usize,
+    /// The maximum length of a URL in an embed.
+    pub max_embed_url_length: usize,
+    /// The maximum length of the question of a poll.
+    pub max_poll_question_length: usize,
+    /// The maximum number of answers in a poll.
+    pub max_poll_answers: usize,
+    /// The maximum length of a single poll answer.
+    pub max_poll_answer_length: usize,
+    /// The maximum duration of a poll, in hours.
+    pub max_poll_duration_hours: u32,
+    /// The maximum number of attachments in a message.
+    pub max_attachments: usize,
+    /// The maximum number of messages that can be referenced (replied to) by a single message.
+    pub max_message_references: usize,
+    /// The maximum number of stickers in a message.
+    pub max_message_stickers: usize,
+    /// The default maximum number of pinned messages in a channel. Channels may override this
+    /// with their own pin limit.
+    pub max_pins_per_channel: usize,
+    /// The maximum number of stops in a gradient.
+    pub max_gradient_stops: usize,
+    /// The number of days soft-deleted messages are retained before they are eligible to be
+    /// purged.
+    pub soft_deleted_message_retention_days: u32,
+    /// The number of hours an email verification link is valid for.
+    pub email_verification_ttl_hours: u32,
+    /// The number of seconds a user must wait before requesting another verification email.
+    pub email_verification_cooldown_secs: u32,
+    /// The number of minutes a password reset link is valid for.
+    pub password_reset_ttl_minutes: u32,
+    /// The maximum number of channels and guilds a user can favorite.
+    pub max_favorites: usize,
+    /// The maximum number of guild folders a user can create.
+    pub max_guild_folders: usize,
+    /// The maximum number of automod rules in a guild.
+    pub max_automod_rules: usize,
+    /// The maximum total size of all attachments uploaded by a single user, in bytes.
+    pub user_upload_quota: u64,
+    /// The maximum total size of all attachments uploaded within a single guild, in bytes.
+    pub guild_upload_quota: u64,
+}
+
+impl Limits {
+    /// The default limits used by Adapt.
+    pub const DEFAULT: Self = Self {
+        max_username_length: 32,
+        max_display_name_length: 32,
+        max_bio_length: 4096,
+        max_pronouns_length: 40,
+        max_user_note_length: 256,
+        max_guild_name_length: 100,
+        max_guild_description_length: 1024,
+        max_channel_name_length: 32,
+        max_channel_topic_length: 1024,
+        max_role_name_length: 32,
+        max_voice_user_limit: 500,
+        max_emoji_name_length: 32,
+        max_event_name_length: 100,
+        max_event_description_length: 1000,
+        max_group_dm_recipients: 10,
+        max_message_content_length: 4096,
+        max_embeds: 10,
+        max_embed_fields: 25,
+        max_embed_title_length: 256,
+        max_embed_description_length: 4096,
+        max_embed_field_name_length: 256,
+        max_embed_field_value_length: 1024,
+        max_embed_footer_length: 2048,
+        max_embed_characters: 6000,
+        max_embed_url_length: 2048,
+        max_poll_question_length: 300,
+        max_poll_answers: 10,
+        max_poll_answer_length: 55,
+        max_poll_duration_hours: 24 * 7,
+        max_attachments: 10,
+        max_message_references: 10,
+        max_message_stickers: 3,
+        max_pins_per_channel: 50,
+        max_gradient_stops: 8,
+        soft_deleted_message_retention_days: 30,
+        email_verification_ttl_hours: 24,
+        email_verification_cooldown_secs: 60,
+        password_reset_ttl_minutes: 30,
+        max_favorites: 50,
+        max_guild_folders: 100,
+        max_automod_rules: 25,
+        user_upload_quota: 10 * 1024 * 1024 * 1024,
+        guild_upload_quota: 100 * 1024 * 1024 * 1024,
+    };
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Overrides the limits used by essence. This may only be called once, and must be called before
+/// any call to [`limits`].
+///
+/// # Errors
+/// * If the limits were already set or read, the given limits are returned back.
+pub fn set_limits(limits: Limits) -> Result<(), Box<Limits>> {
+    LIMITS.set(limits).map_err(Box::new)
+}
+
+/// Returns the limits currently in effect. If no limits were set through [`set_limits`], this is
+/// [`Limits::DEFAULT`].
+#[must_use]
+pub fn limits() -> &'static Limits {
+    LIMITS.get_or_init(Limits::default)
+}
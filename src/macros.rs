@@ -1,7 +1,7 @@
 #[macro_export]
 macro_rules! serde_for_bitflags {
     (@bincode for $t:ty) => {
-        #[cfg(feature = "db")]
+        #[cfg(feature = "bincode")]
         impl bincode::Encode for $t {
             fn encode<E: bincode::enc::Encoder>(
                 &self,
@@ -11,7 +11,7 @@ macro_rules! serde_for_bitflags {
             }
         }
 
-        #[cfg(feature = "db")]
+        #[cfg(feature = "bincode")]
         impl bincode::Decode for $t {
             fn decode<D: bincode::de::Decoder>(
                 decoder: &mut D,
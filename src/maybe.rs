@@ -29,6 +29,15 @@ impl<T> Maybe<T> {
         matches!(self, Self::Absent)
     }
 
+    /// Returns a reference to the value if it is present and not `null`.
+    #[inline]
+    pub const fn as_value(&self) -> Option<&T> {
+        match self {
+            Self::Value(v) => Some(v),
+            _ => None,
+        }
+    }
+
     /// Maps the inner value of `Maybe` to a new value.
     pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Maybe<U> {
         match self {
@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, ops::Deref, str::FromStr};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// The image format of an asset.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AssetFormat {
+    /// A PNG image.
+    Png,
+    /// A JPEG image.
+    Jpeg,
+    /// A WebP image.
+    Webp,
+    /// A GIF image. Only animated assets may use this format.
+    Gif,
+}
+
+impl AssetFormat {
+    /// Returns the file extension of this format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+            Self::Gif => "gif",
+        }
+    }
+}
+
+impl FromStr for AssetFormat {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "png" => Self::Png,
+            "jpg" | "jpeg" => Self::Jpeg,
+            "webp" => Self::Webp,
+            "gif" => Self::Gif,
+            _ => {
+                return Err(crate::Error::InvalidField {
+                    field: "format".to_string(),
+                    message: format!("Unsupported asset format: {s}"),
+                })
+            }
+        })
+    }
+}
+
+/// The kind of entity an asset belongs to, used to determine the CDN path of the asset.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AssetKind {
+    /// A user's avatar.
+    Avatar,
+    /// A user's or guild's banner.
+    Banner,
+    /// A guild's icon.
+    GuildIcon,
+    /// A group DM or guild channel's icon.
+    ChannelIcon,
+    /// A role's icon.
+    RoleIcon,
+}
+
+impl AssetKind {
+    /// Returns the CDN path segment for this kind of asset.
+    #[must_use]
+    pub const fn path(self) -> &'static str {
+        match self {
+            Self::Avatar => "avatars",
+            Self::Banner => "banners",
+            Self::GuildIcon => "icons",
+            Self::ChannelIcon => "channel-icons",
+            Self::RoleIcon => "role-icons",
+        }
+    }
+}
+
+/// A reference to an image asset stored on the CDN, such as an avatar or icon.
+///
+/// Assets are represented as strings in the form ``[a_]<hash>.<ext>``, where the ``a_`` prefix
+/// indicates that the asset is animated, ``hash`` is the hexadecimal hash of the asset, and
+/// ``ext`` is the extension of the [format](AssetFormat) the asset was uploaded in.
+///
+/// Values stored before assets were introduced may not follow this form, so deserialization
+/// accepts any string. These are considered [legacy](Self::is_legacy) values, and payloads
+/// containing them are rejected by [`Self::validate`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(transparent)]
+pub struct Asset(String);
+
+impl Asset {
+    /// Creates a new asset from its components.
+    ///
+    /// # Errors
+    /// * If the hash is not a valid hexadecimal string.
+    /// * If the asset is not animated but the format is [`AssetFormat::Gif`].
+    pub fn new(hash: &str, format: AssetFormat, animated: bool) -> crate::Result<Self> {
+        Self::validate_hash(hash)?;
+        if !animated && format == AssetFormat::Gif {
+            return Err(crate::Error::InvalidField {
+                field: "format".to_string(),
+                message: "Only animated assets may be GIFs".to_string(),
+            });
+        }
+
+        let prefix = if animated { "a_" } else { "" };
+        Ok(Self(format!("{prefix}{hash}.{}", format.extension())))
+    }
+
+    /// Wraps a raw value without validating it, e.g. a value read from the database.
+    #[must_use]
+    #[cfg(feature = "db")]
+    pub(crate) fn from_raw(raw: String) -> Self {
+        Self(raw)
+    }
+
+    /// Returns whether this asset is a legacy value that is not in the form ``[a_]<hash>.<ext>``,
+    /// e.g. a full URL stored before assets were introduced.
+    #[must_use]
+    pub fn is_legacy(&self) -> bool {
+        self.validate().is_err()
+    }
+
+    /// Validates that this asset is in the form ``[a_]<hash>.<ext>``.
+    ///
+    /// # Errors
+    /// * If this asset is a [legacy](Self::is_legacy) value.
+    pub fn validate(&self) -> crate::Result<()> {
+        Self::from_str(&self.0).map(drop)
+    }
+
+    fn validate_hash(hash: &str) -> crate::Result<()> {
+        if hash.is_empty() || hash.len() > 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(crate::Error::InvalidField {
+                field: "hash".to_string(),
+                message: "Asset hash must be a hexadecimal string of at most 64 characters"
+                    .to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn is_legacy_url(&self) -> bool {
+        self.0.starts_with("https://") || self.0.starts_with("http://")
+    }
+
+    /// Returns the raw string representation of this asset.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns whether this asset is animated.
+    #[must_use]
+    pub fn is_animated(&self) -> bool {
+        self.0.starts_with("a_")
+    }
+
+    /// Returns the hash of this asset, excluding the animated prefix and extension.
+    #[must_use]
+    pub fn hash(&self) -> &str {
+        let raw = self.0.strip_prefix("a_").unwrap_or(&self.0);
+        raw.rsplit_once('.').map_or(raw, |(hash, _)| hash)
+    }
+
+    /// Returns the format this asset was uploaded in, or `None` if it could not be determined.
+    #[must_use]
+    pub fn format(&self) -> Option<AssetFormat> {
+        self.0
+            .rsplit_once('.')
+            .and_then(|(_, ext)| AssetFormat::from_str(ext).ok())
+    }
+
+    /// Renders the CDN URL of this asset in the format it was uploaded in.
+    ///
+    /// ``base`` is the base URL of the CDN without a trailing slash, and ``owner_id`` is the ID of
+    /// the entity that owns this asset (e.g. the user ID for avatars). Legacy values that are
+    /// already absolute URLs are returned as-is.
+    #[must_use]
+    pub fn url(&self, base: &str, kind: AssetKind, owner_id: u64) -> String {
+        if self.is_legacy_url() {
+            return self.0.clone();
+        }
+        format!("{base}/{}/{owner_id}/{}", kind.path(), self.0)
+    }
+
+    /// Renders the CDN URL of this asset in the given format, and optionally with the given size.
+    /// If the asset is not animated, [`AssetFormat::Gif`] falls back to [`AssetFormat::Png`].
+    /// Legacy values that are already absolute URLs are returned as-is.
+    #[must_use]
+    pub fn url_with_format(
+        &self,
+        base: &str,
+        kind: AssetKind,
+        owner_id: u64,
+        format: AssetFormat,
+        size: Option<u16>,
+    ) -> String {
+        if self.is_legacy_url() {
+            return self.0.clone();
+        }
+        let format = if format == AssetFormat::Gif && !self.is_animated() {
+            AssetFormat::Png
+        } else {
+            format
+        };
+        let prefix = if self.is_animated() { "a_" } else { "" };
+        let mut url = format!(
+            "{base}/{}/{owner_id}/{prefix}{}.{}",
+            kind.path(),
+            self.hash(),
+            format.extension()
+        );
+
+        if let Some(size) = size {
+            url.push_str(&format!("?size={size}"));
+        }
+        url
+    }
+}
+
+impl FromStr for Asset {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (raw, animated) = s.strip_prefix("a_").map_or((s, false), |raw| (raw, true));
+        let (hash, ext) = raw
+            .rsplit_once('.')
+            .ok_or_else(|| crate::Error::InvalidField {
+                field: "asset".to_string(),
+                message: "Asset must be in the form [a_]<hash>.<ext>".to_string(),
+            })?;
+
+        Self::new(hash, AssetFormat::from_str(ext)?, animated)
+    }
+}
+
+impl TryFrom<String> for Asset {
+    type Error = crate::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<Asset> for String {
+    fn from(asset: Asset) -> Self {
+        asset.0
+    }
+}
+
+impl Deref for Asset {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Asset {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,188 @@
+#[cfg(feature = "snowflakes")]
+use crate::models::Message;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// What causes an automod rule to trigger.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AutoModTrigger {
+    /// Triggers when the message contains any of the given keywords, case-insensitively.
+    ///
+    /// Keywords match whole words by default. A keyword starting with ``*`` also matches at the
+    /// end of a word, and a keyword ending with ``*`` also matches at the start of a word, e.g.
+    /// ``*cat*`` matches ``concatenate``.
+    Keyword {
+        /// The keywords to match.
+        keywords: Vec<String>,
+        /// Words that never trigger the rule, even if they match a keyword.
+        #[serde(default)]
+        allow_list: Vec<String>,
+    },
+    /// Triggers when the message matches any of the given regular expressions.
+    Regex {
+        /// The regular expressions to match.
+        patterns: Vec<String>,
+    },
+    /// Triggers when the message mentions more than the given number of unique users.
+    MentionSpam {
+        /// The maximum number of unique users a message may mention.
+        max_mentions: u16,
+    },
+    /// Triggers when the message contains a link to a domain that is not allowed.
+    LinkFilter {
+        /// The domains links may point to. Subdomains of these domains are allowed as well. If
+        /// empty, all links trigger the rule.
+        #[serde(default)]
+        allowed_domains: Vec<String>,
+    },
+}
+
+/// What happens when an automod rule triggers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AutoModAction {
+    /// Prevents the message from being sent.
+    Block {
+        /// A custom message shown to the author explaining why the message was blocked.
+        message: Option<String>,
+    },
+    /// Sends an alert about the message to a channel.
+    Alert {
+        /// The ID of the channel to send the alert to.
+        channel_id: u64,
+    },
+    /// Times out the author of the message.
+    Timeout {
+        /// How long to time out the author for, in seconds.
+        duration: u32,
+    },
+}
+
+/// An automod rule of a guild.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct AutoModRule {
+    /// The snowflake ID of the rule.
+    pub id: u64,
+    /// The ID of the guild the rule belongs to.
+    pub guild_id: u64,
+    /// The name of the rule.
+    pub name: String,
+    /// Whether the rule is enabled. Disabled rules never trigger.
+    pub enabled: bool,
+    /// What causes the rule to trigger.
+    pub trigger: AutoModTrigger,
+    /// What happens when the rule triggers.
+    pub actions: Vec<AutoModAction>,
+    /// The IDs of the channels the rule does not apply to.
+    pub exempt_channels: Vec<u64>,
+    /// The ID of the user who created the rule, if they still exist.
+    pub created_by: Option<u64>,
+}
+
+/// The maximum compiled size of an automod regular expression, in bytes.
+#[cfg(feature = "snowflakes")]
+pub const AUTOMOD_REGEX_SIZE_LIMIT: usize = 64 * 1024;
+
+#[cfg(feature = "snowflakes")]
+fn keyword_matches(content: &str, keyword: &str) -> bool {
+    let prefix = keyword.starts_with('*');
+    let suffix = keyword.ends_with('*');
+    let keyword = keyword.trim_matches('*').to_lowercase();
+    if keyword.is_empty() {
+        return false;
+    }
+
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    content.match_indices(&keyword).any(|(start, matched)| {
+        let end = start + matched.len();
+        (prefix || !content[..start].ends_with(is_word_char))
+            && (suffix || !content[end..].starts_with(is_word_char))
+    })
+}
+
+#[cfg(feature = "snowflakes")]
+fn link_domains(content: &str) -> impl Iterator<Item = String> + '_ {
+    use regex::Regex;
+    use std::sync::OnceLock;
+
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+
+    let regex = REGEX.get_or_init(|| Regex::new(r"(?i)\bhttps?://([^/\s:?#]+)").unwrap());
+    regex.captures_iter(content).map(|c| {
+        c.get(1)
+            .unwrap()
+            .as_str()
+            .trim_end_matches('.')
+            .to_lowercase()
+    })
+}
+
+#[cfg(feature = "snowflakes")]
+impl AutoModTrigger {
+    /// Returns whether the given message content triggers this trigger. Regular expressions that
+    /// fail to compile never match.
+    #[must_use]
+    pub fn matches(&self, content: &str) -> bool {
+        match self {
+            Self::Keyword {
+                keywords,
+                allow_list,
+            } => {
+                let mut content = content.to_lowercase();
+                for allowed in allow_list {
+                    let allowed = allowed.to_lowercase();
+                    if !allowed.is_empty() {
+                        content = content.replace(&allowed, " ");
+                    }
+                }
+                keywords.iter().any(|k| keyword_matches(&content, k))
+            }
+            Self::Regex { patterns } => patterns.iter().any(|pattern| {
+                regex::RegexBuilder::new(pattern)
+                    .size_limit(AUTOMOD_REGEX_SIZE_LIMIT)
+                    .build()
+                    .is_ok_and(|regex| regex.is_match(content))
+            }),
+            Self::MentionSpam { max_mentions } => {
+                let mentions = crate::snowflake::extract_mentions(content);
+                mentions.users.len() + mentions.roles.len() > *max_mentions as usize
+            }
+            Self::LinkFilter { allowed_domains } => link_domains(content).any(|domain| {
+                !allowed_domains.iter().any(|allowed| {
+                    let allowed = allowed.to_lowercase();
+                    domain == allowed
+                        || domain
+                            .strip_suffix(&allowed)
+                            .is_some_and(|sub| sub.ends_with('.'))
+                })
+            }),
+        }
+    }
+}
+
+/// Evaluates the given automod rules against a message, returning the actions of every enabled
+/// rule that triggered, in the order of the rules. Rules exempting the channel of the message are
+/// skipped, as are messages without content.
+#[cfg(feature = "snowflakes")]
+#[must_use]
+pub fn evaluate_automod(rules: &[AutoModRule], message: &Message) -> Vec<AutoModAction> {
+    let Some(content) = message.content.as_deref() else {
+        return Vec::new();
+    };
+
+    rules
+        .iter()
+        .filter(|rule| rule.enabled && !rule.exempt_channels.contains(&message.channel_id))
+        .filter(|rule| rule.trigger.matches(content))
+        .flat_map(|rule| rule.actions.iter().cloned())
+        .collect()
+}
@@ -1,8 +1,9 @@
 use crate::models::ExtendedColor;
 use crate::{
-    models::{Message, PermissionPair},
+    models::{Asset, Message, PartialEmoji, PermissionPair},
     Error,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 #[cfg(feature = "utoipa")]
@@ -46,6 +47,140 @@ pub struct TextBasedGuildChannelInfo {
     pub last_message: Option<Message>,
 }
 
+/// Information specific to merged channels, which combine two or more text-based guild channels
+/// (the sources) into a single channel. Members can see a merged channel if they can see any of
+/// its sources.
+#[derive(Clone, Debug, Default, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct MergedChannelInfo {
+    /// Information common to text-based guild channels.
+    #[serde(flatten)]
+    pub info: TextBasedGuildChannelInfo,
+    /// The IDs of the source channels of this merged channel, in ascending order.
+    pub source_ids: Vec<u64>,
+}
+
+/// Information specific to thread channels. Threads are sub-channels of a text-based guild
+/// channel, which is specified by the `parent_id` of the thread.
+#[derive(Clone, Debug, Default, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ThreadInfo {
+    /// The ID of the user that created the thread.
+    pub owner_id: u64,
+    /// The ID of the message this thread was started from, if any.
+    pub parent_message_id: Option<u64>,
+    /// The number of minutes of inactivity after which the thread is automatically archived.
+    pub auto_archive_duration: u32,
+    /// When the thread was archived. This is `None` if the thread is active. Archived threads
+    /// are unarchived when a message is sent in them.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Whether the thread is locked. Only people with the `MANAGE_CHANNELS` permission can
+    /// send messages in locked threads.
+    pub locked: bool,
+    /// The slowmode delay of the thread, in **milliseconds**.
+    pub slowmode: u32,
+    /// The number of users that have joined the thread.
+    pub member_count: u32,
+    /// The IDs of the [forum tags](ForumTag) applied to this thread. This is only used for
+    /// threads that are posts in a forum channel.
+    pub applied_tags: Vec<u64>,
+    /// The last message sent in this thread, if any.
+    pub last_message: Option<Message>,
+}
+
+/// The order in which posts in a forum channel are sorted by default.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(rename_all = "snake_case")]
+pub enum ForumSortOrder {
+    /// Sort posts by the time of their latest activity, most recent first.
+    #[default]
+    LatestActivity,
+    /// Sort posts by the time they were created, most recent first.
+    CreationDate,
+}
+
+impl ForumSortOrder {
+    /// Returns the name of the sort order as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::LatestActivity => "latest_activity",
+            Self::CreationDate => "creation_date",
+        }
+    }
+}
+
+impl FromStr for ForumSortOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest_activity" => Ok(Self::LatestActivity),
+            "creation_date" => Ok(Self::CreationDate),
+            _ => Err(Error::InternalError {
+                what: None,
+                message: "Database returned invalid forum sort order".to_string(),
+                debug: None,
+            }),
+        }
+    }
+}
+
+/// A tag that can be applied to posts in a forum channel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ForumTag {
+    /// The ID of the tag.
+    pub id: u64,
+    /// The name of the tag.
+    pub name: String,
+    /// The emoji shown alongside the tag, if any.
+    pub emoji: Option<PartialEmoji>,
+    /// Whether only people with the `MANAGE_CHANNELS` permission can apply this tag to posts.
+    pub moderated: bool,
+}
+
+/// Information specific to forum channels. Posts in forum channels are [threads](ThreadInfo)
+/// whose `parent_id` is the forum channel.
+#[derive(Clone, Debug, Default, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ForumChannelInfo {
+    /// The topic or guidelines of the forum, if any.
+    pub topic: Option<String>,
+    /// Whether the forum is NSFW.
+    pub nsfw: bool,
+    /// The tags that can be applied to posts in this forum.
+    pub tags: Vec<ForumTag>,
+    /// The order in which posts are sorted by default.
+    pub default_sort_order: ForumSortOrder,
+}
+
+/// Represents a user that has joined a thread.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ThreadMember {
+    /// The ID of the thread.
+    pub thread_id: u64,
+    /// The ID of the user.
+    pub user_id: u64,
+    /// When the user joined the thread.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub joined_at: DateTime<Utc>,
+}
+
 /// An intermediate representation of a channel's type. This is never used directly, but is used
 /// to help deserialization.
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
@@ -68,6 +203,10 @@ pub enum ChannelType {
     Dm,
     /// A group DM channel.
     Group,
+    /// A thread within a text-based guild channel.
+    Thread,
+    /// A forum channel, where each post is a thread.
+    Forum,
 }
 
 impl FromStr for ChannelType {
@@ -82,6 +221,8 @@ impl FromStr for ChannelType {
             "merged" => Ok(Self::Merged),
             "dm" => Ok(Self::Dm),
             "group" => Ok(Self::Group),
+            "thread" => Ok(Self::Thread),
+            "forum" => Ok(Self::Forum),
             _ => Err(Error::InternalError {
                 what: None,
                 message: "Database returned invalid channel type".to_string(),
@@ -104,6 +245,8 @@ impl ChannelType {
             Self::Merged => "merged",
             Self::Dm => "dm",
             Self::Group => "group",
+            Self::Thread => "thread",
+            Self::Forum => "forum",
         }
     }
 
@@ -118,7 +261,7 @@ impl ChannelType {
     #[inline]
     #[must_use]
     pub const fn is_text_based(&self) -> bool {
-        self.is_guild_text_based() || self.is_dm()
+        self.is_guild_text_based() || self.is_dm() || self.is_thread()
     }
 
     /// Returns whether the channel type is a guild channel.
@@ -127,10 +270,23 @@ impl ChannelType {
     pub const fn is_guild(&self) -> bool {
         matches!(
             self,
-            Self::Text | Self::Announcement | Self::Voice | Self::Category
+            Self::Text
+                | Self::Announcement
+                | Self::Voice
+                | Self::Category
+                | Self::Merged
+                | Self::Thread
+                | Self::Forum
         )
     }
 
+    /// Returns whether the channel type is a thread.
+    #[inline]
+    #[must_use]
+    pub const fn is_thread(&self) -> bool {
+        matches!(self, Self::Thread)
+    }
+
     /// Returns whether the channel type is a DM channel.
     #[inline]
     #[must_use]
@@ -161,7 +317,11 @@ pub enum GuildChannelInfo {
     /// properties of one.
     Category,
     /// Two or more channels merged together.
-    Merged(TextBasedGuildChannelInfo),
+    Merged(MergedChannelInfo),
+    /// A thread within a text-based guild channel.
+    Thread(ThreadInfo),
+    /// A forum channel, where each post is a thread.
+    Forum(ForumChannelInfo),
 }
 
 impl GuildChannelInfo {
@@ -175,6 +335,8 @@ impl GuildChannelInfo {
             Self::Voice { .. } => ChannelType::Voice,
             Self::Category => ChannelType::Category,
             Self::Merged { .. } => ChannelType::Merged,
+            Self::Thread { .. } => ChannelType::Thread,
+            Self::Forum { .. } => ChannelType::Forum,
         }
     }
 }
@@ -193,6 +355,24 @@ pub struct PermissionOverwrite {
     pub permissions: PermissionPair,
 }
 
+/// Represents a channel following an announcement channel. Messages published in the source
+/// channel are crossposted to the target channel.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ChannelFollow {
+    /// The ID of the announcement channel being followed.
+    pub source_id: u64,
+    /// The ID of the channel that receives crossposts.
+    pub target_id: u64,
+    /// The ID of the guild the target channel is in.
+    pub guild_id: u64,
+    /// When the channel was followed.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub created_at: DateTime<Utc>,
+}
+
 /// Represents a channel in a guild.
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -210,8 +390,8 @@ pub struct GuildChannel {
     pub name: String,
     /// The accent color of the channel, used in the UI, or ``None`` if the channel has no color.
     pub color: Option<ExtendedColor>,
-    /// The URL to the icon of the channel, if any.
-    pub icon: Option<String>,
+    /// The icon of the channel, if any.
+    pub icon: Option<Asset>,
     /// The position of the channel in the channel list. A lower value means appearing "higher" in
     /// the UI, basically think of this as a 0-indexed listing of the channels from top-to-bottom.
     ///
@@ -291,8 +471,8 @@ pub enum DmChannelInfo {
         name: String,
         /// The topic of the group chat, if any.
         topic: Option<String>,
-        /// The URL of the group's icon, if any.
-        icon: Option<String>,
+        /// The group's icon, if any.
+        icon: Option<Asset>,
         /// The ID of the owner of the group chat.
         owner_id: u64,
         /// A list of recipients in the group chat by user ID.
@@ -379,15 +559,14 @@ impl Channel {
     #[must_use]
     pub fn topic(&self) -> Option<&str> {
         match self {
-            Self::Guild(channel) => {
-                if let GuildChannelInfo::Text(ref info) | GuildChannelInfo::Announcement(ref info) =
-                    channel.info
-                {
+            Self::Guild(channel) => match channel.info {
+                GuildChannelInfo::Text(ref info) | GuildChannelInfo::Announcement(ref info) => {
                     info.topic.as_deref()
-                } else {
-                    None
                 }
-            }
+                GuildChannelInfo::Merged(ref info) => info.info.topic.as_deref(),
+                GuildChannelInfo::Forum(ref info) => info.topic.as_deref(),
+                _ => None,
+            },
             Self::Dm(channel) => {
                 if let DmChannelInfo::Group { ref topic, .. } = channel.info {
                     topic.as_deref()
@@ -401,13 +580,13 @@ impl Channel {
     /// Sets the topic of the channel to the given topic.
     pub fn set_topic(&mut self, topic: Option<String>) {
         match self {
-            Self::Guild(channel) => {
-                if let GuildChannelInfo::Text(ref mut info)
-                | GuildChannelInfo::Announcement(ref mut info) = channel.info
-                {
-                    info.topic = topic;
-                }
-            }
+            Self::Guild(channel) => match channel.info {
+                GuildChannelInfo::Text(ref mut info)
+                | GuildChannelInfo::Announcement(ref mut info) => info.topic = topic,
+                GuildChannelInfo::Merged(ref mut info) => info.info.topic = topic,
+                GuildChannelInfo::Forum(ref mut info) => info.topic = topic,
+                _ => {}
+            },
             Self::Dm(channel) => {
                 if let DmChannelInfo::Group {
                     topic: ref mut group_topic,
@@ -422,12 +601,12 @@ impl Channel {
 
     /// Returns the icon of the channel.
     #[must_use]
-    pub fn icon(&self) -> Option<&str> {
+    pub fn icon(&self) -> Option<&Asset> {
         match self {
-            Self::Guild(_) => None, // TODO: icons for guild channels
+            Self::Guild(channel) => channel.icon.as_ref(),
             Self::Dm(channel) => {
                 if let DmChannelInfo::Group { ref icon, .. } = channel.info {
-                    icon.as_deref()
+                    icon.as_ref()
                 } else {
                     None
                 }
@@ -436,9 +615,9 @@ impl Channel {
     }
 
     /// Sets the icon of the channel to the given icon.
-    pub fn set_icon(&mut self, icon: Option<String>) {
+    pub fn set_icon(&mut self, icon: Option<Asset>) {
         match self {
-            Self::Guild(_) => (), // TODO: icons for guild channels
+            Self::Guild(channel) => channel.icon = icon,
             Self::Dm(channel) => {
                 if let DmChannelInfo::Group {
                     icon: ref mut group_icon,
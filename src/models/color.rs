@@ -46,10 +46,11 @@ impl Gradient {
             });
         }
 
-        if self.stops.len() > 8 {
+        let max_stops = crate::limits().max_gradient_stops;
+        if self.stops.len() > max_stops {
             return Err(crate::Error::InvalidField {
                 field: "stops".to_string(),
-                message: "Gradient may only have at most 8 stops".to_string(),
+                message: format!("Gradient may only have at most {max_stops} stops"),
             });
         }
 
@@ -129,6 +130,17 @@ pub(crate) struct DbGradient {
     stops: Vec<DbGradientStop>,
 }
 
+#[cfg(feature = "db")]
+impl sqlx::postgres::PgHasArrayType for DbGradient {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("gradient_type[]")
+    }
+
+    fn array_compatible(_: &PgTypeInfo) -> bool {
+        true
+    }
+}
+
 impl ExtendedColor {
     /// Constructs an extended color from either a solid or gradient entry in the database.
     #[must_use]
@@ -0,0 +1,44 @@
+use crate::models::PartialGuild;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "client")]
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// A guild listed in guild discovery.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct DiscoveryEntry {
+    /// The guild this entry lists.
+    pub guild: PartialGuild,
+    /// The revision of the listing. This starts at ``1`` and is incremented every time the
+    /// listing is republished.
+    pub revision: u32,
+    /// The description shown in discovery. This is separate from the description of the guild.
+    pub description: String,
+    /// The category the guild is listed under, e.g. ``gaming``.
+    pub category: String,
+    /// The keywords the guild can be searched by.
+    pub keywords: Vec<String>,
+    /// The number of users who upvoted the listing.
+    pub upvotes: u32,
+    /// The number of times users joined the guild through discovery.
+    pub uses: u32,
+    /// When the current revision of the listing was published.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub published_at: DateTime<Utc>,
+}
+
+/// The result of a guild discovery search.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct DiscoverySearchResult {
+    /// The total number of entries that matched the search, regardless of pagination.
+    pub total: u64,
+    /// The entries on this page of results.
+    pub entries: Vec<DiscoveryEntry>,
+}
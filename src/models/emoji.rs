@@ -1,7 +1,5 @@
 use chrono::{DateTime, Utc};
-#[cfg(feature = "client")]
-use serde::Deserialize;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 #[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
 
@@ -19,11 +17,13 @@ pub struct CustomEmoji {
     pub name: String,
     /// The ID of the user that created the emoji. This is `None` if the user has been deleted.
     pub created_by: Option<u64>,
+    /// The IDs of the roles that are allowed to use this emoji. If this is empty, everyone can use
+    /// the emoji.
+    pub roles: Vec<u64>,
 }
 
 /// Represents partial information about a custom emoji or a unicode emoji.
-#[derive(Clone, Debug, Serialize, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "client", derive(Deserialize))]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "utoipa", derive(ToSchema))]
 #[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct PartialEmoji {
@@ -42,6 +42,22 @@ impl From<CustomEmoji> for PartialEmoji {
     }
 }
 
+/// Represents how often a custom emoji has been used, either by a single user or across all
+/// members of a guild.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct EmojiUsage {
+    /// The emoji that was used.
+    pub emoji: CustomEmoji,
+    /// The number of times the emoji was used.
+    pub uses: u64,
+    /// When the emoji was last used.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub last_used_at: DateTime<Utc>,
+}
+
 /// Represents a reaction on a message.
 #[derive(Clone, Debug, Serialize, PartialEq, Eq)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -52,8 +68,10 @@ pub struct Reaction {
     pub message_id: u64,
     /// The emoji this reaction represents.
     pub emoji: PartialEmoji,
-    /// A list of user IDs that have reacted with this emoji.
+    /// A list of user IDs that have reacted with this emoji, ordered by when they reacted.
     pub user_ids: Vec<u64>,
+    /// The IDs of the users in `user_ids` whose reaction is a burst (super) reaction.
+    pub burst_user_ids: Vec<u64>,
     /// A list of timestamps representing when the users reacted with this emoji. The index of the
     /// timestamp corresponds to the index of the user ID in `user_ids`.
     ///
@@ -62,3 +80,18 @@ pub struct Reaction {
     #[cfg_attr(feature = "bincode", bincode(with_serde))]
     pub created_at: Option<Vec<DateTime<Utc>>>,
 }
+
+/// A user that reacted to a message with a specific emoji.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ReactionUser {
+    /// The ID of the user that reacted.
+    pub user_id: u64,
+    /// Whether the reaction is a burst (super) reaction.
+    pub burst: bool,
+    /// When the user reacted.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub created_at: DateTime<Utc>,
+}
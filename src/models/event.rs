@@ -0,0 +1,110 @@
+use crate::Error;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// Where a scheduled event takes place.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduledEventLocation {
+    /// The event takes place in a voice channel of the guild.
+    Voice {
+        /// The ID of the voice channel.
+        channel_id: u64,
+    },
+    /// The event takes place somewhere outside of Adapt.
+    External {
+        /// A description of the location, e.g. an address or a URL.
+        location: String,
+    },
+}
+
+/// The status of a scheduled event.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledEventStatus {
+    /// The event has not started yet.
+    #[default]
+    Scheduled,
+    /// The event is currently taking place.
+    Active,
+    /// The event has ended.
+    Completed,
+    /// The event was cancelled.
+    Cancelled,
+}
+
+impl ScheduledEventStatus {
+    /// Returns the name of the status as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Active => "active",
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether the event has finished, either because it was completed or cancelled.
+    #[inline]
+    #[must_use]
+    pub const fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Cancelled)
+    }
+}
+
+impl FromStr for ScheduledEventStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scheduled" => Ok(Self::Scheduled),
+            "active" => Ok(Self::Active),
+            "completed" => Ok(Self::Completed),
+            "cancelled" => Ok(Self::Cancelled),
+            _ => Err(Error::InternalError {
+                what: None,
+                message: "Database returned invalid scheduled event status".to_string(),
+                debug: None,
+            }),
+        }
+    }
+}
+
+/// Represents an event scheduled in a guild.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ScheduledEvent {
+    /// The ID of the event.
+    pub id: u64,
+    /// The ID of the guild the event is in.
+    pub guild_id: u64,
+    /// The ID of the user that created the event. This is `None` if the user has been deleted.
+    pub creator_id: Option<u64>,
+    /// The name of the event.
+    pub name: String,
+    /// The description of the event, if any.
+    pub description: Option<String>,
+    /// Where the event takes place.
+    pub location: ScheduledEventLocation,
+    /// When the event is scheduled to start.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub start_time: DateTime<Utc>,
+    /// When the event is scheduled to end, if known.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub end_time: Option<DateTime<Utc>>,
+    /// The status of the event.
+    pub status: ScheduledEventStatus,
+    /// The number of users interested in the event.
+    pub interested_count: u32,
+}
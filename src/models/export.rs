@@ -0,0 +1,75 @@
+use crate::models::{ClientSettings, ClientUser, Message, Relationship};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// The version of the export schemas. This is bumped whenever a field is removed from or changes
+/// meaning in any export, so that consumers can reject exports they do not understand. Adding
+/// fields does not bump the version.
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// The format of a data export.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// A single JSON document.
+    #[default]
+    Json,
+    /// Newline-delimited JSON. The first line is the header of the export, and every following
+    /// line is a single item of the export, e.g. a message.
+    #[serde(rename = "ndjson")]
+    NdJson,
+}
+
+/// The header of a channel export. In ND-JSON exports this is the first line.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct ChannelExportHeader {
+    /// The version of the export schema. See [`EXPORT_SCHEMA_VERSION`].
+    pub version: u32,
+    /// The ID of the exported channel.
+    pub channel_id: u64,
+    /// When the export was created.
+    pub exported_at: DateTime<Utc>,
+}
+
+/// An export of the message history of a channel.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct ChannelExport {
+    /// The header of the export.
+    #[serde(flatten)]
+    pub header: ChannelExportHeader,
+    /// All messages in the channel, sorted from oldest to newest. Attachments only include their
+    /// metadata.
+    pub messages: Vec<Message>,
+}
+
+/// An export of all data associated with a user, e.g. for data takeout.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct UserDataExport {
+    /// The version of the export schema. See [`EXPORT_SCHEMA_VERSION`].
+    pub version: u32,
+    /// When the export was created.
+    pub exported_at: DateTime<Utc>,
+    /// The user's account information.
+    pub user: ClientUser,
+    /// The user's client settings.
+    pub settings: ClientSettings,
+    /// The relationships the user has with other users.
+    pub relationships: Vec<Relationship>,
+    /// The IDs of the users, channels and guilds the user has favorited, in order.
+    pub favorites: Vec<u64>,
+    /// The IDs of the guilds the user is a member of.
+    pub guild_ids: Vec<u64>,
+    /// All messages sent by the user that have not been deleted, sorted from oldest to newest.
+    /// Attachments only include their metadata.
+    pub messages: Vec<Message>,
+}
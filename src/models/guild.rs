@@ -1,11 +1,11 @@
 use crate::{
-    models::{CustomEmoji, GuildChannel, Permissions, Role, User},
+    models::{
+        Asset, CustomEmoji, GuildChannel, PartialEmoji, Permissions, PresenceStatus, Role, User,
+    },
     serde_for_bitflags,
 };
 use chrono::{DateTime, Utc};
-#[cfg(feature = "client")]
-use serde::Deserialize;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 #[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
 
@@ -22,6 +22,18 @@ pub enum MaybePartialUser {
     Partial { id: u64 },
 }
 
+impl MaybePartialUser {
+    /// The full user information, if available.
+    #[inline]
+    #[must_use]
+    pub const fn as_full(&self) -> Option<&User> {
+        match self {
+            Self::Full(user) => Some(user),
+            Self::Partial { .. } => None,
+        }
+    }
+}
+
 /// Represents a member of a guild. Members are user objects associated with a guild.
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -35,6 +47,15 @@ pub struct Member {
     pub guild_id: u64,
     /// The nickname of the member. `None` if the member has no nickname.
     pub nick: Option<String>,
+    /// The guild-specific avatar of the member. If the member has no guild avatar, this falls
+    /// back to the user's global avatar. `None` if neither is set.
+    pub guild_avatar: Option<Asset>,
+    /// The guild-specific banner of the member. If the member has no guild banner, this falls
+    /// back to the user's global banner. `None` if neither is set.
+    pub guild_banner: Option<Asset>,
+    /// The guild-specific bio of the member. If the member has no guild bio, this falls back to
+    /// the user's global bio. `None` if neither is set.
+    pub guild_bio: Option<String>,
     /// A list of IDs of the roles that the member has. This could be `None` in some cases.
     pub roles: Option<Vec<u64>>,
     /// The time that the member joined the guild.
@@ -43,6 +64,18 @@ pub struct Member {
     /// The base permissions granted to the member.
     #[cfg_attr(feature = "bincode", bincode(with_serde))]
     pub permissions: Permissions,
+    /// The time that the member accepted the rules of the guild. `None` if the member has not
+    /// accepted the rules.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub rules_accepted_at: Option<DateTime<Utc>>,
+    /// Whether the member has yet to accept the rules of the guild. This is only `true` if the
+    /// guild [requires rules acceptance](GuildFlags::REQUIRES_RULES_ACCEPTANCE). Pending members
+    /// cannot send messages.
+    pub pending: bool,
+    /// The time until which the member is timed out. Timed out members cannot send messages,
+    /// add reactions, or connect to voice channels. `None` if the member is not timed out.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub communication_disabled_until: Option<DateTime<Utc>>,
 }
 
 impl Member {
@@ -69,6 +102,24 @@ impl Member {
     }
 }
 
+/// A ban of a user from a guild.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct GuildBan {
+    /// The user that was banned.
+    pub user: User,
+    /// The ID of the moderator that banned the user. This is `None` if the moderator's account
+    /// has been deleted.
+    pub moderator_id: Option<u64>,
+    /// The reason for the ban, if any.
+    pub reason: Option<String>,
+    /// When the user was banned.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub banned_at: DateTime<Utc>,
+}
+
 /// Represents member counts for a guild.
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -77,11 +128,130 @@ impl Member {
 pub struct GuildMemberCount {
     /// The total number of members in the guild.
     pub total: u32,
-    /// The number of members that are online. If this was part of a partial guild object, then
-    /// this will be `None`.
+    /// The number of members that are online, or `None` if the online count is unknown.
     pub online: Option<u32>,
 }
 
+/// The widget settings of a guild.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct GuildWidgetSettings {
+    /// The ID of the guild these settings are for.
+    pub guild_id: u64,
+    /// Whether the widget is enabled.
+    pub enabled: bool,
+    /// The ID of the channel that invites generated from the widget lead to, if any.
+    pub channel_id: Option<u64>,
+}
+
+/// A member shown in a guild widget. This only includes limited information about the member.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct WidgetMember {
+    /// The ID of the member.
+    pub id: u64,
+    /// The name the member is displayed with in the guild.
+    pub display_name: String,
+    /// The avatar of the member, if any.
+    pub avatar: Option<Asset>,
+    /// The presence status of the member.
+    pub status: PresenceStatus,
+}
+
+/// Public information about a guild that can be embedded as a widget.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct GuildWidget {
+    /// The ID of the guild.
+    pub guild_id: u64,
+    /// The name of the guild.
+    pub name: String,
+    /// The icon of the guild, if any.
+    pub icon: Option<Asset>,
+    /// The ID of the channel that invites generated from the widget lead to, if any.
+    pub channel_id: Option<u64>,
+    /// The number of members that are currently online.
+    pub online_count: u32,
+    /// A limited sample of online members.
+    pub members: Vec<WidgetMember>,
+}
+
+/// A channel highlighted on the welcome screen of a guild.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct WelcomeScreenChannel {
+    /// The ID of the channel.
+    pub channel_id: u64,
+    /// A short description of what the channel is for.
+    pub description: String,
+    /// The emoji shown next to the channel, if any.
+    pub emoji: Option<PartialEmoji>,
+}
+
+/// The welcome screen shown to new members of a guild.
+#[derive(Clone, Debug, Default, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct GuildWelcomeScreen {
+    /// Whether the welcome screen is shown to new members.
+    pub enabled: bool,
+    /// The description shown at the top of the welcome screen, if any.
+    pub description: Option<String>,
+    /// The channels highlighted on the welcome screen, in order.
+    pub channels: Vec<WelcomeScreenChannel>,
+}
+
+/// An option of an onboarding prompt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct OnboardingPromptOption {
+    /// The title of the option.
+    pub title: String,
+    /// A description of the option, if any.
+    pub description: Option<String>,
+    /// The emoji shown next to the option, if any.
+    pub emoji: Option<PartialEmoji>,
+    /// The IDs of the roles assigned to members who pick this option.
+    pub role_ids: Vec<u64>,
+}
+
+/// A question asked to new members of a guild during onboarding, letting them pick roles.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct OnboardingPrompt {
+    /// The question asked.
+    pub title: String,
+    /// Whether at most one option may be picked.
+    pub single_select: bool,
+    /// Whether at least one option must be picked.
+    pub required: bool,
+    /// The options that can be picked.
+    pub options: Vec<OnboardingPromptOption>,
+}
+
+/// The onboarding configuration of a guild.
+#[derive(Clone, Debug, Default, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct GuildOnboarding {
+    /// Whether new members are asked the onboarding prompts.
+    pub enabled: bool,
+    /// The prompts asked to new members, in order. Prompts and their options are referenced by
+    /// their index when answering.
+    pub prompts: Vec<OnboardingPrompt>,
+}
+
 /// Represents a guild with partial information, sometimes referred to as a server.
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -94,10 +264,10 @@ pub struct PartialGuild {
     pub name: String,
     /// The description of the guild.
     pub description: Option<String>,
-    /// The URL of the icon of the guild.
-    pub icon: Option<String>,
-    /// The URL of the banner of the guild.
-    pub banner: Option<String>,
+    /// The icon of the guild.
+    pub icon: Option<Asset>,
+    /// The banner of the guild.
+    pub banner: Option<Asset>,
     /// The ID of the owner of the guild.
     pub owner_id: u64,
     /// Extra information about the guild represented through bitflags.
@@ -167,6 +337,8 @@ bitflags::bitflags! {
         const VERIFIED = 1 << 1;
         /// The guild has a vanity invite URL.
         const VANITY_URL = 1 << 2;
+        /// Members must accept the rules of the guild before they can send messages.
+        const REQUIRES_RULES_ACCEPTANCE = 1 << 3;
     }
 }
 
@@ -0,0 +1,489 @@
+use std::borrow::Cow;
+
+/// The maximum nesting depth of formatting. Formatting nested any deeper is kept as plain text,
+/// which bounds the recursion of the parser on adversarial input.
+pub const MAX_MARKDOWN_DEPTH: usize = 16;
+
+/// Characters that are escaped by [`escape_markdown`].
+const ESCAPED_CHARS: &[u8] = b"\\*_~|`[]()<>@#:";
+
+/// A mention in message content.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MarkdownMention {
+    /// A user mention, in the form `<@id>` or `<@!id>`.
+    User(u64),
+    /// A role mention, in the form `<@&id>`.
+    Role(u64),
+    /// A channel mention, in the form `<#id>`.
+    Channel(u64),
+    /// A mention of everyone, `@everyone`.
+    Everyone,
+    /// A mention of everyone online, `@here`.
+    Here,
+}
+
+/// A node in the syntax tree of message content, produced by [`parse_markdown`]. Text is borrowed
+/// from the parsed content.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarkdownNode<'a> {
+    /// Plain text.
+    Text(&'a str),
+    /// Bold text, ``**text**``.
+    Bold(Vec<MarkdownNode<'a>>),
+    /// Italic text, ``*text*`` or ``_text_``.
+    Italic(Vec<MarkdownNode<'a>>),
+    /// Strikethrough text, ``~~text~~``.
+    Strikethrough(Vec<MarkdownNode<'a>>),
+    /// Spoilered content, ``||text||``.
+    Spoiler(Vec<MarkdownNode<'a>>),
+    /// Inline code, `` `code` ``. The code is not parsed.
+    InlineCode(&'a str),
+    /// A fenced code block. The code is not parsed.
+    CodeBlock {
+        /// The language of the code block, if specified on the opening fence.
+        language: Option<&'a str>,
+        /// The code in the code block.
+        code: &'a str,
+    },
+    /// A mention.
+    Mention(MarkdownMention),
+    /// A custom emoji, in the form `<:name:id>` or `<a:name:id>`.
+    CustomEmoji {
+        /// The name of the emoji.
+        name: &'a str,
+        /// The ID of the emoji.
+        id: u64,
+        /// Whether the emoji is animated.
+        animated: bool,
+    },
+    /// A link, either bare (``https://...``), wrapped in angle brackets to suppress embeds
+    /// (``<https://...>``), or masked (``[label](https://...)``).
+    Link {
+        /// The URL of the link.
+        url: &'a str,
+        /// The label of a masked link, or `None` for bare links.
+        label: Option<Vec<MarkdownNode<'a>>>,
+        /// Whether the link was wrapped in angle brackets to suppress its embed.
+        suppress_embed: bool,
+    },
+}
+
+impl<'a> MarkdownNode<'a> {
+    /// Returns the children of this node, if it has any.
+    #[must_use]
+    pub fn children(&self) -> Option<&[MarkdownNode<'a>]> {
+        match self {
+            Self::Bold(children)
+            | Self::Italic(children)
+            | Self::Strikethrough(children)
+            | Self::Spoiler(children) => Some(children),
+            Self::Link {
+                label: Some(label), ..
+            } => Some(label),
+            _ => None,
+        }
+    }
+}
+
+/// Calls ``f`` on each of the given nodes and all of their descendants, in document order.
+pub fn visit_markdown<'a>(nodes: &[MarkdownNode<'a>], f: &mut impl FnMut(&MarkdownNode<'a>)) {
+    for node in nodes {
+        f(node);
+        if let Some(children) = node.children() {
+            visit_markdown(children, f);
+        }
+    }
+}
+
+/// Returns all mentions in the given nodes, in document order. Mentions in code are not
+/// included.
+#[must_use]
+pub fn markdown_mentions(nodes: &[MarkdownNode]) -> Vec<MarkdownMention> {
+    let mut mentions = Vec::new();
+    visit_markdown(nodes, &mut |node| {
+        if let MarkdownNode::Mention(mention) = node {
+            mentions.push(*mention);
+        }
+    });
+    mentions
+}
+
+/// Parses message content into a syntax tree.
+///
+/// This is a small, forgiving subset of markdown: unterminated formatting is kept as plain text
+/// and the parser never fails. Any ASCII punctuation may be escaped with a backslash.
+#[must_use]
+pub fn parse_markdown(content: &str) -> Vec<MarkdownNode<'_>> {
+    parse_inline(content, 0)
+}
+
+/// Escapes all markdown syntax in the given text, including mentions and custom emojis, so that
+/// it is displayed verbatim when parsed with [`parse_markdown`]. This should be used when
+/// embedding untrusted text, such as user names, in message content.
+#[must_use]
+pub fn escape_markdown(text: &str) -> Cow<'_, str> {
+    if !text.bytes().any(|b| ESCAPED_CHARS.contains(&b)) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        if c.is_ascii() && ESCAPED_CHARS.contains(&(c as u8)) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    Cow::Owned(escaped)
+}
+
+/// Returns whether the character before ``i`` in ``s`` is a word character.
+fn preceded_by_word(s: &str, i: usize) -> bool {
+    s[..i]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Finds the start of the closing ``delim`` in ``s``, searching from ``from``. Escaped
+/// characters are skipped, and a single ``*`` never closes on a ``**``. If the closing
+/// delimiter is followed by more of its character (e.g. ``***``), the last possible position is
+/// returned, so that ``***text***`` is parsed as bold italic text.
+fn find_closing(s: &str, from: usize, delim: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let first = delim.as_bytes()[0];
+    let mut j = from;
+
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            _ if delim == "*" && bytes[j..].starts_with(b"**") => j += 2,
+            _ if bytes[j..].starts_with(delim.as_bytes()) => {
+                while bytes.get(j + delim.len()) == Some(&first) {
+                    j += 1;
+                }
+                return Some(j);
+            }
+            _ => j += 1,
+        }
+    }
+    None
+}
+
+/// Parses the inside of angle brackets at ``s[i..]``, returning the node and the number of bytes
+/// it spans.
+fn parse_angle_brackets(s: &str, i: usize) -> Option<(MarkdownNode<'_>, usize)> {
+    let end = i + s[i..].find('>')?;
+    let inner = &s[i + 1..end];
+    let len = end + 1 - i;
+
+    if inner.starts_with("https://") || inner.starts_with("http://") {
+        if inner.contains(char::is_whitespace) {
+            return None;
+        }
+        return Some((
+            MarkdownNode::Link {
+                url: inner,
+                label: None,
+                suppress_embed: true,
+            },
+            len,
+        ));
+    }
+
+    let parse_id = |id: &str| {
+        if id.bytes().all(|b| b.is_ascii_digit()) {
+            id.parse::<u64>().ok()
+        } else {
+            None
+        }
+    };
+    let mention = |mention| Some((MarkdownNode::Mention(mention), len));
+
+    if let Some(id) = inner.strip_prefix("@&") {
+        mention(MarkdownMention::Role(parse_id(id)?))
+    } else if let Some(id) = inner.strip_prefix('@') {
+        mention(MarkdownMention::User(parse_id(
+            id.strip_prefix('!').unwrap_or(id),
+        )?))
+    } else if let Some(id) = inner.strip_prefix('#') {
+        mention(MarkdownMention::Channel(parse_id(id)?))
+    } else {
+        let (animated, emoji) = match inner.strip_prefix("a:") {
+            Some(emoji) => (true, emoji),
+            None => (false, inner.strip_prefix(':')?),
+        };
+        let (name, id) = emoji.split_once(':')?;
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            return None;
+        }
+        Some((
+            MarkdownNode::CustomEmoji {
+                name,
+                id: parse_id(id)?,
+                animated,
+            },
+            len,
+        ))
+    }
+}
+
+/// Returns the length of the bare URL at ``s[i..]``, if there is one.
+fn bare_url_len(s: &str, i: usize) -> Option<usize> {
+    let rest = &s[i..];
+    let scheme = if rest.starts_with("https://") {
+        8
+    } else if rest.starts_with("http://") {
+        7
+    } else {
+        return None;
+    };
+
+    let len = rest
+        .find(|c: char| c.is_whitespace() || c == '<')
+        .unwrap_or(rest.len());
+    // Trailing punctuation is most likely part of the sentence rather than the URL
+    let len = rest[..len]
+        .trim_end_matches(['.', ',', ':', ';', '!', '?', '"', '\'', ')', ']'])
+        .len();
+    (len > scheme).then_some(len)
+}
+
+/// Parses a masked link, ``[label](url)``, at ``s[i..]``, returning the node and the number of
+/// bytes it spans.
+fn parse_masked_link(s: &str, i: usize, depth: usize) -> Option<(MarkdownNode<'_>, usize)> {
+    let label_end = find_closing(s, i + 1, "]")?;
+    let rest = s[label_end + 1..].strip_prefix('(')?;
+    let url_len = rest.find(')')?;
+    let url = &rest[..url_len];
+
+    if label_end == i + 1 || bare_url_len(url, 0) != Some(url.len()) {
+        return None;
+    }
+    Some((
+        MarkdownNode::Link {
+            url,
+            label: Some(parse_inline(&s[i + 1..label_end], depth + 1)),
+            suppress_embed: false,
+        },
+        label_end + 2 + url_len + 1 - i,
+    ))
+}
+
+/// Parses a fenced code block at ``s[i..]``, returning the node and the number of bytes it spans.
+fn parse_code_block(s: &str, i: usize) -> Option<(MarkdownNode<'_>, usize)> {
+    let end = i + 3 + s[i + 3..].find("```")?;
+    let inner = &s[i + 3..end];
+
+    let (language, code) = match inner.split_once('\n') {
+        Some((first, rest))
+            if !first.is_empty()
+                && first
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "+#-_.".contains(c)) =>
+        {
+            (Some(first), rest)
+        }
+        Some(("", rest)) => (None, rest),
+        _ => (None, inner),
+    };
+    Some((MarkdownNode::CodeBlock { language, code }, end + 3 - i))
+}
+
+/// Parses formatting delimited by ``||``, ``**``, ``~~``, ``*`` or ``_`` at ``s[i..]``, returning
+/// the node and the number of bytes it spans.
+fn parse_formatting<'a>(s: &'a str, i: usize, depth: usize) -> Option<(MarkdownNode<'a>, usize)> {
+    let rest = &s[i..];
+    let (delim, make): (&str, fn(Vec<MarkdownNode<'a>>) -> MarkdownNode<'a>) =
+        if rest.starts_with("||") {
+            ("||", MarkdownNode::Spoiler)
+        } else if rest.starts_with("**") {
+            ("**", MarkdownNode::Bold)
+        } else if rest.starts_with("~~") {
+            ("~~", MarkdownNode::Strikethrough)
+        } else if rest.starts_with('*') || (rest.starts_with('_') && !preceded_by_word(s, i)) {
+            (&rest[..1], MarkdownNode::Italic)
+        } else {
+            return None;
+        };
+
+    let start = i + delim.len();
+    let end = find_closing(s, start, delim).filter(|&end| end > start)?;
+    // Underscores within words, e.g. in snake_case, are not formatting
+    if delim == "_" && s[end + 1..].starts_with(|c: char| c.is_alphanumeric()) {
+        return None;
+    }
+    Some((
+        make(parse_inline(&s[start..end], depth + 1)),
+        end + delim.len() - i,
+    ))
+}
+
+/// Parses ``@everyone`` or ``@here`` at the start of ``rest``, returning the node and the number
+/// of bytes it spans.
+fn parse_everyone(rest: &str) -> Option<(MarkdownNode<'_>, usize)> {
+    [
+        ("@everyone", MarkdownMention::Everyone),
+        ("@here", MarkdownMention::Here),
+    ]
+    .into_iter()
+    .find(|(name, _)| {
+        rest.starts_with(name)
+            && !rest[name.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+    })
+    .map(|(name, mention)| (MarkdownNode::Mention(mention), name.len()))
+}
+
+fn parse_inline(s: &str, depth: usize) -> Vec<MarkdownNode<'_>> {
+    let bytes = s.as_bytes();
+    let mut nodes = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let rest = &s[i..];
+
+        // Escapes
+        if bytes[i] == b'\\' && bytes.get(i + 1).is_some_and(u8::is_ascii_punctuation) {
+            if text_start < i {
+                nodes.push(MarkdownNode::Text(&s[text_start..i]));
+            }
+            text_start = i + 1;
+            i += 2;
+            continue;
+        }
+
+        let can_nest = depth < MAX_MARKDOWN_DEPTH;
+        let parsed = match bytes[i] {
+            // Code, which is never parsed further
+            b'`' if rest.starts_with("```") => parse_code_block(s, i),
+            b'`' => rest[1..]
+                .find('`')
+                .filter(|&end| end > 0)
+                .map(|end| (MarkdownNode::InlineCode(&rest[1..=end]), end + 2)),
+            b'[' if can_nest => parse_masked_link(s, i, depth),
+            // Mentions, custom emojis and links
+            b'<' => parse_angle_brackets(s, i),
+            b'@' if !preceded_by_word(s, i) => parse_everyone(rest),
+            b'h' if !preceded_by_word(s, i) => bare_url_len(s, i).map(|len| {
+                let link = MarkdownNode::Link {
+                    url: &rest[..len],
+                    label: None,
+                    suppress_embed: false,
+                };
+                (link, len)
+            }),
+            _ if can_nest => parse_formatting(s, i, depth),
+            _ => None,
+        };
+
+        if let Some((node, len)) = parsed {
+            if text_start < i {
+                nodes.push(MarkdownNode::Text(&s[text_start..i]));
+            }
+            nodes.push(node);
+            i += len;
+            text_start = i;
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    if text_start < s.len() {
+        nodes.push(MarkdownNode::Text(&s[text_start..]));
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MarkdownNode::*;
+
+    #[test]
+    fn test_formatting() {
+        assert_eq!(
+            parse_markdown("a **b *c*** ~~d~~ ||e|| snake_case_name"),
+            vec![
+                Text("a "),
+                Bold(vec![Text("b "), Italic(vec![Text("c")])]),
+                Text(" "),
+                Strikethrough(vec![Text("d")]),
+                Text(" "),
+                Spoiler(vec![Text("e")]),
+                Text(" snake_case_name"),
+            ]
+        );
+        assert_eq!(
+            parse_markdown("**unterminated"),
+            vec![Text("**unterminated")]
+        );
+    }
+
+    #[test]
+    fn test_code() {
+        assert_eq!(
+            parse_markdown("`*a*` ```rs\nlet x = <@1>;```"),
+            vec![
+                InlineCode("*a*"),
+                Text(" "),
+                CodeBlock {
+                    language: Some("rs"),
+                    code: "let x = <@1>;",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mentions_and_links() {
+        let nodes = parse_markdown(
+            "<@1> <@!2> <@&3> <#4> @everyone a@here <a:wave:5> [**x**](https://a.b) https://c.d.",
+        );
+
+        assert_eq!(
+            markdown_mentions(&nodes),
+            vec![
+                MarkdownMention::User(1),
+                MarkdownMention::User(2),
+                MarkdownMention::Role(3),
+                MarkdownMention::Channel(4),
+                MarkdownMention::Everyone,
+            ]
+        );
+        assert!(nodes.contains(&CustomEmoji {
+            name: "wave",
+            id: 5,
+            animated: true,
+        }));
+        assert!(nodes.contains(&Link {
+            url: "https://a.b",
+            label: Some(vec![Bold(vec![Text("x")])]),
+            suppress_embed: false,
+        }));
+        assert!(nodes.contains(&Link {
+            url: "https://c.d",
+            label: None,
+            suppress_embed: false,
+        }));
+    }
+
+    #[test]
+    fn test_escape() {
+        let text = "**<@1>** @everyone _x_ `y`";
+        let escaped = escape_markdown(text);
+
+        let mut plain = String::new();
+        visit_markdown(&parse_markdown(&escaped), &mut |node| {
+            let Text(text) = node else {
+                panic!("unexpected node {node:?}");
+            };
+            plain.push_str(text);
+        });
+        assert_eq!(plain, text);
+        assert!(matches!(escape_markdown("plain text"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// The kind of a marketplace entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(rename_all = "snake_case")]
+pub enum MarketplaceKind {
+    /// A custom client theme.
+    Theme,
+    /// A client plugin.
+    Plugin,
+}
+
+impl MarketplaceKind {
+    /// Returns the name of the kind as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Theme => "theme",
+            Self::Plugin => "plugin",
+        }
+    }
+
+    /// Constructs a kind from its name, as stored in the database.
+    ///
+    /// # Errors
+    /// * If the kind name is invalid.
+    #[cfg(feature = "db")]
+    pub(crate) fn from_db(name: &str) -> crate::Result<Self> {
+        Ok(match name {
+            "theme" => Self::Theme,
+            "plugin" => Self::Plugin,
+            _ => {
+                return Err(crate::Error::InternalError {
+                    what: None,
+                    message: "Database returned invalid marketplace entry kind".to_string(),
+                    debug: None,
+                })
+            }
+        })
+    }
+}
+
+/// A theme or plugin published to the marketplace.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct MarketplaceEntry {
+    /// The snowflake ID of the entry.
+    pub id: u64,
+    /// Whether this entry is a theme or a plugin.
+    pub kind: MarketplaceKind,
+    /// The ID of the user who published the entry.
+    pub author_id: u64,
+    /// The name of the entry.
+    pub name: String,
+    /// The description of the entry.
+    pub description: Option<String>,
+    /// The most recently published version of the entry, or `None` if no version has been
+    /// published yet.
+    pub latest_version: Option<String>,
+    /// The number of users who have the entry installed.
+    pub installs: u32,
+    /// When the entry was created.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub created_at: DateTime<Utc>,
+}
+
+/// A published version of a marketplace entry. Versions are immutable once published, so a
+/// version string always resolves to the same snapshot.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct MarketplaceRelease {
+    /// The ID of the entry this is a version of.
+    pub entry_id: u64,
+    /// The version string, e.g. ``1.2.0``.
+    pub version: String,
+    /// The notes describing the changes in this version.
+    pub changelog: Option<String>,
+    /// The snapshot of the theme or plugin source at this version.
+    pub content: String,
+    /// When this version was published.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub published_at: DateTime<Utc>,
+}
+
+/// A marketplace entry installed by a user.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct InstalledMarketplaceEntry {
+    /// The installed entry.
+    pub entry: MarketplaceEntry,
+    /// The version the user pinned, or `None` to follow the latest version.
+    pub version: Option<String>,
+    /// When the user installed the entry.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub installed_at: DateTime<Utc>,
+}
@@ -1,4 +1,4 @@
-use super::{Member, Reaction, User};
+use super::{Member, Poll, Reaction, User};
 use crate::serde_for_bitflags;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -116,6 +116,164 @@ pub struct Embed {
     pub fields: Option<Vec<EmbedField>>,
 }
 
+/// Validates that text in an embed is within ``max`` characters, returning its length.
+fn validate_embed_text(field: &str, text: &str, max: usize) -> crate::Result<usize> {
+    let len = text.chars().count();
+    if len > max {
+        return Err(crate::Error::InvalidField {
+            field: field.to_string(),
+            message: format!("Must be at most {max} characters long"),
+        });
+    }
+    Ok(len)
+}
+
+/// Validates that a URL in an embed is an HTTP(S) URL within the URL length limit.
+fn validate_embed_url(field: &str, url: &str) -> crate::Result<()> {
+    let max = crate::limits().max_embed_url_length;
+    if url.len() > max {
+        return Err(crate::Error::InvalidField {
+            field: field.to_string(),
+            message: format!("URL must be at most {max} bytes long"),
+        });
+    }
+
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"));
+    if !host.is_some_and(|host| !host.is_empty() && !host.contains(char::is_whitespace)) {
+        return Err(crate::Error::InvalidField {
+            field: field.to_string(),
+            message: "URL must be a valid HTTP or HTTPS URL".to_string(),
+        });
+    }
+    Ok(())
+}
+
+impl Embed {
+    /// Validates the embed by ensuring that all text is within length limits and that all URLs
+    /// are HTTP(S) URLs. Returns the total number of characters of text
+    /// in the embed, which counts towards the budget checked by [`Self::validate_all`].
+    ///
+    /// Fields in errors are relative to the embed, e.g. ``fields.2.name``.
+    pub fn validate(&self) -> crate::Result<usize> {
+        let limits = crate::limits();
+        let mut total = 0;
+
+        if let Some(title) = &self.title {
+            total += validate_embed_text("title", title, limits.max_embed_title_length)?;
+        }
+        if let Some(description) = &self.description {
+            total += validate_embed_text(
+                "description",
+                description,
+                limits.max_embed_description_length,
+            )?;
+        }
+        if let Some(url) = &self.url {
+            validate_embed_url("url", url)?;
+        }
+        if self.color.is_some_and(|color| color > 0xFF_FFFF) {
+            return Err(crate::Error::InvalidField {
+                field: "color".to_string(),
+                message: "Color must be between 0 and 16777215".to_string(),
+            });
+        }
+        if self.hue.is_some_and(|hue| hue > 100) {
+            return Err(crate::Error::InvalidField {
+                field: "hue".to_string(),
+                message: "Hue must be between 0 and 100".to_string(),
+            });
+        }
+
+        if let Some(author) = &self.author {
+            total +=
+                validate_embed_text("author.name", &author.name, limits.max_embed_title_length)?;
+            if let Some(url) = &author.url {
+                validate_embed_url("author.url", url)?;
+            }
+            if let Some(icon_url) = &author.icon_url {
+                validate_embed_url("author.icon_url", icon_url)?;
+            }
+        }
+        if let Some(footer) = &self.footer {
+            total +=
+                validate_embed_text("footer.text", &footer.text, limits.max_embed_footer_length)?;
+            if let Some(icon_url) = &footer.icon_url {
+                validate_embed_url("footer.icon_url", icon_url)?;
+            }
+        }
+        if let Some(image) = &self.image {
+            validate_embed_url("image", image)?;
+        }
+        if let Some(thumbnail) = &self.thumbnail {
+            validate_embed_url("thumbnail", thumbnail)?;
+        }
+
+        if let Some(fields) = &self.fields {
+            if fields.len() > limits.max_embed_fields {
+                return Err(crate::Error::InvalidField {
+                    field: "fields".to_string(),
+                    message: format!(
+                        "Embed may only have at most {} fields",
+                        limits.max_embed_fields
+                    ),
+                });
+            }
+            for (i, field) in fields.iter().enumerate() {
+                total += validate_embed_text(
+                    &format!("fields.{i}.name"),
+                    &field.name,
+                    limits.max_embed_field_name_length,
+                )?;
+                total += validate_embed_text(
+                    &format!("fields.{i}.value"),
+                    &field.value,
+                    limits.max_embed_field_value_length,
+                )?;
+            }
+        }
+
+        Ok(total)
+    }
+
+    /// Validates all embeds of a message with [`Self::validate`], and ensures that there are not
+    /// too many embeds and that their combined text is within the character budget.
+    ///
+    /// Fields in errors are relative to the message, e.g. ``embeds.0.fields.2.name``.
+    pub fn validate_all(embeds: &[Self]) -> crate::Result<()> {
+        let limits = crate::limits();
+        if embeds.len() > limits.max_embeds {
+            return Err(crate::Error::InvalidField {
+                field: "embeds".to_string(),
+                message: format!("Message may only have at most {} embeds", limits.max_embeds),
+            });
+        }
+
+        let mut total = 0;
+        for (i, embed) in embeds.iter().enumerate() {
+            total += embed.validate().map_err(|err| match err {
+                crate::Error::InvalidField { field, message } => crate::Error::InvalidField {
+                    field: format!("embeds.{i}.{field}"),
+                    message,
+                },
+                err => err,
+            })?;
+        }
+
+        if total > limits.max_embed_characters {
+            return Err(crate::Error::InvalidField {
+                field: "embeds".to_string(),
+                message: format!(
+                    "Embeds may only have at most {} characters of text combined",
+                    limits.max_embed_characters
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Represents a message attachment.
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -132,6 +290,14 @@ pub struct Attachment {
     pub alt: Option<String>,
     /// The size of the attachment, in bytes.
     pub size: u64,
+    /// The MIME type of the attachment, e.g. ``image/png``, if known.
+    pub content_type: Option<String>,
+    /// The width of the attachment in pixels, if it is an image or video.
+    pub width: Option<u32>,
+    /// The height of the attachment in pixels, if it is an image or video.
+    pub height: Option<u32>,
+    /// The lowercase hex-encoded SHA-256 digest of the contents of the attachment, if known.
+    pub hash: Option<String>,
 }
 
 /// Represents the type and info of a message.
@@ -228,6 +394,8 @@ pub struct Message {
     pub flags: MessageFlags,
     /// The reactions received on this message.
     pub reactions: Vec<Reaction>,
+    /// The number of users that starred this message.
+    pub stars: u32,
     /// A list of snowflake IDs of users and/or roles that this message mentions. If this message
     /// also mentions everyone/here in a guild, then the guild ID is also included in this list.
     ///
@@ -241,6 +409,23 @@ pub struct Message {
     pub edited_at: Option<DateTime<Utc>>,
     /// A list of messages that this message references.
     pub references: Vec<MessageReference>,
+    /// The IDs of the stickers sent with this message.
+    pub sticker_ids: Vec<u64>,
+    /// The poll attached to this message, if any.
+    pub poll: Option<Poll>,
+}
+
+/// Removes messages authored by any of the given blocked user IDs, in place. ``blocked`` is
+/// expected to be sorted in ascending order, as returned by ``UserDbExt::fetch_blocked_ids``.
+pub fn filter_blocked(messages: &mut Vec<Message>, blocked: &[u64]) {
+    if blocked.is_empty() {
+        return;
+    }
+    messages.retain(|message| {
+        message
+            .author_id
+            .is_none_or(|author_id| blocked.binary_search(&author_id).is_err())
+    });
 }
 
 bitflags::bitflags! {
@@ -261,3 +446,90 @@ bitflags::bitflags! {
 }
 
 serde_for_bitflags!(u32: MessageFlags);
+
+/// The result of a message search.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct MessageSearchResult {
+    /// The total number of messages that matched the search, regardless of pagination.
+    pub total: u64,
+    /// The messages on this page of results.
+    pub messages: Vec<Message>,
+}
+
+/// A pinned message in a channel.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct Pin {
+    /// The message that was pinned.
+    pub message: Message,
+    /// The user that pinned the message. This is `None` if the user has been deleted or if the
+    /// message was pinned before pinners were recorded.
+    pub pinned_by: Option<User>,
+    /// When the message was pinned.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub pinned_at: DateTime<Utc>,
+    /// The position of the pin in the pins panel of the channel, starting from `0` at the top.
+    pub position: u16,
+}
+
+/// The starboard configuration of a guild. Messages that receive at least `threshold` stars are
+/// reposted in the starboard channel.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct StarboardConfig {
+    /// The ID of the guild this configuration is for.
+    pub guild_id: u64,
+    /// The ID of the channel starboard entries are posted in, or `None` if the starboard is
+    /// disabled.
+    pub channel_id: Option<u64>,
+    /// The number of stars a message needs to be posted in the starboard.
+    pub threshold: u16,
+}
+
+/// A message that has reached the starboard threshold of its guild.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct StarboardEntry {
+    /// The ID of the message that was starred.
+    pub message_id: u64,
+    /// The ID of the channel the starred message is in.
+    pub channel_id: u64,
+    /// The number of stars the message has.
+    pub stars: u32,
+    /// The ID of the message posted in the starboard channel for this entry, or `None` if it has
+    /// not been posted yet.
+    pub starboard_message_id: Option<u64>,
+}
+
+/// A previous revision of a message. A revision is stored every time a message is edited,
+/// holding the content and embeds the message had before the edit.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct MessageRevision {
+    /// The ID of the message this is a revision of.
+    pub message_id: u64,
+    /// The ID of this revision. Revision IDs are sequential per message, starting from `1` for
+    /// the original content of the message.
+    pub revision_id: u32,
+    /// The text content of the message in this revision.
+    pub content: Option<String>,
+    /// The embeds of the message in this revision.
+    pub embeds: Vec<Embed>,
+    /// The time at which this revision was created by an edit, or `None` if this revision is the
+    /// original content of the message.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub edited_at: Option<DateTime<Utc>>,
+    /// The time at which this revision was replaced by a newer edit.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub replaced_at: DateTime<Utc>,
+}
@@ -1,27 +1,57 @@
 //! Common object models consumed by Adapt's services.
 
+pub mod asset;
+pub mod automod;
 pub mod channel;
 pub mod color;
+pub mod discovery;
 pub mod emoji;
+pub mod event;
+pub mod export;
 pub mod guild;
 pub mod invite;
+pub mod markdown;
+pub mod marketplace;
 pub mod message;
+pub mod oauth;
 pub mod permissions;
+pub mod poll;
 pub mod presence;
+pub mod push;
+pub mod report;
 pub mod role;
+pub mod session;
+pub mod sticker;
+pub mod template;
 pub mod user;
+pub mod voice;
 
+pub use asset::*;
+pub use automod::*;
 pub use channel::*;
 pub use color::*;
+pub use discovery::*;
 pub use emoji::*;
+pub use event::*;
+pub use export::*;
 pub use guild::*;
 pub use invite::*;
+pub use markdown::*;
+pub use marketplace::*;
 pub use message::*;
+pub use oauth::*;
 pub use permissions::*;
+pub use poll::*;
 pub use presence::*;
+pub use push::*;
+pub use report::*;
 pub use role::*;
+pub use session::*;
 use std::fmt;
+pub use sticker::*;
+pub use template::*;
 pub use user::*;
+pub use voice::*;
 
 /// An enumeration for the type of a model, which takes up 5 bits in a snowflake.
 #[repr(u8)]
@@ -43,6 +73,16 @@ pub enum ModelType {
     Internal = 6,
     /// The model is an emoji.
     Emoji = 7,
+    /// The model is a sticker.
+    Sticker = 8,
+    /// The model is a login session.
+    Session = 9,
+    /// The model is a theme or plugin published to the marketplace.
+    MarketplaceEntry = 10,
+    /// The model is a report of a message or user.
+    Report = 11,
+    /// The model is an automod rule.
+    AutoModRule = 12,
     /// Unknown model.
     Unknown = 31,
 }
@@ -60,6 +100,11 @@ impl ModelType {
             5 => Self::Role,
             6 => Self::Internal,
             7 => Self::Emoji,
+            8 => Self::Sticker,
+            9 => Self::Session,
+            10 => Self::MarketplaceEntry,
+            11 => Self::Report,
+            12 => Self::AutoModRule,
             _ => Self::Unknown,
         }
     }
@@ -79,6 +124,11 @@ impl fmt::Display for ModelType {
                 Self::Role => "role",
                 Self::Internal => "internal",
                 Self::Emoji => "emoji",
+                Self::Sticker => "sticker",
+                Self::Session => "session",
+                Self::MarketplaceEntry => "marketplace_entry",
+                Self::Report => "report",
+                Self::AutoModRule => "automod_rule",
                 Self::Unknown => "unknown",
             }
         )
@@ -0,0 +1,103 @@
+use crate::serde_for_bitflags;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "client")]
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+bitflags::bitflags! {
+    /// The scopes an OAuth2 access token is granted, limiting what the application can do on
+    /// behalf of the user.
+    #[derive(Default)]
+    pub struct OAuthScopes: i64 {
+        /// Read the user's profile, excluding their email.
+        const IDENTIFY = 1 << 0;
+        /// Read the user's email address.
+        const EMAIL = 1 << 1;
+        /// List the guilds the user is a member of.
+        const GUILDS = 1 << 2;
+        /// Join guilds on behalf of the user.
+        const GUILDS_JOIN = 1 << 3;
+        /// Read messages in channels the user can view.
+        const MESSAGES_READ = 1 << 4;
+        /// Add the bot of the application to a guild. This is only valid for the
+        /// authorization code grant.
+        const BOT = 1 << 5;
+    }
+}
+
+serde_for_bitflags!(i64: OAuthScopes);
+
+impl OAuthScopes {
+    const NAMES: [(Self, &'static str); 6] = [
+        (Self::IDENTIFY, "identify"),
+        (Self::EMAIL, "email"),
+        (Self::GUILDS, "guilds"),
+        (Self::GUILDS_JOIN, "guilds.join"),
+        (Self::MESSAGES_READ, "messages.read"),
+        (Self::BOT, "bot"),
+    ];
+
+    /// Parses scopes from a space-delimited list of scope names, as used by OAuth2. Returns
+    /// `None` if any scope is unknown.
+    #[must_use]
+    pub fn parse(scope: &str) -> Option<Self> {
+        scope
+            .split_whitespace()
+            .try_fold(Self::empty(), |acc, name| {
+                Self::NAMES
+                    .iter()
+                    .find(|(_, n)| *n == name)
+                    .map(|(flag, _)| acc | *flag)
+            })
+    }
+
+    /// Returns the scopes as a space-delimited list of scope names, as used by OAuth2.
+    #[must_use]
+    pub fn to_scope_string(self) -> String {
+        Self::NAMES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// An OAuth2 application, allowing third parties to request scoped access to user accounts.
+/// Every application is connected to a bot, and the ID of the bot is used as the client ID.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct OAuthApplication {
+    /// The client ID of the application. This is the ID of its bot.
+    pub client_id: u64,
+    /// The ID of the user who owns the application.
+    pub owner_id: u64,
+    /// The URIs users may be redirected to after authorizing the application. Redirect URIs in
+    /// authorization requests must match one of these exactly.
+    pub redirect_uris: Vec<String>,
+    /// When the application was created.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Information about a valid OAuth2 access token.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct OAuthTokenInfo {
+    /// The client ID of the application the token was issued to.
+    pub client_id: u64,
+    /// The ID of the user the token acts on behalf of.
+    pub user_id: u64,
+    /// The scopes granted to the token.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub scopes: OAuthScopes,
+    /// When the token expires.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub expires_at: DateTime<Utc>,
+}
@@ -104,7 +104,8 @@ bitflags::bitflags! {
         /// This does not take into account the `CREATE_INVITES` permission, meaning they can revoke
         /// invites even if they cannot create them.
         const MANAGE_INVITES = 1 << 18;
-        /// TA: People with this permission can use emojis found in other servers.
+        /// TA: People with this permission can use emojis found in other servers. Emojis that are
+        /// restricted to specific roles can never be used outside of their server.
         const USE_EXTERNAL_EMOJIS = 1 << 19;
         /// \-: People with this permission can change their own nickname.
         const CHANGE_NICKNAME = 1 << 20;
@@ -140,6 +141,11 @@ bitflags::bitflags! {
         /// channel. This means that despite any overwrites, they will have all permissions
         /// throughout the entire guild.
         const ADMINISTRATOR = 1 << 32;
+        /// TA: People with this permission can use stickers found in other servers.
+        const USE_EXTERNAL_STICKERS = 1 << 33;
+        /// \-: People with this permission can view and resolve reports filed in the guild, and
+        /// are notified of new reports.
+        const VIEW_REPORTS = 1 << 34;
 
         /// Default permissions used for new guilds.
         const DEFAULT = Self::VIEW_CHANNEL.bits
@@ -151,6 +157,7 @@ bitflags::bitflags! {
             | Self::SEND_EMBEDS.bits
             | Self::CREATE_INVITES.bits
             | Self::USE_EXTERNAL_EMOJIS.bits
+            | Self::USE_EXTERNAL_STICKERS.bits
             | Self::CHANGE_NICKNAME.bits
             | Self::CONNECT.bits
             | Self::SPEAK.bits;
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+#[cfg(feature = "client")]
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// An answer of a [`Poll`], along with its results.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct PollAnswer {
+    /// The ID of the answer, which is its index in the poll's list of answers.
+    pub id: u16,
+    /// The text of the answer.
+    pub text: String,
+    /// The number of users that voted for this answer.
+    pub votes: u32,
+}
+
+/// A poll attached to a message.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct Poll {
+    /// The question of the poll.
+    pub question: String,
+    /// The answers of the poll, ordered by ID.
+    pub answers: Vec<PollAnswer>,
+    /// Whether users may vote for more than one answer.
+    pub multi_select: bool,
+    /// When the poll expires. This is `None` if the poll never expires.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether the poll has been finalized. Votes can no longer be added or removed from
+    /// finalized polls, so their results are final.
+    pub finalized: bool,
+}
+
+impl Poll {
+    /// The total number of votes across all answers. In multi-select polls, users that voted for
+    /// multiple answers are counted once per answer.
+    #[must_use]
+    pub fn total_votes(&self) -> u32 {
+        self.answers.iter().map(|answer| answer.votes).sum()
+    }
+}
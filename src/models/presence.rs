@@ -21,6 +21,30 @@ pub enum PresenceStatus {
     Offline,
 }
 
+impl PresenceStatus {
+    /// The precedence of this status when merging statuses across multiple devices. The status
+    /// with the highest precedence is the one shown to other users.
+    #[must_use]
+    pub const fn precedence(self) -> u8 {
+        match self {
+            Self::Dnd => 3,
+            Self::Online => 2,
+            Self::Idle => 1,
+            Self::Offline => 0,
+        }
+    }
+
+    /// Merges this status with another status, returning the one with the higher precedence.
+    #[must_use]
+    pub const fn merge(self, other: Self) -> Self {
+        if other.precedence() > self.precedence() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
 /// Represents the presence state (status and activity) of a user.
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
 #[cfg_attr(feature = "utoipa", derive(ToSchema))]
@@ -32,6 +56,9 @@ pub struct Presence {
     pub status: PresenceStatus,
     /// The custom status of the user, if any.
     pub custom_status: Option<String>,
+    /// When the custom status of the user will be automatically cleared, if ever.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub custom_status_clear_at: Option<DateTime<Utc>>,
     /// The devices the user is present on.
     #[cfg_attr(feature = "bincode", bincode(with_serde))]
     pub devices: Devices,
@@ -40,6 +67,52 @@ pub struct Presence {
     pub online_since: Option<DateTime<Utc>>,
 }
 
+impl Presence {
+    /// Clears the custom status of this presence if it has expired. Returns whether the custom
+    /// status was cleared.
+    pub fn clear_expired_custom_status(&mut self) -> bool {
+        if self
+            .custom_status_clear_at
+            .is_some_and(|clear_at| clear_at <= Utc::now())
+        {
+            self.custom_status = None;
+            self.custom_status_clear_at = None;
+            return true;
+        }
+        false
+    }
+}
+
+/// When a custom status should be automatically cleared.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CustomStatusExpiry {
+    /// Clear the custom status after the given number of minutes.
+    After {
+        /// The number of minutes after which the custom status is cleared.
+        minutes: u32,
+    },
+    /// Clear the custom status at the given time.
+    At {
+        /// The time at which the custom status is cleared.
+        #[cfg_attr(feature = "bincode", bincode(with_serde))]
+        time: DateTime<Utc>,
+    },
+}
+
+impl CustomStatusExpiry {
+    /// Resolves this expiry into the absolute time at which the custom status should be cleared.
+    #[must_use]
+    pub fn clear_at(self) -> DateTime<Utc> {
+        match self {
+            Self::After { minutes } => Utc::now() + chrono::Duration::minutes(i64::from(minutes)),
+            Self::At { time } => time,
+        }
+    }
+}
+
 /// Represents a device a user could be present on. This is provided once during the `identify`
 /// payload.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -55,6 +128,39 @@ pub enum Device {
     Web,
 }
 
+impl Device {
+    /// Returns the name of the device as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Desktop => "desktop",
+            Self::Mobile => "mobile",
+            Self::Web => "web",
+        }
+    }
+
+    /// Constructs a device from its name, as stored in the database.
+    ///
+    /// # Errors
+    /// * If the device name is invalid.
+    #[cfg(feature = "db")]
+    pub(crate) fn from_db(name: &str) -> crate::Result<Self> {
+        Ok(match name {
+            "desktop" => Self::Desktop,
+            "mobile" => Self::Mobile,
+            "web" => Self::Web,
+            _ => {
+                return Err(crate::Error::InternalError {
+                    what: None,
+                    message: "Database returned invalid device".to_string(),
+                    debug: None,
+                })
+            }
+        })
+    }
+}
+
 bitflags::bitflags! {
     /// Represents all of the devices a user is present on.
     #[derive(Default)]
@@ -68,4 +174,21 @@ bitflags::bitflags! {
     }
 }
 
+impl From<Device> for Devices {
+    fn from(device: Device) -> Self {
+        match device {
+            Device::Desktop => Self::DESKTOP,
+            Device::Mobile => Self::MOBILE,
+            Device::Web => Self::WEB,
+        }
+    }
+}
+
+impl FromIterator<Device> for Devices {
+    fn from_iter<I: IntoIterator<Item = Device>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Self::empty(), |devices, device| devices | device.into())
+    }
+}
+
 serde_for_bitflags!(u32: Devices);
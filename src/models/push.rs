@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// The service used to deliver push notifications to a device.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(rename_all = "snake_case")]
+pub enum PushProvider {
+    /// The Web Push protocol, used by browsers.
+    WebPush,
+    /// Apple Push Notification service, used by iOS and macOS devices.
+    Apns,
+    /// Firebase Cloud Messaging, used by Android devices.
+    Fcm,
+}
+
+impl PushProvider {
+    /// Returns the name of the provider as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::WebPush => "web_push",
+            Self::Apns => "apns",
+            Self::Fcm => "fcm",
+        }
+    }
+
+    /// Constructs a provider from its name, as stored in the database.
+    ///
+    /// # Errors
+    /// * If the provider name is invalid.
+    #[cfg(feature = "db")]
+    pub(crate) fn from_db(name: &str) -> crate::Result<Self> {
+        Ok(match name {
+            "web_push" => Self::WebPush,
+            "apns" => Self::Apns,
+            "fcm" => Self::Fcm,
+            _ => {
+                return Err(crate::Error::InternalError {
+                    what: None,
+                    message: "Database returned invalid push provider".to_string(),
+                    debug: None,
+                })
+            }
+        })
+    }
+}
+
+/// The keys used to encrypt Web Push payloads, as provided by the browser.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct WebPushKeys {
+    /// The P-256 ECDH public key of the subscription, base64url-encoded.
+    pub p256dh: String,
+    /// The authentication secret of the subscription, base64url-encoded.
+    pub auth: String,
+}
+
+/// A device subscribed to receive push notifications for a user.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct PushSubscription {
+    /// The ID of the user this subscription belongs to.
+    pub user_id: u64,
+    /// The service used to deliver notifications to the device.
+    pub provider: PushProvider,
+    /// The endpoint notifications are delivered to. For Web Push this is the endpoint URL of the
+    /// subscription, for APNs and FCM this is the device token. Endpoints are unique across all
+    /// users.
+    pub endpoint: String,
+    /// The encryption keys of the subscription. This is only present for Web Push subscriptions.
+    pub keys: Option<WebPushKeys>,
+    /// When the device subscribed.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// Why a message or user was reported.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    /// Unsolicited or repetitive content.
+    Spam,
+    /// Harassment or bullying of another user.
+    Harassment,
+    /// Content that attacks people based on protected characteristics.
+    HateSpeech,
+    /// Sexual or graphic content posted outside of NSFW channels.
+    Nsfw,
+    /// Content that promotes or depicts self-harm.
+    SelfHarm,
+    /// Content that is illegal, e.g. scams or malware.
+    Illegal,
+    /// Any other reason, which should be described in the details of the report.
+    Other,
+}
+
+impl ReportReason {
+    /// Returns the name of the reason as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Spam => "spam",
+            Self::Harassment => "harassment",
+            Self::HateSpeech => "hate_speech",
+            Self::Nsfw => "nsfw",
+            Self::SelfHarm => "self_harm",
+            Self::Illegal => "illegal",
+            Self::Other => "other",
+        }
+    }
+
+    /// Constructs a reason from its name, as stored in the database.
+    ///
+    /// # Errors
+    /// * If the reason name is invalid.
+    #[cfg(feature = "db")]
+    pub(crate) fn from_db(name: &str) -> crate::Result<Self> {
+        Ok(match name {
+            "spam" => Self::Spam,
+            "harassment" => Self::Harassment,
+            "hate_speech" => Self::HateSpeech,
+            "nsfw" => Self::Nsfw,
+            "self_harm" => Self::SelfHarm,
+            "illegal" => Self::Illegal,
+            "other" => Self::Other,
+            _ => {
+                return Err(crate::Error::InternalError {
+                    what: None,
+                    message: "Database returned invalid report reason".to_string(),
+                    debug: None,
+                })
+            }
+        })
+    }
+}
+
+/// The status of a report in the moderation queue.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    /// The report has not been reviewed yet.
+    #[default]
+    Open,
+    /// The report was reviewed and action was taken.
+    Resolved,
+    /// The report was reviewed and no action was taken.
+    Dismissed,
+}
+
+impl ReportStatus {
+    /// Returns the name of the status as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Resolved => "resolved",
+            Self::Dismissed => "dismissed",
+        }
+    }
+
+    /// Constructs a status from its name, as stored in the database.
+    ///
+    /// # Errors
+    /// * If the status name is invalid.
+    #[cfg(feature = "db")]
+    pub(crate) fn from_db(name: &str) -> crate::Result<Self> {
+        Ok(match name {
+            "open" => Self::Open,
+            "resolved" => Self::Resolved,
+            "dismissed" => Self::Dismissed,
+            _ => {
+                return Err(crate::Error::InternalError {
+                    what: None,
+                    message: "Database returned invalid report status".to_string(),
+                    debug: None,
+                })
+            }
+        })
+    }
+}
+
+/// A report of a message or user, filed for review by moderators.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct Report {
+    /// The snowflake ID of the report.
+    pub id: u64,
+    /// The ID of the user who filed the report.
+    pub reporter_id: u64,
+    /// The ID of the guild the report was filed in. This is `None` for reports of DM messages or
+    /// users, which are only visible to platform staff.
+    pub guild_id: Option<u64>,
+    /// The ID of the channel the reported message was sent in, if a message was reported.
+    pub channel_id: Option<u64>,
+    /// The ID of the reported message, if a message was reported.
+    pub message_id: Option<u64>,
+    /// The ID of the reported user. If a message was reported, this is its author.
+    pub target_id: u64,
+    /// Why the report was filed.
+    pub reason: ReportReason,
+    /// Additional details provided by the reporter.
+    pub details: Option<String>,
+    /// The status of the report.
+    pub status: ReportStatus,
+    /// The ID of the moderator who resolved or dismissed the report, if any.
+    pub resolved_by: Option<u64>,
+    /// A note left by the moderator who resolved or dismissed the report.
+    pub resolution_note: Option<String>,
+    /// When the report was filed.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub created_at: DateTime<Utc>,
+    /// When the report was resolved or dismissed, if it was.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
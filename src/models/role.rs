@@ -1,4 +1,4 @@
-use crate::models::{ExtendedColor, PermissionPair};
+use crate::models::{Asset, ExtendedColor, PermissionPair};
 use crate::serde_for_bitflags;
 #[cfg(feature = "client")]
 use serde::Deserialize;
@@ -21,8 +21,8 @@ pub struct Role {
     /// The color of the role, or ``None`` if the role has no color (in which case it inherits the
     /// color).
     pub color: Option<ExtendedColor>,
-    /// The URL of the role's icon, if any, displayed alongside member names in chat.
-    pub icon: Option<String>,
+    /// The role's icon, if any, displayed alongside member names in chat.
+    pub icon: Option<Asset>,
     /// The permissions users with this role have.
     #[cfg_attr(feature = "bincode", bincode(with_serde))]
     pub permissions: PermissionPair,
@@ -38,6 +38,17 @@ pub struct Role {
     pub flags: RoleFlags,
 }
 
+/// A page of the members that have a role.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct RoleMembers {
+    /// The total number of members that have the role, regardless of pagination.
+    pub total: u64,
+    /// The IDs of the members on this page, in ascending order.
+    pub user_ids: Vec<u64>,
+}
+
 bitflags::bitflags! {
     #[derive(Default)]
     pub struct RoleFlags: u32 {
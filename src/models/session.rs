@@ -0,0 +1,58 @@
+use super::Device;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "client")]
+use serde::Deserialize;
+use serde::Serialize;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// A logged in session of a user. Each session is associated with its own token, allowing
+/// sessions to be listed and revoked individually.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct Session {
+    /// The snowflake ID of the session.
+    pub id: u64,
+    /// The ID of the user this session belongs to.
+    pub user_id: u64,
+    /// The name of the device the session was created on, as provided by the client.
+    pub device_name: Option<String>,
+    /// The platform the session was created on, e.g. ``web`` or ``ios``, as provided by the
+    /// client.
+    pub platform: Option<String>,
+    /// The IP address the session was created from.
+    pub ip: Option<String>,
+    /// When the session was created.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub created_at: DateTime<Utc>,
+    /// When the session was last used to authenticate.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub last_used_at: DateTime<Utc>,
+}
+
+/// The device a session was last seen on, used to determine which devices a user is active on.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct SessionDevice {
+    /// The ID of the session.
+    pub session_id: u64,
+    /// The ID of the user the session belongs to.
+    pub user_id: u64,
+    /// The type of device the session was last seen on.
+    pub device: Device,
+    /// When the session was last seen on the device.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub last_seen_at: DateTime<Utc>,
+}
+
+impl SessionDevice {
+    /// Whether the session was seen on the device since the given time.
+    #[must_use]
+    pub fn is_active_since(&self, since: DateTime<Utc>) -> bool {
+        self.last_seen_at >= since
+    }
+}
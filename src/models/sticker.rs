@@ -0,0 +1,84 @@
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// The file format of a sticker.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(rename_all = "snake_case")]
+pub enum StickerFormat {
+    /// A static PNG image.
+    Png,
+    /// An animated PNG image.
+    Apng,
+    /// An animated GIF image.
+    Gif,
+    /// A Lottie animation, stored as JSON.
+    Lottie,
+}
+
+impl StickerFormat {
+    /// Returns the name of the format as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Apng => "apng",
+            Self::Gif => "gif",
+            Self::Lottie => "lottie",
+        }
+    }
+
+    /// Returns the file extension of stickers in this format.
+    #[inline]
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Png | Self::Apng => "png",
+            Self::Gif => "gif",
+            Self::Lottie => "json",
+        }
+    }
+}
+
+impl FromStr for StickerFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(Self::Png),
+            "apng" => Ok(Self::Apng),
+            "gif" => Ok(Self::Gif),
+            "lottie" => Ok(Self::Lottie),
+            _ => Err(Error::InternalError {
+                what: None,
+                message: "Database returned invalid sticker format".to_string(),
+                debug: None,
+            }),
+        }
+    }
+}
+
+/// Represents a custom sticker in a guild.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct Sticker {
+    /// The ID of the sticker.
+    pub id: u64,
+    /// The ID of the guild the sticker is in.
+    pub guild_id: u64,
+    /// The name of the sticker.
+    pub name: String,
+    /// A list of keywords used to find the sticker, e.g. in autocomplete.
+    pub tags: Vec<String>,
+    /// The file format of the sticker.
+    pub format: StickerFormat,
+    /// The ID of the user that created the sticker. This is `None` if the user has been deleted.
+    pub created_by: Option<u64>,
+}
@@ -0,0 +1,126 @@
+use crate::models::{ExtendedColor, ForumSortOrder, GuildFlags, PermissionPair, RoleFlags};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// The version of the guild template schema. This is bumped whenever a field is removed from or
+/// changes meaning in [`GuildTemplate`], so that templates of an unknown version can be rejected.
+pub const GUILD_TEMPLATE_VERSION: u32 = 1;
+
+/// A snapshot of the structure of a guild which can be used to create new guilds.
+///
+/// Templates only capture structure: roles, channels, categories, permission overwrites and
+/// settings. Members, messages, emojis, forum tags, invites and assets such as icons are never
+/// included. Since templates are not tied to any guild, roles and channels are referenced by
+/// template-local IDs rather than snowflakes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct GuildTemplate {
+    /// The version of the template schema. See [`GUILD_TEMPLATE_VERSION`].
+    pub version: u32,
+    /// The name of the guild the template was created from.
+    pub name: String,
+    /// The description of the guild the template was created from, if any.
+    pub description: Option<String>,
+    /// The flags of the guild. Only flags that represent settings are kept, i.e.
+    /// `REQUIRES_RULES_ACCEPTANCE`.
+    pub flags: GuildFlags,
+    /// The roles of the guild, excluding managed roles. The default role always has an ID of 0.
+    pub roles: Vec<TemplateRole>,
+    /// The channels of the guild, excluding threads.
+    pub channels: Vec<TemplateChannel>,
+}
+
+/// A role in a [`GuildTemplate`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct TemplateRole {
+    /// The template-local ID of the role. The default role always has an ID of 0.
+    pub id: u32,
+    /// The name of the role.
+    pub name: String,
+    /// The color of the role, if any.
+    pub color: Option<ExtendedColor>,
+    /// The permissions of the role.
+    pub permissions: PermissionPair,
+    /// The position of the role in the role hierarchy.
+    pub position: u16,
+    /// The flags of the role.
+    pub flags: RoleFlags,
+}
+
+/// A channel in a [`GuildTemplate`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct TemplateChannel {
+    /// The template-local ID of the channel.
+    pub id: u32,
+    /// The template-local ID of the parent category of the channel, if any.
+    pub parent_id: Option<u32>,
+    /// Information about the channel.
+    #[serde(flatten)]
+    pub info: TemplateChannelInfo,
+    /// The name of the channel.
+    pub name: String,
+    /// The accent color of the channel, if any.
+    pub color: Option<ExtendedColor>,
+    /// The position of the channel in the channel list.
+    pub position: u16,
+    /// The role permission overwrites of the channel. Overwrites targeting members are not kept.
+    pub overwrites: Vec<TemplateOverwrite>,
+}
+
+/// Type-specific information and settings of a [`TemplateChannel`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TemplateChannelInfo {
+    /// A text channel.
+    Text {
+        /// The topic of the channel, if any.
+        topic: Option<String>,
+        /// Whether the channel is NSFW.
+        nsfw: bool,
+        /// Whether the channel is locked.
+        locked: bool,
+        /// The slowmode delay of the channel, in milliseconds.
+        slowmode: u32,
+    },
+    /// An announcement channel.
+    Announcement {
+        /// The topic of the channel, if any.
+        topic: Option<String>,
+        /// Whether the channel is NSFW.
+        nsfw: bool,
+        /// Whether the channel is locked.
+        locked: bool,
+        /// The slowmode delay of the channel, in milliseconds.
+        slowmode: u32,
+    },
+    /// A voice channel.
+    Voice {
+        /// The maximum number of users that can be in the channel at once, or 0 if unlimited.
+        user_limit: u16,
+    },
+    /// A category channel.
+    Category,
+    /// A forum channel. Forum tags are not kept.
+    Forum {
+        /// The topic or guidelines of the forum, if any.
+        topic: Option<String>,
+        /// Whether the forum is NSFW.
+        nsfw: bool,
+        /// The order in which posts are sorted by default.
+        default_sort_order: ForumSortOrder,
+    },
+}
+
+/// A role permission overwrite in a [`TemplateChannel`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct TemplateOverwrite {
+    /// The template-local ID of the role this overwrite applies to.
+    pub role_id: u32,
+    /// The permissions of the overwrite.
+    pub permissions: PermissionPair,
+}
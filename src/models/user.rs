@@ -1,9 +1,8 @@
-use std::collections::HashMap;
-
 #[cfg(feature = "db")]
 use crate::db::{DbRelationship, DbRelationshipType};
-use crate::models::Permissions;
+use crate::models::{Asset, Permissions};
 use crate::serde_for_bitflags;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
@@ -23,12 +22,17 @@ pub struct User {
     pub username: String,
     /// The display name of the user. This is `None` if the user has no display name.
     pub display_name: Option<String>,
-    /// The URL of the user's avatar. This is `None` if the user has no avatar.
-    pub avatar: Option<String>,
-    /// The URL of the user's banner. This is `None` if the user has no banner.
-    pub banner: Option<String>,
+    /// The user's avatar. This is `None` if the user has no avatar.
+    pub avatar: Option<Asset>,
+    /// The user's banner. This is `None` if the user has no banner.
+    pub banner: Option<Asset>,
     /// The user's bio. This is `None` if the user has no bio.
     pub bio: Option<String>,
+    /// The user's pronouns, e.g. ``they/them``. This is `None` if the user has not set any.
+    pub pronouns: Option<String>,
+    /// The accent color of the user's profile, as an RGB integer. This is `None` if the user has
+    /// no accent color, in which case it should be derived from their avatar.
+    pub accent_color: Option<u32>,
     /// A bitmask of extra information associated with this user.
     #[cfg_attr(feature = "bincode", bincode(with_serde))]
     pub flags: UserFlags,
@@ -66,10 +70,25 @@ pub struct GuildFolderInfo {
     pub color: u32,
 }
 
+/// A private note the client user keeps on another user. Notes are only visible to the user who
+/// wrote them.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct UserNote {
+    /// The ID of the user the note is about.
+    pub user_id: u64,
+    /// The content of the note.
+    pub note: String,
+}
+
 /// Represents a folder that contains a collection of guilds. This is only shown in the client's UI.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "utoipa", derive(ToSchema))]
 pub struct GuildFolder {
+    /// The ID of the folder, or `None` for the root folder.
+    pub id: Option<u64>,
     /// The path of the folder, with the top-level folder first.
     ///
     /// This is `None` if this folder represents the collection of guilds
@@ -80,6 +99,27 @@ pub struct GuildFolder {
     pub guilds: Vec<u64>,
 }
 
+/// Client-side preferences of a user that are synced across devices.
+#[derive(Clone, Debug, Default, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ClientSettings {
+    /// The order of guilds in the sidebar, organized into folders. This is managed through
+    /// guild folders rather than by editing the client settings.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub guild_order: Vec<GuildFolder>,
+    /// The IDs of DM channels pinned to the top of the DM list, in order from top to bottom.
+    pub dm_channel_order: Vec<u64>,
+    /// The ID of the marketplace theme applied to the client, or `None` for the default theme.
+    pub theme: Option<u64>,
+    /// The IDs of the marketplace plugins enabled on the client.
+    pub plugins: Vec<u64>,
+    /// The locale the client is displayed in as a BCP 47 language tag, e.g. ``en-US``, or `None`
+    /// to use the locale of the device.
+    pub locale: Option<String>,
+}
+
 /// Represents user info about the client. This has other information that is not available to the
 /// public, such as emails, guilds, and relationships (friends and blocked users).
 #[derive(Clone, Debug, Default, Serialize)]
@@ -119,9 +159,25 @@ pub struct ClientUser {
     /// Bitmask of client settings.
     #[cfg_attr(feature = "bincode", bincode(with_serde))]
     pub settings: Settings,
-    /// A map for notification settings override.
+    /// A list of notification settings overrides.
+    pub notification_override: Vec<NotificationOverride>,
+}
+
+/// The privacy configurations of a user.
+#[derive(Copy, Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct PrivacySettings {
+    /// Controls who can open and/or send direct messages to the user.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub dm_privacy: PrivacyConfiguration,
+    /// Controls who can add the user to group DMs.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub group_dm_privacy: PrivacyConfiguration,
+    /// Controls who can request to add the user as a friend.
     #[cfg_attr(feature = "bincode", bincode(with_serde))]
-    pub notification_override: HashMap<u64, NotificationFlags>,
+    pub friend_request_privacy: PrivacyConfiguration,
 }
 
 bitflags::bitflags! {
@@ -137,8 +193,11 @@ bitflags::bitflags! {
 bitflags::bitflags! {
     #[derive(Default)]
     pub struct NotificationFlags: i16 {
+        /// Notify for all messages.
         const ALL = 1 << 0;
+        /// Notify for all mentions, including role mentions and mentions of everyone.
         const ALL_MENTIONS = 1 << 1;
+        /// Notify only when the user is mentioned directly.
         const DIRECT_MENTIONS = 1 << 2;
         const HIGHLIGHTS = 1 << 3;
     }
@@ -147,6 +206,219 @@ bitflags::bitflags! {
 serde_for_bitflags!(i32: Settings);
 serde_for_bitflags!(i16: NotificationFlags);
 
+/// How a message mentions a specific user, used to decide whether the user should be notified.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(rename_all = "snake_case")]
+pub enum MentionType {
+    /// The message does not mention the user.
+    None,
+    /// The message mentions the user directly.
+    Direct,
+    /// The message mentions a role the user has.
+    Role,
+    /// The message mentions everyone in the guild.
+    Everyone,
+}
+
+impl MentionType {
+    /// Determines how a message with the given mentions mentions the user. ``role_ids`` are the
+    /// IDs of the roles the user has in the guild of the message, if any. Direct mentions take
+    /// precedence over role mentions, which take precedence over mentions of everyone.
+    #[must_use]
+    pub fn for_user(
+        mentions: &[u64],
+        user_id: u64,
+        role_ids: &[u64],
+        guild_id: Option<u64>,
+    ) -> Self {
+        if mentions.contains(&user_id) {
+            Self::Direct
+        } else if role_ids.iter().any(|id| mentions.contains(id)) {
+            Self::Role
+        } else if guild_id.is_some_and(|id| mentions.contains(&id)) {
+            Self::Everyone
+        } else {
+            Self::None
+        }
+    }
+}
+
+impl NotificationFlags {
+    /// The flags used when no override applies. In guilds, users are only notified of mentions
+    /// by default, while in DMs and group DMs users are notified of all messages.
+    #[inline]
+    #[must_use]
+    pub const fn default_for(in_guild: bool) -> Self {
+        if in_guild {
+            Self::ALL_MENTIONS
+        } else {
+            Self::ALL
+        }
+    }
+
+    /// Returns whether these flags allow a notification for a message with the given mention
+    /// type.
+    #[must_use]
+    pub const fn allows(self, mention: MentionType) -> bool {
+        if self.contains(Self::ALL) {
+            return true;
+        }
+        match mention {
+            MentionType::Direct => self.intersects(Self::ALL_MENTIONS.union(Self::DIRECT_MENTIONS)),
+            MentionType::Role | MentionType::Everyone => self.contains(Self::ALL_MENTIONS),
+            MentionType::None => false,
+        }
+    }
+}
+
+/// The target of a notification settings override.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum NotificationTarget {
+    /// The override applies to all channels in a guild.
+    Guild(u64),
+    /// The override applies to all channels in a category, taking precedence over the guild.
+    Category(u64),
+    /// The override applies to a single channel, including DM channels. This takes precedence
+    /// over both the category and the guild of the channel.
+    Channel(u64),
+    /// The override applies to all messages sent by a user.
+    User(u64),
+}
+
+impl NotificationTarget {
+    /// Returns the ID of the target.
+    #[inline]
+    #[must_use]
+    pub const fn id(self) -> u64 {
+        match self {
+            Self::Guild(id) | Self::Category(id) | Self::Channel(id) | Self::User(id) => id,
+        }
+    }
+
+    /// Returns the name of the target type as it is stored in the database.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Guild(_) => "guild",
+            Self::Category(_) => "category",
+            Self::Channel(_) => "channel",
+            Self::User(_) => "user",
+        }
+    }
+
+    /// Constructs a target from its type name and ID, as stored in the database.
+    ///
+    /// # Errors
+    /// * If the target type is invalid.
+    #[cfg(feature = "db")]
+    pub(crate) fn from_db(kind: &str, id: i64) -> crate::Result<Self> {
+        let id = id as u64;
+        Ok(match kind {
+            "guild" => Self::Guild(id),
+            "category" => Self::Category(id),
+            "channel" => Self::Channel(id),
+            "user" => Self::User(id),
+            _ => {
+                return Err(crate::Error::InternalError {
+                    what: None,
+                    message: "Database returned invalid notification target type".to_string(),
+                    debug: None,
+                })
+            }
+        })
+    }
+}
+
+/// A notification settings override for a specific target.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct NotificationOverride {
+    /// The target this override applies to.
+    pub target: NotificationTarget,
+    /// The notification flags to use for the target.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub flags: NotificationFlags,
+    /// If set, the target is muted until this time, after which ``flags`` take effect again.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub mute_until: Option<DateTime<Utc>>,
+}
+
+impl NotificationOverride {
+    /// Resolves the notification flags from the given overrides. ``targets`` should be ordered
+    /// from most to least specific, e.g. ``[channel, category, guild]``, and the flags of the
+    /// first target that has an override are returned. This makes category-level overrides
+    /// cascade to all channels in the category unless a channel has its own override.
+    ///
+    /// Returns `None` if none of the targets have an override.
+    #[must_use]
+    pub fn resolve(
+        overrides: &[Self],
+        targets: &[NotificationTarget],
+    ) -> Option<NotificationFlags> {
+        targets.iter().find_map(|target| {
+            overrides
+                .iter()
+                .find(|o| o.target == *target)
+                .map(|o| o.flags)
+        })
+    }
+
+    /// Returns whether this override is currently muting its target.
+    #[must_use]
+    pub fn is_muted(&self) -> bool {
+        self.mute_until.is_some_and(|until| until > Utc::now())
+    }
+
+    /// Returns whether any of the given targets is currently muted. Unlike notification flags,
+    /// mutes always cascade, i.e. muting a guild mutes all of its channels regardless of their
+    /// own overrides.
+    #[must_use]
+    pub fn resolve_muted(overrides: &[Self], targets: &[NotificationTarget]) -> bool {
+        overrides
+            .iter()
+            .any(|o| o.is_muted() && targets.contains(&o.target))
+    }
+
+    /// Returns the targets to resolve notification flags for a channel, ordered from most to
+    /// least specific. ``category_id`` is the parent category of the channel, if any.
+    #[must_use]
+    pub fn targets_for_channel(
+        channel_id: u64,
+        category_id: Option<u64>,
+        guild_id: Option<u64>,
+    ) -> Vec<NotificationTarget> {
+        let mut targets = vec![NotificationTarget::Channel(channel_id)];
+        targets.extend(category_id.map(NotificationTarget::Category));
+        targets.extend(guild_id.map(NotificationTarget::Guild));
+        targets
+    }
+}
+
+impl ClientUser {
+    /// Resolves the notification flags the client has set for the given channel, cascading from
+    /// the channel to its category and then its guild. See [`NotificationOverride::resolve`].
+    #[must_use]
+    pub fn notification_flags_for(
+        &self,
+        channel_id: u64,
+        category_id: Option<u64>,
+        guild_id: Option<u64>,
+    ) -> Option<NotificationFlags> {
+        NotificationOverride::resolve(
+            &self.notification_override,
+            &NotificationOverride::targets_for_channel(channel_id, category_id, guild_id),
+        )
+    }
+}
+
 impl std::ops::Deref for ClientUser {
     type Target = User;
 
@@ -268,9 +540,11 @@ impl Relationship {
                 id: data.target_id as _,
                 username: data.username,
                 display_name: data.display_name,
-                avatar: data.avatar,
-                banner: data.banner,
+                avatar: data.avatar.map(Asset::from_raw),
+                banner: data.banner.map(Asset::from_raw),
                 bio: data.bio,
+                pronouns: data.pronouns,
+                accent_color: data.accent_color.map(|color| color as u32),
                 flags: UserFlags::from_bits_truncate(data.flags as _),
             },
             kind: RelationshipType::from(data.kind),
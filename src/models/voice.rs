@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// Represents the state of a user connected to a voice channel.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+#[allow(clippy::struct_excessive_bools)]
+pub struct VoiceState {
+    /// The ID of the user this voice state is for.
+    pub user_id: u64,
+    /// The ID of the guild the voice channel is in, or `None` if this is a DM call.
+    pub guild_id: Option<u64>,
+    /// The ID of the voice channel the user is connected to.
+    pub channel_id: u64,
+    /// Whether the user was muted by a moderator.
+    #[serde(default)]
+    pub mute: bool,
+    /// Whether the user was deafened by a moderator.
+    #[serde(default)]
+    pub deaf: bool,
+    /// Whether the user muted themselves.
+    #[serde(default)]
+    pub self_mute: bool,
+    /// Whether the user deafened themselves.
+    #[serde(default)]
+    pub self_deaf: bool,
+    /// Whether the user is streaming their screen.
+    #[serde(default)]
+    pub streaming: bool,
+    /// When the user connected to the voice channel.
+    #[cfg_attr(feature = "bincode", bincode(with_serde))]
+    pub connected_at: DateTime<Utc>,
+}
@@ -0,0 +1,308 @@
+//! Assembles the OpenAPI document describing the models and payloads of the Adapt API.
+//!
+//! Paths are not included, as the routes themselves are defined by the webserver. The webserver
+//! should [merge](utoipa::openapi::OpenApi::merge) its paths into the document returned by
+//! [`openapi`] before serving it.
+
+use crate::{
+    error::{Error, ErrorCode, FieldError, MalformedBodyErrorType, QuotaType, UserInteractionType},
+    http,
+    models::*,
+};
+use utoipa::{
+    openapi::{
+        schema::{AllOfBuilder, ObjectBuilder, Schema},
+        ContentBuilder, Ref, RefOr, ResponseBuilder,
+    },
+    OpenApi,
+};
+
+/// The error responses registered as components, by HTTP status code.
+pub const ERROR_RESPONSES: [(u16, &str); 7] = [
+    (400, "The request was malformed or failed validation."),
+    (401, "The request is missing valid authentication."),
+    (
+        403,
+        "You do not have permission to perform the requested action.",
+    ),
+    (404, "The requested entity was not found."),
+    (409, "The requested entity conflicts with an existing one."),
+    (429, "You are being ratelimited."),
+    (500, "An internal server error occurred."),
+];
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Adapt",
+        description = "The Adapt chat platform API.",
+        license(name = "AGPL-3.0"),
+    ),
+    components(schemas(
+        // errors
+        Error,
+        ErrorCode,
+        FieldError,
+        MalformedBodyErrorType,
+        QuotaType,
+        UserInteractionType,
+        // models
+        Asset,
+        Attachment,
+        AutoModAction,
+        AutoModRule,
+        AutoModTrigger,
+        Bot,
+        Channel,
+        ChannelExport,
+        ChannelExportHeader,
+        ChannelFollow,
+        ChannelType,
+        ClientSettings,
+        ClientUser,
+        CustomEmoji,
+        CustomStatusExpiry,
+        Device,
+        DiscoveryEntry,
+        DiscoverySearchResult,
+        DmChannel,
+        DmChannelInfo,
+        Embed,
+        EmbedAuthor,
+        EmbedField,
+        EmbedFooter,
+        EmbedType,
+        EmojiUsage,
+        ExportFormat,
+        ExtendedColor,
+        ForumChannelInfo,
+        ForumSortOrder,
+        ForumTag,
+        Gradient,
+        GradientStop,
+        Guild,
+        GuildBan,
+        GuildChannel,
+        GuildChannelInfo,
+        GuildFolder,
+        GuildFolderInfo,
+        GuildMemberCount,
+        GuildOnboarding,
+        GuildTemplate,
+        GuildWelcomeScreen,
+        GuildWidget,
+        GuildWidgetSettings,
+        InstalledMarketplaceEntry,
+        Invite,
+        MarketplaceEntry,
+        MarketplaceKind,
+        MarketplaceRelease,
+        MaybePartialMessage,
+        MaybePartialUser,
+        Member,
+        MemberOrUser,
+        MentionType,
+        MergedChannelInfo,
+        Message,
+        MessageEmbedFieldAlignment,
+        MessageInfo,
+        MessageReference,
+        MessageRevision,
+        MessageSearchResult,
+        NotificationOverride,
+        NotificationTarget,
+        OAuthApplication,
+        OAuthTokenInfo,
+        OnboardingPrompt,
+        OnboardingPromptOption,
+        PartialEmoji,
+        PartialGuild,
+        PermissionOverwrite,
+        PermissionPair,
+        Pin,
+        Poll,
+        PollAnswer,
+        Presence,
+        PresenceStatus,
+        PrivacySettings,
+        PushProvider,
+        PushSubscription,
+        Reaction,
+        ReactionUser,
+        Relationship,
+        RelationshipType,
+        Report,
+        ReportReason,
+        ReportStatus,
+        Role,
+        RoleMembers,
+        ScheduledEvent,
+        ScheduledEventLocation,
+        ScheduledEventStatus,
+        Session,
+        SessionDevice,
+        SidebarSection,
+        StarboardConfig,
+        StarboardEntry,
+        Sticker,
+        StickerFormat,
+        TemplateChannel,
+        TemplateChannelInfo,
+        TemplateOverwrite,
+        TemplateRole,
+        TextBasedGuildChannelInfo,
+        ThreadInfo,
+        ThreadMember,
+        User,
+        UserDataExport,
+        UserNote,
+        VoiceState,
+        WebPushKeys,
+        WelcomeScreenChannel,
+        WidgetMember,
+        // payloads
+        http::auth::LoginRequest,
+        http::auth::LoginResponse,
+        http::auth::MfaCodePayload,
+        http::auth::RecoveryCodesResponse,
+        http::auth::TokenRetrievalMethod,
+        http::auth::TotpEnrollmentResponse,
+        http::automod::CreateAutoModRulePayload,
+        http::automod::EditAutoModRulePayload,
+        http::oauth::AuthorizePayload,
+        http::oauth::CreateOAuthApplicationPayload,
+        http::oauth::OAuthClientCredentials,
+        http::oauth::OAuthGrant,
+        http::oauth::OAuthTokenRequest,
+        http::oauth::OAuthTokenResponse,
+        http::oauth::RevokeOAuthTokenPayload,
+        http::channel::CreateDmChannelPayload,
+        http::channel::CreateForumPostPayload,
+        http::channel::CreateForumTagPayload,
+        http::channel::CreateGuildChannelInfo,
+        http::channel::CreateGuildChannelPayload,
+        http::channel::CreateThreadPayload,
+        http::channel::EditChannelPayload,
+        http::channel::EditChannelPositionPayload,
+        http::channel::EditChannelPositionsPayload,
+        http::emoji::CreateEmojiPayload,
+        http::emoji::EditEmojiPayload,
+        http::event::CreateScheduledEventPayload,
+        http::event::EditScheduledEventPayload,
+        http::guild::CloneGuildOptions,
+        http::guild::CreateGuildPayload,
+        http::guild::DeleteGuildPayload,
+        http::guild::DiscoverySort,
+        http::guild::EditGuildPayload,
+        http::guild::EditGuildWidgetPayload,
+        http::guild::EditOnboardingPayload,
+        http::guild::EditStarboardPayload,
+        http::guild::EditWelcomeScreenPayload,
+        http::guild::OnboardingAnswer,
+        http::guild::PublishDiscoveryEntryPayload,
+        http::invite::CreateInvitePayload,
+        http::marketplace::CreateMarketplaceEntryPayload,
+        http::marketplace::InstallMarketplaceEntryPayload,
+        http::marketplace::PublishMarketplaceVersionPayload,
+        http::member::AddBotPayload,
+        http::member::EditClientMemberPayload,
+        http::member::EditMemberPayload,
+        http::message::CreateMessagePayload,
+        http::message::CreatePollPayload,
+        http::message::EditMessagePayload,
+        http::message::MessageSearchOrder,
+        http::report::CreateReportPayload,
+        http::report::ResolveReportPayload,
+        http::role::CreateRolePayload,
+        http::role::EditRolePayload,
+        http::sticker::CreateStickerPayload,
+        http::sticker::EditStickerPayload,
+        http::user::ChangeEmailPayload,
+        http::user::ChangePasswordPayload,
+        http::user::CreateBotPayload,
+        http::user::CreateBotResponse,
+        http::user::CreateGuildFolderPayload,
+        http::user::CreateUserPayload,
+        http::user::CreateUserResponse,
+        http::user::DeleteBotPayload,
+        http::user::DeleteUserPayload,
+        http::user::EditBotPayload,
+        http::user::EditClientSettingsPayload,
+        http::user::EditGuildFolderPayload,
+        http::user::EditPrivacyPayload,
+        http::user::EditUserNotePayload,
+        http::user::EditUserPayload,
+        http::user::MoveGuildPayload,
+        http::user::RegenerateBotTokenPayload,
+        http::user::RegisterPushSubscriptionPayload,
+        http::user::RemovePushSubscriptionPayload,
+        http::user::SendFriendRequestPayload,
+    )),
+)]
+struct ApiDoc;
+
+/// Returns the OpenAPI document with every model and payload registered as a schema, and an
+/// ``Error{status}`` response component (e.g. ``Error404``) for each status code in
+/// [`ERROR_RESPONSES`].
+#[must_use]
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    let components = doc.components.get_or_insert_with(Default::default);
+
+    // the `code` field is added by the `Serialize` impl of `Error`, so the derived schema lacks it
+    if let Some(error) = components.schemas.remove("Error") {
+        components.schemas.insert(
+            "Error".to_string(),
+            RefOr::T(Schema::AllOf(
+                AllOfBuilder::new()
+                    .item(error)
+                    .item(
+                        ObjectBuilder::new()
+                            .property("code", Ref::from_schema_name("ErrorCode"))
+                            .required("code"),
+                    )
+                    .build(),
+            )),
+        );
+    }
+
+    for (status, description) in ERROR_RESPONSES {
+        components.responses.insert(
+            format!("Error{status}"),
+            ResponseBuilder::new()
+                .description(description)
+                .content(
+                    "application/json",
+                    ContentBuilder::new()
+                        .schema(Ref::from_schema_name("Error"))
+                        .build(),
+                )
+                .build()
+                .into(),
+        );
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_schema_requires_code() {
+        let doc = openapi();
+        let components = doc.components.expect("components should be registered");
+
+        let Some(RefOr::T(Schema::AllOf(error))) = components.schemas.get("Error") else {
+            panic!("the Error schema should be an allOf schema");
+        };
+        assert!(error.items.iter().any(|item| matches!(
+            item,
+            RefOr::T(Schema::Object(object))
+                if object.properties.contains_key("code")
+                    && object.required.iter().any(|field| field == "code")
+        )));
+        assert!(components.schemas.contains_key("ErrorCode"));
+        assert!(components.responses.contains_key("Error404"));
+    }
+}
@@ -1,55 +1,69 @@
-use crate::models::{PermissionOverwrite, Permissions, Role};
+//! Permission calculation.
+//!
+//! Client libraries that mirror these calculations (e.g. for UI gating) can verify their
+//! implementation against the scenarios returned by [`test_vectors`], which are the same cases
+//! this crate is tested against.
+
+use crate::models::{PermissionOverwrite, PermissionPair, Permissions, Role};
+#[cfg(feature = "client")]
+use serde::Deserialize;
+use serde::Serialize;
 
 /// Calculates the permissions after applying all role permissions and channel overwrites.
 /// This mutates `roles` by sorting it by position.
 ///
 /// # Note
-/// This does not account for guild owners (they should have all permissions), this should be
-/// handled by the caller.
+/// This does not account for guild owners (they should have all permissions) or timeouts (see
+/// [`apply_timeout`]), these should be handled by the caller.
 ///
 /// # Parameters
 /// * `user_id` - The ID of the user to calculate permissions for.
 /// * `roles` - The roles the user has.
 /// * `overwrites` - The channel overwrites, or `None` to apply no overwrites.
+/// * `pending` - Whether the user is a [pending](crate::models::Member::pending) member.
 #[must_use]
 pub fn calculate_permissions(
     user_id: u64,
     base: Permissions,
     mut roles: impl AsMut<[Role]>,
     overwrites: Option<&[PermissionOverwrite]>,
+    pending: bool,
 ) -> Permissions {
     let roles = roles.as_mut();
     roles.sort_unstable_by_key(|r| r.position);
 
-    calculate_permissions_sorted(user_id, base, roles, overwrites)
+    calculate_permissions_sorted(user_id, base, roles, overwrites, pending)
 }
 
 /// Calculates the permissions after applying all role permissions and channel overwrites.
 /// This assumes `roles` is sorted by position.
 ///
 /// # Note
-/// This does not account for guild owners (they should have all permissions), this should be
-/// handled by the caller.
+/// This does not account for guild owners (they should have all permissions) or timeouts (see
+/// [`apply_timeout`]), these should be handled by the caller.
 ///
 /// # Parameters
 /// * `user_id` - The ID of the user to calculate permissions for.
 /// * `roles` - The roles the user has.
 /// * `overwrites` - The channel overwrites, or `None` to apply no overwrites.
+/// * `pending` - Whether the user is a [pending](crate::models::Member::pending) member. Pending
+///   members are denied [`Permissions::SEND_MESSAGES`] unless they are administrators.
 #[must_use]
 pub fn calculate_permissions_sorted(
     user_id: u64,
     base: Permissions,
     roles: impl AsRef<[Role]>,
     overwrites: Option<&[PermissionOverwrite]>,
+    pending: bool,
 ) -> Permissions {
     let roles = roles.as_ref();
 
     let mut perms = roles
         .iter()
         .fold(base, |acc, role| acc | role.permissions.allow);
-    perms &= !roles
-        .iter()
-        .fold(base, |acc, role| acc | role.permissions.deny);
+    perms &= !roles.iter().fold(Permissions::empty(), |acc, role| {
+        acc | role.permissions.deny
+    });
 
     // currently, administrator acts after denied perms, meaning administrator does *not* take
     // precedence when a higher role denies the administrator permission. this could change in the
@@ -77,5 +91,416 @@ pub fn calculate_permissions_sorted(
         }
     }
 
+    if pending {
+        perms.remove(Permissions::SEND_MESSAGES);
+    }
+
     perms
 }
+
+/// The permissions a [timed out](crate::models::Member::communication_disabled_until) member
+/// cannot use.
+pub const TIMED_OUT_DENIED: Permissions = Permissions::SEND_MESSAGES
+    .union(Permissions::ADD_REACTIONS)
+    .union(Permissions::CONNECT);
+
+/// Removes the permissions a timed out member cannot use from ``perms``. Unlike pending members,
+/// timed out administrators are restricted as well.
+#[must_use]
+pub const fn apply_timeout(perms: Permissions) -> Permissions {
+    perms.difference(TIMED_OUT_DENIED)
+}
+
+/// A permission calculation scenario with its expected outcome.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct PermissionScenario {
+    /// A short description of what this scenario covers.
+    pub name: String,
+    /// The ID of the user to calculate permissions for.
+    pub user_id: u64,
+    /// Whether the user is the owner of the guild.
+    pub is_owner: bool,
+    /// Whether the user is a [pending](crate::models::Member::pending) member.
+    pub pending: bool,
+    /// Whether the user is timed out. Guild owners cannot be timed out.
+    pub timed_out: bool,
+    /// The base permissions of the member.
+    pub base: Permissions,
+    /// The roles the user has, in any order.
+    pub roles: Vec<Role>,
+    /// The channel overwrites, or `None` to calculate guild-level permissions.
+    pub overwrites: Option<Vec<PermissionOverwrite>>,
+    /// The permissions this scenario is expected to evaluate to.
+    pub expected: Permissions,
+}
+
+impl PermissionScenario {
+    /// Evaluates the permissions of this scenario, accounting for guild owners and timeouts.
+    #[must_use]
+    pub fn evaluate(&self) -> Permissions {
+        if self.is_owner {
+            return Permissions::all();
+        }
+
+        let perms = calculate_permissions(
+            self.user_id,
+            self.base,
+            self.roles.clone(),
+            self.overwrites.as_deref(),
+            self.pending,
+        );
+        if self.timed_out {
+            apply_timeout(perms)
+        } else {
+            perms
+        }
+    }
+
+    /// Returns whether this scenario evaluates to the expected permissions.
+    #[must_use]
+    pub fn passes(&self) -> bool {
+        self.evaluate() == self.expected
+    }
+}
+
+const USER_ID: u64 = 1;
+const DEFAULT_ROLE_ID: u64 = 10;
+const LOW_ROLE_ID: u64 = 11;
+const HIGH_ROLE_ID: u64 = 12;
+const OTHER_ROLE_ID: u64 = 13;
+
+fn role(id: u64, position: u16, allow: Permissions, deny: Permissions) -> Role {
+    Role {
+        id,
+        name: format!("role-{id}"),
+        permissions: PermissionPair { allow, deny },
+        position,
+        ..Role::default()
+    }
+}
+
+fn overwrite(id: u64, allow: Permissions, deny: Permissions) -> PermissionOverwrite {
+    PermissionOverwrite {
+        id,
+        permissions: PermissionPair { allow, deny },
+    }
+}
+
+fn scenario(
+    name: &str,
+    roles: Vec<Role>,
+    overwrites: Option<Vec<PermissionOverwrite>>,
+    expected: Permissions,
+) -> PermissionScenario {
+    PermissionScenario {
+        name: name.to_string(),
+        user_id: USER_ID,
+        is_owner: false,
+        pending: false,
+        timed_out: false,
+        base: Permissions::empty(),
+        roles,
+        overwrites,
+        expected,
+    }
+}
+
+/// Returns the permission calculation scenarios this crate is tested against.
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn test_vectors() -> Vec<PermissionScenario> {
+    let empty = Permissions::empty;
+    let default_role = || role(DEFAULT_ROLE_ID, 0, Permissions::DEFAULT, empty());
+
+    vec![
+        scenario("no roles", Vec::new(), None, empty()),
+        PermissionScenario {
+            base: Permissions::MANAGE_MESSAGES,
+            ..scenario(
+                "base permissions are granted",
+                vec![default_role()],
+                None,
+                Permissions::DEFAULT | Permissions::MANAGE_MESSAGES,
+            )
+        },
+        scenario(
+            "default role",
+            vec![default_role()],
+            None,
+            Permissions::DEFAULT,
+        ),
+        scenario(
+            "allows of all roles are combined",
+            vec![
+                default_role(),
+                role(LOW_ROLE_ID, 1, Permissions::MANAGE_MESSAGES, empty()),
+                role(HIGH_ROLE_ID, 2, Permissions::KICK_MEMBERS, empty()),
+            ],
+            None,
+            Permissions::DEFAULT | Permissions::MANAGE_MESSAGES | Permissions::KICK_MEMBERS,
+        ),
+        scenario(
+            "role denies take precedence over role allows",
+            vec![
+                default_role(),
+                role(LOW_ROLE_ID, 1, empty(), Permissions::SEND_MESSAGES),
+                role(HIGH_ROLE_ID, 2, Permissions::SEND_MESSAGES, empty()),
+            ],
+            None,
+            Permissions::DEFAULT - Permissions::SEND_MESSAGES,
+        ),
+        scenario(
+            "administrator grants all permissions",
+            vec![
+                default_role(),
+                role(LOW_ROLE_ID, 1, Permissions::ADMINISTRATOR, empty()),
+            ],
+            None,
+            Permissions::all(),
+        ),
+        scenario(
+            "administrator can be denied",
+            vec![
+                default_role(),
+                role(LOW_ROLE_ID, 1, Permissions::ADMINISTRATOR, empty()),
+                role(HIGH_ROLE_ID, 2, empty(), Permissions::ADMINISTRATOR),
+            ],
+            None,
+            Permissions::DEFAULT,
+        ),
+        scenario(
+            "administrator ignores channel overwrites",
+            vec![
+                default_role(),
+                role(LOW_ROLE_ID, 1, Permissions::ADMINISTRATOR, empty()),
+            ],
+            Some(vec![overwrite(USER_ID, empty(), Permissions::VIEW_CHANNEL)]),
+            Permissions::all(),
+        ),
+        scenario(
+            "role overwrite denies",
+            vec![default_role()],
+            Some(vec![overwrite(
+                DEFAULT_ROLE_ID,
+                empty(),
+                Permissions::SEND_MESSAGES,
+            )]),
+            Permissions::DEFAULT - Permissions::SEND_MESSAGES,
+        ),
+        scenario(
+            "role overwrite allows",
+            vec![default_role()],
+            Some(vec![overwrite(
+                DEFAULT_ROLE_ID,
+                Permissions::MANAGE_MESSAGES,
+                empty(),
+            )]),
+            Permissions::DEFAULT | Permissions::MANAGE_MESSAGES,
+        ),
+        scenario(
+            "higher role overwrite takes precedence",
+            vec![
+                default_role(),
+                role(LOW_ROLE_ID, 1, empty(), empty()),
+                role(HIGH_ROLE_ID, 2, empty(), empty()),
+            ],
+            Some(vec![
+                overwrite(HIGH_ROLE_ID, Permissions::SEND_MESSAGES, empty()),
+                overwrite(LOW_ROLE_ID, empty(), Permissions::SEND_MESSAGES),
+            ]),
+            Permissions::DEFAULT,
+        ),
+        scenario(
+            "higher role overwrite denies over lower role overwrite",
+            vec![
+                default_role(),
+                role(LOW_ROLE_ID, 1, empty(), empty()),
+                role(HIGH_ROLE_ID, 2, empty(), empty()),
+            ],
+            Some(vec![
+                overwrite(LOW_ROLE_ID, Permissions::SEND_MESSAGES, empty()),
+                overwrite(HIGH_ROLE_ID, empty(), Permissions::SEND_MESSAGES),
+            ]),
+            Permissions::DEFAULT - Permissions::SEND_MESSAGES,
+        ),
+        scenario(
+            "overwrites of roles the user does not have are ignored",
+            vec![default_role()],
+            Some(vec![overwrite(
+                OTHER_ROLE_ID,
+                empty(),
+                Permissions::VIEW_CHANNEL,
+            )]),
+            Permissions::DEFAULT,
+        ),
+        scenario(
+            "member overwrite takes precedence over role overwrites",
+            vec![default_role(), role(HIGH_ROLE_ID, 2, empty(), empty())],
+            Some(vec![
+                overwrite(HIGH_ROLE_ID, empty(), Permissions::SEND_MESSAGES),
+                overwrite(USER_ID, Permissions::SEND_MESSAGES, empty()),
+            ]),
+            Permissions::DEFAULT,
+        ),
+        scenario(
+            "empty overwrites are a no-op",
+            vec![default_role()],
+            Some(Vec::new()),
+            Permissions::DEFAULT,
+        ),
+        PermissionScenario {
+            is_owner: true,
+            ..scenario(
+                "owner has all permissions",
+                vec![role(DEFAULT_ROLE_ID, 0, empty(), Permissions::all())],
+                Some(vec![overwrite(USER_ID, empty(), Permissions::all())]),
+                Permissions::all(),
+            )
+        },
+        PermissionScenario {
+            pending: true,
+            ..scenario(
+                "pending members cannot send messages",
+                vec![default_role()],
+                None,
+                Permissions::DEFAULT - Permissions::SEND_MESSAGES,
+            )
+        },
+        PermissionScenario {
+            pending: true,
+            ..scenario(
+                "pending members cannot send messages even when allowed by an overwrite",
+                vec![default_role()],
+                Some(vec![overwrite(
+                    USER_ID,
+                    Permissions::SEND_MESSAGES,
+                    empty(),
+                )]),
+                Permissions::DEFAULT - Permissions::SEND_MESSAGES,
+            )
+        },
+        PermissionScenario {
+            pending: true,
+            ..scenario(
+                "pending administrators keep all permissions",
+                vec![
+                    default_role(),
+                    role(LOW_ROLE_ID, 1, Permissions::ADMINISTRATOR, empty()),
+                ],
+                None,
+                Permissions::all(),
+            )
+        },
+        PermissionScenario {
+            timed_out: true,
+            ..scenario(
+                "timed out members cannot send messages, react or connect",
+                vec![default_role()],
+                None,
+                Permissions::DEFAULT - TIMED_OUT_DENIED,
+            )
+        },
+        PermissionScenario {
+            timed_out: true,
+            ..scenario(
+                "timed out members cannot send messages even when allowed by an overwrite",
+                vec![default_role()],
+                Some(vec![overwrite(
+                    USER_ID,
+                    Permissions::SEND_MESSAGES | Permissions::CONNECT,
+                    empty(),
+                )]),
+                Permissions::DEFAULT - TIMED_OUT_DENIED,
+            )
+        },
+        PermissionScenario {
+            timed_out: true,
+            ..scenario(
+                "timed out members keep other permissions",
+                vec![
+                    default_role(),
+                    role(LOW_ROLE_ID, 1, Permissions::MANAGE_MESSAGES, empty()),
+                ],
+                None,
+                (Permissions::DEFAULT | Permissions::MANAGE_MESSAGES) - TIMED_OUT_DENIED,
+            )
+        },
+        PermissionScenario {
+            timed_out: true,
+            ..scenario(
+                "timed out administrators are restricted",
+                vec![
+                    default_role(),
+                    role(LOW_ROLE_ID, 1, Permissions::ADMINISTRATOR, empty()),
+                ],
+                None,
+                Permissions::all() - TIMED_OUT_DENIED,
+            )
+        },
+        PermissionScenario {
+            is_owner: true,
+            timed_out: true,
+            ..scenario(
+                "timeouts do not apply to the owner",
+                vec![default_role()],
+                None,
+                Permissions::all(),
+            )
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors_pass() {
+        for scenario in test_vectors() {
+            assert_eq!(
+                scenario.evaluate(),
+                scenario.expected,
+                "scenario failed: {}",
+                scenario.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_sorted_matches_unsorted() {
+        for scenario in test_vectors().into_iter().filter(|s| !s.is_owner) {
+            let mut roles = scenario.roles.clone();
+            roles.sort_by_key(|r| r.position);
+
+            let perms = calculate_permissions_sorted(
+                scenario.user_id,
+                scenario.base,
+                &roles,
+                scenario.overwrites.as_deref(),
+                scenario.pending,
+            );
+            let perms = if scenario.timed_out {
+                apply_timeout(perms)
+            } else {
+                perms
+            };
+
+            assert_eq!(
+                perms, scenario.expected,
+                "scenario failed: {}",
+                scenario.name,
+            );
+        }
+    }
+
+    #[test]
+    fn test_role_order_does_not_matter() {
+        for scenario in test_vectors() {
+            let mut reversed = scenario.clone();
+            reversed.roles.reverse();
+
+            assert!(reversed.passes(), "scenario failed: {}", scenario.name);
+        }
+    }
+}
@@ -20,6 +20,9 @@
 use crate::models::ModelType;
 use regex::Regex;
 use std::{
+    fmt,
+    num::ParseIntError,
+    str::FromStr,
     sync::{
         atomic::{AtomicU8, Ordering::Relaxed},
         OnceLock,
@@ -29,19 +32,35 @@ use std::{
 
 static INCREMENT: AtomicU8 = AtomicU8::new(0);
 
+#[cfg(feature = "test-snowflakes")]
+thread_local! {
+    static TEST_GENERATOR: std::cell::RefCell<Option<TestGenerator>> =
+        const { std::cell::RefCell::new(None) };
+}
+
 /// The snowflake epoch. This is ``2022-12-25T00:00:00Z`` as a Unix timestamp, in milliseconds.
 pub const EPOCH_MILLIS: u64 = 1_671_926_400_000;
 
-/// Returns the current time in milliseconds since the epoch.
+/// Returns the current time as a Unix timestamp in milliseconds.
 #[inline]
-#[must_use]
-pub fn epoch_time() -> u64 {
+fn unix_time_millis() -> u64 {
+    // `SystemTime::now` panics on wasm32-unknown-unknown, chrono reads the time from JS instead
+    #[cfg(target_arch = "wasm32")]
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    #[cfg(not(target_arch = "wasm32"))]
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("system time is before UNIX epoch")
         .as_millis() as u64;
 
-    now.saturating_sub(EPOCH_MILLIS)
+    now
+}
+
+/// Returns the current time in milliseconds since the epoch.
+#[inline]
+#[must_use]
+pub fn epoch_time() -> u64 {
+    unix_time_millis().saturating_sub(EPOCH_MILLIS)
 }
 
 /// Generates a snowflake with the given model type and node ID.
@@ -52,6 +71,16 @@ pub fn epoch_time() -> u64 {
 #[inline]
 #[must_use]
 pub unsafe fn generate_snowflake_unchecked(model_type: ModelType, node_id: u8) -> u64 {
+    #[cfg(feature = "test-snowflakes")]
+    if let Some(snowflake) = TEST_GENERATOR.with(|generator| {
+        generator
+            .borrow()
+            .as_ref()
+            .map(|generator| generator.generate(model_type, node_id))
+    }) {
+        return snowflake;
+    }
+
     let increment = INCREMENT.fetch_add(1, Relaxed);
 
     (epoch_time() << 18) | ((model_type as u64) << 13) | ((node_id as u64) << 8) | increment as u64
@@ -69,6 +98,54 @@ pub fn generate_snowflake(model_type: ModelType, node_id: u8) -> u64 {
     unsafe { generate_snowflake_unchecked(model_type, node_id) }
 }
 
+/// Generates deterministic snowflakes for tests. Instead of the current time, the timestamp of
+/// each snowflake is the seed plus the number of snowflakes generated so far, so IDs are stable
+/// across runs and strictly increasing regardless of model type.
+#[cfg(feature = "test-snowflakes")]
+#[derive(Debug)]
+pub struct TestGenerator {
+    next: std::cell::Cell<u64>,
+}
+
+#[cfg(feature = "test-snowflakes")]
+impl TestGenerator {
+    /// Creates a new generator starting at the given seed, in milliseconds since the epoch.
+    #[inline]
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            next: std::cell::Cell::new(seed),
+        }
+    }
+
+    /// Generates the next snowflake with the given model type and node ID.
+    #[must_use]
+    pub fn generate(&self, model_type: ModelType, node_id: u8) -> u64 {
+        let timestamp = self.next.get();
+        self.next.set(timestamp + 1);
+
+        (timestamp << 18) | ((model_type as u64) << 13) | ((node_id as u64 & 0b11111) << 8)
+    }
+}
+
+/// Returns a deterministic snowflake generator starting at the given seed. See [`TestGenerator`].
+#[cfg(feature = "test-snowflakes")]
+#[inline]
+#[must_use]
+pub const fn test_generator(seed: u64) -> TestGenerator {
+    TestGenerator::new(seed)
+}
+
+/// Overrides snowflake generation on the current thread with the given generator, or restores
+/// time-based generation if `None`. This affects [`generate_snowflake`] and all database methods
+/// that generate IDs, allowing test fixtures to produce stable snapshots.
+///
+/// Returns the previously set generator, if any.
+#[cfg(feature = "test-snowflakes")]
+pub fn set_test_generator(generator: Option<TestGenerator>) -> Option<TestGenerator> {
+    TEST_GENERATOR.with(|current| current.replace(generator))
+}
+
 /// Returns the given snowflake with its model type altered to the given one.
 #[inline]
 #[must_use]
@@ -76,15 +153,79 @@ pub const fn with_model_type(snowflake: u64, model_type: ModelType) -> u64 {
     snowflake & !(0b11111 << 13) | (model_type as u64) << 13
 }
 
-/// Extract all snowflake IDs surrounded by <@!? and >, called mentions, from a string.
+/// Mentions parsed from a string by [`extract_mentions`]. All lists are sorted and deduplicated.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParsedMentions {
+    /// The IDs of mentioned users, in the form `<@id>` or `<@!id>`.
+    pub users: Vec<u64>,
+    /// The IDs of mentioned roles, in the form `<@&id>`.
+    pub roles: Vec<u64>,
+    /// The IDs of mentioned channels, in the form `<#id>`.
+    pub channels: Vec<u64>,
+    /// Whether everyone is mentioned, with `@everyone` or `@here`.
+    pub everyone: bool,
+}
+
+impl ParsedMentions {
+    /// Returns whether nothing is mentioned.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.roles.is_empty() && self.channels.is_empty() && !self.everyone
+    }
+}
+
+/// Extract all user, role and channel mentions, and mentions of everyone, from a string.
 #[must_use]
-pub fn extract_mentions(s: &str) -> Vec<u64> {
+pub fn extract_mentions(s: &str) -> ParsedMentions {
     static REGEX: OnceLock<Regex> = OnceLock::new();
 
-    let regex = REGEX.get_or_init(|| Regex::new(r"<@!?(\d+)>").unwrap());
+    let regex =
+        REGEX.get_or_init(|| Regex::new(r"<(@!?|@&|#)(\d+)>|@(?:everyone|here)\b").unwrap());
+    let mut mentions = ParsedMentions::default();
+    for c in regex.captures_iter(s) {
+        let (Some(kind), Some(id)) = (c.get(1), c.get(2)) else {
+            // Ignore e.g. email addresses
+            let start = c.get(0).unwrap().start();
+            if !s[..start]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_')
+            {
+                mentions.everyone = true;
+            }
+            continue;
+        };
+        let Ok(id) = id.as_str().parse() else {
+            continue;
+        };
+        match kind.as_str() {
+            "@&" => mentions.roles.push(id),
+            "#" => mentions.channels.push(id),
+            _ => mentions.users.push(id),
+        }
+    }
+
+    for ids in [
+        &mut mentions.users,
+        &mut mentions.roles,
+        &mut mentions.channels,
+    ] {
+        ids.sort_unstable();
+        ids.dedup();
+    }
+    mentions
+}
+
+/// Extract the IDs of all custom emojis, in the form `<:name:id>` or `<a:name:id>`, from a string.
+#[must_use]
+pub fn extract_custom_emojis(s: &str) -> Vec<u64> {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+
+    let regex = REGEX.get_or_init(|| Regex::new(r"<a?:[\w-]+:(\d+)>").unwrap());
     regex
         .captures_iter(s)
-        .map(|c| c.get(1).unwrap().as_str().parse().unwrap())
+        .filter_map(|c| c.get(1).unwrap().as_str().parse().ok())
         .collect::<Vec<_>>()
 }
 
@@ -155,6 +296,187 @@ impl From<i64> for SnowflakeReader {
     }
 }
 
+/// A snowflake ID which can be decomposed into its parts. See the [module-level documentation]
+/// for the bit format.
+///
+/// [module-level documentation]: self
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Snowflake(pub u64);
+
+impl Snowflake {
+    /// Wraps the given raw snowflake.
+    #[inline]
+    #[must_use]
+    pub const fn new(snowflake: u64) -> Self {
+        Self(snowflake)
+    }
+
+    /// Returns the raw snowflake.
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Returns a [`SnowflakeReader`] for this snowflake. The accessors below delegate to it.
+    #[inline]
+    #[must_use]
+    pub const fn reader(self) -> SnowflakeReader {
+        SnowflakeReader::new(self.0)
+    }
+
+    /// Returns the timestamp of the snowflake in milliseconds since the epoch it was generated
+    /// with, which is [`EPOCH_MILLIS`] unless a custom epoch was set on the generator.
+    #[inline]
+    #[must_use]
+    pub const fn timestamp_millis(self) -> u64 {
+        self.reader().timestamp_millis()
+    }
+
+    /// Returns the time the snowflake was generated at, assuming it was generated with the
+    /// default epoch ([`EPOCH_MILLIS`]).
+    #[inline]
+    #[must_use]
+    pub fn timestamp(self) -> SystemTime {
+        self.timestamp_with_epoch(EPOCH_MILLIS)
+    }
+
+    /// Returns the time the snowflake was generated at, given the epoch it was generated with as
+    /// a Unix timestamp in milliseconds.
+    #[inline]
+    #[must_use]
+    pub fn timestamp_with_epoch(self, epoch_millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(epoch_millis + self.timestamp_millis())
+    }
+
+    /// Returns the model type of the snowflake.
+    #[inline]
+    #[must_use]
+    pub const fn model_type(self) -> ModelType {
+        self.reader().model_type()
+    }
+
+    /// Returns the ID of the node that generated the snowflake.
+    #[inline]
+    #[must_use]
+    pub const fn node_id(self) -> u8 {
+        self.reader().node_id()
+    }
+
+    /// Returns the increment of the snowflake.
+    #[inline]
+    #[must_use]
+    pub const fn increment(self) -> u8 {
+        self.reader().increment()
+    }
+
+    /// Returns this snowflake with its model type altered to the given one.
+    #[inline]
+    #[must_use]
+    pub const fn with_model_type(self, model_type: ModelType) -> Self {
+        Self(with_model_type(self.0, model_type))
+    }
+}
+
+impl From<u64> for Snowflake {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Snowflake> for u64 {
+    fn from(value: Snowflake) -> Self {
+        value.0
+    }
+}
+
+impl From<Snowflake> for SnowflakeReader {
+    fn from(value: Snowflake) -> Self {
+        value.reader()
+    }
+}
+
+impl fmt::Display for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Snowflake {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// A snowflake generator with a fixed node ID and a configurable epoch, for services that
+/// generate snowflakes outside of this crate.
+///
+/// Unlike [`generate_snowflake`], each generator keeps its own sequence counter and is not
+/// affected by the thread-local test generator.
+#[derive(Debug)]
+pub struct SnowflakeGenerator {
+    node_id: u8,
+    epoch_millis: u64,
+    increment: AtomicU8,
+}
+
+impl SnowflakeGenerator {
+    /// Creates a new generator for the given node ID using the default epoch ([`EPOCH_MILLIS`]).
+    ///
+    /// # Panics
+    /// * If `node_id >= 32`.
+    #[inline]
+    #[must_use]
+    pub const fn new(node_id: u8) -> Self {
+        assert!(node_id < 32, "node ID must be less than 32");
+
+        Self {
+            node_id,
+            epoch_millis: EPOCH_MILLIS,
+            increment: AtomicU8::new(0),
+        }
+    }
+
+    /// Sets the epoch of the generator, as a Unix timestamp in milliseconds. Snowflakes generated
+    /// with a custom epoch must be decoded with [`Snowflake::timestamp_with_epoch`].
+    #[inline]
+    #[must_use]
+    pub const fn with_epoch(mut self, epoch_millis: u64) -> Self {
+        self.epoch_millis = epoch_millis;
+        self
+    }
+
+    /// Returns the node ID of the generator.
+    #[inline]
+    #[must_use]
+    pub const fn node_id(&self) -> u8 {
+        self.node_id
+    }
+
+    /// Returns the epoch of the generator, as a Unix timestamp in milliseconds.
+    #[inline]
+    #[must_use]
+    pub const fn epoch_millis(&self) -> u64 {
+        self.epoch_millis
+    }
+
+    /// Generates a snowflake with the given model type.
+    #[must_use]
+    pub fn generate(&self, model_type: ModelType) -> Snowflake {
+        let timestamp = unix_time_millis().saturating_sub(self.epoch_millis);
+        let increment = self.increment.fetch_add(1, Relaxed);
+
+        Snowflake(
+            (timestamp << 18)
+                | ((model_type as u64) << 13)
+                | ((self.node_id as u64) << 8)
+                | increment as u64,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,4 +517,75 @@ mod tests {
         assert_eq!(original_reader.model_type(), ModelType::User);
         assert_eq!(new_reader.model_type(), ModelType::Channel);
     }
+
+    #[test]
+    fn test_snowflake_parts() {
+        let generator = SnowflakeGenerator::new(4);
+        let snowflake = generator.generate(ModelType::Guild);
+
+        assert_eq!(snowflake.model_type(), ModelType::Guild);
+        assert_eq!(snowflake.node_id(), 4);
+        assert_eq!(
+            generator.generate(ModelType::Guild).increment(),
+            snowflake.increment() + 1
+        );
+        assert_eq!(
+            snowflake.to_string().parse::<Snowflake>().unwrap(),
+            snowflake
+        );
+    }
+
+    #[test]
+    fn test_generator_epoch() {
+        let epoch = EPOCH_MILLIS + 1_000_000;
+        let snowflake = SnowflakeGenerator::new(0)
+            .with_epoch(epoch)
+            .generate(ModelType::User);
+        let default = Snowflake(generate_snowflake(ModelType::User, 0));
+
+        assert!(snowflake.timestamp_millis() < default.timestamp_millis());
+        assert!(
+            snowflake
+                .timestamp_with_epoch(epoch)
+                .duration_since(default.timestamp())
+                .unwrap_or_default()
+                < Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn test_extract_mentions() {
+        let mentions = extract_mentions("<@1> <@!1> <@&2> <#3> <@4> @everyone <@&x> <#>");
+
+        assert_eq!(mentions.users, vec![1, 4]);
+        assert_eq!(mentions.roles, vec![2]);
+        assert_eq!(mentions.channels, vec![3]);
+        assert!(mentions.everyone);
+        assert!(!extract_mentions("email@here.com <@>").everyone);
+    }
+
+    #[test]
+    fn test_extract_custom_emojis() {
+        let emojis = extract_custom_emojis("hi <:wave:123> <a:party-blob:456> <@789> <:bad:>");
+
+        assert_eq!(emojis, vec![123, 456]);
+    }
+
+    #[test]
+    #[cfg(feature = "test-snowflakes")]
+    fn test_deterministic_generator() {
+        set_test_generator(Some(test_generator(1000)));
+        let a = generate_snowflake(ModelType::User, 1);
+        let b = generate_snowflake(ModelType::Channel, 1);
+        set_test_generator(None);
+
+        assert!(a < b);
+        assert_eq!(SnowflakeReader::new(a).timestamp_millis(), 1000);
+        assert_eq!(SnowflakeReader::new(b).model_type(), ModelType::Channel);
+
+        // The same seed always produces the same sequence
+        let generator = test_generator(1000);
+        assert_eq!(generator.generate(ModelType::User, 1), a);
+        assert_eq!(generator.generate(ModelType::Channel, 1), b);
+    }
 }
@@ -0,0 +1,52 @@
+use crate::serde_for_bitflags;
+
+bitflags::bitflags! {
+    /// A bitmask of wire-format features a client supports, sent in
+    /// [`InboundMessage::Identify`](crate::ws::InboundMessage::Identify).
+    ///
+    /// Harmony responds with the capabilities it agreed to in the `Ready` event, see
+    /// [`Capabilities::negotiate`]. The `Identify` message itself is always sent as uncompressed
+    /// JSON; every message after it, starting with `Ready`, is encoded with the negotiated
+    /// capabilities.
+    #[derive(Default)]
+    pub struct Capabilities: u32 {
+        /// Messages are compressed with zstd.
+        const ZSTD_COMPRESSION = 1 << 0;
+        /// Messages are compressed with raw DEFLATE. If zstd compression is also supported, zstd
+        /// is preferred.
+        const DEFLATE_COMPRESSION = 1 << 1;
+        /// Messages are encoded with MsgPack instead of JSON.
+        const MSGPACK_ENCODING = 1 << 2;
+        /// The client caches state across sessions and supports the
+        /// [`ReadyDelta`](crate::ws::OutboundMessage::ReadyDelta) event, where harmony omits data
+        /// that has not changed since the client's last session.
+        const DELTA_READY = 1 << 3;
+    }
+}
+
+serde_for_bitflags!(u32: Capabilities);
+
+impl Capabilities {
+    /// All compression flags.
+    pub const COMPRESSION: Self =
+        Self::from_bits_truncate(Self::ZSTD_COMPRESSION.bits() | Self::DEFLATE_COMPRESSION.bits());
+
+    /// Negotiates the capabilities to use for a session, given the capabilities requested by the
+    /// client and the capabilities supported by the server. At most one compression algorithm is
+    /// kept.
+    #[must_use]
+    pub fn negotiate(requested: Self, supported: Self) -> Self {
+        let mut negotiated = requested & supported;
+        if negotiated.contains(Self::COMPRESSION) {
+            negotiated.remove(Self::DEFLATE_COMPRESSION);
+        }
+        negotiated
+    }
+
+    /// Whether messages encoded with these capabilities are binary, i.e. should be sent in binary
+    /// websocket frames rather than text frames.
+    #[must_use]
+    pub const fn is_binary(&self) -> bool {
+        self.intersects(Self::COMPRESSION.union(Self::MSGPACK_ENCODING))
+    }
+}
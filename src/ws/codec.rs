@@ -0,0 +1,154 @@
+//! The wire format shared by harmony and clients. Messages are encoded as JSON or MsgPack and
+//! optionally compressed, depending on the negotiated [`Capabilities`].
+
+use crate::{
+    error::MalformedBodyErrorType,
+    ws::{Capabilities, InboundMessage, OutboundMessage, SequencedMessage},
+    Error, Result,
+};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
+};
+
+/// The zstd compression level used for outbound messages. Low levels are preferred since
+/// messages are small and compressed individually.
+#[cfg(feature = "ws-zstd")]
+pub const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+/// The capabilities supported by this build of essence. This should be passed as the
+/// ``supported`` capabilities to [`Capabilities::negotiate`].
+#[must_use]
+pub fn supported_capabilities() -> Capabilities {
+    if cfg!(feature = "ws-zstd") {
+        Capabilities::all()
+    } else {
+        Capabilities::all() - Capabilities::ZSTD_COMPRESSION
+    }
+}
+
+#[inline]
+fn malformed(error_type: MalformedBodyErrorType, message: impl ToString) -> Error {
+    Error::MalformedBody {
+        error_type,
+        message: message.to_string(),
+    }
+}
+
+fn encode<T: Serialize>(message: &T, capabilities: Capabilities) -> Result<Vec<u8>> {
+    let encoded = if capabilities.contains(Capabilities::MSGPACK_ENCODING) {
+        rmp_serde::to_vec_named(message)
+            .map_err(|e| Error::custom_for("msgpack_encode", e.to_string()))?
+    } else {
+        serde_json::to_vec(message).map_err(|e| Error::custom_for("json_encode", e.to_string()))?
+    };
+
+    if capabilities.contains(Capabilities::ZSTD_COMPRESSION) {
+        #[cfg(feature = "ws-zstd")]
+        return zstd::bulk::compress(&encoded, ZSTD_COMPRESSION_LEVEL)
+            .map_err(|e| Error::custom_for("zstd_compress", e.to_string()));
+        #[cfg(not(feature = "ws-zstd"))]
+        return Err(Error::custom_for(
+            "zstd_compress",
+            "zstd compression requires the `ws-zstd` feature",
+        ));
+    }
+    if capabilities.contains(Capabilities::DEFLATE_COMPRESSION) {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        return encoder
+            .write_all(&encoded)
+            .and_then(|()| encoder.finish())
+            .map_err(|e| Error::custom_for("deflate_compress", e.to_string()));
+    }
+    Ok(encoded)
+}
+
+fn decompress(bytes: &[u8], capabilities: Capabilities) -> Result<Cow<'_, [u8]>> {
+    if capabilities.contains(Capabilities::ZSTD_COMPRESSION) {
+        #[cfg(feature = "ws-zstd")]
+        return zstd::stream::decode_all(bytes)
+            .map(Cow::Owned)
+            .map_err(|e| malformed(MalformedBodyErrorType::InvalidCompression, e));
+        #[cfg(not(feature = "ws-zstd"))]
+        return Err(malformed(
+            MalformedBodyErrorType::InvalidCompression,
+            "zstd compression is not supported",
+        ));
+    }
+    if capabilities.contains(Capabilities::DEFLATE_COMPRESSION) {
+        let mut buffer = Vec::new();
+        DeflateDecoder::new(bytes)
+            .read_to_end(&mut buffer)
+            .map_err(|e| malformed(MalformedBodyErrorType::InvalidCompression, e))?;
+        return Ok(Cow::Owned(buffer));
+    }
+    Ok(Cow::Borrowed(bytes))
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8], capabilities: Capabilities) -> Result<T> {
+    let bytes = decompress(bytes, capabilities)?;
+
+    if capabilities.contains(Capabilities::MSGPACK_ENCODING) {
+        rmp_serde::from_slice(&bytes)
+            .map_err(|e| malformed(MalformedBodyErrorType::InvalidMsgpack, e))
+    } else {
+        serde_json::from_slice(&bytes)
+            .map_err(|e| malformed(MalformedBodyErrorType::InvalidJson, e))
+    }
+}
+
+/// Encodes a message sent by harmony with the given negotiated capabilities. The result should
+/// be sent in a binary frame if [`Capabilities::is_binary`] is `true`, otherwise in a text frame.
+///
+/// # Errors
+/// * If the message could not be serialized or compressed.
+pub fn encode_outbound(message: &OutboundMessage, capabilities: Capabilities) -> Result<Vec<u8>> {
+    encode(message, capabilities)
+}
+
+/// Encodes a message sent by harmony along with its sequence number, with the given negotiated
+/// capabilities.
+///
+/// # Errors
+/// * If the message could not be serialized or compressed.
+pub fn encode_sequenced(message: &SequencedMessage, capabilities: Capabilities) -> Result<Vec<u8>> {
+    encode(message, capabilities)
+}
+
+/// Decodes a message sent by the client with the given negotiated capabilities.
+///
+/// # Errors
+/// * If the message could not be decompressed or deserialized.
+pub fn decode_inbound(bytes: &[u8], capabilities: Capabilities) -> Result<InboundMessage> {
+    decode(bytes, capabilities)
+}
+
+/// Encodes a message sent by the client with the given negotiated capabilities.
+///
+/// # Errors
+/// * If the message could not be serialized or compressed.
+#[cfg(feature = "client")]
+pub fn encode_inbound(message: &InboundMessage, capabilities: Capabilities) -> Result<Vec<u8>> {
+    encode(message, capabilities)
+}
+
+/// Decodes a message sent by harmony with the given negotiated capabilities.
+///
+/// # Errors
+/// * If the message could not be decompressed or deserialized.
+#[cfg(feature = "client")]
+pub fn decode_outbound(bytes: &[u8], capabilities: Capabilities) -> Result<OutboundMessage> {
+    decode(bytes, capabilities)
+}
+
+/// Decodes a message sent by harmony along with its sequence number, with the given negotiated
+/// capabilities.
+///
+/// # Errors
+/// * If the message could not be decompressed or deserialized.
+#[cfg(feature = "client")]
+pub fn decode_sequenced(bytes: &[u8], capabilities: Capabilities) -> Result<SequencedMessage> {
+    decode(bytes, capabilities)
+}
@@ -1,4 +1,7 @@
-use crate::models::{Device, PresenceStatus};
+use crate::{
+    models::{CustomStatusExpiry, Device, PresenceStatus},
+    ws::{Capabilities, Intents, MemberListRange, ReadyStateHash},
+};
 use serde::Deserialize;
 #[cfg(feature = "client")]
 use serde::Serialize;
@@ -6,6 +9,7 @@ use serde::Serialize;
 /// An inbound websocket message sent by the client, received by the server.
 #[derive(Debug, Deserialize)]
 #[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 #[serde(tag = "op", rename_all = "snake_case")]
 pub enum InboundMessage {
     /// Sent by the client to identify and authenticate itself to the websocket.
@@ -17,8 +21,39 @@ pub enum InboundMessage {
         status: PresenceStatus,
         /// Custom status of the client, if any.
         custom_status: Option<String>,
+        /// When the custom status should be automatically cleared, if ever.
+        #[serde(default)]
+        custom_status_expiry: Option<CustomStatusExpiry>,
         /// The device that this client is connecting on.
         device: Device,
+        /// The wire-format capabilities supported by the client. Defaults to none, i.e.
+        /// uncompressed JSON.
+        #[serde(default)]
+        #[cfg_attr(feature = "bincode", bincode(with_serde))]
+        capabilities: Capabilities,
+        /// The groups of events the client wants to receive. Defaults to all events.
+        #[serde(default = "Intents::all")]
+        #[cfg_attr(feature = "bincode", bincode(with_serde))]
+        intents: Intents,
+        /// The hashes of the state the client has cached from its previous session, if any. If
+        /// specified and the `DELTA_READY` capability is negotiated, harmony responds with a
+        /// [`ReadyDelta`](crate::ws::OutboundMessage::ReadyDelta) event instead of `Ready`.
+        #[serde(default)]
+        state_hash: Option<ReadyStateHash>,
+    },
+    /// Sent by the client instead of [`Identify`](Self::Identify) when reconnecting, to resume a
+    /// previous session. Harmony replays every message sent after ``seq`` and then sends
+    /// [`Resumed`](crate::ws::OutboundMessage::Resumed), or sends
+    /// [`InvalidSession`](crate::ws::OutboundMessage::InvalidSession) if the session cannot be
+    /// resumed.
+    ///
+    /// Like `Identify`, this is always sent as uncompressed JSON. The session keeps the
+    /// capabilities negotiated when it was identified.
+    Resume {
+        /// The ID of the session to resume, received in the `Ready` event.
+        session_id: String,
+        /// The sequence number of the last message the client received.
+        seq: u64,
     },
     /// Ping, sent by the client to harmony.
     Ping,
@@ -30,5 +65,67 @@ pub enum InboundMessage {
         status: PresenceStatus,
         /// The new custom status of the client, if any.
         custom_status: Option<String>,
+        /// When the new custom status should be automatically cleared, if ever.
+        #[serde(default)]
+        custom_status_expiry: Option<CustomStatusExpiry>,
+    },
+    /// Notifies harmony that the client user started typing in a channel. This should be resent
+    /// every few seconds while the user is still typing.
+    TypingStart {
+        /// The ID of the channel the client user is typing in.
+        channel_id: u64,
+    },
+    /// Notifies harmony that the client user stopped typing in a channel, e.g. because the input
+    /// was cleared.
+    TypingStop {
+        /// The ID of the channel the client user stopped typing in.
+        channel_id: u64,
+    },
+    /// Acknowledges ("marks as read") a channel up to the given message. Harmony dispatches a
+    /// [`ChannelAck`](crate::ws::OutboundMessage::ChannelAck) event to the client user's other
+    /// sessions.
+    Ack {
+        /// The ID of the channel to acknowledge.
+        channel_id: u64,
+        /// The ID of the last message to acknowledge. Messages up to this ID are considered read.
+        last_message_id: u64,
+    },
+    /// Requests the members of a guild, which are sent back in one or more
+    /// [`MemberChunk`](crate::ws::OutboundMessage::MemberChunk) events.
+    RequestGuildMembers {
+        /// The ID of the guild to request members from.
+        guild_id: u64,
+        /// If specified, only members whose username starts with this text (case-insensitive)
+        /// are sent.
+        query: Option<String>,
+        /// The maximum number of members to send, or `0` to send all members. This must be
+        /// specified if a query is given, and is at most ``1000``.
+        #[serde(default)]
+        limit: u16,
+        /// A custom nonce that is included in every chunk sent in response to this request.
+        nonce: Option<String>,
+    },
+    /// Subscribes to live updates from a guild. Clients only receive events from the channels
+    /// and member list ranges they are subscribed to, which cuts event fan-out in large guilds.
+    Subscribe {
+        /// The ID of the guild to subscribe to.
+        guild_id: u64,
+        /// The IDs of the channels to receive message events from. These are added to the
+        /// channels the client is already subscribed to.
+        #[serde(default)]
+        channel_ids: Vec<u64>,
+        /// The ranges of the member list to receive updates for. These replace the ranges the
+        /// client was previously subscribed to in this guild.
+        #[serde(default)]
+        member_ranges: Vec<MemberListRange>,
+    },
+    /// Unsubscribes from live updates from a guild.
+    Unsubscribe {
+        /// The ID of the guild to unsubscribe from.
+        guild_id: u64,
+        /// The IDs of the channels to unsubscribe from. If empty, the client is unsubscribed from
+        /// the guild entirely.
+        #[serde(default)]
+        channel_ids: Vec<u64>,
     },
 }
@@ -0,0 +1,116 @@
+use crate::{serde_for_bitflags, ws::OutboundMessage};
+
+bitflags::bitflags! {
+    /// A bitmask of the groups of events a gateway connection wants to receive, sent in
+    /// [`InboundMessage::Identify`](crate::ws::InboundMessage::Identify). This is mostly useful
+    /// for bots, which can suppress events they do not handle to reduce bandwidth.
+    ///
+    /// Events that concern the connection itself, such as `Ready` and pings, are always sent.
+    /// See [`filter_event`].
+    #[derive(Default)]
+    pub struct Intents: u32 {
+        /// Guild, channel, thread and role create, update and delete events.
+        const GUILDS = 1 << 0;
+        /// Member join, update and remove events.
+        const MEMBERS = 1 << 1;
+        /// Emoji and sticker create, update and delete events.
+        const EMOJIS_AND_STICKERS = 1 << 2;
+        /// Scheduled event create, update and delete events, and RSVP events.
+        const SCHEDULED_EVENTS = 1 << 3;
+        /// Message create, update and delete events.
+        const MESSAGES = 1 << 4;
+        /// Reaction add and remove events.
+        const REACTIONS = 1 << 5;
+        /// Poll vote events.
+        const POLLS = 1 << 6;
+        /// Typing start and stop events.
+        const TYPING = 1 << 7;
+        /// Presence update events.
+        const PRESENCES = 1 << 8;
+        /// Voice state update events.
+        const VOICE_STATES = 1 << 9;
+        /// User update, delete, available and unavailable events.
+        const USERS = 1 << 10;
+        /// Relationship create and remove events.
+        const RELATIONSHIPS = 1 << 11;
+        /// Channel and guild acknowledgement events.
+        const ACKS = 1 << 12;
+        /// Report create and update events.
+        const REPORTS = 1 << 13;
+    }
+}
+
+serde_for_bitflags!(u32: Intents);
+
+impl Intents {
+    /// The intents required to receive the given event. If this is empty, the event is always
+    /// sent.
+    #[must_use]
+    pub const fn required_for(message: &OutboundMessage) -> Self {
+        use OutboundMessage as M;
+
+        match message {
+            M::Hello
+            | M::Ping
+            | M::Pong
+            | M::Ready { .. }
+            | M::ReadyDelta { .. }
+            | M::Resumed { .. }
+            | M::InvalidSession { .. }
+            | M::MemberChunk(_) => Self::empty(),
+            M::GuildCreate { .. }
+            | M::GuildUpdate { .. }
+            | M::GuildRemove { .. }
+            | M::ChannelCreate { .. }
+            | M::ChannelUpdate { .. }
+            | M::ChannelDelete { .. }
+            | M::ThreadCreate { .. }
+            | M::ThreadUpdate { .. }
+            | M::ThreadDelete { .. }
+            | M::ThreadMemberUpdate { .. }
+            | M::RoleCreate { .. }
+            | M::RoleUpdate { .. }
+            | M::RolePositionsUpdate { .. }
+            | M::RoleDelete { .. } => Self::GUILDS,
+            M::MemberJoin { .. } | M::MemberUpdate { .. } | M::MemberRemove { .. } => Self::MEMBERS,
+            M::EmojiCreate { .. }
+            | M::EmojiUpdate { .. }
+            | M::EmojiDelete { .. }
+            | M::StickerCreate { .. }
+            | M::StickerUpdate { .. }
+            | M::StickerDelete { .. } => Self::EMOJIS_AND_STICKERS,
+            M::ScheduledEventCreate { .. }
+            | M::ScheduledEventUpdate { .. }
+            | M::ScheduledEventDelete { .. }
+            | M::ScheduledEventUserAdd { .. }
+            | M::ScheduledEventUserRemove { .. } => Self::SCHEDULED_EVENTS,
+            M::MessageCreate { .. }
+            | M::MessageUpdate { .. }
+            | M::MessageDelete { .. }
+            | M::MessageDeleteBulk { .. } => Self::MESSAGES,
+            M::ReactionAdd { .. }
+            | M::ReactionRemove { .. }
+            | M::ReactionRemoveAll { .. }
+            | M::ReactionRemoveEmoji { .. } => Self::REACTIONS,
+            M::PollVoteAdd { .. } | M::PollVoteRemove { .. } => Self::POLLS,
+            M::TypingStart { .. } | M::TypingStop { .. } => Self::TYPING,
+            M::PresenceUpdate { .. } => Self::PRESENCES,
+            M::VoiceStateUpdate { .. } => Self::VOICE_STATES,
+            M::UserUpdate { .. }
+            | M::UserDelete { .. }
+            | M::UserAvailable { .. }
+            | M::UserUnavailable { .. }
+            | M::UserNoteUpdate { .. }
+            | M::UserSettingsUpdate { .. } => Self::USERS,
+            M::RelationshipCreate { .. } | M::RelationshipRemove { .. } => Self::RELATIONSHIPS,
+            M::ChannelAck { .. } | M::GuildAck { .. } => Self::ACKS,
+            M::ReportCreate { .. } | M::ReportUpdate { .. } => Self::REPORTS,
+        }
+    }
+}
+
+/// Whether the given event should be sent to a connection with the given intents.
+#[must_use]
+pub const fn filter_event(message: &OutboundMessage, intents: Intents) -> bool {
+    intents.contains(Intents::required_for(message))
+}
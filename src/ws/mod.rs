@@ -1,5 +1,28 @@
+mod capabilities;
+#[cfg(feature = "ws-codec")]
+pub mod codec;
 mod inbound;
+mod intents;
 mod outbound;
+mod ready;
+mod replay;
+mod subscription;
 
+pub use capabilities::Capabilities;
+#[cfg(feature = "ws-codec")]
+pub use codec::{decode_inbound, encode_outbound, encode_sequenced, supported_capabilities};
+#[cfg(all(feature = "ws-codec", feature = "client"))]
+pub use codec::{decode_outbound, decode_sequenced, encode_inbound};
 pub use inbound::InboundMessage;
-pub use outbound::{MemberRemoveInfo, OutboundMessage, UnackedChannel};
+pub use intents::{filter_event, Intents};
+pub use outbound::{
+    AckedChannel, ChannelAckState, MemberChunk, MemberChunks, MemberRemoveInfo, OutboundMessage,
+    SequencedMessage, UnackedChannel, UnreadCount, MAX_BULK_DELETE_EVENT_SIZE,
+};
+#[cfg(feature = "serde_json")]
+pub use ready::{compute_ready_delta, state_hash};
+pub use ready::{ReadyState, ReadyStateHash};
+pub use replay::{ReplayBuffer, DEFAULT_REPLAY_BUFFER_CAPACITY};
+pub use subscription::{
+    GuildSubscription, MemberListRange, SubscriptionDiff, SubscriptionState, MAX_MEMBER_LIST_RANGES,
+};
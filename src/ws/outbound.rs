@@ -3,9 +3,11 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::models::{
-    Channel, ClientUser, DmChannel, Guild, Invite, Member, Message, PartialEmoji, PartialGuild,
-    Presence, Relationship, Role, User,
+    Channel, ClientSettings, ClientUser, CustomEmoji, DmChannel, Guild, GuildChannel, Invite,
+    Member, Message, PartialEmoji, PartialGuild, Presence, PrivacySettings, Relationship, Report,
+    Role, ScheduledEvent, Sticker, ThreadMember, User, UserNote, VoiceState,
 };
+use crate::ws::{Capabilities, ReadyStateHash};
 
 /// Extra information about member removal.
 #[derive(Debug, Serialize)]
@@ -45,6 +47,166 @@ pub struct UnackedChannel {
     pub mentions: Vec<u64>,
 }
 
+/// A channel that was acknowledged up to a message.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct AckedChannel {
+    /// The ID of the channel that was acknowledged.
+    pub channel_id: u64,
+    /// New messages up to this ID can be considered acknowledged.
+    pub last_message_id: u64,
+}
+
+/// The state of a channel after it was acknowledged, so that other sessions of the user can
+/// clear mentions and update unread counts without refetching them.
+#[derive(Clone, Debug, Default, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ChannelAckState {
+    /// The IDs of the messages mentioning the user that were acknowledged, i.e. the mentions that
+    /// should be cleared.
+    pub cleared_mentions: Vec<u64>,
+    /// The number of messages sent by others that remain unread after the acknowledged message.
+    pub unread_count: u32,
+    /// The number of those messages that mention the user.
+    pub mention_count: u32,
+}
+
+/// The number of unread messages and mentions in a channel.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct UnreadCount {
+    /// The ID of the channel.
+    pub channel_id: u64,
+    /// The number of messages sent by others since the last time you acknowledged this channel.
+    pub unread_count: u32,
+    /// The number of those messages that mention you.
+    pub mention_count: u32,
+}
+
+/// A chunk of the members of a guild, sent in response to a
+/// [`RequestGuildMembers`](crate::ws::InboundMessage::RequestGuildMembers) request.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct MemberChunk {
+    /// The ID of the guild the members are in.
+    pub guild_id: u64,
+    /// The members in this chunk.
+    pub members: Vec<Member>,
+    /// The index of this chunk, starting from `0`.
+    pub chunk_index: u32,
+    /// The total number of chunks sent in response to the request. This is always at least `1`,
+    /// even if no members matched the request.
+    pub chunk_count: u32,
+    /// The nonce of the request, if one was provided.
+    pub nonce: Option<String>,
+}
+
+/// An iterator over the [`MemberChunk`]s of a list of members.
+#[derive(Debug)]
+pub struct MemberChunks {
+    guild_id: u64,
+    members: std::vec::IntoIter<Member>,
+    chunk_size: usize,
+    chunk_index: u32,
+    chunk_count: u32,
+    nonce: Option<String>,
+}
+
+impl MemberChunks {
+    /// Splits the given members of a guild into chunks of at most ``chunk_size`` members. If
+    /// there are no members, a single empty chunk is yielded, so that the request is always
+    /// answered.
+    #[must_use]
+    pub fn new(guild_id: u64, members: Vec<Member>, chunk_size: usize) -> Self {
+        let chunk_size = chunk_size.max(1);
+        let chunk_count = members.len().div_ceil(chunk_size).max(1) as u32;
+
+        Self {
+            guild_id,
+            members: members.into_iter(),
+            chunk_size,
+            chunk_index: 0,
+            chunk_count,
+            nonce: None,
+        }
+    }
+
+    /// Sets the nonce included in every chunk.
+    #[must_use]
+    pub fn with_nonce(mut self, nonce: Option<String>) -> Self {
+        self.nonce = nonce;
+        self
+    }
+}
+
+impl Iterator for MemberChunks {
+    type Item = MemberChunk;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.chunk_index >= self.chunk_count {
+            return None;
+        }
+
+        let chunk = MemberChunk {
+            guild_id: self.guild_id,
+            members: self.members.by_ref().take(self.chunk_size).collect(),
+            chunk_index: self.chunk_index,
+            chunk_count: self.chunk_count,
+            nonce: self.nonce.clone(),
+        };
+        self.chunk_index += 1;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.chunk_count - self.chunk_index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for MemberChunks {}
+
+/// The maximum number of message IDs in a single
+/// [`MessageDeleteBulk`](OutboundMessage::MessageDeleteBulk) event.
+pub const MAX_BULK_DELETE_EVENT_SIZE: usize = 1000;
+
+/// An [`OutboundMessage`] along with its sequence number, which is how all outbound messages are
+/// sent over the websocket. The sequence number is flattened into the message, e.g.
+/// ``{"event": "message_create", "data": {...}, "seq": 42}``.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct SequencedMessage {
+    /// The sequence number of the message, or `None` if the message is not sequenced, see
+    /// [`OutboundMessage::is_sequenced`]. Clients should store the latest sequence number they
+    /// received to resume the session after reconnecting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    /// The message.
+    #[serde(flatten)]
+    pub message: OutboundMessage,
+}
+
+impl SequencedMessage {
+    /// Wraps a message with no sequence number.
+    #[must_use]
+    pub const fn unsequenced(message: OutboundMessage) -> Self {
+        Self { seq: None, message }
+    }
+
+    /// Wraps a message with the given sequence number.
+    #[must_use]
+    pub const fn new(seq: u64, message: OutboundMessage) -> Self {
+        Self {
+            seq: Some(seq),
+            message,
+        }
+    }
+}
+
 /// An outbound websocket message sent by harmony, received by the client.
 #[derive(Debug, Serialize)]
 #[cfg_attr(feature = "client", derive(Deserialize))]
@@ -62,8 +224,14 @@ pub enum OutboundMessage {
     Ready {
         /// The ID of the current session.
         session_id: String,
+        /// The capabilities negotiated for the current session. All messages after the
+        /// `Identify` message, including this one, are encoded with these capabilities.
+        #[cfg_attr(feature = "bincode", bincode(with_serde))]
+        capabilities: Capabilities,
         /// The client user of the current session.
         user: ClientUser,
+        /// The client settings of the user, synced across devices.
+        settings: ClientSettings,
         /// A list of guilds that the session's user is a member of.
         guilds: Vec<Guild>,
         /// A list of DM channels that the session's user is a member of.
@@ -74,12 +242,72 @@ pub enum OutboundMessage {
         presences: Vec<Presence>,
         /// A list of relationships associated with the user.
         relationships: Vec<Relationship>,
+        /// The private notes the user keeps on other users.
+        notes: Vec<UserNote>,
         /// A list of unacknowledged messages, organized by channel ID.
         unacked: Vec<UnackedChannel>,
+        /// The number of unread messages and mentions in each channel with unread messages.
+        unread_counts: Vec<UnreadCount>,
         /// A list of resolved message data for unacknowledged messages where the client user is
         /// mentioned, limited to 100 messages.
         inbox: Vec<Message>,
     },
+    /// Sent by harmony instead of [`Ready`](Self::Ready) when the client identified with the
+    /// hashes of its cached state and the `DELTA_READY` capability was negotiated. Only the
+    /// guilds, DM channels and relationships that changed since the cached state are sent.
+    ReadyDelta {
+        /// The ID of the current session.
+        session_id: String,
+        /// The capabilities negotiated for the current session.
+        #[cfg_attr(feature = "bincode", bincode(with_serde))]
+        capabilities: Capabilities,
+        /// The client user of the current session.
+        user: ClientUser,
+        /// The client settings of the user, synced across devices.
+        settings: ClientSettings,
+        /// The guilds that were joined or changed since the cached state.
+        guilds: Vec<Guild>,
+        /// The IDs of the cached guilds that the user is no longer a member of.
+        removed_guild_ids: Vec<u64>,
+        /// The DM channels that were created or changed since the cached state.
+        dm_channels: Vec<DmChannel>,
+        /// The IDs of the cached DM channels that the user is no longer a member of.
+        removed_dm_channel_ids: Vec<u64>,
+        /// A list of channels or guilds favorited and shown in the user's home sidebar.
+        favorites: Vec<u64>,
+        /// An initial array of all presences observed by the user.
+        presences: Vec<Presence>,
+        /// The relationships that were created or changed since the cached state.
+        relationships: Vec<Relationship>,
+        /// The IDs of the users whose cached relationships were removed.
+        removed_relationship_ids: Vec<u64>,
+        /// The private notes the user keeps on other users.
+        notes: Vec<UserNote>,
+        /// A list of unacknowledged messages, organized by channel ID.
+        unacked: Vec<UnackedChannel>,
+        /// The number of unread messages and mentions in each channel with unread messages.
+        unread_counts: Vec<UnreadCount>,
+        /// A list of resolved message data for unacknowledged messages where the client user is
+        /// mentioned, limited to 100 messages.
+        inbox: Vec<Message>,
+        /// The hashes of the new state. Clients should store these along with their cached state
+        /// and send them when identifying in the next session.
+        state_hash: ReadyStateHash,
+    },
+    /// Sent by harmony after a session was resumed and all missed messages were replayed.
+    Resumed {
+        /// The ID of the resumed session.
+        session_id: String,
+        /// The number of messages that were replayed.
+        replayed: u32,
+    },
+    /// Sent by harmony in response to a [`Resume`](crate::ws::InboundMessage::Resume) message
+    /// when the session cannot be resumed.
+    InvalidSession {
+        /// Whether the session may still be resumed later. If `false`, the client must identify
+        /// again and start a new session.
+        resumable: bool,
+    },
     /// Sent by harmony when an observable user is updated.
     UserUpdate {
         /// The user before it was updated.
@@ -92,6 +320,22 @@ pub enum OutboundMessage {
         /// The ID of the user that was deleted.
         user_id: u64,
     },
+    /// Sent by harmony when a user becomes observable by the client, i.e. when the client joins
+    /// a guild the user is in or adds them as a friend. Clients should start tracking the
+    /// presence of the user.
+    UserAvailable {
+        /// The user that became observable.
+        user: User,
+        /// The presence of the user, if they are online.
+        presence: Option<Presence>,
+    },
+    /// Sent by harmony when a user is no longer observable by the client, i.e. when the client
+    /// no longer shares any guilds or relationships with the user. Clients should stop tracking
+    /// the presence of the user.
+    UserUnavailable {
+        /// The ID of the user that is no longer observable.
+        user_id: u64,
+    },
     /// Sent by harmony when the client joins or creates a guild. Note that this does not include
     /// guilds received from the `Ready` event, those must be accounted for separately.
     GuildCreate {
@@ -124,6 +368,18 @@ pub enum OutboundMessage {
         channel_id: u64,
         /// New messages up to this ID can be considered acknowledged.
         last_message_id: u64,
+        /// The mentions that were cleared and the unread counts that remain after the
+        /// acknowledgement. This is omitted if it was not computed.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state: Option<ChannelAckState>,
+    },
+    /// Sent by harmony when all channels in a guild are acknowledged ("marked as read").
+    GuildAck {
+        /// The ID of the guild that was acknowledged.
+        guild_id: u64,
+        /// The channels that were acknowledged. Channels that were already fully acknowledged are
+        /// not included.
+        channels: Vec<AckedChannel>,
     },
     /// Sent by harmony when a channel is created. This could be any type of channel, including
     /// guild channels, DM channels, and group DM channels.
@@ -151,6 +407,47 @@ pub enum OutboundMessage {
         /// The ID of the guild that the channel was deleted in, if any.
         guild_id: Option<u64>,
     },
+    /// Sent by harmony when a thread is created within a text-based guild channel, or when a
+    /// post is created in a forum channel.
+    ThreadCreate {
+        /// The thread that was created.
+        thread: GuildChannel,
+        /// A custom nonce for this thread. This is a random string that if used, a message with
+        /// the same nonce will be dispatched by the websocket, indicating that the thread was
+        /// created.
+        ///
+        /// This is only used once, and it is not stored.
+        nonce: Option<String>,
+    },
+    /// Sent by harmony when a thread is modified, archived, or unarchived.
+    ThreadUpdate {
+        /// The thread before it was modified.
+        before: GuildChannel,
+        /// The thread after modifications.
+        after: GuildChannel,
+    },
+    /// Sent by harmony when a thread is deleted.
+    ThreadDelete {
+        /// The ID of the thread that was deleted.
+        thread_id: u64,
+        /// The ID of the guild that the thread was deleted in.
+        guild_id: u64,
+        /// The ID of the channel the thread belonged to.
+        parent_id: u64,
+    },
+    /// Sent by harmony when users join or leave a thread.
+    ThreadMemberUpdate {
+        /// The ID of the thread.
+        thread_id: u64,
+        /// The ID of the guild the thread is in.
+        guild_id: u64,
+        /// The members that joined the thread.
+        added: Vec<ThreadMember>,
+        /// The IDs of the users that left the thread.
+        removed: Vec<u64>,
+        /// The number of members in the thread after this update.
+        member_count: u32,
+    },
     /// Sent by harmony when a role is created within a guild.
     RoleCreate {
         /// The role that was created.
@@ -177,6 +474,81 @@ pub enum OutboundMessage {
         /// The ID of the role that was deleted.
         role_id: u64,
     },
+    /// Sent by harmony when a custom emoji is created within a guild.
+    EmojiCreate {
+        /// The emoji that was created.
+        emoji: CustomEmoji,
+    },
+    /// Sent by harmony when a custom emoji is updated.
+    EmojiUpdate {
+        /// The emoji before it was modified.
+        before: CustomEmoji,
+        /// The emoji after it was modified.
+        after: CustomEmoji,
+    },
+    /// Sent by harmony when a custom emoji is deleted.
+    EmojiDelete {
+        /// The ID of the guild the emoji was in.
+        guild_id: u64,
+        /// The ID of the emoji that was deleted.
+        emoji_id: u64,
+    },
+    /// Sent by harmony when a sticker is created within a guild.
+    StickerCreate {
+        /// The sticker that was created.
+        sticker: Sticker,
+    },
+    /// Sent by harmony when a sticker is updated.
+    StickerUpdate {
+        /// The sticker before it was modified.
+        before: Sticker,
+        /// The sticker after it was modified.
+        after: Sticker,
+    },
+    /// Sent by harmony when a sticker is deleted.
+    StickerDelete {
+        /// The ID of the guild the sticker was in.
+        guild_id: u64,
+        /// The ID of the sticker that was deleted.
+        sticker_id: u64,
+    },
+    /// Sent by harmony when a scheduled event is created within a guild.
+    ScheduledEventCreate {
+        /// The event that was created.
+        event: ScheduledEvent,
+    },
+    /// Sent by harmony when a scheduled event is updated.
+    ScheduledEventUpdate {
+        /// The event before it was modified.
+        before: ScheduledEvent,
+        /// The event after it was modified.
+        after: ScheduledEvent,
+    },
+    /// Sent by harmony when a scheduled event is deleted.
+    ScheduledEventDelete {
+        /// The ID of the guild the event was in.
+        guild_id: u64,
+        /// The ID of the event that was deleted.
+        event_id: u64,
+    },
+    /// Sent by harmony when a user marks themselves as interested in a scheduled event.
+    ScheduledEventUserAdd {
+        /// The ID of the guild the event is in.
+        guild_id: u64,
+        /// The ID of the event.
+        event_id: u64,
+        /// The ID of the user that is now interested in the event.
+        user_id: u64,
+    },
+    /// Sent by harmony when a user is no longer interested in a scheduled event.
+    ScheduledEventUserRemove {
+        /// The ID of the guild the event is in.
+        guild_id: u64,
+        /// The ID of the event.
+        event_id: u64,
+        /// The ID of the user that is no longer interested in the event.
+        user_id: u64,
+    },
     /// Sent by harmony when a member joins a guild. The guild ID can be retrieved from
     /// accessing `member.guild_id`.
     MemberJoin {
@@ -228,6 +600,14 @@ pub enum OutboundMessage {
         /// The ID of the message that was deleted.
         message_id: u64,
     },
+    /// Sent by harmony when multiple messages in a channel are deleted at once. Very large
+    /// deletions are split into multiple events, see [`OutboundMessage::message_delete_bulk`].
+    MessageDeleteBulk {
+        /// The ID of the channel that the messages were deleted in.
+        channel_id: u64,
+        /// The IDs of the messages that were deleted.
+        message_ids: Vec<u64>,
+    },
     /// Sent by harmony when a user starts typing.
     TypingStart {
         /// The ID of the channel that the user is typing in.
@@ -252,6 +632,8 @@ pub enum OutboundMessage {
         user_id: u64,
         /// The emoji that was added.
         emoji: PartialEmoji,
+        /// Whether the reaction is a burst (super) reaction.
+        burst: bool,
     },
     /// Sent by harmony when a user removes a reaction from a message.
     ReactionRemove {
@@ -267,23 +649,83 @@ pub enum OutboundMessage {
         /// The emoji that was removed.
         emoji: PartialEmoji,
     },
-    /// Sent by harmony when reactions are removed from a message in bulk.
-    ReactionRemoveBulk {
+    /// Sent by harmony when all reactions are removed from a message.
+    ReactionRemoveAll {
         /// The ID of the channel that the reactions were removed in.
         channel_id: u64,
         /// The ID of the message that the reactions were removed from.
         message_id: u64,
         /// The ID of the moderator that removed the reactions.
         moderator_id: u64,
-        /// The emoji that was removed. If this is `None`, then all reactions were removed.
-        emoji: Option<PartialEmoji>,
     },
+    /// Sent by harmony when all reactions of a single emoji are removed from a message.
+    ReactionRemoveEmoji {
+        /// The ID of the channel that the reactions were removed in.
+        channel_id: u64,
+        /// The ID of the message that the reactions were removed from.
+        message_id: u64,
+        /// The ID of the moderator that removed the reactions.
+        moderator_id: u64,
+        /// The emoji whose reactions were removed.
+        emoji: PartialEmoji,
+    },
+    /// Sent by harmony when a user votes for an answer in a poll.
+    PollVoteAdd {
+        /// The ID of the channel that the poll's message is in.
+        channel_id: u64,
+        /// The ID of the message that the poll is attached to.
+        message_id: u64,
+        /// The ID of the user that voted.
+        user_id: u64,
+        /// The ID of the answer that was voted for.
+        answer_id: u16,
+    },
+    /// Sent by harmony when a user removes their vote for an answer in a poll. This is also sent
+    /// when a vote in a single-select poll is replaced by a vote for another answer.
+    PollVoteRemove {
+        /// The ID of the channel that the poll's message is in.
+        channel_id: u64,
+        /// The ID of the message that the poll is attached to.
+        message_id: u64,
+        /// The ID of the user that removed their vote.
+        user_id: u64,
+        /// The ID of the answer that the vote was removed from.
+        answer_id: u16,
+    },
+    /// Sent by harmony in response to a
+    /// [`RequestGuildMembers`](crate::ws::InboundMessage::RequestGuildMembers) request, once for
+    /// every chunk of members.
+    MemberChunk(MemberChunk),
     /// Sent by harmony when a user updates their presence.
     PresenceUpdate {
         /// The presence after it was updated. The user ID can be retrieved from accessing
         /// `presence.user_id`.
         presence: Presence,
     },
+    /// Sent by harmony when a user connects to, disconnects from, moves between, or updates their
+    /// state in a voice channel.
+    VoiceStateUpdate {
+        /// The ID of the user whose voice state was updated.
+        user_id: u64,
+        /// The voice state before it was updated, or `None` if the user just connected.
+        before: Option<VoiceState>,
+        /// The voice state after it was updated, or `None` if the user disconnected.
+        after: Option<VoiceState>,
+    },
+    /// Sent by harmony to all sessions of the client user when they set or remove their private
+    /// note on another user.
+    UserNoteUpdate {
+        /// The ID of the user the note is about.
+        user_id: u64,
+        /// The new content of the note, or `None` if the note was removed.
+        note: Option<String>,
+    },
+    /// Sent by harmony to all sessions of the client user when its privacy configurations are
+    /// updated.
+    UserSettingsUpdate {
+        /// The updated privacy configurations.
+        privacy: PrivacySettings,
+    },
     /// Sent by harmony when a relationship is created. If a relationship already exists, this
     /// should be treated as an update and replace it.
     RelationshipCreate {
@@ -295,4 +737,50 @@ pub enum OutboundMessage {
         /// The ID of the user that the relationship was removed with.
         user_id: u64,
     },
+    /// Sent by harmony to users with the `VIEW_REPORTS` permission when a report is filed in
+    /// their guild, and to platform staff for every report.
+    ReportCreate {
+        /// The report that was filed.
+        report: Report,
+    },
+    /// Sent by harmony to the same users as [`ReportCreate`](Self::ReportCreate) when a report
+    /// is resolved or dismissed.
+    ReportUpdate {
+        /// The report after it was updated.
+        report: Report,
+    },
+}
+
+impl OutboundMessage {
+    /// Creates [`MessageDeleteBulk`](Self::MessageDeleteBulk) events for the given deleted
+    /// messages, with at most [`MAX_BULK_DELETE_EVENT_SIZE`] message IDs per event. No events are
+    /// returned if no message IDs are given.
+    #[must_use]
+    pub fn message_delete_bulk(channel_id: u64, message_ids: &[u64]) -> Vec<Self> {
+        message_ids
+            .chunks(MAX_BULK_DELETE_EVENT_SIZE)
+            .map(|chunk| Self::MessageDeleteBulk {
+                channel_id,
+                message_ids: chunk.to_vec(),
+            })
+            .collect()
+    }
+
+    /// Whether this message is assigned a sequence number and kept for replay when the session is
+    /// resumed. Messages that only concern the current connection, such as pings or responses to
+    /// member requests, are not sequenced.
+    #[must_use]
+    pub const fn is_sequenced(&self) -> bool {
+        !matches!(
+            self,
+            Self::Hello
+                | Self::Ping
+                | Self::Pong
+                | Self::Ready { .. }
+                | Self::ReadyDelta { .. }
+                | Self::Resumed { .. }
+                | Self::InvalidSession { .. }
+                | Self::MemberChunk(_)
+        )
+    }
 }
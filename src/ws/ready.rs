@@ -0,0 +1,210 @@
+use crate::{
+    models::{
+        ClientSettings, ClientUser, DmChannel, Guild, Message, Presence, Relationship, UserNote,
+    },
+    ws::{Capabilities, OutboundMessage, UnackedChannel, UnreadCount},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "serde_json")]
+use std::io::Write;
+
+/// Hashes of the state a client has cached from a previous session, keyed by the ID of each
+/// entity. This is sent in [`InboundMessage::Identify`](crate::ws::InboundMessage::Identify) so
+/// that harmony can respond with a [`ReadyDelta`](OutboundMessage::ReadyDelta) instead of a full
+/// `Ready` event.
+///
+/// Hashes are computed with [`state_hash`], and the full set of hashes for the new state is sent
+/// in the `ReadyDelta` event, so clients never need to compute them themselves.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct ReadyStateHash {
+    /// The hash of each guild, by guild ID.
+    #[serde(default)]
+    pub guilds: HashMap<u64, u64>,
+    /// The hash of each DM channel, by channel ID.
+    #[serde(default)]
+    pub dm_channels: HashMap<u64, u64>,
+    /// The hash of each relationship, by the ID of the user the relationship is with.
+    #[serde(default)]
+    pub relationships: HashMap<u64, u64>,
+}
+
+/// The state sent to a client when its session is ready, either in full through
+/// [`OutboundMessage::Ready`] or as a delta through [`OutboundMessage::ReadyDelta`].
+#[derive(Debug)]
+pub struct ReadyState {
+    /// The ID of the current session.
+    pub session_id: String,
+    /// The capabilities negotiated for the current session.
+    pub capabilities: Capabilities,
+    /// The client user of the current session.
+    pub user: ClientUser,
+    /// The client settings of the user, synced across devices.
+    pub settings: ClientSettings,
+    /// A list of guilds that the session's user is a member of.
+    pub guilds: Vec<Guild>,
+    /// A list of DM channels that the session's user is a member of.
+    pub dm_channels: Vec<DmChannel>,
+    /// A list of channels or guilds favorited and shown in the user's home sidebar.
+    pub favorites: Vec<u64>,
+    /// An initial array of all presences observed by the user.
+    pub presences: Vec<Presence>,
+    /// A list of relationships associated with the user.
+    pub relationships: Vec<Relationship>,
+    /// The private notes the user keeps on other users.
+    pub notes: Vec<UserNote>,
+    /// A list of unacknowledged messages, organized by channel ID.
+    pub unacked: Vec<UnackedChannel>,
+    /// The number of unread messages and mentions in each channel with unread messages.
+    pub unread_counts: Vec<UnreadCount>,
+    /// A list of resolved message data for unacknowledged messages where the client user is
+    /// mentioned.
+    pub inbox: Vec<Message>,
+}
+
+impl ReadyState {
+    /// Converts this state into a full [`OutboundMessage::Ready`] event.
+    #[must_use]
+    pub fn into_ready(self) -> OutboundMessage {
+        OutboundMessage::Ready {
+            session_id: self.session_id,
+            capabilities: self.capabilities,
+            user: self.user,
+            settings: self.settings,
+            guilds: self.guilds,
+            dm_channels: self.dm_channels,
+            favorites: self.favorites,
+            presences: self.presences,
+            relationships: self.relationships,
+            notes: self.notes,
+            unacked: self.unacked,
+            unread_counts: self.unread_counts,
+            inbox: self.inbox,
+        }
+    }
+}
+
+/// A 64-bit FNV-1a hasher. This is used over [`std::hash::DefaultHasher`] since its output must
+/// be stable across platforms and Rust versions.
+#[cfg(feature = "serde_json")]
+struct Fnv1a(u64);
+
+#[cfg(feature = "serde_json")]
+impl Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &byte in buf {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Computes the stable hash of an entity in the ready state, from its JSON representation.
+#[cfg(feature = "serde_json")]
+#[must_use]
+pub fn state_hash<T: Serialize>(value: &T) -> u64 {
+    let mut hasher = Fnv1a(0xcbf2_9ce4_8422_2325);
+    // Serializing models to JSON never fails, and writing to the hasher is infallible
+    let _ = serde_json::to_writer(&mut hasher, value);
+    hasher.0
+}
+
+/// Keeps the entities whose hash differs from the old hash, and returns the IDs of the entities
+/// that are no longer present.
+#[cfg(feature = "serde_json")]
+fn diff_entities<T: Serialize>(
+    entities: &mut Vec<T>,
+    old: &HashMap<u64, u64>,
+    new: &mut HashMap<u64, u64>,
+    id: impl Fn(&T) -> u64,
+) -> Vec<u64> {
+    entities.retain(|entity| {
+        let id = id(entity);
+        let hash = state_hash(entity);
+        new.insert(id, hash);
+        old.get(&id) != Some(&hash)
+    });
+
+    let mut removed = old
+        .keys()
+        .filter(|id| !new.contains_key(id))
+        .copied()
+        .collect::<Vec<_>>();
+    removed.sort_unstable();
+    removed
+}
+
+/// Computes a [`OutboundMessage::ReadyDelta`] event from the hashes of the state the client has
+/// cached and the new state, so that only the guilds, DM channels and relationships that changed
+/// are sent.
+///
+/// Volatile data such as presences, unacknowledged messages and the inbox is always sent in
+/// full.
+#[cfg(feature = "serde_json")]
+#[must_use]
+pub fn compute_ready_delta(
+    old_state_hash: &ReadyStateHash,
+    new_state: ReadyState,
+) -> OutboundMessage {
+    let ReadyState {
+        session_id,
+        capabilities,
+        user,
+        settings,
+        mut guilds,
+        mut dm_channels,
+        favorites,
+        presences,
+        mut relationships,
+        notes,
+        unacked,
+        unread_counts,
+        inbox,
+    } = new_state;
+
+    let mut state_hash = ReadyStateHash::default();
+    let removed_guild_ids = diff_entities(
+        &mut guilds,
+        &old_state_hash.guilds,
+        &mut state_hash.guilds,
+        |guild: &Guild| guild.partial.id,
+    );
+    let removed_dm_channel_ids = diff_entities(
+        &mut dm_channels,
+        &old_state_hash.dm_channels,
+        &mut state_hash.dm_channels,
+        |channel: &DmChannel| channel.id,
+    );
+    let removed_relationship_ids = diff_entities(
+        &mut relationships,
+        &old_state_hash.relationships,
+        &mut state_hash.relationships,
+        |relationship: &Relationship| relationship.user.id,
+    );
+
+    OutboundMessage::ReadyDelta {
+        session_id,
+        capabilities,
+        user,
+        settings,
+        guilds,
+        removed_guild_ids,
+        dm_channels,
+        removed_dm_channel_ids,
+        favorites,
+        presences,
+        relationships,
+        removed_relationship_ids,
+        notes,
+        unacked,
+        unread_counts,
+        inbox,
+        state_hash,
+    }
+}
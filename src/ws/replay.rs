@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+/// The default number of messages kept in a [`ReplayBuffer`].
+pub const DEFAULT_REPLAY_BUFFER_CAPACITY: usize = 1024;
+
+/// A ring buffer of the most recent sequenced messages sent in a session, keyed by their
+/// sequence numbers. This allows clients to reconnect with
+/// [`InboundMessage::Resume`](crate::ws::InboundMessage::Resume) and receive the messages they
+/// missed, without having to receive a new `Ready` event.
+///
+/// Sequence numbers start at ``1`` and increase by one for every message pushed into the buffer.
+/// Once the buffer is full, the oldest message is discarded for every new message.
+///
+/// ``T`` is the type of the stored messages, which is usually either the message itself or its
+/// encoded form.
+#[derive(Clone, Debug)]
+pub struct ReplayBuffer<T> {
+    entries: VecDeque<(u64, T)>,
+    capacity: usize,
+    seq: u64,
+}
+
+impl<T> Default for ReplayBuffer<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_BUFFER_CAPACITY)
+    }
+}
+
+impl<T> ReplayBuffer<T> {
+    /// Creates a new, empty replay buffer that keeps at most ``capacity`` messages. The capacity
+    /// is at least ``1``.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            seq: 0,
+        }
+    }
+
+    /// The maximum number of messages kept in the buffer.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of messages currently in the buffer.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the buffer contains no messages.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The sequence number of the last message pushed into the buffer, or ``0`` if no messages
+    /// were pushed yet.
+    #[must_use]
+    pub const fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// The sequence number of the oldest message still in the buffer, if any.
+    #[must_use]
+    pub fn oldest_seq(&self) -> Option<u64> {
+        self.entries.front().map(|(seq, _)| *seq)
+    }
+
+    /// Pushes a message into the buffer, discarding the oldest message if the buffer is full.
+    /// Returns the sequence number assigned to the message.
+    pub fn push(&mut self, message: T) -> u64 {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.seq += 1;
+        self.entries.push_back((self.seq, message));
+        self.seq
+    }
+
+    /// Whether a client that last received the message with the given sequence number can
+    /// resume, i.e. whether every message after it is still in the buffer.
+    #[must_use]
+    pub fn can_resume(&self, seq: u64) -> bool {
+        seq <= self.seq && self.seq - seq <= self.entries.len() as u64
+    }
+
+    /// Returns the messages sent after the message with the given sequence number, in order,
+    /// along with their sequence numbers.
+    ///
+    /// Returns `None` if the client cannot resume from the given sequence number, either because
+    /// some of the messages after it were already discarded or because it is ahead of the last
+    /// message sent. In that case, harmony should respond with
+    /// [`OutboundMessage::InvalidSession`](crate::ws::OutboundMessage::InvalidSession).
+    #[must_use]
+    pub fn replay(&self, seq: u64) -> Option<impl Iterator<Item = (u64, &T)> + '_> {
+        if !self.can_resume(seq) {
+            return None;
+        }
+
+        let skip = self.entries.len() - (self.seq - seq) as usize;
+        Some(
+            self.entries
+                .iter()
+                .skip(skip)
+                .map(|(seq, message)| (*seq, message)),
+        )
+    }
+
+    /// Removes all messages from the buffer. Sequence numbers are not reset, so clients can no
+    /// longer resume from any message sent before this call.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,363 @@
+use crate::ws::InboundMessage;
+use serde::Deserialize;
+#[cfg(feature = "client")]
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The maximum number of member list ranges a client may subscribe to in a single guild. Ranges
+/// past this limit are ignored.
+pub const MAX_MEMBER_LIST_RANGES: usize = 8;
+
+/// A range of indices in the member list of a guild, from ``start`` (inclusive) to ``end``
+/// (exclusive).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct MemberListRange {
+    /// The index of the first member in the range.
+    pub start: u32,
+    /// The index after the last member in the range.
+    pub end: u32,
+}
+
+impl MemberListRange {
+    /// Whether the range contains no indices.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// Whether the range contains the given index.
+    #[must_use]
+    pub const fn contains(&self, index: u32) -> bool {
+        self.start <= index && index < self.end
+    }
+}
+
+/// Sorts the given ranges and merges overlapping or adjacent ranges, dropping empty ones.
+fn normalize_ranges(ranges: impl IntoIterator<Item = MemberListRange>) -> Vec<MemberListRange> {
+    let mut ranges = ranges
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .take(MAX_MEMBER_LIST_RANGES)
+        .collect::<Vec<_>>();
+    ranges.sort_unstable();
+
+    let mut merged: Vec<MemberListRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Returns the parts of the normalized ranges ``a`` that are not covered by the normalized
+/// ranges ``b``.
+fn subtract_ranges(a: &[MemberListRange], b: &[MemberListRange]) -> Vec<MemberListRange> {
+    let mut result = Vec::new();
+    for range in a {
+        let mut start = range.start;
+        for other in b {
+            if other.end <= start || other.start >= range.end {
+                continue;
+            }
+            if other.start > start {
+                result.push(MemberListRange {
+                    start,
+                    end: other.start,
+                });
+            }
+            start = start.max(other.end);
+        }
+        if start < range.end {
+            result.push(MemberListRange {
+                start,
+                end: range.end,
+            });
+        }
+    }
+    result
+}
+
+/// What a client is subscribed to in a single guild.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuildSubscription {
+    /// The IDs of the channels the client receives message events from.
+    pub channel_ids: BTreeSet<u64>,
+    /// The ranges of the member list the client receives member list updates for. These are
+    /// always sorted and never overlap.
+    pub member_ranges: Vec<MemberListRange>,
+}
+
+/// The difference between two [`SubscriptionState`]s. Harmony should send initial data for
+/// everything that was added, e.g. the members in added member list ranges.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubscriptionDiff {
+    /// The IDs of the guilds that were subscribed to.
+    pub added_guilds: Vec<u64>,
+    /// The IDs of the guilds that were unsubscribed from.
+    pub removed_guilds: Vec<u64>,
+    /// The ``(guild_id, channel_id)`` pairs of the channels that were subscribed to.
+    pub added_channels: Vec<(u64, u64)>,
+    /// The ``(guild_id, channel_id)`` pairs of the channels that were unsubscribed from.
+    pub removed_channels: Vec<(u64, u64)>,
+    /// The member list ranges that are newly covered, by guild ID.
+    pub added_member_ranges: Vec<(u64, MemberListRange)>,
+    /// The member list ranges that are no longer covered, by guild ID.
+    pub removed_member_ranges: Vec<(u64, MemberListRange)>,
+}
+
+impl SubscriptionDiff {
+    /// Whether nothing changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_guilds.is_empty()
+            && self.removed_guilds.is_empty()
+            && self.added_channels.is_empty()
+            && self.removed_channels.is_empty()
+            && self.added_member_ranges.is_empty()
+            && self.removed_member_ranges.is_empty()
+    }
+}
+
+/// The guilds, channels and member list ranges a client wants live updates for, as declared
+/// through [`InboundMessage::Subscribe`] and [`InboundMessage::Unsubscribe`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubscriptionState {
+    guilds: BTreeMap<u64, GuildSubscription>,
+}
+
+impl SubscriptionState {
+    /// Creates a new state with no subscriptions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The subscription to the given guild, if the client is subscribed to it.
+    #[must_use]
+    pub fn guild(&self, guild_id: u64) -> Option<&GuildSubscription> {
+        self.guilds.get(&guild_id)
+    }
+
+    /// Whether the client is subscribed to the given guild.
+    #[must_use]
+    pub fn is_subscribed_to_guild(&self, guild_id: u64) -> bool {
+        self.guilds.contains_key(&guild_id)
+    }
+
+    /// Whether the client is subscribed to the given channel in the given guild.
+    #[must_use]
+    pub fn is_subscribed_to_channel(&self, guild_id: u64, channel_id: u64) -> bool {
+        self.guilds
+            .get(&guild_id)
+            .is_some_and(|guild| guild.channel_ids.contains(&channel_id))
+    }
+
+    /// Whether the client is subscribed to the given index of the member list of the given guild.
+    #[must_use]
+    pub fn is_subscribed_to_member_index(&self, guild_id: u64, index: u32) -> bool {
+        self.guilds.get(&guild_id).is_some_and(|guild| {
+            guild
+                .member_ranges
+                .iter()
+                .any(|range| range.contains(index))
+        })
+    }
+
+    /// Subscribes to the given guild and channels. The member list ranges of the guild are
+    /// replaced with the given ranges, since they usually follow the client's viewport.
+    pub fn subscribe(
+        &mut self,
+        guild_id: u64,
+        channel_ids: impl IntoIterator<Item = u64>,
+        member_ranges: impl IntoIterator<Item = MemberListRange>,
+    ) -> SubscriptionDiff {
+        let old = self.clone();
+        let guild = self.guilds.entry(guild_id).or_default();
+        guild.channel_ids.extend(channel_ids);
+        guild.member_ranges = normalize_ranges(member_ranges);
+
+        old.diff(self)
+    }
+
+    /// Unsubscribes from the given channels of the given guild, or from the guild entirely if no
+    /// channels are given.
+    pub fn unsubscribe(&mut self, guild_id: u64, channel_ids: &[u64]) -> SubscriptionDiff {
+        let old = self.clone();
+        if channel_ids.is_empty() {
+            self.guilds.remove(&guild_id);
+        } else if let Some(guild) = self.guilds.get_mut(&guild_id) {
+            for channel_id in channel_ids {
+                guild.channel_ids.remove(channel_id);
+            }
+        }
+
+        old.diff(self)
+    }
+
+    /// Applies a [`InboundMessage::Subscribe`] or [`InboundMessage::Unsubscribe`] message. Returns
+    /// `None` if the message is neither.
+    pub fn apply(&mut self, message: &InboundMessage) -> Option<SubscriptionDiff> {
+        match message {
+            InboundMessage::Subscribe {
+                guild_id,
+                channel_ids,
+                member_ranges,
+            } => Some(self.subscribe(
+                *guild_id,
+                channel_ids.iter().copied(),
+                member_ranges.iter().copied(),
+            )),
+            InboundMessage::Unsubscribe {
+                guild_id,
+                channel_ids,
+            } => Some(self.unsubscribe(*guild_id, channel_ids)),
+            _ => None,
+        }
+    }
+
+    /// Removes all subscriptions to the given guild, e.g. when the client leaves it.
+    pub fn remove_guild(&mut self, guild_id: u64) -> SubscriptionDiff {
+        self.unsubscribe(guild_id, &[])
+    }
+
+    /// Computes the difference from this state to the ``other`` state.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> SubscriptionDiff {
+        let mut diff = SubscriptionDiff::default();
+        let empty = GuildSubscription::default();
+
+        let guild_ids = self
+            .guilds
+            .keys()
+            .chain(other.guilds.keys())
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        for guild_id in guild_ids {
+            let before = self.guilds.get(&guild_id);
+            let after = other.guilds.get(&guild_id);
+            match (before, after) {
+                (None, Some(_)) => diff.added_guilds.push(guild_id),
+                (Some(_), None) => diff.removed_guilds.push(guild_id),
+                _ => (),
+            }
+
+            let before = before.unwrap_or(&empty);
+            let after = after.unwrap_or(&empty);
+            diff.added_channels.extend(
+                after
+                    .channel_ids
+                    .difference(&before.channel_ids)
+                    .map(|&id| (guild_id, id)),
+            );
+            diff.removed_channels.extend(
+                before
+                    .channel_ids
+                    .difference(&after.channel_ids)
+                    .map(|&id| (guild_id, id)),
+            );
+            diff.added_member_ranges.extend(
+                subtract_ranges(&after.member_ranges, &before.member_ranges)
+                    .into_iter()
+                    .map(|range| (guild_id, range)),
+            );
+            diff.removed_member_ranges.extend(
+                subtract_ranges(&before.member_ranges, &after.member_ranges)
+                    .into_iter()
+                    .map(|range| (guild_id, range)),
+            );
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn range(start: u32, end: u32) -> MemberListRange {
+        MemberListRange { start, end }
+    }
+
+    #[test]
+    fn test_normalize_ranges() {
+        assert_eq!(
+            normalize_ranges([range(10, 20), range(0, 5), range(15, 30), range(5, 8)]),
+            vec![range(0, 8), range(10, 30)]
+        );
+        assert_eq!(
+            normalize_ranges([range(3, 3), range(5, 2), range(1, 2)]),
+            vec![range(1, 2)]
+        );
+        assert!(normalize_ranges([]).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_ranges_truncates() {
+        let ranges = (0..MAX_MEMBER_LIST_RANGES as u32 + 2).map(|i| range(i * 10, i * 10 + 5));
+        let normalized = normalize_ranges(ranges);
+
+        assert_eq!(normalized.len(), MAX_MEMBER_LIST_RANGES);
+        assert_eq!(
+            normalized.last(),
+            Some(&range(
+                (MAX_MEMBER_LIST_RANGES as u32 - 1) * 10,
+                (MAX_MEMBER_LIST_RANGES as u32 - 1) * 10 + 5
+            ))
+        );
+    }
+
+    #[test]
+    fn test_subtract_ranges() {
+        assert_eq!(
+            subtract_ranges(&[range(0, 100)], &[range(10, 20), range(50, 60)]),
+            vec![range(0, 10), range(20, 50), range(60, 100)]
+        );
+        assert_eq!(
+            subtract_ranges(&[range(10, 20)], &[range(0, 15)]),
+            vec![range(15, 20)]
+        );
+        assert_eq!(
+            subtract_ranges(&[range(0, 10)], &[range(10, 20)]),
+            vec![range(0, 10)]
+        );
+        assert!(subtract_ranges(&[range(5, 10)], &[range(0, 20)]).is_empty());
+        assert!(subtract_ranges(&[], &[range(0, 20)]).is_empty());
+        assert_eq!(subtract_ranges(&[range(0, 10)], &[]), vec![range(0, 10)]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut state = SubscriptionState::new();
+
+        let diff = state.subscribe(1, [2, 3], [range(0, 50)]);
+        assert_eq!(diff.added_guilds, vec![1]);
+        assert_eq!(diff.added_channels, vec![(1, 2), (1, 3)]);
+        assert_eq!(diff.added_member_ranges, vec![(1, range(0, 50))]);
+        assert!(diff.removed_member_ranges.is_empty());
+
+        let diff = state.subscribe(1, [3], [range(25, 75)]);
+        assert!(diff.added_guilds.is_empty());
+        assert!(diff.added_channels.is_empty());
+        assert_eq!(diff.added_member_ranges, vec![(1, range(50, 75))]);
+        assert_eq!(diff.removed_member_ranges, vec![(1, range(0, 25))]);
+
+        let diff = state.subscribe(1, [], [range(25, 75)]);
+        assert!(diff.is_empty());
+
+        let diff = state.unsubscribe(1, &[2]);
+        assert_eq!(diff.removed_channels, vec![(1, 2)]);
+        assert!(state.is_subscribed_to_channel(1, 3));
+        assert!(!state.is_subscribed_to_channel(1, 2));
+
+        let diff = state.remove_guild(1);
+        assert_eq!(diff.removed_guilds, vec![1]);
+        assert_eq!(diff.removed_channels, vec![(1, 3)]);
+        assert_eq!(diff.removed_member_ranges, vec![(1, range(25, 75))]);
+        assert_eq!(state, SubscriptionState::new());
+    }
+}